@@ -2,8 +2,7 @@ use std::time::Duration;
 
 use criterion::{criterion_group, criterion_main, Criterion};
 use mozak_circuits::test_utils::prove_and_verify_mozak_stark;
-use mozak_runner::code;
-use mozak_runner::instruction::{Args, Instruction, Op};
+use mozak_circuits::trace_source::{Synthetic, TraceSource};
 use starky::config::StarkConfig;
 
 fn bench_prove_verify_all(c: &mut Criterion) {
@@ -12,28 +11,12 @@ fn bench_prove_verify_all(c: &mut Criterion) {
     group.measurement_time(Duration::new(10, 0));
     group.bench_function("prove_verify_all", |b| {
         b.iter(|| {
-            let instructions = [
-                Instruction {
-                    op: Op::ADD,
-                    args: Args {
-                        rd: 1,
-                        rs1: 1,
-                        imm: 1_u32.wrapping_neg(),
-                        ..Args::default()
-                    },
-                },
-                Instruction {
-                    op: Op::BLT,
-                    args: Args {
-                        rs1: 0,
-                        rs2: 1,
-                        imm: 0,
-                        ..Args::default()
-                    },
-                },
-            ];
-            let (program, record) = code::execute(instructions, &[], &[(1, 1 << 10)]);
-            prove_and_verify_mozak_stark(&program, &record, &StarkConfig::standard_fast_config())
+            let source = Synthetic::countdown(1 << 10);
+            prove_and_verify_mozak_stark(
+                source.program(),
+                source.record(),
+                &StarkConfig::standard_fast_config(),
+            )
         })
     });
     group.finish();
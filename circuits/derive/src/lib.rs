@@ -1,14 +1,15 @@
 use itertools::{multiunzip, Itertools};
 use proc_macro::TokenStream;
-use proc_macro2::{Literal, Span};
+use proc_macro2::{Literal, Span, TokenStream as TokenStream2};
 use proc_macro_error::{abort, abort_if_dirty, emit_error, emit_warning, proc_macro_error};
 use quote::quote;
 use syn::parse::Parser;
 use syn::punctuated::Punctuated;
 use syn::spanned::Spanned;
+use syn::visit::Visit;
 use syn::{
-    parse_macro_input, Attribute, Data, DeriveInput, Expr, ExprLit, GenericParam, Ident, Index,
-    Lit, Member, Meta, MetaNameValue, Token, TypeParam,
+    parse_macro_input, Attribute, Data, DeriveInput, Expr, ExprLit, Fields, GenericParam, Ident,
+    Index, Lit, Member, Meta, MetaNameValue, Token, Type, TypeParam,
 };
 
 #[proc_macro_derive(StarkNameDisplay)]
@@ -176,3 +177,155 @@ pub fn derive_stark_set(input: TokenStream) -> TokenStream {
     )
     .into()
 }
+
+/// Checks whether a type mentions the identifier `param`.
+struct MentionsParam<'a> {
+    param: &'a Ident,
+    found: bool,
+}
+
+impl<'ast> Visit<'ast> for MentionsParam<'_> {
+    fn visit_ident(&mut self, ident: &'ast Ident) { self.found |= ident == self.param; }
+}
+
+fn mentions_param(ty: &Type, param: &Ident) -> bool {
+    let mut visitor = MentionsParam {
+        param,
+        found: false,
+    };
+    visitor.visit_type(ty);
+    visitor.found
+}
+
+fn is_repr_c(attrs: &[Attribute]) -> bool {
+    attrs.iter().any(|attr| {
+        attr.path().is_ident("repr")
+            && attr
+                .parse_args_with(Punctuated::<Ident, Token![,]>::parse_terminated)
+                .is_ok_and(|reprs| reprs.iter().any(|repr| repr == "C"))
+    })
+}
+
+/// Validates a columns view and returns its type parameter and its fields
+/// as `(name, type)` pairs.
+fn parse_columns_view(ast: &DeriveInput, derive_name: &str) -> (Ident, Vec<(String, Type)>) {
+    let Data::Struct(data) = &ast.data else {
+        abort!(ast, "`{}` only supports structs", derive_name);
+    };
+    let mut params = ast.generics.params.iter();
+    let param = match (params.next(), params.next()) {
+        (Some(GenericParam::Type(param)), None) => param.ident.clone(),
+        _ => abort!(
+            ast.generics,
+            "`{}` needs exactly one type parameter, the type of a single column, eg `struct {}<T>`",
+            derive_name,
+            ast.ident
+        ),
+    };
+    if !is_repr_c(&ast.attrs) {
+        emit_error!(
+            ast.ident,
+            "`{}` needs `#[repr(C)]`, so that fields are laid out as columns in declaration order",
+            derive_name
+        );
+    }
+    let fields = match &data.fields {
+        Fields::Named(fields) => fields
+            .named
+            .iter()
+            .map(|field| (field.ident.as_ref().unwrap().to_string(), field))
+            .collect_vec(),
+        Fields::Unnamed(fields) => fields
+            .unnamed
+            .iter()
+            .enumerate()
+            .map(|(i, field)| (i.to_string(), field))
+            .collect_vec(),
+        Fields::Unit => vec![],
+    };
+    let fields = fields
+        .into_iter()
+        .map(|(name, field)| {
+            if !mentions_param(&field.ty, &param) {
+                emit_error!(
+                    field.ty,
+                    "every field of a `{}` has to hold columns of type `{}`, eg `{}`, `[{}; N]` or \
+                     a nested view like `Ops<{}>`",
+                    derive_name,
+                    param,
+                    param,
+                    param,
+                    param
+                );
+            }
+            (name, field.ty.clone())
+        })
+        .collect_vec();
+    abort_if_dirty();
+    (param, fields)
+}
+
+fn column_names_impl(ident: &Ident, param: &Ident, fields: &[(String, Type)]) -> TokenStream2 {
+    let (field_names, tys): (Vec<_>, Vec<_>) = fields.iter().cloned().unzip();
+    quote!(
+        impl<#param: crate::columns_view::ColumnNames> crate::columns_view::ColumnNames
+            for #ident<#param>
+        {
+            fn push_column_names(prefix: &str, names: &mut Vec<String>) {
+                #(
+                    <#tys as crate::columns_view::ColumnNames>::push_column_names(
+                        &crate::columns_view::join_column_name(prefix, #field_names),
+                        names,
+                    );
+                )*
+            }
+        }
+    )
+}
+
+/// Derives `ColumnNames` for a struct that is nested in a columns view, but
+/// is not a columns view itself.
+#[proc_macro_error]
+#[proc_macro_derive(ColumnNames)]
+pub fn derive_column_names(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+    let (param, fields) = parse_columns_view(&ast, "ColumnNames");
+    column_names_impl(&ast.ident, &param, &fields).into()
+}
+
+/// Turns a `#[repr(C)]` struct over a single column type `T` into a view of
+/// a row of columns.
+///
+/// Fields can be single columns `T`, arrays, or nested views. Next to the
+/// conversions from and to `[T; N]` (including `Borrow<[T]>`), iteration,
+/// `Zip` and the arithmetic used for typed columns, this generates
+/// `column_names()`, which names every column by its path, eg
+/// `inst.ops.add` or `limbs[2]`.
+#[proc_macro_error]
+#[proc_macro_derive(ColumnsView)]
+pub fn derive_columns_view(input: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(input as DeriveInput);
+    let (param, fields) = parse_columns_view(&ast, "ColumnsView");
+    let ident = &ast.ident;
+    let column_names = column_names_impl(ident, &param, &fields);
+
+    quote!(
+        crate::columns_view::columns_view_impl!(#ident);
+
+        #column_names
+
+        impl<#param> #ident<#param> {
+            /// Names of all columns, in order, by their path in this view.
+            #[must_use]
+            pub fn column_names() -> Vec<String> {
+                let mut names = Vec::new();
+                <#ident<crate::columns_view::NamedColumn> as crate::columns_view::ColumnNames>::push_column_names(
+                    "",
+                    &mut names,
+                );
+                names
+            }
+        }
+    )
+    .into()
+}
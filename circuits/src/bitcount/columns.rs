@@ -0,0 +1,47 @@
+use crate::columns_view::{make_col_map, ColumnsView};
+use crate::linear_combination::Column;
+use crate::stark::mozak_stark::{BitCountTable, TableWithTypedOutput};
+
+make_col_map!(BIT_COUNT, BitCount);
+/// One row per `clz`, `ctz` or `cpop` the CPU executed.
+#[repr(C)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
+pub struct BitCount<T> {
+    pub is_clz: T,
+    pub is_ctz: T,
+    pub is_cpop: T,
+    pub value: T,
+    /// Bits of `value`, least significant first.
+    pub bits: [T; 32],
+    /// Bit `i` is 1 iff bits `i` and above of `value` are all zero, so these
+    /// sum up to the number of leading zeros.
+    pub leading_zeros: [T; 32],
+    /// Bit `i` is 1 iff bits `i` and below of `value` are all zero, so these
+    /// sum up to the number of trailing zeros.
+    pub trailing_zeros: [T; 32],
+    /// The count selected by `is_clz`, `is_ctz` or `is_cpop`.
+    pub count: T,
+}
+
+/// A count of `value`, where `op` is 0 for `clz`, 1 for `ctz` and 2 for
+/// `cpop`, like the immediate of the instruction.
+#[repr(C)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
+pub struct BitCountCtl<T> {
+    pub op: T,
+    pub value: T,
+    pub count: T,
+}
+
+/// Lookup between CPU table and `BitCount` stark table.
+#[must_use]
+pub fn lookup_for_cpu() -> TableWithTypedOutput<BitCountCtl<Column>> {
+    BitCountTable::new(
+        BitCountCtl {
+            op: BIT_COUNT.is_ctz + BIT_COUNT.is_cpop * 2,
+            value: BIT_COUNT.value,
+            count: BIT_COUNT.count,
+        },
+        BIT_COUNT.is_clz + BIT_COUNT.is_ctz + BIT_COUNT.is_cpop,
+    )
+}
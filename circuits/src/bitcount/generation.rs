@@ -0,0 +1,44 @@
+use bitfield::Bit;
+use itertools::Itertools;
+use mozak_runner::instruction::Op;
+use mozak_runner::vm::ExecutionRecord;
+use plonky2::hash::hash_types::RichField;
+
+use crate::bitcount::columns::BitCount;
+use crate::utils::{from_u32, pad_trace_with_row};
+
+/// A row for `value`, with none of the counts selected.
+fn count_row<F: RichField>(value: u32) -> BitCount<F> {
+    BitCount {
+        value: from_u32(value),
+        bits: core::array::from_fn(|i| F::from_bool(value.bit(i))),
+        leading_zeros: core::array::from_fn(|i| F::from_bool(value >> i == 0)),
+        trailing_zeros: core::array::from_fn(|i| F::from_bool((0..=i).all(|j| !value.bit(j)))),
+        ..BitCount::default()
+    }
+}
+
+/// Generates one row for each `clz`, `ctz` and `cpop` in `record`.
+///
+/// The padding counts zero, so that the prefix products hold on it as well.
+#[must_use]
+pub fn generate_bitcount_trace<F: RichField>(record: &ExecutionRecord<F>) -> Vec<BitCount<F>> {
+    let trace = record
+        .executed
+        .iter()
+        .filter(|row| matches!(row.instruction.op, Op::CLZ | Op::CTZ | Op::CPOP))
+        .map(|row| {
+            let value = row.aux.op1;
+            let mut count = count_row(value);
+            match row.instruction.op {
+                Op::CLZ => count.is_clz = F::ONE,
+                Op::CTZ => count.is_ctz = F::ONE,
+                _ => count.is_cpop = F::ONE,
+            }
+            count.count = from_u32(row.aux.dst_val);
+            count
+        })
+        .collect_vec();
+    tracing::trace!("BitCount trace {trace:?}");
+    pad_trace_with_row(trace, count_row(0))
+}
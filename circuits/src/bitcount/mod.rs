@@ -0,0 +1,8 @@
+//! This module contains the **`BitCount` STARK Table**.
+//! It counts the leading zeros, trailing zeros or set bits of a `u32`, for
+//! the Zbb instructions `clz`, `ctz` and `cpop`.
+//! It is used from the CPU STARK with the Cross Table Lookup (CTL) technique.
+
+pub mod columns;
+pub mod generation;
+pub mod stark;
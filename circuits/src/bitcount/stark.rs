@@ -0,0 +1,159 @@
+use std::marker::PhantomData;
+
+use expr::{Expr, ExprBuilder, StarkFrameTyped};
+use itertools::{chain, izip};
+use mozak_circuits_derive::StarkNameDisplay;
+use plonky2::field::extension::{Extendable, FieldExtension};
+use plonky2::field::packed::PackedField;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::ext_target::ExtensionTarget;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use starky::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
+use starky::evaluation_frame::StarkFrame;
+use starky::stark::Stark;
+
+use super::columns::BitCount;
+use crate::columns_view::{HasNamedColumns, NumberOfColumns};
+use crate::expr::{build_ext, build_packed, ConstraintBuilder};
+use crate::unstark::NoColumns;
+
+fn generate_constraints<'a, T: Copy>(
+    vars: &StarkFrameTyped<BitCount<Expr<'a, T>>, NoColumns<Expr<'a, T>>>,
+) -> ConstraintBuilder<Expr<'a, T>> {
+    let lv = vars.local_values;
+    let mut constraints = ConstraintBuilder::default();
+
+    for selector in [lv.is_clz, lv.is_ctz, lv.is_cpop] {
+        constraints.always(selector.is_binary());
+    }
+    constraints.always((lv.is_clz + lv.is_ctz + lv.is_cpop).is_binary());
+
+    for bit in lv.bits {
+        constraints.always(bit.is_binary());
+    }
+    constraints.always(Expr::reduce_with_powers(lv.bits, 2) - lv.value);
+
+    // Both are prefix products of the complemented bits, from the top and from
+    // the bottom respectively.
+    let one = Expr::from(1);
+    let above = chain!(lv.leading_zeros.into_iter().skip(1), [one]);
+    for (bit, leading, above) in izip!(lv.bits, lv.leading_zeros, above) {
+        constraints.always(leading - above * (1 - bit));
+    }
+    let below = chain!([one], lv.trailing_zeros);
+    for (bit, trailing, below) in izip!(lv.bits, lv.trailing_zeros, below) {
+        constraints.always(trailing - below * (1 - bit));
+    }
+
+    let sum = |columns: [Expr<'a, T>; 32]| columns.into_iter().sum::<Expr<'a, T>>();
+    constraints.always(
+        lv.count
+            - lv.is_clz * sum(lv.leading_zeros)
+            - lv.is_ctz * sum(lv.trailing_zeros)
+            - lv.is_cpop * sum(lv.bits),
+    );
+
+    constraints
+}
+
+#[derive(Copy, Clone, Default, StarkNameDisplay)]
+#[allow(clippy::module_name_repetitions)]
+pub struct BitCountStark<F, const D: usize> {
+    pub _f: PhantomData<F>,
+}
+
+impl<F, const D: usize> HasNamedColumns for BitCountStark<F, D> {
+    type Columns = BitCount<F>;
+}
+
+const COLUMNS: usize = BitCount::<()>::NUMBER_OF_COLUMNS;
+const PUBLIC_INPUTS: usize = 0;
+
+impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for BitCountStark<F, D> {
+    type EvaluationFrame<FE, P, const D2: usize> = StarkFrame<P, P::Scalar, COLUMNS, PUBLIC_INPUTS>
+
+        where
+            FE: FieldExtension<D2, BaseField = F>,
+            P: PackedField<Scalar = FE>;
+    type EvaluationFrameTarget =
+        StarkFrame<ExtensionTarget<D>, ExtensionTarget<D>, COLUMNS, PUBLIC_INPUTS>;
+
+    fn eval_packed_generic<FE, P, const D2: usize>(
+        &self,
+        vars: &Self::EvaluationFrame<FE, P, D2>,
+        consumer: &mut ConstraintConsumer<P>,
+    ) where
+        FE: FieldExtension<D2, BaseField = F>,
+        P: PackedField<Scalar = FE>, {
+        let eb = ExprBuilder::default();
+        let constraints = generate_constraints(&eb.to_typed_starkframe(vars));
+        build_packed(constraints, consumer);
+    }
+
+    fn constraint_degree(&self) -> usize { 3 }
+
+    fn eval_ext_circuit(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        vars: &Self::EvaluationFrameTarget,
+        consumer: &mut RecursiveConstraintConsumer<F, D>,
+    ) {
+        let eb = ExprBuilder::default();
+        let constraints = generate_constraints(&eb.to_typed_starkframe(vars));
+        build_ext(constraints, builder, consumer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mozak_runner::code;
+    use mozak_runner::instruction::{Args, Instruction, Op};
+    use mozak_runner::test_utils::u32_extra;
+    use proptest::prelude::ProptestConfig;
+    use proptest::proptest;
+    use starky::stark_testing::test_stark_circuit_constraints;
+
+    use crate::cpu::stark::CpuStark;
+    use crate::stark::mozak_stark::MozakStark;
+    use crate::test_utils::{ProveAndVerify, C, D, F};
+
+    type S = super::BitCountStark<F, D>;
+
+    fn prove_bitcount<Stark: ProveAndVerify>(a: u32) {
+        let code = [(Op::CLZ, 0), (Op::CTZ, 1), (Op::CPOP, 2)].map(|(op, imm)| Instruction {
+            op,
+            args: Args {
+                rd: 5,
+                rs1: 6,
+                imm,
+                ..Args::default()
+            },
+        });
+        let (program, record) = code::execute(code, &[], &[(6, a)]);
+        assert_eq!(record.last_state.get_register_value(5), a.count_ones());
+        Stark::prove_and_verify(&program, &record).unwrap();
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(4))]
+        #[test]
+        fn prove_bitcount_cpu(a in u32_extra()) {
+            prove_bitcount::<CpuStark<F, D>>(a);
+        }
+    }
+
+    #[test]
+    fn prove_bitcount_mozak() {
+        for a in [0, 1, 0x8000_0000, 0x00F0_0F00] {
+            prove_bitcount::<MozakStark<F, D>>(a);
+        }
+    }
+
+    #[test]
+    fn test_circuit() -> anyhow::Result<()> {
+        let stark = S::default();
+        test_stark_circuit_constraints::<F, C, S, D>(stark)?;
+
+        Ok(())
+    }
+}
@@ -1,10 +1,9 @@
-use crate::columns_view::{columns_view_impl, make_col_map};
+use crate::columns_view::{make_col_map, ColumnsView};
 use crate::linear_combination::Column;
 use crate::stark::mozak_stark::{BitshiftTable, TableWithTypedOutput};
 
-columns_view_impl!(Bitshift);
 #[repr(C)]
-#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
 pub struct Bitshift<T> {
     pub amount: T,
     pub multiplier: T,
@@ -20,9 +19,8 @@ impl From<u8> for Bitshift<u32> {
 }
 
 make_col_map!(BitshiftView);
-columns_view_impl!(BitshiftView);
 #[repr(C)]
-#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
 pub struct BitshiftView<T> {
     /// Contains the `Bitshift` columns with the shift amount and the
     /// multiplier.
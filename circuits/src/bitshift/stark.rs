@@ -135,15 +135,7 @@ mod tests {
     fn prove_sll() -> Result<()> {
         let p: u32 = 10;
         let q: u32 = 10;
-        let sll = Instruction {
-            op: Op::SLL,
-            args: Args {
-                rd: 5,
-                rs1: 7,
-                rs2: 8,
-                ..Args::default()
-            },
-        };
+        let sll = Instruction::sll(5, 7, 8);
         // We use 3 similar instructions here to ensure duplicates and padding work
         // during trace generation.
         let (program, record) = code::execute([sll, sll, sll], &[], &[(7, p), (8, q)]);
@@ -155,15 +147,7 @@ mod tests {
     fn prove_srl() -> Result<()> {
         let p: u32 = 10;
         let q: u32 = 10;
-        let srl = Instruction {
-            op: Op::SRL,
-            args: Args {
-                rd: 5,
-                rs1: 7,
-                rs2: 8,
-                ..Args::default()
-            },
-        };
+        let srl = Instruction::srl(5, 7, 8);
 
         // We use 3 similar instructions here to ensure duplicates and padding work
         // during trace generation.
@@ -176,15 +160,7 @@ mod tests {
         #[test]
         fn prove_shift_amount_proptest(p in u32_extra(), q in u32_extra()) {
             let (program, record) = code::execute(
-                [Instruction {
-                    op: Op::SLL,
-                    args: Args {
-                        rd: 5,
-                        rs1: 7,
-                        rs2: 8,
-                        ..Args::default()
-                    },
-                },
+                [Instruction::sll(5, 7, 8),
                 Instruction {
                     op: Op::SRL,
                     args: Args {
@@ -0,0 +1,146 @@
+//! Which columns of each table the proof actually reads.
+//!
+//! The prover commits to every column of every table, so a column that no
+//! constraint and no cross table lookup reads costs commitment time without
+//! tying its values to anything. [`unused_columns`] lists those, so that a
+//! test fails when a table grows one.
+//!
+//! Constraints are opaque functions of the local and next rows, so
+//! [`constrained_columns`] probes them: it evaluates the constraints of a
+//! stark on random rows, and again with one column changed. Lookups list their
+//! columns, so [`looked_up_columns`] reads them off directly.
+
+use itertools::chain;
+use plonky2::field::extension::Extendable;
+use plonky2::field::types::Sample;
+use plonky2::hash::hash_types::RichField;
+use starky::constraint_consumer::ConstraintConsumer;
+use starky::evaluation_frame::StarkEvaluationFrame;
+use starky::stark::Stark;
+
+use crate::stark::mozak_stark::{all_starks, MozakStark, TableKind, TableKindArray};
+
+/// Evaluates the constraints of `stark` on `lv` and `nv`, combined with a
+/// single challenge.
+fn eval<F: RichField + Extendable<D>, const D: usize, S: Stark<F, D>>(
+    stark: &S,
+    lv: &[F],
+    nv: &[F],
+    public_inputs: &[F],
+    [alpha, z_last, lagrange_first, lagrange_last]: [F; 4],
+) -> F {
+    let mut consumer = ConstraintConsumer::new(vec![alpha], z_last, lagrange_first, lagrange_last);
+    let vars = S::EvaluationFrame::<F, F, 1>::from_values(lv, nv, public_inputs);
+    stark.eval_packed_generic(&vars, &mut consumer);
+    consumer.accumulators()[0]
+}
+
+/// Whether any constraint of `stark` reads each of its columns, in the local
+/// or the next row.
+///
+/// A constraint that reads a column changes its value when the column changes
+/// to a random value, except with negligible probability, so the columns are
+/// probed one at a time.
+#[must_use]
+pub fn constrained_columns<F: RichField + Extendable<D>, const D: usize, S: Stark<F, D>>(
+    stark: &S,
+) -> Vec<bool> {
+    let lv = F::rand_vec(S::COLUMNS);
+    let nv = F::rand_vec(S::COLUMNS);
+    let public_inputs = F::rand_vec(S::PUBLIC_INPUTS);
+    let selectors = F::rand_array();
+    let unchanged = eval(stark, &lv, &nv, &public_inputs, selectors);
+    (0..S::COLUMNS)
+        .map(|column| {
+            let (mut lv, mut nv) = (lv.clone(), nv.clone());
+            lv[column] = F::rand();
+            nv[column] = F::rand();
+            eval(stark, &lv, &nv, &public_inputs, selectors) != unchanged
+        })
+        .collect()
+}
+
+/// Whether a cross table lookup or a public sub table reads each column of
+/// each table, as a value or as a filter.
+#[must_use]
+pub fn looked_up_columns<F: RichField + Extendable<D>, const D: usize>(
+    mozak_stark: &MozakStark<F, D>,
+) -> TableKindArray<Vec<bool>> {
+    let mut used = all_starks!(mozak_stark, |stark, _kind| vec![false; columns(stark)]);
+    let tables = chain!(
+        mozak_stark
+            .cross_table_lookups
+            .iter()
+            .flat_map(|ctl| &ctl.looking_tables),
+        mozak_stark
+            .public_sub_tables
+            .iter()
+            .map(|public_sub_table| &public_sub_table.table),
+    );
+    for table in tables {
+        for column in chain!(&table.columns, [&table.filter_column]) {
+            for &(index, _) in chain!(&column.lv_linear_combination, &column.nv_linear_combination)
+            {
+                used[table.kind][index] = true;
+            }
+        }
+    }
+    used
+}
+
+fn columns<F: RichField + Extendable<D>, const D: usize, S: Stark<F, D>>(_stark: &S) -> usize {
+    S::COLUMNS
+}
+
+/// The columns that neither a constraint nor a lookup reads, by table and
+/// index.
+#[must_use]
+pub fn unused_columns<F: RichField + Extendable<D>, const D: usize>(
+    mozak_stark: &MozakStark<F, D>,
+) -> Vec<(TableKind, usize)> {
+    let constrained = all_starks!(mozak_stark, |stark, _kind| constrained_columns::<F, D, _>(
+        stark
+    ));
+    let looked_up = looked_up_columns(mozak_stark);
+    constrained
+        .with_kind()
+        .0
+        .into_iter()
+        .flat_map(|(constrained, kind)| {
+            let looked_up = &looked_up[kind];
+            (0..constrained.len())
+                .filter(move |&column| !constrained[column] && !looked_up[column])
+                .map(move |column| (kind, column))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{constrained_columns, unused_columns};
+    use crate::register::general::columns::Register;
+    use crate::register::general::stark::RegisterStark;
+    use crate::stark::mozak_stark::MozakStark;
+    use crate::test_utils::{D, F};
+
+    #[test]
+    fn constraints_of_the_register_table_read_its_columns() {
+        let constrained = constrained_columns::<F, D, _>(&RegisterStark::<F, D>::default());
+        let names = Register::<()>::column_names();
+        let read = |name: &str| constrained[names.iter().position(|n| n == name).unwrap()];
+        assert!(read("addr"));
+        assert!(read("value"));
+        assert!(read("ops.is_read"));
+        // Only the range check of the register table reads the clock.
+        assert!(!read("clk"));
+    }
+
+    #[test]
+    fn every_column_is_read() {
+        let unused = unused_columns(&MozakStark::<F, D>::default());
+        assert!(
+            unused.is_empty(),
+            "no constraint or lookup reads these columns: {unused:?}"
+        );
+    }
+}
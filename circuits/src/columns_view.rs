@@ -1,12 +1,15 @@
 //! This module makes STARK table row values indexing simpler by providing
 //! an abstraction for column-by-name access instead of direct number indexing.
-//! This is achieved by using the macros below.
+//! This is achieved by `#[derive(ColumnsView)]`, which builds on the macros
+//! below.
 //!
 //! This way, they can be nested to group columns by logic they handle.
 
 use std::marker::PhantomData;
 use std::mem::{size_of, ManuallyDrop};
 
+pub(crate) use mozak_circuits_derive::{ColumnNames, ColumnsView};
+
 pub(crate) const unsafe fn transmute_without_compile_time_size_checks<T, U>(t: T) -> U {
     #[repr(C)]
     union MyUnion<T, U> {
@@ -42,12 +45,52 @@ pub trait Zip<Item> {
         F: FnMut(Item, Item) -> Item;
 }
 
+/// Names the columns of a view, see `column_names()` generated by
+/// `#[derive(ColumnsView)]`.
+pub trait ColumnNames {
+    /// Pushes the names of all columns, in order, prefixed by `prefix`.
+    fn push_column_names(prefix: &str, names: &mut Vec<String>);
+}
+
+/// Stands in for a single column when naming the columns of a view.
+pub struct NamedColumn;
+
+impl ColumnNames for NamedColumn {
+    fn push_column_names(prefix: &str, names: &mut Vec<String>) { names.push(prefix.to_owned()); }
+}
+
+/// A lookup row over [`Column`](crate::linear_combination::Column)s names its
+/// columns just like the view it is built from.
+impl ColumnNames for crate::linear_combination::Column {
+    fn push_column_names(prefix: &str, names: &mut Vec<String>) { names.push(prefix.to_owned()); }
+}
+
+impl<T: ColumnNames, const N: usize> ColumnNames for [T; N] {
+    fn push_column_names(prefix: &str, names: &mut Vec<String>) {
+        for i in 0..N {
+            T::push_column_names(&format!("{prefix}[{i}]"), names);
+        }
+    }
+}
+
+impl<T> ColumnNames for PhantomData<T> {
+    fn push_column_names(_prefix: &str, _names: &mut Vec<String>) {}
+}
+
+#[doc(hidden)]
+#[must_use]
+pub fn join_column_name(prefix: &str, field: &str) -> String {
+    if prefix.is_empty() {
+        field.to_owned()
+    } else {
+        format!("{prefix}.{field}")
+    }
+}
+
 /// This structure only exists to improve macro impl hiding
 #[doc(hidden)]
 pub struct ColumnViewImplHider<T>(PhantomData<T>);
 
-// Note: this could also be a custom derive macro, but clippy can't look 'into'
-// procedural macros.  So we leave it as is.
 /// Functions to handle and seamlessly convert between `SubTableView` with named
 /// fields and default `[T, ColumnSize]` column representations.
 ///
@@ -56,6 +99,9 @@ pub struct ColumnViewImplHider<T>(PhantomData<T>);
 /// Now, instead of accessing columns by `columns[i]` one can instead access
 /// them as `new_columns_repr.filter_column` and at the same time `columns` can
 /// `new_columns_repr` can be seamlessly converted between each other.
+///
+/// This is what `#[derive(ColumnsView)]` expands to, next to the column
+/// names. Use the derive instead of invoking the macro directly.
 macro_rules! columns_view_impl {
     ($s: ident) => {
         // This hides all the `unsafe` from clippy
@@ -271,3 +317,28 @@ where
     indices_arr[which] = 1;
     indices_arr.into()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::NumberOfColumns;
+    use crate::cpu::columns::CpuState;
+    use crate::xor::columns::XorColumnsView;
+
+    #[test]
+    fn column_names_of_nested_views_and_arrays() {
+        let names = XorColumnsView::<()>::column_names();
+        assert_eq!(names.len(), XorColumnsView::<()>::NUMBER_OF_COLUMNS);
+        assert_eq!(names[..5], [
+            "is_execution_row",
+            "execution.a",
+            "execution.b",
+            "execution.out",
+            "limbs.a[0]"
+        ]);
+        assert_eq!(names.last().unwrap(), "limbs.out[31]");
+
+        let names = CpuState::<()>::column_names();
+        assert_eq!(names.len(), CpuState::<()>::NUMBER_OF_COLUMNS);
+        assert!(names.iter().any(|name| name == "inst.ops.variant"));
+    }
+}
@@ -0,0 +1,359 @@
+//! A model of what each executed instruction costs to prove.
+//!
+//! Proving time is dominated by committing to the traces, which grows with
+//! their number of cells: rows times columns, after padding every table to a
+//! power of two. Each executed instruction adds rows to a few of the tables:
+//! one to the CPU, one per register and per byte of memory it accesses, the
+//! range checks of those accesses, and the permutations of a Poseidon2 ecall.
+//!
+//! The model counts these rows from an [`ExecutionRecord`] without generating
+//! traces, so it is cheap enough to run on any execution, but it is an
+//! estimate. Tables that do not grow with single instructions, like the
+//! program ROM or memory initialization, are left out, and range checks are
+//! counted per lookup, although the range check table only has a row per
+//! distinct value.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::AddAssign;
+use std::time::Duration;
+
+use itertools::Itertools;
+use mozak_runner::instruction::Op;
+use mozak_runner::vm::{ExecutionRecord, Row};
+use mozak_sdk::core::ecall;
+use mozak_sdk::core::reg_abi::REG_A0;
+use plonky2::field::extension::Extendable;
+use plonky2::hash::hash_types::RichField;
+use serde::{Deserialize, Serialize};
+use starky::stark::Stark;
+
+use crate::generation::MIN_TRACE_LENGTH;
+use crate::stark::mozak_stark::{all_starks, MozakStark, TableKind};
+
+/// Rows an instruction adds to the tables that dominate proving, or, in
+/// [`CostModel::columns`], the columns of a row of each of them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RowCost {
+    /// The CPU and its skeleton.
+    pub cpu: u64,
+    /// The register table, for reads and writes of registers other than
+    /// `zero`.
+    pub register: u64,
+    /// The memory table, per byte.
+    pub memory: u64,
+    /// The range check table, per lookup.
+    pub rangecheck: u64,
+    /// The Poseidon2 sponge and permutation tables, per permutation.
+    pub poseidon2: u64,
+}
+
+impl AddAssign for RowCost {
+    fn add_assign(&mut self, other: Self) {
+        self.cpu += other.cpu;
+        self.register += other.register;
+        self.memory += other.memory;
+        self.rangecheck += other.rangecheck;
+        self.poseidon2 += other.poseidon2;
+    }
+}
+
+impl RowCost {
+    fn fields(self) -> [u64; 5] {
+        [
+            self.cpu,
+            self.register,
+            self.memory,
+            self.rangecheck,
+            self.poseidon2,
+        ]
+    }
+
+    /// The rows of the execution of `row`.
+    #[must_use]
+    pub fn of<F: RichField>(row: &Row<F>) -> Self {
+        let args = row.instruction.args;
+        let register = if row.instruction.op == Op::ECALL {
+            // The ecall number and up to three arguments, in a0 to a3.
+            4
+        } else {
+            [args.rs1, args.rs2, args.rd]
+                .into_iter()
+                .filter(|&reg| reg != 0)
+                .count() as u64
+        };
+        let memory = row.aux.mem_addresses_used.len() as u64;
+        #[cfg(feature = "poseidon2")]
+        let poseidon2 = row
+            .aux
+            .poseidon2
+            .as_ref()
+            .map_or(0, |entry| entry.sponge_data.len() as u64);
+        #[cfg(not(feature = "poseidon2"))]
+        let poseidon2 = 0;
+        Self {
+            cpu: 1,
+            register,
+            memory,
+            // Register and memory accesses each range check the difference
+            // to the previous access of the same address.
+            rangecheck: register + memory,
+            poseidon2,
+        }
+    }
+
+    /// Cells of these rows, with `columns` per row.
+    #[must_use]
+    pub fn cells(self, columns: Self) -> u64 {
+        self.fields()
+            .iter()
+            .zip(columns.fields())
+            .map(|(rows, columns)| rows * columns)
+            .sum()
+    }
+}
+
+/// How fast a machine proves.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MachineProfile {
+    /// Proving time per cell of the padded traces, including their low degree
+    /// extension, commitment and quotient.
+    pub nanos_per_cell: f64,
+}
+
+impl Default for MachineProfile {
+    /// A rough figure for a single core of a recent laptop. Measure a proof on
+    /// the machine at hand for anything better.
+    fn default() -> Self {
+        Self {
+            nanos_per_cell: 100.0,
+        }
+    }
+}
+
+/// The columns of the tables of each part of a [`RowCost`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CostModel {
+    pub columns: RowCost,
+}
+
+fn columns<F: RichField + Extendable<D>, const D: usize, S: Stark<F, D>>(_stark: &S) -> u64 {
+    S::COLUMNS as u64
+}
+
+impl CostModel {
+    /// The model of the tables of `mozak_stark`.
+    #[must_use]
+    pub fn new<F: RichField + Extendable<D>, const D: usize>(
+        mozak_stark: &MozakStark<F, D>,
+    ) -> Self {
+        let widths = all_starks!(mozak_stark, |stark, _kind| columns::<F, D, _>(stark));
+        let width = |kinds: &[TableKind]| kinds.iter().map(|&kind| widths[kind]).sum();
+        Self {
+            columns: RowCost {
+                cpu: width(&[TableKind::Cpu, TableKind::CpuSkeleton]),
+                register: width(&[TableKind::Register]),
+                memory: width(&[TableKind::Memory]),
+                rangecheck: width(&[TableKind::RangeCheck]),
+                #[cfg(feature = "poseidon2")]
+                poseidon2: width(&[TableKind::Poseidon2Sponge, TableKind::Poseidon2]),
+                #[cfg(not(feature = "poseidon2"))]
+                poseidon2: 0,
+            },
+        }
+    }
+
+    /// The cost of every instruction of `record`, by operation.
+    #[must_use]
+    pub fn report<F: RichField>(&self, record: &ExecutionRecord<F>) -> CostReport {
+        let mut by_op: HashMap<String, OpCost> = HashMap::new();
+        for row in &record.executed {
+            let cost = by_op.entry(label(row)).or_default();
+            cost.count += 1;
+            cost.rows += RowCost::of(row);
+        }
+        CostReport {
+            columns: self.columns,
+            by_op,
+        }
+    }
+}
+
+/// Names an executed instruction by its operation, and ecalls by what they
+/// call.
+fn label<F: RichField>(row: &Row<F>) -> String {
+    match row.instruction.op {
+        Op::ECALL => match ecall::log(row.state.get_register_value(REG_A0)) {
+            "" => "ecall".to_string(),
+            name => format!("ecall {name}"),
+        },
+        op => format!("{op:?}").to_lowercase(),
+    }
+}
+
+/// How often an operation was executed, and the rows it added.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OpCost {
+    pub count: u64,
+    pub rows: RowCost,
+}
+
+/// The cost of the instructions of an execution, by operation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CostReport {
+    pub columns: RowCost,
+    pub by_op: HashMap<String, OpCost>,
+}
+
+impl CostReport {
+    /// Rows of all instructions.
+    #[must_use]
+    pub fn total(&self) -> RowCost {
+        let mut total = RowCost::default();
+        for cost in self.by_op.values() {
+            total += cost.rows;
+        }
+        total
+    }
+
+    /// Operations by the cells they added, most first. Ties are broken by
+    /// name.
+    #[must_use]
+    pub fn costliest(&self) -> Vec<(&str, OpCost)> {
+        self.by_op
+            .iter()
+            .map(|(op, &cost)| (op.as_str(), cost))
+            .sorted_by_key(|&(op, cost)| (std::cmp::Reverse(cost.rows.cells(self.columns)), op))
+            .collect()
+    }
+
+    /// Cells of the traces, with every table padded like the prover does.
+    #[must_use]
+    pub fn padded_cells(&self) -> u64 {
+        let padded = |rows: u64| match rows {
+            0 => 0,
+            rows => rows.next_power_of_two().max(MIN_TRACE_LENGTH as u64),
+        };
+        let [cpu, register, memory, rangecheck, poseidon2] = self.total().fields().map(padded);
+        RowCost {
+            cpu,
+            register,
+            memory,
+            rangecheck,
+            poseidon2,
+        }
+        .cells(self.columns)
+    }
+
+    /// Estimated time to prove the execution on `profile`.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)]
+    pub fn proving_time(&self, profile: &MachineProfile) -> Duration {
+        Duration::from_secs_f64(self.padded_cells() as f64 * profile.nanos_per_cell * 1e-9)
+    }
+
+    /// Report of the `n` costliest operations and the estimated proving time
+    /// on `profile`, for printing.
+    #[must_use]
+    pub fn display<'a>(&'a self, n: usize, profile: &'a MachineProfile) -> CostTable<'a> {
+        CostTable {
+            report: self,
+            n,
+            profile,
+        }
+    }
+}
+
+/// Displays a [`CostReport`] as a table of the costliest operations.
+pub struct CostTable<'a> {
+    report: &'a CostReport,
+    n: usize,
+    profile: &'a MachineProfile,
+}
+
+impl fmt::Display for CostTable<'_> {
+    #[allow(clippy::cast_precision_loss)]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let columns = self.report.columns;
+        let total = self.report.total();
+        let total_cells = total.cells(columns).max(1);
+        let count: u64 = self.report.by_op.values().map(|cost| cost.count).sum();
+        writeln!(
+            f,
+            "{:<32} {:>10} {:>10} {:>10} {:>10} {:>10} {:>10} {:>7}",
+            "op", "count", "cpu", "register", "memory", "rangecheck", "poseidon2", "cells"
+        )?;
+        for (op, cost) in self.report.costliest().into_iter().take(self.n) {
+            let rows = cost.rows;
+            writeln!(
+                f,
+                "{op:<32} {:>10} {:>10} {:>10} {:>10} {:>10} {:>10} {:>6.2}%",
+                cost.count,
+                rows.cpu,
+                rows.register,
+                rows.memory,
+                rows.rangecheck,
+                rows.poseidon2,
+                100.0 * rows.cells(columns) as f64 / total_cells as f64
+            )?;
+        }
+        writeln!(
+            f,
+            "{:<32} {:>10} {:>10} {:>10} {:>10} {:>10} {:>10}",
+            "total",
+            count,
+            total.cpu,
+            total.register,
+            total.memory,
+            total.rangecheck,
+            total.poseidon2
+        )?;
+        write!(
+            f,
+            "{} padded cells, about {:.1?} to prove at {} ns per cell",
+            self.report.padded_cells(),
+            self.report.proving_time(self.profile),
+            self.profile.nanos_per_cell
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mozak_runner::code;
+    use mozak_runner::instruction::Instruction;
+
+    use super::{CostModel, RowCost};
+    use crate::stark::mozak_stark::MozakStark;
+    use crate::test_utils::{D, F};
+
+    #[test]
+    fn rows_by_op() {
+        let store = Instruction::sw(5, 0, 0x100);
+        let load = Instruction::lbu(6, 0, 0x100);
+        let (_program, record) = code::execute([store, load, load], &[], &[(5, 7)]);
+        let model = CostModel::new(&MozakStark::<F, D>::default());
+        let report = model.report(&record);
+
+        assert_eq!(report.by_op["sw"].count, 1);
+        assert_eq!(report.by_op["sw"].rows, RowCost {
+            cpu: 1,
+            register: 1,
+            memory: 4,
+            rangecheck: 5,
+            poseidon2: 0,
+        });
+        assert_eq!(report.by_op["lbu"].count, 2);
+        assert_eq!(report.by_op["lbu"].rows, RowCost {
+            cpu: 2,
+            register: 2,
+            memory: 2,
+            rangecheck: 4,
+            poseidon2: 0,
+        });
+        // The halting `li a0, 0` and ecall that `code::execute` appends.
+        assert_eq!(report.by_op["ecall halt"].count, 1);
+        assert_eq!(report.total().cpu, 5);
+        assert!(report.padded_cells() >= report.total().cells(model.columns));
+    }
+}
@@ -23,6 +23,10 @@
 //!  2 * (x & y) := (x + y - (x ^ y))
 //!  2 * (x | y) := (x + y + (x ^ y))
 //! `
+//!
+//! The Zbb instructions ANDN, ORN and XNOR reuse the same gadgets: for a `u32`
+//! the complement is `!x = 0xFFFF_FFFF - x`, so we complement the second input
+//! of AND and OR, or the output of XOR.
 
 use expr::Expr;
 
@@ -84,7 +88,7 @@ pub(crate) fn xor_gadget<'a, P: Copy>(xor: &XorView<Expr<'a, P>>) -> BinaryOp<Ex
     }
 }
 
-/// Constraints for the AND, OR and XOR opcodes.
+/// Constraints for the AND, OR and XOR opcodes, and their Zbb variants.
 /// As each opcode has an associated selector, we use selectors to enable only
 /// the correct opcode constraints. It can be that all selectors are not active,
 /// representing that the operation is neither AND, nor OR or XOR.
@@ -97,11 +101,15 @@ pub(crate) fn constraints<'a, P: Copy>(
     let op1 = lv.op1_value;
     let op2 = lv.op2_value;
     let dst = lv.dst_value;
+    let not = |x: Expr<'a, P>| i64::from(u32::MAX) - x;
 
-    for (selector, gadget) in [
-        (lv.inst.ops.and, and_gadget(&lv.xor)),
-        (lv.inst.ops.or, or_gadget(&lv.xor)),
-        (lv.inst.ops.xor, xor_gadget(&lv.xor)),
+    for (selector, gadget, op2, dst) in [
+        (lv.inst.ops.and(), and_gadget(&lv.xor), op2, dst),
+        (lv.inst.ops.or(), or_gadget(&lv.xor), op2, dst),
+        (lv.inst.ops.xor(), xor_gadget(&lv.xor), op2, dst),
+        (lv.inst.ops.andn(), and_gadget(&lv.xor), not(op2), dst),
+        (lv.inst.ops.orn(), or_gadget(&lv.xor), not(op2), dst),
+        (lv.inst.ops.xnor(), xor_gadget(&lv.xor), op2, not(dst)),
     ] {
         cb.always(selector * (gadget.input_a - op1));
         cb.always(selector * (gadget.input_b - op2));
@@ -123,7 +131,7 @@ mod tests {
 
     fn prove_bitwise<Stark: ProveAndVerify>(a: u32, b: u32, imm: u32, use_imm: bool) {
         let (b, imm) = if use_imm { (0, imm) } else { (b, 0) };
-        let code: Vec<_> = [Op::AND, Op::OR, Op::XOR]
+        let code: Vec<_> = [Op::AND, Op::OR, Op::XOR, Op::ANDN, Op::ORN, Op::XNOR]
             .into_iter()
             .map(|kind| Instruction {
                 op: kind,
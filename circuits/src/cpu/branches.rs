@@ -50,8 +50,6 @@ pub(crate) fn constraints<'a, P: Copy>(
     cb: &mut ConstraintBuilder<Expr<'a, P>>,
 ) {
     let ops = &lv.inst.ops;
-    let is_blt = ops.blt;
-    let is_bge = ops.bge;
 
     let bumped_pc = lv.inst.pc + 4;
     let branched_pc = lv.inst.imm_value;
@@ -59,23 +57,27 @@ pub(crate) fn constraints<'a, P: Copy>(
 
     let lt = lv.less_than;
 
-    // Check: for BLT and BLTU branch if `lt == 1`, otherwise just increment the pc.
-    // Note that BLT and BLTU behave equivalently, as `lt` handles signed
-    // conversions.
-    cb.always(is_blt * lt * (next_pc - branched_pc));
-    cb.always(is_blt * (1 - lt) * (next_pc - bumped_pc));
-
-    // Check: for BGE and BGEU we reverse the checks of BLT and BLTU.
-    cb.always(is_bge * lt * (next_pc - bumped_pc));
-    cb.always(is_bge * (1 - lt) * (next_pc - branched_pc));
-
-    // Check: for BEQ, branch if `normalised_diff == 0`, otherwise increment the pc.
-    cb.always(ops.beq * (1 - lv.normalised_diff) * (next_pc - branched_pc));
-    cb.always(ops.beq * lv.normalised_diff * (next_pc - bumped_pc));
-
-    // Check: for BNE, we reverse the checks of BEQ.
-    cb.always(ops.bne * lv.normalised_diff * (next_pc - branched_pc));
-    cb.always(ops.bne * (1 - lv.normalised_diff) * (next_pc - bumped_pc));
+    // Check: `branch_taken` holds the condition of the branch.
+    // BLT and BLTU branch if `lt == 1`. Note that BLT and BLTU behave
+    // equivalently, as `lt` handles signed conversions.
+    // BGE and BGEU reverse the condition of BLT and BLTU.
+    // BEQ branches if `normalised_diff == 0`, and BNE reverses that.
+    cb.always(
+        lv.branch_taken
+            - (ops.blt() * lt
+                + ops.bge() * (1 - lt)
+                + ops.beq() * (1 - lv.normalised_diff)
+                + ops.bne() * lv.normalised_diff),
+    );
+
+    // Check: a branch whose condition holds jumps, otherwise it just increments
+    // the pc.
+    // The decoded branches above already have degree two, so this goes
+    // through `branch_taken` and the selectors of their families.
+    cb.always(
+        (ops.beq_bne + ops.blt_bge)
+            * (next_pc - bumped_pc - lv.branch_taken * (branched_pc - bumped_pc)),
+    );
 }
 
 #[cfg(test)]
@@ -105,14 +107,7 @@ mod tests {
                     },
                 },
                 // if above branch is not taken R1 has value 10.
-                Instruction {
-                    op: Op::ADD,
-                    args: Args {
-                        rd: 1,
-                        imm: 10,
-                        ..Args::default()
-                    },
-                },
+                Instruction::li(1, 10),
             ],
             &[],
             &[(6, a), (7, b)],
@@ -1,75 +1,107 @@
 use core::iter::Sum;
 use core::ops::{Add, Mul, Sub};
 
-use mozak_runner::instruction::Op;
+use anyhow::{anyhow, Result};
+use itertools::{chain, izip};
+use mozak_runner::instruction::{Args, Op};
+use plonky2::hash::hash_types::RichField;
 
+#[cfg(feature = "zbb")]
+use crate::bitcount::columns::BitCountCtl;
 use crate::bitshift::columns::Bitshift;
-use crate::columns_view::{columns_view_impl, make_col_map};
-use crate::cpu_skeleton::columns::CpuSkeletonCtl;
+use crate::columns_view::{make_col_map, ColumnsView};
+use crate::cpu_skeleton::columns::{make_exit_code_public, CpuSkeletonCtl};
 use crate::cross_table_lookup::{Column, ColumnWithTypedInput};
+use crate::event_counters::columns::{EventCounterCtl, EventKind};
+use crate::event_emission::columns::EventEmissionCtl;
+#[cfg(feature = "goldilocks")]
+use crate::goldilocks::columns::GoldilocksCtl;
+#[cfg(feature = "memcopy")]
+use crate::memcopy::columns::MemCopyCtl;
 use crate::memory::columns::MemoryCtl;
+#[cfg(feature = "poseidon2")]
 use crate::poseidon2_sponge::columns::Poseidon2SpongeCtl;
 use crate::program::columns::ProgramRom;
+use crate::public_sub_table::{PublicSubTable, PublicValue};
 use crate::rangecheck::columns::RangeCheckCtl;
 use crate::register::RegisterCtl;
+#[cfg(feature = "secp256k1")]
+use crate::secp256k1::columns::Secp256k1Ctl;
 use crate::stark::mozak_stark::{CpuTable, TableWithTypedOutput};
 use crate::storage_device::columns::StorageDeviceCtl;
+#[cfg(feature = "u256")]
+use crate::u256::columns::U256Ctl;
 use crate::xor::columns::XorView;
 
-columns_view_impl!(OpSelectors);
 /// Selectors for which instruction is currently active.
+///
+/// Instead of a one-hot selector per operation, operations come in families
+/// of one or two. Exactly one family selector is set in a running row, and the
+/// binary [`variant`](OpSelectors::variant) picks the second operation of a
+/// family of two. The operations of a family of two decode to the product of
+/// the family selector and the variant, which adds one to the degree of their
+/// constraints, and which cross table lookups cannot use as a filter.
+/// Operations that need either thus get a family of their own, and families of
+/// two share their lookups, eg the loads and stores of a byte look up the
+/// memory with the variant as `is_store`.
+///
+/// The op code, which the program ROM holds, is the
+/// [ascending sum](crate::generation::ascending_sum) of
+/// [`OpSelectors::code_terms`]: one more than the position of the family,
+/// plus one more than the position of the variant for the second operation.
+/// This leaves the op code zero to `ADD`, which has a table of its own, and
+/// keeps all op codes below `2^5`.
 #[repr(C)]
-#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
 pub struct OpSelectors<T> {
-    pub add: T,
+    /// AND, and AND with inverted operand from Zbb as the variant
+    pub and_andn: T,
+    /// OR, and OR with inverted operand from Zbb as the variant
+    pub or_orn: T,
+    /// XOR, and exclusive NOR from Zbb as the variant
+    pub xor_xnor: T,
+    /// Division, and remainder as the variant
+    pub div_rem: T,
+    /// Multiplication, and MUL High as the variant, which returns the most
+    /// significant 'overflow' bits
+    pub mul_mulh: T,
+    /// Set Less Than, and Read Cycle Counter (also used for Read Instructions
+    /// Retired) as the variant. Neither needs a lookup of its own.
+    pub slt_rdcycle: T,
+    /// Branch on Equal, and Branch on Not Equal as the variant
+    pub beq_bne: T,
+    /// Branch Less Than, and Branch Greater or Equal as the variant
+    pub blt_bge: T,
+    /// Load Byte, which places it in the least significant byte position of
+    /// the target register, and Store Byte as the variant
+    pub lb_sb: T,
+    /// Load Half Word, and Store Half Word as the variant
+    pub lh_sh: T,
+    /// Load Word, and Store Word as the variant
+    pub lw_sw: T,
     pub sub: T,
-    pub xor: T,
-    pub or: T,
-    pub and: T,
-    pub div: T,
-    pub rem: T,
-    pub mul: T,
-    // MUL High: Multiply two values, and return most significant 'overflow' bits
-    pub mulh: T,
     /// Shift Left Logical by amount
     pub sll: T,
-    /// Set Less Than
-    pub slt: T,
     /// Shift Right Logical by amount
     pub srl: T,
     /// Arithmetic Right Shifts
     pub sra: T,
     /// Jump And Link Register
     pub jalr: T,
-    /// Branch on Equal
-    pub beq: T,
-    /// Branch on Not Equal
-    pub bne: T,
-    /// Store Byte
-    pub sb: T,
-    /// Store Half Word
-    pub sh: T,
-    /// Store Word
-    pub sw: T,
-    /// Load Byte Unsigned and places it in the least significant byte position
-    /// of the target register.
-    pub lb: T,
-    /// Load Half Word
-    pub lh: T,
-    /// Load Word
-    pub lw: T,
-    /// Branch Less Than
-    pub blt: T,
-    /// Branch Greater or Equal
-    pub bge: T,
     /// Environment Call
     pub ecall: T,
+    /// Multiply-Add, our custom-0 instruction
+    pub madd: T,
+    /// Count leading or trailing zeros, or set bits, from Zbb. The immediate
+    /// picks which count.
+    pub bitcount: T,
+    /// Picks the second operation of a family of two, and is zero otherwise.
+    pub variant: T,
 }
 
-columns_view_impl!(Instruction);
 /// Internal [Instruction] of Stark used for transition constrains
 #[repr(C)]
-#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
 pub struct Instruction<T> {
     /// The original instruction (+ `imm_value`) used for program
     /// cross-table-lookup.
@@ -108,33 +140,45 @@ impl From<(u32, mozak_runner::instruction::Instruction)> for Instruction<u32> {
             is_dst_signed: matches!(inst.op, Op::LB | Op::LH).into(),
             ..Self::default()
         };
-        *match inst.op {
-            Op::ADD => &mut cols.ops.add,
-            Op::LBU | Op::LB => &mut cols.ops.lb,
-            Op::LH | Op::LHU => &mut cols.ops.lh,
-            Op::LW => &mut cols.ops.lw,
-            Op::SLL => &mut cols.ops.sll,
-            Op::SLT | Op::SLTU => &mut cols.ops.slt,
-            Op::SB => &mut cols.ops.sb,
-            Op::SH => &mut cols.ops.sh,
-            Op::SW => &mut cols.ops.sw,
-            Op::SRL => &mut cols.ops.srl,
-            Op::SRA => &mut cols.ops.sra,
-            Op::SUB => &mut cols.ops.sub,
-            Op::DIV | Op::DIVU => &mut cols.ops.div,
-            Op::REM | Op::REMU => &mut cols.ops.rem,
-            Op::MUL => &mut cols.ops.mul,
-            Op::MULH | Op::MULHU | Op::MULHSU => &mut cols.ops.mulh,
-            Op::JALR => &mut cols.ops.jalr,
-            Op::BEQ => &mut cols.ops.beq,
-            Op::BNE => &mut cols.ops.bne,
-            Op::BLT | Op::BLTU => &mut cols.ops.blt,
-            Op::BGE | Op::BGEU => &mut cols.ops.bge,
-            Op::ECALL => &mut cols.ops.ecall,
-            Op::XOR => &mut cols.ops.xor,
-            Op::OR => &mut cols.ops.or,
-            Op::AND => &mut cols.ops.and,
-        } = 1;
+        let ops = &mut cols.ops;
+        let (family, variant) = match inst.op {
+            // ADD has a table of its own.
+            Op::ADD => (None, false),
+            Op::AND => (Some(&mut ops.and_andn), false),
+            Op::ANDN => (Some(&mut ops.and_andn), true),
+            Op::OR => (Some(&mut ops.or_orn), false),
+            Op::ORN => (Some(&mut ops.or_orn), true),
+            Op::XOR => (Some(&mut ops.xor_xnor), false),
+            Op::XNOR => (Some(&mut ops.xor_xnor), true),
+            Op::DIV | Op::DIVU => (Some(&mut ops.div_rem), false),
+            Op::REM | Op::REMU => (Some(&mut ops.div_rem), true),
+            Op::MUL => (Some(&mut ops.mul_mulh), false),
+            Op::MULH | Op::MULHU | Op::MULHSU => (Some(&mut ops.mul_mulh), true),
+            Op::SLT | Op::SLTU => (Some(&mut ops.slt_rdcycle), false),
+            Op::RDCYCLE => (Some(&mut ops.slt_rdcycle), true),
+            Op::BEQ => (Some(&mut ops.beq_bne), false),
+            Op::BNE => (Some(&mut ops.beq_bne), true),
+            Op::BLT | Op::BLTU => (Some(&mut ops.blt_bge), false),
+            Op::BGE | Op::BGEU => (Some(&mut ops.blt_bge), true),
+            Op::LB | Op::LBU => (Some(&mut ops.lb_sb), false),
+            Op::SB => (Some(&mut ops.lb_sb), true),
+            Op::LH | Op::LHU => (Some(&mut ops.lh_sh), false),
+            Op::SH => (Some(&mut ops.lh_sh), true),
+            Op::LW => (Some(&mut ops.lw_sw), false),
+            Op::SW => (Some(&mut ops.lw_sw), true),
+            Op::SUB => (Some(&mut ops.sub), false),
+            Op::SLL => (Some(&mut ops.sll), false),
+            Op::SRL => (Some(&mut ops.srl), false),
+            Op::SRA => (Some(&mut ops.sra), false),
+            Op::JALR => (Some(&mut ops.jalr), false),
+            Op::ECALL => (Some(&mut ops.ecall), false),
+            Op::MADD => (Some(&mut ops.madd), false),
+            Op::CLZ | Op::CTZ | Op::CPOP => (Some(&mut ops.bitcount), false),
+        };
+        if let Some(family) = family {
+            *family = 1;
+        }
+        cols.ops.variant = variant.into();
         cols.rs1_selected = u32::from(inst.args.rs1);
         cols.rs2_selected = u32::from(inst.args.rs2);
         cols.rd_selected = u32::from(inst.args.rd);
@@ -142,10 +186,9 @@ impl From<(u32, mozak_runner::instruction::Instruction)> for Instruction<u32> {
     }
 }
 
-columns_view_impl!(EcallSelectors);
 /// Internal [`Instruction`] of Stark used for transition constraints
 #[repr(C)]
-#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
 pub struct EcallSelectors<T> {
     // We don't need all of these 'is_<some-ecall>' columns.  Because our CPU table (by itself)
     // doesn't need to be deterministic. We can assert these things in the CTL-ed
@@ -162,13 +205,28 @@ pub struct EcallSelectors<T> {
     pub is_halt: T,
     pub is_poseidon2: T,
     pub is_self_prog_id_tape: T,
+    pub is_public_tape_at: T,
+    pub is_prove_assert: T,
+    pub is_secp256k1_add: T,
+    pub is_secp256k1_double: T,
+    pub is_u256_add: T,
+    pub is_u256_mul: T,
+    pub is_u256_mulmod: T,
+    pub is_memcopy: T,
+    pub is_memset: T,
+    pub is_goldilocks_add: T,
+    pub is_goldilocks_mul: T,
+    pub is_goldilocks_inverse: T,
+    pub is_emit_event: T,
+    pub is_sbrk: T,
+    pub is_io_write: T,
+    pub is_hint_read: T,
 }
 
 make_col_map!(CpuState);
-columns_view_impl!(CpuState);
 /// Represents the State of the CPU, which is also a row of the trace
 #[repr(C)]
-#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
 pub struct CpuState<T> {
     pub clk: T,
     pub new_pc: T,
@@ -209,6 +267,8 @@ pub struct CpuState<T> {
     /// `normalised_diff` == 1 iff op1 != op2
     /// We need this intermediate variable to keep the constraint degree <= 3.
     pub normalised_diff: T,
+    /// 1 iff this row is a conditional branch whose condition holds.
+    pub branch_taken: T,
 
     /// Linked values with the Xor Stark Table
     pub xor: XorView<T>,
@@ -238,6 +298,11 @@ pub struct CpuState<T> {
     /// when `product_sign` is 0 and `product_high_limb != 0` when
     /// `product_sign` is 1
     pub product_high_limb_inv_helper: T,
+    /// Value of the destination register before a multiply-add, which gets
+    /// added to the product.
+    pub madd_addend: T,
+    /// Carry out of `product_low_limb + madd_addend` for a multiply-add.
+    pub madd_carry: T,
     pub mem_addr: T,
     pub io_addr: T,
     pub io_size: T,
@@ -274,21 +339,132 @@ impl<P> OpSelectors<P>
 where
     P: Copy + Add<Output = P> + Sum<P> + Sub<Output = P> + Sum,
 {
-    pub fn is_running(self) -> P { self.into_iter().sum() }
+    pub fn is_running(self) -> P { self.into_iter().sum::<P>() - self.variant }
 
     // List of opcodes that manipulated the program counter, instead of
     // straight line incrementing it.
     // Note: ecall is only 'jumping' in the sense that a 'halt'
     // does not bump the PC. It sort-of jumps back to itself.
-    pub fn is_jumping(&self) -> P {
-        self.beq + self.bge + self.blt + self.bne + self.ecall + self.jalr
-    }
+    pub fn is_jumping(&self) -> P { self.beq_bne + self.blt_bge + self.ecall + self.jalr }
 
     /// List of opcodes that only bump the program counter.
     pub fn is_straightline(self) -> P { self.is_running() - self.is_jumping() }
 
     /// List of opcodes that work with memory.
-    pub fn is_mem_op(&self) -> P { self.sb + self.lb + self.sh + self.lh + self.sw + self.lw }
+    pub fn is_mem_op(&self) -> P { self.lb_sb + self.lh_sh + self.lw_sw }
+
+    /// The families with two operations, which are the only ones with a
+    /// variant.
+    pub fn families_of_two(&self) -> P {
+        self.and_andn
+            + self.or_orn
+            + self.xor_xnor
+            + self.div_rem
+            + self.mul_mulh
+            + self.slt_rdcycle
+            + self.beq_bne
+            + self.blt_bge
+            + self.is_mem_op()
+    }
+}
+
+impl<T: Default> OpSelectors<T> {
+    /// The selectors after a leading zero, so that their ascending sum is the
+    /// op code, see [`OpSelectors`].
+    pub fn code_terms(self) -> impl Iterator<Item = T> { chain!([T::default()], self) }
+}
+
+/// The operations of the families of two, decoded with the variant.
+impl<T: Copy + Sub<Output = T> + Mul<Output = T>> OpSelectors<T> {
+    fn first(&self, family: T) -> T { family - family * self.variant }
+
+    fn second(&self, family: T) -> T { family * self.variant }
+
+    #[must_use]
+    pub fn and(&self) -> T { self.first(self.and_andn) }
+
+    #[must_use]
+    pub fn andn(&self) -> T { self.second(self.and_andn) }
+
+    #[must_use]
+    pub fn or(&self) -> T { self.first(self.or_orn) }
+
+    #[must_use]
+    pub fn orn(&self) -> T { self.second(self.or_orn) }
+
+    #[must_use]
+    pub fn xor(&self) -> T { self.first(self.xor_xnor) }
+
+    #[must_use]
+    pub fn xnor(&self) -> T { self.second(self.xor_xnor) }
+
+    #[must_use]
+    pub fn div(&self) -> T { self.first(self.div_rem) }
+
+    #[must_use]
+    pub fn rem(&self) -> T { self.second(self.div_rem) }
+
+    #[must_use]
+    pub fn mul(&self) -> T { self.first(self.mul_mulh) }
+
+    #[must_use]
+    pub fn mulh(&self) -> T { self.second(self.mul_mulh) }
+
+    #[must_use]
+    pub fn slt(&self) -> T { self.first(self.slt_rdcycle) }
+
+    #[must_use]
+    pub fn rdcycle(&self) -> T { self.second(self.slt_rdcycle) }
+
+    #[must_use]
+    pub fn beq(&self) -> T { self.first(self.beq_bne) }
+
+    #[must_use]
+    pub fn bne(&self) -> T { self.second(self.beq_bne) }
+
+    #[must_use]
+    pub fn blt(&self) -> T { self.first(self.blt_bge) }
+
+    #[must_use]
+    pub fn bge(&self) -> T { self.second(self.blt_bge) }
+
+    #[must_use]
+    pub fn lb(&self) -> T { self.first(self.lb_sb) }
+
+    #[must_use]
+    pub fn sb(&self) -> T { self.second(self.lb_sb) }
+
+    #[must_use]
+    pub fn lh(&self) -> T { self.first(self.lh_sh) }
+
+    #[must_use]
+    pub fn sh(&self) -> T { self.second(self.lh_sh) }
+
+    #[must_use]
+    pub fn lw(&self) -> T { self.first(self.lw_sw) }
+
+    #[must_use]
+    pub fn sw(&self) -> T { self.second(self.lw_sw) }
+}
+
+impl<T: Copy + Add<Output = T> + Sub<Output = T> + Mul<Output = T>> OpSelectors<T> {
+    #[must_use]
+    pub fn load_ops(&self) -> T { self.lb() + self.lh() + self.lw() }
+
+    #[must_use]
+    pub fn store_ops(&self) -> T { self.sb() + self.sh() + self.sw() }
+}
+
+/// The op code of `op`, as the program ROM holds it.
+#[must_use]
+pub fn op_code(op: Op) -> i64 {
+    let inst = Instruction::from((
+        0,
+        mozak_runner::instruction::Instruction::new(op, Args::default()),
+    ));
+    izip!(0.., inst.ops.code_terms())
+        .map(|(i, term)| i * i64::from(term))
+        .sum()
 }
 
 /// Expressions we need to range check
@@ -298,16 +474,16 @@ where
 #[must_use]
 pub fn rangecheck_looking() -> Vec<TableWithTypedOutput<RangeCheckCtl<Column>>> {
     let ops = &CPU.inst.ops;
-    let divs = ops.div + ops.rem + ops.srl + ops.sra;
-    let muls: ColumnWithTypedInput<CpuState<i64>> = ops.mul + ops.mulh + ops.sll;
+    let divs = ops.div_rem + ops.srl + ops.sra;
+    let muls: ColumnWithTypedInput<CpuState<i64>> = ops.mul_mulh + ops.sll + ops.madd;
 
     [
         (CPU.quotient_value, divs),
         (CPU.remainder_value, divs),
         (CPU.remainder_slack, divs),
-        (CPU.dst_value, ops.add + ops.sub + ops.jalr),
+        (CPU.dst_value, ops.sub + ops.jalr + ops.madd),
         (CPU.inst.pc, ops.jalr),
-        (CPU.abs_diff, ops.bge + ops.blt),
+        (CPU.abs_diff, ops.blt_bge),
         (CPU.product_high_limb, muls),
         (CPU.product_low_limb, muls),
         // apply range constraints for the sign bits of each operand
@@ -321,8 +497,9 @@ pub fn rangecheck_looking() -> Vec<TableWithTypedOutput<RangeCheckCtl<Column>>>
             CPU.op2_value - CPU.op2_sign_bit * (1 << 32) + CPU.inst.is_op2_signed * (1 << 31),
             CPU.inst.is_op2_signed,
         ),
-        (CPU.dst_value - CPU.dst_sign_bit * 0xFFFF_FF00, ops.lb),
-        (CPU.dst_value - CPU.dst_sign_bit * 0xFFFF_0000, ops.lh),
+        // Stores share the family of their loads, and have no sign bit.
+        (CPU.dst_value - CPU.dst_sign_bit * 0xFFFF_FF00, ops.lb_sb),
+        (CPU.dst_value - CPU.dst_sign_bit * 0xFFFF_0000, ops.lh_sh),
     ]
     .into_iter()
     .map(|(columns, filter)| CpuTable::new(RangeCheckCtl(columns), filter))
@@ -343,12 +520,12 @@ pub fn lookup_for_memory() -> TableWithTypedOutput<MemoryCtl<Column>> {
     CpuTable::new(
         MemoryCtl {
             clk: CPU.clk,
-            is_store: CPU.inst.ops.sb,
-            is_load: CPU.inst.ops.lb, // For both `LB` and `LBU`
+            is_store: CPU.inst.ops.variant,
+            is_load: 1 - CPU.inst.ops.variant, // For both `LB` and `LBU`
             addr: CPU.mem_addr,
             value: CPU.mem_value_raw,
         },
-        CPU.inst.ops.byte_mem_ops(),
+        CPU.inst.ops.lb_sb,
     )
 }
 
@@ -359,12 +536,12 @@ pub fn lookup_for_halfword_memory() -> TableWithTypedOutput<MemoryCtl<Column>> {
     CpuTable::new(
         MemoryCtl {
             clk: CPU.clk,
-            is_store: CPU.inst.ops.sh,
-            is_load: CPU.inst.ops.lh,
+            is_store: CPU.inst.ops.variant,
+            is_load: 1 - CPU.inst.ops.variant,
             addr: CPU.mem_addr,
             value: CPU.mem_value_raw,
         },
-        CPU.inst.ops.halfword_mem_ops(),
+        CPU.inst.ops.lh_sh,
     )
 }
 
@@ -375,12 +552,12 @@ pub fn lookup_for_fullword_memory() -> TableWithTypedOutput<MemoryCtl<Column>> {
     CpuTable::new(
         MemoryCtl {
             clk: CPU.clk,
-            is_store: CPU.inst.ops.sw,
-            is_load: CPU.inst.ops.lw,
+            is_store: CPU.inst.ops.variant,
+            is_load: 1 - CPU.inst.ops.variant,
             addr: CPU.mem_addr,
             value: CPU.mem_value_raw,
         },
-        CPU.inst.ops.fullword_mem_ops(),
+        CPU.inst.ops.lw_sw,
     )
 }
 
@@ -396,31 +573,49 @@ pub fn lookup_for_storage_tables() -> TableWithTypedOutput<StorageDeviceCtl<Colu
         CPU.ecall_selectors.is_events_commitment_tape,
         CPU.ecall_selectors.is_cast_list_commitment_tape,
         CPU.ecall_selectors.is_self_prog_id_tape,
+        CPU.ecall_selectors.is_hint_read,
+        #[cfg(feature = "poseidon2")]
+        CPU.ecall_selectors.is_io_write,
     ];
+    // Reads at an explicit position go to the public tape too.
+    let is_public_tape_at = CPU.ecall_selectors.is_public_tape_at;
     CpuTable::new(
         StorageDeviceCtl {
-            op: ColumnWithTypedInput::ascending_sum(storage),
+            op: ColumnWithTypedInput::ascending_sum(storage) + is_public_tape_at,
             clk: CPU.clk,
             addr: CPU.io_addr,
             size: CPU.io_size,
+            is_read_at: is_public_tape_at,
         },
-        storage.iter().sum(),
+        storage.iter().sum::<ColumnWithTypedInput<_>>() + is_public_tape_at,
     )
 }
 
 impl<T: core::ops::Add<Output = T>> OpSelectors<T> {
     #[must_use]
     pub fn ops_that_use_xor(self) -> T {
-        self.xor + self.or + self.and + self.srl + self.sll + self.sra
+        self.xor_xnor + self.or_orn + self.and_andn + self.srl + self.sll + self.sra
     }
 
     pub fn ops_that_shift(self) -> T { self.sll + self.srl + self.sra }
+}
 
-    pub fn byte_mem_ops(self) -> T { self.sb + self.lb }
-
-    pub fn halfword_mem_ops(self) -> T { self.sh + self.lh }
-
-    pub fn fullword_mem_ops(self) -> T { self.sw + self.lw }
+/// Events counted by the `EventCounters` stark.
+#[must_use]
+pub fn lookup_for_event_counters() -> Vec<TableWithTypedOutput<EventCounterCtl<Column>>> {
+    let ops = CPU.inst.ops;
+    let constant = |kind: EventKind| ColumnWithTypedInput::constant(kind as i64);
+    // Loads and stores share their families, and the variant picks stores.
+    let memory = constant(EventKind::MemoryLoad)
+        + ops.variant * (EventKind::MemoryStore as i64 - EventKind::MemoryLoad as i64);
+    [
+        (constant(EventKind::BranchTaken), CPU.branch_taken),
+        (memory, ops.is_mem_op()),
+        (constant(EventKind::Ecall), ops.ecall),
+    ]
+    .into_iter()
+    .map(|(kind, filter)| CpuTable::new(EventCounterCtl { kind }, filter))
+    .collect()
 }
 
 /// Lookup into `Bitshift` stark.
@@ -448,7 +643,7 @@ pub fn lookup_for_program_rom() -> TableWithTypedOutput<ProgramRom<Column>> {
             // column in the list to ensure the correct functioning of 'reduce_with_powers'.
             inst_data: ColumnWithTypedInput::reduce_with_powers(
                 [
-                    ColumnWithTypedInput::ascending_sum(inst.ops),
+                    ColumnWithTypedInput::ascending_sum(inst.ops.code_terms()),
                     inst.is_op1_signed,
                     inst.is_op2_signed,
                     inst.rs1_selected,
@@ -463,6 +658,7 @@ pub fn lookup_for_program_rom() -> TableWithTypedOutput<ProgramRom<Column>> {
     )
 }
 
+#[cfg(feature = "poseidon2")]
 #[must_use]
 pub fn lookup_for_poseidon2_sponge() -> TableWithTypedOutput<Poseidon2SpongeCtl<Column>> {
     CpuTable::new(
@@ -471,6 +667,82 @@ pub fn lookup_for_poseidon2_sponge() -> TableWithTypedOutput<Poseidon2SpongeCtl<
     )
 }
 
+#[cfg(feature = "secp256k1")]
+#[must_use]
+pub fn lookup_for_secp256k1() -> TableWithTypedOutput<Secp256k1Ctl<Column>> {
+    CpuTable::new(
+        Secp256k1Ctl {
+            clk: CPU.clk,
+            is_double: CPU.ecall_selectors.is_secp256k1_double,
+        },
+        CPU.ecall_selectors.is_secp256k1_add + CPU.ecall_selectors.is_secp256k1_double,
+    )
+}
+
+/// The count of a `clz`, `ctz` or `cpop`, whose immediate says which one it
+/// is.
+#[cfg(feature = "zbb")]
+#[must_use]
+pub fn lookup_for_bitcount() -> TableWithTypedOutput<BitCountCtl<Column>> {
+    CpuTable::new(
+        BitCountCtl {
+            op: CPU.inst.imm_value,
+            value: CPU.op1_value,
+            count: CPU.dst_value,
+        },
+        CPU.inst.ops.bitcount,
+    )
+}
+
+#[must_use]
+pub fn lookup_for_event_emission() -> TableWithTypedOutput<EventEmissionCtl<Column>> {
+    CpuTable::new(
+        EventEmissionCtl { clk: CPU.clk },
+        CPU.ecall_selectors.is_emit_event,
+    )
+}
+
+#[cfg(feature = "u256")]
+#[must_use]
+pub fn lookup_for_u256() -> TableWithTypedOutput<U256Ctl<Column>> {
+    let ecalls = CPU.ecall_selectors;
+    CpuTable::new(
+        U256Ctl {
+            clk: CPU.clk,
+            is_mul: ecalls.is_u256_mul,
+            is_mulmod: ecalls.is_u256_mulmod,
+        },
+        ecalls.is_u256_add + ecalls.is_u256_mul + ecalls.is_u256_mulmod,
+    )
+}
+
+#[cfg(feature = "memcopy")]
+#[must_use]
+pub fn lookup_for_memcopy() -> TableWithTypedOutput<MemCopyCtl<Column>> {
+    let ecalls = CPU.ecall_selectors;
+    CpuTable::new(
+        MemCopyCtl {
+            clk: CPU.clk,
+            is_set: ecalls.is_memset,
+        },
+        ecalls.is_memcopy + ecalls.is_memset,
+    )
+}
+
+#[cfg(feature = "goldilocks")]
+#[must_use]
+pub fn lookup_for_goldilocks() -> TableWithTypedOutput<GoldilocksCtl<Column>> {
+    let ecalls = CPU.ecall_selectors;
+    CpuTable::new(
+        GoldilocksCtl {
+            clk: CPU.clk,
+            is_mul: ecalls.is_goldilocks_mul,
+            is_inverse: ecalls.is_goldilocks_inverse,
+        },
+        ecalls.is_goldilocks_add + ecalls.is_goldilocks_mul + ecalls.is_goldilocks_inverse,
+    )
+}
+
 #[must_use]
 pub fn register_looking() -> Vec<TableWithTypedOutput<RegisterCtl<Column>>> {
     let is_read = ColumnWithTypedInput::constant(1);
@@ -495,6 +767,16 @@ pub fn register_looking() -> Vec<TableWithTypedOutput<RegisterCtl<Column>>> {
             },
             CPU.is_running(),
         ),
+        // Multiply-add also reads its destination register.
+        CpuTable::new(
+            RegisterCtl {
+                clk: CPU.clk,
+                op: is_read,
+                addr: CPU.inst.rd_selected,
+                value: CPU.madd_addend,
+            },
+            CPU.inst.ops.madd,
+        ),
         CpuTable::new(
             RegisterCtl {
                 clk: CPU.clk,
@@ -515,7 +797,85 @@ pub fn lookup_for_skeleton() -> TableWithTypedOutput<CpuSkeletonCtl<Column>> {
             pc: CPU.inst.pc,
             new_pc: CPU.new_pc,
             will_halt: CPU.ecall_selectors.is_halt,
+            exit_code: CPU.op2_value_raw,
         },
         CPU.is_running(),
     )
 }
+
+/// Exit code of the proven execution, as a [`PublicValue`].
+///
+/// By convention guests halt with their exit code in `a1`, see
+/// `mozak_sdk::core::ecall::halt`. The `HALT` ecall reads `a1` as its second
+/// operand.
+///
+/// An execution that ran out of gas, see
+/// [`PublicInputs::out_of_gas`](crate::stark::mozak_stark::PublicInputs::out_of_gas),
+/// has no exit code. This is then the second operand of its last
+/// instruction, and meaningless.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExitCode(pub u32);
+
+impl<F: RichField> PublicValue<F> for ExitCode {
+    fn public_sub_table() -> PublicSubTable { make_exit_code_public() }
+
+    fn from_rows(rows: &[Vec<F>]) -> Result<Self> {
+        let [row] = rows else {
+            return Err(anyhow!("expected a single halt row, got {}", rows.len()));
+        };
+        let [exit_code] = row[..] else {
+            return Err(anyhow!("expected a single exit code, got {row:?}"));
+        };
+        Ok(Self(u32::try_from(exit_code.to_canonical_u64())?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+    use mozak_runner::instruction::Op;
+
+    use super::op_code;
+
+    /// The op code takes one 5 bit limb of the program ROM, and ops that the
+    /// CPU tells apart need different codes.
+    #[test]
+    fn op_codes_are_unique_and_fit_in_five_bits() {
+        let codes = [
+            Op::ADD,
+            Op::SUB,
+            Op::AND,
+            Op::ANDN,
+            Op::OR,
+            Op::ORN,
+            Op::XOR,
+            Op::XNOR,
+            Op::DIV,
+            Op::REM,
+            Op::MUL,
+            Op::MULH,
+            Op::SLT,
+            Op::RDCYCLE,
+            Op::BEQ,
+            Op::BNE,
+            Op::BLT,
+            Op::BGE,
+            Op::LB,
+            Op::SB,
+            Op::LH,
+            Op::SH,
+            Op::LW,
+            Op::SW,
+            Op::SLL,
+            Op::SRL,
+            Op::SRA,
+            Op::JALR,
+            Op::ECALL,
+            Op::MADD,
+            Op::CLZ,
+        ]
+        .map(op_code);
+        assert!(codes.iter().all(|code| (0..32).contains(code)), "{codes:?}");
+        assert!(codes.iter().all_unique(), "{codes:?}");
+    }
+}
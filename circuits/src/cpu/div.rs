@@ -114,15 +114,15 @@ pub(crate) fn constraints<'a, P: Copy>(
 
     // Last, we 'copy' our results:
     let dst = lv.dst_value;
-    cb.always((ops.div + ops.srl + ops.sra) * (dst - quotient_value));
-    cb.always(ops.rem * (dst - remainder_value));
+    cb.always((ops.div() + ops.srl + ops.sra) * (dst - quotient_value));
+    cb.always(ops.rem() * (dst - remainder_value));
 }
 
 #[cfg(test)]
 mod tests {
     use anyhow::Result;
     use mozak_runner::code;
-    use mozak_runner::instruction::{Args, Instruction, Op};
+    use mozak_runner::instruction::Instruction;
     use mozak_runner::test_utils::u32_extra;
     use proptest::prelude::{prop_assert_eq, ProptestConfig};
     use proptest::test_runner::TestCaseError;
@@ -133,49 +133,11 @@ mod tests {
     use crate::test_utils::{inv, ProveAndVerify, D, F};
 
     fn divu_remu_instructions(rd: u8) -> [Instruction; 2] {
-        [
-            Instruction {
-                op: Op::DIVU,
-                args: Args {
-                    rd,
-                    rs1: 1,
-                    rs2: 2,
-                    ..Args::default()
-                },
-            },
-            Instruction {
-                op: Op::REMU,
-                args: Args {
-                    rd,
-                    rs1: 1,
-                    rs2: 2,
-                    ..Args::default()
-                },
-            },
-        ]
+        [Instruction::divu(rd, 1, 2), Instruction::remu(rd, 1, 2)]
     }
 
     fn div_rem_instructions(rd: u8) -> [Instruction; 2] {
-        [
-            Instruction {
-                op: Op::DIV,
-                args: Args {
-                    rd,
-                    rs1: 1,
-                    rs2: 2,
-                    ..Args::default()
-                },
-            },
-            Instruction {
-                op: Op::REM,
-                args: Args {
-                    rd,
-                    rs1: 1,
-                    rs2: 2,
-                    ..Args::default()
-                },
-            },
-        ]
+        [Instruction::div(rd, 1, 2), Instruction::rem(rd, 1, 2)]
     }
 
     fn prove_divu<Stark: ProveAndVerify>(p: u32, q: u32, rd: u8) -> Result<(), TestCaseError> {
@@ -19,8 +19,32 @@ pub(crate) fn constraints<'a, P: Copy>(
     }
     cb.always(lv.inst.ops.ecall - ecalls.iter().sum::<Expr<'a, P>>());
     cb.always(lv.ecall_selectors.is_halt * (lv.op1_value - i64::from(ecall::HALT)));
+    cb.always(lv.ecall_selectors.is_emit_event * (lv.op1_value - i64::from(ecall::EMIT_EVENT)));
+    // The runner keeps the program break, and traps accesses above it. Like
+    // guard regions, the break is not constrained here, so proofs do not show
+    // that the guest stayed below it. Fresh heap memory is zero initialised
+    // either way.
+    cb.always(lv.ecall_selectors.is_sbrk * (lv.op1_value - i64::from(ecall::SBRK)));
+    prove_assert_constraints(lv, cb);
     storage_device_constraints(lv, cb);
+    output_tape_constraints(lv, cb);
     poseidon2_constraints(lv, cb);
+    secp256k1_constraints(lv, cb);
+    u256_constraints(lv, cb);
+    memcopy_constraints(lv, cb);
+    goldilocks_constraints(lv, cb);
+}
+
+/// A provable assertion passes its condition in `a1`, which ECALL reads as
+/// `op2_value`. The condition has to be exactly one, otherwise there is no
+/// valid trace.
+pub(crate) fn prove_assert_constraints<'a, P: Copy>(
+    lv: &CpuState<Expr<'a, P>>,
+    cb: &mut ConstraintBuilder<Expr<'a, P>>,
+) {
+    let is_prove_assert = lv.ecall_selectors.is_prove_assert;
+    cb.always(is_prove_assert * (lv.op1_value - i64::from(ecall::PROVE_ASSERT)));
+    cb.always(is_prove_assert * (1 - lv.op2_value));
 }
 
 pub(crate) fn storage_device_constraints<'a, P: Copy>(
@@ -30,6 +54,7 @@ pub(crate) fn storage_device_constraints<'a, P: Copy>(
     let ecalls = &lv.ecall_selectors;
     cb.always(ecalls.is_private_tape * (lv.op1_value - i64::from(ecall::PRIVATE_TAPE)));
     cb.always(ecalls.is_public_tape * (lv.op1_value - i64::from(ecall::PUBLIC_TAPE)));
+    cb.always(ecalls.is_public_tape_at * (lv.op1_value - i64::from(ecall::PUBLIC_TAPE_READ_AT)));
     cb.always(ecalls.is_call_tape * (lv.op1_value - i64::from(ecall::CALL_TAPE)));
     cb.always(ecalls.is_event_tape * (lv.op1_value - i64::from(ecall::EVENT_TAPE)));
     cb.always(
@@ -44,13 +69,205 @@ pub(crate) fn storage_device_constraints<'a, P: Copy>(
         lv.ecall_selectors.is_self_prog_id_tape
             * (lv.op1_value - i64::from(ecall::SELF_PROG_ID_TAPE)),
     );
+    cb.always(ecalls.is_hint_read * (lv.op1_value - i64::from(ecall::HINT_READ)));
+}
+
+// The ecalls below are backed by precompile tables behind cargo features. When
+// a feature is off its tables are not part of the proof, so nothing would
+// check what the ecall does, and the ecall must not be taken at all. The
+// output tape counts as such an ecall, since its digest takes the Poseidon2
+// tables.
+pub(crate) fn output_tape_constraints<'a, P: Copy>(
+    lv: &CpuState<Expr<'a, P>>,
+    cb: &mut ConstraintBuilder<Expr<'a, P>>,
+) {
+    #[cfg(feature = "poseidon2")]
+    cb.always(lv.ecall_selectors.is_io_write * (lv.op1_value - i64::from(ecall::IO_WRITE)));
+    #[cfg(not(feature = "poseidon2"))]
+    cb.always(lv.ecall_selectors.is_io_write);
 }
 
 pub(crate) fn poseidon2_constraints<'a, P: Copy>(
     lv: &CpuState<Expr<'a, P>>,
     cb: &mut ConstraintBuilder<Expr<'a, P>>,
 ) {
+    #[cfg(feature = "poseidon2")]
     cb.always(lv.ecall_selectors.is_poseidon2 * (lv.op1_value - i64::from(ecall::POSEIDON2)));
+    #[cfg(not(feature = "poseidon2"))]
+    cb.always(lv.ecall_selectors.is_poseidon2);
+}
+
+pub(crate) fn secp256k1_constraints<'a, P: Copy>(
+    lv: &CpuState<Expr<'a, P>>,
+    cb: &mut ConstraintBuilder<Expr<'a, P>>,
+) {
+    let ecalls = &lv.ecall_selectors;
+    #[cfg(feature = "secp256k1")]
+    {
+        cb.always(ecalls.is_secp256k1_add * (lv.op1_value - i64::from(ecall::SECP256K1_ADD)));
+        cb.always(ecalls.is_secp256k1_double * (lv.op1_value - i64::from(ecall::SECP256K1_DOUBLE)));
+    }
+    #[cfg(not(feature = "secp256k1"))]
+    {
+        cb.always(ecalls.is_secp256k1_add);
+        cb.always(ecalls.is_secp256k1_double);
+    }
+}
+
+pub(crate) fn u256_constraints<'a, P: Copy>(
+    lv: &CpuState<Expr<'a, P>>,
+    cb: &mut ConstraintBuilder<Expr<'a, P>>,
+) {
+    let ecalls = &lv.ecall_selectors;
+    #[cfg(feature = "u256")]
+    {
+        cb.always(ecalls.is_u256_add * (lv.op1_value - i64::from(ecall::U256_ADD)));
+        cb.always(ecalls.is_u256_mul * (lv.op1_value - i64::from(ecall::U256_MUL)));
+        cb.always(ecalls.is_u256_mulmod * (lv.op1_value - i64::from(ecall::U256_MULMOD)));
+    }
+    #[cfg(not(feature = "u256"))]
+    {
+        cb.always(ecalls.is_u256_add);
+        cb.always(ecalls.is_u256_mul);
+        cb.always(ecalls.is_u256_mulmod);
+    }
+}
+
+pub(crate) fn memcopy_constraints<'a, P: Copy>(
+    lv: &CpuState<Expr<'a, P>>,
+    cb: &mut ConstraintBuilder<Expr<'a, P>>,
+) {
+    let ecalls = &lv.ecall_selectors;
+    #[cfg(feature = "memcopy")]
+    {
+        cb.always(ecalls.is_memcopy * (lv.op1_value - i64::from(ecall::MEMCOPY)));
+        cb.always(ecalls.is_memset * (lv.op1_value - i64::from(ecall::MEMSET)));
+    }
+    #[cfg(not(feature = "memcopy"))]
+    {
+        cb.always(ecalls.is_memcopy);
+        cb.always(ecalls.is_memset);
+    }
+}
+
+pub(crate) fn goldilocks_constraints<'a, P: Copy>(
+    lv: &CpuState<Expr<'a, P>>,
+    cb: &mut ConstraintBuilder<Expr<'a, P>>,
+) {
+    let ecalls = &lv.ecall_selectors;
+    #[cfg(feature = "goldilocks")]
+    {
+        cb.always(ecalls.is_goldilocks_add * (lv.op1_value - i64::from(ecall::GOLDILOCKS_ADD)));
+        cb.always(ecalls.is_goldilocks_mul * (lv.op1_value - i64::from(ecall::GOLDILOCKS_MUL)));
+        cb.always(
+            ecalls.is_goldilocks_inverse * (lv.op1_value - i64::from(ecall::GOLDILOCKS_INVERSE)),
+        );
+    }
+    #[cfg(not(feature = "goldilocks"))]
+    {
+        cb.always(ecalls.is_goldilocks_add);
+        cb.always(ecalls.is_goldilocks_mul);
+        cb.always(ecalls.is_goldilocks_inverse);
+    }
 }
 
 // We are already testing ecall halt with our coda of every `code::execute`.
+
+#[cfg(test)]
+mod tests {
+    use mozak_runner::code;
+    use mozak_runner::decode::ECALL;
+    use mozak_sdk::core::ecall;
+    use mozak_sdk::core::reg_abi::{REG_A0, REG_A1};
+    use plonky2::field::types::Field;
+    use plonky2::util::timing::TimingTree;
+    use starky::config::StarkConfig;
+
+    use crate::cpu::columns::{CpuState, ExitCode};
+    use crate::cpu::generation::generate_cpu_trace;
+    use crate::cpu::stark::CpuStark;
+    use crate::cross_table_lookup::ctl_utils::{check_single_ctl, unmatched_tuples};
+    use crate::generation::{debug_single_trace, generate_traces};
+    use crate::stark::mozak_stark::{
+        CpuToSkeletonTable, Lookups, MozakStark, PublicInputs, TableKind,
+    };
+    use crate::stark::prover::prove;
+    use crate::stark::utils::trace_rows_to_poly_values;
+    use crate::stark::verifier::verify_proof;
+    use crate::test_utils::{ProveAndVerify, C, D, F};
+    use crate::trace_source::Execution;
+    use crate::utils::from_u32;
+
+    #[test]
+    fn prove_exit_code() -> anyhow::Result<()> {
+        let (program, record) = code::execute([], &[], &[(REG_A1, 7)]);
+        let stark = MozakStark::<F, D>::default();
+        let config = StarkConfig::standard_fast_config();
+        let public_inputs = PublicInputs::new(from_u32(program.entry_point));
+        let all_proof = prove::<F, C, D>(
+            &program,
+            &record,
+            &stark,
+            &config,
+            public_inputs,
+            &mut TimingTree::default(),
+        )?;
+        assert_eq!(all_proof.public_value::<ExitCode>(&stark)?, ExitCode(7));
+        verify_proof(&stark, all_proof.clone(), &config)?;
+
+        // The exit code is tied to `a1` at halt, so it can't be changed.
+        let mut forged_proof = all_proof;
+        forged_proof.public_sub_table_values[TableKind::Cpu][0][0][0] = F::from_canonical_u32(0);
+        assert!(verify_proof(&stark, forged_proof, &config).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn prove_assert_holds() {
+        let (program, record) =
+            code::execute([ECALL], &[], &[(REG_A0, ecall::PROVE_ASSERT), (REG_A1, 1)]);
+        CpuStark::prove_and_verify(&program, &record).unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "debug_api_has_constraint_failed")]
+    fn prove_assert_fails() {
+        let (_program, record) =
+            code::execute([ECALL], &[], &[(REG_A0, ecall::PROVE_ASSERT), (REG_A1, 0)]);
+        let trace = trace_rows_to_poly_values(generate_cpu_trace(&record));
+        debug_single_trace::<F, D, _>(&CpuStark::<F, D>::default(), &trace, &[]);
+    }
+
+    /// An instruction after the halt has no running row in the skeleton to
+    /// match, so the lookup from the CPU into the skeleton fails.
+    #[test]
+    fn instruction_after_halt_fails() {
+        let (program, record) = code::execute([], &[], &[]);
+        let mut traces = generate_traces::<F, D>(
+            &Execution::new(&program, &record),
+            &mut TimingTree::default(),
+        );
+        let mut rows = generate_cpu_trace(&record);
+        let halt = *rows
+            .iter()
+            .find(|row| row.ecall_selectors.is_halt.is_one())
+            .unwrap();
+        let padding = rows
+            .iter_mut()
+            .find(|row| row.is_running().is_zero())
+            .unwrap();
+        *padding = CpuState {
+            clk: halt.clk + F::ONE,
+            ..halt
+        };
+        traces[TableKind::Cpu] = trace_rows_to_poly_values(rows);
+        let lookups = CpuToSkeletonTable::lookups();
+        assert!(check_single_ctl(&traces, &lookups).is_err());
+
+        // The diagnostics point at the extra CPU row.
+        let unmatched = unmatched_tuples(&traces, &lookups);
+        assert!(unmatched
+            .iter()
+            .any(|tuple| tuple.looking == [(TableKind::Cpu, F::ONE)] && tuple.looked.is_empty()));
+    }
+}
@@ -1,12 +1,20 @@
 use expr::{Evaluator, ExprBuilder};
 use itertools::Itertools;
-use log::debug;
+#[cfg(feature = "goldilocks")]
+use mozak_runner::goldilocks;
 use mozak_runner::instruction::{Instruction, Op};
+#[cfg(feature = "memcopy")]
+use mozak_runner::memcopy;
 use mozak_runner::state::{Aux, State, StorageDeviceEntry, StorageDeviceOpcode};
+#[cfg(feature = "u256")]
+use mozak_runner::u256::Operation;
 use mozak_runner::vm::{ExecutionRecord, Row};
 use mozak_sdk::core::ecall;
 use mozak_sdk::core::reg_abi::REG_A0;
 use plonky2::hash::hash_types::RichField;
+#[allow(clippy::wildcard_imports)]
+use plonky2_maybe_rayon::*;
+use tracing::debug;
 
 use crate::bitshift::columns::Bitshift;
 use crate::cpu::columns as cpu_cols;
@@ -32,129 +40,229 @@ pub fn pad_trace<F: RichField>(mut trace: Vec<CpuState<F>>) -> Vec<CpuState<F>>
     trace
 }
 
+/// Generates the multiplicities of the instructions of the program ROM and
+/// of the library ROM, see [`Program::link`](mozak_runner::elf::Program::link).
+///
+/// Each execution counts towards the first ROM row of its `pc` only, so
+/// instructions that both ROMs hold are not counted twice.
 #[must_use]
 pub fn generate_program_mult_trace<F: RichField>(
     skeleton: &[CpuSkeleton<F>],
     program_rom: &[ProgramRom<F>],
-) -> Vec<ProgramMult<F>> {
+    library_rom: &[ProgramRom<F>],
+) -> (Vec<ProgramMult<F>>, Vec<ProgramMult<F>>) {
     let mut counts = skeleton
         .iter()
         .filter(|row| row.is_running.is_nonzero())
         .map(|row| row.pc)
         .counts();
-    program_rom
-        .iter()
-        .map(|&inst| ProgramMult {
-            // We use `remove` instead of a plain `get` to deal with duplicates (from padding) in
-            // the ROM.
-            mult_in_cpu: F::from_canonical_usize(counts.remove(&inst.pc).unwrap_or_default()),
-            rom_row: inst,
-        })
-        .collect()
+    let mut mults = |rom: &[ProgramRom<F>]| {
+        rom.iter()
+            .map(|&inst| ProgramMult {
+                // We use `remove` instead of a plain `get` to deal with duplicates (from padding)
+                // in the ROM.
+                mult_in_cpu: F::from_canonical_usize(counts.remove(&inst.pc).unwrap_or_default()),
+                rom_row: inst,
+            })
+            .collect()
+    };
+    (mults(program_rom), mults(library_rom))
 }
 
 /// Converting each row of the `record` to a row represented by [`CpuState`]
 pub fn generate_cpu_trace<F: RichField>(record: &ExecutionRecord<F>) -> Vec<CpuState<F>> {
     debug!("Starting CPU Trace Generation");
-    let mut trace: Vec<CpuState<F>> = vec![];
+    // Rows are independent of each other, and `collect` keeps them in order.
+    let trace: Vec<CpuState<F>> = record
+        .executed
+        .par_iter()
+        .filter_map(generate_cpu_row)
+        .collect();
 
-    let default_io_entry = StorageDeviceEntry::default();
-    for Row {
+    tracing::trace!("trace {:?}", trace);
+
+    pad_trace(trace)
+}
+
+/// Generates the CPU row for a single step, or `None` for steps that are
+/// handled by their own tables.
+fn generate_cpu_row<F: RichField>(
+    Row {
         state,
         instruction,
         aux,
-    } in &record.executed
+    }: &Row<F>,
+) -> Option<CpuState<F>> {
+    let inst = instruction;
+    let default_io_entry = StorageDeviceEntry::default();
+    let io = aux
+        .storage_device_entry
+        .as_ref()
+        .unwrap_or(&default_io_entry);
+    // Skip instruction handled by their own tables.
+    // TODO: refactor, so we don't repeat logic.
     {
-        let inst = instruction;
-        let io = aux
-            .storage_device_entry
-            .as_ref()
-            .unwrap_or(&default_io_entry);
-        // Skip instruction handled by their own tables.
-        // TODO: refactor, so we don't repeat logic.
-        {
-            if let Op::ADD = inst.op {
-                continue;
-            }
-
-            let op1_value = state.get_register_value(inst.args.rs1);
-            let op2_value = state.get_register_value(inst.args.rs2);
-            if op1_value < op2_value && Op::BLTU == inst.op {
-                continue;
-            }
+        if let Op::ADD = inst.op {
+            return None;
         }
-        let mut row = CpuState {
-            clk: F::from_noncanonical_u64(state.clk),
-            new_pc: F::from_canonical_u32(aux.new_pc),
-            inst: cpu_cols::Instruction::from((state.get_pc(), *inst)).map(from_u32),
-            op1_value: from_u32(aux.op1),
-            op2_value_raw: from_u32(aux.op2_raw),
-            op2_value: from_u32(aux.op2),
-            // This seems reasonable-ish, but it's also suspicious?
-            // It seems too simple.
-            op2_value_overflowing: from_u32::<F>(state.get_register_value(inst.args.rs2))
-                + from_u32(inst.args.imm),
-            dst_value: from_u32(aux.dst_val),
-            // Valid defaults for the powers-of-two gadget.
-            // To be overridden by users of the gadget.
-            // TODO(Matthias): find a way to make either compiler or runtime complain
-            // if we have two (conflicting) users in the same row.
-            bitshift: Bitshift::from(0).map(F::from_canonical_u32),
-            xor: generate_xor_row(inst, state),
-            mem_addr: F::from_canonical_u32(aux.mem.unwrap_or_default().addr),
-            mem_value_raw: from_u32(aux.mem.unwrap_or_default().raw_value),
-            io_addr: F::from_canonical_u32(io.addr),
-            io_size: F::from_canonical_usize(io.data.len()),
-            ecall_selectors: EcallSelectors {
-                is_poseidon2: F::from_bool(aux.poseidon2.is_some()),
-                is_private_tape: F::from_bool(matches!(
-                    (inst.op, io.op),
-                    (Op::ECALL, StorageDeviceOpcode::StorePrivate)
-                )),
-                is_public_tape: F::from_bool(matches!(
-                    (inst.op, io.op),
-                    (Op::ECALL, StorageDeviceOpcode::StorePublic)
-                )),
-                is_call_tape: F::from_bool(matches!(
-                    (inst.op, io.op),
-                    (Op::ECALL, StorageDeviceOpcode::StoreCallTape)
-                )),
-                is_event_tape: F::from_bool(matches!(
-                    (inst.op, io.op),
-                    (Op::ECALL, StorageDeviceOpcode::StoreEventTape)
-                )),
-                is_events_commitment_tape: F::from_bool(matches!(
-                    (inst.op, io.op),
-                    (Op::ECALL, StorageDeviceOpcode::StoreEventsCommitmentTape)
-                )),
-                is_cast_list_commitment_tape: F::from_bool(matches!(
-                    (inst.op, io.op),
-                    (Op::ECALL, StorageDeviceOpcode::StoreCastListCommitmentTape)
-                )),
-                is_self_prog_id_tape: F::from_bool(matches!(
-                    (inst.op, io.op),
-                    (Op::ECALL, StorageDeviceOpcode::StoreSelfProgIdTape)
-                )),
-                is_halt: F::from_bool(matches!(
-                    (inst.op, state.registers[usize::from(REG_A0)]),
-                    (Op::ECALL, ecall::HALT)
-                )),
-            },
-            ..CpuState::default()
-        };
 
-        generate_shift_row(&mut row, aux);
-        generate_mul_row(&mut row, aux);
-        generate_div_row(&mut row, inst, aux);
-        operands_sign_handling(&mut row, aux);
-        memory_sign_handling(&mut row, inst, aux);
-        generate_conditional_branch_row(&mut row);
-        trace.push(row);
+        let op1_value = state.get_register_value(inst.args.rs1);
+        let op2_value = state.get_register_value(inst.args.rs2);
+        if op1_value < op2_value && Op::BLTU == inst.op {
+            return None;
+        }
     }
+    #[cfg(feature = "u256")]
+    let u256_operation = aux.u256.as_ref().map(|entry| entry.operation);
+    #[cfg(feature = "memcopy")]
+    let memcopy_operation = aux.memcopy.as_ref().map(|entry| entry.operation);
+    #[cfg(feature = "goldilocks")]
+    let goldilocks_operation = aux.goldilocks.as_ref().map(|entry| entry.operation);
+    let mut row = CpuState {
+        clk: F::from_noncanonical_u64(state.clk),
+        new_pc: F::from_canonical_u32(aux.new_pc),
+        inst: cpu_cols::Instruction::from((state.get_pc(), *inst)).map(from_u32),
+        op1_value: from_u32(aux.op1),
+        op2_value_raw: from_u32(aux.op2_raw),
+        op2_value: from_u32(aux.op2),
+        // This seems reasonable-ish, but it's also suspicious?
+        // It seems too simple.
+        op2_value_overflowing: from_u32::<F>(state.get_register_value(inst.args.rs2))
+            + from_u32(inst.args.imm),
+        dst_value: from_u32(aux.dst_val),
+        // Valid defaults for the powers-of-two gadget.
+        // To be overridden by users of the gadget.
+        // TODO(Matthias): find a way to make either compiler or runtime complain
+        // if we have two (conflicting) users in the same row.
+        bitshift: Bitshift::from(0).map(F::from_canonical_u32),
+        xor: generate_xor_row(inst, state),
+        mem_addr: F::from_canonical_u32(aux.mem.unwrap_or_default().addr),
+        mem_value_raw: from_u32(aux.mem.unwrap_or_default().raw_value),
+        io_addr: F::from_canonical_u32(io.addr),
+        io_size: F::from_canonical_usize(io.data.len()),
+        ecall_selectors: EcallSelectors {
+            #[cfg(feature = "poseidon2")]
+            is_poseidon2: F::from_bool(aux.poseidon2.is_some()),
+            #[cfg(not(feature = "poseidon2"))]
+            is_poseidon2: F::ZERO,
+            is_private_tape: F::from_bool(matches!(
+                (inst.op, io.op),
+                (Op::ECALL, StorageDeviceOpcode::StorePrivate)
+            )),
+            is_public_tape: F::from_bool(matches!(
+                (inst.op, io.op, io.is_read_at),
+                (Op::ECALL, StorageDeviceOpcode::StorePublic, false)
+            )),
+            is_public_tape_at: F::from_bool(matches!(
+                (inst.op, io.op, io.is_read_at),
+                (Op::ECALL, StorageDeviceOpcode::StorePublic, true)
+            )),
+            is_call_tape: F::from_bool(matches!(
+                (inst.op, io.op),
+                (Op::ECALL, StorageDeviceOpcode::StoreCallTape)
+            )),
+            is_event_tape: F::from_bool(matches!(
+                (inst.op, io.op),
+                (Op::ECALL, StorageDeviceOpcode::StoreEventTape)
+            )),
+            is_events_commitment_tape: F::from_bool(matches!(
+                (inst.op, io.op),
+                (Op::ECALL, StorageDeviceOpcode::StoreEventsCommitmentTape)
+            )),
+            is_cast_list_commitment_tape: F::from_bool(matches!(
+                (inst.op, io.op),
+                (Op::ECALL, StorageDeviceOpcode::StoreCastListCommitmentTape)
+            )),
+            is_self_prog_id_tape: F::from_bool(matches!(
+                (inst.op, io.op),
+                (Op::ECALL, StorageDeviceOpcode::StoreSelfProgIdTape)
+            )),
+            is_halt: F::from_bool(matches!(
+                (inst.op, state.registers[usize::from(REG_A0)]),
+                (Op::ECALL, ecall::HALT)
+            )),
+            is_prove_assert: F::from_bool(matches!(
+                (inst.op, state.registers[usize::from(REG_A0)]),
+                (Op::ECALL, ecall::PROVE_ASSERT)
+            )),
+            #[cfg(feature = "secp256k1")]
+            is_secp256k1_add: F::from_bool(
+                aux.secp256k1.as_ref().is_some_and(|entry| !entry.is_double),
+            ),
+            #[cfg(feature = "secp256k1")]
+            is_secp256k1_double: F::from_bool(
+                aux.secp256k1.as_ref().is_some_and(|entry| entry.is_double),
+            ),
+            #[cfg(not(feature = "secp256k1"))]
+            is_secp256k1_add: F::ZERO,
+            #[cfg(not(feature = "secp256k1"))]
+            is_secp256k1_double: F::ZERO,
+            #[cfg(feature = "u256")]
+            is_u256_add: F::from_bool(u256_operation == Some(Operation::Add)),
+            #[cfg(feature = "u256")]
+            is_u256_mul: F::from_bool(u256_operation == Some(Operation::Mul)),
+            #[cfg(feature = "u256")]
+            is_u256_mulmod: F::from_bool(u256_operation == Some(Operation::MulMod)),
+            #[cfg(not(feature = "u256"))]
+            is_u256_add: F::ZERO,
+            #[cfg(not(feature = "u256"))]
+            is_u256_mul: F::ZERO,
+            #[cfg(not(feature = "u256"))]
+            is_u256_mulmod: F::ZERO,
+            #[cfg(feature = "memcopy")]
+            is_memcopy: F::from_bool(memcopy_operation == Some(memcopy::Operation::Copy)),
+            #[cfg(feature = "memcopy")]
+            is_memset: F::from_bool(memcopy_operation == Some(memcopy::Operation::Set)),
+            #[cfg(not(feature = "memcopy"))]
+            is_memcopy: F::ZERO,
+            #[cfg(not(feature = "memcopy"))]
+            is_memset: F::ZERO,
+            #[cfg(feature = "goldilocks")]
+            is_goldilocks_add: F::from_bool(
+                goldilocks_operation == Some(goldilocks::Operation::Add),
+            ),
+            #[cfg(feature = "goldilocks")]
+            is_goldilocks_mul: F::from_bool(
+                goldilocks_operation == Some(goldilocks::Operation::Mul),
+            ),
+            #[cfg(feature = "goldilocks")]
+            is_goldilocks_inverse: F::from_bool(
+                goldilocks_operation == Some(goldilocks::Operation::Inverse),
+            ),
+            #[cfg(not(feature = "goldilocks"))]
+            is_goldilocks_add: F::ZERO,
+            #[cfg(not(feature = "goldilocks"))]
+            is_goldilocks_mul: F::ZERO,
+            #[cfg(not(feature = "goldilocks"))]
+            is_goldilocks_inverse: F::ZERO,
+            is_emit_event: F::from_bool(aux.event.is_some()),
+            is_sbrk: F::from_bool(matches!(
+                (inst.op, state.registers[usize::from(REG_A0)]),
+                (Op::ECALL, ecall::SBRK)
+            )),
+            #[cfg(feature = "poseidon2")]
+            is_io_write: F::from_bool(matches!(
+                (inst.op, io.op),
+                (Op::ECALL, StorageDeviceOpcode::LoadOutputTape)
+            )),
+            #[cfg(not(feature = "poseidon2"))]
+            is_io_write: F::ZERO,
+            is_hint_read: F::from_bool(matches!(
+                (inst.op, io.op),
+                (Op::ECALL, StorageDeviceOpcode::StoreHint)
+            )),
+        },
+        ..CpuState::default()
+    };
 
-    log::trace!("trace {:?}", trace);
-
-    pad_trace(trace)
+    generate_shift_row(&mut row, aux);
+    generate_mul_row(&mut row, aux);
+    generate_madd_row(&mut row, inst, state);
+    generate_div_row(&mut row, inst, aux);
+    operands_sign_handling(&mut row, aux);
+    memory_sign_handling(&mut row, inst, aux);
+    generate_conditional_branch_row(&mut row);
+    Some(row)
 }
 
 /// This is a wrapper to make the Expr mechanics work directly with a Field.
@@ -170,6 +278,13 @@ fn generate_conditional_branch_row<F: RichField>(row: &mut CpuState<F>) {
     let signed_diff = signed_diff(row);
     row.cmp_diff_inv = signed_diff.try_inverse().unwrap_or_default();
     row.normalised_diff = F::from_bool(signed_diff.is_nonzero());
+
+    let ops = row.inst.ops;
+    let (lt, normalised_diff) = (row.less_than, row.normalised_diff);
+    row.branch_taken = ops.blt() * lt
+        + ops.bge() * (F::ONE - lt)
+        + ops.beq() * (F::ONE - normalised_diff)
+        + ops.bne() * normalised_diff;
 }
 
 /// Generates a bitshift row on a shift operation. This is used in the bitshift
@@ -250,6 +365,16 @@ fn generate_mul_row<F: RichField>(row: &mut CpuState<F>, aux: &Aux<F>) {
         .unwrap_or_default();
 }
 
+fn generate_madd_row<F: RichField>(row: &mut CpuState<F>, inst: &Instruction, state: &State<F>) {
+    if inst.op != Op::MADD {
+        return;
+    }
+    let addend = state.get_register_value(inst.args.rd);
+    let low_limb = u32::try_from(row.product_low_limb.to_canonical_u64()).unwrap();
+    row.madd_addend = from_u32(addend);
+    row.madd_carry = F::from_bool(low_limb.checked_add(addend).is_none());
+}
+
 #[allow(clippy::cast_possible_truncation)]
 #[allow(clippy::cast_sign_loss)]
 fn generate_div_row<F: RichField>(row: &mut CpuState<F>, inst: &Instruction, aux: &Aux<F>) {
@@ -315,15 +440,19 @@ fn operands_sign_handling<F: RichField>(row: &mut CpuState<F>, aux: &Aux<F>) {
 }
 
 fn generate_xor_row<F: RichField>(inst: &Instruction, state: &State<F>) -> XorView<F> {
+    let op2 = state
+        .get_register_value(inst.args.rs2)
+        .wrapping_add(inst.args.imm);
     let a = match inst.op {
-        Op::AND | Op::OR | Op::XOR | Op::SB | Op::SH => state.get_register_value(inst.args.rs1),
+        Op::AND | Op::OR | Op::XOR | Op::ANDN | Op::ORN | Op::XNOR | Op::SB | Op::SH =>
+            state.get_register_value(inst.args.rs1),
         Op::SRL | Op::SLL | Op::SRA => 0b1_1111,
         _ => 0,
     };
     let b = match inst.op {
-        Op::AND | Op::OR | Op::XOR | Op::SRL | Op::SLL | Op::SRA => state
-            .get_register_value(inst.args.rs2)
-            .wrapping_add(inst.args.imm),
+        Op::AND | Op::OR | Op::XOR | Op::XNOR | Op::SRL | Op::SLL | Op::SRA => op2,
+        // The gadgets for AND and OR take the complement directly.
+        Op::ANDN | Op::ORN => !op2,
         Op::SB => 0x0000_00FF,
         Op::SH => 0x0000_FFFF,
         _ => 0,
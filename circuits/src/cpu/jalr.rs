@@ -45,76 +45,30 @@ mod tests {
 
     #[test]
     fn prove_jalr_goto_no_rs1() {
-        let (program, record) = code::execute(
-            [Instruction {
-                op: Op::JALR,
-                args: Args {
-                    rd: 0,
-                    rs1: 0,
-                    imm: 4,
-                    ..Args::default()
-                },
-            }],
-            &[],
-            &[],
-        );
+        let (program, record) = code::execute([Instruction::jal(0, 4).unwrap()], &[], &[]);
         assert_eq!(record.last_state.get_pc(), 8);
         CpuStark::prove_and_verify(&program, &record).unwrap();
     }
 
     #[test]
     fn prove_jalr_goto_rs1_zero() {
-        let (program, record) = code::execute(
-            [Instruction {
-                op: Op::JALR,
-                args: Args {
-                    rd: 0,
-                    rs1: 1,
-                    imm: 4,
-                    ..Args::default()
-                },
-            }],
-            &[],
-            &[(0x1, 0)],
-        );
+        let (program, record) =
+            code::execute([Instruction::jalr(0, 1, 4).unwrap()], &[], &[(0x1, 0)]);
         assert_eq!(record.last_state.get_pc(), 8);
         CpuStark::prove_and_verify(&program, &record).unwrap();
     }
 
     #[test]
     fn prove_jalr_goto_imm_zero_rs1_not_zero() {
-        let (program, record) = code::execute(
-            [Instruction {
-                op: Op::JALR,
-                args: Args {
-                    rd: 0,
-                    rs1: 1,
-                    imm: 0,
-                    ..Args::default()
-                },
-            }],
-            &[],
-            &[(0x1, 4)],
-        );
+        let (program, record) =
+            code::execute([Instruction::jalr(0, 1, 0).unwrap()], &[], &[(0x1, 4)]);
         assert_eq!(record.last_state.get_pc(), 8);
         CpuStark::prove_and_verify(&program, &record).unwrap();
     }
 
     #[test]
     fn prove_jalr() {
-        let (program, record) = code::execute(
-            [Instruction {
-                op: Op::JALR,
-                args: Args {
-                    rd: 1,
-                    rs1: 0,
-                    imm: 4,
-                    ..Args::default()
-                },
-            }],
-            &[],
-            &[(0x1, 0)],
-        );
+        let (program, record) = code::execute([Instruction::jal(1, 4).unwrap()], &[], &[(0x1, 0)]);
         assert_eq!(record.last_state.get_pc(), 8);
         CpuStark::prove_and_verify(&program, &record).unwrap();
     }
@@ -164,25 +118,17 @@ mod tests {
             let jump_target: u32 = 8;
             let imm = jump_target.wrapping_sub(rs1_val);
             let (program, record) = code::execute(
-                [Instruction {
-                    op: Op::JALR,
-                    args: Args {
-                        rd,
-                        rs1,
-                        imm,
-                        ..Args::default()
-                    },
-                },
+                // The offset is a whole word, which no `jalr` encodes, so
+                // `Instruction::jalr` would reject it.
+                [Instruction::new(Op::JALR, Args {
+                    rd,
+                    rs1,
+                    imm,
+                    ..Args::default()
+                }),
                 // We are jumping past this instruction, so it should not be executed.
                 // So we should not overwrite register `rd` with `sentinel`.
-                Instruction {
-                    op: Op::ADD,
-                    args: Args {
-                        rd,
-                        imm: sentinel,
-                        ..Args::default()
-                    },
-                }],
+                Instruction::li(rd, sentinel)],
                 &[],
                 &[(rs1, rs1_val)],
             );
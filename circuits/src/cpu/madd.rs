@@ -0,0 +1,60 @@
+//! This module implements the constraints for MADD, our custom-0 instruction
+//! `rd = rs1 * rs2 + rd`.
+//!
+//! The product comes from the multiplication gadget in
+//! [`mul`](super::mul), and the old value of `rd` from an extra register
+//! read.
+
+use expr::Expr;
+
+use super::columns::CpuState;
+use crate::expr::ConstraintBuilder;
+
+pub(crate) fn constraints<'a, P: Copy>(
+    lv: &CpuState<Expr<'a, P>>,
+    cb: &mut ConstraintBuilder<Expr<'a, P>>,
+) {
+    cb.always(lv.madd_carry.is_binary());
+
+    // Both the low limb of the product and the addend are u32, so their sum
+    // is below 2^33, and the range checked destination together with a
+    // binary carry determine it uniquely.
+    cb.always(
+        lv.inst.ops.madd
+            * (lv.dst_value + lv.madd_carry * (1 << 32) - lv.product_low_limb - lv.madd_addend),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use mozak_runner::code;
+    use mozak_runner::instruction::Instruction;
+    use mozak_runner::test_utils::u32_extra;
+    use proptest::prelude::ProptestConfig;
+    use proptest::proptest;
+
+    use crate::cpu::stark::CpuStark;
+    use crate::stark::mozak_stark::MozakStark;
+    use crate::test_utils::{ProveAndVerify, D, F};
+
+    fn prove_madd<Stark: ProveAndVerify>(a: u32, b: u32, c: u32) {
+        let (program, record) =
+            code::execute([Instruction::madd(5, 6, 7)], &[], &[(5, c), (6, a), (7, b)]);
+        assert_eq!(
+            record.last_state.get_register_value(5),
+            a.wrapping_mul(b).wrapping_add(c)
+        );
+        Stark::prove_and_verify(&program, &record).unwrap();
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(4))]
+        #[test]
+        fn prove_madd_cpu(a in u32_extra(), b in u32_extra(), c in u32_extra()) {
+            prove_madd::<CpuStark<F, D>>(a, b, c);
+        }
+    }
+
+    #[test]
+    fn prove_madd_mozak() { prove_madd::<MozakStark<F, D>>(0xFFFF_FFFF, 0xFFFF_FFFF, 0xFFFF_FFFF); }
+}
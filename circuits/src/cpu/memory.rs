@@ -19,22 +19,22 @@ pub(crate) fn signed_constraints<'a, P: Copy>(
     // When dst is not signed as per instruction semantics, dst_sign_bit must be 0.
     cb.always((1 - lv.inst.is_dst_signed) * lv.dst_sign_bit);
 
+    let ops = &lv.inst.ops;
+
     // Ensure `dst_value` is `0xFFFF_FF00` greater than
     // `mem_access_raw` in case `dst_sign_bit` is set
-    cb.always(lv.inst.ops.lb * (lv.dst_value - (lv.mem_value_raw + lv.dst_sign_bit * 0xFFFF_FF00)));
+    cb.always(ops.lb() * (lv.dst_value - (lv.mem_value_raw + lv.dst_sign_bit * 0xFFFF_FF00)));
 
     // Ensure `dst_value` is `0xFFFF_0000` greater than
     // `mem_access_raw` in case `dst_sign_bit` is set
-    cb.always(lv.inst.ops.lh * (lv.dst_value - (lv.mem_value_raw + lv.dst_sign_bit * 0xFFFF_0000)));
+    cb.always(ops.lh() * (lv.dst_value - (lv.mem_value_raw + lv.dst_sign_bit * 0xFFFF_0000)));
 
     let and_gadget = and_gadget(&lv.xor);
     // SB/SH uses only least significant 8/16 bit from RS1 register.
-    cb.always((lv.inst.ops.sb + lv.inst.ops.sh) * (and_gadget.input_a - lv.op1_value));
-    cb.always(lv.inst.ops.sb * (and_gadget.input_b - 0x0000_00FF));
-    cb.always(lv.inst.ops.sh * (and_gadget.input_b - 0x0000_FFFF));
-    cb.always(
-        (lv.inst.ops.sb + lv.inst.ops.sh) * (and_gadget.doubled_output - 2 * lv.mem_value_raw),
-    );
+    cb.always((ops.sb() + ops.sh()) * (and_gadget.input_a - lv.op1_value));
+    cb.always(ops.sb() * (and_gadget.input_b - 0x0000_00FF));
+    cb.always(ops.sh() * (and_gadget.input_b - 0x0000_FFFF));
+    cb.always((ops.sb() + ops.sh()) * (and_gadget.doubled_output - 2 * lv.mem_value_raw));
 }
 
 pub(crate) fn constraints<'a, P: Copy>(
@@ -60,18 +60,8 @@ mod tests {
     use crate::test_utils::{ProveAndVerify, D, F};
 
     fn prove_sb<Stark: ProveAndVerify>(a: u32, b: u32) {
-        let (program, record) = code::execute(
-            [Instruction {
-                op: Op::SB,
-                args: Args {
-                    rs1: 6,
-                    rs2: 7,
-                    ..Args::default()
-                },
-            }],
-            &[(b, 0)],
-            &[(6, a), (7, b)],
-        );
+        let (program, record) =
+            code::execute([Instruction::sb(6, 7, 0)], &[(b, 0)], &[(6, a), (7, b)]);
 
         Stark::prove_and_verify(&program, &record).unwrap();
     }
@@ -111,25 +101,7 @@ mod tests {
 
     fn prove_sb_lbu<Stark: ProveAndVerify>(offset: u32, imm: u32, content: u32) {
         let (program, record) = code::execute(
-            [
-                Instruction {
-                    op: Op::SB,
-                    args: Args {
-                        rs1: 1,
-                        rs2: 2,
-                        imm,
-                        ..Args::default()
-                    },
-                },
-                Instruction {
-                    op: Op::LBU,
-                    args: Args {
-                        rs2: 2,
-                        imm,
-                        ..Args::default()
-                    },
-                },
-            ],
+            [Instruction::sb(1, 2, imm), Instruction::lbu(0, 2, imm)],
             &[(imm.wrapping_add(offset), 0)],
             &[(1, content), (2, offset)],
         );
@@ -139,25 +111,7 @@ mod tests {
 
     fn prove_sb_lb<Stark: ProveAndVerify>(offset: u32, imm: u32, content: u32) {
         let (program, record) = code::execute(
-            [
-                Instruction {
-                    op: Op::SB,
-                    args: Args {
-                        rs1: 1,
-                        rs2: 2,
-                        imm,
-                        ..Args::default()
-                    },
-                },
-                Instruction {
-                    op: Op::LB,
-                    args: Args {
-                        rs2: 2,
-                        imm,
-                        ..Args::default()
-                    },
-                },
-            ],
+            [Instruction::sb(1, 2, imm), Instruction::lb(0, 2, imm)],
             &[(imm.wrapping_add(offset), 0)],
             &[(1, content), (2, offset)],
         );
@@ -167,25 +121,7 @@ mod tests {
 
     fn prove_sh_lh<Stark: ProveAndVerify>(offset: u32, imm: u32, content: u32) {
         let (program, record) = code::execute(
-            [
-                Instruction {
-                    op: Op::SH,
-                    args: Args {
-                        rs1: 1,
-                        rs2: 2,
-                        imm,
-                        ..Args::default()
-                    },
-                },
-                Instruction {
-                    op: Op::LH,
-                    args: Args {
-                        rs2: 2,
-                        imm,
-                        ..Args::default()
-                    },
-                },
-            ],
+            [Instruction::sh(1, 2, imm), Instruction::lh(0, 2, imm)],
             &[(imm.wrapping_add(offset), 0)],
             &[(1, content), (2, offset)],
         );
@@ -5,8 +5,10 @@ pub mod div;
 pub mod ecall;
 pub mod generation;
 pub mod jalr;
+pub mod madd;
 pub mod memory;
 pub mod mul;
+pub mod rdcycle;
 pub mod shift;
 pub mod signed_comparison;
 pub mod stark;
@@ -89,15 +89,15 @@ pub(crate) fn constraints<'a, P: Copy>(
 
     // Now, check, that we select the correct output based on the opcode.
     let destination = lv.dst_value;
-    cb.always((lv.inst.ops.mul + lv.inst.ops.sll) * (destination - low_limb));
-    cb.always((lv.inst.ops.mulh) * (destination - high_limb));
+    cb.always((lv.inst.ops.mul() + lv.inst.ops.sll) * (destination - low_limb));
+    cb.always(lv.inst.ops.mulh() * (destination - high_limb));
 }
 
 #[cfg(test)]
 mod tests {
     use anyhow::Result;
     use mozak_runner::code;
-    use mozak_runner::instruction::{Args, Instruction, Op};
+    use mozak_runner::instruction::Instruction;
     use mozak_runner::test_utils::{i32_extra, u32_extra};
     use plonky2::timed;
     use plonky2::util::timing::TimingTree;
@@ -119,19 +119,10 @@ mod tests {
         let config = fast_test_config();
         let a = -2_147_451_028_i32;
         let b = 2_147_483_648_u32;
-        let (_program, record) = code::execute(
-            [Instruction {
-                op: Op::MULHSU,
-                args: Args {
-                    rd: 8,
-                    rs1: 6,
-                    rs2: 7,
-                    ..Args::default()
-                },
-            }],
-            &[],
-            &[(6, a as u32), (7, b)],
-        );
+        let (_program, record) = code::execute([Instruction::mulhsu(8, 6, 7)], &[], &[
+            (6, a as u32),
+            (7, b),
+        ]);
         let res = i64::from(a).wrapping_mul(i64::from(b));
         assert_eq!(record.executed[0].aux.dst_val, (res >> 32) as u32);
         let mut timing = TimingTree::new("mulhsu", log::Level::Debug);
@@ -159,19 +150,7 @@ mod tests {
     }
 
     fn prove_mul<Stark: ProveAndVerify>(a: u32, b: u32) -> Result<(), TestCaseError> {
-        let (program, record) = code::execute(
-            [Instruction {
-                op: Op::MUL,
-                args: Args {
-                    rd: 8,
-                    rs1: 6,
-                    rs2: 7,
-                    ..Args::default()
-                },
-            }],
-            &[],
-            &[(6, a), (7, b)],
-        );
+        let (program, record) = code::execute([Instruction::mul(8, 6, 7)], &[], &[(6, a), (7, b)]);
         let low = a.wrapping_mul(b);
         prop_assert_eq!(record.executed[0].aux.dst_val, low);
         Stark::prove_and_verify(&program, &record).unwrap();
@@ -179,19 +158,8 @@ mod tests {
     }
 
     fn prove_mulhu<Stark: ProveAndVerify>(a: u32, b: u32) -> Result<(), TestCaseError> {
-        let (program, record) = code::execute(
-            [Instruction {
-                op: Op::MULHU,
-                args: Args {
-                    rd: 9,
-                    rs1: 6,
-                    rs2: 7,
-                    ..Args::default()
-                },
-            }],
-            &[],
-            &[(6, a), (7, b)],
-        );
+        let (program, record) =
+            code::execute([Instruction::mulhu(9, 6, 7)], &[], &[(6, a), (7, b)]);
         let (res, _) = u64::from(a).overflowing_mul(u64::from(b));
         prop_assert_eq!(record.executed[0].aux.dst_val, (res >> 32) as u32);
         Stark::prove_and_verify(&program, &record).unwrap();
@@ -200,19 +168,10 @@ mod tests {
 
     #[allow(clippy::cast_sign_loss)]
     fn prove_mulh<Stark: ProveAndVerify>(a: i32, b: i32) -> Result<(), TestCaseError> {
-        let (program, record) = code::execute(
-            [Instruction {
-                op: Op::MULH,
-                args: Args {
-                    rd: 8,
-                    rs1: 6,
-                    rs2: 7,
-                    ..Args::default()
-                },
-            }],
-            &[],
-            &[(6, a as u32), (7, b as u32)],
-        );
+        let (program, record) = code::execute([Instruction::mulh(8, 6, 7)], &[], &[
+            (6, a as u32),
+            (7, b as u32),
+        ]);
         let (res, overflow) = i64::from(a).overflowing_mul(i64::from(b));
         assert!(!overflow);
         prop_assert_eq!(record.executed[0].aux.dst_val, (res >> 32) as u32);
@@ -222,19 +181,10 @@ mod tests {
 
     #[allow(clippy::cast_sign_loss)]
     fn prove_mulhsu<Stark: ProveAndVerify>(a: i32, b: u32) -> Result<(), TestCaseError> {
-        let (program, record) = code::execute(
-            [Instruction {
-                op: Op::MULHSU,
-                args: Args {
-                    rd: 8,
-                    rs1: 6,
-                    rs2: 7,
-                    ..Args::default()
-                },
-            }],
-            &[],
-            &[(6, a as u32), (7, b)],
-        );
+        let (program, record) = code::execute([Instruction::mulhsu(8, 6, 7)], &[], &[
+            (6, a as u32),
+            (7, b),
+        ]);
         let (res, _overflow) = i64::from(a).overflowing_mul(i64::from(b));
         prop_assert_eq!(record.executed[0].aux.dst_val, (res >> 32) as u32);
         Stark::prove_and_verify(&program, &record).unwrap();
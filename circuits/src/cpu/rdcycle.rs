@@ -0,0 +1,43 @@
+//! This module implements the constraints for reading the cycle counter,
+//! ie `rdcycle` and `rdinstret`. Both write the clock of the current row to
+//! the destination register.
+
+use expr::Expr;
+
+use super::columns::CpuState;
+use crate::expr::ConstraintBuilder;
+
+pub(crate) fn constraints<'a, P: Copy>(
+    lv: &CpuState<Expr<'a, P>>,
+    cb: &mut ConstraintBuilder<Expr<'a, P>>,
+) {
+    // Check: the clock is saved to destination.
+    // The clock itself is tied to the execution order via the skeleton table.
+    cb.always(lv.inst.ops.rdcycle() * (lv.dst_value - lv.clk));
+}
+
+#[cfg(test)]
+mod tests {
+    use mozak_runner::code;
+    use mozak_runner::instruction::Instruction;
+
+    use crate::cpu::stark::CpuStark;
+    use crate::stark::mozak_stark::MozakStark;
+    use crate::test_utils::{ProveAndVerify, D, F};
+
+    fn prove_rdcycle<Stark: ProveAndVerify>() {
+        let rdcycle = Instruction::rdcycle;
+        let (program, record) =
+            code::execute([rdcycle(5), Instruction::sub(7, 6, 5), rdcycle(6)], &[], &[
+            ]);
+        let state = &record.last_state;
+        assert_eq!(state.get_register_value(6) - state.get_register_value(5), 2);
+        Stark::prove_and_verify(&program, &record).unwrap();
+    }
+
+    #[test]
+    fn prove_rdcycle_cpu() { prove_rdcycle::<CpuStark<F, D>>(); }
+
+    #[test]
+    fn prove_rdcycle_mozak() { prove_rdcycle::<MozakStark<F, D>>(); }
+}
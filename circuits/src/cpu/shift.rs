@@ -53,26 +53,15 @@ mod tests {
         prop_assume!(rs1 != rd);
         prop_assume!(rs2 != rd);
         let (program, record) = code::execute(
-            [
-                Instruction {
-                    op: Op::SRL,
-                    args: Args {
-                        rd,
-                        rs1,
-                        rs2,
-                        ..Args::default()
-                    },
+            [Instruction::srl(rd, rs1, rs2), Instruction {
+                op: Op::SRL,
+                args: Args {
+                    rd,
+                    rs1,
+                    imm: q,
+                    ..Args::default()
                 },
-                Instruction {
-                    op: Op::SRL,
-                    args: Args {
-                        rd,
-                        rs1,
-                        imm: q,
-                        ..Args::default()
-                    },
-                },
-            ],
+            }],
             &[],
             &[(rs1, p), (rs2, q)],
         );
@@ -92,26 +81,15 @@ mod tests {
         prop_assume!(rs1 != rd);
         prop_assume!(rs2 != rd);
         let (program, record) = code::execute(
-            [
-                Instruction {
-                    op: Op::SLL,
-                    args: Args {
-                        rd,
-                        rs1,
-                        rs2,
-                        ..Args::default()
-                    },
-                },
-                Instruction {
-                    op: Op::SLL,
-                    args: Args {
-                        rd,
-                        rs1,
-                        imm: q,
-                        ..Args::default()
-                    },
+            [Instruction::sll(rd, rs1, rs2), Instruction {
+                op: Op::SLL,
+                args: Args {
+                    rd,
+                    rs1,
+                    imm: q,
+                    ..Args::default()
                 },
-            ],
+            }],
             &[],
             &[(rs1, p), (rs2, q)],
         );
@@ -131,26 +109,15 @@ mod tests {
         prop_assume!(rs1 != rd);
         prop_assume!(rs2 != rd);
         let (program, record) = code::execute(
-            [
-                Instruction {
-                    op: Op::SRA,
-                    args: Args {
-                        rd,
-                        rs1,
-                        rs2,
-                        ..Args::default()
-                    },
-                },
-                Instruction {
-                    op: Op::SRA,
-                    args: Args {
-                        rd,
-                        rs1,
-                        imm: q,
-                        ..Args::default()
-                    },
+            [Instruction::sra(rd, rs1, rs2), Instruction {
+                op: Op::SRA,
+                args: Args {
+                    rd,
+                    rs1,
+                    imm: q,
+                    ..Args::default()
                 },
-            ],
+            }],
             &[],
             &[(rs1, p), (rs2, q)],
         );
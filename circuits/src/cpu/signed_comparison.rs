@@ -41,7 +41,7 @@ pub(crate) fn slt_constraints<'a, P: Copy>(
     cb: &mut ConstraintBuilder<Expr<'a, P>>,
 ) {
     // Check: the destination has the same value as stored in `less_than`.
-    cb.always(lv.inst.ops.slt * (lv.less_than - lv.dst_value));
+    cb.always(lv.inst.ops.slt() * (lv.less_than - lv.dst_value));
 }
 
 #[cfg(test)]
@@ -12,7 +12,9 @@ use starky::evaluation_frame::StarkFrame;
 use starky::stark::Stark;
 
 use super::columns::{CpuState, OpSelectors};
-use super::{bitwise, branches, div, ecall, jalr, memory, mul, signed_comparison, sub};
+use super::{
+    bitwise, branches, div, ecall, jalr, madd, memory, mul, rdcycle, signed_comparison, sub,
+};
 use crate::columns_view::{HasNamedColumns, NumberOfColumns};
 use crate::cpu::shift;
 use crate::expr::{build_ext, build_packed, ConstraintBuilder};
@@ -37,18 +39,22 @@ fn pc_ticks_up<'a, P: Copy>(lv: &CpuState<Expr<'a, P>>, cb: &mut ConstraintBuild
     cb.transition(lv.inst.ops.is_straightline() * (lv.new_pc - (lv.inst.pc + 4)));
 }
 
-/// Enforce that selectors of opcode are one-hot encoded.
-/// Ie exactly one of them should be 1, and all others 0 in each row.
+/// Enforce that selectors of the op families are one-hot encoded.
+/// Ie at most one of them should be 1, and all others 0 in each row.
 /// See <https://en.wikipedia.org/wiki/One-hot>
 fn binary_selectors<'a, P: Copy>(
     ops: &OpSelectors<Expr<'a, P>>,
     cb: &mut ConstraintBuilder<Expr<'a, P>>,
 ) {
-    // selectors have value 0 or 1.
+    // selectors, and the variant, have value 0 or 1.
     ops.into_iter().for_each(|s| cb.always(s.is_binary()));
 
     // Only at most one selector enabled.
     cb.always(ops.is_running().is_binary());
+
+    // Only families of two have a variant, otherwise the op code of a family
+    // of one with a variant could be that of another instruction.
+    cb.always(ops.variant * (1 - ops.families_of_two()));
 }
 
 /// Constraints for values in op2, which is the sum of the value of the second
@@ -59,7 +65,7 @@ fn populate_op2_value<'a, P: Copy>(
     cb: &mut ConstraintBuilder<Expr<'a, P>>,
 ) {
     let ops = &lv.inst.ops;
-    let is_branch_operation = ops.beq + ops.bne + ops.blt + ops.bge;
+    let is_branch_operation = ops.beq_bne + ops.blt_bge;
     let is_shift_operation = ops.sll + ops.srl + ops.sra;
 
     cb.always(is_branch_operation * (lv.op2_value - lv.op2_value_raw));
@@ -91,8 +97,6 @@ fn generate_constraints<'a, T: Copy>(
     // Registers
     populate_op2_value(lv, &mut constraints);
 
-    // ADD is now handled by its own table.
-    constraints.always(lv.inst.ops.add);
     sub::constraints(lv, &mut constraints);
     bitwise::constraints(lv, &mut constraints);
     branches::comparison_constraints(lv, &mut constraints);
@@ -103,8 +107,13 @@ fn generate_constraints<'a, T: Copy>(
     shift::constraints(lv, &mut constraints);
     div::constraints(lv, &mut constraints);
     mul::constraints(lv, &mut constraints);
+    madd::constraints(lv, &mut constraints);
+    // Without the bit count table nothing would check the count.
+    #[cfg(not(feature = "zbb"))]
+    constraints.always(lv.inst.ops.bitcount);
     jalr::constraints(lv, &mut constraints);
     ecall::constraints(lv, &mut constraints);
+    rdcycle::constraints(lv, &mut constraints);
 
     constraints
 }
@@ -19,7 +19,7 @@ pub(crate) fn constraints<'a, P: Copy>(
 #[cfg(test)]
 mod tests {
     use mozak_runner::code;
-    use mozak_runner::instruction::{Args, Instruction, Op};
+    use mozak_runner::instruction::Instruction;
     use mozak_runner::test_utils::u32_extra;
     use proptest::prelude::ProptestConfig;
     use proptest::proptest;
@@ -29,19 +29,7 @@ mod tests {
     use crate::test_utils::{ProveAndVerify, D, F};
 
     fn prove_sub<Stark: ProveAndVerify>(a: u32, b: u32) {
-        let (program, record) = code::execute(
-            [Instruction {
-                op: Op::SUB,
-                args: Args {
-                    rd: 5,
-                    rs1: 6,
-                    rs2: 7,
-                    ..Args::default()
-                },
-            }],
-            &[],
-            &[(6, a), (7, b)],
-        );
+        let (program, record) = code::execute([Instruction::sub(5, 6, 7)], &[], &[(6, a), (7, b)]);
         assert_eq!(record.last_state.get_register_value(5), a.wrapping_sub(b));
         Stark::prove_and_verify(&program, &record).unwrap();
     }
@@ -1,25 +1,37 @@
-use crate::columns_view::{columns_view_impl, make_col_map};
+use crate::columns_view::{make_col_map, ColumnsView};
 use crate::linear_combination::Column;
+use crate::public_sub_table::PublicSubTable;
 use crate::stark::mozak_stark::{SkeletonTable, TableWithTypedOutput};
 
-columns_view_impl!(CpuSkeleton);
 make_col_map!(CpuSkeleton);
 #[repr(C)]
-#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
 pub struct CpuSkeleton<T> {
     pub clk: T,
     pub pc: T,
     pub is_running: T,
+    /// Set on the last running row, whose instruction halts the execution.
+    pub is_halting: T,
+    /// Set on the halting row, if the execution halts for exhausting its
+    /// cycle budget rather than via the `HALT` ecall.
+    pub is_out_of_gas: T,
+    /// Second operand of the instruction, ie the exit code of the `HALT`
+    /// ecall.
+    pub exit_code: T,
+    /// Inverse of the number of instructions the budget leaves, including
+    /// this one, which shows that the budget is not exceeded.
+    pub cycles_left_inv: T,
 }
 
-columns_view_impl!(CpuSkeletonCtl);
 #[repr(C)]
-#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
 pub struct CpuSkeletonCtl<T> {
     pub clk: T,
     pub pc: T,
     pub new_pc: T,
     pub will_halt: T,
+    /// See [`CpuSkeleton::exit_code`].
+    pub exit_code: T,
 }
 
 #[allow(dead_code)]
@@ -28,10 +40,22 @@ pub(crate) fn lookup_for_cpu() -> TableWithTypedOutput<CpuSkeletonCtl<Column>> {
         CpuSkeletonCtl {
             clk: COL_MAP.clk,
             pc: COL_MAP.pc,
-            // The `flip`s here mean that we need at least one row of padding at the end.
+            // The `flip` here means that we need at least one row of padding at the end.
             new_pc: COL_MAP.pc.flip(),
-            will_halt: !COL_MAP.is_running.flip(),
+            // Running out of gas halts without the instruction asking for it.
+            will_halt: COL_MAP.is_halting - COL_MAP.is_out_of_gas,
+            exit_code: COL_MAP.exit_code,
         },
         COL_MAP.is_running,
     )
 }
+
+/// Makes the exit code of the halting row public, see
+/// [`ExitCode`](crate::cpu::columns::ExitCode).
+#[must_use]
+pub fn make_exit_code_public() -> PublicSubTable {
+    PublicSubTable {
+        table: SkeletonTable::new(vec![COL_MAP.exit_code], COL_MAP.is_halting),
+        num_rows: 1,
+    }
+}
@@ -1,4 +1,3 @@
-use itertools::chain;
 use mozak_runner::vm::{ExecutionRecord, Row};
 use plonky2::hash::hash_types::RichField;
 
@@ -13,22 +12,37 @@ pub fn generate_cpu_skeleton_trace<F: RichField>(
         executed,
         last_state,
     } = record;
-    let last_row = &[Row {
-        state: last_state.clone(),
-        // `Aux` has auxiliary information about an executed CPU cycle.
-        // The last state is the final state after the last execution.  Thus naturally it has no
-        // associated auxiliary execution information. We use a dummy aux to make the row
-        // generation work, but we could refactor to make this unnecessary.
-        ..executed.last().unwrap().clone()
-    }];
+    let max_cycles = last_state.max_cycles.map_or(F::NEG_ONE, |max_cycles| {
+        F::from_noncanonical_u64(max_cycles.get())
+    });
 
-    let trace = chain![executed, last_row]
-        .map(|Row { state, .. }| CpuSkeleton {
-            clk: F::from_noncanonical_u64(state.clk),
-            pc: F::from_canonical_u32(state.get_pc()),
-            is_running: F::from_bool(!state.halted),
+    let trace = executed
+        .iter()
+        .enumerate()
+        .map(|(i, row)| {
+            let state = &row.state;
+            let clk = F::from_noncanonical_u64(state.clk);
+            let is_halting = i + 1 == executed.len();
+            CpuSkeleton {
+                clk,
+                pc: F::from_canonical_u32(state.get_pc()),
+                is_running: F::ONE,
+                is_halting: F::from_bool(is_halting),
+                is_out_of_gas: F::from_bool(is_halting && last_state.out_of_gas),
+                exit_code: F::from_canonical_u32(
+                    state.get_register_value(row.instruction.args.rs2),
+                ),
+                cycles_left_inv: (max_cycles + F::TWO - clk)
+                    .try_inverse()
+                    .unwrap_or_default(),
+            }
         })
+        .chain([CpuSkeleton {
+            clk: F::from_noncanonical_u64(last_state.clk),
+            pc: F::from_canonical_u32(last_state.get_pc()),
+            ..CpuSkeleton::default()
+        }])
         .collect();
-    log::trace!("trace {:?}", trace);
+    tracing::trace!("trace {:?}", trace);
     pad_trace_with_last(trace)
 }
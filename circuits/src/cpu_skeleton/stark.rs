@@ -27,7 +27,8 @@ impl<F, const D: usize> HasNamedColumns for CpuSkeletonStark<F, D> {
 }
 
 const COLUMNS: usize = CpuSkeleton::<()>::NUMBER_OF_COLUMNS;
-// Public inputs: [PC of the first row]
+// Public inputs: PC of the first row, cycle budget and whether we ran out of
+// gas
 const PUBLIC_INPUTS: usize = PublicInputs::<()>::NUMBER_OF_COLUMNS;
 
 fn generate_constraints<'a, T: Copy>(
@@ -60,11 +61,34 @@ fn generate_constraints<'a, T: Copy>(
     // We end in a non-running state.
     constraints.last_row(lv.is_running);
 
-    // NOTE: in our old CPU table we had constraints that made sure nothing
-    // changes anymore, once we are halted. We don't need those
-    // anymore: the only thing that can change are memory or registers.  And
-    // our CTLs make sure, that after we are halted, no more memory
-    // or register changes are allowed.
+    // Once halted, nothing moves anymore: the clock is frozen above, and so is
+    // the pc.
+    constraints.transition((1 - lv.is_running) * (nv.pc - lv.pc));
+
+    // `is_running` only ever drops from one to zero, so this marks the one row
+    // where it does.
+    constraints.transition(lv.is_halting - (lv.is_running - nv.is_running));
+    constraints.last_row(lv.is_halting);
+
+    // The cycle budget allows instructions up to clock `max_cycles + 1`, so
+    // there are `max_cycles + 2 - clk` left on each row. A budget of `-1`
+    // never runs out.
+    let cycles_left = public_inputs.max_cycles + 2 - lv.clk;
+    constraints.always(lv.is_running * (cycles_left * lv.cycles_left_inv - 1));
+
+    // Running out of gas halts after the last instruction the budget allows.
+    constraints.always(lv.is_out_of_gas.is_binary());
+    constraints.always(lv.is_out_of_gas * (1 - lv.is_halting));
+    constraints.always(lv.is_out_of_gas * (cycles_left - 1));
+    constraints.first_row(public_inputs.out_of_gas.is_binary());
+    constraints.always(lv.is_halting * (lv.is_out_of_gas - public_inputs.out_of_gas));
+
+    // NOTE: the CPU table has no such constraints, because its rows are not
+    // ordered. Instead, every executed CPU row is looked up in a running row
+    // of this table by its clock, and the halt row has to be the last running
+    // row, via `will_halt`. So there is no running row left for any activity
+    // after the halt to match, and memory and register changes are tied to
+    // executed CPU rows by their own CTLs.
     constraints
 }
 
@@ -103,3 +127,153 @@ impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for CpuSkeletonSt
 
     fn constraint_degree(&self) -> usize { 3 }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Borrow;
+    use std::num::NonZeroU64;
+
+    use mozak_runner::code::{self, Code};
+    use mozak_runner::elf::Program;
+    use mozak_runner::instruction::{Args, Instruction, Op};
+    use mozak_runner::state::{RawTapes, State};
+    use mozak_runner::vm::{step, ExecutionRecord};
+    use plonky2::field::types::Field;
+    use plonky2::plonk::config::{GenericConfig, Poseidon2GoldilocksConfig};
+    use plonky2::util::timing::TimingTree;
+    use starky::stark_testing::test_stark_circuit_constraints;
+
+    use super::*;
+    use crate::cpu::columns::ExitCode;
+    use crate::cpu_skeleton::generation::generate_cpu_skeleton_trace;
+    use crate::generation::debug_single_trace;
+    use crate::stark::mozak_stark::MozakStark;
+    use crate::stark::prover::prove;
+    use crate::stark::utils::trace_rows_to_poly_values;
+    use crate::stark::verifier::verify_proof;
+    use crate::test_utils::fast_test_config;
+    use crate::utils::from_u32;
+
+    const D: usize = 2;
+    type C = Poseidon2GoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+    type S = CpuSkeletonStark<F, D>;
+
+    /// Checks the skeleton of a short program, after `forge` changed the row
+    /// right after the halt.
+    fn check_after_halt(forge: impl FnOnce(&mut CpuSkeleton<F>)) {
+        let (program, record) = code::execute([], &[], &[]);
+        let mut rows = generate_cpu_skeleton_trace(&record);
+        let first_halted = rows
+            .iter()
+            .position(|row| row.is_running.is_zero())
+            .unwrap();
+        forge(&mut rows[first_halted + 1]);
+        debug_single_trace::<F, D, _>(
+            &S::default(),
+            &trace_rows_to_poly_values(rows),
+            PublicInputs::new(from_u32(program.entry_point)).borrow(),
+        );
+    }
+
+    #[test]
+    fn halted_rows_pass() { check_after_halt(|_| {}); }
+
+    #[test]
+    #[should_panic(expected = "debug_api_has_constraint_failed")]
+    fn running_after_halt_fails() {
+        check_after_halt(|row| {
+            row.is_running = F::ONE;
+            row.clk += F::ONE;
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "debug_api_has_constraint_failed")]
+    fn clock_after_halt_fails() { check_after_halt(|row| row.clk += F::ONE); }
+
+    #[test]
+    #[should_panic(expected = "debug_api_has_constraint_failed")]
+    fn pc_after_halt_fails() { check_after_halt(|row| row.pc += F::from_canonical_u32(4)); }
+
+    /// Counts up `t0` in an endless loop, until its budget of five
+    /// instructions runs out.
+    fn count_out_of_gas() -> (Program, ExecutionRecord<F>) {
+        let code = [
+            Instruction::addi(5, 5, 1),
+            Instruction::new(Op::JALR, Args::default()),
+        ];
+        let program = Program::create(&[], &[], Code((0..).step_by(4).zip(code.map(Ok)).collect()));
+        let state = State::new(program.clone(), RawTapes::default())
+            .with_max_cycles(NonZeroU64::new(5).unwrap());
+        let record = step(&program, state).unwrap();
+        (program, record)
+    }
+
+    fn check_budget(record: &ExecutionRecord<F>, public_inputs: PublicInputs<F>) {
+        debug_single_trace::<F, D, _>(
+            &S::default(),
+            &trace_rows_to_poly_values(generate_cpu_skeleton_trace(record)),
+            public_inputs.borrow(),
+        );
+    }
+
+    #[test]
+    fn out_of_gas_passes() {
+        let (program, record) = count_out_of_gas();
+        check_budget(&record, PublicInputs::for_execution(&program, &record));
+    }
+
+    #[test]
+    #[should_panic(expected = "debug_api_has_constraint_failed")]
+    fn hiding_out_of_gas_fails() {
+        let (program, record) = count_out_of_gas();
+        check_budget(&record, PublicInputs {
+            out_of_gas: F::ZERO,
+            ..PublicInputs::for_execution(&program, &record)
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "debug_api_has_constraint_failed")]
+    fn exceeding_budget_fails() {
+        let (program, mut record) = count_out_of_gas();
+        record.last_state.max_cycles = NonZeroU64::new(4);
+        check_budget(&record, PublicInputs::for_execution(&program, &record));
+    }
+
+    #[test]
+    #[should_panic(expected = "debug_api_has_constraint_failed")]
+    fn out_of_gas_before_budget_fails() {
+        let (program, mut record) = count_out_of_gas();
+        record.last_state.max_cycles = NonZeroU64::new(6);
+        check_budget(&record, PublicInputs::for_execution(&program, &record));
+    }
+
+    #[test]
+    fn prove_out_of_gas() -> anyhow::Result<()> {
+        let (program, record) = count_out_of_gas();
+        let stark = MozakStark::default();
+        let config = fast_test_config();
+        let all_proof = prove::<F, C, D>(
+            &program,
+            &record,
+            &stark,
+            &config,
+            PublicInputs::for_execution(&program, &record),
+            &mut TimingTree::default(),
+        )?;
+        assert_eq!(all_proof.public_inputs.out_of_gas, F::ONE);
+        // The exit code is meaningless, but still there.
+        all_proof.public_value::<ExitCode>(&stark)?;
+        verify_proof(&stark, all_proof, &config)
+    }
+
+    #[test]
+    fn test_circuit() -> anyhow::Result<()> {
+        let stark = S::default();
+        test_stark_circuit_constraints::<F, C, S, D>(stark)?;
+
+        Ok(())
+    }
+}
@@ -19,6 +19,7 @@ use starky::evaluation_frame::StarkEvaluationFrame;
 use starky::stark::Stark;
 use thiserror::Error;
 
+use crate::columns_view::ColumnNames;
 pub use crate::linear_combination::Column;
 use crate::linear_combination::ColumnSparse;
 pub use crate::linear_combination_typed::ColumnWithTypedInput;
@@ -31,6 +32,8 @@ use crate::stark::proof::{StarkProof, StarkProofTarget};
 pub enum LookupError {
     #[error("Inconsistency found between looking and looked tables")]
     InconsistentTableRows,
+    #[error("Padding row takes part in a cross table lookup")]
+    ActivePaddingRow,
 }
 
 #[derive(Clone, Debug, Default)]
@@ -75,7 +78,7 @@ pub(crate) fn verify_cross_table_lookups_and_public_sub_tables<
 ) -> Result<()> {
     let mut ctl_zs_openings = ctl_zs_lasts.each_ref().map(|v| v.iter().copied());
     for _ in 0..config.num_challenges {
-        for CrossTableLookup { looking_tables } in cross_table_lookups {
+        for CrossTableLookup { looking_tables, .. } in cross_table_lookups {
             let looking_zs_sum = looking_tables
                 .iter()
                 .map(|table| ctl_zs_openings[table.kind].next().unwrap())
@@ -119,7 +122,7 @@ pub(crate) fn verify_cross_table_lookups_and_public_sub_table_circuit<
 ) {
     let mut ctl_zs_openings = ctl_zs_lasts.each_ref().map(|v| v.iter());
     for _ in 0..config.num_challenges {
-        for CrossTableLookup { looking_tables } in cross_table_lookups {
+        for CrossTableLookup { looking_tables, .. } in cross_table_lookups {
             let looking_zs_sum = builder.add_many(
                 looking_tables
                     .iter()
@@ -154,11 +157,11 @@ pub(crate) fn cross_table_lookup_data<F: RichField, const D: usize>(
     cross_table_lookups: &[CrossTableLookup],
     ctl_challenges: &GrandProductChallengeSet<F>,
 ) -> TableKindArray<CtlData<F>> {
-    let mut tables = iproduct!(
+    let tables = iproduct!(
         &ctl_challenges.challenges,
         cross_table_lookups
             .iter()
-            .flat_map(|CrossTableLookup { looking_tables }| looking_tables)
+            .flat_map(|CrossTableLookup { looking_tables, .. }| looking_tables)
     )
     .collect::<Vec<_>>()
     .into_par_iter()
@@ -175,9 +178,17 @@ pub(crate) fn cross_table_lookup_data<F: RichField, const D: usize>(
             filter_column: table.filter_column.clone(),
         })
     })
-    .collect::<Vec<_>>()
-    .into_iter()
-    .into_group_map();
+    .collect::<Vec<_>>();
+    if cfg!(debug_assertions) {
+        ctl_utils::report_unbalanced_ctls(
+            trace_poly_values,
+            cross_table_lookups,
+            tables
+                .iter()
+                .map(|(_, data)| data.z.values.last().copied().unwrap_or_default()),
+        );
+    }
+    let mut tables = tables.into_iter().into_group_map();
     all_kind!(|kind| CtlData {
         zs_columns: tables.remove(&kind).unwrap(),
     })
@@ -244,6 +255,12 @@ pub fn partial_sums<F: Field>(
 #[derive(Clone, Debug)]
 pub struct CrossTableLookupWithTypedOutput<Row> {
     pub looking_tables: Vec<TableWithTypedOutput<Row>>,
+    /// The first `num_looking` of `looking_tables` look up the rest, which
+    /// were passed to [`new`](Self::new) as looked tables.
+    pub num_looking: usize,
+    /// Names of the columns of a row, see
+    /// [`ColumnNames`](crate::columns_view::ColumnNames).
+    pub column_names: Vec<String>,
 }
 
 // This is a little trick, so that we can use `CrossTableLookup` as a
@@ -260,13 +277,22 @@ impl<Row: IntoIterator<Item = Column>> CrossTableLookupWithTypedOutput<Row> {
             .into_iter()
             .map(TableWithTypedOutput::to_untyped_output)
             .collect();
-        CrossTableLookup { looking_tables }
+        CrossTableLookup {
+            looking_tables,
+            num_looking: self.num_looking,
+            column_names: self.column_names,
+        }
     }
 }
 
-impl<Row> CrossTableLookupWithTypedOutput<Row> {
+impl<Row: ColumnNames> CrossTableLookupWithTypedOutput<Row> {
     /// Instantiates a new cross table lookup between 2 tables.
     ///
+    /// Filters are multiplicities, on both sides: a row with filter `m` looks
+    /// up, or provides, its tuple `m` times. So looked tables like the range
+    /// check tables provide each value once, with a multiplicity column as
+    /// their filter, instead of once per lookup.
+    ///
     /// # Panics
     /// Panics if the two tables do not have equal number of columns.
     #[must_use]
@@ -274,8 +300,23 @@ impl<Row> CrossTableLookupWithTypedOutput<Row> {
         mut looking_tables: Vec<TableWithTypedOutput<Row>>,
         looked_tables: Vec<TableWithTypedOutput<Row>>,
     ) -> Self {
+        let num_looking = looking_tables.len();
         looking_tables.extend(looked_tables.into_iter().map(Neg::neg));
-        Self { looking_tables }
+        let mut column_names = Vec::new();
+        Row::push_column_names("", &mut column_names);
+        Self {
+            looking_tables,
+            num_looking,
+            column_names,
+        }
+    }
+}
+
+impl<Row> CrossTableLookupWithTypedOutput<Row> {
+    /// The looking tables, and the looked tables with their filters negated.
+    #[must_use]
+    pub fn split_tables(&self) -> (&[TableWithTypedOutput<Row>], &[TableWithTypedOutput<Row>]) {
+        self.looking_tables.split_at(self.num_looking)
     }
 
     #[must_use]
@@ -473,6 +514,7 @@ pub mod ctl_utils {
     use std::collections::BTreeMap;
 
     use anyhow::Result;
+    use itertools::Itertools;
     use plonky2::field::extension::Extendable;
     use plonky2::field::polynomial::PolynomialValues;
     use plonky2::hash::hash_types::RichField;
@@ -519,51 +561,209 @@ pub mod ctl_utils {
             }
         }
     }
-    pub fn check_single_ctl<F: RichField>(
-        trace_poly_values: &TableKindArray<Vec<PolynomialValues<F>>>,
-        // TODO(Matthias): make this one work with CrossTableLookupNamed, instead of having to
-        // forget the types first.  That should also help with adding better debug messages.
-        ctl: &CrossTableLookup,
-    ) -> Result<(), LookupError> {
-        /// Sums and compares the multiplicities of the given looking and looked
-        /// locations previously processed.
-        ///
-        /// The CTL check holds iff `looking_multiplicity ==
-        /// looked_multiplicity`.
-        fn check_multiplicities<F: RichField>(
-            row: &[u64],
-            looking_locations: &[(TableKind, F)],
-        ) -> Result<(), LookupError> {
-            let looking_multiplicity = looking_locations.iter().map(|l| l.1).sum::<F>();
-            if looking_multiplicity != F::ZERO {
-                eprintln!(
-                    "Row {row:?} has multiplicity {looking_multiplicity} != 0 in the looking tables.\n\
-                    Looking locations: {looking_locations:?}."
-                );
-                return Err(LookupError::InconsistentTableRows);
-            }
 
-            Ok(())
+    /// A tuple that the looking tables look up a different number of times
+    /// than the looked tables provide it.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub struct UnmatchedTuple<F> {
+        pub row: Vec<u64>,
+        /// Multiplicity of `row` in each looking table.
+        pub looking: Vec<(TableKind, F)>,
+        /// Multiplicity of `row` in each looked table.
+        pub looked: Vec<(TableKind, F)>,
+    }
+
+    impl<F: RichField> UnmatchedTuple<F> {
+        /// Describes the tuple, naming its values after `column_names`.
+        #[must_use]
+        pub fn describe(&self, column_names: &[String]) -> String {
+            let values = self
+                .row
+                .iter()
+                .enumerate()
+                .map(|(i, value)| match column_names.get(i) {
+                    Some(name) => format!("{name}={value}"),
+                    None => format!("[{i}]={value}"),
+                })
+                .join(", ");
+            let tables = |locations: &[(TableKind, F)]| {
+                locations
+                    .iter()
+                    .map(|(kind, multiplicity)| format!("{kind:?} x {multiplicity}"))
+                    .join(", ")
+            };
+            format!(
+                "{values}: looking {{{}}}, looked {{{}}}",
+                tables(&self.looking),
+                tables(&self.looked)
+            )
         }
+    }
 
+    /// Sums the multiplicities of each table.
+    fn per_table<F: RichField>(locations: Option<&Vec<(TableKind, F)>>) -> Vec<(TableKind, F)> {
+        locations
+            .into_iter()
+            .flatten()
+            .copied()
+            .into_grouping_map()
+            .sum()
+            .into_iter()
+            .sorted_by_key(|&(kind, _)| kind as usize)
+            .collect()
+    }
+
+    /// Recomputes the multisets of the looking and the looked tables of `ctl`
+    /// from the traces, and returns the tuples where they differ, in order.
+    #[must_use]
+    pub fn unmatched_tuples<F: RichField>(
+        trace_poly_values: &TableKindArray<Vec<PolynomialValues<F>>>,
+        ctl: &CrossTableLookup,
+    ) -> Vec<UnmatchedTuple<F>> {
         // Maps `m` with `(table.kind, multiplicity) in m[row]`
         let mut looking_multiset = MultiSet::<F>::default();
+        let mut looked_multiset = MultiSet::<F>::default();
+        let (looking_tables, looked_tables) = ctl.split_tables();
+        for table in looking_tables {
+            looking_multiset.process_row(trace_poly_values, table);
+        }
+        // The filters of looked tables are negated, see
+        // `CrossTableLookup::split_tables`.
+        for table in looked_tables {
+            looked_multiset.process_row(trace_poly_values, &-table.clone());
+        }
 
-        for looking_table in &ctl.looking_tables {
-            looking_multiset.process_row(trace_poly_values, looking_table);
+        looking_multiset
+            .keys()
+            .merge(looked_multiset.keys())
+            .dedup()
+            .filter_map(|row| {
+                let looking = per_table(looking_multiset.get(row));
+                let looked = per_table(looked_multiset.get(row));
+                let multiplicity = |locations: &[(TableKind, F)]| -> F {
+                    locations
+                        .iter()
+                        .map(|&(_, multiplicity)| multiplicity)
+                        .sum()
+                };
+                (multiplicity(&looking) != multiplicity(&looked)).then(|| UnmatchedTuple {
+                    row: row.clone(),
+                    looking,
+                    looked,
+                })
+            })
+            .collect()
+    }
+
+    /// How many unmatched tuples [`check_single_ctl`] prints.
+    const REPORTED_TUPLES: usize = 10;
+
+    /// Checks that every tuple of the looking tables of `ctl` appears in the
+    /// looked tables the same number of times, and prints a sample of the
+    /// [`unmatched_tuples`] otherwise.
+    pub fn check_single_ctl<F: RichField>(
+        trace_poly_values: &TableKindArray<Vec<PolynomialValues<F>>>,
+        ctl: &CrossTableLookup,
+    ) -> Result<(), LookupError> {
+        let unmatched = unmatched_tuples(trace_poly_values, ctl);
+        if unmatched.is_empty() {
+            return Ok(());
+        }
+        eprintln!(
+            "Lookup of ({}) is unbalanced in {} tuples, the first {} are:",
+            ctl.column_names.join(", "),
+            unmatched.len(),
+            unmatched.len().min(REPORTED_TUPLES)
+        );
+        for tuple in unmatched.iter().take(REPORTED_TUPLES) {
+            eprintln!("    {}", tuple.describe(&ctl.column_names));
         }
+        Err(LookupError::InconsistentTableRows)
+    }
 
-        // Check that every row in the looking tables appears in the looked table the
-        // same number of times.
-        for (row, looking_locations) in &looking_multiset.0 {
-            check_multiplicities(row, looking_locations).map_err(|e| {
-                eprintln!("Looking multiset: {looking_multiset:?}");
-                e
-            })?;
+    /// Reports the cross table lookups that fail verification, ie whose sums
+    /// `zs_lasts` do not add up to zero, with [`check_single_ctl`].
+    ///
+    /// `zs_lasts` are ordered by challenge, then by cross table lookup, then
+    /// by table, as in `cross_table_lookup_data`.
+    pub(crate) fn report_unbalanced_ctls<F: RichField>(
+        trace_poly_values: &TableKindArray<Vec<PolynomialValues<F>>>,
+        ctls: &[CrossTableLookup],
+        zs_lasts: impl IntoIterator<Item = F>,
+    ) {
+        let mut zs_lasts = zs_lasts.into_iter();
+        let unbalanced = ctls
+            .iter()
+            .enumerate()
+            .cycle()
+            .map_while(|(i, ctl)| {
+                let sum = zs_lasts
+                    .by_ref()
+                    .take(ctl.looking_tables.len())
+                    .sum1::<F>()?;
+                Some((i, sum))
+            })
+            .filter(|(_, sum)| sum.is_nonzero())
+            .map(|(i, _)| i)
+            .unique()
+            .collect_vec();
+        for i in unbalanced {
+            eprintln!("CTL {i} will fail verification.");
+            // Only reports, the verifier rejects the proof.
+            let _ = check_single_ctl(trace_poly_values, &ctls[i]);
         }
+    }
 
+    /// Checks that no all-zero row of a trace, ie a row produced by padding
+    /// with `Default`, takes part in the given cross table lookup.
+    ///
+    /// Such a row has to be inert: the filter of every table in the lookup
+    /// has to evaluate to zero on it.  Otherwise padding can silently add
+    /// (or cancel) entries on either side of the lookup.
+    ///
+    /// Note that filters are not required to be binary here: multiplicity
+    /// columns are legitimately used as filters, and selector columns are
+    /// already constrained to be binary by their own STARKs.
+    pub fn check_padding_is_inert<F: RichField>(
+        trace_poly_values: &TableKindArray<Vec<PolynomialValues<F>>>,
+        ctl: &CrossTableLookup,
+    ) -> Result<(), LookupError> {
+        for table in &ctl.looking_tables {
+            let trace = &trace_poly_values[table.kind];
+            let filter_column = table.filter_column.to_field();
+            let len = trace[0].len();
+            let is_zero_row = |i: usize| trace.iter().all(|column| column.values[i].is_zero());
+            for i in 0..len {
+                // Filters may look at the next row, so only rows that are followed by
+                // another padding row are known to be padding.
+                let is_padding = is_zero_row(i) && is_zero_row((i + 1) % len);
+                let filter = filter_column.eval_table(trace, i);
+                if is_padding && filter.is_nonzero() {
+                    eprintln!(
+                        "Padding row {i} of {:?} has filter {filter} != 0.",
+                        table.kind
+                    );
+                    return Err(LookupError::ActivePaddingRow);
+                }
+            }
+        }
         Ok(())
     }
+
+    pub fn debug_padding<F: RichField + Extendable<D>, const D: usize>(
+        traces_poly_values: &TableKindArray<Vec<PolynomialValues<F>>>,
+        mozak_stark: &MozakStark<F, D>,
+    ) {
+        mozak_stark
+            .cross_table_lookups
+            .iter()
+            .enumerate()
+            .for_each(|(i, ctl)| {
+                check_padding_is_inert(traces_poly_values, ctl)
+                    .unwrap_or_else(|e| panic!("CTL {i} failed: {e:?}"));
+            });
+    }
+
     pub fn debug_ctl<F: RichField + Extendable<D>, const D: usize>(
         traces_poly_values: &TableKindArray<Vec<PolynomialValues<F>>>,
         mozak_stark: &MozakStark<F, D>,
@@ -580,3 +780,84 @@ pub mod ctl_utils {
 }
 
 // TODO(Matthias): restore the tests from before https://github.com/0xmozak/mozak-vm/pull/1371
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::polynomial::PolynomialValues;
+    use plonky2::field::types::Field;
+
+    use super::ctl_utils::{unmatched_tuples, UnmatchedTuple};
+    use super::{partial_sums, Column, CrossTableLookup};
+    use crate::stark::mozak_stark::{Table, TableKind, TableKindArray};
+    use crate::stark::permutation::challenge::GrandProductChallenge;
+    use crate::test_utils::F;
+
+    fn column(index: usize) -> Column {
+        Column {
+            lv_linear_combination: vec![(index, 1)],
+            ..Column::default()
+        }
+    }
+
+    fn trace(columns: &[&[u64]]) -> Vec<PolynomialValues<F>> {
+        columns
+            .iter()
+            .map(|values| {
+                PolynomialValues::new(values.iter().map(|&v| F::from_canonical_u64(v)).collect())
+            })
+            .collect()
+    }
+
+    /// Looks up 3, 5, 3 and 3 in a table that provides 3 three times and 5
+    /// once, in a single row each, with `multiplicity_of_3` for 3.
+    fn lookup(
+        multiplicity_of_3: u64,
+    ) -> (TableKindArray<Vec<PolynomialValues<F>>>, CrossTableLookup) {
+        let mut traces = TableKindArray::default();
+        traces[TableKind::Cpu] = trace(&[&[3, 5, 3, 3], &[1, 1, 1, 1]]);
+        traces[TableKind::RangeCheckU8] = trace(&[&[3, 5, 7, 0], &[multiplicity_of_3, 1, 0, 0]]);
+        let ctl = CrossTableLookup {
+            looking_tables: vec![
+                Table::new(TableKind::Cpu, vec![column(0)], column(1)),
+                -Table::new(TableKind::RangeCheckU8, vec![column(0)], column(1)),
+            ],
+            num_looking: 1,
+            column_names: vec!["value".to_string()],
+        };
+        (traces, ctl)
+    }
+
+    #[test]
+    fn looked_multiplicities_balance() {
+        let (traces, ctl) = lookup(3);
+        let challenge = GrandProductChallenge {
+            beta: F::from_canonical_u64(7),
+            gamma: F::from_canonical_u64(11),
+        };
+        let sum: F = ctl
+            .looking_tables
+            .iter()
+            .map(|table| {
+                let z = partial_sums(
+                    &traces[table.kind],
+                    &table.columns,
+                    &table.filter_column,
+                    challenge,
+                );
+                *z.values.last().unwrap()
+            })
+            .sum();
+        assert_eq!(sum, F::ZERO);
+        assert!(unmatched_tuples(&traces, &ctl).is_empty());
+    }
+
+    #[test]
+    fn wrong_looked_multiplicity_is_unmatched() {
+        let (traces, ctl) = lookup(2);
+        assert_eq!(unmatched_tuples(&traces, &ctl), [UnmatchedTuple {
+            row: vec![3],
+            looking: vec![(TableKind::Cpu, F::from_canonical_u64(3))],
+            looked: vec![(TableKind::RangeCheckU8, F::from_canonical_u64(2))],
+        }]);
+    }
+}
@@ -0,0 +1,410 @@
+use core::iter::Sum;
+use core::ops::{Add, Mul, Sub};
+
+use itertools::izip;
+use mozak_runner::decode::ECALL;
+use mozak_runner::instruction::{Args, Op};
+
+use crate::columns_view::{make_col_map, ColumnsView, NumberOfColumns};
+use crate::cpu::columns::Instruction;
+use crate::cross_table_lookup::{Column, ColumnWithTypedInput};
+use crate::program::columns::ProgramRom;
+use crate::rangecheck::columns::RangeCheckCtl;
+use crate::stark::mozak_stark::{DecodeTable, TableWithTypedOutput};
+
+/// How the operands of an [`Op`] are taken from the bits of the word.
+///
+/// This follows the quirks of
+/// [`decode_instruction`](mozak_runner::decode::decode_instruction), eg
+/// loads and stores read their address from `rs2`, and jumps and branches
+/// have absolute targets.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    /// `rd`, `rs1` and `rs2`.
+    Register,
+    /// `rd`, `rs1` and the I-type immediate.
+    Immediate,
+    /// `rd`, `rs1` as `rs2`, and the I-type immediate.
+    Load,
+    /// `rs1` and `rs2` swapped, and the S-type immediate.
+    Store,
+    /// `rs1`, `rs2` and the B-type offset from `pc`.
+    Branch,
+    /// `rd` and the J-type offset from `pc`.
+    Jump,
+    /// `rd` and the U-type immediate.
+    Upper,
+    /// `rd` and the U-type immediate plus `pc`.
+    UpperPc,
+    /// `rd`, `rs1` and two to the power of `shamt`, which turns shifts into
+    /// multiplication and division.
+    ShiftPower,
+    /// `rd`, `rs1` and `shamt`.
+    Shift,
+    /// `rd`, `rs1` and the `rs2` field as the immediate, which picks the
+    /// count of a Zbb `clz`, `ctz` or `cpop`.
+    Unary,
+    /// The fixed operands of [`ECALL`].
+    Ecall,
+    /// `rd` only, for reading the cycle counter.
+    Counter,
+    /// No operands.
+    Nop,
+    /// No operands, for any CSR access that is not a [`Format::Counter`].
+    CsrNop,
+}
+
+/// A way to decode a word: every word that agrees with `pattern` on the bits
+/// of `mask` decodes to `op` with operands in `format`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Case {
+    pub op: Op,
+    pub format: Format,
+    pub mask: u32,
+    pub pattern: u32,
+}
+
+impl Case {
+    const fn new(op: Op, format: Format, opcode: u32) -> Self {
+        Self {
+            op,
+            format,
+            mask: 0x7F,
+            pattern: opcode,
+        }
+    }
+
+    /// Fixes the bits of `mask << lsb` to `value << lsb`.
+    const fn bits(self, lsb: u32, mask: u32, value: u32) -> Self {
+        Self {
+            mask: self.mask | (mask << lsb),
+            pattern: self.pattern | (value << lsb),
+            ..self
+        }
+    }
+
+    const fn funct3(self, funct3: u32) -> Self { self.bits(12, 0x7, funct3) }
+
+    const fn funct7(self, funct7: u32) -> Self { self.bits(25, 0x7F, funct7) }
+
+    const fn funct12(self, funct12: u32) -> Self { self.bits(20, 0xFFF, funct12) }
+
+    const fn rs1_zero(self) -> Self { self.bits(15, 0x1F, 0) }
+
+    #[must_use]
+    pub fn matches(&self, word: u32) -> bool {
+        word & self.mask == self.pattern
+            && (self.format != Format::CsrNop || counter_distance(&word_bits(word)) != 0)
+    }
+
+    /// The columns of the decoded instruction that do not depend on the
+    /// operands, as in the CPU table.
+    #[must_use]
+    pub fn prototype(&self) -> Instruction<u32> {
+        Instruction::from((
+            0,
+            mozak_runner::instruction::Instruction::new(self.op, Args::default()),
+        ))
+    }
+}
+
+const OP: u32 = 0b011_0011;
+const LOAD: u32 = 0b000_0011;
+const STORE: u32 = 0b010_0011;
+const OP_IMM: u32 = 0b001_0011;
+const SYSTEM: u32 = 0b111_0011;
+const BRANCH: u32 = 0b110_0011;
+const CUSTOM_0: u32 = 0b000_1011;
+
+const fn r(op: Op, funct3: u32, funct7: u32) -> Case {
+    Case::new(op, Format::Register, OP)
+        .funct3(funct3)
+        .funct7(funct7)
+}
+
+const fn counter(op: Op, funct12: u32) -> Case {
+    // `funct12` may have bit 1 set or not, which picks between cycles and
+    // retired instructions.
+    Case::new(op, Format::Counter, SYSTEM)
+        .funct3(0x2)
+        .rs1_zero()
+        .bits(20, 0xFFD, funct12)
+}
+
+/// The Zbb subset, see the `zbb` feature.
+#[cfg(feature = "zbb")]
+const NUM_ZBB_CASES: usize = 6;
+#[cfg(not(feature = "zbb"))]
+const NUM_ZBB_CASES: usize = 0;
+pub const NUM_CASES: usize = 55 + NUM_ZBB_CASES;
+
+/// All words that decode, in the order in which
+/// [`decode_instruction`](mozak_runner::decode::decode_instruction) tries
+/// them. Only [`Format::Counter`] and [`Format::CsrNop`] overlap, and the
+/// latter excludes the former explicitly.
+pub const CASES: [Case; NUM_CASES] = [
+    r(Op::ADD, 0x0, 0x00),
+    r(Op::SUB, 0x0, 0x20),
+    r(Op::SLL, 0x1, 0x00),
+    r(Op::SLT, 0x2, 0x00),
+    r(Op::SLTU, 0x3, 0x00),
+    r(Op::XOR, 0x4, 0x00),
+    r(Op::SRL, 0x5, 0x00),
+    r(Op::SRA, 0x5, 0x20),
+    r(Op::OR, 0x6, 0x00),
+    r(Op::AND, 0x7, 0x00),
+    r(Op::DIV, 0x4, 0x01),
+    r(Op::DIVU, 0x5, 0x01),
+    r(Op::REM, 0x6, 0x01),
+    r(Op::REMU, 0x7, 0x01),
+    r(Op::MUL, 0x0, 0x01),
+    r(Op::MULH, 0x1, 0x01),
+    r(Op::MULHSU, 0x2, 0x01),
+    r(Op::MULHU, 0x3, 0x01),
+    #[cfg(feature = "zbb")]
+    r(Op::ANDN, 0x7, 0x20),
+    #[cfg(feature = "zbb")]
+    r(Op::ORN, 0x6, 0x20),
+    #[cfg(feature = "zbb")]
+    r(Op::XNOR, 0x4, 0x20),
+    Case::new(Op::LB, Format::Load, LOAD).funct3(0x0),
+    Case::new(Op::LH, Format::Load, LOAD).funct3(0x1),
+    Case::new(Op::LW, Format::Load, LOAD).funct3(0x2),
+    Case::new(Op::LBU, Format::Load, LOAD).funct3(0x4),
+    Case::new(Op::LHU, Format::Load, LOAD).funct3(0x5),
+    Case::new(Op::SB, Format::Store, STORE).funct3(0x0),
+    Case::new(Op::SH, Format::Store, STORE).funct3(0x1),
+    Case::new(Op::SW, Format::Store, STORE).funct3(0x2),
+    Case::new(Op::ADD, Format::Immediate, OP_IMM).funct3(0x0),
+    Case::new(Op::MUL, Format::ShiftPower, OP_IMM)
+        .funct3(0x1)
+        .funct7(0x00),
+    #[cfg(feature = "zbb")]
+    Case::new(Op::CLZ, Format::Unary, OP_IMM)
+        .funct3(0x1)
+        .funct12(0x600),
+    #[cfg(feature = "zbb")]
+    Case::new(Op::CTZ, Format::Unary, OP_IMM)
+        .funct3(0x1)
+        .funct12(0x601),
+    #[cfg(feature = "zbb")]
+    Case::new(Op::CPOP, Format::Unary, OP_IMM)
+        .funct3(0x1)
+        .funct12(0x602),
+    Case::new(Op::SLT, Format::Immediate, OP_IMM).funct3(0x2),
+    Case::new(Op::SLTU, Format::Immediate, OP_IMM).funct3(0x3),
+    Case::new(Op::XOR, Format::Immediate, OP_IMM).funct3(0x4),
+    Case::new(Op::SRA, Format::Shift, OP_IMM)
+        .funct3(0x5)
+        .funct7(0x20),
+    Case::new(Op::DIVU, Format::ShiftPower, OP_IMM)
+        .funct3(0x5)
+        .funct7(0x00),
+    Case::new(Op::OR, Format::Immediate, OP_IMM).funct3(0x6),
+    Case::new(Op::AND, Format::Immediate, OP_IMM).funct3(0x7),
+    Case::new(Op::ECALL, Format::Ecall, SYSTEM)
+        .funct3(0x0)
+        .funct12(0x0),
+    Case::new(Op::ADD, Format::Nop, SYSTEM)
+        .funct3(0x0)
+        .funct12(0x302),
+    Case::new(Op::ADD, Format::Nop, SYSTEM)
+        .funct3(0x0)
+        .funct12(0x1),
+    Case::new(Op::ADD, Format::Nop, SYSTEM).funct3(0x1),
+    counter(Op::RDCYCLE, 0xC00),
+    counter(Op::ADD, 0xC80),
+    Case::new(Op::ADD, Format::CsrNop, SYSTEM).funct3(0x2),
+    Case::new(Op::ADD, Format::Nop, SYSTEM).funct3(0x5),
+    Case::new(Op::JALR, Format::Jump, 0b110_1111),
+    Case::new(Op::JALR, Format::Immediate, 0b110_0111).funct3(0x0),
+    Case::new(Op::BEQ, Format::Branch, BRANCH).funct3(0x0),
+    Case::new(Op::BNE, Format::Branch, BRANCH).funct3(0x1),
+    Case::new(Op::BLT, Format::Branch, BRANCH).funct3(0x4),
+    Case::new(Op::BGE, Format::Branch, BRANCH).funct3(0x5),
+    Case::new(Op::BLTU, Format::Branch, BRANCH).funct3(0x6),
+    Case::new(Op::BGEU, Format::Branch, BRANCH).funct3(0x7),
+    Case::new(Op::ADD, Format::Upper, 0b011_0111),
+    Case::new(Op::ADD, Format::UpperPc, 0b001_0111),
+    Case::new(Op::ADD, Format::Nop, 0b000_1111),
+    Case::new(Op::MADD, Format::Register, CUSTOM_0)
+        .funct3(0x0)
+        .funct7(0x00),
+];
+
+make_col_map!(Decode);
+/// A row proves that the word `bits` at `inst.pc` decodes to `inst`.
+#[repr(C)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
+pub struct Decode<T> {
+    /// Bits of the word, least significant first.
+    pub bits: [T; 32],
+    /// One-hot selector of the [`CASES`] the word falls into.
+    pub cases: [T; NUM_CASES],
+    pub inst: Instruction<T>,
+    /// `2^shamt` in steps of one bit of `shamt`, ie the last one is for all
+    /// five bits.
+    pub shift_powers: [T; 4],
+    /// Whether the immediate plus `pc` wrapped around `2^32`.
+    pub wraps: T,
+    /// Inverse of [`Decode::counter_distance`] for [`Format::CsrNop`], which
+    /// shows that the word is not a [`Format::Counter`].
+    pub csr_inverse: T,
+}
+
+pub const NUM_DECODE_COLS: usize = Decode::<()>::NUMBER_OF_COLUMNS;
+
+/// Operands of a decoded instruction, see [`Format`].
+#[derive(Clone, Copy, Debug)]
+pub struct Operands<T> {
+    pub rs1: T,
+    pub rs2: T,
+    pub rd: T,
+    /// Before wrapping around `2^32`.
+    pub imm: T,
+}
+
+fn word_bits(word: u32) -> [i64; 32] { core::array::from_fn(|i| i64::from((word >> i) & 1)) }
+
+/// The number of bits in which `rs1` and `funct12` of a CSR access differ
+/// from those of a [`Format::Counter`]. Zero exactly for counters.
+#[must_use]
+pub fn counter_distance<T>(bits: &[T; 32]) -> T
+where
+    T: Copy + Add<Output = T> + Sub<Output = T> + Sum + From<i64>, {
+    let rs1 = bits[15..20].iter().copied();
+    let funct12 = (0..12).filter(|i| (0xF7D >> i) & 1 == 1).map(|i| {
+        let bit = bits[20 + i];
+        if (0xC00 >> i) & 1 == 1 {
+            T::from(1) - bit
+        } else {
+            bit
+        }
+    });
+    rs1.chain(funct12).sum()
+}
+
+impl<T> Decode<T>
+where
+    T: Copy + Add<Output = T> + Sub<Output = T> + Mul<i64, Output = T> + Sum + From<i64>,
+{
+    /// The bits `range` as a number, shifted left by `shift`.
+    fn pack(&self, range: core::ops::Range<usize>, shift: usize) -> T {
+        izip!(&self.bits[range], shift..)
+            .map(|(&bit, i)| bit * (1_i64 << i))
+            .sum()
+    }
+
+    /// The sign bit, extended from bit `from` up to bit 31.
+    fn sign(&self, from: usize) -> T { self.bits[31] * ((1_i64 << 32) - (1 << from)) }
+
+    #[must_use]
+    pub fn rd(&self) -> T { self.pack(7..12, 0) }
+
+    #[must_use]
+    pub fn rs1(&self) -> T { self.pack(15..20, 0) }
+
+    #[must_use]
+    pub fn rs2(&self) -> T { self.pack(20..25, 0) }
+
+    #[must_use]
+    pub fn shamt(&self) -> T { self.rs2() }
+
+    #[must_use]
+    pub fn i_imm(&self) -> T { self.pack(20..31, 0) + self.sign(11) }
+
+    #[must_use]
+    pub fn s_imm(&self) -> T { self.pack(7..12, 0) + self.pack(25..31, 5) + self.sign(11) }
+
+    #[must_use]
+    pub fn b_offset(&self) -> T {
+        self.pack(8..12, 1) + self.pack(25..31, 5) + self.bits[7] * (1 << 11) + self.sign(12)
+    }
+
+    #[must_use]
+    pub fn j_offset(&self) -> T {
+        self.pack(21..31, 1) + self.bits[20] * (1 << 11) + self.pack(12..20, 12) + self.sign(20)
+    }
+
+    #[must_use]
+    pub fn u_imm(&self) -> T { self.pack(12..32, 12) }
+
+    #[must_use]
+    pub fn counter_distance(&self) -> T { counter_distance(&self.bits) }
+
+    /// The operands that words in `format` decode to.
+    #[must_use]
+    pub fn operands(&self, format: Format) -> Operands<T> {
+        let zero = T::from(0);
+        let pc = self.inst.pc;
+        let (rs1, rs2, rd, imm) = match format {
+            Format::Register => (self.rs1(), self.rs2(), self.rd(), zero),
+            Format::Immediate => (self.rs1(), zero, self.rd(), self.i_imm()),
+            Format::Load => (zero, self.rs1(), self.rd(), self.i_imm()),
+            Format::Store => (self.rs2(), self.rs1(), zero, self.s_imm()),
+            Format::Branch => (self.rs1(), self.rs2(), zero, self.b_offset() + pc),
+            Format::Jump => (zero, zero, self.rd(), self.j_offset() + pc),
+            Format::Upper => (zero, zero, self.rd(), self.u_imm()),
+            Format::UpperPc => (zero, zero, self.rd(), self.u_imm() + pc),
+            Format::ShiftPower => (self.rs1(), zero, self.rd(), self.shift_powers[3]),
+            Format::Shift => (self.rs1(), zero, self.rd(), self.shamt()),
+            Format::Unary => (self.rs1(), zero, self.rd(), self.rs2()),
+            Format::Ecall => (
+                T::from(i64::from(ECALL.args.rs1)),
+                T::from(i64::from(ECALL.args.rs2)),
+                T::from(i64::from(ECALL.args.rd)),
+                T::from(i64::from(ECALL.args.imm)),
+            ),
+            Format::Counter => (zero, zero, self.rd(), zero),
+            Format::Nop | Format::CsrNop => (zero, zero, zero, zero),
+        };
+        Operands { rs1, rs2, rd, imm }
+    }
+}
+
+/// The raw word at `pc`, looked up in the program ROM.
+#[must_use]
+pub fn lookup_for_program_rom() -> TableWithTypedOutput<ProgramRom<Column>> {
+    DecodeTable::new(
+        ProgramRom {
+            pc: COL_MAP.inst.pc,
+            inst_data: ColumnWithTypedInput::reduce_with_powers(COL_MAP.bits, 2),
+        },
+        ColumnWithTypedInput::constant(1),
+    )
+}
+
+/// The decoded instruction at `pc`, packed like
+/// [`lookup_for_program_rom`](crate::cpu::columns::lookup_for_program_rom)
+/// of the CPU.
+#[must_use]
+pub fn lookup_for_program_mult() -> TableWithTypedOutput<ProgramRom<Column>> {
+    let inst = COL_MAP.inst;
+    DecodeTable::new(
+        ProgramRom {
+            pc: inst.pc,
+            inst_data: ColumnWithTypedInput::reduce_with_powers(
+                [
+                    ColumnWithTypedInput::ascending_sum(inst.ops.code_terms()),
+                    inst.is_op1_signed,
+                    inst.is_op2_signed,
+                    inst.rs1_selected,
+                    inst.rs2_selected,
+                    inst.rd_selected,
+                    inst.imm_value,
+                ],
+                1 << 5,
+            ),
+        },
+        ColumnWithTypedInput::constant(1),
+    )
+}
+
+/// The immediate has to be a `u32`, so that `wraps` is unique.
+#[must_use]
+pub fn rangecheck_looking() -> Vec<TableWithTypedOutput<RangeCheckCtl<Column>>> {
+    vec![DecodeTable::new(
+        RangeCheckCtl(COL_MAP.inst.imm_value),
+        ColumnWithTypedInput::constant(1),
+    )]
+}
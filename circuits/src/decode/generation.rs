@@ -0,0 +1,56 @@
+use mozak_runner::decode::decode_instruction;
+use plonky2::field::types::Field;
+use plonky2::hash::hash_types::RichField;
+
+use super::columns::{Decode, Format, CASES};
+use crate::cpu::columns::Instruction;
+use crate::program::columns::ProgramRom;
+
+fn to_u32<F: RichField>(value: F) -> u32 {
+    u32::try_from(value.to_canonical_u64()).expect("program ROM holds u32 values")
+}
+
+fn generate_decode_row<F: RichField>(pc: u32, word: u32) -> Decode<F> {
+    let inst = decode_instruction(pc, word).expect("program ROM only holds words that decode");
+    let case = CASES
+        .iter()
+        .position(|case| case.matches(word))
+        .expect("every word that decodes has a case");
+    let shamt = (word >> 20) & 0x1F;
+
+    let mut row = Decode {
+        bits: core::array::from_fn(|i| i64::from((word >> i) & 1)),
+        inst: Instruction::from((pc, inst)).map(i64::from),
+        shift_powers: core::array::from_fn(|i| 1 << (shamt & ((4 << i) - 1))),
+        ..Decode::default()
+    };
+    row.cases[case] = 1;
+    row.wraps = i64::from(row.operands(CASES[case].format).imm != row.inst.imm_value);
+
+    let distance = row.counter_distance();
+    let mut row = row.map(F::from_noncanonical_i64);
+    if CASES[case].format == Format::CsrNop {
+        row.csr_inverse = F::from_noncanonical_i64(distance).inverse();
+    }
+    row
+}
+
+/// Generates the decode trace, one row for each row of the program ROM,
+/// which holds raw words.
+#[must_use]
+pub fn generate_decode_trace<F: RichField>(program_rom: &[ProgramRom<F>]) -> Vec<Decode<F>> {
+    program_rom
+        .iter()
+        .map(|row| generate_decode_row(to_u32(row.pc), to_u32(row.inst_data)))
+        .collect()
+}
+
+/// The decoded instructions of the program, like the program ROM would hold
+/// without the decode table.
+#[must_use]
+pub fn generate_decoded_program_rom<F: RichField>(decode_rows: &[Decode<F>]) -> Vec<ProgramRom<F>> {
+    decode_rows
+        .iter()
+        .map(|row| ProgramRom::from(row.inst))
+        .collect()
+}
@@ -0,0 +1,9 @@
+//! This module contains the **`Decode` STARK Table**.
+//!
+//! With the `decode` feature, the Program ROM holds raw instruction words
+//! instead of decoded instructions. This table proves what each word decodes
+//! to, so that the CPU looks up decoded instructions without anyone having to
+//! trust the decoder that built the ROM.
+pub mod columns;
+pub mod generation;
+pub mod stark;
@@ -0,0 +1,253 @@
+use std::marker::PhantomData;
+
+use expr::{Expr, ExprBuilder, StarkFrameTyped};
+use itertools::{chain, izip};
+use mozak_circuits_derive::StarkNameDisplay;
+use plonky2::field::extension::{Extendable, FieldExtension};
+use plonky2::field::packed::PackedField;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::ext_target::ExtensionTarget;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use starky::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
+use starky::evaluation_frame::StarkFrame;
+use starky::stark::Stark;
+
+use super::columns::{Decode, Format, CASES, NUM_DECODE_COLS};
+use crate::columns_view::{HasNamedColumns, Zip};
+use crate::cpu::columns::Instruction;
+use crate::expr::{build_ext, build_packed, ConstraintBuilder};
+use crate::unstark::NoColumns;
+
+#[derive(Copy, Clone, Default, StarkNameDisplay)]
+#[allow(clippy::module_name_repetitions)]
+pub struct DecodeStark<F, const D: usize> {
+    pub _f: PhantomData<F>,
+}
+
+impl<F, const D: usize> HasNamedColumns for DecodeStark<F, D> {
+    type Columns = Decode<F>;
+}
+
+const COLUMNS: usize = NUM_DECODE_COLS;
+const PUBLIC_INPUTS: usize = 0;
+
+fn generate_constraints<'a, T: Copy>(
+    vars: &StarkFrameTyped<Decode<Expr<'a, T>>, NoColumns<Expr<'a, T>>>,
+) -> ConstraintBuilder<Expr<'a, T>> {
+    let lv = vars.local_values;
+    let mut constraints = ConstraintBuilder::default();
+
+    for bit in lv.bits {
+        constraints.always(bit.is_binary());
+    }
+    for selector in lv.cases {
+        constraints.always(selector.is_binary());
+    }
+    constraints.always(lv.cases.into_iter().sum::<Expr<'a, T>>() - 1);
+
+    // The word agrees with the pattern of its case.
+    for (i, bit) in lv.bits.into_iter().enumerate() {
+        constraints.always(
+            izip!(lv.cases, CASES)
+                .filter(|(_, case)| (case.mask >> i) & 1 == 1)
+                .map(|(selector, case)| selector * (bit - i64::from((case.pattern >> i) & 1)))
+                .sum(),
+        );
+    }
+
+    // CSR accesses only decode as no-ops when they do not read a counter.
+    let csr_nop: Expr<'a, T> = izip!(lv.cases, CASES)
+        .filter(|(_, case)| case.format == Format::CsrNop)
+        .map(|(selector, _)| selector)
+        .sum();
+    constraints.always(csr_nop * (lv.counter_distance() * lv.csr_inverse - 1));
+
+    // Each factor is `2^(2^i)` if bit `i` of `shamt` is set, and one otherwise,
+    // so each power takes one more bit of `shamt` into account.
+    let factor = |i: usize| lv.bits[20 + i] * ((1_i64 << (1 << i)) - 1) + 1;
+    for (i, power, previous) in izip!(1.., lv.shift_powers, chain!([factor(0)], lv.shift_powers)) {
+        constraints.always(power - previous * factor(i));
+    }
+
+    constraints.always(lv.wraps.is_binary());
+    let expected = izip!(lv.cases, CASES)
+        .map(|(selector, case)| {
+            let operands = lv.operands(case.format);
+            Instruction {
+                rs1_selected: operands.rs1,
+                rs2_selected: operands.rs2,
+                rd_selected: operands.rd,
+                imm_value: operands.imm,
+                ..case.prototype().map(|column| Expr::from(i64::from(column)))
+            }
+            .map(|column| selector * column)
+        })
+        .fold(Instruction::default(), |acc, inst| {
+            acc.zip_with(inst, |a, b| a + b)
+        });
+    // The `pc` is given rather than decoded, and the immediate may wrap.
+    let decoded = Instruction {
+        imm_value: lv.inst.imm_value + lv.wraps * (1_i64 << 32),
+        ..lv.inst
+    };
+    let expected = Instruction {
+        pc: lv.inst.pc,
+        ..expected
+    };
+    for (decoded, expected) in izip!(decoded, expected) {
+        constraints.always(decoded - expected);
+    }
+
+    constraints
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for DecodeStark<F, D> {
+    type EvaluationFrame<FE, P, const D2: usize> = StarkFrame<P, P::Scalar, COLUMNS, PUBLIC_INPUTS>
+    where
+        FE: FieldExtension<D2, BaseField = F>,
+        P: PackedField<Scalar = FE>;
+    type EvaluationFrameTarget =
+        StarkFrame<ExtensionTarget<D>, ExtensionTarget<D>, COLUMNS, PUBLIC_INPUTS>;
+
+    fn eval_packed_generic<FE, P, const D2: usize>(
+        &self,
+        vars: &Self::EvaluationFrame<FE, P, D2>,
+        consumer: &mut ConstraintConsumer<P>,
+    ) where
+        FE: FieldExtension<D2, BaseField = F>,
+        P: PackedField<Scalar = FE>, {
+        let eb = ExprBuilder::default();
+        let constraints = generate_constraints(&eb.to_typed_starkframe(vars));
+        build_packed(constraints, consumer);
+    }
+
+    fn eval_ext_circuit(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        vars: &Self::EvaluationFrameTarget,
+        consumer: &mut RecursiveConstraintConsumer<F, D>,
+    ) {
+        let eb = ExprBuilder::default();
+        let constraints = generate_constraints(&eb.to_typed_starkframe(vars));
+        build_ext(constraints, builder, consumer);
+    }
+
+    fn constraint_degree(&self) -> usize { 3 }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use im::hashmap::HashMap;
+    use itertools::izip;
+    use mozak_runner::decode::decode_instruction;
+    use mozak_runner::elf::Program;
+    use mozak_runner::state::State;
+    use mozak_runner::vm::step;
+    use plonky2::field::types::Field;
+    use plonky2::plonk::config::{GenericConfig, Poseidon2GoldilocksConfig};
+    use plonky2::util::timing::TimingTree;
+    use proptest::prelude::{any, ProptestConfig};
+    use proptest::{collection, proptest};
+    use starky::prover::prove;
+    use starky::stark_testing::{test_stark_circuit_constraints, test_stark_low_degree};
+    use starky::verifier::verify_stark_proof;
+
+    use super::DecodeStark;
+    use crate::decode::columns::{Decode, CASES, NUM_CASES};
+    use crate::decode::generation::generate_decode_trace;
+    use crate::generation::debug_single_trace;
+    use crate::program::columns::ProgramRom;
+    use crate::stark::mozak_stark::MozakStark;
+    use crate::stark::utils::trace_rows_to_poly_values;
+    use crate::test_utils::{fast_test_config, ProveAndVerify};
+    use crate::utils::pad_trace_with_last;
+
+    const D: usize = 2;
+    type C = Poseidon2GoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+    type S = DecodeStark<F, D>;
+
+    /// One word for each case, with the bits that the case leaves open taken
+    /// from `noise`.
+    fn decode_rows(pc: u32, noise: &[u32]) -> Vec<Decode<F>> {
+        let rom = izip!(0.., CASES, noise)
+            .map(|(i, case, noise)| ProgramRom {
+                pc: F::from_canonical_u32(pc.wrapping_add(4 * i)),
+                inst_data: F::from_canonical_u32((noise & !case.mask) | case.pattern),
+            })
+            .collect();
+        generate_decode_trace(&pad_trace_with_last(rom))
+    }
+
+    fn prove_decode(pc: u32, noise: &[u32]) -> Result<()> {
+        let config = fast_test_config();
+        let stark = S::default();
+        let trace = trace_rows_to_poly_values(decode_rows(pc, noise));
+        let proof = prove::<F, C, S, D>(stark, &config, trace, &[], &mut TimingTree::default())?;
+        verify_stark_proof(stark, proof, &config)
+    }
+
+    proptest! {
+        #[test]
+        fn cases_cover_exactly_the_words_that_decode(pc in any::<u32>(), word in any::<u32>()) {
+            assert_eq!(
+                decode_instruction(pc, word).is_ok(),
+                CASES.iter().any(|case| case.matches(word))
+            );
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(4))]
+        #[test]
+        fn prove_decode_proptest(
+            pc in any::<u32>(),
+            noise in collection::vec(any::<u32>(), NUM_CASES),
+        ) {
+            prove_decode(pc & !3, &noise).unwrap();
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "debug_api_has_constraint_failed")]
+    fn wrong_register_fails() {
+        let mut rows = decode_rows(0, &[0x0123_4567; NUM_CASES]);
+        rows[0].inst.rd_selected += F::ONE;
+        debug_single_trace::<F, D, _>(&S::default(), &trace_rows_to_poly_values(rows), &[]);
+    }
+
+    /// Runs a hand-assembled program, whose code is in memory as words.
+    #[test]
+    fn prove_program_with_mozak_stark() {
+        let code = [
+            0x1234_55b7, // lui a1, 0x12345
+            0x0000_1617, // auipc a2, 1
+            0x0035_9593, // slli a1, a1, 3
+            0x4026_5613, // srai a2, a2, 2
+            0xc000_26f3, // rdcycle a3
+            0x0000_0463, // beq zero, zero, 8
+            0x0010_0513, // li a0, 1
+            0x0000_0513, // li a0, 0
+            0x0000_0073, // ecall
+        ];
+        let image: HashMap<u32, u32> = izip!((0..).step_by(4), code).collect();
+        let program = Program::from(image);
+        let record = step(&program, State::from(program.clone())).unwrap();
+        assert!(record.last_state.has_halted());
+        MozakStark::prove_and_verify(&program, &record).unwrap();
+    }
+
+    #[test]
+    fn test_degree() -> Result<()> {
+        let stark = S::default();
+        test_stark_low_degree(stark)
+    }
+
+    #[test]
+    fn test_circuit() -> Result<()> {
+        let stark = S::default();
+        test_stark_circuit_constraints::<F, C, S, D>(stark)?;
+        Ok(())
+    }
+}
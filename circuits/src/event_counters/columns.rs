@@ -0,0 +1,106 @@
+use anyhow::{anyhow, ensure, Result};
+use plonky2::hash::hash_types::RichField;
+
+use crate::columns_view::{make_col_map, ColumnsView};
+use crate::linear_combination::Column;
+use crate::public_sub_table::{PublicSubTable, PublicValue};
+use crate::stark::mozak_stark::{EventCountersTable, TableWithTypedOutput};
+
+/// Architectural events tracked by the [`EventCounters`] table.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u8)]
+pub enum EventKind {
+    /// Conditional branches whose condition held.
+    BranchTaken = 0,
+    MemoryLoad = 1,
+    MemoryStore = 2,
+    Ecall = 3,
+}
+
+pub const NUM_EVENT_KINDS: usize = 4;
+
+impl EventKind {
+    pub const ALL: [Self; NUM_EVENT_KINDS] = [
+        Self::BranchTaken,
+        Self::MemoryLoad,
+        Self::MemoryStore,
+        Self::Ecall,
+    ];
+}
+
+make_col_map!(EVENT_COUNTERS, EventCounters);
+/// One row per [`EventKind`], holding the number of times that event occurred
+/// during execution. The `count` is the multiplicity with which the row is
+/// looked up by the tables that perform the events, so it can not differ from
+/// the actual number of events.
+///
+/// The counter rows come first, one per kind in the order of
+/// [`EventKind::ALL`], so each kind is counted exactly once.
+#[repr(C)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
+pub struct EventCounters<T> {
+    pub kind: T,
+    pub count: T,
+    /// 1 for the counter rows, 0 for padding.
+    pub is_counter_row: T,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
+pub struct EventCounterCtl<T> {
+    pub kind: T,
+}
+
+#[must_use]
+pub fn lookup_for_events() -> TableWithTypedOutput<EventCounterCtl<Column>> {
+    EventCountersTable::new(
+        EventCounterCtl {
+            kind: EVENT_COUNTERS.kind,
+        },
+        EVENT_COUNTERS.count,
+    )
+}
+
+/// Number of times each [`EventKind`] occurred during execution, as a
+/// [`PublicValue`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EventCounts(pub [u64; NUM_EVENT_KINDS]);
+
+impl EventCounts {
+    #[must_use]
+    pub fn count(&self, kind: EventKind) -> u64 { self.0[kind as usize] }
+}
+
+impl<F: RichField> PublicValue<F> for EventCounts {
+    fn public_sub_table() -> PublicSubTable {
+        PublicSubTable {
+            table: EventCountersTable::new(
+                vec![EVENT_COUNTERS.kind, EVENT_COUNTERS.count],
+                EVENT_COUNTERS.is_counter_row,
+            ),
+            num_rows: NUM_EVENT_KINDS,
+        }
+    }
+
+    fn from_rows(rows: &[Vec<F>]) -> Result<Self> {
+        ensure!(
+            rows.len() == NUM_EVENT_KINDS,
+            "expected {NUM_EVENT_KINDS} counter rows, got {}",
+            rows.len()
+        );
+        let mut counts = [None; NUM_EVENT_KINDS];
+        for row in rows {
+            let [kind, count] = row[..] else {
+                return Err(anyhow!("expected a kind and a count, got {row:?}"));
+            };
+            let kind = kind.to_canonical_u64();
+            let slot = usize::try_from(kind)
+                .ok()
+                .and_then(|kind| counts.get_mut(kind))
+                .ok_or_else(|| anyhow!("unknown event kind {kind}"))?;
+            ensure!(slot.is_none(), "event kind {kind} counted twice");
+            *slot = Some(count.to_canonical_u64());
+        }
+        Ok(Self(counts.map(|count| count.unwrap_or_default())))
+    }
+}
@@ -0,0 +1,34 @@
+use itertools::Itertools;
+use plonky2::hash::hash_types::RichField;
+
+use crate::cpu::columns::CpuState;
+use crate::event_counters::columns::{EventCounters, EventKind};
+use crate::ops::blt_taken::columns::BltTaken;
+use crate::utils::pad_trace_with_default;
+
+/// Counts the events in the traces of the tables that look up
+/// [`EventCounters`], using the same filters as their lookups.
+#[must_use]
+pub fn generate_event_counters_trace<F: RichField>(
+    cpu_rows: &[CpuState<F>],
+    blt_taken_rows: &[BltTaken<F>],
+) -> Vec<EventCounters<F>> {
+    let count = |filter: fn(&CpuState<F>) -> F| cpu_rows.iter().map(filter).sum::<F>();
+    let trace = EventKind::ALL
+        .into_iter()
+        .map(|kind| EventCounters {
+            kind: F::from_canonical_u8(kind as u8),
+            count: match kind {
+                EventKind::BranchTaken =>
+                    count(|row| row.branch_taken)
+                        + blt_taken_rows.iter().map(|row| row.is_running).sum::<F>(),
+                EventKind::MemoryLoad => count(|row| row.inst.ops.load_ops()),
+                EventKind::MemoryStore => count(|row| row.inst.ops.store_ops()),
+                EventKind::Ecall => count(|row| row.inst.ops.ecall),
+            },
+            is_counter_row: F::ONE,
+        })
+        .collect_vec();
+    tracing::trace!("EventCounters trace {trace:?}");
+    pad_trace_with_default(trace)
+}
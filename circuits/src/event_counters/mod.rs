@@ -0,0 +1,12 @@
+//! This module contains the **`EventCounters` STARK Table**.
+//!
+//! It accumulates architectural event counts (taken branches, memory loads
+//! and stores, and ecalls) over the whole execution. The counts are tied to
+//! the CPU tables via CTL multiplicities. Applications that want to prove
+//! claims about resource usage, e.g. "this execution performed exactly N
+//! memory stores", make them public as
+//! [`EventCounts`](columns::EventCounts). Otherwise a proof may leave the
+//! table out when nothing happened to count.
+pub mod columns;
+pub mod generation;
+pub mod stark;
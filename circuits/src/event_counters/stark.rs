@@ -0,0 +1,233 @@
+use std::marker::PhantomData;
+
+use expr::{Expr, ExprBuilder, StarkFrameTyped};
+use mozak_circuits_derive::StarkNameDisplay;
+use plonky2::field::extension::{Extendable, FieldExtension};
+use plonky2::field::packed::PackedField;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::ext_target::ExtensionTarget;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use starky::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
+use starky::evaluation_frame::StarkFrame;
+use starky::stark::Stark;
+
+use super::columns::{EventCounters, NUM_EVENT_KINDS};
+use crate::columns_view::{HasNamedColumns, NumberOfColumns};
+use crate::expr::{build_ext, build_packed, ConstraintBuilder};
+use crate::unstark::NoColumns;
+
+fn generate_constraints<'a, T: Copy>(
+    vars: &StarkFrameTyped<EventCounters<Expr<'a, T>>, NoColumns<Expr<'a, T>>>,
+) -> ConstraintBuilder<Expr<'a, T>> {
+    let lv: &EventCounters<Expr<'a, T>> = &vars.local_values;
+    let nv: &EventCounters<Expr<'a, T>> = &vars.next_values;
+    let last_kind = i64::try_from(NUM_EVENT_KINDS - 1).unwrap();
+    let mut constraint = ConstraintBuilder::default();
+    constraint.always(lv.is_counter_row.is_binary());
+    // Only the counter rows may absorb events, otherwise a prover could hide
+    // events in padding.
+    constraint.always(lv.count * (1 - lv.is_counter_row));
+
+    // The counter rows run through the kinds in order, starting from the
+    // first row. Padding alone satisfies this, as its kind is zero.
+    constraint.first_row(lv.kind);
+    constraint.transition(nv.is_counter_row * (1 - lv.is_counter_row));
+    constraint.transition(nv.is_counter_row * (nv.kind - lv.kind - 1));
+    // ... and stop at the last kind.
+    constraint.transition(lv.is_counter_row * (1 - nv.is_counter_row) * (lv.kind - last_kind));
+    constraint.last_row(lv.is_counter_row * (lv.kind - last_kind));
+    constraint
+}
+
+#[derive(Copy, Clone, Default, StarkNameDisplay)]
+#[allow(clippy::module_name_repetitions)]
+pub struct EventCountersStark<F, const D: usize> {
+    pub _f: PhantomData<F>,
+}
+
+impl<F, const D: usize> HasNamedColumns for EventCountersStark<F, D> {
+    type Columns = EventCounters<F>;
+}
+
+const COLUMNS: usize = EventCounters::<()>::NUMBER_OF_COLUMNS;
+const PUBLIC_INPUTS: usize = 0;
+
+impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for EventCountersStark<F, D> {
+    type EvaluationFrame<FE, P, const D2: usize> = StarkFrame<P, P::Scalar, COLUMNS, PUBLIC_INPUTS>
+
+        where
+            FE: FieldExtension<D2, BaseField = F>,
+            P: PackedField<Scalar = FE>;
+    type EvaluationFrameTarget =
+        StarkFrame<ExtensionTarget<D>, ExtensionTarget<D>, COLUMNS, PUBLIC_INPUTS>;
+
+    fn eval_packed_generic<FE, P, const D2: usize>(
+        &self,
+        vars: &Self::EvaluationFrame<FE, P, D2>,
+        consumer: &mut ConstraintConsumer<P>,
+    ) where
+        FE: FieldExtension<D2, BaseField = F>,
+        P: PackedField<Scalar = FE>, {
+        let eb = ExprBuilder::default();
+        let constraints = generate_constraints(&eb.to_typed_starkframe(vars));
+        build_packed(constraints, consumer);
+    }
+
+    fn constraint_degree(&self) -> usize { 3 }
+
+    fn eval_ext_circuit(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        vars: &Self::EvaluationFrameTarget,
+        consumer: &mut RecursiveConstraintConsumer<F, D>,
+    ) {
+        let eb = ExprBuilder::default();
+        let constraints = generate_constraints(&eb.to_typed_starkframe(vars));
+        build_ext(constraints, builder, consumer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU64;
+
+    use mozak_runner::code::{self, Code};
+    use mozak_runner::elf::Program;
+    use mozak_runner::instruction::{Args, Instruction, Op};
+    use mozak_runner::state::{RawTapes, State};
+    use mozak_runner::vm::{step, ExecutionRecord};
+    use plonky2::field::types::Field;
+    use plonky2::util::timing::TimingTree;
+    use starky::stark_testing::test_stark_circuit_constraints;
+
+    use crate::cpu::generation::generate_cpu_trace;
+    use crate::event_counters::columns::{EventCounts, EventKind};
+    use crate::event_counters::generation::generate_event_counters_trace;
+    use crate::generation::debug_single_trace;
+    use crate::ops;
+    use crate::stark::mozak_stark::{MozakStark, PublicInputs, TableKind};
+    use crate::stark::prover::prove;
+    use crate::stark::utils::trace_rows_to_poly_values;
+    use crate::stark::verifier::verify_proof;
+    use crate::test_utils::{fast_test_config, ProveAndVerify, C, D, F};
+
+    type S = super::EventCountersStark<F, D>;
+
+    fn events_code() -> Vec<Instruction> {
+        let address = 0x100;
+        vec![
+            Instruction::sb(1, 0, address),
+            Instruction::lbu(3, 0, address),
+            // Taken, handled by the CPU table.
+            Instruction::beq(1, 1, 16).unwrap(),
+            Instruction::li(4, 10),
+            // Taken, handled by the `BltTaken` table.
+            Instruction::bltu(0, 1, 24).unwrap(),
+            Instruction::li(4, 10),
+        ]
+    }
+
+    #[test]
+    fn count_events() {
+        let (_program, record) = code::execute(events_code(), &[(0x100, 0)], &[(1, 7)]);
+        let cpu_rows = generate_cpu_trace::<F>(&record);
+        let blt_taken_rows = ops::blt_taken::generate(&record);
+        let trace = generate_event_counters_trace(&cpu_rows, &blt_taken_rows);
+
+        let counts = |kind: EventKind| trace[kind as usize].count;
+        assert_eq!(counts(EventKind::BranchTaken), F::from_canonical_u8(2));
+        assert_eq!(counts(EventKind::MemoryLoad), F::ONE);
+        assert_eq!(counts(EventKind::MemoryStore), F::ONE);
+        // Only the final halt.
+        assert_eq!(counts(EventKind::Ecall), F::ONE);
+    }
+
+    #[test]
+    fn prove_event_counters() -> anyhow::Result<()> {
+        let (program, record) = code::execute(events_code(), &[(0x100, 0)], &[(1, 7)]);
+        MozakStark::prove_and_verify(&program, &record)
+    }
+
+    #[test]
+    fn prove_event_counts() -> anyhow::Result<()> {
+        let (program, record) = code::execute(events_code(), &[(0x100, 0)], &[(1, 7)]);
+        let stark = MozakStark::default().with_public_value::<EventCounts>();
+        let config = fast_test_config();
+        let all_proof = prove::<F, C, D>(
+            &program,
+            &record,
+            &stark,
+            &config,
+            PublicInputs::for_execution(&program, &record),
+            &mut TimingTree::default(),
+        )?;
+        assert_eq!(
+            all_proof.public_value::<EventCounts>(&stark)?,
+            EventCounts([2, 1, 1, 1])
+        );
+        verify_proof(&stark, all_proof, &config)
+    }
+
+    #[test]
+    #[should_panic(expected = "debug_api_has_constraint_failed")]
+    fn counting_a_kind_twice_fails() {
+        let (_program, record) = code::execute(events_code(), &[(0x100, 0)], &[(1, 7)]);
+        let cpu_rows = generate_cpu_trace::<F>(&record);
+        let blt_taken_rows = ops::blt_taken::generate(&record);
+        let mut trace = generate_event_counters_trace(&cpu_rows, &blt_taken_rows);
+        trace[1].kind = trace[0].kind;
+        debug_single_trace::<F, D, _>(&S::default(), &trace_rows_to_poly_values(trace), &[]);
+    }
+
+    #[test]
+    #[should_panic(expected = "debug_api_has_constraint_failed")]
+    fn counting_an_unknown_kind_fails() {
+        let (_program, record) = code::execute(events_code(), &[(0x100, 0)], &[(1, 7)]);
+        let cpu_rows = generate_cpu_trace::<F>(&record);
+        let blt_taken_rows = ops::blt_taken::generate(&record);
+        let mut trace = generate_event_counters_trace(&cpu_rows, &blt_taken_rows);
+        trace[4].kind = trace[3].kind + F::ONE;
+        trace[4].is_counter_row = F::ONE;
+        debug_single_trace::<F, D, _>(&S::default(), &trace_rows_to_poly_values(trace), &[]);
+    }
+
+    /// Jumps in an endless loop, until its budget runs out, so neither halts
+    /// nor causes any other event.
+    fn loop_without_events() -> (Program, ExecutionRecord<F>) {
+        let code = [Instruction::new(Op::JALR, Args::default())];
+        let program = Program::create(&[], &[], Code((0..).step_by(4).zip(code.map(Ok)).collect()));
+        let state = State::new(program.clone(), RawTapes::default())
+            .with_max_cycles(NonZeroU64::new(5).unwrap());
+        let record = step(&program, state).unwrap();
+        (program, record)
+    }
+
+    #[test]
+    fn skip_without_events() -> anyhow::Result<()> {
+        let (program, record) = loop_without_events();
+        let config = fast_test_config();
+        let all_proof = prove::<F, C, D>(
+            &program,
+            &record,
+            &MozakStark::default().skipping_unused_tables(),
+            &config,
+            PublicInputs::for_execution(&program, &record),
+            &mut TimingTree::default(),
+        )?;
+        assert!(all_proof.skipped[TableKind::EventCounters]);
+        verify_proof(&MozakStark::default(), all_proof, &config)?;
+
+        // Public counts need the table.
+        let stark = MozakStark::<F, D>::default().with_public_value::<EventCounts>();
+        assert!(!stark.can_skip(TableKind::EventCounters));
+        Ok(())
+    }
+
+    #[test]
+    fn test_circuit() -> anyhow::Result<()> {
+        let stark = S::default();
+        test_stark_circuit_constraints::<F, C, S, D>(stark)?;
+
+        Ok(())
+    }
+}
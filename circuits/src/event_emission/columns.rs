@@ -0,0 +1,144 @@
+use core::ops::{Add, Mul};
+
+use itertools::{chain, izip};
+use mozak_sdk::core::constants::{DIGEST_BYTES, EVENT_BYTES, EVENT_TYPES, EVENT_TYPE_OFFSET};
+use mozak_sdk::core::reg_abi::{REG_A1, REG_A2};
+
+use crate::columns_view::{make_col_map, ColumnsView, NumberOfColumns};
+use crate::cross_table_lookup::ColumnWithTypedInput;
+use crate::linear_combination::Column;
+use crate::memory::columns::MemoryCtl;
+use crate::rangecheck::columns::RangeCheckCtl;
+use crate::register::RegisterCtl;
+use crate::stark::mozak_stark::{EventEmissionTable, TableWithTypedOutput};
+
+/// How the key of the next event compares to the key of this one. Keys are
+/// the address of the object, byte by byte, and then the event type, which is
+/// the canonical order of events.
+#[repr(C)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
+pub struct Order<T> {
+    /// The first four bytes of the address increase.
+    pub high_increases: T,
+    /// The first four bytes of the address are equal, and the last four
+    /// increase.
+    pub low_increases: T,
+    /// The addresses are equal, and the event type does not decrease.
+    pub same_object: T,
+    /// The increase, less one when it has to be strict. Range checked, which
+    /// shows that the key does not decrease.
+    pub difference: T,
+}
+
+make_col_map!(EventEmission);
+/// A row emits the event of [`EVENT_BYTES`] bytes at `addr`. Events are in
+/// canonical order, with padding at the end.
+#[repr(C)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
+pub struct EventEmission<T> {
+    pub is_executed: T,
+    pub clk: T,
+    pub addr: T,
+    /// The address of the object the event is about.
+    pub object: [T; EVENT_TYPE_OFFSET],
+    /// One-hot encoding of the event type.
+    pub event_type: [T; EVENT_TYPES],
+    /// The hash of the object's data.
+    pub value: [T; DIGEST_BYTES],
+    /// Compares this event to the next one, if any.
+    pub order: Order<T>,
+}
+
+pub const NUM_EVENT_EMISSION_COLS: usize = EventEmission::<()>::NUMBER_OF_COLUMNS;
+
+impl<T: Copy + Add<Output = T> + Mul<i64, Output = T>> EventEmission<T> {
+    /// The event type as a byte.
+    pub fn event_type(&self) -> T {
+        izip!(1.., &self.event_type[1..]).fold(self.event_type[0] * 0, |acc, (i, &selector)| {
+            acc + selector * i
+        })
+    }
+
+    /// The first four bytes of the object's address, big-endian.
+    pub fn high(&self) -> T { big_endian(&self.object[..EVENT_TYPE_OFFSET / 2]) }
+
+    /// The last four bytes of the object's address, big-endian.
+    pub fn low(&self) -> T { big_endian(&self.object[EVENT_TYPE_OFFSET / 2..]) }
+
+    /// The bytes of the event, as laid out in memory.
+    pub fn bytes(&self) -> impl Iterator<Item = T> {
+        chain!(self.object, [self.event_type()], self.value)
+    }
+}
+
+fn big_endian<T: Copy + Add<Output = T> + Mul<i64, Output = T>>(bytes: &[T]) -> T {
+    bytes[1..]
+        .iter()
+        .fold(bytes[0], |acc, &byte| acc * (1 << 8) + byte)
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
+pub struct EventEmissionCtl<T> {
+    pub clk: T,
+}
+
+#[must_use]
+pub fn lookup_for_cpu() -> TableWithTypedOutput<EventEmissionCtl<Column>> {
+    EventEmissionTable::new(EventEmissionCtl { clk: COL_MAP.clk }, COL_MAP.is_executed)
+}
+
+/// Reads the address of the event from `a1`, and checks that its length in
+/// `a2` is [`EVENT_BYTES`].
+#[must_use]
+pub fn register_looking() -> Vec<TableWithTypedOutput<RegisterCtl<Column>>> {
+    let is_read = ColumnWithTypedInput::constant(1);
+    vec![
+        EventEmissionTable::new(
+            RegisterCtl {
+                clk: COL_MAP.clk,
+                op: is_read,
+                value: COL_MAP.addr,
+                addr: ColumnWithTypedInput::constant(REG_A1.into()),
+            },
+            COL_MAP.is_executed,
+        ),
+        EventEmissionTable::new(
+            RegisterCtl {
+                clk: COL_MAP.clk,
+                op: is_read,
+                value: ColumnWithTypedInput::constant(
+                    i64::try_from(EVENT_BYTES).expect("events are short"),
+                ),
+                addr: ColumnWithTypedInput::constant(REG_A2.into()),
+            },
+            COL_MAP.is_executed,
+        ),
+    ]
+}
+
+/// Loads of the bytes of the event. The memory table range checks them, and
+/// the one-hot encoding keeps the event type below [`EVENT_TYPES`].
+pub fn lookup_for_memory() -> impl Iterator<Item = TableWithTypedOutput<MemoryCtl<Column>>> {
+    izip!(0.., COL_MAP.bytes()).map(|(i, value)| {
+        EventEmissionTable::new(
+            MemoryCtl {
+                clk: COL_MAP.clk,
+                is_store: ColumnWithTypedInput::constant(0),
+                is_load: ColumnWithTypedInput::constant(1),
+                value,
+                addr: COL_MAP.addr + i,
+            },
+            COL_MAP.is_executed,
+        )
+    })
+}
+
+/// The difference between the keys of two consecutive events.
+#[must_use]
+pub fn rangecheck_looking() -> Vec<TableWithTypedOutput<RangeCheckCtl<Column>>> {
+    vec![EventEmissionTable::new(
+        RangeCheckCtl(COL_MAP.order.difference),
+        COL_MAP.is_executed.flip(),
+    )]
+}
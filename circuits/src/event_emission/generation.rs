@@ -0,0 +1,98 @@
+use mozak_runner::state::EventEntry;
+use mozak_runner::vm::Row;
+use mozak_sdk::core::constants::{EVENT_TYPES, EVENT_TYPE_OFFSET};
+use plonky2::hash::hash_types::RichField;
+
+use super::columns::{EventEmission, Order};
+use crate::utils::pad_trace_with_default;
+
+/// The key that events are ordered by: the halves of the address of the
+/// object, big-endian, and the event type.
+fn key(event: &EventEntry) -> (u64, u64, u64) {
+    let half = |bytes: &[u8]| u64::from(u32::from_be_bytes(bytes.try_into().unwrap()));
+    (
+        half(&event.event[..EVENT_TYPE_OFFSET / 2]),
+        half(&event.event[EVENT_TYPE_OFFSET / 2..EVENT_TYPE_OFFSET]),
+        u64::from(event.event[EVENT_TYPE_OFFSET]),
+    )
+}
+
+/// Compares the key of an event to the key of the next one.
+///
+/// # Panics
+///
+/// Panics if the next key is smaller.
+fn order<F: RichField>(key: (u64, u64, u64), next: (u64, u64, u64)) -> Order<F> {
+    let difference = |value: u64, next_value: u64, strict: u64| {
+        F::from_canonical_u64(
+            next_value
+                .checked_sub(value + strict)
+                .expect("events are not in canonical order"),
+        )
+    };
+    let ((high, low, event_type), (next_high, next_low, next_event_type)) = (key, next);
+    if high != next_high {
+        Order {
+            high_increases: F::ONE,
+            difference: difference(high, next_high, 1),
+            ..Order::default()
+        }
+    } else if low != next_low {
+        Order {
+            low_increases: F::ONE,
+            difference: difference(low, next_low, 1),
+            ..Order::default()
+        }
+    } else {
+        Order {
+            same_object: F::ONE,
+            difference: difference(event_type, next_event_type, 0),
+            ..Order::default()
+        }
+    }
+}
+
+fn generate_row<F: RichField>(
+    clk: u64,
+    entry: &EventEntry,
+    next: Option<&EventEntry>,
+) -> EventEmission<F> {
+    let (object, rest) = entry.event.split_at(EVENT_TYPE_OFFSET);
+    let (&event_type, value) = rest.split_first().unwrap();
+    assert!(
+        usize::from(event_type) < EVENT_TYPES,
+        "unknown event type {event_type}"
+    );
+    EventEmission {
+        is_executed: F::ONE,
+        clk: F::from_canonical_u64(clk),
+        addr: F::from_canonical_u32(entry.addr),
+        object: core::array::from_fn(|i| F::from_canonical_u8(object[i])),
+        event_type: core::array::from_fn(|i| F::from_bool(i == usize::from(event_type))),
+        value: core::array::from_fn(|i| F::from_canonical_u8(value[i])),
+        order: next.map_or_else(Order::default, |next| order(key(entry), key(next))),
+    }
+}
+
+/// Generates the event emission trace, one row for each emitted event.
+///
+/// # Panics
+///
+/// Panics if events were not emitted in canonical order, or have an event type
+/// of [`EVENT_TYPES`] or above.
+#[must_use]
+pub fn generate_event_emission_trace<F: RichField>(step_rows: &[Row<F>]) -> Vec<EventEmission<F>> {
+    let events: Vec<_> = step_rows
+        .iter()
+        .filter_map(|row| Some((row.state.clk, row.aux.event.as_ref()?)))
+        .collect();
+    pad_trace_with_default(
+        events
+            .iter()
+            .enumerate()
+            .map(|(i, &(clk, entry))| {
+                generate_row(clk, entry, events.get(i + 1).map(|&(_, next)| next))
+            })
+            .collect(),
+    )
+}
@@ -0,0 +1,9 @@
+//! This module contains the **`EventEmission` STARK Table**.
+//!
+//! Guests emit the events of their event tape with the `EMIT_EVENT` ecall, in
+//! canonical order. This table checks that each event is well-formed, and
+//! that the events are ordered by the address of their object, and then by
+//! event type, as the SDK orders them to commit to the event tape.
+pub mod columns;
+pub mod generation;
+pub mod stark;
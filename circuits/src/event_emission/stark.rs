@@ -0,0 +1,180 @@
+use std::marker::PhantomData;
+
+use expr::{Expr, ExprBuilder, StarkFrameTyped};
+use mozak_circuits_derive::StarkNameDisplay;
+use plonky2::field::extension::{Extendable, FieldExtension};
+use plonky2::field::packed::PackedField;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::ext_target::ExtensionTarget;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use starky::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
+use starky::evaluation_frame::StarkFrame;
+use starky::stark::Stark;
+
+use super::columns::{EventEmission, NUM_EVENT_EMISSION_COLS};
+use crate::columns_view::HasNamedColumns;
+use crate::expr::{build_ext, build_packed, ConstraintBuilder};
+use crate::unstark::NoColumns;
+
+#[derive(Copy, Clone, Default, StarkNameDisplay)]
+#[allow(clippy::module_name_repetitions)]
+pub struct EventEmissionStark<F, const D: usize> {
+    pub _f: PhantomData<F>,
+}
+
+impl<F, const D: usize> HasNamedColumns for EventEmissionStark<F, D> {
+    type Columns = EventEmission<F>;
+}
+
+const COLUMNS: usize = NUM_EVENT_EMISSION_COLS;
+const PUBLIC_INPUTS: usize = 0;
+
+fn generate_constraints<'a, T: Copy>(
+    vars: &StarkFrameTyped<EventEmission<Expr<'a, T>>, NoColumns<Expr<'a, T>>>,
+) -> ConstraintBuilder<Expr<'a, T>> {
+    let lv = vars.local_values;
+    let nv = vars.next_values;
+    let mut constraints = ConstraintBuilder::default();
+
+    constraints.always(lv.is_executed.is_binary());
+    // Events come first, and padding last.
+    constraints.transition(nv.is_executed * (nv.is_executed - lv.is_executed));
+
+    // Events have exactly one event type, padding has none.
+    for selector in lv.event_type {
+        constraints.always(selector.is_binary());
+    }
+    constraints.always(lv.event_type.into_iter().sum::<Expr<'a, T>>() - lv.is_executed);
+
+    // Exactly one comparison holds between an event and the next one.
+    let order = lv.order;
+    for selector in [order.high_increases, order.low_increases, order.same_object] {
+        constraints.always(selector.is_binary());
+    }
+    constraints.transition(
+        order.high_increases + order.low_increases + order.same_object - nv.is_executed,
+    );
+    constraints.transition(order.low_increases * (nv.high() - lv.high()));
+    constraints.transition(order.same_object * (nv.high() - lv.high()));
+    constraints.transition(order.same_object * (nv.low() - lv.low()));
+    // Halves of the address and event types are far below the field size, so a
+    // range checked difference shows that the key does not decrease.
+    constraints.transition(
+        order.difference
+            - order.high_increases * (nv.high() - lv.high() - 1)
+            - order.low_increases * (nv.low() - lv.low() - 1)
+            - order.same_object * (nv.event_type() - lv.event_type()),
+    );
+
+    constraints
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for EventEmissionStark<F, D> {
+    type EvaluationFrame<FE, P, const D2: usize> = StarkFrame<P, P::Scalar, COLUMNS, PUBLIC_INPUTS>
+    where
+        FE: FieldExtension<D2, BaseField = F>,
+        P: PackedField<Scalar = FE>;
+    type EvaluationFrameTarget =
+        StarkFrame<ExtensionTarget<D>, ExtensionTarget<D>, COLUMNS, PUBLIC_INPUTS>;
+
+    fn eval_packed_generic<FE, P, const D2: usize>(
+        &self,
+        vars: &Self::EvaluationFrame<FE, P, D2>,
+        consumer: &mut ConstraintConsumer<P>,
+    ) where
+        FE: FieldExtension<D2, BaseField = F>,
+        P: PackedField<Scalar = FE>, {
+        let eb = ExprBuilder::default();
+        let constraints = generate_constraints(&eb.to_typed_starkframe(vars));
+        build_packed(constraints, consumer);
+    }
+
+    fn eval_ext_circuit(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        vars: &Self::EvaluationFrameTarget,
+        consumer: &mut RecursiveConstraintConsumer<F, D>,
+    ) {
+        let eb = ExprBuilder::default();
+        let constraints = generate_constraints(&eb.to_typed_starkframe(vars));
+        build_ext(constraints, builder, consumer);
+    }
+
+    fn constraint_degree(&self) -> usize { 3 }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use plonky2::field::types::Field;
+    use plonky2::plonk::config::{GenericConfig, Poseidon2GoldilocksConfig};
+    use plonky2::util::timing::TimingTree;
+    use starky::prover::prove;
+    use starky::stark_testing::{test_stark_circuit_constraints, test_stark_low_degree};
+    use starky::verifier::verify_stark_proof;
+
+    use super::EventEmissionStark;
+    use crate::event_emission::generation::generate_event_emission_trace;
+    use crate::generation::debug_single_trace;
+    use crate::stark::mozak_stark::MozakStark;
+    use crate::stark::utils::trace_rows_to_poly_values;
+    use crate::test_utils::{event_emission_test, fast_test_config, ProveAndVerify};
+
+    const D: usize = 2;
+    type C = Poseidon2GoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+    type S = EventEmissionStark<F, D>;
+
+    /// A write and a read of one object, and a read of another one.
+    const EVENTS: [([u8; 8], u8); 3] = [
+        ([0, 0, 0, 1, 0, 0, 0, 0], 0),
+        ([0, 0, 0, 1, 0, 0, 0, 0], 2),
+        ([0, 0, 0, 1, 0, 0, 1, 0], 2),
+    ];
+
+    #[test]
+    fn prove_events() -> Result<()> {
+        let (_program, record) = event_emission_test(&EVENTS);
+        let config = fast_test_config();
+        let stark = S::default();
+        let trace = trace_rows_to_poly_values(generate_event_emission_trace(&record.executed));
+        let proof = prove::<F, C, S, D>(stark, &config, trace, &[], &mut TimingTree::default())?;
+        verify_stark_proof(stark, proof, &config)
+    }
+
+    #[test]
+    #[should_panic(expected = "events are not in canonical order")]
+    fn out_of_order_events_do_not_generate() {
+        let (_program, record) = event_emission_test(&[EVENTS[1], EVENTS[0]]);
+        let _ = generate_event_emission_trace::<F>(&record.executed);
+    }
+
+    #[test]
+    #[should_panic(expected = "debug_api_has_constraint_failed")]
+    fn out_of_order_events_fail() {
+        let (_program, record) = event_emission_test(&EVENTS);
+        let mut rows = generate_event_emission_trace(&record.executed);
+        // Moves the last event before the first one, but keeps the comparisons.
+        rows[2].object[3] = F::ZERO;
+        debug_single_trace::<F, D, _>(&S::default(), &trace_rows_to_poly_values(rows), &[]);
+    }
+
+    #[test]
+    fn prove_events_with_mozak_stark() {
+        let (program, record) = event_emission_test(&EVENTS);
+        MozakStark::prove_and_verify(&program, &record).unwrap();
+    }
+
+    #[test]
+    fn test_degree() -> Result<()> {
+        let stark = S::default();
+        test_stark_low_degree(stark)
+    }
+
+    #[test]
+    fn test_circuit() -> Result<()> {
+        let stark = S::default();
+        test_stark_circuit_constraints::<F, C, S, D>(stark)?;
+        Ok(())
+    }
+}
@@ -1,3 +1,19 @@
+//! Write constraints once, and evaluate them both natively and in circuits.
+//!
+//! Constraints are plain functions from a typed
+//! [`StarkFrameTyped`](expr::StarkFrameTyped) of [`Expr`]s to a
+//! [`ConstraintBuilder`], see eg `crate::cpu::jalr::constraints`. They know
+//! nothing about the field they are evaluated over. The `Stark`
+//! implementations then lower the very same constraints twice:
+//! - [`build_packed`] evaluates them over a `PackedField` for the prover and
+//!   the native verifier, and
+//! - [`build_ext`] turns them into gates of a `CircuitBuilder` for the
+//!   recursive verifier.
+//!
+//! So there is no separate circuit version of any constraint that could drift
+//! out of sync. `starky`'s `test_stark_circuit_constraints` checks that both
+//! lowerings agree; every STARK should have a test that calls it.
+
 use std::panic::Location;
 
 pub use expr::PureEvaluator;
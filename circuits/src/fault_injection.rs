@@ -0,0 +1,309 @@
+//! Deliberately corrupted traces, to check that the constraints reject bad
+//! witnesses.
+//!
+//! A [`Fault`] changes a single cell of the traces generated from an
+//! execution. [`Fault::check`] proves and verifies the corrupted traces, and
+//! reports how the fault was caught. A constraint that does not hold makes the
+//! prover panic, while a cross table lookup that does not balance only fails
+//! verification, so both count as a [`Rejection`].
+//!
+//! A fault that is not rejected shows that the cell is under-constrained,
+//! unless it sits in a row that nothing looks at, like padding.
+//!
+//! Corruptions that touch more than one cell, like reordering rows, go through
+//! [`check_corrupted`] directly.
+
+use std::panic::{self, AssertUnwindSafe};
+
+use mozak_runner::elf::Program;
+use mozak_runner::vm::ExecutionRecord;
+use plonky2::field::polynomial::PolynomialValues;
+use plonky2::field::types::Field;
+use plonky2::util::timing::TimingTree;
+
+use crate::generation::generate_traces;
+use crate::stark::error::{ProverError, VerifierError};
+use crate::stark::mozak_stark::{MozakStark, PublicInputs, TableKind, TableKindArray};
+use crate::stark::prover::prove_with_traces;
+use crate::stark::verifier::verify_proof;
+use crate::test_utils::{fast_test_config, C, D, F};
+use crate::trace_source::Execution;
+
+/// Adds one to the cell of `table` at `row` and `column`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Fault {
+    pub table: TableKind,
+    pub row: usize,
+    pub column: usize,
+}
+
+/// How a [`Fault`] was caught.
+#[derive(Debug)]
+pub enum Rejection {
+    /// The prover panicked, e.g. because a constraint does not hold.
+    ProverPanicked,
+    /// The prover refused the traces.
+    Prover(ProverError),
+    /// The proof did not verify, e.g. because a lookup does not balance.
+    Verifier(VerifierError),
+}
+
+impl Fault {
+    /// The fault in the column called `column` in `names`, e.g.
+    /// `CpuState::<()>::column_names()`.
+    ///
+    /// # Panics
+    /// Panics if no column is called `column`.
+    #[must_use]
+    pub fn named(table: TableKind, row: usize, names: &[String], column: &str) -> Self {
+        let column = names
+            .iter()
+            .position(|name| name == column)
+            .unwrap_or_else(|| panic!("{table:?} has no column {column}"));
+        Self { table, row, column }
+    }
+
+    /// Corrupts `traces`.
+    ///
+    /// # Panics
+    /// Panics if the cell lies outside of the trace of the table.
+    pub fn inject(&self, traces: &mut TableKindArray<Vec<PolynomialValues<F>>>) {
+        let cell = traces[self.table]
+            .get_mut(self.column)
+            .and_then(|column| column.values.get_mut(self.row))
+            .unwrap_or_else(|| panic!("{self:?} lies outside of the trace"));
+        *cell += F::ONE;
+    }
+
+    /// Proves the execution in `record` with the fault injected into its
+    /// traces, and verifies the proof.
+    ///
+    /// Returns `None` if the proof verifies, ie if the fault went unnoticed.
+    #[must_use]
+    pub fn check(&self, program: &Program, record: &ExecutionRecord<F>) -> Option<Rejection> {
+        check_corrupted(program, record, |traces| self.inject(traces))
+    }
+}
+
+/// Proves the execution in `record` with its traces changed by `corrupt`, and
+/// verifies the proof.
+///
+/// Returns `None` if the proof verifies, ie if the corruption went unnoticed.
+#[must_use]
+pub fn check_corrupted(
+    program: &Program,
+    record: &ExecutionRecord<F>,
+    corrupt: impl FnOnce(&mut TableKindArray<Vec<PolynomialValues<F>>>),
+) -> Option<Rejection> {
+    let stark = MozakStark::<F, D>::default();
+    let config = fast_test_config();
+    let mut traces = generate_traces(&Execution::new(program, record), &mut TimingTree::default());
+    corrupt(&mut traces);
+    let proof = panic::catch_unwind(AssertUnwindSafe(|| {
+        prove_with_traces::<F, C, D>(
+            &stark,
+            &config,
+            PublicInputs::for_execution(program, record),
+            &traces,
+            None,
+            &mut TimingTree::default(),
+        )
+    }));
+    match proof {
+        Err(_) => Some(Rejection::ProverPanicked),
+        Ok(Err(err)) => Some(Rejection::Prover(err)),
+        Ok(Ok(proof)) => verify_proof(&stark, proof, &config)
+            .err()
+            .map(Rejection::Verifier),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mozak_runner::elf::Program;
+    use mozak_runner::instruction::Instruction;
+    use mozak_runner::vm::ExecutionRecord;
+    use mozak_sdk::core::ecall;
+    use plonky2::field::types::Field;
+
+    use super::{check_corrupted, Fault};
+    use crate::cpu::columns::CpuState;
+    use crate::cpu_skeleton::columns::CpuSkeleton;
+    use crate::memory::columns::Memory;
+    use crate::memory_fullword::columns::FullWordMemory;
+    use crate::memoryinit::columns::MemoryInit;
+    use crate::ops::add::columns::Add;
+    use crate::program_multiplicities::columns::ProgramMult;
+    use crate::rangecheck::columns::RangeCheckColumnsView;
+    use crate::register::general::columns::Register;
+    use crate::stark::mozak_stark::TableKind;
+    use crate::storage_device::columns::StorageDevice;
+    use crate::test_utils::{fast_test_config, prove_and_verify_mozak_stark, ScenarioBuilder, F};
+    use crate::xor::columns::XorColumnsView;
+
+    /// Adds and xors two registers, stores and loads the sum, and reads the
+    /// private tape, so that every table under test has rows in use.
+    fn execute() -> (Program, ExecutionRecord<F>) {
+        ScenarioBuilder::default()
+            .register(6, 0x1234_5678)
+            .register(7, 0xFF00_FF00)
+            .instructions([
+                Instruction::add(5, 6, 7),
+                Instruction::xor(8, 6, 7),
+                Instruction::sw(5, 0, 0x100),
+                Instruction::lw(9, 0, 0x100),
+            ])
+            .private_tape(&[1, 2, 3, 4])
+            .read_tape(ecall::PRIVATE_TAPE, 0x200, 4)
+            .expect_register(9, 0x1234_5678_u32.wrapping_add(0xFF00_FF00))
+            .expect_memory(0x200, &[1, 2, 3, 4])
+            .build()
+    }
+
+    /// Checks that corrupting `column` in the first row of `table` is
+    /// rejected.
+    fn assert_rejected(table: TableKind, names: &[String], column: &str) {
+        let (program, record) = execute();
+        let fault = Fault::named(table, 0, names, column);
+        assert!(
+            fault.check(&program, &record).is_some(),
+            "{fault:?} ({column}) was not rejected"
+        );
+    }
+
+    /// The faults below would be moot if the uncorrupted traces did not
+    /// verify.
+    #[test]
+    fn no_fault_verifies() {
+        let (program, record) = execute();
+        prove_and_verify_mozak_stark(&program, &record, &fast_test_config()).unwrap();
+    }
+
+    #[test]
+    fn cpu_result() {
+        assert_rejected(TableKind::Cpu, &CpuState::<()>::column_names(), "dst_value");
+    }
+
+    #[test]
+    fn cpu_skeleton_clock() {
+        assert_rejected(
+            TableKind::CpuSkeleton,
+            &CpuSkeleton::<()>::column_names(),
+            "clk",
+        );
+    }
+
+    #[test]
+    fn program_multiplicity() {
+        assert_rejected(
+            TableKind::ProgramMult,
+            &ProgramMult::<()>::column_names(),
+            "mult_in_cpu",
+        );
+    }
+
+    #[test]
+    fn add_result() { assert_rejected(TableKind::Add, &Add::<()>::column_names(), "dst_value"); }
+
+    #[test]
+    fn xor_result() {
+        assert_rejected(
+            TableKind::Xor,
+            &XorColumnsView::<()>::column_names(),
+            "execution.out",
+        );
+    }
+
+    #[test]
+    fn register_value() {
+        assert_rejected(
+            TableKind::Register,
+            &Register::<()>::column_names(),
+            "value",
+        );
+    }
+
+    #[test]
+    fn memory_value() {
+        assert_rejected(TableKind::Memory, &Memory::<()>::column_names(), "value");
+    }
+
+    #[test]
+    fn memory_init_value() {
+        assert_rejected(
+            TableKind::MemoryInit,
+            &MemoryInit::<()>::column_names(),
+            "value",
+        );
+    }
+
+    #[test]
+    fn fullword_address() {
+        assert_rejected(
+            TableKind::FullWordMemory,
+            &FullWordMemory::<()>::column_names(),
+            "addrs[0]",
+        );
+    }
+
+    #[test]
+    fn private_tape_address() {
+        assert_rejected(
+            TableKind::StorageDevicePrivate,
+            &StorageDevice::<()>::column_names(),
+            "addr",
+        );
+    }
+
+    #[test]
+    fn rangecheck_multiplicity() {
+        assert_rejected(
+            TableKind::RangeCheck,
+            &RangeCheckColumnsView::<()>::column_names(),
+            "multiplicity",
+        );
+    }
+
+    /// Swaps the clocks of two reads of the same register. The reads still
+    /// match those of the CPU, so only the check that the accesses of a
+    /// register are sorted by their augmented clock can catch this.
+    #[test]
+    fn register_reads_out_of_order() {
+        let (program, record) = execute();
+        let names = Register::<()>::column_names();
+        let column = |name: &str| names.iter().position(|n| n == name).unwrap();
+        let (addr, clk, is_read) = (column("addr"), column("clk"), column("ops.is_read"));
+        let rejection = check_corrupted(&program, &record, |traces| {
+            let register = &mut traces[TableKind::Register];
+            let at = |column: usize, row: usize| register[column].values[row];
+            let row = (1..register[clk].len())
+                .find(|&row| {
+                    at(is_read, row - 1).is_one()
+                        && at(is_read, row).is_one()
+                        && at(addr, row - 1) == at(addr, row)
+                        && at(clk, row - 1) != at(clk, row)
+                })
+                .expect("two reads of the same register");
+            register[clk].values.swap(row - 1, row);
+        });
+        assert!(rejection.is_some(), "out of order reads were not rejected");
+    }
+
+    /// Shrinks the stack of the layout below the stored sum, in every row,
+    /// so that its zero init lies outside of the layout.
+    #[test]
+    fn zero_init_outside_of_the_stack() {
+        let (program, record) = execute();
+        let names = MemoryInit::<()>::column_names();
+        let stack_end = names.iter().position(|n| n == "layout.stack_end").unwrap();
+        let rejection = check_corrupted(&program, &record, |traces| {
+            for value in &mut traces[TableKind::MemoryInit][stack_end].values {
+                *value = F::from_canonical_u32(0x100);
+            }
+        });
+        assert!(
+            rejection.is_some(),
+            "zero init outside of the stack was not rejected"
+        );
+    }
+}
@@ -1,51 +1,80 @@
 //! This module is responsible for populating the the Stark Tables with the
-//! appropriate values based on the [`Program`] and [`ExecutionRecord`].
+//! appropriate values based on the [`Program`](mozak_runner::elf::Program) and
+//! [`ExecutionRecord`](mozak_runner::vm::ExecutionRecord) of a
+//! [`TraceSource`].
 
 use std::borrow::Borrow;
 use std::fmt::{Debug, Display};
 
-use itertools::{izip, Itertools};
-use log::debug;
-use mozak_runner::elf::Program;
-use mozak_runner::vm::ExecutionRecord;
+use itertools::{chain, izip, Itertools};
 use plonky2::field::extension::Extendable;
 use plonky2::field::packed::PackedField;
 use plonky2::field::polynomial::PolynomialValues;
 use plonky2::hash::hash_types::RichField;
 use plonky2::util::timing::TimingTree;
-use plonky2::util::transpose;
 use starky::constraint_consumer::ConstraintConsumer;
 use starky::evaluation_frame::StarkEvaluationFrame;
 use starky::stark::Stark;
+use tracing::{debug, instrument};
 
+#[cfg(feature = "zbb")]
+use crate::bitcount::generation::generate_bitcount_trace;
 use crate::bitshift::generation::generate_shift_amount_trace;
 use crate::columns_view::HasNamedColumns;
 use crate::cpu::generation::{generate_cpu_trace, generate_program_mult_trace};
 use crate::cpu_skeleton::generation::generate_cpu_skeleton_trace;
+use crate::cross_table_lookup::ctl_utils::debug_padding;
+#[cfg(feature = "decode")]
+use crate::decode::generation::{generate_decode_trace, generate_decoded_program_rom};
+use crate::event_counters::generation::generate_event_counters_trace;
+use crate::event_emission::generation::generate_event_emission_trace;
+#[cfg(feature = "goldilocks")]
+use crate::goldilocks::generation::generate_goldilocks_trace;
+#[cfg(feature = "memcopy")]
+use crate::memcopy::generation::generate_memcopy_trace;
 use crate::memory::generation::generate_memory_trace;
 use crate::memory_fullword::generation::generate_fullword_memory_trace;
 use crate::memory_halfword::generation::generate_halfword_memory_trace;
-use crate::memory_zeroinit::generation::generate_memory_zero_init_trace;
-use crate::memoryinit::generation::{generate_elf_memory_init_trace, generate_memory_init_trace};
+use crate::memoryinit::generation::{
+    generate_elf_memory_init_trace, generate_library_elf_memory_init_trace,
+    generate_memory_init_trace,
+};
 use crate::ops;
+#[cfg(feature = "poseidon2")]
+use crate::output_tape::generation::generate_output_tape_hash_trace;
+#[cfg(feature = "poseidon2")]
 use crate::poseidon2::generation::generate_poseidon2_trace;
+#[cfg(feature = "poseidon2")]
 use crate::poseidon2_output_bytes::generation::generate_poseidon2_output_bytes_trace;
+#[cfg(feature = "poseidon2")]
 use crate::poseidon2_sponge::generation::generate_poseidon2_sponge_trace;
-use crate::program::generation::generate_program_rom_trace;
+#[cfg(feature = "poseidon2")]
+use crate::private_tape_hash::generation::generate_private_tape_hash_trace;
+use crate::program::generation::{generate_library_rom_trace, generate_program_rom_trace};
+#[cfg(feature = "poseidon2")]
+use crate::public_tape_hash::generation::generate_public_tape_hash_trace;
 use crate::rangecheck::generation::generate_rangecheck_trace;
 use crate::rangecheck_u8::generation::generate_rangecheck_u8_trace;
 use crate::register::generation::{generate_register_init_trace, generate_register_trace};
+#[cfg(feature = "secp256k1")]
+use crate::secp256k1::generation::generate_secp256k1_trace;
 use crate::stark::mozak_stark::{
     all_starks, MozakStark, PublicInputs, TableKindArray, TableKindSetBuilder,
 };
-use crate::stark::utils::trace_rows_to_poly_values;
+use crate::stark::utils::TraceMatrix;
+#[cfg(feature = "poseidon2")]
+use crate::storage_device::generation::generate_output_tape_trace;
 use crate::storage_device::generation::{
     generate_call_tape_trace, generate_cast_list_commitment_tape_trace, generate_event_tape_trace,
-    generate_events_commitment_tape_trace, generate_private_tape_trace, generate_public_tape_trace,
-    generate_self_prog_id_tape_trace,
+    generate_events_commitment_tape_trace, generate_hint_tape_trace, generate_private_tape_trace,
+    generate_public_tape_trace, generate_self_prog_id_tape_trace,
 };
 use crate::tape_commitments::generation::generate_tape_commitments_trace;
+use crate::trace_source::TraceSource;
+#[cfg(feature = "u256")]
+use crate::u256::generation::generate_u256_trace;
 use crate::xor::generation::generate_xor_trace;
+use crate::xor_byte::generation::generate_xor_byte_trace;
 
 pub const MIN_TRACE_LENGTH: usize = 8;
 
@@ -53,29 +82,43 @@ pub const MIN_TRACE_LENGTH: usize = 8;
 /// Returns the polynomial encoding of each row
 ///
 /// ## Parameters
-/// `program`: A serialized ELF Program
-/// `record`: Non-constrained execution trace generated by the runner
+/// `source`: A serialized ELF Program and the non-constrained execution trace
+/// generated by the runner for it, see [`TraceSource`]
 #[must_use]
 #[allow(clippy::too_many_lines)]
+#[instrument(name = "trace_gen", skip_all)]
 pub fn generate_traces<F: RichField + Extendable<D>, const D: usize>(
-    program: &Program,
-    record: &ExecutionRecord<F>,
+    source: &impl TraceSource<F>,
     _timing: &mut TimingTree,
 ) -> TableKindArray<Vec<PolynomialValues<F>>> {
     debug!("Starting Trace Generation");
+    let (program, record) = (source.program(), source.record());
     let cpu_rows = generate_cpu_trace::<F>(record);
     let skeleton_rows = generate_cpu_skeleton_trace(record);
     let add_rows = ops::add::generate(record);
     let blt_taken_rows = ops::blt_taken::generate(record);
     let xor_rows = generate_xor_trace(&cpu_rows);
+    let xor_byte_rows = generate_xor_byte_trace(&xor_rows);
+    #[cfg(feature = "zbb")]
+    let bitcount_rows = generate_bitcount_trace(record);
     let shift_amount_rows = generate_shift_amount_trace(&cpu_rows);
     let program_rows = generate_program_rom_trace(program);
-    let program_mult_rows = generate_program_mult_trace(&skeleton_rows, &program_rows);
+    let library_program_rows = generate_library_rom_trace(program);
+    #[cfg(feature = "decode")]
+    let decode_rows = generate_decode_trace(&program_rows);
+    #[cfg(not(feature = "decode"))]
+    let (program_mult_rows, library_program_mult_rows) =
+        generate_program_mult_trace(&skeleton_rows, &program_rows, &library_program_rows);
+    #[cfg(feature = "decode")]
+    let (program_mult_rows, library_program_mult_rows) = generate_program_mult_trace(
+        &skeleton_rows,
+        &generate_decoded_program_rom(&decode_rows),
+        &library_program_rows,
+    );
 
-    let memory_init = generate_memory_init_trace(program);
     let elf_memory_init_rows = generate_elf_memory_init_trace(program);
-
-    let memory_zeroinit_rows = generate_memory_zero_init_trace(&record.executed, program);
+    let library_elf_memory_init_rows = generate_library_elf_memory_init_trace(program);
+    let memory_init_rows = generate_memory_init_trace(&record.executed, program);
 
     let halfword_memory_rows = generate_halfword_memory_trace(&record.executed);
     let fullword_memory_rows = generate_fullword_memory_trace(&record.executed);
@@ -86,14 +129,49 @@ pub fn generate_traces<F: RichField + Extendable<D>, const D: usize>(
     let events_commitment_tape_rows = generate_events_commitment_tape_trace(&record.executed);
     let cast_list_commitment_tape_rows = generate_cast_list_commitment_tape_trace(&record.executed);
     let self_prog_id_tape_rows = generate_self_prog_id_tape_trace(&record.executed);
+    let hint_tape_rows = generate_hint_tape_trace(&record.executed);
+    let event_emission_rows = generate_event_emission_trace(&record.executed);
+    #[cfg(feature = "secp256k1")]
+    let secp256k1_rows = generate_secp256k1_trace(&record.executed);
+    #[cfg(feature = "u256")]
+    let u256_rows = generate_u256_trace(&record.executed);
+    #[cfg(feature = "memcopy")]
+    let memcopy_rows = generate_memcopy_trace(&record.executed);
+    #[cfg(feature = "goldilocks")]
+    let goldilocks_rows = generate_goldilocks_trace(&record.executed);
+    #[cfg(feature = "poseidon2")]
     let poseiden2_sponge_rows = generate_poseidon2_sponge_trace(&record.executed);
+    #[cfg(feature = "poseidon2")]
     let poseidon2_output_bytes_rows = generate_poseidon2_output_bytes_trace(&poseiden2_sponge_rows);
-    let poseidon2_rows = generate_poseidon2_trace(&record.executed);
+    #[cfg(feature = "poseidon2")]
+    let public_tape_hash_rows =
+        generate_public_tape_hash_trace(&record.last_state.public_tape.data, &public_tape_rows);
+    #[cfg(feature = "poseidon2")]
+    let private_tape_hash_rows =
+        generate_private_tape_hash_trace(&record.last_state.private_tape.data, &private_tape_rows);
+    #[cfg(feature = "poseidon2")]
+    let output_tape_rows = generate_output_tape_trace(&record.executed);
+    #[cfg(feature = "poseidon2")]
+    let output_tape_hash_rows = generate_output_tape_hash_trace(
+        &record.last_state.output_tape.iter().copied().collect_vec(),
+        &output_tape_rows,
+    );
+    #[cfg(feature = "poseidon2")]
+    let poseidon2_rows = generate_poseidon2_trace(
+        &record.executed,
+        &public_tape_hash_rows,
+        &private_tape_hash_rows,
+        &output_tape_hash_rows,
+    );
 
+    // The Memory table takes the ELF inits of the program and its library alike.
+    let all_elf_memory_init_rows = chain!(&elf_memory_init_rows, &library_elf_memory_init_rows)
+        .copied()
+        .collect_vec();
     let memory_rows = generate_memory_trace(
         &record.executed,
-        &memory_init,
-        &memory_zeroinit_rows,
+        &all_elf_memory_init_rows,
+        &memory_init_rows,
         &halfword_memory_rows,
         &fullword_memory_rows,
         &private_tape_rows,
@@ -103,8 +181,22 @@ pub fn generate_traces<F: RichField + Extendable<D>, const D: usize>(
         &events_commitment_tape_rows,
         &cast_list_commitment_tape_rows,
         &self_prog_id_tape_rows,
+        &hint_tape_rows,
+        &event_emission_rows,
+        #[cfg(feature = "poseidon2")]
         &poseiden2_sponge_rows,
+        #[cfg(feature = "poseidon2")]
         &poseidon2_output_bytes_rows,
+        #[cfg(feature = "poseidon2")]
+        &output_tape_rows,
+        #[cfg(feature = "secp256k1")]
+        &secp256k1_rows,
+        #[cfg(feature = "u256")]
+        &u256_rows,
+        #[cfg(feature = "memcopy")]
+        &memcopy_rows,
+        #[cfg(feature = "goldilocks")]
+        &goldilocks_rows,
     );
 
     let register_init_rows = generate_register_init_trace::<F>(record);
@@ -113,7 +205,18 @@ pub fn generate_traces<F: RichField + Extendable<D>, const D: usize>(
             &cpu_rows,
             &add_rows,
             &blt_taken_rows,
+            #[cfg(feature = "poseidon2")]
             &poseiden2_sponge_rows,
+            #[cfg(feature = "poseidon2")]
+            &output_tape_rows,
+            #[cfg(feature = "secp256k1")]
+            &secp256k1_rows,
+            #[cfg(feature = "u256")]
+            &u256_rows,
+            #[cfg(feature = "memcopy")]
+            &memcopy_rows,
+            #[cfg(feature = "goldilocks")]
+            &goldilocks_rows,
             &private_tape_rows,
             &public_tape_rows,
             &call_tape_rows,
@@ -121,6 +224,8 @@ pub fn generate_traces<F: RichField + Extendable<D>, const D: usize>(
             &events_commitment_tape_rows,
             &cast_list_commitment_tape_rows,
             &self_prog_id_tape_rows,
+            &hint_tape_rows,
+            &event_emission_rows,
             &register_init_rows,
         );
     // Generate rows for the looking values with their multiplicities.
@@ -129,46 +234,95 @@ pub fn generate_traces<F: RichField + Extendable<D>, const D: usize>(
         &add_rows,
         &blt_taken_rows,
         &memory_rows,
+        &memory_init_rows,
         &register_rows,
+        &event_emission_rows,
+        #[cfg(feature = "secp256k1")]
+        &secp256k1_rows,
+        #[cfg(feature = "u256")]
+        &u256_rows,
+        #[cfg(feature = "decode")]
+        &decode_rows,
     );
     // Generate a trace of values containing 0..u8::MAX, with multiplicities to be
     // looked.
-    let rangecheck_u8_rows = generate_rangecheck_u8_trace(&rangecheck_rows, &memory_rows);
+    let rangecheck_u8_rows = generate_rangecheck_u8_trace(
+        &rangecheck_rows,
+        &memory_rows,
+        #[cfg(feature = "secp256k1")]
+        &secp256k1_rows,
+        #[cfg(feature = "u256")]
+        &u256_rows,
+    );
     let add_trace = ops::add::generate(record);
     let blt_trace = ops::blt_taken::generate(record);
     let tape_commitments_rows = generate_tape_commitments_trace(record);
+    let event_counters_rows = generate_event_counters_trace(&cpu_rows, &blt_taken_rows);
 
+    // All tables go through the same scratch buffer, so we only allocate it
+    // for the largest one.
+    let mut matrix = TraceMatrix::default();
     TableKindSetBuilder {
-        cpu_stark: trace_rows_to_poly_values(cpu_rows),
-        rangecheck_stark: trace_rows_to_poly_values(rangecheck_rows),
-        xor_stark: trace_rows_to_poly_values(xor_rows),
-        shift_amount_stark: trace_rows_to_poly_values(shift_amount_rows),
-        program_stark: trace_rows_to_poly_values(program_rows),
-        program_mult_stark: trace_rows_to_poly_values(program_mult_rows),
-        memory_stark: trace_rows_to_poly_values(memory_rows),
-        elf_memory_init_stark: trace_rows_to_poly_values(elf_memory_init_rows),
-        memory_zeroinit_stark: trace_rows_to_poly_values(memory_zeroinit_rows),
-        rangecheck_u8_stark: trace_rows_to_poly_values(rangecheck_u8_rows),
-        halfword_memory_stark: trace_rows_to_poly_values(halfword_memory_rows),
-        fullword_memory_stark: trace_rows_to_poly_values(fullword_memory_rows),
-        private_tape_stark: trace_rows_to_poly_values(private_tape_rows),
-        public_tape_stark: trace_rows_to_poly_values(public_tape_rows),
-        call_tape_stark: trace_rows_to_poly_values(call_tape_rows),
-        event_tape_stark: trace_rows_to_poly_values(event_tape_rows),
-        events_commitment_tape_stark: trace_rows_to_poly_values(events_commitment_tape_rows),
-        cast_list_commitment_tape_stark: trace_rows_to_poly_values(cast_list_commitment_tape_rows),
-        self_prog_id_tape_stark: trace_rows_to_poly_values(self_prog_id_tape_rows),
-        register_init_stark: trace_rows_to_poly_values(register_init_rows),
-        register_stark: trace_rows_to_poly_values(register_rows),
-        register_zero_read_stark: trace_rows_to_poly_values(register_zero_read_rows),
-        register_zero_write_stark: trace_rows_to_poly_values(register_zero_write_rows),
-        poseidon2_stark: trace_rows_to_poly_values(poseidon2_rows),
-        poseidon2_sponge_stark: trace_rows_to_poly_values(poseiden2_sponge_rows),
-        poseidon2_output_bytes_stark: trace_rows_to_poly_values(poseidon2_output_bytes_rows),
-        cpu_skeleton_stark: trace_rows_to_poly_values(skeleton_rows),
-        add_stark: trace_rows_to_poly_values(add_trace),
-        blt_taken_stark: trace_rows_to_poly_values(blt_trace),
-        tape_commitments_stark: trace_rows_to_poly_values(tape_commitments_rows),
+        cpu_stark: matrix.rows_to_poly_values(cpu_rows),
+        rangecheck_stark: matrix.rows_to_poly_values(rangecheck_rows),
+        xor_stark: matrix.rows_to_poly_values(xor_rows),
+        xor_byte_stark: matrix.rows_to_poly_values(xor_byte_rows),
+        #[cfg(feature = "zbb")]
+        bitcount_stark: matrix.rows_to_poly_values(bitcount_rows),
+        shift_amount_stark: matrix.rows_to_poly_values(shift_amount_rows),
+        program_stark: matrix.rows_to_poly_values(program_rows),
+        program_mult_stark: matrix.rows_to_poly_values(program_mult_rows),
+        library_program_stark: matrix.rows_to_poly_values(library_program_rows),
+        library_program_mult_stark: matrix.rows_to_poly_values(library_program_mult_rows),
+        #[cfg(feature = "decode")]
+        decode_stark: matrix.rows_to_poly_values(decode_rows),
+        memory_stark: matrix.rows_to_poly_values(memory_rows),
+        elf_memory_init_stark: matrix.rows_to_poly_values(elf_memory_init_rows),
+        library_elf_memory_init_stark: matrix.rows_to_poly_values(library_elf_memory_init_rows),
+        memory_init_stark: matrix.rows_to_poly_values(memory_init_rows),
+        rangecheck_u8_stark: matrix.rows_to_poly_values(rangecheck_u8_rows),
+        halfword_memory_stark: matrix.rows_to_poly_values(halfword_memory_rows),
+        fullword_memory_stark: matrix.rows_to_poly_values(fullword_memory_rows),
+        private_tape_stark: matrix.rows_to_poly_values(private_tape_rows),
+        public_tape_stark: matrix.rows_to_poly_values(public_tape_rows),
+        call_tape_stark: matrix.rows_to_poly_values(call_tape_rows),
+        event_tape_stark: matrix.rows_to_poly_values(event_tape_rows),
+        events_commitment_tape_stark: matrix.rows_to_poly_values(events_commitment_tape_rows),
+        cast_list_commitment_tape_stark: matrix.rows_to_poly_values(cast_list_commitment_tape_rows),
+        self_prog_id_tape_stark: matrix.rows_to_poly_values(self_prog_id_tape_rows),
+        hint_tape_stark: matrix.rows_to_poly_values(hint_tape_rows),
+        register_init_stark: matrix.rows_to_poly_values(register_init_rows),
+        register_stark: matrix.rows_to_poly_values(register_rows),
+        register_zero_read_stark: matrix.rows_to_poly_values(register_zero_read_rows),
+        register_zero_write_stark: matrix.rows_to_poly_values(register_zero_write_rows),
+        #[cfg(feature = "poseidon2")]
+        poseidon2_stark: matrix.rows_to_poly_values(poseidon2_rows),
+        #[cfg(feature = "poseidon2")]
+        poseidon2_sponge_stark: matrix.rows_to_poly_values(poseiden2_sponge_rows),
+        #[cfg(feature = "poseidon2")]
+        poseidon2_output_bytes_stark: matrix.rows_to_poly_values(poseidon2_output_bytes_rows),
+        #[cfg(feature = "poseidon2")]
+        public_tape_hash_stark: matrix.rows_to_poly_values(public_tape_hash_rows),
+        #[cfg(feature = "poseidon2")]
+        private_tape_hash_stark: matrix.rows_to_poly_values(private_tape_hash_rows),
+        #[cfg(feature = "poseidon2")]
+        output_tape_stark: matrix.rows_to_poly_values(output_tape_rows),
+        #[cfg(feature = "poseidon2")]
+        output_tape_hash_stark: matrix.rows_to_poly_values(output_tape_hash_rows),
+        #[cfg(feature = "secp256k1")]
+        secp256k1_stark: matrix.rows_to_poly_values(secp256k1_rows),
+        #[cfg(feature = "u256")]
+        u256_stark: matrix.rows_to_poly_values(u256_rows),
+        #[cfg(feature = "memcopy")]
+        memcopy_stark: matrix.rows_to_poly_values(memcopy_rows),
+        #[cfg(feature = "goldilocks")]
+        goldilocks_stark: matrix.rows_to_poly_values(goldilocks_rows),
+        cpu_skeleton_stark: matrix.rows_to_poly_values(skeleton_rows),
+        add_stark: matrix.rows_to_poly_values(add_trace),
+        blt_taken_stark: matrix.rows_to_poly_values(blt_trace),
+        tape_commitments_stark: matrix.rows_to_poly_values(tape_commitments_rows),
+        event_counters_stark: matrix.rows_to_poly_values(event_counters_rows),
+        event_emission_stark: matrix.rows_to_poly_values(event_emission_rows),
     }
     .build()
 }
@@ -187,14 +341,10 @@ pub fn transpose_polys<
 >(
     cols: Vec<PolynomialValues<F>>,
 ) -> Vec<Vec<F>> {
-    transpose(
-        &cols
-            .into_iter()
-            .map(|PolynomialValues { values }| values)
-            .collect_vec(),
-    )
-    .into_iter()
-    .collect_vec()
+    let mut matrix = TraceMatrix::default();
+    matrix.load_columns(&cols);
+    drop(cols);
+    matrix.rows().map(<[F]>::to_vec).collect()
 }
 
 pub fn debug_traces<F: RichField + Extendable<D>, const D: usize>(
@@ -211,6 +361,7 @@ pub fn debug_traces<F: RichField + Extendable<D>, const D: usize>(
     all_starks!(mozak_stark, |stark, kind| {
         debug_single_trace::<F, D, _>(stark, &traces_poly_values[kind], public_inputs[kind]);
     });
+    debug_padding(traces_poly_values, mozak_stark);
 }
 
 pub fn debug_single_trace<
@@ -223,21 +374,22 @@ pub fn debug_single_trace<
     public_inputs: &[F],
 ) where
     S::Columns: FromIterator<F> + Debug, {
-    transpose_polys::<F, D, S>(trace_rows.to_vec())
-        .iter()
+    let mut matrix = TraceMatrix::default();
+    matrix.load_columns(trace_rows);
+    matrix
+        .rows()
         .enumerate()
         .circular_tuple_windows()
         .for_each(|((lv_row, lv), (nv_row, nv))| {
             let mut consumer = ConstraintConsumer::new_debug_api(lv_row == 0, nv_row == 0);
-            let vars =
-                StarkEvaluationFrame::from_values(lv.as_slice(), nv.as_slice(), public_inputs);
+            let vars = StarkEvaluationFrame::from_values(lv, nv, public_inputs);
             stark.eval_packed_generic(&vars, &mut consumer);
             if consumer.debug_api_has_constraint_failed() {
                 let lv: S::Columns = lv.iter().copied().collect();
                 let nv: S::Columns = nv.iter().copied().collect();
-                log::error!("Debug constraints for {stark}");
-                log::error!("lv-row[{lv_row}] - values: {lv:?}");
-                log::error!("nv-row[{nv_row}] - values: {nv:?}");
+                tracing::error!("Debug constraints for {stark}");
+                tracing::error!("lv-row[{lv_row}] - values: {lv:?}");
+                tracing::error!("nv-row[{nv_row}] - values: {nv:?}");
             }
             assert!(!consumer.debug_api_has_constraint_failed());
         });
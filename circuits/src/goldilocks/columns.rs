@@ -0,0 +1,145 @@
+use core::ops::Add;
+
+use expr::Expr;
+use itertools::{chain, izip};
+pub use mozak_sdk::core::constants::GOLDILOCKS_BYTES;
+use mozak_sdk::core::reg_abi::{REG_A1, REG_A2};
+
+use crate::columns_view::{make_col_map, ColumnsView, NumberOfColumns};
+use crate::cross_table_lookup::ColumnWithTypedInput;
+use crate::linear_combination::Column;
+use crate::memory::columns::MemoryCtl;
+use crate::register::RegisterCtl;
+use crate::stark::mozak_stark::{GoldilocksTable, TableWithTypedOutput};
+
+/// The high half of the largest canonical element with a non-zero low half
+/// is one less than this.
+pub const MAX_HIGH_HALF: i64 = 0xFFFF_FFFF;
+
+/// A field element as stored in memory.
+#[repr(C)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
+pub struct Element<T> {
+    /// The canonical representative, in little-endian bytes.
+    pub bytes: [T; GOLDILOCKS_BYTES],
+    /// The inverse of [`MAX_HIGH_HALF`] minus the high half of `bytes`, or
+    /// zero if there is none. It shows that the element is canonical.
+    pub high_inv: T,
+}
+
+impl<'a, T: Copy> Element<Expr<'a, T>> {
+    /// The element in the field of the proof.
+    pub fn value(&self) -> Expr<'a, T> { Expr::reduce_with_powers(self.bytes, 256) }
+
+    /// Zero iff the element is canonical, ie below the order
+    /// `2^64 - 2^32 + 1`: its high half is [`MAX_HIGH_HALF`] only if its low
+    /// half is zero.
+    pub fn canonical(&self) -> Expr<'a, T> {
+        let (low, high) = self.bytes.split_at(GOLDILOCKS_BYTES / 2);
+        let (low, high) = (
+            Expr::reduce_with_powers(low.iter().copied(), 256),
+            Expr::reduce_with_powers(high.iter().copied(), 256),
+        );
+        low * (1 - (MAX_HIGH_HALF - high) * self.high_inv)
+    }
+}
+
+make_col_map!(Goldilocks);
+/// A row adds, multiplies or inverts field elements, and stores the `result`
+/// over `a`.
+#[repr(C)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
+pub struct Goldilocks<T> {
+    pub is_add: T,
+    pub is_mul: T,
+    pub is_inverse: T,
+    pub clk: T,
+    pub a_addr: T,
+    /// Zero when inverting.
+    pub b_addr: T,
+    pub a: Element<T>,
+    /// Zero when inverting.
+    pub b: Element<T>,
+    pub result: Element<T>,
+}
+
+pub const NUM_GOLDILOCKS_COLS: usize = Goldilocks::<()>::NUMBER_OF_COLUMNS;
+
+impl<T: Copy + Add<Output = T>> Goldilocks<T> {
+    pub fn is_executed(&self) -> T { self.is_add + self.is_mul + self.is_inverse }
+
+    /// Whether the row has a second operand `b`.
+    pub fn is_binary(&self) -> T { self.is_add + self.is_mul }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
+pub struct GoldilocksCtl<T> {
+    pub clk: T,
+    pub is_mul: T,
+    pub is_inverse: T,
+}
+
+#[must_use]
+pub fn lookup_for_cpu() -> TableWithTypedOutput<GoldilocksCtl<Column>> {
+    GoldilocksTable::new(
+        GoldilocksCtl {
+            clk: COL_MAP.clk,
+            is_mul: COL_MAP.is_mul,
+            is_inverse: COL_MAP.is_inverse,
+        },
+        COL_MAP.is_executed(),
+    )
+}
+
+#[must_use]
+pub fn register_looking() -> Vec<TableWithTypedOutput<RegisterCtl<Column>>> {
+    let is_read = ColumnWithTypedInput::constant(1);
+    [
+        (COL_MAP.a_addr, REG_A1, COL_MAP.is_executed()),
+        (COL_MAP.b_addr, REG_A2, COL_MAP.is_binary()),
+    ]
+    .into_iter()
+    .map(|(value, reg, filter)| {
+        GoldilocksTable::new(
+            RegisterCtl {
+                clk: COL_MAP.clk,
+                op: is_read,
+                value,
+                addr: ColumnWithTypedInput::constant(reg.into()),
+            },
+            filter,
+        )
+    })
+    .collect()
+}
+
+/// Loads of `a` and `b`, and the store of the result over `a`. Loads come
+/// before stores in the same cycle, so they see the old `a`. The memory table
+/// range checks the bytes.
+pub fn lookup_for_memory() -> impl Iterator<Item = TableWithTypedOutput<MemoryCtl<Column>>> {
+    let access = |addr, value: [_; GOLDILOCKS_BYTES], is_store, filter| {
+        izip!(0.., value).map(move |(i, value)| {
+            GoldilocksTable::new(
+                MemoryCtl {
+                    clk: COL_MAP.clk,
+                    is_store: ColumnWithTypedInput::constant(is_store),
+                    is_load: ColumnWithTypedInput::constant(1 - is_store),
+                    value,
+                    addr: addr + i,
+                },
+                filter,
+            )
+        })
+    };
+    chain!(
+        access(COL_MAP.a_addr, COL_MAP.a.bytes, 0, COL_MAP.is_executed()),
+        access(COL_MAP.b_addr, COL_MAP.b.bytes, 0, COL_MAP.is_binary()),
+        access(
+            COL_MAP.a_addr,
+            COL_MAP.result.bytes,
+            1,
+            COL_MAP.is_executed()
+        ),
+    )
+}
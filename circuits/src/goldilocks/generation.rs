@@ -0,0 +1,54 @@
+use mozak_runner::goldilocks::{Entry, Operation};
+use mozak_runner::vm::Row;
+use plonky2::hash::hash_types::RichField;
+
+use super::columns::{Element, Goldilocks, MAX_HIGH_HALF};
+use crate::utils::pad_trace_with_default;
+
+/// The columns of the element with representative `value`, which need not be
+/// canonical.
+#[must_use]
+pub fn element<F: RichField>(value: u64) -> Element<F> {
+    let high_diff = MAX_HIGH_HALF.unsigned_abs() - (value >> 32);
+    Element {
+        bytes: value.to_le_bytes().map(F::from_canonical_u8),
+        high_inv: F::from_canonical_u64(high_diff)
+            .try_inverse()
+            .unwrap_or_default(),
+    }
+}
+
+impl<F: RichField> Element<F> {
+    /// The representative in the bytes.
+    #[must_use]
+    pub fn value_u64(&self) -> u64 {
+        self.bytes
+            .iter()
+            .rev()
+            .fold(0, |acc, byte| acc << 8 | byte.to_canonical_u64())
+    }
+}
+
+fn generate_row<F: RichField>(clk: u64, entry: &Entry) -> Goldilocks<F> {
+    Goldilocks {
+        is_add: F::from_bool(entry.operation == Operation::Add),
+        is_mul: F::from_bool(entry.operation == Operation::Mul),
+        is_inverse: F::from_bool(entry.operation == Operation::Inverse),
+        clk: F::from_canonical_u64(clk),
+        a_addr: F::from_canonical_u32(entry.a_addr),
+        b_addr: F::from_canonical_u32(entry.b_addr),
+        a: element(entry.a),
+        b: element(entry.b),
+        result: element(entry.result),
+    }
+}
+
+#[must_use]
+pub fn generate_goldilocks_trace<F: RichField>(step_rows: &[Row<F>]) -> Vec<Goldilocks<F>> {
+    pad_trace_with_default(
+        step_rows
+            .iter()
+            .filter_map(|row| Some(generate_row(row.state.clk, row.aux.goldilocks.as_ref()?)))
+            .collect(),
+    )
+}
@@ -0,0 +1,155 @@
+use std::marker::PhantomData;
+
+use expr::{Expr, ExprBuilder, StarkFrameTyped};
+use mozak_circuits_derive::StarkNameDisplay;
+use plonky2::field::extension::{Extendable, FieldExtension};
+use plonky2::field::packed::PackedField;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::ext_target::ExtensionTarget;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use starky::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
+use starky::evaluation_frame::StarkFrame;
+use starky::stark::Stark;
+
+use super::columns::{Goldilocks, NUM_GOLDILOCKS_COLS};
+use crate::columns_view::HasNamedColumns;
+use crate::expr::{build_ext, build_packed, ConstraintBuilder};
+use crate::unstark::NoColumns;
+
+#[derive(Copy, Clone, Default, StarkNameDisplay)]
+#[allow(clippy::module_name_repetitions)]
+pub struct GoldilocksStark<F, const D: usize> {
+    pub _f: PhantomData<F>,
+}
+
+impl<F, const D: usize> HasNamedColumns for GoldilocksStark<F, D> {
+    type Columns = Goldilocks<F>;
+}
+
+const COLUMNS: usize = NUM_GOLDILOCKS_COLS;
+const PUBLIC_INPUTS: usize = 0;
+
+/// The proofs are over the Goldilocks field itself, so each operation is a
+/// single constraint on the values of the elements. The memory table range
+/// checks their bytes, which leaves showing that they are canonical, so that
+/// the result in memory is the one the runner computes.
+fn generate_constraints<'a, T: Copy>(
+    vars: &StarkFrameTyped<Goldilocks<Expr<'a, T>>, NoColumns<Expr<'a, T>>>,
+) -> ConstraintBuilder<Expr<'a, T>> {
+    let lv = vars.local_values;
+    let mut constraints = ConstraintBuilder::default();
+
+    constraints.always(lv.is_add.is_binary());
+    constraints.always(lv.is_mul.is_binary());
+    constraints.always(lv.is_inverse.is_binary());
+    constraints.always(lv.is_executed().is_binary());
+
+    // All elements are zero on padding rows, and `b` is zero when inverting,
+    // so these hold there too.
+    for element in [lv.a, lv.b, lv.result] {
+        constraints.always(element.canonical());
+    }
+
+    let (a, b, result) = (lv.a.value(), lv.b.value(), lv.result.value());
+    constraints.always(lv.is_add * (result - (a + b)));
+    constraints.always(lv.is_mul * (result - a * b));
+    // This also shows that `a` is not zero.
+    constraints.always(lv.is_inverse * (result * a - 1));
+
+    constraints
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for GoldilocksStark<F, D> {
+    type EvaluationFrame<FE, P, const D2: usize> = StarkFrame<P, P::Scalar, COLUMNS, PUBLIC_INPUTS>
+    where
+        FE: FieldExtension<D2, BaseField = F>,
+        P: PackedField<Scalar = FE>;
+    type EvaluationFrameTarget =
+        StarkFrame<ExtensionTarget<D>, ExtensionTarget<D>, COLUMNS, PUBLIC_INPUTS>;
+
+    fn eval_packed_generic<FE, P, const D2: usize>(
+        &self,
+        vars: &Self::EvaluationFrame<FE, P, D2>,
+        consumer: &mut ConstraintConsumer<P>,
+    ) where
+        FE: FieldExtension<D2, BaseField = F>,
+        P: PackedField<Scalar = FE>, {
+        let eb = ExprBuilder::default();
+        let constraints = generate_constraints(&eb.to_typed_starkframe(vars));
+        build_packed(constraints, consumer);
+    }
+
+    fn eval_ext_circuit(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        vars: &Self::EvaluationFrameTarget,
+        consumer: &mut RecursiveConstraintConsumer<F, D>,
+    ) {
+        let eb = ExprBuilder::default();
+        let constraints = generate_constraints(&eb.to_typed_starkframe(vars));
+        build_ext(constraints, builder, consumer);
+    }
+
+    fn constraint_degree(&self) -> usize { 3 }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use plonky2::field::types::{Field, Field64};
+    use plonky2::plonk::config::{GenericConfig, Poseidon2GoldilocksConfig};
+    use starky::stark_testing::{test_stark_circuit_constraints, test_stark_low_degree};
+
+    use super::GoldilocksStark;
+    use crate::generation::debug_single_trace;
+    use crate::goldilocks::generation::{element, generate_goldilocks_trace};
+    use crate::stark::mozak_stark::MozakStark;
+    use crate::stark::utils::trace_rows_to_poly_values;
+    use crate::test_utils::{goldilocks_test, ProveAndVerify};
+
+    const D: usize = 2;
+    type C = Poseidon2GoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+    type S = GoldilocksStark<F, D>;
+
+    #[test]
+    fn prove_add_mul_and_inverse_with_mozak_stark() {
+        let (program, record) = goldilocks_test();
+        MozakStark::prove_and_verify(&program, &record).unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "debug_api_has_constraint_failed")]
+    fn wrong_result_fails() {
+        let (_program, record) = goldilocks_test();
+        let mut rows = generate_goldilocks_trace(&record.executed);
+        rows[0].result.bytes[0] += F::ONE;
+        debug_single_trace::<F, D, _>(&S::default(), &trace_rows_to_poly_values(rows), &[]);
+    }
+
+    /// Adding the order to a small result keeps its value in the field, but
+    /// the bytes in memory would differ from the canonical result.
+    #[test]
+    #[should_panic(expected = "debug_api_has_constraint_failed")]
+    fn non_canonical_result_fails() {
+        let (_program, record) = goldilocks_test();
+        let mut rows = generate_goldilocks_trace(&record.executed);
+        let row = &mut rows[0];
+        let value = row.result.value_u64();
+        row.result = element(value.checked_add(F::ORDER).expect("a small result"));
+        debug_single_trace::<F, D, _>(&S::default(), &trace_rows_to_poly_values(rows), &[]);
+    }
+
+    #[test]
+    fn test_degree() -> Result<()> {
+        let stark = S::default();
+        test_stark_low_degree(stark)
+    }
+
+    #[test]
+    fn test_circuit() -> Result<()> {
+        let stark = S::default();
+        test_stark_circuit_constraints::<F, C, S, D>(stark)?;
+        Ok(())
+    }
+}
@@ -6,38 +6,75 @@
 #![allow(clippy::missing_errors_doc)]
 #![feature(const_trait_impl)]
 
+#[cfg(feature = "zbb")]
+pub mod bitcount;
 pub mod bitshift;
+#[cfg(any(feature = "test", test))]
+pub mod column_usage;
 pub mod columns_view;
+pub mod cost;
 pub mod cpu;
 pub mod cpu_skeleton;
 pub mod cross_table_lookup;
+#[cfg(feature = "decode")]
+pub mod decode;
+pub mod event_counters;
+pub mod event_emission;
 pub mod expr;
+#[cfg(any(feature = "test", test))]
+pub mod fault_injection;
 pub mod generation;
+#[cfg(feature = "goldilocks")]
+pub mod goldilocks;
+#[cfg(any(feature = "secp256k1", feature = "u256"))]
+pub mod limbs;
 pub mod linear_combination;
 pub mod linear_combination_typed;
+#[cfg(feature = "memcopy")]
+pub mod memcopy;
 pub mod memory;
 pub mod memory_fullword;
 pub mod memory_halfword;
-pub mod memory_zeroinit;
 pub mod memoryinit;
 pub mod ops;
+#[cfg(feature = "poseidon2")]
+pub mod output_tape;
+#[cfg(feature = "poseidon2")]
 pub mod poseidon2;
+#[cfg(feature = "poseidon2")]
 pub mod poseidon2_output_bytes;
+#[cfg(feature = "poseidon2")]
 pub mod poseidon2_sponge;
+#[cfg(feature = "poseidon2")]
+pub mod private_tape_hash;
 pub mod program;
 pub mod program_multiplicities;
 pub mod public_sub_table;
+#[cfg(feature = "poseidon2")]
+pub mod public_tape_hash;
+#[cfg(test)]
+pub mod random_programs;
 pub mod rangecheck;
 pub mod rangecheck_u8;
 pub mod register;
+#[cfg(feature = "secp256k1")]
+pub mod secp256k1;
 pub mod stark;
 pub mod storage_device;
 pub mod tape_commitments;
 #[cfg(any(feature = "test", test))]
 pub mod test_utils;
+#[cfg(any(feature = "test", test))]
+pub mod test_vectors;
+#[cfg(feature = "trace-cache")]
+pub mod trace_cache;
+pub mod trace_source;
+#[cfg(feature = "u256")]
+pub mod u256;
 pub mod unstark;
 pub mod utils;
 pub mod xor;
+pub mod xor_byte;
 
 extern crate serde;
 extern crate serde_big_array;
@@ -0,0 +1,101 @@
+//! Integers that do not fit into our field, as polynomials in 16-bit limbs
+//! evaluated at `2^16`.
+//!
+//! An integer identity holds when the limbs of its difference vanish at
+//! `2^16`, ie when each limb plus the carry from below is a multiple of
+//! `2^16`, and the carry out of the top limb is zero. As long as the limbs and
+//! carries are small enough, none of this wraps around in our field, so the
+//! identity holds over the integers.
+//!
+//! The helpers here are generic, so that the constraints (over [`Expr`]s) and
+//! trace generation (over `i128`s) share them.
+//!
+//! [`Expr`]: expr::Expr
+
+use core::ops::{Add, Mul, Sub};
+
+use itertools::{chain, izip};
+use num::bigint::BigUint;
+use num::Integer;
+use plonky2::hash::hash_types::RichField;
+
+pub trait Limb:
+    Copy + From<i64> + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> {
+}
+
+impl<E> Limb for E where
+    E: Copy + From<i64> + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self>
+{
+}
+
+/// 16-bit limbs of a number given by its little-endian bytes.
+pub fn limbs<E: Limb>(bytes: &[E]) -> Vec<E> {
+    bytes
+        .chunks(2)
+        .map(|pair| pair[0] + pair[1] * E::from(256))
+        .collect()
+}
+
+fn zip_longest<E: Limb>(a: &[E], b: &[E], f: impl Fn(E, E) -> E) -> Vec<E> {
+    let zero = E::from(0);
+    (0..a.len().max(b.len()))
+        .map(|i| f(*a.get(i).unwrap_or(&zero), *b.get(i).unwrap_or(&zero)))
+        .collect()
+}
+
+pub fn add<E: Limb>(a: &[E], b: &[E]) -> Vec<E> { zip_longest(a, b, |x, y| x + y) }
+
+pub fn sub<E: Limb>(a: &[E], b: &[E]) -> Vec<E> { zip_longest(a, b, |x, y| x - y) }
+
+pub fn scale<E: Limb>(a: &[E], factor: E) -> Vec<E> { a.iter().map(|&x| x * factor).collect() }
+
+pub fn mul<E: Limb>(a: &[E], b: &[E]) -> Vec<E> {
+    let mut product = vec![E::from(0); a.len() + b.len() - 1];
+    for (i, &x) in a.iter().enumerate() {
+        for (j, &y) in b.iter().enumerate() {
+            product[i + j] = product[i + j] + x * y;
+        }
+    }
+    product
+}
+
+/// The constraints that `limbs` vanish at `2^16`, given the `carries`.
+pub fn carry_constraints<E: Limb>(limbs: &[E], carries: &[E]) -> Vec<E> {
+    let zero = E::from(0);
+    izip!(limbs, chain!([&zero], carries), chain!(carries, [&zero]),)
+        .map(|(&limb, &carry_in, &carry_out)| limb + carry_in - carry_out * E::from(1 << 16))
+        .collect()
+}
+
+/// The carries that make `limbs` vanish at `2^16`.
+///
+/// # Panics
+///
+/// Panics if `limbs` do not vanish at `2^16`.
+#[must_use]
+pub fn carries<F: RichField>(limbs: &[i128]) -> Vec<F> {
+    let mut carry = 0;
+    let carries = limbs
+        .iter()
+        .map(|&limb| {
+            let (quotient, remainder) = (limb + carry).div_rem(&(1 << 16));
+            assert_eq!(remainder, 0, "limbs do not vanish at 2^16");
+            carry = quotient;
+            F::from_noncanonical_i64(i64::try_from(carry).expect("carry fits into i64"))
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(carry, 0, "limbs do not vanish at 2^16");
+    carries[..carries.len() - 1].to_vec()
+}
+
+/// Little-endian bytes of `value`, which has to fit into `N` bytes.
+///
+/// # Panics
+///
+/// Panics if `value` does not fit into `N` bytes.
+#[must_use]
+pub fn to_bytes<F: RichField, const N: usize>(value: &BigUint) -> [F; N] {
+    let le = value.to_bytes_le();
+    assert!(le.len() <= N, "{value} does not fit into {N} bytes");
+    core::array::from_fn(|i| F::from_canonical_u8(le.get(i).copied().unwrap_or_default()))
+}
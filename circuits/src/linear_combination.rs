@@ -14,7 +14,7 @@ use plonky2::plonk::circuit_builder::CircuitBuilder;
 use crate::cross_table_lookup::ColumnWithTypedInput;
 
 /// Represent a linear combination of columns.
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct ColumnSparse<F> {
     /// Linear combination of the local row
     pub lv_linear_combination: Vec<(usize, F)>,
@@ -0,0 +1,111 @@
+use core::ops::Add;
+
+use mozak_sdk::core::reg_abi::{REG_A1, REG_A2, REG_A3};
+
+use crate::columns_view::{make_col_map, ColumnsView, NumberOfColumns};
+use crate::cross_table_lookup::ColumnWithTypedInput;
+use crate::linear_combination::Column;
+use crate::memory::columns::MemoryCtl;
+use crate::register::RegisterCtl;
+use crate::stark::mozak_stark::{MemCopyTable, TableWithTypedOutput};
+
+make_col_map!(MemCopy);
+/// A row writes one byte of a memcopy or memset ecall to `dst`, after loading
+/// it from `src` for a memcopy.
+///
+/// The rows of an ecall are consecutive, and count `len` down from the length
+/// of the ecall to one. An empty ecall has a single row with a `len` of zero,
+/// which accesses no memory.
+#[repr(C)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
+pub struct MemCopy<T> {
+    pub is_copy: T,
+    pub is_set: T,
+    /// The first row of an ecall.
+    pub is_start: T,
+    pub clk: T,
+    pub dst: T,
+    /// The source address when copying, and the byte to set when setting.
+    pub src: T,
+    /// Bytes left, including the one of this row.
+    pub len: T,
+    pub len_inv: T,
+    /// Whether `len` is not zero, ie the row writes a byte.
+    pub is_byte: T,
+    /// Whether the row loads a byte, ie `is_copy` and `is_byte`.
+    pub is_load: T,
+    pub value: T,
+}
+
+pub const NUM_MEMCOPY_COLS: usize = MemCopy::<()>::NUMBER_OF_COLUMNS;
+
+impl<T: Copy + Add<Output = T>> MemCopy<T> {
+    pub fn is_executed(&self) -> T { self.is_copy + self.is_set }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
+pub struct MemCopyCtl<T> {
+    pub clk: T,
+    pub is_set: T,
+}
+
+#[must_use]
+pub fn lookup_for_cpu() -> TableWithTypedOutput<MemCopyCtl<Column>> {
+    MemCopyTable::new(
+        MemCopyCtl {
+            clk: COL_MAP.clk,
+            is_set: COL_MAP.is_set,
+        },
+        COL_MAP.is_start,
+    )
+}
+
+/// The first row of an ecall reads its arguments.
+#[must_use]
+pub fn register_looking() -> Vec<TableWithTypedOutput<RegisterCtl<Column>>> {
+    let is_read = ColumnWithTypedInput::constant(1);
+    [
+        (COL_MAP.dst, REG_A1),
+        (COL_MAP.src, REG_A2),
+        (COL_MAP.len, REG_A3),
+    ]
+    .into_iter()
+    .map(|(value, reg)| {
+        MemCopyTable::new(
+            RegisterCtl {
+                clk: COL_MAP.clk,
+                op: is_read,
+                value,
+                addr: ColumnWithTypedInput::constant(reg.into()),
+            },
+            COL_MAP.is_start,
+        )
+    })
+    .collect()
+}
+
+/// The load from `src` and the store to `dst`. Loads come before stores in
+/// the same cycle, so a memcopy sees the bytes of `src` as they were before
+/// the ecall, even if the ranges overlap.
+#[must_use]
+pub fn lookup_for_memory() -> Vec<TableWithTypedOutput<MemoryCtl<Column>>> {
+    [
+        (COL_MAP.src, 0, COL_MAP.is_load),
+        (COL_MAP.dst, 1, COL_MAP.is_byte),
+    ]
+    .into_iter()
+    .map(|(addr, is_store, filter)| {
+        MemCopyTable::new(
+            MemoryCtl {
+                clk: COL_MAP.clk,
+                is_store: ColumnWithTypedInput::constant(is_store),
+                is_load: ColumnWithTypedInput::constant(1 - is_store),
+                addr,
+                value: COL_MAP.value,
+            },
+            filter,
+        )
+    })
+    .collect()
+}
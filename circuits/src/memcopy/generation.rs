@@ -0,0 +1,55 @@
+use mozak_runner::memcopy::{Entry, Operation};
+use mozak_runner::vm::Row;
+use plonky2::hash::hash_types::RichField;
+
+use super::columns::MemCopy;
+use crate::utils::pad_trace_with_default;
+
+/// One row per byte of the ecall, or a single row without a byte if it is
+/// empty.
+fn generate_rows<F: RichField>(clk: u64, entry: &Entry) -> Vec<MemCopy<F>> {
+    let is_copy = entry.operation == Operation::Copy;
+    let row = |i: usize, value: u32| {
+        let offset = u32::try_from(i).expect("memcopy lengths fit into u32");
+        let len = F::from_canonical_usize(entry.bytes.len() - i);
+        let is_byte = i < entry.bytes.len();
+        MemCopy {
+            is_copy: F::from_bool(is_copy),
+            is_set: F::from_bool(!is_copy),
+            is_start: F::from_bool(i == 0),
+            clk: F::from_canonical_u64(clk),
+            dst: F::from_canonical_u32(entry.dst.wrapping_add(offset)),
+            src: F::from_canonical_u32(if is_copy {
+                entry.src.wrapping_add(offset)
+            } else {
+                entry.src
+            }),
+            len,
+            len_inv: len.try_inverse().unwrap_or_default(),
+            is_byte: F::from_bool(is_byte),
+            is_load: F::from_bool(is_copy && is_byte),
+            value: F::from_canonical_u32(value),
+        }
+    };
+    if entry.bytes.is_empty() {
+        // A memset repeats the byte to set in `value`, even without bytes.
+        return vec![row(0, entry.src)];
+    }
+    entry
+        .bytes
+        .iter()
+        .enumerate()
+        .map(|(i, &value)| row(i, u32::from(value)))
+        .collect()
+}
+
+#[must_use]
+pub fn generate_memcopy_trace<F: RichField>(step_rows: &[Row<F>]) -> Vec<MemCopy<F>> {
+    pad_trace_with_default(
+        step_rows
+            .iter()
+            .filter_map(|row| Some(generate_rows(row.state.clk, row.aux.memcopy.as_ref()?)))
+            .flatten()
+            .collect(),
+    )
+}
@@ -0,0 +1,3 @@
+pub mod columns;
+pub mod generation;
+pub mod stark;
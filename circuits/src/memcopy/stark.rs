@@ -0,0 +1,161 @@
+use std::marker::PhantomData;
+
+use expr::{Expr, ExprBuilder, StarkFrameTyped};
+use mozak_circuits_derive::StarkNameDisplay;
+use plonky2::field::extension::{Extendable, FieldExtension};
+use plonky2::field::packed::PackedField;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::ext_target::ExtensionTarget;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use starky::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
+use starky::evaluation_frame::StarkFrame;
+use starky::stark::Stark;
+
+use super::columns::{MemCopy, NUM_MEMCOPY_COLS};
+use crate::columns_view::HasNamedColumns;
+use crate::expr::{build_ext, build_packed, ConstraintBuilder};
+use crate::unstark::NoColumns;
+
+#[derive(Copy, Clone, Default, StarkNameDisplay)]
+#[allow(clippy::module_name_repetitions)]
+pub struct MemCopyStark<F, const D: usize> {
+    pub _f: PhantomData<F>,
+}
+
+impl<F, const D: usize> HasNamedColumns for MemCopyStark<F, D> {
+    type Columns = MemCopy<F>;
+}
+
+const COLUMNS: usize = NUM_MEMCOPY_COLS;
+const PUBLIC_INPUTS: usize = 0;
+
+fn generate_constraints<'a, T: Copy>(
+    vars: &StarkFrameTyped<MemCopy<Expr<'a, T>>, NoColumns<Expr<'a, T>>>,
+) -> ConstraintBuilder<Expr<'a, T>> {
+    let lv = vars.local_values;
+    let nv = vars.next_values;
+    let mut constraints = ConstraintBuilder::default();
+
+    constraints.always(lv.is_copy.is_binary());
+    constraints.always(lv.is_set.is_binary());
+    constraints.always(lv.is_executed().is_binary());
+    constraints.always(lv.is_start.is_binary());
+    constraints.always(lv.is_start * (1 - lv.is_executed()));
+    constraints.first_row(lv.is_executed() - lv.is_start);
+
+    // A row writes a byte exactly when `len` is not zero, and padding has
+    // none.
+    constraints.always(lv.is_byte - lv.len * lv.len_inv);
+    constraints.always(lv.len * (1 - lv.is_byte));
+    constraints.always((1 - lv.is_executed()) * lv.len);
+    constraints.always(lv.is_load - lv.is_copy * lv.is_byte);
+
+    // A memset writes the byte to set, which the memory table range checks.
+    constraints.always(lv.is_set * (lv.value - lv.src));
+
+    // The next row continues the ecall of this one with the next byte...
+    let continues = nv.is_executed() - nv.is_start;
+    constraints.transition(continues * (nv.is_copy - lv.is_copy));
+    constraints.transition(continues * (nv.is_set - lv.is_set));
+    constraints.transition(continues * (nv.clk - lv.clk));
+    constraints.transition(continues * (nv.dst - lv.dst - 1));
+    constraints.transition(continues * (nv.src - lv.src - nv.is_copy));
+    constraints.transition(continues * (nv.len - lv.len + 1));
+    // ... unless this row has the last byte, or none.
+    constraints.transition((lv.is_executed() - continues) * (lv.len - lv.is_byte));
+    constraints.last_row(lv.len - lv.is_byte);
+
+    constraints
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for MemCopyStark<F, D> {
+    type EvaluationFrame<FE, P, const D2: usize> = StarkFrame<P, P::Scalar, COLUMNS, PUBLIC_INPUTS>
+    where
+        FE: FieldExtension<D2, BaseField = F>,
+        P: PackedField<Scalar = FE>;
+    type EvaluationFrameTarget =
+        StarkFrame<ExtensionTarget<D>, ExtensionTarget<D>, COLUMNS, PUBLIC_INPUTS>;
+
+    fn eval_packed_generic<FE, P, const D2: usize>(
+        &self,
+        vars: &Self::EvaluationFrame<FE, P, D2>,
+        consumer: &mut ConstraintConsumer<P>,
+    ) where
+        FE: FieldExtension<D2, BaseField = F>,
+        P: PackedField<Scalar = FE>, {
+        let eb = ExprBuilder::default();
+        let constraints = generate_constraints(&eb.to_typed_starkframe(vars));
+        build_packed(constraints, consumer);
+    }
+
+    fn eval_ext_circuit(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        vars: &Self::EvaluationFrameTarget,
+        consumer: &mut RecursiveConstraintConsumer<F, D>,
+    ) {
+        let eb = ExprBuilder::default();
+        let constraints = generate_constraints(&eb.to_typed_starkframe(vars));
+        build_ext(constraints, builder, consumer);
+    }
+
+    fn constraint_degree(&self) -> usize { 3 }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use plonky2::field::types::Field;
+    use plonky2::plonk::config::{GenericConfig, Poseidon2GoldilocksConfig};
+    use starky::stark_testing::{test_stark_circuit_constraints, test_stark_low_degree};
+
+    use super::MemCopyStark;
+    use crate::generation::debug_single_trace;
+    use crate::memcopy::generation::generate_memcopy_trace;
+    use crate::stark::mozak_stark::MozakStark;
+    use crate::stark::utils::trace_rows_to_poly_values;
+    use crate::test_utils::{memcopy_test, ProveAndVerify};
+
+    const D: usize = 2;
+    type C = Poseidon2GoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+    type S = MemCopyStark<F, D>;
+
+    #[test]
+    fn prove_copy_and_set_with_mozak_stark() {
+        let (program, record) = memcopy_test();
+        MozakStark::prove_and_verify(&program, &record).unwrap();
+    }
+
+    /// Starting a new ecall in the middle of one would skip its last bytes.
+    #[test]
+    #[should_panic(expected = "debug_api_has_constraint_failed")]
+    fn restart_fails() {
+        let (_program, record) = memcopy_test();
+        let mut rows = generate_memcopy_trace(&record.executed);
+        rows[1].is_start = F::ONE;
+        debug_single_trace::<F, D, _>(&S::default(), &trace_rows_to_poly_values(rows), &[]);
+    }
+
+    #[test]
+    #[should_panic(expected = "debug_api_has_constraint_failed")]
+    fn skipped_byte_fails() {
+        let (_program, record) = memcopy_test();
+        let mut rows = generate_memcopy_trace(&record.executed);
+        rows[1].dst += F::ONE;
+        debug_single_trace::<F, D, _>(&S::default(), &trace_rows_to_poly_values(rows), &[]);
+    }
+
+    #[test]
+    fn test_degree() -> Result<()> {
+        let stark = S::default();
+        test_stark_low_degree(stark)
+    }
+
+    #[test]
+    fn test_circuit() -> Result<()> {
+        let stark = S::default();
+        test_stark_circuit_constraints::<F, C, S, D>(stark)?;
+        Ok(())
+    }
+}
@@ -1,26 +1,38 @@
-use core::ops::Add;
+use core::ops::{Add, Sub};
 
-use itertools::izip;
+use itertools::{chain, izip};
 use plonky2::hash::hash_types::RichField;
+#[cfg(feature = "poseidon2")]
 use plonky2::hash::hashing::PlonkyPermutation;
+#[cfg(feature = "poseidon2")]
 use plonky2::hash::poseidon2::Poseidon2Permutation;
 
-use crate::columns_view::{columns_view_impl, make_col_map};
+use crate::columns_view::{make_col_map, ColumnsView};
 use crate::cross_table_lookup::Column;
+use crate::event_emission::columns::EventEmission;
+#[cfg(feature = "goldilocks")]
+use crate::goldilocks::columns::{Goldilocks, GOLDILOCKS_BYTES};
+#[cfg(feature = "memcopy")]
+use crate::memcopy::columns::MemCopy;
 use crate::memory_fullword::columns::FullWordMemory;
 use crate::memory_halfword::columns::HalfWordMemory;
-use crate::memory_zeroinit::columns::MemoryZeroInit;
 use crate::memoryinit::columns::{MemoryInit, MemoryInitCtl};
+#[cfg(feature = "poseidon2")]
 use crate::poseidon2_output_bytes::columns::{Poseidon2OutputBytes, BYTES_COUNT};
+#[cfg(feature = "poseidon2")]
 use crate::poseidon2_sponge::columns::Poseidon2Sponge;
 use crate::rangecheck::columns::RangeCheckCtl;
+#[cfg(feature = "secp256k1")]
+use crate::secp256k1::columns::{Point, Secp256k1};
 use crate::stark::mozak_stark::{MemoryTable, TableWithTypedOutput};
 use crate::storage_device::columns::StorageDevice;
+#[cfg(feature = "u256")]
+use crate::u256::columns::{U256, U256_BYTES};
 
 /// Represents a row of the memory trace that is transformed from read-only,
 /// read-write, halfword and fullword memories
 #[repr(C)]
-#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
 pub struct Memory<T> {
     /// Indicates if a the memory address is writable.
     pub is_writable: T,
@@ -47,32 +59,19 @@ pub struct Memory<T> {
     /// Value of memory access.
     pub value: T,
 }
-columns_view_impl!(Memory);
 make_col_map!(MEM, Memory);
 
 impl<F: RichField> From<&MemoryInit<F>> for Option<Memory<F>> {
-    /// All other fields are intentionally set to defaults, and clk is
-    /// deliberately set to one, to come after any zero-init rows.
+    /// All other fields are intentionally set to defaults. Clock `clk` is
+    /// deliberately set to zero for zero-init rows, and to one for all other
+    /// inits, so that they come after any zero-init rows.
     fn from(row: &MemoryInit<F>) -> Self {
-        row.filter.is_one().then(|| Memory {
+        row.is_executed().is_one().then(|| Memory {
             is_writable: row.is_writable,
             addr: row.address,
             is_init: F::ONE,
             value: row.value,
-            clk: F::ONE,
-            ..Default::default()
-        })
-    }
-}
-
-impl<F: RichField> From<&MemoryZeroInit<F>> for Option<Memory<F>> {
-    /// Clock `clk` is deliberately set to zero, to come before 'real' init
-    /// rows.
-    fn from(row: &MemoryZeroInit<F>) -> Self {
-        row.filter.is_one().then(|| Memory {
-            is_writable: F::ONE,
-            addr: row.addr,
-            is_init: F::ONE,
+            clk: F::ONE - row.source.is_zero,
             ..Default::default()
         })
     }
@@ -116,6 +115,7 @@ impl<F: RichField> From<&FullWordMemory<F>> for Vec<Memory<F>> {
     }
 }
 
+#[cfg(feature = "poseidon2")]
 impl<F: RichField> From<&Poseidon2Sponge<F>> for Vec<Memory<F>> {
     fn from(value: &Poseidon2Sponge<F>) -> Self {
         if (value.ops.is_permute + value.ops.is_init_permute).is_zero() {
@@ -137,6 +137,7 @@ impl<F: RichField> From<&Poseidon2Sponge<F>> for Vec<Memory<F>> {
     }
 }
 
+#[cfg(feature = "poseidon2")]
 impl<F: RichField> From<&Poseidon2OutputBytes<F>> for Vec<Memory<F>> {
     fn from(value: &Poseidon2OutputBytes<F>) -> Self {
         if value.is_executed.is_zero() {
@@ -158,6 +159,132 @@ impl<F: RichField> From<&Poseidon2OutputBytes<F>> for Vec<Memory<F>> {
     }
 }
 
+#[cfg(feature = "secp256k1")]
+impl<F: RichField> From<&Secp256k1<F>> for Vec<Memory<F>> {
+    /// Loads come first, so that they see `p` before the result is stored over
+    /// it in the same cycle.
+    fn from(value: &Secp256k1<F>) -> Self {
+        let access = |addr: F, point: Point<F>, is_store: bool| {
+            izip!(0_u64.., chain!(point.x, point.y)).map(move |(i, byte)| Memory {
+                clk: value.clk,
+                addr: addr + F::from_canonical_u64(i),
+                is_store: F::from_bool(is_store),
+                is_load: F::from_bool(!is_store),
+                value: byte,
+                ..Default::default()
+            })
+        };
+        let mut accesses = vec![];
+        if value.is_executed().is_one() {
+            accesses.extend(access(value.p_addr, value.p, false));
+        }
+        if value.is_add.is_one() {
+            accesses.extend(access(value.q_addr, value.q, false));
+        }
+        if value.is_executed().is_one() {
+            accesses.extend(access(value.p_addr, value.result, true));
+        }
+        accesses
+    }
+}
+
+#[cfg(feature = "u256")]
+impl<F: RichField> From<&U256<F>> for Vec<Memory<F>> {
+    /// Loads come first, so that they see `a` before the result is stored over
+    /// it in the same cycle.
+    fn from(value: &U256<F>) -> Self {
+        let access = |addr: F, bytes: [F; U256_BYTES], is_store: bool| {
+            izip!(0_u64.., bytes).map(move |(i, byte)| Memory {
+                clk: value.clk,
+                addr: addr + F::from_canonical_u64(i),
+                is_store: F::from_bool(is_store),
+                is_load: F::from_bool(!is_store),
+                value: byte,
+                ..Default::default()
+            })
+        };
+        if value.is_executed().is_zero() {
+            return vec![];
+        }
+        let mut accesses: Vec<_> = chain!(
+            access(value.a_addr, value.a, false),
+            access(value.b_addr, value.b, false),
+        )
+        .collect();
+        if value.is_mulmod.is_one() {
+            accesses.extend(access(value.m_addr, value.m, false));
+        }
+        accesses.extend(access(value.a_addr, value.result, true));
+        accesses
+    }
+}
+
+#[cfg(feature = "memcopy")]
+impl<F: RichField> From<&MemCopy<F>> for Vec<Memory<F>> {
+    fn from(value: &MemCopy<F>) -> Self {
+        let access = |addr: F, is_store: bool| Memory {
+            clk: value.clk,
+            addr,
+            is_store: F::from_bool(is_store),
+            is_load: F::from_bool(!is_store),
+            value: value.value,
+            ..Default::default()
+        };
+        chain!(
+            value.is_load.is_one().then(|| access(value.src, false)),
+            value.is_byte.is_one().then(|| access(value.dst, true)),
+        )
+        .collect()
+    }
+}
+
+#[cfg(feature = "goldilocks")]
+impl<F: RichField> From<&Goldilocks<F>> for Vec<Memory<F>> {
+    /// Loads come first, so that they see `a` before the result is stored over
+    /// it in the same cycle.
+    fn from(value: &Goldilocks<F>) -> Self {
+        let access = |addr: F, bytes: [F; GOLDILOCKS_BYTES], is_store: bool| {
+            izip!(0_u64.., bytes).map(move |(i, byte)| Memory {
+                clk: value.clk,
+                addr: addr + F::from_canonical_u64(i),
+                is_store: F::from_bool(is_store),
+                is_load: F::from_bool(!is_store),
+                value: byte,
+                ..Default::default()
+            })
+        };
+        if value.is_executed().is_zero() {
+            return vec![];
+        }
+        let mut accesses: Vec<_> = access(value.a_addr, value.a.bytes, false).collect();
+        if value.is_binary().is_one() {
+            accesses.extend(access(value.b_addr, value.b.bytes, false));
+        }
+        accesses.extend(access(value.a_addr, value.result.bytes, true));
+        accesses
+    }
+}
+
+impl<F: RichField> From<&EventEmission<F>> for Vec<Memory<F>> {
+    fn from(value: &EventEmission<F>) -> Self {
+        if value.is_executed.is_zero() {
+            return vec![];
+        }
+        let event_type: F = izip!(0_u64.., value.event_type)
+            .map(|(i, selector)| selector * F::from_canonical_u64(i))
+            .sum();
+        izip!(0_u64.., chain!(value.object, [event_type], value.value))
+            .map(|(i, byte)| Memory {
+                clk: value.clk,
+                addr: value.addr + F::from_canonical_u64(i),
+                is_load: F::ONE,
+                value: byte,
+                ..Default::default()
+            })
+            .collect()
+    }
+}
+
 impl<F: RichField> From<&StorageDevice<F>> for Option<Memory<F>> {
     fn from(val: &StorageDevice<F>) -> Self {
         (val.ops.is_memory_store).is_one().then(|| Memory {
@@ -174,6 +301,45 @@ impl<T: Copy + Add<Output = T>> Memory<T> {
     pub fn is_executed(&self) -> T { self.is_store + self.is_load + self.is_init }
 }
 
+impl<T: Copy + Add<Output = T> + Sub<Output = T>> Memory<T> {
+    /// `4 * clk - 3 * is_init - 2 * is_load - is_store`, so that within a
+    /// clock cycle an address is initialised first, then loaded, then
+    /// stored, and padding comes last. See [`rangecheck_looking`] for how
+    /// this orders the trace.
+    pub fn augmented_clk(self) -> T {
+        let double = |x: T| x + x;
+        double(double(self.clk))
+            - double(self.is_load)
+            - self.is_store
+            - double(self.is_init)
+            - self.is_init
+    }
+}
+
+/// Range checks that order the memory trace by address first, and by
+/// [`Memory::augmented_clk`] second.
+///
+/// Together with the constraints in
+/// [`MemoryStark`](crate::memory::stark::MemoryStark), this is the memory
+/// consistency argument:
+///
+/// - The first row is an init of address 0, and the last row has address
+///   `u32::MAX`.
+/// - The address only changes at an init, and then strictly increases. So every
+///   address is a contiguous block of rows that starts with its only init.
+/// - Within a block, the augmented clock never decreases. That puts the init
+///   first, and within any clock cycle loads before stores, so that eg a
+///   poseidon2 ecall that reads and writes the same addresses sees the old
+///   values.
+/// - Stores strictly increase the augmented clock. So there is at most one
+///   store per address and clock cycle, even when several tables (eg the
+///   poseidon2 output and a tape) write at the same clock cycle, and the value
+///   the next clock cycle sees is well defined.
+/// - Every load repeats the value of the row before it, ie the value of the
+///   last init or store.
+///
+/// Padding rows come after all executed rows, and only repeat the last
+/// address.
 #[must_use]
 pub fn rangecheck_looking() -> Vec<TableWithTypedOutput<RangeCheckCtl<Column>>> {
     vec![
@@ -183,14 +349,10 @@ pub fn rangecheck_looking() -> Vec<TableWithTypedOutput<RangeCheckCtl<Column>>>
             RangeCheckCtl(MEM.addr.diff() - MEM.is_init.flip()),
             MEM.is_executed(),
         ),
-        // Anything but an init has a non-negative clock difference.
-        // We augment the clock difference, to make sure that for the same clock cycle the order is
-        // as follows: init, load, store, dummy.
-        // Specifically, loads should come before stores, so that eg a poseidon ecall that reads
-        // and writes to the same memory addresses will do the Right Thing.
+        // Anything but an init has a non-negative augmented clock difference, and a store has a
+        // positive one.
         MemoryTable::new(
-            // TODO: put augmented_clock function into columns, like for registers.
-            RangeCheckCtl((MEM.clk * 4 - MEM.is_store - MEM.is_load * 2 - MEM.is_init * 3).diff()),
+            RangeCheckCtl(MEM.augmented_clk().diff() - MEM.is_store.flip()),
             (1 - MEM.is_init).flip(),
         ),
     ]
@@ -204,9 +366,8 @@ pub fn rangecheck_u8_looking() -> Vec<TableWithTypedOutput<RangeCheckCtl<Column>
     )]
 }
 
-columns_view_impl!(MemoryCtl);
 #[repr(C)]
-#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
 pub struct MemoryCtl<T> {
     pub clk: T,
     pub is_store: T,
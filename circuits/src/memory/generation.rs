@@ -4,17 +4,29 @@ use itertools::chain;
 use mozak_runner::instruction::Op;
 use mozak_runner::vm::Row;
 use plonky2::hash::hash_types::RichField;
+#[allow(clippy::wildcard_imports)]
+use plonky2_maybe_rayon::*;
 
+use crate::event_emission::columns::EventEmission;
 use crate::generation::MIN_TRACE_LENGTH;
+#[cfg(feature = "goldilocks")]
+use crate::goldilocks::columns::Goldilocks;
+#[cfg(feature = "memcopy")]
+use crate::memcopy::columns::MemCopy;
 use crate::memory::columns::Memory;
 use crate::memory::trace::{get_memory_inst_addr, get_memory_inst_clk, get_memory_raw_value};
 use crate::memory_fullword::columns::FullWordMemory;
 use crate::memory_halfword::columns::HalfWordMemory;
-use crate::memory_zeroinit::columns::MemoryZeroInit;
 use crate::memoryinit::columns::MemoryInit;
+#[cfg(feature = "poseidon2")]
 use crate::poseidon2_output_bytes::columns::Poseidon2OutputBytes;
+#[cfg(feature = "poseidon2")]
 use crate::poseidon2_sponge::columns::Poseidon2Sponge;
+#[cfg(feature = "secp256k1")]
+use crate::secp256k1::columns::Secp256k1;
 use crate::storage_device::columns::StorageDevice;
+#[cfg(feature = "u256")]
+use crate::u256::columns::U256;
 
 /// Pad the memory trace to a power of 2.
 #[must_use]
@@ -39,11 +51,12 @@ fn pad_mem_trace<F: RichField>(mut trace: Vec<Memory<F>>) -> Vec<Memory<F>> {
 /// `Program`. These need to be further interleaved with
 /// static memory trace generated from `Program` for final
 /// execution for final memory trace.
-pub fn generate_memory_trace_from_execution<F: RichField>(
-    step_rows: &[Row<F>],
-) -> impl Iterator<Item = Memory<F>> + '_ {
+///
+/// Rows are generated in parallel, in the order of `step_rows`.
+#[must_use]
+pub fn generate_memory_trace_from_execution<F: RichField>(step_rows: &[Row<F>]) -> Vec<Memory<F>> {
     step_rows
-        .iter()
+        .par_iter()
         .filter(|row| {
             row.aux.mem.is_some() && matches!(row.instruction.op, Op::LB | Op::LBU | Op::SB)
         })
@@ -61,6 +74,7 @@ pub fn generate_memory_trace_from_execution<F: RichField>(
                 ..Default::default()
             }
         })
+        .collect()
 }
 
 /// Generates Memory trace from a memory init table.
@@ -75,18 +89,6 @@ pub fn transform_memory_init<F: RichField>(
         .filter_map(Option::<Memory<F>>::from)
 }
 
-/// Generates Memory trace from a memory zero init table.
-///
-/// These need to be further interleaved with runtime memory trace generated
-/// from VM execution for final memory trace.
-pub fn transform_memory_zero_init<F: RichField>(
-    memory_init_rows: &[MemoryZeroInit<F>],
-) -> impl Iterator<Item = Memory<F>> + '_ {
-    memory_init_rows
-        .iter()
-        .filter_map(Option::<Memory<F>>::from)
-}
-
 /// Generates Memory trace from a memory half-word table.
 ///
 /// These need to be further interleaved with runtime memory trace generated
@@ -99,18 +101,57 @@ pub fn transform_halfword<F: RichField>(
         .flat_map(Into::<Vec<Memory<F>>>::into)
 }
 
+#[cfg(feature = "poseidon2")]
 pub fn transform_poseidon2_sponge<F: RichField>(
     sponge_data: &[Poseidon2Sponge<F>],
 ) -> impl Iterator<Item = Memory<F>> + '_ {
     sponge_data.iter().flat_map(Into::<Vec<Memory<F>>>::into)
 }
 
+#[cfg(feature = "poseidon2")]
 pub fn transform_poseidon2_output_bytes<F: RichField>(
     output_bytes: &[Poseidon2OutputBytes<F>],
 ) -> impl Iterator<Item = Memory<F>> + '_ {
     output_bytes.iter().flat_map(Into::<Vec<Memory<F>>>::into)
 }
 
+#[cfg(feature = "secp256k1")]
+pub fn transform_secp256k1<F: RichField>(
+    secp256k1_rows: &[Secp256k1<F>],
+) -> impl Iterator<Item = Memory<F>> + '_ {
+    secp256k1_rows.iter().flat_map(Into::<Vec<Memory<F>>>::into)
+}
+
+#[cfg(feature = "u256")]
+pub fn transform_u256<F: RichField>(u256_rows: &[U256<F>]) -> impl Iterator<Item = Memory<F>> + '_ {
+    u256_rows.iter().flat_map(Into::<Vec<Memory<F>>>::into)
+}
+
+#[cfg(feature = "memcopy")]
+pub fn transform_memcopy<F: RichField>(
+    memcopy_rows: &[MemCopy<F>],
+) -> impl Iterator<Item = Memory<F>> + '_ {
+    memcopy_rows.iter().flat_map(Into::<Vec<Memory<F>>>::into)
+}
+
+#[cfg(feature = "goldilocks")]
+pub fn transform_goldilocks<F: RichField>(
+    goldilocks_rows: &[Goldilocks<F>],
+) -> impl Iterator<Item = Memory<F>> + '_ {
+    goldilocks_rows
+        .iter()
+        .flat_map(Into::<Vec<Memory<F>>>::into)
+}
+
+/// Generates Memory trace from the loads of emitted events.
+pub fn transform_event_emission<F: RichField>(
+    event_emission_rows: &[EventEmission<F>],
+) -> impl Iterator<Item = Memory<F>> + '_ {
+    event_emission_rows
+        .iter()
+        .flat_map(Into::<Vec<Memory<F>>>::into)
+}
+
 /// Generates Memory trace from a memory full-word table.
 ///
 /// These need to be further interleaved with runtime memory trace generated
@@ -133,16 +174,34 @@ pub fn transform_storage<F: RichField>(
     storage.iter().filter_map(Option::<Memory<F>>::from)
 }
 
-fn key<F: RichField>(memory: &Memory<F>) -> (u64, u64) {
+/// Generates Memory trace from the output tape table, whose memory rows load
+/// the bytes that the guest writes.
+#[cfg(feature = "poseidon2")]
+pub fn transform_output_tape<F: RichField>(
+    output_tape: &[StorageDevice<F>],
+) -> impl Iterator<Item = Memory<F>> + '_ {
+    transform_storage(output_tape).map(|memory| Memory {
+        is_store: F::ZERO,
+        is_load: F::ONE,
+        ..memory
+    })
+}
+
+/// Sorts by address, and then by [`Memory::augmented_clk`], as the range
+/// checks in [`rangecheck_looking`](crate::memory::columns::rangecheck_looking)
+/// require.
+fn key<F: RichField>(memory: &Memory<F>) -> (u64, u64, u64) {
+    let op_rank = memory.is_load + memory.is_store.double();
     (
         memory.addr.to_canonical_u64(),
         memory.clk.to_canonical_u64(),
+        op_rank.to_canonical_u64(),
     )
 }
 
-/// Generates memory trace using static component `program` for memory
-/// initialization and dynamic component `step_rows` for access (load and store)
-/// of memory elements.
+/// Generates memory trace using static component `elf_memory_init_rows` and
+/// the zero inits in `memory_init_rows` for memory initialization and dynamic
+/// component `step_rows` for access (load and store) of memory elements.
 /// Trace constraints are supposed to abide by read-only and read-write address
 /// constraints.
 /// Merge different types of memory traces in to one [Memory] trace
@@ -150,8 +209,8 @@ fn key<F: RichField>(memory: &Memory<F>) -> (u64, u64) {
 #[allow(clippy::too_many_arguments)]
 pub fn generate_memory_trace<F: RichField>(
     step_rows: &[Row<F>],
+    elf_memory_init_rows: &[MemoryInit<F>],
     memory_init_rows: &[MemoryInit<F>],
-    memory_zeroinit_rows: &[MemoryZeroInit<F>],
     halfword_memory_rows: &[HalfWordMemory<F>],
     fullword_memory_rows: &[FullWordMemory<F>],
     private_tape_rows: &[StorageDevice<F>],
@@ -161,15 +220,22 @@ pub fn generate_memory_trace<F: RichField>(
     events_commitment_tape_rows: &[StorageDevice<F>],
     castlist_commitment_tape_rows: &[StorageDevice<F>],
     self_prog_id_tape_rows: &[StorageDevice<F>],
-    poseidon2_sponge_rows: &[Poseidon2Sponge<F>],
-    poseidon2_output_bytes_rows: &[Poseidon2OutputBytes<F>],
+    hint_tape_rows: &[StorageDevice<F>],
+    event_emission_rows: &[EventEmission<F>],
+    #[cfg(feature = "poseidon2")] poseidon2_sponge_rows: &[Poseidon2Sponge<F>],
+    #[cfg(feature = "poseidon2")] poseidon2_output_bytes_rows: &[Poseidon2OutputBytes<F>],
+    #[cfg(feature = "poseidon2")] output_tape_rows: &[StorageDevice<F>],
+    #[cfg(feature = "secp256k1")] secp256k1_rows: &[Secp256k1<F>],
+    #[cfg(feature = "u256")] u256_rows: &[U256<F>],
+    #[cfg(feature = "memcopy")] memcopy_rows: &[MemCopy<F>],
+    #[cfg(feature = "goldilocks")] goldilocks_rows: &[Goldilocks<F>],
 ) -> Vec<Memory<F>> {
     // `merged_trace` is address sorted combination of static and
     // dynamic memory trace components of program (ELF and execution)
     // `merge` operation is expected to be stable
     let mut merged_trace: Vec<Memory<F>> = chain!(
+        transform_memory_init::<F>(elf_memory_init_rows),
         transform_memory_init::<F>(memory_init_rows),
-        transform_memory_zero_init(memory_zeroinit_rows),
         generate_memory_trace_from_execution(step_rows),
         transform_halfword(halfword_memory_rows),
         transform_fullword(fullword_memory_rows),
@@ -180,27 +246,41 @@ pub fn generate_memory_trace<F: RichField>(
         transform_storage(events_commitment_tape_rows),
         transform_storage(castlist_commitment_tape_rows),
         transform_storage(self_prog_id_tape_rows),
-        transform_poseidon2_sponge(poseidon2_sponge_rows),
-        transform_poseidon2_output_bytes(poseidon2_output_bytes_rows,),
+        transform_storage(hint_tape_rows),
+        transform_event_emission(event_emission_rows),
     )
     .collect();
-
-    let read_only_addresses: HashSet<F> = memory_init_rows
+    #[cfg(feature = "poseidon2")]
+    merged_trace.extend(chain!(
+        transform_poseidon2_sponge(poseidon2_sponge_rows),
+        transform_poseidon2_output_bytes(poseidon2_output_bytes_rows),
+        transform_output_tape(output_tape_rows),
+    ));
+    #[cfg(feature = "secp256k1")]
+    merged_trace.extend(transform_secp256k1(secp256k1_rows));
+    #[cfg(feature = "u256")]
+    merged_trace.extend(transform_u256(u256_rows));
+    #[cfg(feature = "memcopy")]
+    merged_trace.extend(transform_memcopy(memcopy_rows));
+    #[cfg(feature = "goldilocks")]
+    merged_trace.extend(transform_goldilocks(goldilocks_rows));
+
+    let read_only_addresses: HashSet<F> = elf_memory_init_rows
         .iter()
-        .filter(|row| row.filter.is_nonzero() && row.is_writable.is_zero())
+        .filter(|row| row.is_executed().is_nonzero() && row.is_writable.is_zero())
         .map(|row| row.address)
         .collect();
 
     merged_trace.sort_by_key(key);
     let merged_trace: Vec<_> = merged_trace
-        .into_iter()
+        .into_par_iter()
         .map(|mem| Memory {
             is_writable: F::from_bool(!read_only_addresses.contains(&mem.addr)),
             ..mem
         })
         .collect();
 
-    log::trace!("trace {:?}", merged_trace);
+    tracing::trace!("trace {:?}", merged_trace);
     pad_mem_trace(merged_trace)
 }
 
@@ -209,28 +289,42 @@ mod tests {
     use im::hashmap::HashMap;
     use mozak_runner::elf::{Data, Program};
     use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::field::types::PrimeField64;
     use plonky2::plonk::config::{GenericConfig, Poseidon2GoldilocksConfig};
-    use plonky2::util::timing::TimingTree;
-    use starky::prover::prove as prove_table;
-    use starky::verifier::verify_stark_proof;
 
     use super::pad_mem_trace;
-    use crate::memory::columns::Memory;
+    use crate::event_emission::generation::generate_event_emission_trace;
+    use crate::generation::debug_single_trace;
+    #[cfg(feature = "goldilocks")]
+    use crate::goldilocks::generation::generate_goldilocks_trace;
+    #[cfg(feature = "memcopy")]
+    use crate::memcopy::generation::generate_memcopy_trace;
+    use crate::memory::columns::{rangecheck_looking, Memory};
     use crate::memory::stark::MemoryStark;
     use crate::memory::test_utils::memory_trace_test_case;
     use crate::memory_fullword::generation::generate_fullword_memory_trace;
     use crate::memory_halfword::generation::generate_halfword_memory_trace;
-    use crate::memory_zeroinit::generation::generate_memory_zero_init_trace;
-    use crate::memoryinit::generation::generate_memory_init_trace;
+    use crate::memoryinit::generation::{
+        generate_elf_memory_init_trace, generate_memory_init_trace,
+    };
+    #[cfg(feature = "poseidon2")]
     use crate::poseidon2_output_bytes::generation::generate_poseidon2_output_bytes_trace;
+    #[cfg(feature = "poseidon2")]
     use crate::poseidon2_sponge::generation::generate_poseidon2_sponge_trace;
+    use crate::rangecheck::generation::extract_with_mul;
+    #[cfg(feature = "secp256k1")]
+    use crate::secp256k1::generation::generate_secp256k1_trace;
     use crate::stark::utils::trace_rows_to_poly_values;
+    #[cfg(feature = "poseidon2")]
+    use crate::storage_device::generation::generate_output_tape_trace;
     use crate::storage_device::generation::{
         generate_call_tape_trace, generate_cast_list_commitment_tape_trace,
-        generate_event_tape_trace, generate_events_commitment_tape_trace,
+        generate_event_tape_trace, generate_events_commitment_tape_trace, generate_hint_tape_trace,
         generate_private_tape_trace, generate_public_tape_trace, generate_self_prog_id_tape_trace,
     };
-    use crate::test_utils::{fast_test_config, prep_table};
+    use crate::test_utils::prep_table;
+    #[cfg(feature = "u256")]
+    use crate::u256::generation::generate_u256_trace;
 
     const D: usize = 2;
     type C = Poseidon2GoldilocksConfig;
@@ -239,31 +333,44 @@ mod tests {
 
     #[rustfmt::skip]
     #[test]
-    #[ignore]
-    #[should_panic = "Constraint failed in"]
-    // TODO(Roman): fix this test, looks like we should constrain the `is_init`
+    #[should_panic = "debug_api_has_constraint_failed"]
     /// Test that we have a constraint to catch, if there is no init for any memory address.
     fn no_init() {
-        let _ = env_logger::try_init();
-        let stark = S::default();
-
         let trace: Vec<Memory<GoldilocksField>> = prep_table(vec![
-            //is_writable  addr  clk is_store, is_load, is_init  value
-            [       0,     100,   1,     0,      0,       0,        1],
-            [       1,     100,   1,     0,      0,       0,        2],
+            //is_writable  addr            clk is_store, is_load, is_init  value
+            [       1,     0,               1,     1,      0,       0,        1],
+            [       1,     u64::from(u32::MAX), 0, 0,      0,       1,        0],
         ]);
         let trace = pad_mem_trace(trace);
-        let trace_poly_values = trace_rows_to_poly_values(trace);
-        let config = fast_test_config();
-        // This will fail, iff debug assertions are enabled.
-        let proof = prove_table::<F, C, S, D>(
-            stark,
-            &config,
-            trace_poly_values,
-            &[],
-            &mut TimingTree::default(),
-        ).unwrap();
-        assert!(verify_stark_proof(stark, proof, &config).is_ok(), "failing constraint: init is required per memory address");
+        debug_single_trace::<F, D, _>(&S::default(), &trace_rows_to_poly_values(trace), &[]);
+    }
+
+    /// Values the memory table range checks, or `None` if any of them does not
+    /// fit into a `u32`.
+    fn rangechecked_values(trace: &[Memory<F>]) -> Option<Vec<u32>> {
+        rangecheck_looking()
+            .into_iter()
+            .flat_map(|table| extract_with_mul(trace, &table.to_untyped_output()))
+            .map(|(value, _)| u32::try_from(value.to_canonical_u64()).ok())
+            .collect()
+    }
+
+    #[rustfmt::skip]
+    #[test]
+    /// Test that two stores to the same address in the same clock cycle fail
+    /// the range check, while a load and a store pass it.
+    fn double_store_in_one_clk() {
+        let trace = |first_is_store: u64| -> Vec<Memory<F>> {
+            pad_mem_trace(prep_table(vec![
+                //is_writable  addr  clk  is_store,       is_load,            is_init  value
+                [       1,     0,    0,     0,              0,                  1,        0],
+                [       1,     0,    5,   first_is_store, 1 - first_is_store,   0,        0],
+                [       1,     0,    5,     1,              0,                  0,        9],
+                [       1, u64::from(u32::MAX), 0, 0,       0,                  1,        0],
+            ]))
+        };
+        assert!(rangechecked_values(&trace(0)).is_some());
+        assert_eq!(rangechecked_values(&trace(1)), None);
     }
 
     // TODO(Matthias): restore the test that shows that double-init is not allowed.
@@ -278,8 +385,8 @@ mod tests {
     fn generate_memory_trace() {
         let (program, record) = memory_trace_test_case(1);
 
-        let memory_init = generate_memory_init_trace(&program);
-        let memory_zeroinit_rows = generate_memory_zero_init_trace(&record.executed, &program);
+        let elf_memory_init_rows = generate_elf_memory_init_trace(&program);
+        let memory_init_rows = generate_memory_init_trace(&record.executed, &program);
 
         let halfword_memory = generate_halfword_memory_trace(&record.executed);
         let fullword_memory = generate_fullword_memory_trace(&record.executed);
@@ -291,13 +398,27 @@ mod tests {
         let events_commitment_tape_rows = generate_events_commitment_tape_trace(&record.executed);
         let cast_list_commitment_tape_rows = generate_cast_list_commitment_tape_trace(&record.executed);
         let self_prog_id_tape_rows = generate_self_prog_id_tape_trace(&record.executed);
+        let hint_tape_rows = generate_hint_tape_trace(&record.executed);
+        let event_emission_rows = generate_event_emission_trace(&record.executed);
+        #[cfg(feature = "secp256k1")]
+        let secp256k1_rows = generate_secp256k1_trace(&record.executed);
+        #[cfg(feature = "u256")]
+        let u256_rows = generate_u256_trace(&record.executed);
+        #[cfg(feature = "memcopy")]
+        let memcopy_rows = generate_memcopy_trace(&record.executed);
+        #[cfg(feature = "goldilocks")]
+        let goldilocks_rows = generate_goldilocks_trace(&record.executed);
+        #[cfg(feature = "poseidon2")]
         let poseidon2_sponge_trace = generate_poseidon2_sponge_trace(&record.executed);
+        #[cfg(feature = "poseidon2")]
         let poseidon2_output_bytes = generate_poseidon2_output_bytes_trace(&poseidon2_sponge_trace);
+        #[cfg(feature = "poseidon2")]
+        let output_tape_rows = generate_output_tape_trace(&record.executed);
 
         let trace = super::generate_memory_trace::<GoldilocksField>(
             &record.executed,
-            &memory_init,
-            &memory_zeroinit_rows,
+            &elf_memory_init_rows,
+            &memory_init_rows,
             &halfword_memory,
             &fullword_memory,
             &private_tape_rows,
@@ -307,8 +428,22 @@ mod tests {
             &events_commitment_tape_rows,
             &cast_list_commitment_tape_rows,
             &self_prog_id_tape_rows,
+            &hint_tape_rows,
+            &event_emission_rows,
+            #[cfg(feature = "poseidon2")]
             &poseidon2_sponge_trace,
+            #[cfg(feature = "poseidon2")]
             &poseidon2_output_bytes,
+            #[cfg(feature = "poseidon2")]
+            &output_tape_rows,
+            #[cfg(feature = "secp256k1")]
+            &secp256k1_rows,
+            #[cfg(feature = "u256")]
+            &u256_rows,
+            #[cfg(feature = "memcopy")]
+            &memcopy_rows,
+            #[cfg(feature = "goldilocks")]
+            &goldilocks_rows,
         );
         let last = u64::from(u32::MAX);
         assert_eq!(
@@ -370,8 +505,8 @@ mod tests {
             ..Program::default()
         };
 
-        let memory_init = generate_memory_init_trace(&program);
-        let memory_zeroinit_rows = generate_memory_zero_init_trace(&[], &program);
+        let elf_memory_init_rows = generate_elf_memory_init_trace(&program);
+        let memory_init_rows = generate_memory_init_trace(&[], &program);
 
         let halfword_memory = generate_halfword_memory_trace(&[]);
         let fullword_memory = generate_fullword_memory_trace(&[]);
@@ -383,12 +518,26 @@ mod tests {
         let cast_list_commitment_tape_rows =
             generate_cast_list_commitment_tape_trace(&[]);
         let self_prog_id_tape_rows = generate_self_prog_id_tape_trace(&[]);
+        let hint_tape_rows = generate_hint_tape_trace(&[]);
+        let event_emission_rows = generate_event_emission_trace(&[]);
+        #[cfg(feature = "secp256k1")]
+        let secp256k1_rows = generate_secp256k1_trace(&[]);
+        #[cfg(feature = "u256")]
+        let u256_rows = generate_u256_trace(&[]);
+        #[cfg(feature = "memcopy")]
+        let memcopy_rows = generate_memcopy_trace(&[]);
+        #[cfg(feature = "goldilocks")]
+        let goldilocks_rows = generate_goldilocks_trace(&[]);
+        #[cfg(feature = "poseidon2")]
         let poseidon2_trace = generate_poseidon2_sponge_trace(&[]);
+        #[cfg(feature = "poseidon2")]
         let poseidon2_output_bytes = generate_poseidon2_output_bytes_trace(&poseidon2_trace);
+        #[cfg(feature = "poseidon2")]
+        let output_tape_rows = generate_output_tape_trace(&[]);
         let trace = super::generate_memory_trace::<F>(
             &[],
-            &memory_init,
-            &memory_zeroinit_rows,
+            &elf_memory_init_rows,
+            &memory_init_rows,
             &halfword_memory,
             &fullword_memory,
             &private_tape_rows,
@@ -398,8 +547,22 @@ mod tests {
             &events_commitment_tape_rows,
             &cast_list_commitment_tape_rows,
             &self_prog_id_tape_rows,
+            &hint_tape_rows,
+            &event_emission_rows,
+            #[cfg(feature = "poseidon2")]
             &poseidon2_trace,
+            #[cfg(feature = "poseidon2")]
             &poseidon2_output_bytes,
+            #[cfg(feature = "poseidon2")]
+            &output_tape_rows,
+            #[cfg(feature = "secp256k1")]
+            &secp256k1_rows,
+            #[cfg(feature = "u256")]
+            &u256_rows,
+            #[cfg(feature = "memcopy")]
+            &memcopy_rows,
+            #[cfg(feature = "goldilocks")]
+            &goldilocks_rows,
         );
 
         let last = u64::from(u32::MAX);
@@ -59,6 +59,8 @@ fn generate_constraints<'a, T: Copy>(
     constraints.always((1 - nv.is_init) * (nv.addr - lv.addr));
     // ... and we have a range-check to make sure that addresses go up for each
     // init.
+    // Starting with an init then means that every address starts with one.
+    constraints.first_row(lv.is_init - 1);
 
     // Operation constraints
     // ---------------------
@@ -120,7 +122,7 @@ impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for MemoryStark<F
 mod tests {
     use anyhow::Result;
     use mozak_runner::code;
-    use mozak_runner::instruction::{Args, Instruction, Op};
+    use mozak_runner::instruction::Instruction;
     use plonky2::plonk::config::{GenericConfig, Poseidon2GoldilocksConfig};
     use starky::stark_testing::{test_stark_circuit_constraints, test_stark_low_degree};
 
@@ -161,33 +163,9 @@ mod tests {
         addr_offset: u32,
     ) -> Result<(), anyhow::Error> {
         let instructions = [
-            Instruction {
-                op: Op::ADD,
-                args: Args {
-                    rd: 1,
-                    rs1: 1,
-                    imm: 1_u32.wrapping_neg(),
-                    ..Args::default()
-                },
-            },
-            Instruction {
-                op: Op::SB,
-                args: Args {
-                    rs1: 1,
-                    rs2: 1,
-                    imm: addr_offset,
-                    ..Args::default()
-                },
-            },
-            Instruction {
-                op: Op::BLT,
-                args: Args {
-                    rs1: 0,
-                    rs2: 1,
-                    imm: 0,
-                    ..Args::default()
-                },
-            },
+            Instruction::addi(1, 1, 1_u32.wrapping_neg()),
+            Instruction::sb(1, 1, addr_offset),
+            Instruction::blt(0, 1, 0).unwrap(),
         ];
         let (program, record) = code::execute(instructions, &[], &[(1, iterations)]);
         Stark::prove_and_verify(&program, &record)
@@ -2,7 +2,7 @@ use core::ops::Add;
 
 use itertools::izip;
 
-use crate::columns_view::{columns_view_impl, make_col_map, NumberOfColumns};
+use crate::columns_view::{make_col_map, ColumnNames, ColumnsView, NumberOfColumns};
 use crate::cross_table_lookup::ColumnWithTypedInput;
 use crate::linear_combination::Column;
 use crate::memory::columns::MemoryCtl;
@@ -10,7 +10,7 @@ use crate::stark::mozak_stark::{FullWordMemoryTable, TableWithTypedOutput};
 
 /// Operations (one-hot encoded)
 #[repr(C)]
-#[derive(Clone, Copy, Eq, PartialEq, Debug, Default)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Default, ColumnNames)]
 pub struct Ops<T> {
     // One of `is_store`, `is_load`
     // If none are `1`, it is a padding row
@@ -24,7 +24,7 @@ pub struct Ops<T> {
 // value == linear combination via range-check
 // address_limbs also linear combination + forbid  wrapping add
 #[repr(C)]
-#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
 pub struct FullWordMemory<T> {
     /// Clock at memory access.
     pub clk: T,
@@ -34,7 +34,6 @@ pub struct FullWordMemory<T> {
     pub limbs: [T; 4],
 }
 
-columns_view_impl!(FullWordMemory);
 make_col_map!(FullWordMemory);
 
 impl<T: Copy + Add<Output = T>> FullWordMemory<T> {
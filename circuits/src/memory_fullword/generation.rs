@@ -74,19 +74,31 @@ mod tests {
     use mozak_runner::vm::ExecutionRecord;
     use plonky2::field::goldilocks_field::GoldilocksField;
 
+    use crate::event_emission::generation::generate_event_emission_trace;
+    #[cfg(feature = "goldilocks")]
+    use crate::goldilocks::generation::generate_goldilocks_trace;
+    #[cfg(feature = "memcopy")]
+    use crate::memcopy::generation::generate_memcopy_trace;
     use crate::memory::generation::generate_memory_trace;
     use crate::memory_fullword::generation::generate_fullword_memory_trace;
     use crate::memory_halfword::generation::generate_halfword_memory_trace;
-    use crate::memory_zeroinit::generation::generate_memory_zero_init_trace;
-    use crate::memoryinit::generation::generate_memory_init_trace;
+    use crate::memoryinit::generation::{
+        generate_elf_memory_init_trace, generate_memory_init_trace,
+    };
+    #[cfg(feature = "poseidon2")]
     use crate::poseidon2_output_bytes::generation::generate_poseidon2_output_bytes_trace;
+    #[cfg(feature = "poseidon2")]
     use crate::poseidon2_sponge::generation::generate_poseidon2_sponge_trace;
+    #[cfg(feature = "secp256k1")]
+    use crate::secp256k1::generation::generate_secp256k1_trace;
     use crate::storage_device::generation::{
         generate_call_tape_trace, generate_cast_list_commitment_tape_trace,
-        generate_event_tape_trace, generate_events_commitment_tape_trace,
+        generate_event_tape_trace, generate_events_commitment_tape_trace, generate_hint_tape_trace,
         generate_private_tape_trace, generate_public_tape_trace, generate_self_prog_id_tape_trace,
     };
     use crate::test_utils::prep_table;
+    #[cfg(feature = "u256")]
+    use crate::u256::generation::generate_u256_trace;
 
     // TODO(Matthias): Consider unifying with the byte memory example?
     #[must_use]
@@ -167,8 +179,9 @@ mod tests {
     fn generate_full_memory_trace() {
         let (program, record) = fullword_memory_trace_test_case(1);
 
-        let memory_init = generate_memory_init_trace(&program);
-        let memory_zeroinit_rows = generate_memory_zero_init_trace(&record.executed, &program);
+        let elf_memory_init_rows = generate_elf_memory_init_trace(&program);
+
+        let memory_init_rows = generate_memory_init_trace(&record.executed, &program);
 
         let halfword_memory = generate_halfword_memory_trace(&record.executed);
         let fullword_memory = generate_fullword_memory_trace(&record.executed);
@@ -180,12 +193,24 @@ mod tests {
         let cast_list_commitment_tape_rows =
             generate_cast_list_commitment_tape_trace(&record.executed);
         let self_prog_id_tape_rows = generate_self_prog_id_tape_trace(&record.executed);
+        let hint_tape_rows = generate_hint_tape_trace(&record.executed);
+        let event_emission_rows = generate_event_emission_trace(&record.executed);
+        #[cfg(feature = "secp256k1")]
+        let secp256k1_rows = generate_secp256k1_trace(&record.executed);
+        #[cfg(feature = "u256")]
+        let u256_rows = generate_u256_trace(&record.executed);
+        #[cfg(feature = "memcopy")]
+        let memcopy_rows = generate_memcopy_trace(&record.executed);
+        #[cfg(feature = "goldilocks")]
+        let goldilocks_rows = generate_goldilocks_trace(&record.executed);
+        #[cfg(feature = "poseidon2")]
         let poseidon2_rows = generate_poseidon2_sponge_trace(&record.executed);
+        #[cfg(feature = "poseidon2")]
         let poseidon2_output_bytes = generate_poseidon2_output_bytes_trace(&poseidon2_rows);
         let trace = generate_memory_trace::<GoldilocksField>(
             &record.executed,
-            &memory_init,
-            &memory_zeroinit_rows,
+            &elf_memory_init_rows,
+            &memory_init_rows,
             &halfword_memory,
             &fullword_memory,
             &private_tape_rows,
@@ -195,8 +220,22 @@ mod tests {
             &events_commitment_tape_rows,
             &cast_list_commitment_tape_rows,
             &self_prog_id_tape_rows,
+            &hint_tape_rows,
+            &event_emission_rows,
+            #[cfg(feature = "poseidon2")]
             &poseidon2_rows,
+            #[cfg(feature = "poseidon2")]
             &poseidon2_output_bytes,
+            #[cfg(feature = "poseidon2")]
+            &[],
+            #[cfg(feature = "secp256k1")]
+            &secp256k1_rows,
+            #[cfg(feature = "u256")]
+            &u256_rows,
+            #[cfg(feature = "memcopy")]
+            &memcopy_rows,
+            #[cfg(feature = "goldilocks")]
+            &goldilocks_rows,
         );
         let last = u64::from(u32::MAX);
         assert_eq!(
@@ -91,7 +91,7 @@ impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for FullWordMemor
 #[cfg(test)]
 mod tests {
     use mozak_runner::code;
-    use mozak_runner::instruction::{Args, Instruction, Op};
+    use mozak_runner::instruction::Instruction;
     use mozak_runner::test_utils::{u32_extra, u8_extra};
     use plonky2::plonk::config::Poseidon2GoldilocksConfig;
     use proptest::prelude::ProptestConfig;
@@ -104,25 +104,7 @@ mod tests {
 
     pub fn prove_mem_read_write<Stark: ProveAndVerify>(offset: u32, imm: u32, content: u8) {
         let (program, record) = code::execute(
-            [
-                Instruction {
-                    op: Op::SW,
-                    args: Args {
-                        rs1: 1,
-                        rs2: 2,
-                        imm,
-                        ..Args::default()
-                    },
-                },
-                Instruction {
-                    op: Op::LW,
-                    args: Args {
-                        rs2: 2,
-                        imm,
-                        ..Args::default()
-                    },
-                },
-            ],
+            [Instruction::sw(1, 2, imm), Instruction::lw(0, 2, imm)],
             &[
                 (imm.wrapping_add(offset), 0),
                 (imm.wrapping_add(offset).wrapping_add(1), 0),
@@ -2,7 +2,7 @@ use core::ops::Add;
 
 use itertools::izip;
 
-use crate::columns_view::{columns_view_impl, make_col_map, NumberOfColumns};
+use crate::columns_view::{make_col_map, ColumnNames, ColumnsView, NumberOfColumns};
 use crate::cross_table_lookup::ColumnWithTypedInput;
 use crate::linear_combination::Column;
 use crate::memory::columns::MemoryCtl;
@@ -11,7 +11,7 @@ use crate::stark::mozak_stark::{HalfWordMemoryTable, TableWithTypedOutput};
 
 /// Operations (one-hot encoded)
 #[repr(C)]
-#[derive(Clone, Copy, Eq, PartialEq, Debug, Default)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Default, ColumnNames)]
 pub struct Ops<T> {
     // One of `is_store`, `is_load_u`
     // If none are `1`, it is a padding row
@@ -22,12 +22,11 @@ pub struct Ops<T> {
 }
 
 make_col_map!(HalfWordMemory);
-columns_view_impl!(HalfWordMemory);
 // TODO(roman): address_limbs & value columns can be optimized
 // value == linear combination via range-check
 // address_limbs also linear combination + forbid  wrapping add
 #[repr(C)]
-#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
 pub struct HalfWordMemory<T> {
     /// Clock at memory access.
     pub clk: T,
@@ -106,25 +106,14 @@ mod tests {
         is_unsigned: bool,
     ) {
         let (program, record) = code::execute(
-            [
-                Instruction {
-                    op: Op::SH,
-                    args: Args {
-                        rs1: 1,
-                        rs2: 2,
-                        imm,
-                        ..Args::default()
-                    },
+            [Instruction::sh(1, 2, imm), Instruction {
+                op: if is_unsigned { Op::LHU } else { Op::LH },
+                args: Args {
+                    rs2: 2,
+                    imm,
+                    ..Args::default()
                 },
-                Instruction {
-                    op: if is_unsigned { Op::LHU } else { Op::LH },
-                    args: Args {
-                        rs2: 2,
-                        imm,
-                        ..Args::default()
-                    },
-                },
-            ],
+            }],
             &[
                 (imm.wrapping_add(offset), 0),
                 (imm.wrapping_add(offset).wrapping_add(1), 0),
@@ -1,40 +1,116 @@
+use core::ops::{Add, Range};
+
+use anyhow::{anyhow, Result};
+use mozak_runner::elf::Program;
 use plonky2::hash::hash_types::RichField;
 
-use crate::columns_view::{columns_view_impl, make_col_map};
-use crate::cross_table_lookup::ColumnWithTypedInput;
+use crate::columns_view::{make_col_map, ColumnNames, ColumnsView};
 use crate::linear_combination::Column;
-use crate::stark::mozak_stark::TableWithTypedOutput;
+use crate::linear_combination_typed::ColumnWithTypedInput;
+use crate::public_sub_table::{PublicSubTable, PublicValue};
+use crate::rangecheck::columns::RangeCheckCtl;
+use crate::stark::mozak_stark::{
+    ElfMemoryInitTable, LibraryElfMemoryInitTable, MemoryInitTable, TableKind, TableWithTypedOutput,
+};
+
+/// Where the initial value of a memory address comes from.
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum InitSource {
+    /// Static memory of the ELF.
+    Elf,
+    /// Memory that is accessed during execution, but not initialised by
+    /// the ELF. It starts out as zero.
+    Zero,
+}
+
+/// Provenance of a memory init row (one-hot encoded).
+/// If none are `1`, it is a padding row.
+#[repr(C)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Default, ColumnNames)]
+pub struct InitSourceColumns<T> {
+    pub is_elf: T,
+    pub is_zero: T,
+}
+
+/// The regions of a [`MemoryLayout`], with exclusive ends.
+#[repr(C)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Default, ColumnNames)]
+pub struct LayoutColumns<T> {
+    pub stack_start: T,
+    pub stack_end: T,
+    pub heap_start: T,
+    pub heap_end: T,
+}
+
+/// Which region of the [`MemoryLayout`] a zero init lies in (one-hot).
+/// If none are `1`, the row is not a zero init.
+#[repr(C)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Default, ColumnNames)]
+pub struct RegionColumns<T> {
+    pub in_stack: T,
+    pub in_heap: T,
+    /// The zero init of address `0` or `u32::MAX` outside of the layout.
+    ///
+    /// The Memory table starts at the one and ends at the other, so both are
+    /// always zero initialised, unless the ELF initialises them. The runner
+    /// traps accesses to them outside of the layout all the same.
+    pub is_bound: T,
+}
 
-columns_view_impl!(MemoryInit);
 make_col_map!(MemoryInit);
-/// A Row of Memory generated from both read-only and read-write memory
+/// A Row of Memory initialisation, regardless of its [`InitSource`].
 #[repr(C)]
-#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
 pub struct MemoryInit<T> {
     pub address: T,
     pub value: T,
-    pub filter: T,
     /// 1 if this row is a read-write, 0 if this row is read-only
     pub is_writable: T,
+    pub source: InitSourceColumns<T>,
+    /// 1 in the first row, and 0 in all others.
+    pub is_first: T,
+    /// The memory layout of the program, the same in every row, and public
+    /// via the first row.
+    ///
+    /// The ELF table of the program holds the layout that the program
+    /// declares, so it is part of the program id, and the execution dependent
+    /// `MemoryInit` table looks it up from there, see [`lookup_for_layout`].
+    /// It stays zero in the ELF table of the library.
+    pub layout: LayoutColumns<T>,
+    pub region: RegionColumns<T>,
+}
+
+impl<T: Copy + Add<Output = T>> MemoryInit<T> {
+    pub fn is_executed(&self) -> T { self.source.is_elf + self.source.is_zero }
 }
 
 impl<F: RichField> MemoryInit<F> {
-    /// Create a new `MemoryInit` row that is not writable. Useful
-    /// for memory traces that are initialized once and never written over.
     #[must_use]
-    pub fn new_readonly((addr, value): (u32, u8)) -> Self {
+    pub fn new(source: InitSource, is_writable: bool, (addr, value): (u32, u8)) -> Self {
         Self {
             address: F::from_canonical_u32(addr),
             value: F::from_canonical_u8(value),
-            filter: F::ONE,
-            is_writable: F::ZERO,
+            is_writable: F::from_bool(is_writable),
+            source: InitSourceColumns {
+                is_elf: F::from_bool(source == InitSource::Elf),
+                is_zero: F::from_bool(source == InitSource::Zero),
+            },
+            is_first: F::ZERO,
+            layout: LayoutColumns::default(),
+            region: RegionColumns::default(),
         }
     }
+
+    /// Create a new `MemoryInit` row that is not writable. Useful
+    /// for memory traces that are initialized once and never written over.
+    #[must_use]
+    pub fn new_readonly(source: InitSource, entry: (u32, u8)) -> Self {
+        Self::new(source, false, entry)
+    }
 }
 
-columns_view_impl!(MemoryInitCtl);
 #[repr(C)]
-#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
 pub struct MemoryInitCtl<T> {
     pub is_writable: T,
     pub address: T,
@@ -42,21 +118,194 @@ pub struct MemoryInitCtl<T> {
     pub value: T,
 }
 
+fn ctl_columns() -> MemoryInitCtl<ColumnWithTypedInput<MemoryInit<i64>>> {
+    MemoryInitCtl {
+        is_writable: COL_MAP.is_writable,
+        address: COL_MAP.address,
+        clk: 1 - COL_MAP.source.is_zero,
+        value: COL_MAP.value,
+    }
+}
+
 /// Columns containing the data which are looked up from the Memory Table
+///
+/// Zero inits happen at `clk` 0, all other inits at `clk` 1, so that they come
+/// after any zero-init rows.
+///
+/// Every table of `MemoryInit` rows only contributes the sources it is
+/// responsible for: the (public) ELF tables of the program and of its linked
+/// library only contribute ELF rows, and the execution dependent table
+/// contributes the zero inits.
+///
+/// Every ELF init makes it into the Memory table, whether the execution
+/// touches its address or not.
+#[must_use]
+pub fn lookup_for_memory(kind: TableKind) -> TableWithTypedOutput<MemoryInitCtl<Column>> {
+    match kind {
+        TableKind::ElfMemoryInit => ElfMemoryInitTable::new(ctl_columns(), COL_MAP.source.is_elf),
+        TableKind::LibraryElfMemoryInit =>
+            LibraryElfMemoryInitTable::new(ctl_columns(), COL_MAP.source.is_elf),
+        TableKind::MemoryInit => MemoryInitTable::new(ctl_columns(), COL_MAP.source.is_zero),
+        _ => panic!("{kind:?} does not hold memory init rows"),
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
+pub struct MemoryLayoutCtl<T> {
+    pub stack_start: T,
+    pub stack_end: T,
+    pub heap_start: T,
+    pub heap_end: T,
+}
+
+/// The layout of the first row, which the execution dependent `MemoryInit`
+/// table looks up in the ELF table of the program.
+#[must_use]
+pub fn lookup_for_layout(kind: TableKind) -> TableWithTypedOutput<MemoryLayoutCtl<Column>> {
+    let layout = COL_MAP.layout;
+    let columns = MemoryLayoutCtl {
+        stack_start: layout.stack_start,
+        stack_end: layout.stack_end,
+        heap_start: layout.heap_start,
+        heap_end: layout.heap_end,
+    };
+    match kind {
+        TableKind::ElfMemoryInit => ElfMemoryInitTable::new(columns, COL_MAP.is_first),
+        TableKind::MemoryInit => MemoryInitTable::new(columns, COL_MAP.is_first),
+        _ => panic!("{kind:?} does not hold the memory layout"),
+    }
+}
+
+/// Addresses of the zero inits in the execution dependent `MemoryInit` table
+/// are strictly increasing, and they lie in the region of the layout that
+/// they claim.
+///
+/// Padding rows repeat the last address, and there is always at least one of
+/// them.
 #[must_use]
-pub fn lookup_for_memory<T>(new: T) -> TableWithTypedOutput<MemoryInitCtl<Column>>
-where
-    T: Fn(
-        MemoryInitCtl<ColumnWithTypedInput<MemoryInit<i64>>>,
-        ColumnWithTypedInput<MemoryInit<i64>>,
-    ) -> TableWithTypedOutput<MemoryInitCtl<Column>>, {
-    new(
-        MemoryInitCtl {
-            is_writable: COL_MAP.is_writable,
-            address: COL_MAP.address,
-            clk: ColumnWithTypedInput::constant(1),
-            value: COL_MAP.value,
-        },
-        COL_MAP.filter,
-    )
+pub fn rangecheck_looking() -> Vec<TableWithTypedOutput<RangeCheckCtl<Column>>> {
+    let layout = COL_MAP.layout;
+    let region = COL_MAP.region;
+    vec![
+        MemoryInitTable::new(
+            RangeCheckCtl(COL_MAP.address.diff() - COL_MAP.is_executed().flip()),
+            COL_MAP.is_executed(),
+        ),
+        MemoryInitTable::new(
+            RangeCheckCtl(COL_MAP.address - layout.stack_start),
+            region.in_stack,
+        ),
+        MemoryInitTable::new(
+            RangeCheckCtl(layout.stack_end - 1 - COL_MAP.address),
+            region.in_stack,
+        ),
+        MemoryInitTable::new(
+            RangeCheckCtl(COL_MAP.address - layout.heap_start),
+            region.in_heap,
+        ),
+        MemoryInitTable::new(
+            RangeCheckCtl(layout.heap_end - 1 - COL_MAP.address),
+            region.in_heap,
+        ),
+    ]
+}
+
+/// The regions of memory that a proven execution may zero initialise, as a
+/// [`PublicValue`].
+///
+/// A load from an address that neither the ELF nor the execution wrote
+/// before reads zero, and the proof shows that each such address lies in the
+/// stack or the heap. The layout is [`MemoryLayout::of`] the proven program,
+/// as the program id covers it.
+///
+/// Ends are exclusive, and may be `1 << 32`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MemoryLayout {
+    pub stack: Range<u64>,
+    pub heap: Range<u64>,
+}
+
+impl MemoryLayout {
+    /// The layout of `program`, see [`Program::stack`].
+    ///
+    /// A program without a stack region declares no layout, so its stack
+    /// spans all of memory, and its heap is empty.
+    #[must_use]
+    pub fn of(program: &Program) -> Self {
+        let widen = |region: &Range<u32>| u64::from(region.start)..u64::from(region.end);
+        match &program.stack {
+            None => Self {
+                stack: 0..1 << 32,
+                heap: 0..0,
+            },
+            Some(stack) => Self {
+                stack: widen(stack),
+                heap: program.heap.as_ref().map_or(0..0, widen),
+            },
+        }
+    }
+
+    /// The columns of the layout, for the rows of the `MemoryInit` table.
+    #[must_use]
+    pub fn columns<F: RichField>(&self) -> LayoutColumns<F> {
+        LayoutColumns {
+            stack_start: F::from_canonical_u64(self.stack.start),
+            stack_end: F::from_canonical_u64(self.stack.end),
+            heap_start: F::from_canonical_u64(self.heap.start),
+            heap_end: F::from_canonical_u64(self.heap.end),
+        }
+    }
+
+    /// Whether `addr` lies in the stack or the heap.
+    #[must_use]
+    pub fn contains(&self, addr: u32) -> bool {
+        let addr = u64::from(addr);
+        self.stack.contains(&addr) || self.heap.contains(&addr)
+    }
+
+    /// The region that a zero init of `addr` lies in, preferring the heap.
+    /// All zero if it lies in neither, and is not a bound.
+    #[must_use]
+    pub fn region<F: RichField>(&self, addr: u32) -> RegionColumns<F> {
+        let is_bound = !self.contains(addr) && (addr == 0 || addr == u32::MAX);
+        let addr = u64::from(addr);
+        let in_heap = self.heap.contains(&addr);
+        RegionColumns {
+            in_stack: F::from_bool(!in_heap && self.stack.contains(&addr)),
+            in_heap: F::from_bool(in_heap),
+            is_bound: F::from_bool(is_bound),
+        }
+    }
+}
+
+impl<F: RichField> PublicValue<F> for MemoryLayout {
+    fn public_sub_table() -> PublicSubTable {
+        let layout = COL_MAP.layout;
+        PublicSubTable {
+            table: MemoryInitTable::new(
+                vec![
+                    layout.stack_start,
+                    layout.stack_end,
+                    layout.heap_start,
+                    layout.heap_end,
+                ],
+                COL_MAP.is_first,
+            ),
+            num_rows: 1,
+        }
+    }
+
+    fn from_rows(rows: &[Vec<F>]) -> Result<Self> {
+        let [row] = rows else {
+            return Err(anyhow!("expected a single first row, got {}", rows.len()));
+        };
+        let [stack_start, stack_end, heap_start, heap_end] = row[..] else {
+            return Err(anyhow!("expected four bounds, got {row:?}"));
+        };
+        Ok(Self {
+            stack: stack_start.to_canonical_u64()..stack_end.to_canonical_u64(),
+            heap: heap_start.to_canonical_u64()..heap_end.to_canonical_u64(),
+        })
+    }
 }
@@ -1,56 +1,218 @@
+use std::collections::BTreeSet;
+
 use itertools::{chain, Itertools};
 use mozak_runner::elf::Program;
+use mozak_runner::vm::Row;
 use plonky2::hash::hash_types::RichField;
 
-use crate::memoryinit::columns::MemoryInit;
+use crate::generation::MIN_TRACE_LENGTH;
+use crate::memoryinit::columns::{InitSource, MemoryInit, MemoryLayout};
 use crate::utils::pad_trace_with_default;
 
-/// Generates a memory init ROM trace (ELF + Mozak)
-#[must_use]
-pub fn generate_memory_init_trace<F: RichField>(program: &Program) -> Vec<MemoryInit<F>> {
-    let mut memory_inits: Vec<MemoryInit<F>> = chain! {
-        elf_memory_init(program),
+fn sorted_by_address<F: RichField>(
+    memory_inits: impl IntoIterator<Item = MemoryInit<F>>,
+) -> Vec<MemoryInit<F>> {
+    memory_inits
+        .into_iter()
+        .sorted_by_key(|init| init.address.to_canonical_u64())
+        .collect()
+}
+
+/// Marks the first row of `trace`.
+fn mark_first<F: RichField>(mut trace: Vec<MemoryInit<F>>) -> Vec<MemoryInit<F>> {
+    if let Some(first) = trace.first_mut() {
+        first.is_first = F::ONE;
     }
-    .collect();
+    trace
+}
 
-    memory_inits.sort_by_key(|init| init.address.to_canonical_u64());
+/// Generates a memory init trace from rows of any [`InitSource`], sorted by
+/// address.
+pub fn generate_init_trace<F: RichField>(
+    memory_inits: impl IntoIterator<Item = MemoryInit<F>>,
+) -> Vec<MemoryInit<F>> {
+    mark_first(pad_trace_with_default(sorted_by_address(memory_inits)))
+}
 
-    let trace = pad_trace_with_default(memory_inits);
-    log::trace!("MemoryInit trace {:?}", trace);
+/// Pad the execution dependent memory init trace to a power of 2.
+///
+/// Padding rows repeat the last address, so that the range check on the
+/// address difference holds for them, too.
+#[must_use]
+fn pad_memory_init_trace<F: RichField>(mut trace: Vec<MemoryInit<F>>) -> Vec<MemoryInit<F>> {
+    let address = trace.last().map_or(F::ZERO, |init| init.address);
+    trace.resize(
+        // We need to pad by at least one, so that the range check on the address difference
+        // does not wrap around to the first row.
+        (trace.len() + 1).next_power_of_two().max(MIN_TRACE_LENGTH),
+        MemoryInit {
+            address,
+            ..Default::default()
+        },
+    );
     trace
 }
 
-/// Generates a generic memory init trace sorted by address. Useful for memory
-/// represented as
-/// [`MozakMemoryRegion`](mozak_runner::elf::MozakMemoryRegion) traces.
-pub fn generate_init_trace<F: RichField, Fn>(program: &Program, f: Fn) -> Vec<MemoryInit<F>>
-where
-    Fn: FnOnce(&Program) -> Vec<MemoryInit<F>>, {
-    let mut memory_inits: Vec<MemoryInit<F>> = f(program);
-    memory_inits.sort_by_key(|init| init.address.to_canonical_u64());
-
-    pad_trace_with_default(memory_inits)
+fn elf_entries(program: &Program) -> impl Iterator<Item = (bool, (u32, u8))> + '_ {
+    [(false, &program.ro_memory), (true, &program.rw_memory)]
+        .into_iter()
+        .flat_map(|(is_writable, mem)| {
+            mem.iter()
+                .map(move |(&addr, &value)| (is_writable, (addr, value)))
+        })
 }
 
+/// Memory init rows for the static memory of the ELF.
 #[must_use]
 pub fn elf_memory_init<F: RichField>(program: &Program) -> Vec<MemoryInit<F>> {
-    [(F::ZERO, &program.ro_memory), (F::ONE, &program.rw_memory)]
+    elf_entries(program)
+        .map(|(is_writable, entry)| MemoryInit::new(InitSource::Elf, is_writable, entry))
+        .collect_vec()
+}
+
+#[must_use]
+pub(crate) fn init_in_program(program: &Program) -> BTreeSet<u32> {
+    chain!(program.ro_memory.keys(), program.rw_memory.keys())
+        .copied()
+        .collect()
+}
+
+#[must_use]
+pub(crate) fn used_in_execution<F: RichField>(step_rows: &[Row<F>]) -> BTreeSet<u32> {
+    step_rows
         .iter()
-        .flat_map(|&(is_writable, mem)| {
-            mem.iter().map(move |(&addr, &value)| MemoryInit {
-                filter: F::ONE,
-                is_writable,
-                address: F::from_canonical_u32(addr),
-                value: F::from_canonical_u8(value),
-            })
+        .flat_map(|row| row.aux.mem_addresses_used.clone())
+        // Our constraints require that we start at memory address 0 and end at u32::MAX,
+        // so we always consider these two used.  (This saves rangechecking the addresses
+        // themselves, we only rangecheck their difference.)
+        .chain([0, u32::MAX])
+        .collect()
+}
+
+/// Memory init rows for addresses that are accessed (through both stores and
+/// loads) during execution, but not initialised by the ELF.
+///
+/// Zero initialising these circumvents having to require a store before a
+/// load for a specific address. This includes heap pages that the `SBRK` ecall
+/// mapped during execution, which start out zero.
+///
+/// Only the stack and the heap of the [`MemoryLayout`] start out zero, and the
+/// runner traps accesses elsewhere. The exceptions are the bounds `0` and
+/// `u32::MAX`, see
+/// [`RegionColumns::is_bound`](crate::memoryinit::columns::RegionColumns::is_bound).
+#[must_use]
+pub fn zero_memory_init<F: RichField>(
+    step_rows: &[Row<F>],
+    program: &Program,
+) -> Vec<MemoryInit<F>> {
+    let layout = MemoryLayout::of(program);
+    used_in_execution(step_rows)
+        .difference(&init_in_program(program))
+        .map(|&addr| MemoryInit {
+            region: layout.region(addr),
+            ..MemoryInit::new(InitSource::Zero, true, (addr, 0))
         })
-        .collect_vec()
+        .collect()
 }
 
-/// Generates a elf memory init ROM trace
+/// Generates the ELF memory init ROM trace
+///
+/// The data of a linked library is left out, see
+/// [`generate_library_elf_memory_init_trace`]. Every row carries the
+/// [`MemoryLayout`] of the program.
 #[must_use]
 pub fn generate_elf_memory_init_trace<F: RichField>(program: &Program) -> Vec<MemoryInit<F>> {
-    let trace = generate_init_trace(program, elf_memory_init);
-    log::trace!("ElfMemoryInit trace {:?}", trace);
+    let layout = MemoryLayout::of(program).columns();
+    let trace = generate_init_trace(
+        elf_entries(program)
+            .filter(|(_, (addr, _))| !program.in_library(*addr))
+            .map(|(is_writable, entry)| MemoryInit::new(InitSource::Elf, is_writable, entry)),
+    )
+    .into_iter()
+    .map(|init| MemoryInit { layout, ..init })
+    .collect_vec();
+    tracing::trace!("ElfMemoryInit trace {:?}", trace);
+    trace
+}
+
+/// Generates the ELF memory init ROM trace of the library linked into
+/// `program`, see [`Program::link`]. Without a library, it only holds
+/// padding.
+#[must_use]
+pub fn generate_library_elf_memory_init_trace<F: RichField>(
+    program: &Program,
+) -> Vec<MemoryInit<F>> {
+    let trace = generate_init_trace(
+        program
+            .library
+            .as_deref()
+            .map(elf_memory_init)
+            .unwrap_or_default(),
+    );
+    tracing::trace!("LibraryElfMemoryInit trace {:?}", trace);
+    trace
+}
+
+/// Generates the trace of all memory inits that depend on the execution, ie
+/// the zero inits, sorted by address.
+///
+/// Every row carries the [`MemoryLayout`] of the program.
+#[must_use]
+pub fn generate_memory_init_trace<F: RichField>(
+    step_rows: &[Row<F>],
+    program: &Program,
+) -> Vec<MemoryInit<F>> {
+    let layout = MemoryLayout::of(program).columns();
+    let trace = mark_first(
+        pad_memory_init_trace(sorted_by_address(zero_memory_init(step_rows, program)))
+            .into_iter()
+            .map(|init| MemoryInit { layout, ..init })
+            .collect(),
+    );
+    tracing::trace!("MemoryInit trace length: {:?}", trace.len());
     trace
 }
+
+#[cfg(test)]
+mod tests {
+    use plonky2::plonk::config::{GenericConfig, Poseidon2GoldilocksConfig};
+
+    use super::*;
+    use crate::memory::test_utils::memory_trace_test_case;
+    use crate::test_utils::prep_table;
+
+    const D: usize = 2;
+    type C = Poseidon2GoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+
+    #[test]
+    fn generate_trace() {
+        let (program, record) = memory_trace_test_case(1);
+        let trace = generate_memory_init_trace::<F>(&record.executed, &program);
+        let last = u64::from(u32::MAX);
+        // Without a stack region, the stack spans all of memory.
+        let all = 1 << 32;
+
+        assert_eq!(
+            trace,
+            // In `memory_trace_test_case()`, there is 1 operation each on addresses
+            // '100' and '200' that only happen upon execution that is not in
+            // the ELF. This is tracked in this trace here, to prep for CTL.
+            // The ELF inits of 101..=103 and 201..=203 are left to the
+            // `ElfMemoryInit` table.
+            prep_table(vec![
+                // addr, value, is_writable, is_elf, is_zero, is_first,
+                // stack_start, stack_end, heap_start, heap_end, in_stack, in_heap, is_bound
+                [0, 0, 1, 0, 1, 1, 0, all, 0, 0, 1, 0, 0],
+                [100, 0, 1, 0, 1, 0, 0, all, 0, 0, 1, 0, 0],
+                [200, 0, 1, 0, 1, 0, 0, all, 0, 0, 1, 0, 0],
+                [last, 0, 1, 0, 1, 0, 0, all, 0, 0, 1, 0, 0],
+                // padding
+                [last, 0, 0, 0, 0, 0, 0, all, 0, 0, 0, 0, 0],
+                [last, 0, 0, 0, 0, 0, 0, all, 0, 0, 0, 0, 0],
+                [last, 0, 0, 0, 0, 0, 0, all, 0, 0, 0, 0, 0],
+                [last, 0, 0, 0, 0, 0, 0, all, 0, 0, 0, 0, 0],
+            ])
+        );
+    }
+}
@@ -1,6 +1,33 @@
 //! This module contains the **`MemoryInit` STARK Table**.
-//! It stores the initialized read-only memory values referenced by the Memory
-//! STARK.
+//!
+//! It stores the initial values of memory referenced by the Memory STARK,
+//! together with where they come from (see
+//! [`InitSource`](columns::InitSource)):
+//! - the static ELF, in the public `ElfMemoryInit` table, or the
+//!   `LibraryElfMemoryInit` table for a linked library, and
+//! - memory zero initialised upon first access during execution, in the
+//!   `MemoryInit` table.
+//!
+//! All of these tables share this STARK, and a single CTL into the Memory
+//! table.
+//!
+//! They can not be merged into one table though. The ELF tables only depend
+//! on the program: their trace caps make up the program id and the library
+//! id, and the verifier checks them against the ones it expects. The zero
+//! inits depend on the execution, ie on which addresses it touches first. A
+//! table holding both would commit to different traces for every execution
+//! of the same program, and could not be part of its id.
+//!
+//! Every ELF init enters the Memory table, even if the execution never
+//! touches its address. Leaving the untouched ones out would need a separate
+//! argument that exactly the touched ones made it in. The ELF tables are part
+//! of the program id, so they can not carry multiplicities that depend on
+//! the execution. Without such an argument, a prover could drop the init of
+//! a touched address and have it read zero instead.
+//!
+//! The `MemoryInit` table also makes the
+//! [`MemoryLayout`](columns::MemoryLayout) of the program public, and shows
+//! that every zero init lies in its stack or heap.
 pub mod columns;
 pub mod generation;
 pub mod stark;
@@ -33,9 +33,39 @@ fn generate_constraints<'a, T: Copy>(
     vars: &StarkFrameTyped<MemoryInit<Expr<'a, T>>, NoColumns<Expr<'a, T>>>,
 ) -> ConstraintBuilder<Expr<'a, T>> {
     let lv = vars.local_values;
+    let nv = vars.next_values;
     let mut constraints = ConstraintBuilder::default();
 
-    constraints.always(lv.filter.is_binary());
+    constraints.always(lv.source.is_elf.is_binary());
+    constraints.always(lv.source.is_zero.is_binary());
+    constraints.always(lv.is_executed().is_binary());
+    constraints.always(lv.is_writable.is_binary());
+
+    // Zero inits are, well, zero, and they are always writable.
+    constraints.always(lv.source.is_zero * lv.value);
+    constraints.always(lv.source.is_zero * (1 - lv.is_writable));
+
+    // Zero inits lie in the stack or the heap of the layout, or are one of
+    // the bounds of the Memory table. The range checks in `rangecheck_looking`
+    // hold them to the region they claim.
+    constraints.always(lv.region.in_stack.is_binary());
+    constraints.always(lv.region.in_heap.is_binary());
+    constraints.always(lv.region.is_bound.is_binary());
+    constraints
+        .always(lv.source.is_zero - lv.region.in_stack - lv.region.in_heap - lv.region.is_bound);
+    constraints.always(lv.region.is_bound * lv.address * (lv.address - i64::from(u32::MAX)));
+
+    // The layout is the same in every row, and public via the first one.
+    constraints.first_row(1 - lv.is_first);
+    constraints.transition(nv.is_first);
+    constraints.transition(nv.layout.stack_start - lv.layout.stack_start);
+    constraints.transition(nv.layout.stack_end - lv.layout.stack_end);
+    constraints.transition(nv.layout.heap_start - lv.layout.heap_start);
+    constraints.transition(nv.layout.heap_end - lv.layout.heap_end);
+
+    // Once we have padding, all subsequent rows are padding; ie not
+    // `is_executed`.
+    constraints.transition((lv.is_executed() - nv.is_executed()) * nv.is_executed());
 
     constraints
 }
@@ -77,16 +107,119 @@ impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for MemoryInitSta
 
 #[cfg(test)]
 mod tests {
+    use mozak_runner::code::Code;
+    use mozak_runner::decode::ECALL;
+    use mozak_runner::elf::Program;
+    use mozak_runner::instruction::Instruction;
+    use mozak_runner::state::{RawTapes, State};
+    use mozak_runner::vm::{step, ExecutionRecord};
+    use mozak_sdk::core::ecall;
+    use mozak_sdk::core::reg_abi::REG_A0;
+    use plonky2::field::types::Field;
     use plonky2::plonk::config::{GenericConfig, Poseidon2GoldilocksConfig};
+    use plonky2::util::timing::TimingTree;
     use starky::stark_testing::test_stark_circuit_constraints;
 
     use super::*;
+    use crate::cross_table_lookup::ctl_utils::check_single_ctl;
+    use crate::generation::{debug_single_trace, generate_traces};
+    use crate::memoryinit::columns::MemoryLayout;
+    use crate::memoryinit::generation::generate_memory_init_trace;
+    use crate::stark::mozak_stark::{
+        Lookups, MemoryLayoutTable, MozakStark, PublicInputs, TableKind,
+    };
+    use crate::stark::prover::prove;
+    use crate::stark::utils::trace_rows_to_poly_values;
+    use crate::stark::verifier::verify_proof;
+    use crate::test_utils::fast_test_config;
+    use crate::trace_source::Execution;
 
     const D: usize = 2;
     type C = Poseidon2GoldilocksConfig;
     type F = <C as GenericConfig<D>>::F;
     type S = MemoryInitStark<F, D>;
 
+    const STACK_TOP: u32 = 0x2_0000;
+
+    /// Pushes a word onto a stack from `0x1_0000` to [`STACK_TOP`].
+    fn push() -> (Program, ExecutionRecord<F>) {
+        let code = [
+            Instruction::li(5, STACK_TOP - 4),
+            Instruction::sw(6, 5, 0),
+            Instruction::li(REG_A0, ecall::HALT),
+            ECALL,
+        ];
+        let program = Program::create(&[], &[], Code((0..).step_by(4).zip(code.map(Ok)).collect()))
+            .with_stack(0x1_0000..STACK_TOP);
+        let state =
+            State::new(program.clone(), RawTapes::default()).set_register_value(6, 0xDEAD_BEEF);
+        let record = step(&program, state).unwrap();
+        (program, record)
+    }
+
+    #[test]
+    fn prove_with_layout() -> anyhow::Result<()> {
+        let (program, record) = push();
+        let stark = MozakStark::default();
+        let config = fast_test_config();
+        let all_proof = prove::<F, C, D>(
+            &program,
+            &record,
+            &stark,
+            &config,
+            PublicInputs::for_execution(&program, &record),
+            &mut TimingTree::default(),
+        )?;
+        let layout = all_proof.public_value::<MemoryLayout>(&stark)?;
+        assert_eq!(layout, MemoryLayout {
+            stack: 0x1_0000..u64::from(STACK_TOP),
+            heap: 0..0,
+        });
+        assert_eq!(layout, MemoryLayout::of(&program));
+        verify_proof(&stark, all_proof, &config)
+    }
+
+    /// The range checks keep a zero init inside the region it claims, so
+    /// only claiming to be a bound could take it outside of the layout.
+    #[test]
+    #[should_panic(expected = "debug_api_has_constraint_failed")]
+    fn zero_init_outside_of_the_layout_fails() {
+        let (program, record) = push();
+        let mut rows = generate_memory_init_trace::<F>(&record.executed, &program);
+        let pushed = rows
+            .iter_mut()
+            .find(|row| row.address == F::from_canonical_u32(STACK_TOP - 4))
+            .unwrap();
+        pushed.region.in_stack = F::ZERO;
+        pushed.region.is_bound = F::ONE;
+        debug_single_trace::<F, D, _>(&S::default(), &trace_rows_to_poly_values(rows), &[]);
+    }
+
+    /// The layout comes from the ELF table of the program, so a proof can't
+    /// claim another one, eg a stack over all of memory.
+    #[test]
+    fn forged_layout_fails() {
+        let (program, record) = push();
+        let mut traces = generate_traces::<F, D>(
+            &Execution::new(&program, &record),
+            &mut TimingTree::default(),
+        );
+        let forged = MemoryLayout {
+            stack: 0..1 << 32,
+            heap: 0..0,
+        }
+        .columns();
+        let rows = generate_memory_init_trace::<F>(&record.executed, &program)
+            .into_iter()
+            .map(|row| MemoryInit {
+                layout: forged,
+                ..row
+            })
+            .collect();
+        traces[TableKind::MemoryInit] = trace_rows_to_poly_values(rows);
+        assert!(check_single_ctl(&traces, &MemoryLayoutTable::lookups()).is_err());
+    }
+
     #[test]
     fn test_circuit() -> anyhow::Result<()> {
         let stark = S::default();
@@ -2,7 +2,10 @@ pub mod stark;
 
 pub mod columns {
 
-    use crate::columns_view::{columns_view_impl, make_col_map};
+    use mozak_runner::instruction::Op;
+
+    use crate::columns_view::{make_col_map, ColumnsView};
+    use crate::cpu::columns::op_code;
     use crate::cpu_skeleton::columns::CpuSkeletonCtl;
     use crate::linear_combination::Column;
     use crate::linear_combination_typed::ColumnWithTypedInput;
@@ -11,9 +14,8 @@ pub mod columns {
     use crate::register::RegisterCtl;
     use crate::stark::mozak_stark::{AddTable, TableWithTypedOutput};
 
-    columns_view_impl!(Instruction);
     #[repr(C)]
-    #[derive(Clone, Copy, Eq, PartialEq, Debug)]
+    #[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
     pub struct Instruction<T> {
         /// The original instruction (+ `imm_value`) used for program
         /// cross-table-lookup.
@@ -29,9 +31,8 @@ pub mod columns {
     }
 
     make_col_map!(Add);
-    columns_view_impl!(Add);
     #[repr(C)]
-    #[derive(Clone, Copy, Eq, PartialEq, Debug)]
+    #[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
     pub struct Add<T> {
         pub inst: Instruction<T>,
         // TODO(Matthias): could we get rid of the clk here?
@@ -96,6 +97,7 @@ pub mod columns {
                 pc: ADD.inst.pc,
                 new_pc: ADD.inst.pc + 4,
                 will_halt: ColumnWithTypedInput::constant(0),
+                exit_code: ADD.op2_value,
             },
             ADD.is_running,
         )
@@ -120,8 +122,7 @@ pub mod columns {
                 // 'reduce_with_powers'.
                 inst_data: ColumnWithTypedInput::reduce_with_powers(
                     [
-                        // TODO: don't hard-code ADD like this.
-                        ColumnWithTypedInput::constant(0),
+                        ColumnWithTypedInput::constant(op_code(Op::ADD)),
                         // TODO: use a struct here to name the components, and make IntoIterator,
                         // like we do with our stark tables.
                         ColumnWithTypedInput::constant(0),
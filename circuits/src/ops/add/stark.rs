@@ -15,6 +15,13 @@ use super::columns::Add;
 use crate::columns_view::{HasNamedColumns, NumberOfColumns};
 use crate::expr::{build_ext, build_packed, ConstraintBuilder};
 
+/// Proves ADD, and with it everything that decodes to ADD, like NOPs.
+///
+/// Runs of NOPs are deliberately not coalesced into a single row: every pc
+/// in the run would still need its own program ROM lookup to show that it
+/// holds a NOP, so a run costs as much as its rows. Executed NOPs are rare
+/// anyway, because the toolchains' alignment padding sits between functions
+/// and is never reached.
 #[derive(Copy, Clone, Default, StarkNameDisplay)]
 #[allow(clippy::module_name_repetitions)]
 pub struct AddStark<F, const D: usize> {
@@ -80,3 +87,24 @@ impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for AddStark<F, D
 
     fn constraint_degree(&self) -> usize { 3 }
 }
+
+#[cfg(test)]
+mod tests {
+    use plonky2::plonk::config::{GenericConfig, Poseidon2GoldilocksConfig};
+    use starky::stark_testing::test_stark_circuit_constraints;
+
+    use super::*;
+
+    const D: usize = 2;
+    type C = Poseidon2GoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+    type S = AddStark<F, D>;
+
+    #[test]
+    fn test_circuit() -> anyhow::Result<()> {
+        let stark = S::default();
+        test_stark_circuit_constraints::<F, C, S, D>(stark)?;
+
+        Ok(())
+    }
+}
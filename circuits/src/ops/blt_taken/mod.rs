@@ -2,8 +2,12 @@ pub mod stark;
 
 pub mod columns {
 
-    use crate::columns_view::{columns_view_impl, make_col_map};
+    use mozak_runner::instruction::Op;
+
+    use crate::columns_view::{make_col_map, ColumnsView};
+    use crate::cpu::columns::op_code;
     use crate::cpu_skeleton::columns::CpuSkeletonCtl;
+    use crate::event_counters::columns::{EventCounterCtl, EventKind};
     use crate::linear_combination::Column;
     use crate::linear_combination_typed::ColumnWithTypedInput;
     use crate::program::columns::ProgramRom;
@@ -11,9 +15,8 @@ pub mod columns {
     use crate::register::RegisterCtl;
     use crate::stark::mozak_stark::{BltTakenTable, TableWithTypedOutput};
 
-    columns_view_impl!(Instruction);
     #[repr(C)]
-    #[derive(Clone, Copy, Eq, PartialEq, Debug)]
+    #[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
     pub struct Instruction<T> {
         /// The original instruction (+ `imm_value`) used for program
         /// cross-table-lookup.
@@ -27,9 +30,8 @@ pub mod columns {
     }
 
     make_col_map!(BltTaken);
-    columns_view_impl!(BltTaken);
     #[repr(C)]
-    #[derive(Clone, Copy, Eq, PartialEq, Debug)]
+    #[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
     pub struct BltTaken<T> {
         pub inst: Instruction<T>,
         // TODO(Matthias): could we get rid of the clk here?
@@ -87,15 +89,25 @@ pub mod columns {
                 pc: COL_MAP.inst.pc,
                 new_pc: COL_MAP.inst.imm_value,
                 will_halt: ColumnWithTypedInput::constant(0),
+                exit_code: COL_MAP.op2_value,
+            },
+            COL_MAP.is_running,
+        )
+    }
+    /// Every row of this table is a taken branch.
+    #[must_use]
+    pub fn lookup_for_event_counters() -> TableWithTypedOutput<EventCounterCtl<Column>> {
+        BltTakenTable::new(
+            EventCounterCtl {
+                kind: ColumnWithTypedInput::constant(EventKind::BranchTaken as i64),
             },
             COL_MAP.is_running,
         )
     }
+
     #[must_use]
     pub fn lookup_for_program_rom() -> TableWithTypedOutput<ProgramRom<Column>> {
         let inst = COL_MAP.inst;
-        // TODO: don't hardcode this.
-        let blt = 22;
         BltTakenTable::new(
             ProgramRom {
                 pc: inst.pc,
@@ -112,8 +124,7 @@ pub mod columns {
                 // 'reduce_with_powers'.
                 inst_data: ColumnWithTypedInput::reduce_with_powers(
                     [
-                        // TODO: don't hard-code BLTU like this.
-                        ColumnWithTypedInput::constant(blt),
+                        ColumnWithTypedInput::constant(op_code(Op::BLTU)),
                         // TODO: use a struct here to name the components, and make IntoIterator,
                         // like we do with our stark tables.
                         ColumnWithTypedInput::constant(0),
@@ -0,0 +1,83 @@
+use anyhow::{bail, Result};
+use itertools::Itertools;
+use plonky2::hash::hash_types::{HashOut, RichField, NUM_HASH_OUT_ELTS};
+
+use crate::linear_combination::Column;
+use crate::poseidon2::columns::Poseidon2StateCtl;
+use crate::public_sub_table::{PublicSubTable, PublicValue};
+use crate::public_tape_hash::columns::{TapeByteCtl, PUBLIC_TAPE_HASH};
+use crate::public_tape_hash::generation::absorb_tape;
+use crate::stark::mozak_stark::{OutputTapeHashTable, TableWithTypedOutput};
+
+#[must_use]
+pub fn lookup_for_poseidon2() -> TableWithTypedOutput<Poseidon2StateCtl<Column>> {
+    OutputTapeHashTable::new(
+        Poseidon2StateCtl {
+            input: PUBLIC_TAPE_HASH.preimage,
+            output: PUBLIC_TAPE_HASH.output,
+        },
+        PUBLIC_TAPE_HASH.is_executed,
+    )
+}
+
+#[must_use]
+pub fn lookup_for_output_tape() -> Vec<TableWithTypedOutput<TapeByteCtl<Column>>> {
+    (0..)
+        .zip(PUBLIC_TAPE_HASH.multiplicities)
+        .map(|(i, multiplicity)| {
+            OutputTapeHashTable::new(
+                TapeByteCtl {
+                    index: PUBLIC_TAPE_HASH.index + i,
+                    value: PUBLIC_TAPE_HASH.preimage[usize::try_from(i).unwrap()],
+                },
+                multiplicity,
+            )
+        })
+        .collect()
+}
+
+/// Digest of the output tape of the proven execution, as a [`PublicValue`].
+///
+/// Verifiers compare it against [`OutputTapeDigest::from_tape`] of the output
+/// that the prover claims the guest wrote.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OutputTapeDigest<F: RichField>(pub HashOut<F>);
+
+impl<F: RichField> OutputTapeDigest<F> {
+    /// Computes the digest that a proof of an execution that wrote `tape`
+    /// makes public.
+    ///
+    /// The tape is zero padded to a multiple of
+    /// [`RATE`](crate::public_tape_hash::columns::RATE) bytes, so trailing zero
+    /// bytes are not part of the digest.
+    #[must_use]
+    pub fn from_tape(tape: &[u8]) -> Self {
+        let (_, output) = absorb_tape::<F>(tape)
+            .last()
+            .copied()
+            .expect("the sponge absorbs at least one chunk");
+        Self(HashOut::from_partial(&output[..NUM_HASH_OUT_ELTS]))
+    }
+}
+
+impl<F: RichField> PublicValue<F> for OutputTapeDigest<F> {
+    fn public_sub_table() -> PublicSubTable {
+        PublicSubTable {
+            table: OutputTapeHashTable::new(
+                PUBLIC_TAPE_HASH.output[..NUM_HASH_OUT_ELTS].to_vec(),
+                PUBLIC_TAPE_HASH.is_last,
+            ),
+            num_rows: 1,
+        }
+    }
+
+    fn from_rows(rows: &[Vec<F>]) -> Result<Self> {
+        let Some((row,)) = rows.iter().collect_tuple() else {
+            bail!("expected a single row for the output tape digest, got {rows:?}")
+        };
+        let Ok(elements) = <[F; NUM_HASH_OUT_ELTS]>::try_from(&row[..]) else {
+            bail!("expected {NUM_HASH_OUT_ELTS} elements for the output tape digest, got {row:?}")
+        };
+        Ok(Self(HashOut { elements }))
+    }
+}
@@ -0,0 +1,15 @@
+use plonky2::hash::hash_types::RichField;
+
+use crate::public_tape_hash::columns::PublicTapeHash;
+use crate::public_tape_hash::generation::generate_public_tape_hash_trace;
+use crate::storage_device::columns::StorageDevice;
+
+/// Generates the sponge over the output `tape`, where the memory rows of the
+/// `output_tape_rows` write each byte once.
+#[must_use]
+pub fn generate_output_tape_hash_trace<F: RichField>(
+    tape: &[u8],
+    output_tape_rows: &[StorageDevice<F>],
+) -> Vec<PublicTapeHash<F>> {
+    generate_public_tape_hash_trace(tape, output_tape_rows)
+}
@@ -0,0 +1,14 @@
+//! This module contains the **`OutputTape` and `OutputTapeHash` STARK
+//! Tables**.
+//!
+//! The guest appends to the output tape with the `IO_WRITE` ecall. The
+//! `OutputTape` table is a storage device whose memory rows load the written
+//! bytes from memory, and each write continues the tape where the previous one
+//! stopped. The `OutputTapeHash` table absorbs the tape into a Poseidon2
+//! sponge, like [`public_tape_hash`](crate::public_tape_hash) does for the
+//! public tape, and makes the digest public. Every absorbed byte has to be
+//! written exactly once, except for the zero padding of the last chunk, so the
+//! digest commits to exactly what the guest wrote.
+pub mod columns;
+pub mod generation;
+pub mod stark;
@@ -0,0 +1,222 @@
+use std::marker::PhantomData;
+
+use expr::{Expr, ExprBuilder, StarkFrameTyped};
+use itertools::izip;
+use mozak_circuits_derive::StarkNameDisplay;
+use plonky2::field::extension::{Extendable, FieldExtension};
+use plonky2::field::packed::PackedField;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::ext_target::ExtensionTarget;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use starky::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
+use starky::evaluation_frame::StarkFrame;
+use starky::stark::Stark;
+
+use crate::columns_view::{HasNamedColumns, NumberOfColumns};
+use crate::expr::{build_ext, build_packed, ConstraintBuilder};
+use crate::public_tape_hash::columns::{PublicTapeHash, RATE};
+use crate::storage_device::columns::StorageDevice;
+use crate::unstark::NoColumns;
+use crate::{public_tape_hash, storage_device};
+
+/// The constraints of a storage device, and on top that the guest only ever
+/// appends to the tape.
+fn generate_output_tape_constraints<'a, T: Copy>(
+    vars: &StarkFrameTyped<StorageDevice<Expr<'a, T>>, NoColumns<Expr<'a, T>>>,
+) -> ConstraintBuilder<Expr<'a, T>> {
+    let lv = vars.local_values;
+    let nv = vars.next_values;
+    let mut constraints = storage_device::stark::generate_constraints(vars);
+
+    constraints.always(lv.is_read_at);
+    // Rows in use come first, otherwise padding between two writes could move
+    // the second one anywhere on the tape.
+    constraints.transition(nv.is_executed() * (1 - lv.is_executed()));
+    // Each write starts where the previous one stopped, ie right after the
+    // last byte it wrote, and the first write at the start of the tape.
+    constraints.first_row(lv.tape_index);
+    constraints.transition(
+        nv.ops.is_storage_device * (nv.tape_index - (lv.tape_index + lv.ops.is_memory_store)),
+    );
+
+    constraints
+}
+
+/// The constraints of the sponge over the public tape, and on top that each
+/// absorbed byte is written exactly once, unless it is zero padding.
+fn generate_output_tape_hash_constraints<'a, T: Copy>(
+    vars: &StarkFrameTyped<PublicTapeHash<Expr<'a, T>>, NoColumns<Expr<'a, T>>>,
+) -> ConstraintBuilder<Expr<'a, T>> {
+    let lv = vars.local_values;
+    let mut constraints = public_tape_hash::stark::generate_constraints(vars);
+
+    for (byte, multiplicity) in izip!(&lv.preimage[..RATE], lv.multiplicities) {
+        constraints.always(multiplicity.is_binary());
+        constraints.always(*byte * (1 - multiplicity));
+    }
+
+    constraints
+}
+
+#[derive(Copy, Clone, Default, StarkNameDisplay)]
+#[allow(clippy::module_name_repetitions)]
+pub struct OutputTapeStark<F, const D: usize> {
+    pub _f: PhantomData<F>,
+}
+
+impl<F, const D: usize> HasNamedColumns for OutputTapeStark<F, D> {
+    type Columns = StorageDevice<F>;
+}
+
+const OUTPUT_TAPE_COLUMNS: usize = StorageDevice::<()>::NUMBER_OF_COLUMNS;
+const PUBLIC_INPUTS: usize = 0;
+
+impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for OutputTapeStark<F, D> {
+    type EvaluationFrame<FE, P, const D2: usize> = StarkFrame<P, P::Scalar, OUTPUT_TAPE_COLUMNS, PUBLIC_INPUTS>
+
+        where
+            FE: FieldExtension<D2, BaseField = F>,
+            P: PackedField<Scalar = FE>;
+    type EvaluationFrameTarget =
+        StarkFrame<ExtensionTarget<D>, ExtensionTarget<D>, OUTPUT_TAPE_COLUMNS, PUBLIC_INPUTS>;
+
+    fn eval_packed_generic<FE, P, const D2: usize>(
+        &self,
+        vars: &Self::EvaluationFrame<FE, P, D2>,
+        consumer: &mut ConstraintConsumer<P>,
+    ) where
+        FE: FieldExtension<D2, BaseField = F>,
+        P: PackedField<Scalar = FE>, {
+        let eb = ExprBuilder::default();
+        let constraints = generate_output_tape_constraints(&eb.to_typed_starkframe(vars));
+        build_packed(constraints, consumer);
+    }
+
+    fn eval_ext_circuit(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        vars: &Self::EvaluationFrameTarget,
+        consumer: &mut RecursiveConstraintConsumer<F, D>,
+    ) {
+        let eb = ExprBuilder::default();
+        let constraints = generate_output_tape_constraints(&eb.to_typed_starkframe(vars));
+        build_ext(constraints, builder, consumer);
+    }
+
+    fn constraint_degree(&self) -> usize { 3 }
+}
+
+#[derive(Copy, Clone, Default, StarkNameDisplay)]
+#[allow(clippy::module_name_repetitions)]
+pub struct OutputTapeHashStark<F, const D: usize> {
+    pub _f: PhantomData<F>,
+}
+
+impl<F, const D: usize> HasNamedColumns for OutputTapeHashStark<F, D> {
+    type Columns = PublicTapeHash<F>;
+}
+
+const OUTPUT_TAPE_HASH_COLUMNS: usize = PublicTapeHash::<()>::NUMBER_OF_COLUMNS;
+
+impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for OutputTapeHashStark<F, D> {
+    type EvaluationFrame<FE, P, const D2: usize> = StarkFrame<P, P::Scalar, OUTPUT_TAPE_HASH_COLUMNS, PUBLIC_INPUTS>
+
+        where
+            FE: FieldExtension<D2, BaseField = F>,
+            P: PackedField<Scalar = FE>;
+    type EvaluationFrameTarget =
+        StarkFrame<ExtensionTarget<D>, ExtensionTarget<D>, OUTPUT_TAPE_HASH_COLUMNS, PUBLIC_INPUTS>;
+
+    fn eval_packed_generic<FE, P, const D2: usize>(
+        &self,
+        vars: &Self::EvaluationFrame<FE, P, D2>,
+        consumer: &mut ConstraintConsumer<P>,
+    ) where
+        FE: FieldExtension<D2, BaseField = F>,
+        P: PackedField<Scalar = FE>, {
+        let eb = ExprBuilder::default();
+        let constraints = generate_output_tape_hash_constraints(&eb.to_typed_starkframe(vars));
+        build_packed(constraints, consumer);
+    }
+
+    fn constraint_degree(&self) -> usize { 3 }
+
+    fn eval_ext_circuit(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        vars: &Self::EvaluationFrameTarget,
+        consumer: &mut RecursiveConstraintConsumer<F, D>,
+    ) {
+        let eb = ExprBuilder::default();
+        let constraints = generate_output_tape_hash_constraints(&eb.to_typed_starkframe(vars));
+        build_ext(constraints, builder, consumer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mozak_runner::code;
+    use mozak_runner::decode::ECALL;
+    use mozak_runner::instruction::Instruction;
+    use mozak_sdk::core::ecall;
+    use mozak_sdk::core::reg_abi::{REG_A0, REG_A1, REG_A2};
+    use plonky2::util::timing::TimingTree;
+    use starky::stark_testing::test_stark_circuit_constraints;
+
+    use super::{OutputTapeHashStark, OutputTapeStark};
+    use crate::output_tape::columns::OutputTapeDigest;
+    use crate::stark::mozak_stark::{MozakStark, PublicInputs};
+    use crate::stark::prover::prove;
+    use crate::stark::verifier::verify_proof;
+    use crate::test_utils::{fast_test_config, C, D, F};
+
+    #[test]
+    fn prove_output_tape_digest() -> anyhow::Result<()> {
+        // Two writes, the second one longer than a chunk, and an empty one.
+        let output: Vec<u8> = (1..=12).collect();
+        let memory: Vec<(u32, u8)> = (0x100..).zip(output.iter().copied()).collect();
+        let (program, record) = code::execute(
+            [
+                ECALL,
+                Instruction::li(REG_A1, 0x103),
+                Instruction::li(REG_A2, 9),
+                ECALL,
+                Instruction::li(REG_A2, 0),
+                ECALL,
+            ],
+            &memory,
+            &[(REG_A0, ecall::IO_WRITE), (REG_A1, 0x100), (REG_A2, 3)],
+        );
+        let stark = MozakStark::<F, D>::default();
+        let config = fast_test_config();
+        let proof = prove::<F, C, D>(
+            &program,
+            &record,
+            &stark,
+            &config,
+            PublicInputs::for_execution(&program, &record),
+            &mut TimingTree::default(),
+        )?;
+        assert_eq!(
+            proof.public_value::<OutputTapeDigest<F>>(&stark)?,
+            OutputTapeDigest::from_tape(&output)
+        );
+        assert_ne!(
+            proof.public_value::<OutputTapeDigest<F>>(&stark)?,
+            OutputTapeDigest::from_tape(&output[3..])
+        );
+        verify_proof(&stark, proof, &config)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_circuit() -> anyhow::Result<()> {
+        test_stark_circuit_constraints::<F, C, OutputTapeStark<F, D>, D>(
+            OutputTapeStark::default(),
+        )?;
+        test_stark_circuit_constraints::<F, C, OutputTapeHashStark<F, D>, D>(
+            OutputTapeHashStark::default(),
+        )?;
+        Ok(())
+    }
+}
@@ -1,6 +1,6 @@
 use plonky2::hash::poseidon2::{ROUND_F_END, ROUND_P, WIDTH};
 
-use crate::columns_view::{columns_view_impl, make_col_map, NumberOfColumns};
+use crate::columns_view::{make_col_map, ColumnsView, NumberOfColumns};
 use crate::linear_combination::Column;
 use crate::stark::mozak_stark::{Poseidon2Table, TableWithTypedOutput};
 
@@ -14,10 +14,9 @@ pub(crate) const ROUNDS_P: usize = ROUND_P;
 
 pub(crate) const STATE_SIZE_AFTER: usize = STATE_SIZE * (ROUNDS_F / 2);
 
-columns_view_impl!(Poseidon2State);
 make_col_map!(Poseidon2State);
 #[repr(C)]
-#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
 pub struct Poseidon2State<F> {
     pub is_exe: F,
     pub input: [F; STATE_SIZE],
@@ -33,9 +32,8 @@ pub struct Poseidon2State<F> {
 
 pub const NUM_POSEIDON2_COLS: usize = Poseidon2State::<()>::NUMBER_OF_COLUMNS;
 
-columns_view_impl!(Poseidon2StateCtl);
 #[repr(C)]
-#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
 pub struct Poseidon2StateCtl<F> {
     pub input: [F; STATE_SIZE],
     pub output: [F; STATE_SIZE],
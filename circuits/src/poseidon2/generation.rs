@@ -1,10 +1,11 @@
-use itertools::Itertools;
+use itertools::chain;
 use mozak_runner::poseidon2;
 use mozak_runner::vm::Row;
 use plonky2::hash::hash_types::RichField;
 use plonky2::hash::poseidon2::{Poseidon2, WIDTH};
 
 use crate::poseidon2::columns::{Poseidon2State, ROUNDS_F, ROUNDS_P, STATE_SIZE};
+use crate::public_tape_hash::columns::PublicTapeHash;
 use crate::utils::pad_trace_with_row;
 
 struct FullRoundOutput<F> {
@@ -138,23 +139,37 @@ fn generate_poseidon2_states<F: RichField>(
         .collect()
 }
 
+/// Generates a permutation for every sponge row of the Poseidon2 ecalls in
+/// `step_rows`, and of the hashes over the public, the private and the output
+/// tape.
 #[must_use]
-pub fn generate_poseidon2_trace<F: RichField>(step_rows: &[Row<F>]) -> Vec<Poseidon2State<F>> {
+pub fn generate_poseidon2_trace<F: RichField>(
+    step_rows: &[Row<F>],
+    public_tape_hash_rows: &[PublicTapeHash<F>],
+    private_tape_hash_rows: &[PublicTapeHash<F>],
+    output_tape_hash_rows: &[PublicTapeHash<F>],
+) -> Vec<Poseidon2State<F>> {
     let trace = pad_trace_with_row(
-        step_rows
-            .iter()
-            .filter(|row| row.aux.poseidon2.is_some())
-            .map(|s| {
-                let poseidon_data = s.aux.poseidon2.clone().expect("can't fail");
-                generate_poseidon2_states(&poseidon_data)
-            })
-            .collect_vec()
-            .into_iter()
-            .flatten()
-            .collect::<Vec<Poseidon2State<F>>>(),
+        chain!(
+            step_rows
+                .iter()
+                .filter(|row| row.aux.poseidon2.is_some())
+                .flat_map(|s| {
+                    let poseidon_data = s.aux.poseidon2.clone().expect("can't fail");
+                    generate_poseidon2_states(&poseidon_data)
+                }),
+            chain!(
+                public_tape_hash_rows,
+                private_tape_hash_rows,
+                output_tape_hash_rows
+            )
+            .filter(|row| row.is_executed.is_one())
+            .map(|row| generate_poseidon2_state(&row.preimage, true)),
+        )
+        .collect::<Vec<Poseidon2State<F>>>(),
         generate_poseidon2_state(&[F::ZERO; STATE_SIZE], false),
     );
-    log::trace!("Poseison2 trace {:?}", trace);
+    tracing::trace!("Poseison2 trace {:?}", trace);
     trace
 }
 
@@ -193,7 +208,7 @@ mod test {
         }]);
 
         let step_rows = record.executed;
-        let trace = super::generate_poseidon2_trace(&step_rows);
+        let trace = super::generate_poseidon2_trace(&step_rows, &[], &[], &[]);
         for step_row in &step_rows {
             if let Some(poseidon2) = step_row.aux.poseidon2.as_ref() {
                 for (i, sponge_datum) in poseidon2.sponge_data.iter().enumerate() {
@@ -213,7 +228,8 @@ mod test {
     #[test]
     fn generate_poseidon2_trace_with_dummy() {
         let step_rows = vec![];
-        let trace: Vec<Poseidon2State<F>> = super::generate_poseidon2_trace(&step_rows);
+        let trace: Vec<Poseidon2State<F>> =
+            super::generate_poseidon2_trace(&step_rows, &[], &[], &[]);
         assert_eq!(trace.len(), MIN_TRACE_LENGTH);
     }
 }
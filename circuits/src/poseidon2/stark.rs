@@ -266,7 +266,7 @@ mod tests {
         let step_rows = record.executed;
 
         let stark = S::default();
-        let trace = generate_poseidon2_trace(&step_rows);
+        let trace = generate_poseidon2_trace(&step_rows, &[], &[], &[]);
         let trace_poly_values = trace_rows_to_poly_values(trace);
 
         let proof = prove::<F, C, S, D>(
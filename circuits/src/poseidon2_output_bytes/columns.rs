@@ -2,7 +2,7 @@ use itertools::izip;
 use plonky2::hash::hash_types::{HashOut, RichField};
 use plonky2::plonk::config::GenericHashOut;
 
-use crate::columns_view::{columns_view_impl, make_col_map, NumberOfColumns};
+use crate::columns_view::{make_col_map, ColumnsView, NumberOfColumns};
 use crate::cross_table_lookup::ColumnWithTypedInput;
 use crate::linear_combination::Column;
 use crate::memory::columns::MemoryCtl;
@@ -12,10 +12,9 @@ use crate::stark::mozak_stark::{Poseidon2OutputBytesTable, TableWithTypedOutput}
 pub const FIELDS_COUNT: usize = 4;
 pub const BYTES_COUNT: usize = 32;
 
-columns_view_impl!(Poseidon2OutputBytes);
 make_col_map!(Poseidon2OutputBytes);
 #[repr(C)]
-#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
 pub struct Poseidon2OutputBytes<F> {
     pub is_executed: F,
     pub clk: F,
@@ -51,9 +50,8 @@ impl<F: RichField> From<&Poseidon2Sponge<F>> for Vec<Poseidon2OutputBytes<F>> {
     }
 }
 
-columns_view_impl!(Poseidon2OutputBytesCtl);
 #[repr(C)]
-#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
 pub struct Poseidon2OutputBytesCtl<F> {
     pub clk: F,
     pub output_addr: F,
@@ -12,7 +12,7 @@ pub fn generate_poseidon2_output_bytes_trace<F: RichField>(
         .flat_map(Into::<Vec<Poseidon2OutputBytes<F>>>::into)
         .collect();
     let trace = pad_trace_with_default(trace);
-    log::trace!("trace {:?}", trace);
+    tracing::trace!("trace {:?}", trace);
     trace
 }
 
@@ -1,13 +1,13 @@
 use core::ops::Add;
 
 use itertools::izip;
-use mozak_sdk::core::reg_abi::{REG_A1, REG_A2, REG_A3};
+use mozak_sdk::core::reg_abi::{REG_A1, REG_A2, REG_A3, REG_A4};
 use plonky2::field::goldilocks_field::GoldilocksField;
 use plonky2::hash::hash_types::NUM_HASH_OUT_ELTS;
 use plonky2::hash::hashing::PlonkyPermutation;
 use plonky2::hash::poseidon2::{Poseidon2Permutation, WIDTH};
 
-use crate::columns_view::{columns_view_impl, make_col_map, NumberOfColumns};
+use crate::columns_view::{make_col_map, ColumnNames, ColumnsView, NumberOfColumns};
 use crate::cross_table_lookup::ColumnWithTypedInput;
 use crate::linear_combination::Column;
 use crate::memory::columns::MemoryCtl;
@@ -17,37 +17,44 @@ use crate::register::RegisterCtl;
 use crate::stark::mozak_stark::{Poseidon2SpongeTable, TableWithTypedOutput};
 
 #[repr(C)]
-#[derive(Clone, Copy, Eq, PartialEq, Debug, Default)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Default, ColumnNames)]
 pub struct Ops<T> {
     pub is_init_permute: T,
     pub is_permute: T,
+    /// Permutes the output of the previous row again, to squeeze another
+    /// digest for outputs longer than one digest.
+    pub is_squeeze: T,
 }
 
 #[repr(C)]
-#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
 pub struct Poseidon2Sponge<T> {
     pub clk: T,
     pub ops: Ops<T>,
     pub input_addr: T,
     pub output_addr: T,
     pub input_len: T,
+    /// Number of output bytes still to write, including the digest of this
+    /// row if it has `gen_output` set.
+    pub output_len: T,
     pub preimage: [T; WIDTH],
     pub output: [T; WIDTH],
+    /// The first `NUM_HASH_OUT_ELTS` of `output` are written to `output_addr`.
     pub gen_output: T,
 }
 
-columns_view_impl!(Poseidon2Sponge);
 make_col_map!(Poseidon2Sponge);
 
 pub const NUM_POSEIDON2_SPONGE_COLS: usize = Poseidon2Sponge::<()>::NUMBER_OF_COLUMNS;
 
 impl<T: Copy + Add<Output = T>> Poseidon2Sponge<T> {
-    pub fn is_executed(&self) -> T { self.ops.is_init_permute + self.ops.is_permute }
+    pub fn is_executed(&self) -> T {
+        self.ops.is_init_permute + self.ops.is_permute + self.ops.is_squeeze
+    }
 }
 
-columns_view_impl!(Poseidon2SpongeCtl);
 #[repr(C)]
-#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
 pub struct Poseidon2SpongeCtl<T> {
     pub clk: T,
 }
@@ -91,6 +98,15 @@ pub fn register_looking() -> Vec<TableWithTypedOutput<RegisterCtl<Column>>> {
             },
             COL_MAP.ops.is_init_permute,
         ),
+        Poseidon2SpongeTable::new(
+            RegisterCtl {
+                clk: COL_MAP.clk,
+                op: is_read,
+                value: COL_MAP.output_len,
+                addr: ColumnWithTypedInput::constant(REG_A4.into()),
+            },
+            COL_MAP.ops.is_init_permute,
+        ),
     ]
 }
 
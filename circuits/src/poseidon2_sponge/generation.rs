@@ -1,5 +1,6 @@
 use itertools::Itertools;
 use mozak_runner::vm::Row;
+use mozak_sdk::core::constants::DIGEST_BYTES;
 use plonky2::hash::hash_types::RichField;
 use plonky2::hash::hashing::PlonkyPermutation;
 use plonky2::hash::poseidon2::Poseidon2Permutation;
@@ -15,16 +16,20 @@ fn unroll_sponge_data<F: RichField>(row: &Row<F>) -> Vec<Poseidon2Sponge<F>> {
     let poseidon2 = row.aux.poseidon2.clone().expect("please pass filtered row");
     let mut unroll = vec![];
     let rate_size = u32::try_from(Poseidon2Permutation::<F>::RATE).expect("RATE > 2^32");
+    let digest_bytes = u32::try_from(DIGEST_BYTES).expect("DIGEST_BYTES > 2^32");
     assert_eq!(poseidon2.len % rate_size, 0);
     let unroll_count = u32::try_from(poseidon2.sponge_data.len()).expect("too many rows");
+    let absorb_count = poseidon2.len / rate_size;
 
-    let output_addr = poseidon2.output_addr;
+    let mut output_addr = poseidon2.output_addr;
+    let mut output_len = poseidon2.output_len;
     let mut input_addr = poseidon2.addr;
     let mut input_len = poseidon2.len;
     for i in 0..unroll_count {
         let ops: Ops<F> = Ops {
             is_init_permute: F::from_bool(i == 0),
-            is_permute: F::from_bool(i != 0),
+            is_permute: F::from_bool(i != 0 && i < absorb_count),
+            is_squeeze: F::from_bool(i >= absorb_count),
         };
         let sponge_datum = poseidon2
             .sponge_data
@@ -36,12 +41,19 @@ fn unroll_sponge_data<F: RichField>(row: &Row<F>) -> Vec<Poseidon2Sponge<F>> {
             input_addr: F::from_canonical_u32(input_addr),
             output_addr: F::from_canonical_u32(output_addr),
             input_len: F::from_canonical_u32(input_len),
+            output_len: F::from_canonical_u32(output_len),
             preimage: sponge_datum.preimage,
             output: sponge_datum.output,
             gen_output: sponge_datum.gen_output,
         });
-        input_addr += rate_size;
-        input_len -= rate_size;
+        if i + 1 < absorb_count {
+            input_addr += rate_size;
+            input_len -= rate_size;
+        }
+        if sponge_datum.gen_output.is_one() {
+            output_addr = output_addr.wrapping_add(digest_bytes);
+            output_len -= digest_bytes;
+        }
     }
 
     unroll
@@ -59,7 +71,7 @@ pub fn generate_poseidon2_sponge_trace<F: RichField>(
             .flatten()
             .collect::<Vec<Poseidon2Sponge<F>>>(),
     );
-    log::trace!("Poseidon2 Sponge trace {:#?}", trace);
+    tracing::trace!("Poseidon2 Sponge trace {:#?}", trace);
     trace
 }
 
@@ -2,6 +2,7 @@ use std::marker::PhantomData;
 
 use expr::{Expr, ExprBuilder, StarkFrameTyped};
 use mozak_circuits_derive::StarkNameDisplay;
+use mozak_sdk::core::constants::DIGEST_BYTES;
 use plonky2::field::extension::{Extendable, FieldExtension};
 use plonky2::field::packed::PackedField;
 use plonky2::hash::hash_types::RichField;
@@ -46,38 +47,61 @@ fn generate_constraints<'a, T: Copy>(
     let rate = u8::try_from(rate).expect("rate > 255");
     let state_size = u8::try_from(state_size).expect("state_size > 255");
     let rate_scalar = i64::from(rate);
+    let digest_bytes = i64::try_from(DIGEST_BYTES).unwrap();
     let lv = vars.local_values;
     let nv = vars.next_values;
     let mut constraints = ConstraintBuilder::default();
 
-    for val in [lv.ops.is_permute, lv.ops.is_init_permute, lv.gen_output] {
+    for val in [
+        lv.ops.is_permute,
+        lv.ops.is_init_permute,
+        lv.ops.is_squeeze,
+        lv.gen_output,
+    ] {
         constraints.always(val.is_binary());
     }
-    let is_exe = lv.ops.is_init_permute + lv.ops.is_permute;
+    let is_exe = lv.is_executed();
     constraints.always(is_exe.is_binary());
 
     let is_dummy = 1 - is_exe;
 
     // dummy row does not generate output
     constraints.always(is_dummy * lv.gen_output);
+    // and every squeeze row does.
+    constraints.always(lv.ops.is_squeeze * (1 - lv.gen_output));
 
-    // if row generates output then it must be last rate sized
+    // if an absorbing row generates output then it must be last rate sized
     // chunk of input.
-    constraints.always(lv.gen_output * (lv.input_len - rate_scalar));
+    constraints.always((lv.gen_output - lv.ops.is_squeeze) * (lv.input_len - rate_scalar));
 
-    let is_init_or_dummy = |vars: &Poseidon2Sponge<Expr<'a, T>>| {
-        (1 - vars.ops.is_init_permute) * (vars.ops.is_init_permute + vars.ops.is_permute)
-    };
+    let is_init_or_dummy =
+        |vars: &Poseidon2Sponge<Expr<'a, T>>| (1 - vars.ops.is_init_permute) * vars.is_executed();
 
     // First row must be init permute or dummy row.
     constraints.first_row(is_init_or_dummy(&lv));
-    // if row generates output then next row can be dummy or start of next hashing
-    constraints.always(lv.gen_output * is_init_or_dummy(&nv));
-
-    // Clk should not change within a sponge
-    constraints.transition(nv.ops.is_permute * (lv.clk - nv.clk));
-
-    let not_last_sponge = (1 - lv.gen_output) * (lv.ops.is_permute + lv.ops.is_init_permute);
+    // if row generates output then next row can be dummy, start of next hashing,
+    // or a squeeze for the next digest,
+    constraints.always(lv.gen_output * nv.ops.is_permute);
+    // and only such rows can be followed by a squeeze.
+    constraints.transition(nv.ops.is_squeeze * (1 - lv.gen_output));
+
+    // Clk and output_addr should not change within a sponge, except that each
+    // squeeze writes the next digest.
+    let continues = nv.ops.is_permute + nv.ops.is_squeeze;
+    constraints.transition(continues * (lv.clk - nv.clk));
+    constraints
+        .transition(continues * (nv.output_addr - lv.output_addr - lv.gen_output * digest_bytes));
+    // Every digest written counts against the output length, and the sponge
+    // ends with its last digest.
+    constraints
+        .transition(continues * (nv.output_len - lv.output_len + lv.gen_output * digest_bytes));
+    let is_last = lv.is_executed() * (1 - continues);
+    constraints.transition(is_last * (1 - lv.gen_output));
+    constraints.transition(is_last * (lv.output_len - digest_bytes));
+    constraints.last_row(lv.is_executed() * (1 - lv.gen_output));
+    constraints.last_row(lv.is_executed() * (lv.output_len - digest_bytes));
+
+    let not_last_sponge = (1 - lv.gen_output) * lv.is_executed();
     // if current row consumes input and its not last sponge then next row must have
     // length decreases by RATE, note that only actual execution row can consume
     // input
@@ -98,6 +122,12 @@ fn generate_constraints<'a, T: Copy>(
                 * (nv.preimage[i as usize] - lv.output[i as usize]),
         );
     }
+
+    // A squeeze permutes the whole previous output.
+    for i in 0..state_size {
+        constraints
+            .transition(nv.ops.is_squeeze * (nv.preimage[i as usize] - lv.output[i as usize]));
+    }
     constraints
 }
 
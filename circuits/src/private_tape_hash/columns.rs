@@ -0,0 +1,127 @@
+use anyhow::{bail, Result};
+use itertools::Itertools;
+use plonky2::hash::hash_types::{HashOut, RichField, NUM_HASH_OUT_ELTS};
+
+use crate::linear_combination::Column;
+use crate::poseidon2::columns::Poseidon2StateCtl;
+use crate::public_sub_table::{PublicSubTable, PublicValue};
+use crate::public_tape_hash::columns::{TapeByteCtl, PUBLIC_TAPE_HASH};
+use crate::public_tape_hash::generation::absorb_tape;
+use crate::stark::mozak_stark::{PrivateTapeHashTable, TableWithTypedOutput};
+
+#[must_use]
+pub fn lookup_for_poseidon2() -> TableWithTypedOutput<Poseidon2StateCtl<Column>> {
+    PrivateTapeHashTable::new(
+        Poseidon2StateCtl {
+            input: PUBLIC_TAPE_HASH.preimage,
+            output: PUBLIC_TAPE_HASH.output,
+        },
+        PUBLIC_TAPE_HASH.is_executed,
+    )
+}
+
+#[must_use]
+pub fn lookup_for_private_tape() -> Vec<TableWithTypedOutput<TapeByteCtl<Column>>> {
+    (0..)
+        .zip(PUBLIC_TAPE_HASH.multiplicities)
+        .map(|(i, multiplicity)| {
+            PrivateTapeHashTable::new(
+                TapeByteCtl {
+                    index: PUBLIC_TAPE_HASH.index + i,
+                    value: PUBLIC_TAPE_HASH.preimage[usize::try_from(i).unwrap()],
+                },
+                multiplicity,
+            )
+        })
+        .collect()
+}
+
+/// Digest of the private tape of the proven execution, as a [`PublicValue`].
+///
+/// Together with the program id of the proof, it shows which private tape the
+/// program consumed. Verifiers compare it against
+/// [`PrivateTapeDigest::from_tape`] of the tape they expect.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PrivateTapeDigest<F: RichField>(pub HashOut<F>);
+
+impl<F: RichField> PrivateTapeDigest<F> {
+    /// Computes the digest that a proof of an execution with the private
+    /// `tape` makes public.
+    ///
+    /// The tape is zero padded to a multiple of
+    /// [`RATE`](crate::public_tape_hash::columns::RATE) bytes, so its length
+    /// is not part of the digest.
+    #[must_use]
+    pub fn from_tape(tape: &[u8]) -> Self {
+        let (_, output) = absorb_tape::<F>(tape)
+            .last()
+            .copied()
+            .expect("the sponge absorbs at least one chunk");
+        Self(HashOut::from_partial(&output[..NUM_HASH_OUT_ELTS]))
+    }
+}
+
+impl<F: RichField> PublicValue<F> for PrivateTapeDigest<F> {
+    fn public_sub_table() -> PublicSubTable {
+        PublicSubTable {
+            table: PrivateTapeHashTable::new(
+                PUBLIC_TAPE_HASH.output[..NUM_HASH_OUT_ELTS].to_vec(),
+                PUBLIC_TAPE_HASH.is_last,
+            ),
+            num_rows: 1,
+        }
+    }
+
+    fn from_rows(rows: &[Vec<F>]) -> Result<Self> {
+        let Some((row,)) = rows.iter().collect_tuple() else {
+            bail!("expected a single row for the private tape digest, got {rows:?}")
+        };
+        let Ok(elements) = <[F; NUM_HASH_OUT_ELTS]>::try_from(&row[..]) else {
+            bail!("expected {NUM_HASH_OUT_ELTS} elements for the private tape digest, got {row:?}")
+        };
+        Ok(Self(HashOut { elements }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mozak_sdk::core::ecall;
+    use plonky2::util::timing::TimingTree;
+
+    use super::PrivateTapeDigest;
+    use crate::stark::mozak_stark::{MozakStark, PublicInputs};
+    use crate::stark::prover::prove;
+    use crate::stark::verifier::verify_proof;
+    use crate::test_utils::{fast_test_config, ScenarioBuilder, C, D, F};
+
+    #[test]
+    fn prove_private_tape_digest() -> anyhow::Result<()> {
+        // Longer than one chunk, and only partly read.
+        let private_tape: Vec<u8> = (1..=11).collect();
+        let (program, record) = ScenarioBuilder::default()
+            .private_tape(&private_tape)
+            .read_tape(ecall::PRIVATE_TAPE, 0x100, 10)
+            .expect_memory(0x100, &private_tape[..10])
+            .build();
+        let stark = MozakStark::<F, D>::default();
+        let config = fast_test_config();
+        let proof = prove::<F, C, D>(
+            &program,
+            &record,
+            &stark,
+            &config,
+            PublicInputs::for_execution(&program, &record),
+            &mut TimingTree::default(),
+        )?;
+        assert_eq!(
+            proof.public_value::<PrivateTapeDigest<F>>(&stark)?,
+            PrivateTapeDigest::from_tape(&private_tape)
+        );
+        assert_ne!(
+            proof.public_value::<PrivateTapeDigest<F>>(&stark)?,
+            PrivateTapeDigest::from_tape(&private_tape[..10])
+        );
+        verify_proof(&stark, proof, &config)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,15 @@
+use plonky2::hash::hash_types::RichField;
+
+use crate::public_tape_hash::columns::PublicTapeHash;
+use crate::public_tape_hash::generation::generate_public_tape_hash_trace;
+use crate::storage_device::columns::StorageDevice;
+
+/// Generates the sponge over the private `tape`, with the multiplicities of
+/// the bytes that the memory rows of the `private_tape_rows` read.
+#[must_use]
+pub fn generate_private_tape_hash_trace<F: RichField>(
+    tape: &[u8],
+    private_tape_rows: &[StorageDevice<F>],
+) -> Vec<PublicTapeHash<F>> {
+    generate_public_tape_hash_trace(tape, private_tape_rows)
+}
@@ -0,0 +1,16 @@
+//! This module contains the **`PrivateTapeHash` STARK Table**.
+//!
+//! It is a sponge over the private tape, like
+//! [`public_tape_hash`](crate::public_tape_hash) is over the public tape, and
+//! proves with the same constraints. Every byte the guest reads from the
+//! private tape is looked up here at its tape index, and the final digest is
+//! public.
+//!
+//! Each program of a multi-program execution is proven on its own, with only
+//! its own private tape, see
+//! [`RawTapes::with_private_tape_from`](mozak_runner::state::RawTapes::with_private_tape_from).
+//! The program id and the private tape digest of a proof thus tell which
+//! program consumed which private tape. The digest binds the tape, but does
+//! not hide it: a verifier who can guess the tape can check the guess.
+pub mod columns;
+pub mod generation;
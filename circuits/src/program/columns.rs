@@ -1,17 +1,16 @@
 use itertools::izip;
 use plonky2::hash::hash_types::RichField;
 
-use crate::columns_view::{columns_view_impl, make_col_map, NumberOfColumns};
+use crate::columns_view::{make_col_map, ColumnsView, NumberOfColumns};
 use crate::cpu::columns::Instruction;
 use crate::generation::ascending_sum;
 use crate::linear_combination::Column;
 use crate::linear_combination_typed::ColumnWithTypedInput;
-use crate::stark::mozak_stark::{ProgramTable, TableWithTypedOutput};
+use crate::stark::mozak_stark::{LibraryProgramTable, ProgramTable, TableWithTypedOutput};
 
-columns_view_impl!(ProgramRom);
 make_col_map!(ProgramRom);
 #[repr(C)]
-#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
 /// A Row of ROM generated from read-only memory
 pub struct ProgramRom<T> {
     // Design doc for CPU <> Program cross-table-lookup:
@@ -40,7 +39,7 @@ impl<F: RichField> From<Instruction<F>> for ProgramRom<F> {
             pc: inst.pc,
             inst_data: reduce_with_powers(
                 [
-                    ascending_sum(inst.ops),
+                    ascending_sum(inst.ops.code_terms()),
                     inst.is_op1_signed,
                     inst.is_op2_signed,
                     inst.rs1_selected,
@@ -61,3 +60,8 @@ pub const NUM_PROGRAM_COLS: usize = ProgramRom::<()>::NUMBER_OF_COLUMNS;
 pub fn lookup_for_ctl() -> TableWithTypedOutput<ProgramRom<Column>> {
     ProgramTable::new(COL_MAP, ColumnWithTypedInput::constant(1))
 }
+
+#[must_use]
+pub fn library_lookup_for_ctl() -> TableWithTypedOutput<ProgramRom<Column>> {
+    LibraryProgramTable::new(COL_MAP, ColumnWithTypedInput::constant(1))
+}
@@ -1,3 +1,4 @@
+use itertools::Itertools;
 use mozak_runner::elf::Program;
 use plonky2::hash::hash_types::RichField;
 
@@ -5,20 +6,83 @@ use crate::cpu::columns::Instruction;
 use crate::program::columns::ProgramRom;
 use crate::utils::pad_trace_with_last;
 
+fn decoded_rom_row<F: RichField>(
+    pc: u32,
+    inst: mozak_runner::instruction::Instruction,
+) -> ProgramRom<F> {
+    ProgramRom::from(Instruction::from((pc, inst)).map(F::from_canonical_u32))
+}
+
+#[cfg(not(feature = "decode"))]
+fn rom_row<F: RichField>(
+    _program: &Program,
+    pc: u32,
+    inst: mozak_runner::instruction::Instruction,
+) -> ProgramRom<F> {
+    decoded_rom_row(pc, inst)
+}
+
+/// With the decode table, the ROM holds the raw word at `pc`, as found in
+/// memory.
+#[cfg(feature = "decode")]
+fn rom_row<F: RichField>(
+    program: &Program,
+    pc: u32,
+    _inst: mozak_runner::instruction::Instruction,
+) -> ProgramRom<F> {
+    let byte = |addr: u32| {
+        program
+            .ro_memory
+            .get(&addr)
+            .or_else(|| program.rw_memory.get(&addr))
+            .copied()
+            .unwrap_or_else(|| panic!("code at {pc:#x} is missing from memory"))
+    };
+    ProgramRom {
+        pc: F::from_canonical_u32(pc),
+        inst_data: F::from_canonical_u32(u32::from_le_bytes([0, 1, 2, 3].map(|i| byte(pc + i)))),
+    }
+}
+
 /// Generates a program ROM trace from a given program.
+///
+/// The code of a linked library is left out, see
+/// [`generate_library_rom_trace`].
 #[must_use]
 pub fn generate_program_rom_trace<F: RichField>(program: &Program) -> Vec<ProgramRom<F>> {
     let mut roms = program
         .ro_code
         .iter()
-        .filter_map(|(&pc, &inst)| {
-            Some(ProgramRom::from(
-                Instruction::from((pc, inst.ok()?)).map(F::from_canonical_u32),
-            ))
-        })
+        .filter(|(&pc, _)| !program.in_library(pc))
+        .filter_map(|(&pc, &inst)| Some(rom_row(program, pc, inst.ok()?)))
         .collect::<Vec<_>>();
 
     roms.sort_by_key(|entry| entry.pc.to_canonical_u64());
 
     pad_trace_with_last(roms)
 }
+
+/// Generates the ROM trace of the library linked into `program`, see
+/// [`Program::link`].
+///
+/// The library ROM holds decoded instructions even with the decode table, so
+/// that the library needs no decode table of its own. Without a library, the
+/// trace repeats the last instruction of the program, which the CPU may
+/// execute either way.
+#[must_use]
+pub fn generate_library_rom_trace<F: RichField>(program: &Program) -> Vec<ProgramRom<F>> {
+    let decoded_rom = |program: &Program| {
+        program
+            .ro_code
+            .iter()
+            .filter_map(|(&pc, &inst)| Some(decoded_rom_row(pc, inst.ok()?)))
+            .sorted_by_key(|entry: &ProgramRom<F>| entry.pc.to_canonical_u64())
+            .collect_vec()
+    };
+    let roms = match &program.library {
+        Some(library) => decoded_rom(library),
+        None => decoded_rom(program).pop().into_iter().collect(),
+    };
+
+    pad_trace_with_last(roms)
+}
@@ -1,14 +1,13 @@
-use crate::columns_view::{columns_view_impl, make_col_map};
+use crate::columns_view::{make_col_map, ColumnsView};
 use crate::linear_combination::Column;
 use crate::linear_combination_typed::ColumnWithTypedInput;
 use crate::program::columns::ProgramRom;
-use crate::stark::mozak_stark::{ProgramMultTable, TableWithTypedOutput};
+use crate::stark::mozak_stark::{LibraryProgramMultTable, ProgramMultTable, TableWithTypedOutput};
 
-columns_view_impl!(ProgramMult);
 make_col_map!(ProgramMult);
 /// A Row of ROM generated from read-only memory
 #[repr(C)]
-#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
 pub struct ProgramMult<T> {
     pub rom_row: ProgramRom<T>,
     pub mult_in_cpu: T,
@@ -23,3 +22,15 @@ pub fn lookup_for_cpu() -> TableWithTypedOutput<ProgramRom<Column>> {
 pub fn lookup_for_rom() -> TableWithTypedOutput<ProgramRom<Column>> {
     ProgramMultTable::new(COL_MAP.rom_row, ColumnWithTypedInput::constant(1))
 }
+
+/// Like [`lookup_for_cpu`], for the instructions of the linked library.
+#[must_use]
+pub fn library_lookup_for_cpu() -> TableWithTypedOutput<ProgramRom<Column>> {
+    LibraryProgramMultTable::new(COL_MAP.rom_row, COL_MAP.mult_in_cpu)
+}
+
+/// Like [`lookup_for_rom`], into the ROM of the linked library.
+#[must_use]
+pub fn library_lookup_for_rom() -> TableWithTypedOutput<ProgramRom<Column>> {
+    LibraryProgramMultTable::new(COL_MAP.rom_row, ColumnWithTypedInput::constant(1))
+}
@@ -11,6 +11,7 @@
 //! CTL to `combine`, since the procedure is preceded by commitment to trace
 //! polynomials already
 #![allow(clippy::module_name_repetitions)]
+use anyhow::{anyhow, ensure, Result};
 use itertools::{iproduct, Itertools};
 use plonky2::field::extension::Extendable;
 use plonky2::field::polynomial::PolynomialValues;
@@ -27,7 +28,7 @@ use crate::stark::permutation::challenge::{GrandProductChallenge, GrandProductCh
 /// Specifies a Subtable with `table.columns` and `table.filter_column`
 /// which the prover wants to make public. We include `num_rows` since
 /// it cannot be computed from `table` alone.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct PublicSubTable {
     pub table: Table,
     pub num_rows: usize,
@@ -35,6 +36,83 @@ pub struct PublicSubTable {
 /// Actual values, as field elements, of the entries
 /// of `PublicSubTable`
 pub type PublicSubTableValues<F> = Vec<Vec<F>>;
+
+/// A typed value that proofs make public through a `PublicSubTable`.
+///
+/// Applications expose their own values by implementing this trait for a
+/// sub table of the relevant stark table, and registering it with
+/// [`MozakStark::with_public_value`](crate::stark::mozak_stark::MozakStark::with_public_value).
+/// Verifying a proof checks the rows of every registered sub table, so the
+/// value read back with `AllProof::public_value` can be trusted once the proof
+/// is verified.
+pub trait PublicValue<F>: Sized {
+    /// The sub table the value is read from.
+    fn public_sub_table() -> PublicSubTable;
+
+    /// Decodes the value from the rows of its sub table.
+    ///
+    /// Rows come in no particular order, so values spread over several rows
+    /// need to carry their position in a column.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the rows do not encode a value.
+    fn from_rows(rows: &[Vec<F>]) -> Result<Self>;
+}
+
+/// Reads the public value `V` out of the values of `public_sub_tables`.
+///
+/// # Errors
+///
+/// Errors if `V` is not one of `public_sub_tables`, or its rows do not encode
+/// a value.
+pub fn public_value<F, V: PublicValue<F>>(
+    public_sub_tables: &[PublicSubTable],
+    public_sub_table_values: &TableKindArray<Vec<PublicSubTableValues<F>>>,
+) -> Result<V> {
+    let public_sub_table = V::public_sub_table();
+    let kind = public_sub_table.table.kind;
+    let index = public_sub_tables
+        .iter()
+        .filter(|twc| twc.table.kind == kind)
+        .position(|twc| *twc == public_sub_table)
+        .ok_or_else(|| anyhow!("public value of {kind:?} table is not part of the stark"))?;
+    let rows = public_sub_table_values[kind]
+        .get(index)
+        .ok_or_else(|| anyhow!("public value of {kind:?} table is missing from the proof"))?;
+    V::from_rows(rows)
+}
+
+/// Checks that the proof carries exactly the `public_sub_table_values`
+/// that `public_sub_tables` prescribe: the given number of rows, each as wide
+/// as the sub table.
+///
+/// The lookup argument only checks the values that are expected, so
+/// without this extra values could be slipped into a proof unnoticed.
+pub(crate) fn check_public_sub_table_values_shape<F>(
+    public_sub_tables: &[PublicSubTable],
+    public_sub_table_values: &TableKindArray<Vec<PublicSubTableValues<F>>>,
+) -> Result<()> {
+    let mut values = public_sub_table_values.each_ref().map(|v| v.iter());
+    for public_sub_table in public_sub_tables {
+        let kind = public_sub_table.table.kind;
+        let rows = values[kind]
+            .next()
+            .ok_or_else(|| anyhow!("public sub table of {kind:?} table is missing"))?;
+        ensure!(
+            rows.len() == public_sub_table.num_rows
+                && rows
+                    .iter()
+                    .all(|row| row.len() == public_sub_table.table.columns.len()),
+            "public sub table of {kind:?} table has the wrong shape"
+        );
+    }
+    ensure!(
+        values.iter_mut().all(|v| v.next().is_none()),
+        "proof carries unexpected public sub tables"
+    );
+    Ok(())
+}
 /// Plonky2 target version of `PublicSubTableValuesTarget`
 pub type PublicSubTableValuesTarget = Vec<Vec<Target>>;
 impl PublicSubTable {
@@ -0,0 +1,115 @@
+use anyhow::{bail, Result};
+use itertools::Itertools;
+use plonky2::field::goldilocks_field::GoldilocksField;
+use plonky2::hash::hash_types::{HashOut, RichField, NUM_HASH_OUT_ELTS};
+use plonky2::hash::hashing::PlonkyPermutation;
+use plonky2::hash::poseidon2::{Poseidon2Permutation, WIDTH};
+
+use super::generation::absorb_tape;
+use crate::columns_view::{make_col_map, ColumnsView};
+use crate::linear_combination::Column;
+use crate::poseidon2::columns::Poseidon2StateCtl;
+use crate::public_sub_table::{PublicSubTable, PublicValue};
+use crate::stark::mozak_stark::{PublicTapeHashTable, TableWithTypedOutput};
+
+/// Number of tape bytes absorbed per row.
+pub const RATE: usize = Poseidon2Permutation::<GoldilocksField>::RATE;
+
+make_col_map!(PUBLIC_TAPE_HASH, PublicTapeHash);
+/// One row per permutation of the sponge over the public tape. The rate part
+/// of the `preimage` holds the bytes of the tape from `index` on, zero padded
+/// at the end of the tape, and the capacity part is carried over from the
+/// `output` of the previous row.
+#[repr(C)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
+pub struct PublicTapeHash<T> {
+    /// Tape index of the first byte absorbed on this row.
+    pub index: T,
+    pub preimage: [T; WIDTH],
+    pub output: [T; WIDTH],
+    /// Number of times the guest read each of the bytes absorbed on this row.
+    pub multiplicities: [T; RATE],
+    /// 1 for the rows of the sponge, 0 for padding.
+    pub is_executed: T,
+    /// 1 for the last row of the sponge, which holds the digest.
+    pub is_last: T,
+}
+
+/// A byte of a tape together with its position on the tape.
+#[repr(C)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
+pub struct TapeByteCtl<T> {
+    pub index: T,
+    pub value: T,
+}
+
+#[must_use]
+pub fn lookup_for_poseidon2() -> TableWithTypedOutput<Poseidon2StateCtl<Column>> {
+    PublicTapeHashTable::new(
+        Poseidon2StateCtl {
+            input: PUBLIC_TAPE_HASH.preimage,
+            output: PUBLIC_TAPE_HASH.output,
+        },
+        PUBLIC_TAPE_HASH.is_executed,
+    )
+}
+
+#[must_use]
+pub fn lookup_for_public_tape() -> Vec<TableWithTypedOutput<TapeByteCtl<Column>>> {
+    (0..)
+        .zip(PUBLIC_TAPE_HASH.multiplicities)
+        .map(|(i, multiplicity)| {
+            PublicTapeHashTable::new(
+                TapeByteCtl {
+                    index: PUBLIC_TAPE_HASH.index + i,
+                    value: PUBLIC_TAPE_HASH.preimage[usize::try_from(i).unwrap()],
+                },
+                multiplicity,
+            )
+        })
+        .collect()
+}
+
+/// Digest of the public tape of the proven execution, as a [`PublicValue`].
+///
+/// Verifiers compare it against [`PublicTapeDigest::from_tape`] of the input
+/// they expect the guest to have read.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PublicTapeDigest<F: RichField>(pub HashOut<F>);
+
+impl<F: RichField> PublicTapeDigest<F> {
+    /// Computes the digest that a proof over `tape` makes public.
+    ///
+    /// The tape is zero padded to a multiple of [`RATE`] bytes, so its length
+    /// is not part of the digest.
+    #[must_use]
+    pub fn from_tape(tape: &[u8]) -> Self {
+        let (_, output) = absorb_tape::<F>(tape)
+            .last()
+            .copied()
+            .expect("the sponge absorbs at least one chunk");
+        Self(HashOut::from_partial(&output[..NUM_HASH_OUT_ELTS]))
+    }
+}
+
+impl<F: RichField> PublicValue<F> for PublicTapeDigest<F> {
+    fn public_sub_table() -> PublicSubTable {
+        PublicSubTable {
+            table: PublicTapeHashTable::new(
+                PUBLIC_TAPE_HASH.output[..NUM_HASH_OUT_ELTS].to_vec(),
+                PUBLIC_TAPE_HASH.is_last,
+            ),
+            num_rows: 1,
+        }
+    }
+
+    fn from_rows(rows: &[Vec<F>]) -> Result<Self> {
+        let Some((row,)) = rows.iter().collect_tuple() else {
+            bail!("expected a single row for the public tape digest, got {rows:?}")
+        };
+        let Ok(elements) = <[F; NUM_HASH_OUT_ELTS]>::try_from(&row[..]) else {
+            bail!("expected {NUM_HASH_OUT_ELTS} elements for the public tape digest, got {row:?}")
+        };
+        Ok(Self(HashOut { elements }))
+    }
+}
@@ -0,0 +1,69 @@
+use itertools::Itertools;
+use plonky2::hash::hash_types::RichField;
+use plonky2::hash::poseidon2::{Poseidon2, WIDTH};
+
+use crate::public_tape_hash::columns::{PublicTapeHash, RATE};
+use crate::storage_device::columns::StorageDevice;
+use crate::utils::pad_trace_with_default;
+
+/// Runs the sponge over `tape`, and returns the preimage and output of each
+/// permutation.
+///
+/// An empty tape still takes one permutation over zeros, so that there is
+/// always a digest to make public.
+#[must_use]
+pub fn absorb_tape<F: RichField>(tape: &[u8]) -> Vec<([F; WIDTH], [F; WIDTH])> {
+    let mut state = [F::ZERO; WIDTH];
+    let chunks = tape.chunks(RATE).collect_vec();
+    let chunks = if chunks.is_empty() {
+        vec![&[][..]]
+    } else {
+        chunks
+    };
+    chunks
+        .into_iter()
+        .map(|chunk| {
+            let mut preimage = state;
+            preimage[..RATE].fill(F::ZERO);
+            for (slot, &byte) in preimage.iter_mut().zip(chunk) {
+                *slot = F::from_canonical_u8(byte);
+            }
+            let output = <F as Poseidon2>::poseidon2(preimage);
+            state = output;
+            (preimage, output)
+        })
+        .collect()
+}
+
+/// Generates the sponge over the public `tape`, with the multiplicities of
+/// the bytes that the memory rows of the `public_tape_rows` read.
+#[must_use]
+pub fn generate_public_tape_hash_trace<F: RichField>(
+    tape: &[u8],
+    public_tape_rows: &[StorageDevice<F>],
+) -> Vec<PublicTapeHash<F>> {
+    let mut trace = absorb_tape::<F>(tape)
+        .into_iter()
+        .enumerate()
+        .map(|(i, (preimage, output))| PublicTapeHash {
+            index: F::from_canonical_usize(i * RATE),
+            preimage,
+            output,
+            multiplicities: [F::ZERO; RATE],
+            is_executed: F::ONE,
+            is_last: F::ZERO,
+        })
+        .collect_vec();
+    if let Some(last) = trace.last_mut() {
+        last.is_last = F::ONE;
+    }
+    for row in public_tape_rows
+        .iter()
+        .filter(|row| row.ops.is_memory_store.is_one())
+    {
+        let index = usize::try_from(row.tape_index.to_canonical_u64()).unwrap();
+        trace[index / RATE].multiplicities[index % RATE] += F::ONE;
+    }
+    tracing::trace!("PublicTapeHash trace {trace:?}");
+    pad_trace_with_default(trace)
+}
@@ -0,0 +1,10 @@
+//! This module contains the **`PublicTapeHash` STARK Table**.
+//!
+//! It absorbs the whole public tape into a Poseidon2 sponge, eight bytes per
+//! row in tape order, and makes the final digest public. Every byte the guest
+//! reads from the public tape is looked up here at its tape index, so a
+//! verifier who recomputes the digest from the claimed input knows that the
+//! guest read exactly those bytes.
+pub mod columns;
+pub mod generation;
+pub mod stark;
@@ -0,0 +1,173 @@
+use std::marker::PhantomData;
+
+use expr::{Expr, ExprBuilder, StarkFrameTyped};
+use mozak_circuits_derive::StarkNameDisplay;
+use plonky2::field::extension::{Extendable, FieldExtension};
+use plonky2::field::packed::PackedField;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::ext_target::ExtensionTarget;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use starky::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
+use starky::evaluation_frame::StarkFrame;
+use starky::stark::Stark;
+
+use super::columns::{PublicTapeHash, RATE};
+use crate::columns_view::{HasNamedColumns, NumberOfColumns};
+use crate::expr::{build_ext, build_packed, ConstraintBuilder};
+use crate::unstark::NoColumns;
+
+pub(crate) fn generate_constraints<'a, T: Copy>(
+    vars: &StarkFrameTyped<PublicTapeHash<Expr<'a, T>>, NoColumns<Expr<'a, T>>>,
+) -> ConstraintBuilder<Expr<'a, T>> {
+    let lv = vars.local_values;
+    let nv = vars.next_values;
+    let mut constraints = ConstraintBuilder::default();
+
+    constraints.always(lv.is_executed.is_binary());
+    constraints.always(lv.is_last.is_binary());
+    // Only the rows of the sponge may be read, otherwise a prover could hide
+    // bytes in padding.
+    for multiplicity in lv.multiplicities {
+        constraints.always(multiplicity * (1 - lv.is_executed));
+    }
+
+    // The sponge starts at the beginning of the tape, with zero capacity.
+    constraints.first_row(lv.is_executed - 1);
+    constraints.first_row(lv.index);
+    for capacity in &lv.preimage[RATE..] {
+        constraints.first_row(*capacity);
+    }
+
+    // Each following row absorbs the next chunk, on top of the state left
+    // by the previous row.
+    constraints.transition(nv.is_executed * (1 - lv.is_executed));
+    constraints.transition(nv.is_executed * (nv.index - lv.index - i64::try_from(RATE).unwrap()));
+    for (preimage, output) in nv.preimage[RATE..].iter().zip(&lv.output[RATE..]) {
+        constraints.transition(nv.is_executed * (*preimage - *output));
+    }
+
+    // The digest is on the last row of the sponge.
+    constraints.transition(lv.is_last - (lv.is_executed - nv.is_executed));
+    constraints.last_row(lv.is_last - lv.is_executed);
+
+    constraints
+}
+
+#[derive(Copy, Clone, Default, StarkNameDisplay)]
+#[allow(clippy::module_name_repetitions)]
+pub struct PublicTapeHashStark<F, const D: usize> {
+    pub _f: PhantomData<F>,
+}
+
+impl<F, const D: usize> HasNamedColumns for PublicTapeHashStark<F, D> {
+    type Columns = PublicTapeHash<F>;
+}
+
+const COLUMNS: usize = PublicTapeHash::<()>::NUMBER_OF_COLUMNS;
+const PUBLIC_INPUTS: usize = 0;
+
+impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for PublicTapeHashStark<F, D> {
+    type EvaluationFrame<FE, P, const D2: usize> = StarkFrame<P, P::Scalar, COLUMNS, PUBLIC_INPUTS>
+
+        where
+            FE: FieldExtension<D2, BaseField = F>,
+            P: PackedField<Scalar = FE>;
+    type EvaluationFrameTarget =
+        StarkFrame<ExtensionTarget<D>, ExtensionTarget<D>, COLUMNS, PUBLIC_INPUTS>;
+
+    fn eval_packed_generic<FE, P, const D2: usize>(
+        &self,
+        vars: &Self::EvaluationFrame<FE, P, D2>,
+        consumer: &mut ConstraintConsumer<P>,
+    ) where
+        FE: FieldExtension<D2, BaseField = F>,
+        P: PackedField<Scalar = FE>, {
+        let eb = ExprBuilder::default();
+        let constraints = generate_constraints(&eb.to_typed_starkframe(vars));
+        build_packed(constraints, consumer);
+    }
+
+    fn constraint_degree(&self) -> usize { 3 }
+
+    fn eval_ext_circuit(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        vars: &Self::EvaluationFrameTarget,
+        consumer: &mut RecursiveConstraintConsumer<F, D>,
+    ) {
+        let eb = ExprBuilder::default();
+        let constraints = generate_constraints(&eb.to_typed_starkframe(vars));
+        build_ext(constraints, builder, consumer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mozak_runner::code::execute_code_with_ro_memory;
+    use mozak_runner::decode::ECALL;
+    use mozak_runner::state::RawTapes;
+    use mozak_sdk::core::ecall;
+    use mozak_sdk::core::reg_abi::{REG_A0, REG_A1, REG_A2};
+    use plonky2::util::timing::TimingTree;
+    use starky::stark_testing::test_stark_circuit_constraints;
+
+    use crate::public_tape_hash::columns::PublicTapeDigest;
+    use crate::stark::mozak_stark::{MozakStark, PublicInputs};
+    use crate::stark::prover::prove;
+    use crate::stark::verifier::verify_proof;
+    use crate::test_utils::{fast_test_config, C, D, F};
+    use crate::utils::from_u32;
+
+    type S = super::PublicTapeHashStark<F, D>;
+
+    #[test]
+    fn prove_public_tape_digest() -> anyhow::Result<()> {
+        let address = 0x100;
+        // Longer than one chunk, and only partly read.
+        let public_tape: Vec<u8> = (1..=11).collect();
+        let (program, record) = execute_code_with_ro_memory(
+            [ECALL],
+            &[],
+            &(address..address + 10)
+                .map(|addr| (addr, 0))
+                .collect::<Vec<_>>(),
+            &[
+                (REG_A0, ecall::PUBLIC_TAPE),
+                (REG_A1, address),
+                (REG_A2, 10),
+            ],
+            RawTapes {
+                public_tape: public_tape.clone(),
+                ..Default::default()
+            },
+        );
+        let stark = MozakStark::<F, D>::default().with_public_value::<PublicTapeDigest<F>>();
+        let config = fast_test_config();
+        let proof = prove::<F, C, D>(
+            &program,
+            &record,
+            &stark,
+            &config,
+            PublicInputs::new(from_u32(program.entry_point)),
+            &mut TimingTree::default(),
+        )?;
+        assert_eq!(
+            proof.public_value::<PublicTapeDigest<F>>(&stark)?,
+            PublicTapeDigest::from_tape(&public_tape)
+        );
+        assert_ne!(
+            proof.public_value::<PublicTapeDigest<F>>(&stark)?,
+            PublicTapeDigest::from_tape(&public_tape[..10])
+        );
+        verify_proof(&stark, proof, &config)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_circuit() -> anyhow::Result<()> {
+        let stark = S::default();
+        test_stark_circuit_constraints::<F, C, S, D>(stark)?;
+
+        Ok(())
+    }
+}
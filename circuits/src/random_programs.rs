@@ -0,0 +1,251 @@
+//! Random, well-formed programs, to prove and verify end to end.
+//!
+//! A [`RandomProgram`] is a sequence of [`Block`]s: straight-line arithmetic,
+//! loads and stores, forward branches over a few instructions, and loops that
+//! run a bounded number of times. Proving whole programs, rather than single
+//! instructions, exercises the cross table lookups between the tables.
+//!
+//! Loads and stores stay within [`SCRATCH`], and only loops write to
+//! [`LOOP_COUNTER`], so every program halts.
+//!
+//! The tests prove [`CASES_ENV_VAR`] programs, or only a few if it is not set.
+
+use mozak_runner::code;
+use mozak_runner::elf::Program;
+use mozak_runner::instruction::{Args, Instruction, InstructionError, Op};
+use mozak_runner::test_utils::u32_extra;
+use mozak_runner::vm::ExecutionRecord;
+use proptest::collection::vec;
+use proptest::prelude::any;
+use proptest::sample::select;
+use proptest::strategy::Strategy;
+use proptest::{prop_compose, prop_oneof};
+
+use crate::test_utils::F;
+
+/// Sets the number of programs that the tests prove, eg for a nightly run.
+pub const CASES_ENV_VAR: &str = "MOZAK_RANDOM_PROGRAM_CASES";
+
+/// The initialised memory that loads and stores access.
+pub const SCRATCH: std::ops::Range<u32> = 0x1000..0x1040;
+
+/// The register that counts down the iterations of a loop.
+pub const LOOP_COUNTER: u8 = 31;
+
+const REGISTER_OPS: &[Op] = &[
+    Op::ADD,
+    Op::SUB,
+    Op::XOR,
+    Op::OR,
+    Op::AND,
+    Op::SLL,
+    Op::SRL,
+    Op::SRA,
+    Op::SLT,
+    Op::SLTU,
+    Op::MUL,
+    Op::MULH,
+    Op::MULHU,
+    Op::MULHSU,
+    Op::DIV,
+    Op::DIVU,
+    Op::REM,
+    Op::REMU,
+];
+
+const IMMEDIATE_OPS: &[Op] = &[Op::ADD, Op::XOR, Op::OR, Op::AND, Op::SLT, Op::SLTU];
+
+const LOAD_OPS: &[Op] = &[Op::LB, Op::LH, Op::LW, Op::LBU, Op::LHU];
+
+const STORE_OPS: &[Op] = &[Op::SB, Op::SH, Op::SW];
+
+const BRANCH_OPS: &[Op] = &[Op::BEQ, Op::BNE, Op::BLT, Op::BGE, Op::BLTU, Op::BGEU];
+
+type Shift = fn(u8, u8, u32) -> Result<Instruction, InstructionError>;
+
+const SHIFTS: &[Shift] = &[Instruction::slli, Instruction::srli, Instruction::srai];
+
+/// A piece of a [`RandomProgram`].
+#[derive(Clone, Debug)]
+pub enum Block {
+    Straight(Vec<Instruction>),
+    /// Skips `skipped` if `op` branches on `rs1` and `rs2`.
+    Skip {
+        op: Op,
+        rs1: u8,
+        rs2: u8,
+        skipped: Vec<Instruction>,
+    },
+    /// Runs `body` `iterations` times.
+    Loop {
+        iterations: u32,
+        body: Vec<Instruction>,
+    },
+}
+
+impl Block {
+    /// The instructions of the block, when it starts at `pc`.
+    ///
+    /// # Panics
+    /// Panics if `pc` is not aligned to 4 bytes.
+    #[must_use]
+    pub fn code(&self, pc: u32) -> Vec<Instruction> {
+        let end = |len: usize| pc + u32::try_from(len * 4).unwrap();
+        match self {
+            Block::Straight(code) => code.clone(),
+            Block::Skip {
+                op,
+                rs1,
+                rs2,
+                skipped,
+            } => {
+                let branch = Instruction::new(*op, Args {
+                    rs1: *rs1,
+                    rs2: *rs2,
+                    imm: end(skipped.len() + 1),
+                    ..Args::default()
+                });
+                [&[branch][..], skipped].concat()
+            }
+            Block::Loop { iterations, body } => {
+                let start = end(1);
+                [&[Instruction::li(LOOP_COUNTER, *iterations)][..], body, &[
+                    Instruction::addi(LOOP_COUNTER, LOOP_COUNTER, u32::MAX),
+                    Instruction::bne(LOOP_COUNTER, 0, start).unwrap(),
+                ]]
+                .concat()
+            }
+        }
+    }
+}
+
+/// A random program, with the memory and registers it starts from.
+#[derive(Clone, Debug)]
+pub struct RandomProgram {
+    pub blocks: Vec<Block>,
+    /// The initial bytes of [`SCRATCH`].
+    pub scratch: Vec<u8>,
+    pub registers: Vec<(u8, u32)>,
+}
+
+impl RandomProgram {
+    /// The instructions of all blocks, laid out from address 0.
+    #[must_use]
+    pub fn code(&self) -> Vec<Instruction> {
+        self.blocks.iter().fold(vec![], |mut code, block| {
+            let pc = u32::try_from(code.len() * 4).unwrap();
+            code.extend(block.code(pc));
+            code
+        })
+    }
+
+    #[must_use]
+    pub fn execute(&self) -> (Program, ExecutionRecord<F>) {
+        let memory: Vec<_> = SCRATCH.zip(self.scratch.iter().copied()).collect();
+        code::execute(self.code(), &memory, &self.registers)
+    }
+}
+
+/// A register that the instructions of a block may write to.
+fn dst() -> impl Strategy<Value = u8> { 1..LOOP_COUNTER }
+
+fn src() -> impl Strategy<Value = u8> { 0_u8..32 }
+
+/// An address in [`SCRATCH`], aligned for every width of load and store.
+fn scratch_addr() -> impl Strategy<Value = u32> { select(SCRATCH.step_by(4).collect::<Vec<_>>()) }
+
+/// An instruction that neither jumps nor touches [`LOOP_COUNTER`].
+pub fn straight_instruction() -> impl Strategy<Value = Instruction> {
+    prop_oneof![
+        (select(REGISTER_OPS), dst(), src(), src()).prop_map(|(op, rd, rs1, rs2)| {
+            Instruction::new(op, Args {
+                rd,
+                rs1,
+                rs2,
+                imm: 0,
+            })
+        }),
+        (select(IMMEDIATE_OPS), dst(), src(), u32_extra()).prop_map(|(op, rd, rs1, imm)| {
+            Instruction::new(op, Args {
+                rd,
+                rs1,
+                imm,
+                ..Args::default()
+            })
+        }),
+        (select(SHIFTS), dst(), src(), 0_u32..32)
+            .prop_map(|(shift, rd, rs1, shamt)| shift(rd, rs1, shamt).unwrap()),
+        (select(LOAD_OPS), dst(), scratch_addr()).prop_map(|(op, rd, imm)| {
+            Instruction::new(op, Args {
+                rd,
+                imm,
+                ..Args::default()
+            })
+        }),
+        (select(STORE_OPS), src(), scratch_addr()).prop_map(|(op, rs1, imm)| {
+            Instruction::new(op, Args {
+                rs1,
+                imm,
+                ..Args::default()
+            })
+        }),
+    ]
+}
+
+pub fn block() -> impl Strategy<Value = Block> {
+    let code = || vec(straight_instruction(), 1..4);
+    prop_oneof![
+        code().prop_map(Block::Straight),
+        (select(BRANCH_OPS), src(), src(), code()).prop_map(|(op, rs1, rs2, skipped)| {
+            Block::Skip {
+                op,
+                rs1,
+                rs2,
+                skipped,
+            }
+        }),
+        (1_u32..4, code()).prop_map(|(iterations, body)| Block::Loop { iterations, body }),
+    ]
+}
+
+prop_compose! {
+    pub fn random_program()(
+        blocks in vec(block(), 1..8),
+        scratch in vec(any::<u8>(), SCRATCH.len()),
+        values in vec(u32_extra(), usize::from(LOOP_COUNTER - 1)),
+    ) -> RandomProgram {
+        let registers = (1..LOOP_COUNTER).zip(values).collect();
+        RandomProgram { blocks, scratch, registers }
+    }
+}
+
+/// The number of programs to prove, from [`CASES_ENV_VAR`].
+///
+/// # Panics
+/// Panics if [`CASES_ENV_VAR`] is set, but not to a number.
+#[must_use]
+pub fn cases() -> u32 {
+    std::env::var(CASES_ENV_VAR).map_or(2, |cases| {
+        cases
+            .parse()
+            .unwrap_or_else(|_| panic!("{CASES_ENV_VAR} must be a number, not {cases:?}"))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::ProptestConfig;
+    use proptest::proptest;
+
+    use super::{cases, random_program};
+    use crate::test_utils::{fast_test_config, prove_and_verify_mozak_stark};
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(cases()))]
+        #[test]
+        fn prove_random_programs(program in random_program()) {
+            let (program, record) = program.execute();
+            prove_and_verify_mozak_stark(&program, &record, &fast_test_config()).unwrap();
+        }
+    }
+}
@@ -1,16 +1,15 @@
-use crate::columns_view::{columns_view_impl, make_col_map};
+use crate::columns_view::{make_col_map, ColumnsView};
 use crate::cross_table_lookup::Column;
 use crate::stark::mozak_stark::{RangeCheckTable, TableWithTypedOutput};
 
 #[repr(C)]
-#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
 pub struct RangeCheckColumnsView<T> {
     /// The limbs (u8) of the u32 value to be range
     /// checked.
     pub limbs: [T; 4],
     pub multiplicity: T,
 }
-columns_view_impl!(RangeCheckColumnsView);
 make_col_map!(RangeCheckColumnsView);
 
 /// Lookup for columns be range checked in the Mozak
@@ -25,9 +24,8 @@ pub fn lookup() -> TableWithTypedOutput<RangeCheckCtl<Column>> {
     RangeCheckTable::new(data, COL_MAP.multiplicity)
 }
 
-columns_view_impl!(RangeCheckCtl);
 #[repr(C)]
-#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
 pub struct RangeCheckCtl<T>(pub T);
 
 #[must_use]
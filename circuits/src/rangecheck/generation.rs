@@ -2,14 +2,24 @@ use std::ops::Index;
 
 use itertools::Itertools;
 use plonky2::hash::hash_types::RichField;
+#[allow(clippy::wildcard_imports)]
+use plonky2_maybe_rayon::*;
 
 use crate::cpu::columns::CpuState;
+#[cfg(feature = "decode")]
+use crate::decode::columns::Decode;
+use crate::event_emission::columns::EventEmission;
 use crate::memory::columns::Memory;
+use crate::memoryinit::columns::MemoryInit;
 use crate::ops::add::columns::Add;
 use crate::ops::blt_taken::columns::BltTaken;
 use crate::rangecheck::columns::RangeCheckColumnsView;
 use crate::register::general::columns::Register;
+#[cfg(feature = "secp256k1")]
+use crate::secp256k1::columns::Secp256k1;
 use crate::stark::mozak_stark::{Lookups, RangecheckTable, Table, TableKind};
+#[cfg(feature = "u256")]
+use crate::u256::columns::U256;
 use crate::utils::pad_trace_with_default;
 
 /// Converts a u32 into 4 u8 limbs represented in [`RichField`].
@@ -19,13 +29,16 @@ pub fn limbs_from_u32<F: RichField>(value: u32) -> [F; 4] {
 }
 
 /// extract the values with multiplicities
+///
+/// Rows are evaluated in parallel, each paired with the next one, wrapping
+/// around at the end of the trace. The output keeps the order of the trace.
 pub fn extract_with_mul<F: RichField, Row>(trace: &[Row], looking_table: &Table) -> Vec<(F, F)>
 where
-    Row: Index<usize, Output = F>, {
+    Row: Index<usize, Output = F> + Sync, {
     if let [column] = &looking_table.columns[..] {
-        trace
-            .iter()
-            .circular_tuple_windows()
+        (0..trace.len())
+            .into_par_iter()
+            .map(|i| (&trace[i], &trace[(i + 1) % trace.len()]))
             .filter_map(|(prev_row, row)| {
                 let mult = looking_table.filter_column.eval(prev_row, row);
                 mult.is_nonzero().then_some((
@@ -54,7 +67,12 @@ pub(crate) fn generate_rangecheck_trace<F: RichField>(
     add_trace: &[Add<F>],
     blt_taken_trace: &[BltTaken<F>],
     memory_trace: &[Memory<F>],
+    memory_init_trace: &[MemoryInit<F>],
     register_trace: &[Register<F>],
+    event_emission_trace: &[EventEmission<F>],
+    #[cfg(feature = "secp256k1")] secp256k1_trace: &[Secp256k1<F>],
+    #[cfg(feature = "u256")] u256_trace: &[U256<F>],
+    #[cfg(feature = "decode")] decode_trace: &[Decode<F>],
 ) -> Vec<RangeCheckColumnsView<F>> {
     pad_trace_with_default(
         RangecheckTable::lookups()
@@ -64,9 +82,18 @@ pub(crate) fn generate_rangecheck_trace<F: RichField>(
                 match looking_table.kind {
                     TableKind::Cpu => extract_with_mul(cpu_trace, &looking_table),
                     TableKind::Memory => extract_with_mul(memory_trace, &looking_table),
+                    TableKind::MemoryInit => extract_with_mul(memory_init_trace, &looking_table),
                     TableKind::Register => extract_with_mul(register_trace, &looking_table),
                     TableKind::Add => extract_with_mul(add_trace, &looking_table),
                     TableKind::BltTaken => extract_with_mul(blt_taken_trace, &looking_table),
+                    TableKind::EventEmission =>
+                        extract_with_mul(event_emission_trace, &looking_table),
+                    #[cfg(feature = "secp256k1")]
+                    TableKind::Secp256k1 => extract_with_mul(secp256k1_trace, &looking_table),
+                    #[cfg(feature = "u256")]
+                    TableKind::U256 => extract_with_mul(u256_trace, &looking_table),
+                    #[cfg(feature = "decode")]
+                    TableKind::Decode => extract_with_mul(decode_trace, &looking_table),
                     // We are trying to build the RangeCheck table, so we have to ignore it here.
                     TableKind::RangeCheck => vec![],
                     other => unimplemented!("Can't range check {other:#?} tables"),
@@ -91,40 +118,45 @@ pub(crate) fn generate_rangecheck_trace<F: RichField>(
 #[cfg(test)]
 mod tests {
     use mozak_runner::code;
-    use mozak_runner::instruction::{Args, Instruction, Op};
+    use mozak_runner::instruction::Instruction;
     use plonky2::field::goldilocks_field::GoldilocksField;
     use plonky2::field::types::Field;
 
     use super::*;
     use crate::cpu::generation::generate_cpu_trace;
+    use crate::event_emission::generation::generate_event_emission_trace;
     use crate::generation::MIN_TRACE_LENGTH;
+    #[cfg(feature = "goldilocks")]
+    use crate::goldilocks::generation::generate_goldilocks_trace;
+    #[cfg(feature = "memcopy")]
+    use crate::memcopy::generation::generate_memcopy_trace;
     use crate::memory::generation::generate_memory_trace;
     use crate::memory_fullword::generation::generate_fullword_memory_trace;
     use crate::memory_halfword::generation::generate_halfword_memory_trace;
-    use crate::memory_zeroinit::generation::generate_memory_zero_init_trace;
-    use crate::memoryinit::generation::generate_memory_init_trace;
+    use crate::memoryinit::generation::{
+        generate_elf_memory_init_trace, generate_memory_init_trace,
+    };
     use crate::ops::{self, blt_taken};
+    #[cfg(feature = "poseidon2")]
     use crate::poseidon2_output_bytes::generation::generate_poseidon2_output_bytes_trace;
+    #[cfg(feature = "poseidon2")]
     use crate::poseidon2_sponge::generation::generate_poseidon2_sponge_trace;
     use crate::register::generation::{generate_register_init_trace, generate_register_trace};
+    #[cfg(feature = "secp256k1")]
+    use crate::secp256k1::generation::generate_secp256k1_trace;
     use crate::storage_device::generation::{
         generate_call_tape_trace, generate_cast_list_commitment_tape_trace,
-        generate_event_tape_trace, generate_events_commitment_tape_trace,
+        generate_event_tape_trace, generate_events_commitment_tape_trace, generate_hint_tape_trace,
         generate_private_tape_trace, generate_public_tape_trace, generate_self_prog_id_tape_trace,
     };
+    #[cfg(feature = "u256")]
+    use crate::u256::generation::generate_u256_trace;
 
     #[test]
     fn test_generate_trace() {
         type F = GoldilocksField;
         let (program, record) = code::execute(
-            [Instruction {
-                op: Op::SB,
-                args: Args {
-                    rs1: 1,
-                    imm: u32::MAX,
-                    ..Args::default()
-                },
-            }],
+            [Instruction::sb(1, 0, u32::MAX)],
             // Use values that would become limbs later
             &[],
             &[(1, u32::MAX)],
@@ -134,8 +166,9 @@ mod tests {
         let add_rows = ops::add::generate(&record);
         let blt_rows = blt_taken::generate(&record);
 
-        let memory_init = generate_memory_init_trace(&program);
-        let memory_zeroinit_rows = generate_memory_zero_init_trace(&record.executed, &program);
+        let elf_memory_init_rows = generate_elf_memory_init_trace(&program);
+
+        let memory_init_rows = generate_memory_init_trace(&record.executed, &program);
 
         let halfword_memory = generate_halfword_memory_trace(&record.executed);
         let fullword_memory = generate_fullword_memory_trace(&record.executed);
@@ -147,12 +180,24 @@ mod tests {
         let cast_list_commitment_tape_rows =
             generate_cast_list_commitment_tape_trace(&record.executed);
         let self_prog_id_tape_rows = generate_self_prog_id_tape_trace(&record.executed);
+        let hint_tape_rows = generate_hint_tape_trace(&record.executed);
+        let event_emission_rows = generate_event_emission_trace(&record.executed);
+        #[cfg(feature = "secp256k1")]
+        let secp256k1_rows = generate_secp256k1_trace(&record.executed);
+        #[cfg(feature = "u256")]
+        let u256_rows = generate_u256_trace(&record.executed);
+        #[cfg(feature = "memcopy")]
+        let memcopy_rows = generate_memcopy_trace(&record.executed);
+        #[cfg(feature = "goldilocks")]
+        let goldilocks_rows = generate_goldilocks_trace(&record.executed);
+        #[cfg(feature = "poseidon2")]
         let poseidon2_sponge_trace = generate_poseidon2_sponge_trace(&record.executed);
+        #[cfg(feature = "poseidon2")]
         let poseidon2_output_bytes = generate_poseidon2_output_bytes_trace(&poseidon2_sponge_trace);
         let memory_rows = generate_memory_trace::<F>(
             &record.executed,
-            &memory_init,
-            &memory_zeroinit_rows,
+            &elf_memory_init_rows,
+            &memory_init_rows,
             &halfword_memory,
             &fullword_memory,
             &private_tape_rows,
@@ -162,15 +207,40 @@ mod tests {
             &events_commitment_tape_rows,
             &cast_list_commitment_tape_rows,
             &self_prog_id_tape_rows,
+            &hint_tape_rows,
+            &event_emission_rows,
+            #[cfg(feature = "poseidon2")]
             &poseidon2_sponge_trace,
+            #[cfg(feature = "poseidon2")]
             &poseidon2_output_bytes,
+            #[cfg(feature = "poseidon2")]
+            &[],
+            #[cfg(feature = "secp256k1")]
+            &secp256k1_rows,
+            #[cfg(feature = "u256")]
+            &u256_rows,
+            #[cfg(feature = "memcopy")]
+            &memcopy_rows,
+            #[cfg(feature = "goldilocks")]
+            &goldilocks_rows,
         );
         let register_init = generate_register_init_trace(&record);
         let (_, _, register_rows) = generate_register_trace(
             &cpu_rows,
             &add_rows,
             &blt_rows,
+            #[cfg(feature = "poseidon2")]
             &poseidon2_sponge_trace,
+            #[cfg(feature = "poseidon2")]
+            &[],
+            #[cfg(feature = "secp256k1")]
+            &secp256k1_rows,
+            #[cfg(feature = "u256")]
+            &u256_rows,
+            #[cfg(feature = "memcopy")]
+            &memcopy_rows,
+            #[cfg(feature = "goldilocks")]
+            &goldilocks_rows,
             &private_tape_rows,
             &public_tape_rows,
             &call_tape_rows,
@@ -178,6 +248,8 @@ mod tests {
             &events_commitment_tape_rows,
             &cast_list_commitment_tape_rows,
             &self_prog_id_tape_rows,
+            &hint_tape_rows,
+            &event_emission_rows,
             &register_init,
         );
         let trace = generate_rangecheck_trace::<F>(
@@ -185,7 +257,15 @@ mod tests {
             &add_rows,
             &blt_rows,
             &memory_rows,
+            &memory_init_rows,
             &register_rows,
+            &event_emission_rows,
+            #[cfg(feature = "secp256k1")]
+            &secp256k1_rows,
+            #[cfg(feature = "u256")]
+            &u256_rows,
+            #[cfg(feature = "decode")]
+            &[],
         );
         assert_eq!(
             trace.len(),
@@ -195,7 +275,7 @@ mod tests {
         );
         for (i, row) in trace.iter().enumerate() {
             match i {
-                0 => assert_eq!(row.multiplicity, F::from_canonical_u8(7)),
+                0 => assert_eq!(row.multiplicity, F::from_canonical_u8(11)),
                 1 => assert_eq!(row.multiplicity, F::from_canonical_u8(2)),
                 _ => {}
             }
@@ -7,6 +7,13 @@
 //!
 //! The STARK is then used by the CPU STARK with the Cross Table Lookup (CTL)
 //! technique.
+//!
+//! This table is also the one place that splits u32 values into byte limbs.
+//! Tables that hold a value as bytes, like the full-word and half-word memory
+//! tables, keep only the bytes and put the value back together as a linear
+//! combination in their lookups, so they need neither a decomposition of
+//! their own nor constraints for it. The bytes themselves are range checked
+//! by the memory table, via the `RangeCheckU8` table.
 
 pub mod columns;
 pub mod generation;
@@ -9,7 +9,7 @@ pub type RangeCheckStark<F, const D: usize> =
 #[cfg(test)]
 mod tests {
     use mozak_runner::code;
-    use mozak_runner::instruction::{Args, Instruction, Op};
+    use mozak_runner::instruction::Instruction;
     use plonky2::plonk::config::{GenericConfig, Poseidon2GoldilocksConfig};
     use starky::stark_testing::test_stark_circuit_constraints;
 
@@ -30,19 +30,8 @@ mod tests {
             .step_by(23)
             .map(|i| (i, inst))
             .collect::<Vec<_>>();
-        let (program, record) = code::execute(
-            [Instruction {
-                op: Op::ADD,
-                args: Args {
-                    rd: 5,
-                    rs1: 6,
-                    rs2: 7,
-                    ..Args::default()
-                },
-            }],
-            &mem,
-            &[(6, 100), (7, 100)],
-        );
+        let (program, record) =
+            code::execute([Instruction::add(5, 6, 7)], &mem, &[(6, 100), (7, 100)]);
         MozakStark::prove_and_verify(&program, &record).unwrap();
     }
 
@@ -1,10 +1,10 @@
-use crate::columns_view::{columns_view_impl, make_col_map};
+use crate::columns_view::{make_col_map, ColumnsView};
 use crate::linear_combination::Column;
 use crate::rangecheck::columns::RangeCheckCtl;
 use crate::stark::mozak_stark::{RangeCheckU8Table, TableWithTypedOutput};
 
 #[repr(C)]
-#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
 pub struct RangeCheckU8<T> {
     /// The u8 value to be range checked
     pub value: T,
@@ -13,7 +13,6 @@ pub struct RangeCheckU8<T> {
     /// the trace. This is m(x) in the paper.
     pub multiplicity: T,
 }
-columns_view_impl!(RangeCheckU8);
 make_col_map!(RangeCheckU8);
 
 #[must_use]
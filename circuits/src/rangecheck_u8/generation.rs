@@ -5,7 +5,11 @@ use crate::memory::columns::Memory;
 use crate::rangecheck::columns::RangeCheckColumnsView;
 use crate::rangecheck::generation::extract_with_mul;
 use crate::rangecheck_u8::columns::RangeCheckU8;
+#[cfg(feature = "secp256k1")]
+use crate::secp256k1::columns::Secp256k1;
 use crate::stark::mozak_stark::{Lookups, RangeCheckU8LookupTable, TableKind};
+#[cfg(feature = "u256")]
+use crate::u256::columns::U256;
 
 /// Generate a limb lookup trace from `rangecheck_trace`
 ///
@@ -14,6 +18,8 @@ use crate::stark::mozak_stark::{Lookups, RangeCheckU8LookupTable, TableKind};
 pub(crate) fn generate_rangecheck_u8_trace<F: RichField>(
     rangecheck_trace: &[RangeCheckColumnsView<F>],
     memory_trace: &[Memory<F>],
+    #[cfg(feature = "secp256k1")] secp256k1_trace: &[Secp256k1<F>],
+    #[cfg(feature = "u256")] u256_trace: &[U256<F>],
 ) -> Vec<RangeCheckU8<F>> {
     RangeCheckU8LookupTable::lookups()
         .looking_tables
@@ -21,6 +27,10 @@ pub(crate) fn generate_rangecheck_u8_trace<F: RichField>(
         .flat_map(|looking_table| match looking_table.kind {
             TableKind::RangeCheck => extract_with_mul(rangecheck_trace, &looking_table),
             TableKind::Memory => extract_with_mul(memory_trace, &looking_table),
+            #[cfg(feature = "secp256k1")]
+            TableKind::Secp256k1 => extract_with_mul(secp256k1_trace, &looking_table),
+            #[cfg(feature = "u256")]
+            TableKind::U256 => extract_with_mul(u256_trace, &looking_table),
             // We are trying to build this table, so we have to ignore it here.
             TableKind::RangeCheckU8 => vec![],
             other => unimplemented!("Can't range check {other:?} tables"),
@@ -39,40 +49,45 @@ pub(crate) fn generate_rangecheck_u8_trace<F: RichField>(
 #[cfg(test)]
 mod tests {
     use mozak_runner::code;
-    use mozak_runner::instruction::{Args, Instruction, Op};
+    use mozak_runner::instruction::Instruction;
     use plonky2::field::goldilocks_field::GoldilocksField;
     use plonky2::field::types::{Field, PrimeField64};
 
     use super::*;
     use crate::cpu::generation::generate_cpu_trace;
+    use crate::event_emission::generation::generate_event_emission_trace;
+    #[cfg(feature = "goldilocks")]
+    use crate::goldilocks::generation::generate_goldilocks_trace;
+    #[cfg(feature = "memcopy")]
+    use crate::memcopy::generation::generate_memcopy_trace;
     use crate::memory::generation::generate_memory_trace;
     use crate::memory_fullword::generation::generate_fullword_memory_trace;
     use crate::memory_halfword::generation::generate_halfword_memory_trace;
-    use crate::memory_zeroinit::generation::generate_memory_zero_init_trace;
-    use crate::memoryinit::generation::generate_memory_init_trace;
+    use crate::memoryinit::generation::{
+        generate_elf_memory_init_trace, generate_memory_init_trace,
+    };
     use crate::ops;
+    #[cfg(feature = "poseidon2")]
     use crate::poseidon2_output_bytes::generation::generate_poseidon2_output_bytes_trace;
+    #[cfg(feature = "poseidon2")]
     use crate::poseidon2_sponge::generation::generate_poseidon2_sponge_trace;
     use crate::rangecheck::generation::generate_rangecheck_trace;
     use crate::register::generation::{generate_register_init_trace, generate_register_trace};
+    #[cfg(feature = "secp256k1")]
+    use crate::secp256k1::generation::generate_secp256k1_trace;
     use crate::storage_device::generation::{
         generate_call_tape_trace, generate_cast_list_commitment_tape_trace,
-        generate_event_tape_trace, generate_events_commitment_tape_trace,
+        generate_event_tape_trace, generate_events_commitment_tape_trace, generate_hint_tape_trace,
         generate_private_tape_trace, generate_public_tape_trace, generate_self_prog_id_tape_trace,
     };
+    #[cfg(feature = "u256")]
+    use crate::u256::generation::generate_u256_trace;
 
     #[test]
     fn test_generate_trace() {
         type F = GoldilocksField;
         let (program, record) = code::execute(
-            [Instruction {
-                op: Op::SB,
-                args: Args {
-                    rs1: 1,
-                    imm: u32::MAX,
-                    ..Args::default()
-                },
-            }],
+            [Instruction::sb(1, 0, u32::MAX)],
             // Use values that would become limbs later
             &[],
             &[(1, u32::MAX)],
@@ -82,8 +97,9 @@ mod tests {
         let add_rows = ops::add::generate(&record);
         let blt_rows = ops::blt_taken::generate(&record);
 
-        let memory_init = generate_memory_init_trace(&program);
-        let memory_zeroinit_rows = generate_memory_zero_init_trace(&record.executed, &program);
+        let elf_memory_init_rows = generate_elf_memory_init_trace(&program);
+
+        let memory_init_rows = generate_memory_init_trace(&record.executed, &program);
 
         let halfword_memory = generate_halfword_memory_trace(&record.executed);
         let fullword_memory = generate_fullword_memory_trace(&record.executed);
@@ -95,12 +111,24 @@ mod tests {
         let cast_list_commitment_tape_rows =
             generate_cast_list_commitment_tape_trace(&record.executed);
         let self_prog_id_tape_rows = generate_self_prog_id_tape_trace(&record.executed);
+        let hint_tape_rows = generate_hint_tape_trace(&record.executed);
+        let event_emission_rows = generate_event_emission_trace(&record.executed);
+        #[cfg(feature = "secp256k1")]
+        let secp256k1_rows = generate_secp256k1_trace(&record.executed);
+        #[cfg(feature = "u256")]
+        let u256_rows = generate_u256_trace(&record.executed);
+        #[cfg(feature = "memcopy")]
+        let memcopy_rows = generate_memcopy_trace(&record.executed);
+        #[cfg(feature = "goldilocks")]
+        let goldilocks_rows = generate_goldilocks_trace(&record.executed);
+        #[cfg(feature = "poseidon2")]
         let poseidon2_sponge_trace = generate_poseidon2_sponge_trace(&record.executed);
+        #[cfg(feature = "poseidon2")]
         let poseidon2_output_bytes = generate_poseidon2_output_bytes_trace(&poseidon2_sponge_trace);
         let memory_rows = generate_memory_trace::<F>(
             &record.executed,
-            &memory_init,
-            &memory_zeroinit_rows,
+            &elf_memory_init_rows,
+            &memory_init_rows,
             &halfword_memory,
             &fullword_memory,
             &private_tape,
@@ -110,15 +138,40 @@ mod tests {
             &events_commitment_tape_rows,
             &cast_list_commitment_tape_rows,
             &self_prog_id_tape_rows,
+            &hint_tape_rows,
+            &event_emission_rows,
+            #[cfg(feature = "poseidon2")]
             &poseidon2_sponge_trace,
+            #[cfg(feature = "poseidon2")]
             &poseidon2_output_bytes,
+            #[cfg(feature = "poseidon2")]
+            &[],
+            #[cfg(feature = "secp256k1")]
+            &secp256k1_rows,
+            #[cfg(feature = "u256")]
+            &u256_rows,
+            #[cfg(feature = "memcopy")]
+            &memcopy_rows,
+            #[cfg(feature = "goldilocks")]
+            &goldilocks_rows,
         );
         let register_init = generate_register_init_trace(&record);
         let (_, _, register_rows) = generate_register_trace(
             &cpu_rows,
             &add_rows,
             &blt_rows,
+            #[cfg(feature = "poseidon2")]
             &poseidon2_sponge_trace,
+            #[cfg(feature = "poseidon2")]
+            &[],
+            #[cfg(feature = "secp256k1")]
+            &secp256k1_rows,
+            #[cfg(feature = "u256")]
+            &u256_rows,
+            #[cfg(feature = "memcopy")]
+            &memcopy_rows,
+            #[cfg(feature = "goldilocks")]
+            &goldilocks_rows,
             &private_tape,
             &public_tape,
             &call_tape_rows,
@@ -126,6 +179,8 @@ mod tests {
             &events_commitment_tape_rows,
             &cast_list_commitment_tape_rows,
             &self_prog_id_tape_rows,
+            &hint_tape_rows,
+            &event_emission_rows,
             &register_init,
         );
         let rangecheck_rows = generate_rangecheck_trace::<F>(
@@ -133,10 +188,25 @@ mod tests {
             &add_rows,
             &blt_rows,
             &memory_rows,
+            &memory_init_rows,
             &register_rows,
+            &event_emission_rows,
+            #[cfg(feature = "secp256k1")]
+            &secp256k1_rows,
+            #[cfg(feature = "u256")]
+            &u256_rows,
+            #[cfg(feature = "decode")]
+            &[],
         );
 
-        let trace = generate_rangecheck_u8_trace(&rangecheck_rows, &memory_rows);
+        let trace = generate_rangecheck_u8_trace(
+            &rangecheck_rows,
+            &memory_rows,
+            #[cfg(feature = "secp256k1")]
+            &secp256k1_rows,
+            #[cfg(feature = "u256")]
+            &u256_rows,
+        );
 
         for row in &trace {
             // TODO(bing): more comprehensive test once we rip out the old trace gen logic.
@@ -145,8 +215,8 @@ mod tests {
         }
 
         assert_eq!(trace[0].value, F::from_canonical_u8(0));
-        assert_eq!(trace[0].multiplicity, F::from_canonical_u64(48));
+        assert_eq!(trace[0].multiplicity, F::from_canonical_u64(64));
         assert_eq!(trace[255].value, F::from_canonical_u8(u8::MAX));
-        assert_eq!(trace[255].multiplicity, F::from_canonical_u64(4));
+        assert_eq!(trace[255].multiplicity, F::from_canonical_u64(15));
     }
 }
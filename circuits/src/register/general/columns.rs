@@ -2,16 +2,15 @@ use core::ops::Add;
 
 use plonky2::hash::hash_types::RichField;
 
-use crate::columns_view::{columns_view_impl, make_col_map};
+use crate::columns_view::{make_col_map, ColumnsView};
 use crate::linear_combination::Column;
 use crate::linear_combination_typed::ColumnWithTypedInput;
 use crate::rangecheck::columns::RangeCheckCtl;
 use crate::register::RegisterCtl;
 use crate::stark::mozak_stark::{RegisterTable, TableWithTypedOutput};
 
-columns_view_impl!(Ops);
 #[repr(C)]
-#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
 pub struct Ops<T> {
     /// Binary filter column that marks a row as the initialization of
     /// a register.
@@ -61,11 +60,10 @@ impl<F: RichField> Ops<F> {
     }
 }
 
-columns_view_impl!(Register);
 make_col_map!(Register);
 /// [`Design doc for RegisterSTARK`](https://www.notion.so/0xmozak/Register-File-STARK-62459d68aea648a0abf4e97aa0093ea2?pvs=4#0729f89ddc724967ac991c9e299cc4fc)
 #[repr(C)]
-#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
 pub struct Register<T> {
     /// The register 'address' that indexes into 1 of our 32 registers.
     /// Should only take values 0-31, so this column should be a running sum
@@ -101,8 +99,10 @@ impl<T: Add<Output = T> + Copy> Register<T> {
 
     pub fn is_rw(self) -> T { self.ops.is_read + self.ops.is_write }
 
-    // See, if we want to add a Mul constraint, we need to add a Mul trait bound?
-    // Or whether we want to keep manual addition and clone?
+    /// `2 * clk + is_write`, so that reads of a clock cycle come before its
+    /// writes. The range check in [`rangecheck_looking`] takes the difference
+    /// between the local and the next row directly, so this needs no column of
+    /// its own.
     pub fn augmented_clk(self) -> T { self.clk + self.clk + self.ops.is_write }
 }
 
@@ -120,6 +120,11 @@ pub fn register_looked() -> TableWithTypedOutput<RegisterCtl<Column>> {
     )
 }
 
+/// Sorts the reads and writes of each register by
+/// [`augmented_clk`](Register::augmented_clk): the step from the previous row
+/// to a read or write has to fit in a `u32`, so it can not go back in time.
+/// Together with constraint 4 of the `RegisterStark`, which keeps the address
+/// the same from row to row, this orders the accesses within each register.
 #[must_use]
 pub fn rangecheck_looking() -> Vec<TableWithTypedOutput<RangeCheckCtl<Column>>> {
     vec![RegisterTable::new(
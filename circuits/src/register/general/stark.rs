@@ -124,7 +124,7 @@ impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for RegisterStark
 mod tests {
     use anyhow::Result;
     use mozak_runner::code;
-    use mozak_runner::instruction::{Args, Instruction, Op};
+    use mozak_runner::instruction::Instruction;
     use mozak_runner::test_utils::{reg, u32_extra};
     use plonky2::plonk::config::{GenericConfig, Poseidon2GoldilocksConfig};
     use starky::stark_testing::{test_stark_circuit_constraints, test_stark_low_degree};
@@ -151,26 +151,7 @@ mod tests {
 
     fn prove_stark<Stark: ProveAndVerify>(a: u32, b: u32, imm: u32, rd: u8) {
         let (program, record) = code::execute(
-            [
-                Instruction {
-                    op: Op::ADD,
-                    args: Args {
-                        rd,
-                        rs1: 6,
-                        rs2: 7,
-                        ..Args::default()
-                    },
-                },
-                Instruction {
-                    op: Op::ADD,
-                    args: Args {
-                        rd,
-                        rs1: 6,
-                        imm,
-                        ..Args::default()
-                    },
-                },
-            ],
+            [Instruction::add(rd, 6, 7), Instruction::addi(rd, 6, imm)],
             &[],
             &[(6, a), (7, b)],
         );
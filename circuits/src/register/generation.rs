@@ -5,15 +5,25 @@ use mozak_runner::vm::ExecutionRecord;
 use plonky2::hash::hash_types::RichField;
 
 use crate::cpu::columns::CpuState;
+use crate::event_emission::columns::EventEmission;
+#[cfg(feature = "goldilocks")]
+use crate::goldilocks::columns::Goldilocks;
+#[cfg(feature = "memcopy")]
+use crate::memcopy::columns::MemCopy;
 use crate::ops;
+#[cfg(feature = "poseidon2")]
 use crate::poseidon2_sponge::columns::Poseidon2Sponge;
 use crate::register::general::columns::{Ops, Register};
 use crate::register::init::columns::RegisterInit;
 use crate::register::zero_read::columns::RegisterZeroRead;
 use crate::register::zero_write::columns::RegisterZeroWrite;
 use crate::register::RegisterCtl;
+#[cfg(feature = "secp256k1")]
+use crate::secp256k1::columns::Secp256k1;
 use crate::stark::mozak_stark::{Lookups, RegisterLookups, Table, TableKind};
 use crate::storage_device::columns::StorageDevice;
+#[cfg(feature = "u256")]
+use crate::u256::columns::U256;
 use crate::utils::{pad_trace_with_default, pad_trace_with_last, pad_trace_with_row};
 
 /// Sort rows into blocks of ascending addresses, and then sort each block
@@ -88,7 +98,12 @@ pub fn generate_register_trace<F: RichField>(
     cpu_trace: &[CpuState<F>],
     add_trace: &[ops::add::columns::Add<F>],
     blt_trace: &[ops::blt_taken::columns::BltTaken<F>],
-    poseidon2_sponge: &[Poseidon2Sponge<F>],
+    #[cfg(feature = "poseidon2")] poseidon2_sponge: &[Poseidon2Sponge<F>],
+    #[cfg(feature = "poseidon2")] mem_output_tape: &[StorageDevice<F>],
+    #[cfg(feature = "secp256k1")] secp256k1: &[Secp256k1<F>],
+    #[cfg(feature = "u256")] u256: &[U256<F>],
+    #[cfg(feature = "memcopy")] memcopy: &[MemCopy<F>],
+    #[cfg(feature = "goldilocks")] goldilocks: &[Goldilocks<F>],
     mem_private: &[StorageDevice<F>],
     mem_public: &[StorageDevice<F>],
     mem_call_tape: &[StorageDevice<F>],
@@ -96,6 +111,8 @@ pub fn generate_register_trace<F: RichField>(
     mem_events_commitment_tape: &[StorageDevice<F>],
     mem_cast_list_commitment_tape: &[StorageDevice<F>],
     mem_self_prog_id_tape: &[StorageDevice<F>],
+    mem_hint_tape: &[StorageDevice<F>],
+    event_emission: &[EventEmission<F>],
     reg_init: &[RegisterInit<F>],
 ) -> (
     Vec<RegisterZeroRead<F>>,
@@ -118,8 +135,21 @@ pub fn generate_register_trace<F: RichField>(
             TableKind::CastListCommitmentTape =>
                 extract(mem_cast_list_commitment_tape, &looking_table),
             TableKind::SelfProgIdTape => extract(mem_self_prog_id_tape, &looking_table),
+            TableKind::HintTape => extract(mem_hint_tape, &looking_table),
+            TableKind::EventEmission => extract(event_emission, &looking_table),
             TableKind::RegisterInit => extract(reg_init, &looking_table),
+            #[cfg(feature = "poseidon2")]
             TableKind::Poseidon2Sponge => extract(poseidon2_sponge, &looking_table),
+            #[cfg(feature = "poseidon2")]
+            TableKind::OutputTape => extract(mem_output_tape, &looking_table),
+            #[cfg(feature = "secp256k1")]
+            TableKind::Secp256k1 => extract(secp256k1, &looking_table),
+            #[cfg(feature = "u256")]
+            TableKind::U256 => extract(u256, &looking_table),
+            #[cfg(feature = "memcopy")]
+            TableKind::MemCopy => extract(memcopy, &looking_table),
+            #[cfg(feature = "goldilocks")]
+            TableKind::Goldilocks => extract(goldilocks, &looking_table),
             // We are trying to build the Register tables, so we don't have the values to extract.
             TableKind::Register | TableKind::RegisterZeroRead | TableKind::RegisterZeroWrite =>
                 vec![],
@@ -138,7 +168,7 @@ pub fn generate_register_trace<F: RichField>(
         .map(RegisterZeroWrite::from)
         .collect();
 
-    log::trace!("trace for general registers {:?}", general);
+    tracing::trace!("trace for general registers {:?}", general);
     let last = *general.last().unwrap();
     (
         pad_trace_with_default(zeros_read),
@@ -174,19 +204,29 @@ pub fn generate_register_init_trace<F: RichField>(
 #[cfg(test)]
 mod tests {
     use mozak_runner::code;
-    use mozak_runner::instruction::{Args, Instruction, Op};
+    use mozak_runner::instruction::Instruction;
     use plonky2::field::goldilocks_field::GoldilocksField;
     use plonky2::field::types::Field;
 
     use super::*;
     use crate::cpu::generation::generate_cpu_trace;
+    use crate::event_emission::generation::generate_event_emission_trace;
+    #[cfg(feature = "goldilocks")]
+    use crate::goldilocks::generation::generate_goldilocks_trace;
+    #[cfg(feature = "memcopy")]
+    use crate::memcopy::generation::generate_memcopy_trace;
+    #[cfg(feature = "poseidon2")]
     use crate::poseidon2_sponge;
+    #[cfg(feature = "secp256k1")]
+    use crate::secp256k1::generation::generate_secp256k1_trace;
     use crate::storage_device::generation::{
         generate_call_tape_trace, generate_cast_list_commitment_tape_trace,
-        generate_event_tape_trace, generate_events_commitment_tape_trace,
+        generate_event_tape_trace, generate_events_commitment_tape_trace, generate_hint_tape_trace,
         generate_private_tape_trace, generate_public_tape_trace, generate_self_prog_id_tape_trace,
     };
     use crate::test_utils::prep_table;
+    #[cfg(feature = "u256")]
+    use crate::u256::generation::generate_u256_trace;
 
     type F = GoldilocksField;
 
@@ -194,24 +234,9 @@ mod tests {
         // Use same instructions as in the Notion document, see:
         // https://www.notion.so/0xmozak/Register-File-STARK-62459d68aea648a0abf4e97aa0093ea2?pvs=4#0729f89ddc724967ac991c9e299cc4fc
         let instructions = [
-            Instruction::new(Op::ADD, Args {
-                rs1: 6,
-                rs2: 7,
-                rd: 4,
-                ..Args::default()
-            }),
-            Instruction::new(Op::ADD, Args {
-                rs1: 4,
-                rs2: 6,
-                rd: 5,
-                ..Args::default()
-            }),
-            Instruction::new(Op::ADD, Args {
-                rs1: 5,
-                rd: 4,
-                imm: 100,
-                ..Args::default()
-            }),
+            Instruction::add(4, 6, 7),
+            Instruction::add(5, 4, 6),
+            Instruction::addi(4, 5, 100),
         ];
 
         code::execute(instructions, &[], &[(6, 100), (7, 200)]).1
@@ -232,6 +257,17 @@ mod tests {
         let cast_list_commitment_tape_rows =
             generate_cast_list_commitment_tape_trace(&record.executed);
         let self_prog_id_tape_rows = generate_self_prog_id_tape_trace(&record.executed);
+        let hint_tape_rows = generate_hint_tape_trace(&record.executed);
+        let event_emission_rows = generate_event_emission_trace(&record.executed);
+        #[cfg(feature = "secp256k1")]
+        let secp256k1_rows = generate_secp256k1_trace(&record.executed);
+        #[cfg(feature = "u256")]
+        let u256_rows = generate_u256_trace(&record.executed);
+        #[cfg(feature = "memcopy")]
+        let memcopy_rows = generate_memcopy_trace(&record.executed);
+        #[cfg(feature = "goldilocks")]
+        let goldilocks_rows = generate_goldilocks_trace(&record.executed);
+        #[cfg(feature = "poseidon2")]
         let poseidon2_sponge_trace =
             poseidon2_sponge::generation::generate_poseidon2_sponge_trace(&record.executed);
 
@@ -240,7 +276,18 @@ mod tests {
             &cpu_rows,
             &add_rows,
             &blt_rows,
+            #[cfg(feature = "poseidon2")]
             &poseidon2_sponge_trace,
+            #[cfg(feature = "poseidon2")]
+            &[],
+            #[cfg(feature = "secp256k1")]
+            &secp256k1_rows,
+            #[cfg(feature = "u256")]
+            &u256_rows,
+            #[cfg(feature = "memcopy")]
+            &memcopy_rows,
+            #[cfg(feature = "goldilocks")]
+            &goldilocks_rows,
             &private_tape,
             &public_tape,
             &call_tape,
@@ -248,6 +295,8 @@ mod tests {
             &events_commitment_tape_rows,
             &cast_list_commitment_tape_rows,
             &self_prog_id_tape_rows,
+            &hint_tape_rows,
+            &event_emission_rows,
             &register_init,
         );
 
@@ -1,13 +1,12 @@
-use crate::columns_view::{columns_view_impl, make_col_map};
+use crate::columns_view::{make_col_map, ColumnsView};
 use crate::linear_combination::Column;
 use crate::linear_combination_typed::ColumnWithTypedInput;
 use crate::register::RegisterCtl;
 use crate::stark::mozak_stark::{RegisterInitTable, TableWithTypedOutput};
 
-columns_view_impl!(RegisterInit);
 make_col_map!(RegisterInit);
 #[repr(C)]
-#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
 pub struct RegisterInit<T> {
     /// The 'address' that indexes into 1 of our 32 registers. Should only
     /// take values 0-31, so this column should be a running sum
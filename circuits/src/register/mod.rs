@@ -6,17 +6,16 @@
 //! This implementation is very similar to that of the
 //! [Memory STARK](crate::memory)
 
-use crate::columns_view::columns_view_impl;
+use crate::columns_view::ColumnsView;
 pub mod general;
 pub mod generation;
 pub mod init;
 pub mod zero_read;
 pub mod zero_write;
 
-columns_view_impl!(RegisterCtl);
 #[allow(clippy::module_name_repetitions)]
 #[repr(C)]
-#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
 pub struct RegisterCtl<T> {
     pub clk: T,
     pub op: T,
@@ -1,16 +1,15 @@
 use plonky2::hash::hash_types::RichField;
 
-use crate::columns_view::{columns_view_impl, make_col_map};
+use crate::columns_view::{make_col_map, ColumnsView};
 use crate::linear_combination::Column;
 use crate::linear_combination_typed::ColumnWithTypedInput;
 use crate::register::general::columns::Register;
 use crate::register::RegisterCtl;
 use crate::stark::mozak_stark::{RegisterZeroWriteTable, TableWithTypedOutput};
 
-columns_view_impl!(RegisterZeroWrite);
 make_col_map!(RegisterZeroWrite);
 #[repr(C)]
-#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
 /// The columns of the register 0 table.
 /// Register 0 is a special register that is always 0.
 /// Thus we don't need neither a value column nor a register address column.
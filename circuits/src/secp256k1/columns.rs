@@ -0,0 +1,185 @@
+use core::ops::Add;
+
+use itertools::{chain, izip};
+use mozak_sdk::core::reg_abi::{REG_A1, REG_A2};
+
+use crate::columns_view::{make_col_map, ColumnsView, NumberOfColumns};
+use crate::cross_table_lookup::ColumnWithTypedInput;
+use crate::linear_combination::Column;
+use crate::memory::columns::MemoryCtl;
+use crate::rangecheck::columns::RangeCheckCtl;
+use crate::register::RegisterCtl;
+use crate::stark::mozak_stark::{Secp256k1Table, TableWithTypedOutput};
+
+pub const COORDINATE_BYTES: usize = 32;
+/// Coordinates are multiplied as polynomials in 16-bit limbs.
+pub const COORDINATE_LIMBS: usize = COORDINATE_BYTES / 2;
+/// Quotients by the prime stay below `2^261`, see [`super::field`].
+pub const QUOTIENT_BYTES: usize = 34;
+/// One carry between each pair of adjacent limbs of a product.
+pub const PRODUCT_CARRIES: usize = 2 * COORDINATE_LIMBS - 1;
+/// Carries can be negative, so we range check them with this offset.
+pub const CARRY_OFFSET: i64 = 1 << 31;
+
+/// Affine coordinates, little-endian bytes each.
+#[repr(C)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
+pub struct Point<T> {
+    pub x: [T; COORDINATE_BYTES],
+    pub y: [T; COORDINATE_BYTES],
+}
+
+/// Witness that an identity in the coordinates holds modulo the prime: the
+/// identity plus a fixed multiple of the prime equals `quotient` times the
+/// prime, limb by limb up to `carries`.
+#[repr(C)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
+pub struct Reduction<T> {
+    pub quotient: [T; QUOTIENT_BYTES],
+    pub carries: [T; PRODUCT_CARRIES],
+}
+
+/// Witness that a coordinate is below the prime: it adds up with `slack` to
+/// the prime minus one.
+#[repr(C)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
+pub struct Canonical<T> {
+    pub slack: [T; COORDINATE_BYTES],
+    pub carries: [T; COORDINATE_LIMBS - 1],
+}
+
+make_col_map!(Secp256k1);
+/// A row adds `q` to `p`, or doubles `p`, and stores the `result` over `p`.
+#[repr(C)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
+pub struct Secp256k1<T> {
+    pub is_add: T,
+    pub is_double: T,
+    pub clk: T,
+    pub p_addr: T,
+    pub q_addr: T,
+    pub p: Point<T>,
+    /// Equal to `p` when doubling.
+    pub q: Point<T>,
+    pub result: Point<T>,
+    /// Slope of the line through `p` and `q`, or of the tangent at `p`.
+    pub slope: [T; COORDINATE_BYTES],
+    /// Inverse of the denominator of the slope, which shows that the slope
+    /// is defined.
+    pub inverse: [T; COORDINATE_BYTES],
+    pub inverse_reduction: Reduction<T>,
+    pub slope_reduction: Reduction<T>,
+    pub x_reduction: Reduction<T>,
+    pub y_reduction: Reduction<T>,
+    pub x_canonical: Canonical<T>,
+    pub y_canonical: Canonical<T>,
+}
+
+pub const NUM_SECP256K1_COLS: usize = Secp256k1::<()>::NUMBER_OF_COLUMNS;
+
+impl<T: Copy + Add<Output = T>> Secp256k1<T> {
+    pub fn is_executed(&self) -> T { self.is_add + self.is_double }
+
+    pub fn reductions(&self) -> [Reduction<T>; 4] {
+        [
+            self.inverse_reduction,
+            self.slope_reduction,
+            self.x_reduction,
+            self.y_reduction,
+        ]
+    }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
+pub struct Secp256k1Ctl<T> {
+    pub clk: T,
+    pub is_double: T,
+}
+
+#[must_use]
+pub fn lookup_for_cpu() -> TableWithTypedOutput<Secp256k1Ctl<Column>> {
+    Secp256k1Table::new(
+        Secp256k1Ctl {
+            clk: COL_MAP.clk,
+            is_double: COL_MAP.is_double,
+        },
+        COL_MAP.is_executed(),
+    )
+}
+
+#[must_use]
+pub fn register_looking() -> Vec<TableWithTypedOutput<RegisterCtl<Column>>> {
+    let is_read = ColumnWithTypedInput::constant(1);
+    vec![
+        Secp256k1Table::new(
+            RegisterCtl {
+                clk: COL_MAP.clk,
+                op: is_read,
+                value: COL_MAP.p_addr,
+                addr: ColumnWithTypedInput::constant(REG_A1.into()),
+            },
+            COL_MAP.is_executed(),
+        ),
+        Secp256k1Table::new(
+            RegisterCtl {
+                clk: COL_MAP.clk,
+                op: is_read,
+                value: COL_MAP.q_addr,
+                addr: ColumnWithTypedInput::constant(REG_A2.into()),
+            },
+            COL_MAP.is_add,
+        ),
+    ]
+}
+
+/// Loads of `p` and `q`, and the store of the result over `p`. Loads come
+/// before stores in the same cycle, so they see the old `p`.
+pub fn lookup_for_memory() -> impl Iterator<Item = TableWithTypedOutput<MemoryCtl<Column>>> {
+    let access = |addr, point: Point<_>, is_store, filter| {
+        izip!(0.., chain!(point.x, point.y)).map(move |(i, value)| {
+            Secp256k1Table::new(
+                MemoryCtl {
+                    clk: COL_MAP.clk,
+                    is_store: ColumnWithTypedInput::constant(is_store),
+                    is_load: ColumnWithTypedInput::constant(1 - is_store),
+                    value,
+                    addr: addr + i,
+                },
+                filter,
+            )
+        })
+    };
+    chain!(
+        access(COL_MAP.p_addr, COL_MAP.p, 0, COL_MAP.is_executed()),
+        access(COL_MAP.q_addr, COL_MAP.q, 0, COL_MAP.is_add),
+        access(COL_MAP.p_addr, COL_MAP.result, 1, COL_MAP.is_executed()),
+    )
+}
+
+/// The coordinates of `p`, `q` and the result are range checked by the memory
+/// table, so only the witnesses are left.
+#[must_use]
+pub fn rangecheck_u8_looking() -> Vec<TableWithTypedOutput<RangeCheckCtl<Column>>> {
+    chain!(
+        COL_MAP.slope,
+        COL_MAP.inverse,
+        COL_MAP.reductions().into_iter().flat_map(|r| r.quotient),
+        COL_MAP.x_canonical.slack,
+        COL_MAP.y_canonical.slack,
+    )
+    .map(|byte| Secp256k1Table::new(RangeCheckCtl(byte), COL_MAP.is_executed()))
+    .collect()
+}
+
+#[must_use]
+pub fn rangecheck_looking() -> Vec<TableWithTypedOutput<RangeCheckCtl<Column>>> {
+    COL_MAP
+        .reductions()
+        .into_iter()
+        .flat_map(|r| r.carries)
+        .map(|carry| {
+            Secp256k1Table::new(RangeCheckCtl(carry + CARRY_OFFSET), COL_MAP.is_executed())
+        })
+        .collect()
+}
@@ -0,0 +1,70 @@
+//! Arithmetic modulo the secp256k1 prime `p`, which does not fit into our
+//! field.
+//!
+//! Numbers are in 16-bit limbs, see [`crate::limbs`]. We show that an
+//! identity `f` holds modulo `p` by exhibiting a quotient `q` with
+//! `f + 2^260 p = q p`. Multiplying out `f + 2^260 p - q p` gives 32 limbs
+//! of less than `2^40` in absolute value, which vanish at `2^16` exactly when
+//! the identity holds over the integers, as long as the carries are range
+//! checked.
+//!
+//! Our identities stay below `2^515` in absolute value, so adding `2^260 p`
+//! keeps their quotients positive and below `2^261`.
+
+use itertools::chain;
+
+use super::columns::{Secp256k1, COORDINATE_LIMBS};
+use crate::limbs::{add, limbs, mul, scale, sub, Limb};
+
+/// Limbs of `p`, least significant first.
+pub const PRIME_LIMBS: [i64; COORDINATE_LIMBS] = [
+    0xFC2F, 0xFFFF, 0xFFFE, 0xFFFF, 0xFFFF, 0xFFFF, 0xFFFF, 0xFFFF, 0xFFFF, 0xFFFF, 0xFFFF, 0xFFFF,
+    0xFFFF, 0xFFFF, 0xFFFF, 0xFFFF,
+];
+
+/// The four identities a row has to satisfy modulo `p`, with `D` and `N` the
+/// denominator and numerator of the slope `L`, and `T` the inverse of `D`:
+/// - `T D = 1`,
+/// - `L D = N`,
+/// - `L^2 = x1 + x2 + x3`, and
+/// - `L (x1 - x3) = y1 + y3`.
+///
+/// They are in the order of [`Secp256k1::reductions`]. On padding rows all of
+/// them are zero.
+pub fn identities<E: Limb>(row: &Secp256k1<E>) -> [Vec<E>; 4] {
+    let (x1, y1) = (limbs(&row.p.x), limbs(&row.p.y));
+    let (x2, y2) = (limbs(&row.q.x), limbs(&row.q.y));
+    let (x3, y3) = (limbs(&row.result.x), limbs(&row.result.y));
+    let slope = limbs(&row.slope);
+    let inverse = limbs(&row.inverse);
+
+    let denominator = add(
+        &scale(&sub(&x2, &x1), row.is_add),
+        &scale(&y1, row.is_double * E::from(2)),
+    );
+    let numerator = add(
+        &scale(&sub(&y2, &y1), row.is_add),
+        &scale(&mul(&x1, &x1), row.is_double * E::from(3)),
+    );
+    [
+        sub(&mul(&inverse, &denominator), &[row.is_executed()]),
+        sub(&mul(&slope, &denominator), &numerator),
+        sub(&sub(&sub(&mul(&slope, &slope), &x1), &x2), &x3),
+        sub(&sub(&mul(&slope, &sub(&x1, &x3)), &y1), &y3),
+    ]
+}
+
+/// Limbs of `identity + 2^260 p - quotient p`, which vanish at `2^16` when
+/// the identity holds modulo `p`.
+pub fn reduce<E: Limb>(identity: &[E], quotient_bytes: &[E], is_executed: E) -> Vec<E> {
+    let prime = PRIME_LIMBS.map(E::from);
+    let offset: Vec<E> = chain!(
+        [E::from(0); COORDINATE_LIMBS],
+        prime.map(|limb| limb * E::from(16) * is_executed),
+    )
+    .collect();
+    sub(
+        &add(identity, &offset),
+        &mul(&limbs(quotient_bytes), &prime),
+    )
+}
@@ -0,0 +1,95 @@
+use itertools::izip;
+use mozak_runner::secp256k1::{inverse, modulus, slope_fraction, Entry};
+use mozak_runner::vm::Row;
+use num::bigint::{BigInt, BigUint, Sign};
+use num::{Integer, Zero};
+use plonky2::hash::hash_types::RichField;
+
+use super::columns::{Canonical, Point, Reduction, Secp256k1, COORDINATE_BYTES, QUOTIENT_BYTES};
+use super::field::{identities, reduce, PRIME_LIMBS};
+use crate::limbs::{add, carries, limbs, sub, to_bytes};
+use crate::utils::pad_trace_with_default;
+
+fn to_point<F: RichField>(point: &[u8]) -> Point<F> {
+    let (x, y) = point.split_at(COORDINATE_BYTES);
+    Point {
+        x: core::array::from_fn(|i| F::from_canonical_u8(x[i])),
+        y: core::array::from_fn(|i| F::from_canonical_u8(y[i])),
+    }
+}
+
+fn reduction<F: RichField>(identity: &[i128]) -> Reduction<F> {
+    let value = izip!(0.., identity).fold(BigInt::zero(), |sum, (i, &limb)| {
+        sum + (BigInt::from(limb) << (16 * i))
+    });
+    let modulus = BigInt::from(modulus());
+    let (quotient, remainder) = (value + (&modulus << 260)).div_rem(&modulus);
+    assert!(remainder.is_zero(), "identity does not hold modulo p");
+    let (Sign::Plus, quotient) = quotient.into_parts() else {
+        panic!("quotient is not positive")
+    };
+    let quotient: [F; QUOTIENT_BYTES] = to_bytes(&quotient);
+    let quotient_limbs = quotient.map(|byte| i128::from(byte.to_canonical_u64()));
+    Reduction {
+        quotient,
+        carries: carries(&reduce(identity, &quotient_limbs, 1))
+            .try_into()
+            .unwrap(),
+    }
+}
+
+fn canonical<F: RichField>(coordinate: &[F; COORDINATE_BYTES]) -> Canonical<F> {
+    let value = BigUint::from_bytes_le(
+        &coordinate.map(|byte| u8::try_from(byte.to_canonical_u64()).expect("coordinate is bytes")),
+    );
+    let slack: [F; COORDINATE_BYTES] = to_bytes(&(modulus() - 1_u32 - value));
+    let to_i128 = |bytes: [F; COORDINATE_BYTES]| bytes.map(|b| i128::from(b.to_canonical_u64()));
+    let mut prime_minus_one = PRIME_LIMBS.map(i128::from);
+    prime_minus_one[0] -= 1;
+    let sum = sub(
+        &add(&limbs(&to_i128(*coordinate)), &limbs(&to_i128(slack))),
+        &prime_minus_one,
+    );
+    Canonical {
+        slack,
+        carries: carries(&sum).try_into().unwrap(),
+    }
+}
+
+fn generate_row<F: RichField>(clk: u64, entry: &Entry) -> Secp256k1<F> {
+    let (numerator, denominator) = slope_fraction(entry.is_double, &entry.p, &entry.q);
+    let inverse = inverse(&denominator);
+    let slope = numerator * &inverse % modulus();
+    let mut row = Secp256k1 {
+        is_add: F::from_bool(!entry.is_double),
+        is_double: F::from_bool(entry.is_double),
+        clk: F::from_canonical_u64(clk),
+        p_addr: F::from_canonical_u32(entry.p_addr),
+        q_addr: F::from_canonical_u32(entry.q_addr),
+        p: to_point(&entry.p),
+        q: to_point(&entry.q),
+        result: to_point(&entry.result),
+        slope: to_bytes(&slope),
+        inverse: to_bytes(&inverse),
+        ..Default::default()
+    };
+    let [inverse_identity, slope_identity, x_identity, y_identity] =
+        identities(&row.map(|f| i128::from(f.to_canonical_u64())));
+    row.inverse_reduction = reduction(&inverse_identity);
+    row.slope_reduction = reduction(&slope_identity);
+    row.x_reduction = reduction(&x_identity);
+    row.y_reduction = reduction(&y_identity);
+    row.x_canonical = canonical(&row.result.x);
+    row.y_canonical = canonical(&row.result.y);
+    row
+}
+
+#[must_use]
+pub fn generate_secp256k1_trace<F: RichField>(step_rows: &[Row<F>]) -> Vec<Secp256k1<F>> {
+    pad_trace_with_default(
+        step_rows
+            .iter()
+            .filter_map(|row| Some(generate_row(row.state.clk, row.aux.secp256k1.as_ref()?)))
+            .collect(),
+    )
+}
@@ -0,0 +1,4 @@
+pub mod columns;
+pub mod field;
+pub mod generation;
+pub mod stark;
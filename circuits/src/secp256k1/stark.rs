@@ -0,0 +1,170 @@
+use std::marker::PhantomData;
+
+use expr::{Expr, ExprBuilder, StarkFrameTyped};
+use itertools::izip;
+use mozak_circuits_derive::StarkNameDisplay;
+use plonky2::field::extension::{Extendable, FieldExtension};
+use plonky2::field::packed::PackedField;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::ext_target::ExtensionTarget;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use starky::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
+use starky::evaluation_frame::StarkFrame;
+use starky::stark::Stark;
+
+use super::columns::{Secp256k1, NUM_SECP256K1_COLS};
+use super::field::{identities, reduce, PRIME_LIMBS};
+use crate::columns_view::HasNamedColumns;
+use crate::expr::{build_ext, build_packed, ConstraintBuilder};
+use crate::limbs::{add, carry_constraints, limbs, scale, sub};
+use crate::unstark::NoColumns;
+
+#[derive(Copy, Clone, Default, StarkNameDisplay)]
+#[allow(clippy::module_name_repetitions)]
+pub struct Secp256k1Stark<F, const D: usize> {
+    pub _f: PhantomData<F>,
+}
+
+impl<F, const D: usize> HasNamedColumns for Secp256k1Stark<F, D> {
+    type Columns = Secp256k1<F>;
+}
+
+const COLUMNS: usize = NUM_SECP256K1_COLS;
+const PUBLIC_INPUTS: usize = 0;
+
+fn generate_constraints<'a, T: Copy>(
+    vars: &StarkFrameTyped<Secp256k1<Expr<'a, T>>, NoColumns<Expr<'a, T>>>,
+) -> ConstraintBuilder<Expr<'a, T>> {
+    let lv = vars.local_values;
+    let mut constraints = ConstraintBuilder::default();
+
+    constraints.always(lv.is_add.is_binary());
+    constraints.always(lv.is_double.is_binary());
+    constraints.always(lv.is_executed().is_binary());
+
+    // Doubling only loads `p`, so `q` has to repeat it.
+    for (&p, &q) in izip!(lv.p.iter(), lv.q.iter()) {
+        constraints.always(lv.is_double * (q - p));
+    }
+
+    for (identity, reduction) in izip!(identities(&lv), lv.reductions()) {
+        let reduced = reduce(&identity, &reduction.quotient, lv.is_executed());
+        for constraint in carry_constraints(&reduced, &reduction.carries) {
+            constraints.always(constraint);
+        }
+    }
+
+    // The result adds up with a non-negative slack to `p - 1`, so it is
+    // reduced. Limbs of the sum are below `2^17`, so the carries are bits.
+    let mut prime_minus_one = PRIME_LIMBS.map(Expr::from);
+    prime_minus_one[0] = prime_minus_one[0] - 1;
+    for (coordinate, canonical) in [(lv.result.x, lv.x_canonical), (lv.result.y, lv.y_canonical)] {
+        let sum = sub(
+            &add(&limbs(&coordinate), &limbs(&canonical.slack)),
+            &scale(&prime_minus_one, lv.is_executed()),
+        );
+        for constraint in carry_constraints(&sum, &canonical.carries) {
+            constraints.always(constraint);
+        }
+        for carry in canonical.carries {
+            constraints.always(carry.is_binary());
+        }
+    }
+
+    constraints
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for Secp256k1Stark<F, D> {
+    type EvaluationFrame<FE, P, const D2: usize> = StarkFrame<P, P::Scalar, COLUMNS, PUBLIC_INPUTS>
+    where
+        FE: FieldExtension<D2, BaseField = F>,
+        P: PackedField<Scalar = FE>;
+    type EvaluationFrameTarget =
+        StarkFrame<ExtensionTarget<D>, ExtensionTarget<D>, COLUMNS, PUBLIC_INPUTS>;
+
+    fn eval_packed_generic<FE, P, const D2: usize>(
+        &self,
+        vars: &Self::EvaluationFrame<FE, P, D2>,
+        consumer: &mut ConstraintConsumer<P>,
+    ) where
+        FE: FieldExtension<D2, BaseField = F>,
+        P: PackedField<Scalar = FE>, {
+        let eb = ExprBuilder::default();
+        let constraints = generate_constraints(&eb.to_typed_starkframe(vars));
+        build_packed(constraints, consumer);
+    }
+
+    fn eval_ext_circuit(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        vars: &Self::EvaluationFrameTarget,
+        consumer: &mut RecursiveConstraintConsumer<F, D>,
+    ) {
+        let eb = ExprBuilder::default();
+        let constraints = generate_constraints(&eb.to_typed_starkframe(vars));
+        build_ext(constraints, builder, consumer);
+    }
+
+    fn constraint_degree(&self) -> usize { 3 }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use plonky2::field::types::Field;
+    use plonky2::plonk::config::{GenericConfig, Poseidon2GoldilocksConfig};
+    use plonky2::util::timing::TimingTree;
+    use starky::prover::prove;
+    use starky::stark_testing::{test_stark_circuit_constraints, test_stark_low_degree};
+    use starky::verifier::verify_stark_proof;
+
+    use super::Secp256k1Stark;
+    use crate::generation::debug_single_trace;
+    use crate::secp256k1::generation::generate_secp256k1_trace;
+    use crate::stark::mozak_stark::MozakStark;
+    use crate::stark::utils::trace_rows_to_poly_values;
+    use crate::test_utils::{fast_test_config, secp256k1_test, ProveAndVerify};
+
+    const D: usize = 2;
+    type C = Poseidon2GoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+    type S = Secp256k1Stark<F, D>;
+
+    #[test]
+    fn prove_add_and_double() -> Result<()> {
+        let (_program, record) = secp256k1_test();
+        let config = fast_test_config();
+        let stark = S::default();
+        let trace = trace_rows_to_poly_values(generate_secp256k1_trace(&record.executed));
+        let proof = prove::<F, C, S, D>(stark, &config, trace, &[], &mut TimingTree::default())?;
+        verify_stark_proof(stark, proof, &config)
+    }
+
+    #[test]
+    #[should_panic(expected = "debug_api_has_constraint_failed")]
+    fn wrong_result_fails() {
+        let (_program, record) = secp256k1_test();
+        let mut rows = generate_secp256k1_trace(&record.executed);
+        rows[0].result.y[0] += F::ONE;
+        debug_single_trace::<F, D, _>(&S::default(), &trace_rows_to_poly_values(rows), &[]);
+    }
+
+    #[test]
+    fn prove_add_and_double_with_mozak_stark() {
+        let (program, record) = secp256k1_test();
+        MozakStark::prove_and_verify(&program, &record).unwrap();
+    }
+
+    #[test]
+    fn test_degree() -> Result<()> {
+        let stark = S::default();
+        test_stark_low_degree(stark)
+    }
+
+    #[test]
+    fn test_circuit() -> Result<()> {
+        let stark = S::default();
+        test_stark_circuit_constraints::<F, C, S, D>(stark)?;
+        Ok(())
+    }
+}
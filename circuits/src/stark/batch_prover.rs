@@ -1,11 +1,12 @@
 #![allow(clippy::too_many_lines)]
 
+use std::borrow::Borrow;
 use std::collections::HashMap;
 
 use anyhow::{ensure, Result};
 use itertools::{chain, Itertools};
+use log::log_enabled;
 use log::Level::Debug;
-use log::{debug, log_enabled};
 use mozak_runner::elf::Program;
 use mozak_runner::vm::ExecutionRecord;
 use plonky2::batch_fri::oracle::BatchFriOracle;
@@ -23,7 +24,7 @@ use plonky2::hash::merkle_tree::MerkleCap;
 use plonky2::iop::challenger::Challenger;
 use plonky2::iop::ext_target::ExtensionTarget;
 use plonky2::plonk::circuit_builder::CircuitBuilder;
-use plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
+use plonky2::plonk::config::GenericConfig;
 use plonky2::timed;
 use plonky2::util::log2_strict;
 use plonky2::util::timing::TimingTree;
@@ -31,6 +32,7 @@ use plonky2::util::timing::TimingTree;
 use plonky2_maybe_rayon::*;
 use starky::config::StarkConfig;
 use starky::stark::{LookupConfig, Stark};
+use tracing::debug;
 
 use super::mozak_stark::{MozakStark, TableKind, TableKindArray, TableKindSetBuilder};
 use super::proof::{BatchProof, StarkOpeningSet, StarkProof};
@@ -42,6 +44,8 @@ use crate::stark::mozak_stark::{all_kind, all_starks, PublicInputs};
 use crate::stark::permutation::challenge::GrandProductChallengeTrait;
 use crate::stark::poly::compute_quotient_polys;
 use crate::stark::prover::{get_program_id, prove_single_table};
+use crate::stark::transcript::Transcript;
+use crate::trace_source::Execution;
 
 const ORACLE_COUNT: usize = 3;
 const BATCH_COUNT: usize = 3;
@@ -362,10 +366,9 @@ pub fn batch_prove<F, C, const D: usize>(
 ) -> Result<(BatchProof<F, C, D>, TableKindArray<usize>)>
 where
     F: RichField + Extendable<D>,
-    C: GenericConfig<D, F = F>,
-    <C as GenericConfig<D>>::Hasher: AlgebraicHasher<F>, {
+    C: GenericConfig<D, F = F>, {
     debug!("Starting Prove");
-    let traces_poly_values = generate_traces(program, record, timing);
+    let traces_poly_values = generate_traces(&Execution::new(program, record), timing);
     if mozak_stark.debug || std::env::var("MOZAK_STARK_DEBUG").is_ok() {
         debug_traces(&traces_poly_values, mozak_stark, &public_inputs);
         debug_ctl(&traces_poly_values, mozak_stark);
@@ -486,6 +489,12 @@ where
         trace_caps[TableKind::ElfMemoryInit]
             .as_ref()
             .expect("elf memory ini trace cap not found"),
+        trace_caps[TableKind::LibraryProgram]
+            .as_ref()
+            .expect("library program trace cap not found"),
+        trace_caps[TableKind::LibraryElfMemoryInit]
+            .as_ref()
+            .expect("library elf memory init trace cap not found"),
     );
 
     if log_enabled!(Debug) {
@@ -531,9 +540,8 @@ where
     let cap_height = config.fri_config.cap_height;
 
     // TODO(Matthias): Unify everything in this function with the non-batch version.
-    let cpu_skeleton_stark = [public_inputs.entry_point];
     let public_inputs = TableKindSetBuilder::<&[_]> {
-        cpu_skeleton_stark: &cpu_skeleton_stark,
+        cpu_skeleton_stark: public_inputs.borrow(),
         ..Default::default()
     }
     .build();
@@ -546,10 +554,14 @@ where
             stark,
             config,
             trace_commitment,
+            // Batch proofs commit to the fixed columns along with the others.
+            None,
             public_inputs[kind],
             &ctl_data_per_table[kind],
             &public_sub_data_per_table[kind],
             challenger,
+            // Batch proofs do not record their transcript.
+            &Transcript::default(),
             timing,
         )?)
     } else {
@@ -883,7 +895,7 @@ pub(crate) fn batch_reduction_arity_bits(
 #[cfg(test)]
 mod tests {
     use mozak_runner::code;
-    use mozak_runner::instruction::{Args, Instruction, Op};
+    use mozak_runner::instruction::Instruction;
     use plonky2::plonk::config::{GenericConfig, PoseidonGoldilocksConfig};
     use plonky2::util::timing::TimingTree;
 
@@ -928,25 +940,11 @@ mod tests {
         type C = PoseidonGoldilocksConfig;
         type F = <C as GenericConfig<D>>::F;
 
-        let (program, record) = code::execute(
-            [Instruction {
-                op: Op::ADD,
-                args: Args {
-                    rd: 5,
-                    rs1: 6,
-                    rs2: 7,
-                    ..Args::default()
-                },
-            }],
-            &[],
-            &[(6, 3), (7, 4)],
-        );
+        let (program, record) = code::execute([Instruction::add(5, 6, 7)], &[], &[(6, 3), (7, 4)]);
         let config = fast_test_config();
 
         let stark: MozakStark<F, D> = MozakStark::default();
-        let public_inputs = PublicInputs {
-            entry_point: from_u32(program.entry_point),
-        };
+        let public_inputs = PublicInputs::new(from_u32(program.entry_point));
 
         let (all_proof, degree_bits) = batch_prove::<F, C, D>(
             &program,
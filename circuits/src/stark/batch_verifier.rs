@@ -1,28 +1,32 @@
 use std::borrow::Borrow;
 
 use anyhow::{ensure, Result};
-use log::debug;
 use plonky2::batch_fri::verifier::verify_batch_fri_proof;
 use plonky2::field::extension::Extendable;
 use plonky2::fri::proof::FriProof;
 use plonky2::fri::structure::{FriOpeningBatch, FriOpenings};
 use plonky2::hash::hash_types::RichField;
 use plonky2::iop::challenger::Challenger;
-use plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
+use plonky2::plonk::config::GenericConfig;
 use starky::config::StarkConfig;
+use tracing::debug;
 
 use super::mozak_stark::{
     all_kind, all_starks, MozakStark, TableKind, TableKindArray, TableKindSetBuilder,
 };
 use crate::cross_table_lookup::{verify_cross_table_lookups_and_public_sub_tables, CtlCheckVars};
-use crate::public_sub_table::reduce_public_sub_tables_values;
+use crate::public_sub_table::{
+    check_public_sub_table_values_shape, reduce_public_sub_tables_values,
+};
 use crate::stark::batch_prover::{
     batch_fri_instances, batch_reduction_arity_bits, sort_degree_bits,
 };
 use crate::stark::permutation::challenge::GrandProductChallengeTrait;
 use crate::stark::proof::{BatchProof, StarkProof, StarkProofChallenges};
 use crate::stark::prover::get_program_id;
+use crate::stark::transcript::Transcript;
 use crate::stark::verifier::{verify_quotient_polynomials, verify_stark_proof_with_challenges};
+use crate::tape_commitments::columns::check_self_program_id;
 
 #[allow(clippy::too_many_lines)]
 pub fn batch_verify_proof<F, C, const D: usize>(
@@ -34,8 +38,7 @@ pub fn batch_verify_proof<F, C, const D: usize>(
 ) -> Result<()>
 where
     F: RichField + Extendable<D>,
-    C: GenericConfig<D, F = F>,
-    <C as GenericConfig<D>>::Hasher: AlgebraicHasher<F>, {
+    C: GenericConfig<D, F = F>, {
     debug!("Starting Batch Verify");
 
     let sorted_degree_bits = sort_degree_bits(public_table_kinds, degree_bits);
@@ -55,7 +58,12 @@ where
     let stark_challenges = all_kind!(|kind| {
         if public_table_kinds.contains(&kind) {
             challenger.compact();
-            Some(all_proof.proofs[kind].get_challenges(&mut challenger, config))
+            // Batch proofs do not record their transcript.
+            Some(all_proof.proofs[kind].get_challenges(
+                &mut challenger,
+                config,
+                &Transcript::default(),
+            ))
         } else {
             None
         }
@@ -109,6 +117,10 @@ where
         &ctl_challenges,
     );
 
+    check_public_sub_table_values_shape(
+        &mozak_stark.public_sub_tables,
+        &all_proof.public_sub_table_values,
+    )?;
     let reduced_public_sub_tables_values =
         reduce_public_sub_tables_values(&all_proof.public_sub_table_values, &ctl_challenges);
 
@@ -122,8 +134,15 @@ where
         all_proof.public_inputs.entry_point,
         &all_proof.proofs[TableKind::Program].trace_cap,
         &all_proof.proofs[TableKind::ElfMemoryInit].trace_cap,
+        &all_proof.proofs[TableKind::LibraryProgram].trace_cap,
+        &all_proof.proofs[TableKind::LibraryElfMemoryInit].trace_cap,
     );
     ensure!(program_id == all_proof.program_id);
+    check_self_program_id(
+        &mozak_stark.public_sub_tables,
+        &all_proof.public_sub_table_values,
+        program_id,
+    )?;
 
     all_starks!(mozak_stark, |stark, kind| {
         if public_table_kinds.contains(&kind) {
@@ -131,7 +150,9 @@ where
                 // Verifying public tables proof, including individual FRI proof
                 verify_stark_proof_with_challenges(
                     stark,
+                    kind,
                     &all_proof.proofs[kind],
+                    None,
                     challenges,
                     public_inputs[kind],
                     &ctl_vars_per_table[kind],
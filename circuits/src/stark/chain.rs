@@ -0,0 +1,192 @@
+//! Proof-carrying data over VM proofs.
+//!
+//! [`VMProofChain`] folds a sequence of [`AllProof`]s, e.g. consecutive state
+//! transitions of a rollup, into a single chain proof. Each `AllProof` is
+//! recursively verified and shrunk to [`VM_RECURSION_THRESHOLD_DEGREE_BITS`],
+//! so that every link has the same shape, and then appended to the chain with
+//! a [`verify_chain::Circuit`].
+//!
+//! The chain proof exposes a digest of all links in order. It can be
+//! recomputed from the links' verifier keys and public inputs with
+//! [`link_digest`].
+
+use anyhow::{bail, ensure, Result};
+use mozak_recproofs::circuits::verify_chain;
+pub use mozak_recproofs::circuits::verify_chain::core::link_digest;
+use plonky2::field::extension::Extendable;
+use plonky2::hash::hash_types::{HashOut, RichField};
+use plonky2::plonk::circuit_data::VerifierOnlyCircuitData;
+use plonky2::plonk::config::{AlgebraicHasher, GenericConfig, Hasher};
+use plonky2::plonk::proof::ProofWithPublicInputs;
+use starky::config::StarkConfig;
+
+use super::mozak_stark::MozakStark;
+use super::proof::AllProof;
+use super::recursive_verifier::{
+    circuit_data_for_recursion, recursive_mozak_stark_circuit,
+    shrink_to_target_degree_bits_circuit, VM_PUBLIC_INPUT_SIZE, VM_RECURSION_CONFIG,
+    VM_RECURSION_THRESHOLD_DEGREE_BITS,
+};
+
+/// Degree bits of chain proofs. A chain proof verifies a shrunk VM proof and
+/// the previous chain proof, which does not fit under plonky2's recursion
+/// threshold.
+pub const CHAIN_RECURSION_DEGREE_BITS: usize = 14;
+
+pub type ChainProof<F, C, const D: usize> = verify_chain::LinkProof<F, C, D>;
+
+/// Represents a circuit which chains recursive VM proofs.
+pub struct VMProofChain<F, C, const D: usize>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>, {
+    pub chain: verify_chain::Circuit<F, C, D>,
+}
+
+impl<F, C, const D: usize> Default for VMProofChain<F, C, D>
+where
+    F: RichField + Extendable<D>,
+    C: 'static + GenericConfig<D, F = F>,
+    C::Hasher: AlgebraicHasher<F> + Hasher<F, Hash = HashOut<F>>,
+{
+    fn default() -> Self { Self::new() }
+}
+
+impl<F, C, const D: usize> VMProofChain<F, C, D>
+where
+    F: RichField + Extendable<D>,
+    C: 'static + GenericConfig<D, F = F>,
+    C::Hasher: AlgebraicHasher<F> + Hasher<F, Hash = HashOut<F>>,
+{
+    #[must_use]
+    pub fn new() -> Self {
+        let link_common = circuit_data_for_recursion::<F, C, D>(
+            &VM_RECURSION_CONFIG,
+            VM_RECURSION_THRESHOLD_DEGREE_BITS,
+            VM_PUBLIC_INPUT_SIZE,
+        )
+        .common;
+        let chain = verify_chain::Circuit::new(
+            &VM_RECURSION_CONFIG,
+            CHAIN_RECURSION_DEGREE_BITS,
+            &link_common,
+        );
+        Self { chain }
+    }
+
+    /// Recursively verifies `all_proof`, and shrinks the result into a link
+    /// proof of the chain.
+    pub fn link(
+        mozak_stark: &MozakStark<F, D>,
+        all_proof: &AllProof<F, C, D>,
+        stark_config: &StarkConfig,
+    ) -> Result<(
+        VerifierOnlyCircuitData<C, D>,
+        ProofWithPublicInputs<F, C, D>,
+    )> {
+        let recursive_circuit = recursive_mozak_stark_circuit::<F, C, D>(
+            mozak_stark,
+            &all_proof.degree_bits(stark_config),
+            &VM_RECURSION_CONFIG,
+            stark_config,
+        );
+        let recursive_proof = recursive_circuit.prove(all_proof)?;
+        let (link_circuit, link_proof) = shrink_to_target_degree_bits_circuit(
+            &recursive_circuit.circuit.verifier_only,
+            &recursive_circuit.circuit.common,
+            &VM_RECURSION_CONFIG,
+            VM_RECURSION_THRESHOLD_DEGREE_BITS,
+            &recursive_proof,
+        )?;
+        Ok((link_circuit.circuit.verifier_only, link_proof))
+    }
+
+    /// Folds `all_proofs`, in order, into one chain proof.
+    pub fn fold(
+        &self,
+        mozak_stark: &MozakStark<F, D>,
+        all_proofs: &[AllProof<F, C, D>],
+        stark_config: &StarkConfig,
+    ) -> Result<ChainProof<F, C, D>> {
+        let Some((first, rest)) = all_proofs.split_first() else {
+            bail!("cannot fold an empty chain");
+        };
+
+        let base = self.chain.prove_base()?;
+        let (link_verifier, link_proof) = Self::link(mozak_stark, first, stark_config)?;
+        let mut chain_proof = self.chain.prove(&link_verifier, &link_proof, &base)?;
+        for all_proof in rest {
+            let (link_verifier, link_proof) = Self::link(mozak_stark, all_proof, stark_config)?;
+            chain_proof = self
+                .chain
+                .prove(&link_verifier, &link_proof, &chain_proof)?;
+        }
+        Ok(chain_proof)
+    }
+
+    pub fn verify(&self, chain_proof: ChainProof<F, C, D>) -> Result<()> {
+        ensure!(
+            chain_proof.verifier() == self.chain.circuit.verifier_only,
+            "chain proof was not produced by this chain circuit"
+        );
+        self.chain.verify(chain_proof)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use mozak_runner::code;
+    use mozak_runner::instruction::Instruction;
+    use plonky2::hash::hash_types::HashOut;
+    use plonky2::util::timing::TimingTree;
+    use starky::config::StarkConfig;
+
+    use super::{link_digest, VMProofChain};
+    use crate::stark::mozak_stark::{MozakStark, PublicInputs};
+    use crate::stark::prover::prove;
+    use crate::test_utils::{C, D, F};
+    use crate::utils::from_u32;
+
+    #[test]
+    #[ignore]
+    fn fold_three_vm_proofs() -> Result<()> {
+        let stark = MozakStark::default();
+        let config = StarkConfig::standard_fast_config();
+        let all_proofs = [100, 200, 300]
+            .into_iter()
+            .map(|x| {
+                let (program, record) =
+                    code::execute([Instruction::add(5, 6, 7)], &[], &[(6, x), (7, 1)]);
+                let public_inputs = PublicInputs::new(from_u32(program.entry_point));
+                prove::<F, C, D>(
+                    &program,
+                    &record,
+                    &stark,
+                    &config,
+                    public_inputs,
+                    &mut TimingTree::default(),
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let chain = VMProofChain::<F, C, D>::new();
+        let chain_proof = chain.fold(&stark, &all_proofs, &config)?;
+        assert_eq!(chain_proof.length(), 3);
+
+        let expected_digest = all_proofs
+            .iter()
+            .try_fold(HashOut::ZERO, |digest, all_proof| {
+                let (link_verifier, link_proof) =
+                    VMProofChain::<F, C, D>::link(&stark, all_proof, &config)?;
+                Ok::<_, anyhow::Error>(link_digest(
+                    digest,
+                    &link_verifier,
+                    &link_proof.public_inputs,
+                ))
+            })?;
+        assert_eq!(chain_proof.digest(), expected_digest);
+
+        chain.verify(chain_proof)
+    }
+}
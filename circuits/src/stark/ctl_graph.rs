@@ -0,0 +1,131 @@
+//! Describes the cross table lookups of a
+//! [`MozakStark`](crate::stark::mozak_stark::MozakStark) as a graph, with a
+//! node per table and an edge from each looking table to each table it looks
+//! up.
+//!
+//! [`to_dot`] renders the graph for graphviz, e.g.
+//! `mozak-cli ctl-graph --dot | dot -Tsvg > ctls.svg`.
+
+use std::fmt::{self, Display, Formatter, Write};
+
+use itertools::Itertools;
+
+use crate::cross_table_lookup::CrossTableLookup;
+use crate::stark::mozak_stark::{all_kind, TableKind};
+
+/// A looking table and a table it looks up, in cross table lookup `ctl`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CtlEdge {
+    /// Index of the lookup in `MozakStark::cross_table_lookups`.
+    pub ctl: usize,
+    pub looking: TableKind,
+    pub looked: TableKind,
+    /// Names of the looked up columns, see [`compact_column_names`].
+    pub columns: Vec<String>,
+}
+
+impl Display for CtlEdge {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?} -> {:?} (ctl {}): {}",
+            self.looking,
+            self.looked,
+            self.ctl,
+            self.columns.join(", ")
+        )
+    }
+}
+
+/// Collapses runs of array elements, eg `input[0], .., input[11]` into
+/// `input[12]`, so that labels stay readable.
+#[must_use]
+pub fn compact_column_names(names: &[String]) -> Vec<String> {
+    let element_of = |name: &str| {
+        let (array, index) = name.strip_suffix(']')?.rsplit_once('[')?;
+        index.parse::<usize>().ok().map(|_| array.to_owned())
+    };
+    names
+        .iter()
+        .map(|name| (element_of(name), name))
+        .chunk_by(|(array, _)| array.clone())
+        .into_iter()
+        .flat_map(|(array, group)| {
+            let group = group.map(|(_, name)| name.clone()).collect_vec();
+            match array {
+                Some(array) if group.len() > 1 => vec![format!("{array}[{}]", group.len())],
+                _ => group,
+            }
+        })
+        .collect()
+}
+
+/// Lists the edges of all `ctls`, once per pair of tables.
+#[must_use]
+pub fn ctl_edges(ctls: &[CrossTableLookup]) -> Vec<CtlEdge> {
+    ctls.iter()
+        .enumerate()
+        .flat_map(|(ctl, lookup)| {
+            let (looking, looked) = lookup.split_tables();
+            let columns = compact_column_names(&lookup.column_names);
+            looking
+                .iter()
+                .cartesian_product(looked)
+                .map(|(looking, looked)| (looking.kind, looked.kind))
+                .unique()
+                .map(move |(looking, looked)| CtlEdge {
+                    ctl,
+                    looking,
+                    looked,
+                    columns: columns.clone(),
+                })
+                .collect_vec()
+        })
+        .collect()
+}
+
+/// Renders `ctls` as a graphviz digraph, see the module docs.
+#[must_use]
+pub fn to_dot(ctls: &[CrossTableLookup]) -> String {
+    let mut dot = String::from("digraph ctls {\n    node [shape=box];\n");
+    for kind in all_kind!(|kind| kind).iter() {
+        writeln!(dot, "    \"{kind:?}\";").unwrap();
+    }
+    for edge in ctl_edges(ctls) {
+        writeln!(
+            dot,
+            "    \"{:?}\" -> \"{:?}\" [label=\"{}\"];",
+            edge.looking,
+            edge.looked,
+            edge.columns.join("\\n")
+        )
+        .unwrap();
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stark::mozak_stark::MozakStark;
+    use crate::test_utils::{D, F};
+
+    #[test]
+    fn compacts_arrays() {
+        let names = ["a", "input[0]", "input[1]", "b[0]", "c"].map(String::from);
+        assert_eq!(compact_column_names(&names), ["a", "input[2]", "b[0]", "c"]);
+    }
+
+    #[test]
+    fn cpu_looks_up_xor() {
+        let stark = MozakStark::<F, D>::default();
+        let edges = ctl_edges(&stark.cross_table_lookups);
+        let edge = edges
+            .iter()
+            .find(|edge| edge.looking == TableKind::Cpu && edge.looked == TableKind::Xor)
+            .unwrap();
+        assert_eq!(edge.columns, ["a", "b", "out"]);
+        assert!(to_dot(&stark.cross_table_lookups).contains("\"Cpu\" -> \"Xor\""));
+    }
+}
@@ -0,0 +1,136 @@
+//! Errors of the public [prover](super::prover) and
+//! [verifier](super::verifier) API.
+//!
+//! Internally we still use `anyhow`; its errors end up as the `source` of the
+//! variants below.
+
+use std::fmt;
+
+use mozak_sdk::core::trap::TrapReport;
+use thiserror::Error;
+
+use super::mozak_stark::TableKind;
+use super::security::ProofConfig;
+
+#[derive(Error, Debug)]
+pub enum ProverError {
+    #[error("failed to read the ELF")]
+    Io(#[from] std::io::Error),
+    #[error("failed to load the ELF")]
+    LoadElf(#[source] anyhow::Error),
+    #[error("execution failed")]
+    Execution(#[source] anyhow::Error),
+    #[error("guest trapped at clk {clk}: {report}")]
+    Trapped { clk: u64, report: TrapReport },
+    #[error(
+        "trace of {table:?} has 2^{degree_bits} rows, but at most 2^{max_degree_bits} fit the \
+         config"
+    )]
+    TraceTooLarge {
+        table: TableKind,
+        degree_bits: usize,
+        max_degree_bits: usize,
+    },
+    #[error("bad config for {table:?}: {reason}")]
+    BadConfig { table: TableKind, reason: String },
+    #[error("static table commitment for {table:?} does not match the program and config")]
+    StaticCommitmentMismatch { table: TableKind },
+    #[error("failed to prove {table:?}")]
+    Table {
+        table: TableKind,
+        #[source]
+        source: anyhow::Error,
+    },
+    #[error("failed to read a public value")]
+    PublicValue(#[source] anyhow::Error),
+}
+
+/// Check of a single table proof that failed, see
+/// [`VerifierError::Table`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VerifierPhase {
+    /// The proof has the wrong number of openings or caps of the wrong height.
+    ProofShape,
+    /// The openings violate the constraints of the table.
+    Constraints,
+    /// The FRI proof of the openings does not verify.
+    Fri,
+}
+
+impl fmt::Display for VerifierPhase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            VerifierPhase::ProofShape => "proof shape",
+            VerifierPhase::Constraints => "constraints",
+            VerifierPhase::Fri => "FRI",
+        })
+    }
+}
+
+#[derive(Error, Debug)]
+pub enum VerifierError {
+    #[error("public sub table values have the wrong shape")]
+    PublicSubTableShape(#[source] anyhow::Error),
+    #[error("program id does not match the commitments to the program and its library")]
+    ProgramIdMismatch,
+    #[error("self program id does not match the program id")]
+    SelfProgramIdMismatch(#[source] anyhow::Error),
+    #[error("{table:?} failed the {phase} check")]
+    Table {
+        table: TableKind,
+        phase: VerifierPhase,
+        #[source]
+        source: anyhow::Error,
+    },
+    #[error("cross table lookups do not match")]
+    CrossTableLookup(#[source] anyhow::Error),
+    #[error("the proof skipped {table:?}, which can not be skipped")]
+    UnskippableTable { table: TableKind },
+    #[error("the proof was made with other preprocessed commitments than the known ones")]
+    PreprocessedMismatch,
+    #[error("proof was made with {recorded}, but the verifier expects {expected}")]
+    ConfigMismatch {
+        recorded: ProofConfig,
+        expected: ProofConfig,
+    },
+}
+
+/// A proof or config that falls short of a
+/// [`SecurityPolicy`](super::security::SecurityPolicy).
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum InsecureParameters {
+    #[error(
+        "{num_query_rounds} FRI queries at rate 2^-{rate_bits} with {proof_of_work_bits} bits of \
+         proof of work give {security_bits} bits of conjectured security, but at least \
+         {min_security_bits} are required"
+    )]
+    TooFewSecurityBits {
+        num_query_rounds: usize,
+        rate_bits: usize,
+        proof_of_work_bits: u32,
+        security_bits: usize,
+        min_security_bits: usize,
+    },
+    #[error("cap height {cap_height} exceeds the maximum of {max_cap_height}")]
+    CapTooHigh {
+        cap_height: usize,
+        max_cap_height: usize,
+    },
+    #[error("{table:?} proof has {actual} FRI queries, but the config asks for {expected}")]
+    QueryRoundsMismatch {
+        table: TableKind,
+        expected: usize,
+        actual: usize,
+    },
+    #[error("{table:?} proof has caps of length {actual}, but the config asks for {expected}")]
+    CapLengthMismatch {
+        table: TableKind,
+        expected: usize,
+        actual: usize,
+    },
+    #[error("proof was made with {recorded}, but the verifier expects {expected}")]
+    ConfigMismatch {
+        recorded: ProofConfig,
+        expected: ProofConfig,
+    },
+}
@@ -4,12 +4,20 @@
 
 pub mod batch_prover;
 pub mod batch_verifier;
+pub mod chain;
+pub mod ctl_graph;
+pub mod error;
 #[allow(clippy::module_name_repetitions)]
 pub mod mozak_stark;
 pub mod permutation;
 pub mod poly;
+pub mod preprocessed;
 pub mod proof;
+pub mod proof_envelope;
+pub mod proof_size;
 pub mod prover;
 pub mod recursive_verifier;
+pub mod security;
+pub mod transcript;
 pub mod utils;
 pub mod verifier;
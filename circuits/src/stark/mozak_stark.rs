@@ -1,5 +1,6 @@
 use std::array::from_fn;
 use std::ops::{Index, IndexMut, Neg};
+use std::str::FromStr;
 extern crate serde;
 extern crate serde_json;
 use cpu::columns::CpuState;
@@ -14,41 +15,81 @@ use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use serde_big_array::BigArray;
 
+#[cfg(feature = "zbb")]
+use crate::bitcount::columns::{BitCount, BitCountCtl};
+#[cfg(feature = "zbb")]
+use crate::bitcount::stark::BitCountStark;
 use crate::bitshift::columns::{Bitshift, BitshiftView};
 use crate::bitshift::stark::BitshiftStark;
-use crate::columns_view::columns_view_impl;
+use crate::columns_view::ColumnsView;
 use crate::cpu::stark::CpuStark;
 use crate::cpu_skeleton::columns::{CpuSkeleton, CpuSkeletonCtl};
 use crate::cpu_skeleton::stark::CpuSkeletonStark;
 use crate::cross_table_lookup::{
     Column, ColumnWithTypedInput, CrossTableLookup, CrossTableLookupWithTypedOutput,
 };
+#[cfg(feature = "decode")]
+use crate::decode;
+#[cfg(feature = "decode")]
+use crate::decode::columns::Decode;
+#[cfg(feature = "decode")]
+use crate::decode::stark::DecodeStark;
+use crate::event_counters::columns::{EventCounterCtl, EventCounters};
+use crate::event_counters::stark::EventCountersStark;
+use crate::event_emission::columns::{EventEmission, EventEmissionCtl};
+use crate::event_emission::stark::EventEmissionStark;
+#[cfg(feature = "goldilocks")]
+use crate::goldilocks;
+#[cfg(feature = "goldilocks")]
+use crate::goldilocks::columns::{Goldilocks, GoldilocksCtl};
+#[cfg(feature = "goldilocks")]
+use crate::goldilocks::stark::GoldilocksStark;
+#[cfg(feature = "memcopy")]
+use crate::memcopy;
+#[cfg(feature = "memcopy")]
+use crate::memcopy::columns::{MemCopy, MemCopyCtl};
+#[cfg(feature = "memcopy")]
+use crate::memcopy::stark::MemCopyStark;
 use crate::memory::columns::{Memory, MemoryCtl};
 use crate::memory::stark::MemoryStark;
 use crate::memory_fullword::columns::FullWordMemory;
 use crate::memory_fullword::stark::FullWordMemoryStark;
 use crate::memory_halfword::columns::HalfWordMemory;
 use crate::memory_halfword::stark::HalfWordMemoryStark;
-use crate::memory_zeroinit::columns::MemoryZeroInit;
-use crate::memory_zeroinit::stark::MemoryZeroInitStark;
-use crate::memoryinit::columns::{MemoryInit, MemoryInitCtl};
+use crate::memoryinit::columns::{MemoryInit, MemoryInitCtl, MemoryLayout, MemoryLayoutCtl};
 use crate::memoryinit::stark::MemoryInitStark;
 use crate::ops::add::columns::Add;
 use crate::ops::add::stark::AddStark;
 use crate::ops::blt_taken::columns::BltTaken;
 use crate::ops::blt_taken::stark::BltTakenStark;
 use crate::ops::{add, blt_taken};
+#[cfg(feature = "poseidon2")]
+use crate::output_tape::columns::OutputTapeDigest;
+#[cfg(feature = "poseidon2")]
+use crate::output_tape::stark::{OutputTapeHashStark, OutputTapeStark};
+#[cfg(feature = "poseidon2")]
 use crate::poseidon2::columns::{Poseidon2State, Poseidon2StateCtl};
+#[cfg(feature = "poseidon2")]
 use crate::poseidon2::stark::Poseidon2_12Stark;
+#[cfg(feature = "poseidon2")]
 use crate::poseidon2_output_bytes::columns::{Poseidon2OutputBytes, Poseidon2OutputBytesCtl};
+#[cfg(feature = "poseidon2")]
 use crate::poseidon2_output_bytes::stark::Poseidon2OutputBytesStark;
+#[cfg(feature = "poseidon2")]
 use crate::poseidon2_sponge::columns::{Poseidon2Sponge, Poseidon2SpongeCtl};
+#[cfg(feature = "poseidon2")]
 use crate::poseidon2_sponge::stark::Poseidon2SpongeStark;
+#[cfg(feature = "poseidon2")]
+use crate::private_tape_hash::columns::PrivateTapeDigest;
 use crate::program::columns::ProgramRom;
 use crate::program::stark::ProgramStark;
 use crate::program_multiplicities::columns::ProgramMult;
 use crate::program_multiplicities::stark::ProgramMultStark;
-use crate::public_sub_table::PublicSubTable;
+use crate::public_sub_table::{PublicSubTable, PublicValue};
+#[cfg(feature = "poseidon2")]
+use crate::public_tape_hash::columns::{PublicTapeHash, TapeByteCtl};
+#[cfg(feature = "poseidon2")]
+use crate::public_tape_hash::stark::PublicTapeHashStark;
 use crate::rangecheck::columns::{rangecheck_looking, RangeCheckColumnsView, RangeCheckCtl};
 use crate::rangecheck::stark::RangeCheckStark;
 use crate::rangecheck_u8::columns::RangeCheckU8;
@@ -62,26 +103,120 @@ use crate::register::zero_read::stark::RegisterZeroReadStark;
 use crate::register::zero_write::columns::RegisterZeroWrite;
 use crate::register::zero_write::stark::RegisterZeroWriteStark;
 use crate::register::RegisterCtl;
+#[cfg(feature = "secp256k1")]
+use crate::secp256k1;
+#[cfg(feature = "secp256k1")]
+use crate::secp256k1::columns::{Secp256k1, Secp256k1Ctl};
+#[cfg(feature = "secp256k1")]
+use crate::secp256k1::stark::Secp256k1Stark;
+use crate::stark::transcript::Transcript;
 use crate::storage_device::columns::{StorageDevice, StorageDeviceCtl};
 use crate::storage_device::stark::StorageDeviceStark;
-use crate::tape_commitments::columns::{TapeCommitmentCTL, TapeCommitments};
+use crate::tape_commitments::columns::{SelfProgramId, TapeCommitmentCTL, TapeCommitments};
 use crate::tape_commitments::stark::TapeCommitmentsStark;
+#[cfg(feature = "u256")]
+use crate::u256;
+#[cfg(feature = "u256")]
+use crate::u256::columns::{U256Ctl, U256};
+#[cfg(feature = "u256")]
+use crate::u256::stark::U256Stark;
 use crate::xor::columns::{XorColumnsView, XorView};
 use crate::xor::stark::XorStark;
+use crate::xor_byte::columns::XorByte;
+use crate::xor_byte::stark::XorByteStark;
 use crate::{
-    bitshift, cpu, cpu_skeleton, memory, memory_fullword, memory_halfword, memory_zeroinit,
-    memoryinit, ops, poseidon2_output_bytes, poseidon2_sponge, program, program_multiplicities,
-    rangecheck, register, storage_device, xor,
+    bitshift, cpu, cpu_skeleton, event_emission, memory, memory_fullword, memory_halfword,
+    memoryinit, ops, program, program_multiplicities, rangecheck, register, storage_device, xor,
+    xor_byte,
 };
-
-const NUM_CROSS_TABLE_LOOKUP: usize = 18;
-const NUM_PUBLIC_SUB_TABLES: usize = 2;
-const NUM_PUBLIC_TABLES: usize = 2;
-pub const PUBLIC_TABLE_KINDS: [TableKind; NUM_PUBLIC_TABLES] =
-    [TableKind::Program, TableKind::ElfMemoryInit];
+#[cfg(feature = "poseidon2")]
+use crate::{poseidon2_output_bytes, poseidon2_sponge};
+
+/// Cross table lookups between the tables of the Poseidon2 precompile, from
+/// the CPU into them, and from the public, the private and the output tape
+/// into their hashes.
+#[cfg(feature = "poseidon2")]
+const NUM_POSEIDON2_CROSS_TABLE_LOOKUP: usize = 6;
+#[cfg(not(feature = "poseidon2"))]
+const NUM_POSEIDON2_CROSS_TABLE_LOOKUP: usize = 0;
+/// The lookup from the CPU into the secp256k1 table.
+#[cfg(feature = "secp256k1")]
+const NUM_SECP256K1_CROSS_TABLE_LOOKUP: usize = 1;
+#[cfg(not(feature = "secp256k1"))]
+const NUM_SECP256K1_CROSS_TABLE_LOOKUP: usize = 0;
+/// The lookup from the CPU into the u256 table.
+#[cfg(feature = "u256")]
+const NUM_U256_CROSS_TABLE_LOOKUP: usize = 1;
+#[cfg(not(feature = "u256"))]
+const NUM_U256_CROSS_TABLE_LOOKUP: usize = 0;
+/// The lookup from the CPU into the memcopy table.
+#[cfg(feature = "memcopy")]
+const NUM_MEMCOPY_CROSS_TABLE_LOOKUP: usize = 1;
+#[cfg(not(feature = "memcopy"))]
+const NUM_MEMCOPY_CROSS_TABLE_LOOKUP: usize = 0;
+/// The lookup from the CPU into the Goldilocks table.
+#[cfg(feature = "goldilocks")]
+const NUM_GOLDILOCKS_CROSS_TABLE_LOOKUP: usize = 1;
+#[cfg(not(feature = "goldilocks"))]
+const NUM_GOLDILOCKS_CROSS_TABLE_LOOKUP: usize = 0;
+/// The lookup from the decode table into the program ROM.
+#[cfg(feature = "decode")]
+const NUM_DECODE_CROSS_TABLE_LOOKUP: usize = 1;
+#[cfg(not(feature = "decode"))]
+const NUM_DECODE_CROSS_TABLE_LOOKUP: usize = 0;
+/// The lookup from the CPU into the bit count table.
+#[cfg(feature = "zbb")]
+const NUM_ZBB_CROSS_TABLE_LOOKUP: usize = 1;
+#[cfg(not(feature = "zbb"))]
+const NUM_ZBB_CROSS_TABLE_LOOKUP: usize = 0;
+const NUM_CROSS_TABLE_LOOKUP: usize = 21
+    + NUM_POSEIDON2_CROSS_TABLE_LOOKUP
+    + NUM_SECP256K1_CROSS_TABLE_LOOKUP
+    + NUM_U256_CROSS_TABLE_LOOKUP
+    + NUM_MEMCOPY_CROSS_TABLE_LOOKUP
+    + NUM_GOLDILOCKS_CROSS_TABLE_LOOKUP
+    + NUM_DECODE_CROSS_TABLE_LOOKUP
+    + NUM_ZBB_CROSS_TABLE_LOOKUP;
+const NUM_PUBLIC_TABLES: usize = 4;
+pub const PUBLIC_TABLE_KINDS: [TableKind; NUM_PUBLIC_TABLES] = [
+    TableKind::Program,
+    TableKind::ElfMemoryInit,
+    TableKind::LibraryProgram,
+    TableKind::LibraryElfMemoryInit,
+];
+
+/// Tables that a proof may leave out when no lookup involves any of their
+/// rows, see [`MozakStark::skip_unused`].
+///
+/// A left out table counts as an empty one, so this is only sound for tables
+/// whose padding alone satisfies their constraints, and that feed neither the
+/// public inputs nor the program id. The precompile tables qualify, and so
+/// does the event counters table unless its counts are made public.
+pub const SKIPPABLE_TABLE_KINDS: &[TableKind] = &[
+    TableKind::EventCounters,
+    #[cfg(feature = "zbb")]
+    TableKind::BitCount,
+    #[cfg(feature = "poseidon2")]
+    TableKind::Poseidon2,
+    #[cfg(feature = "poseidon2")]
+    TableKind::Poseidon2Sponge,
+    #[cfg(feature = "poseidon2")]
+    TableKind::Poseidon2OutputBytes,
+    #[cfg(feature = "secp256k1")]
+    TableKind::Secp256k1,
+    #[cfg(feature = "u256")]
+    TableKind::U256,
+    #[cfg(feature = "memcopy")]
+    TableKind::MemCopy,
+    #[cfg(feature = "goldilocks")]
+    TableKind::Goldilocks,
+];
 
 /// STARK Gadgets of Mozak-VM
 ///
+/// Precompile tables are only part of the set when their feature is enabled,
+/// and [`TableKind`] follows suit.
+///
 /// ## Generics
 /// `F`: The [Field] that the STARK is defined over
 /// `D`: Degree of the extension field of `F`
@@ -94,21 +229,37 @@ pub struct MozakStark<F: RichField + Extendable<D>, const D: usize> {
     pub rangecheck_stark: RangeCheckStark<F, D>,
     #[StarkSet(stark_kind = "Xor")]
     pub xor_stark: XorStark<F, D>,
+    #[StarkSet(stark_kind = "XorByte")]
+    pub xor_byte_stark: XorByteStark<F, D>,
+    #[cfg(feature = "zbb")]
+    #[StarkSet(stark_kind = "BitCount")]
+    pub bitcount_stark: BitCountStark<F, D>,
     #[StarkSet(stark_kind = "Bitshift")]
     pub shift_amount_stark: BitshiftStark<F, D>,
     #[StarkSet(stark_kind = "Program")]
     pub program_stark: ProgramStark<F, D>,
     #[StarkSet(stark_kind = "ProgramMult")]
     pub program_mult_stark: ProgramMultStark<F, D>,
+    /// The ROM of the library linked into the program, see
+    /// [`Program::link`](mozak_runner::elf::Program::link).
+    #[StarkSet(stark_kind = "LibraryProgram")]
+    pub library_program_stark: ProgramStark<F, D>,
+    #[StarkSet(stark_kind = "LibraryProgramMult")]
+    pub library_program_mult_stark: ProgramMultStark<F, D>,
+    #[cfg(feature = "decode")]
+    #[StarkSet(stark_kind = "Decode")]
+    pub decode_stark: DecodeStark<F, D>,
     #[StarkSet(stark_kind = "Memory")]
     pub memory_stark: MemoryStark<F, D>,
     #[StarkSet(stark_kind = "ElfMemoryInit")]
     pub elf_memory_init_stark: MemoryInitStark<F, D>,
+    #[StarkSet(stark_kind = "LibraryElfMemoryInit")]
+    pub library_elf_memory_init_stark: MemoryInitStark<F, D>,
     // TODO(Bing): find a way to natively constrain zero initializations within
     // the `MemoryStark`, instead of relying on a CTL between this and the
     // `MemoryStark`.
-    #[StarkSet(stark_kind = "MemoryZeroInit")]
-    pub memory_zeroinit_stark: MemoryZeroInitStark<F, D>,
+    #[StarkSet(stark_kind = "MemoryInit")]
+    pub memory_init_stark: MemoryInitStark<F, D>,
     #[StarkSet(stark_kind = "RangeCheckU8")]
     pub rangecheck_u8_stark: RangeCheckU8Stark<F, D>,
     #[StarkSet(stark_kind = "HalfWordMemory")]
@@ -133,6 +284,10 @@ pub struct MozakStark<F: RichField + Extendable<D>, const D: usize> {
     pub cast_list_commitment_tape_stark: StorageDeviceStark<F, D>,
     #[StarkSet(stark_kind = "SelfProgIdTape")]
     pub self_prog_id_tape_stark: StorageDeviceStark<F, D>,
+    /// Hints from the host. Unlike the tapes, hints are neither public nor
+    /// hashed, so this table only moves them into memory.
+    #[StarkSet(stark_kind = "HintTape")]
+    pub hint_tape_stark: StorageDeviceStark<F, D>,
     #[StarkSet(stark_kind = "RegisterInit")]
     pub register_init_stark: RegisterInitStark<F, D>,
     #[StarkSet(stark_kind = "Register")]
@@ -141,12 +296,39 @@ pub struct MozakStark<F: RichField + Extendable<D>, const D: usize> {
     pub register_zero_read_stark: RegisterZeroReadStark<F, D>,
     #[StarkSet(stark_kind = "RegisterZeroWrite")]
     pub register_zero_write_stark: RegisterZeroWriteStark<F, D>,
+    #[cfg(feature = "poseidon2")]
     #[StarkSet(stark_kind = "Poseidon2")]
     pub poseidon2_stark: Poseidon2_12Stark<F, D>,
+    #[cfg(feature = "poseidon2")]
     #[StarkSet(stark_kind = "Poseidon2Sponge")]
     pub poseidon2_sponge_stark: Poseidon2SpongeStark<F, D>,
+    #[cfg(feature = "poseidon2")]
     #[StarkSet(stark_kind = "Poseidon2OutputBytes")]
     pub poseidon2_output_bytes_stark: Poseidon2OutputBytesStark<F, D>,
+    #[cfg(feature = "poseidon2")]
+    #[StarkSet(stark_kind = "PublicTapeHash")]
+    pub public_tape_hash_stark: PublicTapeHashStark<F, D>,
+    #[cfg(feature = "poseidon2")]
+    #[StarkSet(stark_kind = "PrivateTapeHash")]
+    pub private_tape_hash_stark: PublicTapeHashStark<F, D>,
+    #[cfg(feature = "poseidon2")]
+    #[StarkSet(stark_kind = "OutputTape")]
+    pub output_tape_stark: OutputTapeStark<F, D>,
+    #[cfg(feature = "poseidon2")]
+    #[StarkSet(stark_kind = "OutputTapeHash")]
+    pub output_tape_hash_stark: OutputTapeHashStark<F, D>,
+    #[cfg(feature = "secp256k1")]
+    #[StarkSet(stark_kind = "Secp256k1")]
+    pub secp256k1_stark: Secp256k1Stark<F, D>,
+    #[cfg(feature = "u256")]
+    #[StarkSet(stark_kind = "U256")]
+    pub u256_stark: U256Stark<F, D>,
+    #[cfg(feature = "memcopy")]
+    #[StarkSet(stark_kind = "MemCopy")]
+    pub memcopy_stark: MemCopyStark<F, D>,
+    #[cfg(feature = "goldilocks")]
+    #[StarkSet(stark_kind = "Goldilocks")]
+    pub goldilocks_stark: GoldilocksStark<F, D>,
     #[StarkSet(stark_kind = "CpuSkeleton")]
     pub cpu_skeleton_stark: CpuSkeletonStark<F, D>,
     #[StarkSet(stark_kind = "Add")]
@@ -155,9 +337,20 @@ pub struct MozakStark<F: RichField + Extendable<D>, const D: usize> {
     pub blt_taken_stark: BltTakenStark<F, D>,
     #[StarkSet(stark_kind = "TapeCommitments")]
     pub tape_commitments_stark: TapeCommitmentsStark<F, D>,
+    #[StarkSet(stark_kind = "EventCounters")]
+    pub event_counters_stark: EventCountersStark<F, D>,
+    #[StarkSet(stark_kind = "EventEmission")]
+    pub event_emission_stark: EventEmissionStark<F, D>,
     pub cross_table_lookups: [CrossTableLookup; NUM_CROSS_TABLE_LOOKUP],
-    pub public_sub_tables: [PublicSubTable; NUM_PUBLIC_SUB_TABLES],
+    pub public_sub_tables: Vec<PublicSubTable>,
     pub debug: bool,
+    /// Tables that the prover leaves out of the proof when no lookup involves
+    /// any of their rows, instead of committing to their padding. Only
+    /// [`MozakStark::can_skip`] tables are ever left out.
+    pub skip_unused: TableKindArray<bool>,
+    /// Where the prover and the verifier record the Fiat-Shamir transcript
+    /// of a proof. Records nothing by default.
+    pub transcript: Transcript,
 }
 
 // A macro which takes metadata about `MozakStark`
@@ -372,6 +565,21 @@ impl<'a, T> IntoIterator for &'a TableKindArray<T> {
     fn into_iter(self) -> Self::IntoIter { self.0.iter() }
 }
 
+/// Parses the name of a table kind, ignoring case, underscores and dashes, so
+/// that `cpu_skeleton` names [`TableKind::CpuSkeleton`].
+impl FromStr for TableKind {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        let normalized = name.replace(['_', '-'], "");
+        all_kind!(|kind| kind)
+            .iter()
+            .find(|kind| format!("{kind:?}").eq_ignore_ascii_case(&normalized))
+            .copied()
+            .ok_or_else(|| format!("unknown table: {name}"))
+    }
+}
+
 impl<T: Send> TableKindArray<T> {
     pub fn par_map<F, U>(self, f: F) -> TableKindArray<U>
     where
@@ -406,27 +614,82 @@ impl<T> TableKindArray<T> {
     pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> { self.0.iter_mut() }
 }
 
-columns_view_impl!(PublicInputs);
+impl<T, E> TableKindArray<Result<T, E>> {
+    /// Returns the error of the first table that has one, or else the values
+    /// of all tables.
+    pub fn transpose(self) -> Result<TableKindArray<T>, E> {
+        let values = self.0.into_iter().collect::<Result<Vec<T>, E>>()?;
+        Ok(TableKindArray(
+            values
+                .try_into()
+                .unwrap_or_else(|_| unreachable!("one value per table")),
+        ))
+    }
+}
 
 #[repr(C)]
-#[derive(Clone, Copy, Eq, PartialEq, Debug, Serialize, Deserialize)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Serialize, Deserialize, ColumnsView)]
 #[serde(bound = "F: Field")]
 pub struct PublicInputs<F> {
     pub entry_point: F,
+    /// Most instructions the execution may take, or `-1` for no limit. See
+    /// [`State::with_max_cycles`](mozak_runner::state::State::with_max_cycles).
+    pub max_cycles: F,
+    /// One if the execution was halted for exhausting `max_cycles`, and zero
+    /// if it halted via the `HALT` ecall.
+    pub out_of_gas: F,
+}
+
+impl<F: RichField> PublicInputs<F> {
+    /// Public inputs of an execution without a cycle budget.
+    #[must_use]
+    pub fn new(entry_point: F) -> Self {
+        Self {
+            entry_point,
+            max_cycles: F::NEG_ONE,
+            out_of_gas: F::ZERO,
+        }
+    }
+
+    /// Public inputs of the execution of `program` in `record`, with its cycle
+    /// budget and whether it ran out of gas.
+    #[must_use]
+    pub fn for_execution(
+        program: &mozak_runner::elf::Program,
+        record: &mozak_runner::vm::ExecutionRecord<F>,
+    ) -> Self {
+        let last_state = &record.last_state;
+        Self {
+            max_cycles: last_state.max_cycles.map_or(F::NEG_ONE, |max_cycles| {
+                F::from_noncanonical_u64(max_cycles.get())
+            }),
+            out_of_gas: F::from_bool(last_state.out_of_gas),
+            ..Self::new(F::from_canonical_u32(program.entry_point))
+        }
+    }
 }
 
 impl<F: RichField + Extendable<D>, const D: usize> Default for MozakStark<F, D> {
+    #[cfg_attr(not(feature = "poseidon2"), allow(clippy::let_and_return))]
     fn default() -> Self {
-        Self {
+        let stark = Self {
             cpu_stark: CpuStark::default(),
             rangecheck_stark: RangeCheckStark::default(),
             xor_stark: XorStark::default(),
+            xor_byte_stark: XorByteStark::default(),
+            #[cfg(feature = "zbb")]
+            bitcount_stark: BitCountStark::default(),
             shift_amount_stark: BitshiftStark::default(),
             program_stark: ProgramStark::default(),
             program_mult_stark: ProgramMultStark::default(),
+            library_program_stark: ProgramStark::default(),
+            library_program_mult_stark: ProgramMultStark::default(),
+            #[cfg(feature = "decode")]
+            decode_stark: DecodeStark::default(),
             memory_stark: MemoryStark::default(),
             elf_memory_init_stark: MemoryInitStark::default(),
-            memory_zeroinit_stark: MemoryZeroInitStark::default(),
+            library_elf_memory_init_stark: MemoryInitStark::default(),
+            memory_init_stark: MemoryInitStark::default(),
             rangecheck_u8_stark: RangeCheckU8Stark::default(),
             halfword_memory_stark: HalfWordMemoryStark::default(),
             fullword_memory_stark: FullWordMemoryStark::default(),
@@ -441,42 +704,107 @@ impl<F: RichField + Extendable<D>, const D: usize> Default for MozakStark<F, D>
             events_commitment_tape_stark: StorageDeviceStark::default(),
             cast_list_commitment_tape_stark: StorageDeviceStark::default(),
             self_prog_id_tape_stark: StorageDeviceStark::default(),
+            hint_tape_stark: StorageDeviceStark::default(),
+            #[cfg(feature = "poseidon2")]
             poseidon2_sponge_stark: Poseidon2SpongeStark::default(),
+            #[cfg(feature = "poseidon2")]
             poseidon2_stark: Poseidon2_12Stark::default(),
+            #[cfg(feature = "poseidon2")]
             poseidon2_output_bytes_stark: Poseidon2OutputBytesStark::default(),
+            #[cfg(feature = "poseidon2")]
+            public_tape_hash_stark: PublicTapeHashStark::default(),
+            #[cfg(feature = "poseidon2")]
+            private_tape_hash_stark: PublicTapeHashStark::default(),
+            #[cfg(feature = "poseidon2")]
+            output_tape_stark: OutputTapeStark::default(),
+            #[cfg(feature = "poseidon2")]
+            output_tape_hash_stark: OutputTapeHashStark::default(),
+            #[cfg(feature = "secp256k1")]
+            secp256k1_stark: Secp256k1Stark::default(),
+            #[cfg(feature = "u256")]
+            u256_stark: U256Stark::default(),
+            #[cfg(feature = "memcopy")]
+            memcopy_stark: MemCopyStark::default(),
+            #[cfg(feature = "goldilocks")]
+            goldilocks_stark: GoldilocksStark::default(),
             cpu_skeleton_stark: CpuSkeletonStark::default(),
             add_stark: AddStark::default(),
             blt_taken_stark: BltTakenStark::default(),
             tape_commitments_stark: TapeCommitmentsStark::default(),
+            event_counters_stark: EventCountersStark::default(),
+            event_emission_stark: EventEmissionStark::default(),
 
             // These tables contain only descriptions of the tables.
             // The values of the tables are generated as traces.
             cross_table_lookups: [
                 RangecheckTable::lookups(),
                 XorCpuTable::lookups(),
+                XorByteXorTable::lookups(),
+                #[cfg(feature = "zbb")]
+                BitCountCpuTable::lookups(),
                 BitshiftCpuTable::lookups(),
                 InnerCpuTable::lookups(),
                 ProgramCpuTable::lookups(),
+                LibraryProgramCpuTable::lookups(),
+                #[cfg(feature = "decode")]
+                DecodeProgramTable::lookups(),
                 IntoMemoryTable::lookups(),
                 MemoryInitMemoryTable::lookups(),
+                MemoryLayoutTable::lookups(),
                 RangeCheckU8LookupTable::lookups(),
                 HalfWordMemoryCpuTable::lookups(),
                 FullWordMemoryCpuTable::lookups(),
                 RegisterLookups::lookups(),
                 StorageDeviceToCpuTable::lookups(),
+                #[cfg(feature = "poseidon2")]
                 Poseidon2SpongeCpuTable::lookups(),
+                #[cfg(feature = "poseidon2")]
                 Poseidon2Poseidon2SpongeTable::lookups(),
+                #[cfg(feature = "poseidon2")]
                 Poseidon2OutputBytesPoseidon2SpongeTable::lookups(),
+                #[cfg(feature = "poseidon2")]
+                PublicTapeHashLookupTable::lookups(),
+                #[cfg(feature = "poseidon2")]
+                PrivateTapeHashLookupTable::lookups(),
+                #[cfg(feature = "poseidon2")]
+                OutputTapeHashLookupTable::lookups(),
+                #[cfg(feature = "secp256k1")]
+                Secp256k1CpuTable::lookups(),
+                #[cfg(feature = "u256")]
+                U256CpuTable::lookups(),
+                #[cfg(feature = "memcopy")]
+                MemCopyCpuTable::lookups(),
+                #[cfg(feature = "goldilocks")]
+                GoldilocksCpuTable::lookups(),
                 CpuToSkeletonTable::lookups(),
                 EventCommitmentTapeIOLookupTable::lookups(),
                 CastlistCommitmentTapeIOLookupTable::lookups(),
+                SelfProgIdTapeIOLookupTable::lookups(),
+                EventCountersLookupTable::lookups(),
+                EventEmissionCpuTable::lookups(),
             ],
-            public_sub_tables: [
+            public_sub_tables: vec![
+                crate::cpu_skeleton::columns::make_exit_code_public(),
                 crate::tape_commitments::columns::make_event_commitment_tape_public(),
                 crate::tape_commitments::columns::make_castlist_commitment_tape_public(),
             ],
             debug: false,
-        }
+            skip_unused: TableKindArray::default(),
+            transcript: Transcript::default(),
+        };
+        // Every proof shows which memory started out zero.
+        let stark = stark.with_public_value::<MemoryLayout>();
+        // Every proof shows the program id that the guest read, if any, so
+        // that it can be checked against the program id of the proof.
+        let stark = stark.with_public_value::<SelfProgramId>();
+        // Every proof commits to what the guest wrote, even if that is
+        // nothing.
+        #[cfg(feature = "poseidon2")]
+        let stark = stark.with_public_value::<OutputTapeDigest<F>>();
+        // Every proof shows which private tape the program consumed.
+        #[cfg(feature = "poseidon2")]
+        let stark = stark.with_public_value::<PrivateTapeDigest<F>>();
+        stark
     }
 }
 
@@ -488,6 +816,43 @@ impl<F: RichField + Extendable<D>, const D: usize> MozakStark<F, D> {
             ..Self::default()
         }
     }
+
+    /// Leaves every table that [`MozakStark::can_skip`] out of the proofs of
+    /// this stark when the execution does not use it.
+    #[must_use]
+    pub fn skipping_unused_tables(self) -> Self {
+        Self {
+            skip_unused: all_kind!(|kind| self.can_skip(kind)),
+            ..self
+        }
+    }
+
+    /// Records the Fiat-Shamir transcript of the proofs that this stark
+    /// proves or verifies into `transcript`.
+    #[must_use]
+    pub fn with_transcript(self, transcript: Transcript) -> Self { Self { transcript, ..self } }
+
+    /// Whether a proof may leave `kind` out, see [`SKIPPABLE_TABLE_KINDS`].
+    /// A table with public sub table values never may.
+    #[must_use]
+    pub fn can_skip(&self, kind: TableKind) -> bool {
+        SKIPPABLE_TABLE_KINDS.contains(&kind)
+            && self
+                .public_sub_tables
+                .iter()
+                .all(|public_sub_table| public_sub_table.table.kind != kind)
+    }
+
+    /// Makes the public value `V` part of the proofs of this stark, see
+    /// [`PublicValue`].
+    #[must_use]
+    pub fn with_public_value<V: PublicValue<F>>(mut self) -> Self {
+        let public_sub_table = V::public_sub_table();
+        if !self.public_sub_tables.contains(&public_sub_table) {
+            self.public_sub_tables.push(public_sub_table);
+        }
+        self
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -513,7 +878,7 @@ where
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct TableWithTypedOutput<Row> {
     // TODO: when converting to untyped table, check that TableKind agrees with columns type.
     // That would have prevented some mistakes.
@@ -592,16 +957,28 @@ table_impl!(
 );
 table_impl!(CpuTable, TableKind::Cpu, CpuState);
 table_impl!(XorTable, TableKind::Xor, XorColumnsView);
+table_impl!(XorByteTable, TableKind::XorByte, XorByte);
+#[cfg(feature = "zbb")]
+table_impl!(BitCountTable, TableKind::BitCount, BitCount);
 table_impl!(BitshiftTable, TableKind::Bitshift, BitshiftView);
 table_impl!(ProgramTable, TableKind::Program, ProgramRom);
 table_impl!(ProgramMultTable, TableKind::ProgramMult, ProgramMult);
+table_impl!(LibraryProgramTable, TableKind::LibraryProgram, ProgramRom);
+table_impl!(
+    LibraryProgramMultTable,
+    TableKind::LibraryProgramMult,
+    ProgramMult
+);
+#[cfg(feature = "decode")]
+table_impl!(DecodeTable, TableKind::Decode, Decode);
 table_impl!(MemoryTable, TableKind::Memory, Memory);
 table_impl!(ElfMemoryInitTable, TableKind::ElfMemoryInit, MemoryInit);
 table_impl!(
-    MemoryZeroInitTable,
-    TableKind::MemoryZeroInit,
-    MemoryZeroInit
+    LibraryElfMemoryInitTable,
+    TableKind::LibraryElfMemoryInit,
+    MemoryInit
 );
+table_impl!(MemoryInitTable, TableKind::MemoryInit, MemoryInit);
 table_impl!(RangeCheckU8Table, TableKind::RangeCheckU8, RangeCheckU8);
 table_impl!(
     HalfWordMemoryTable,
@@ -652,6 +1029,8 @@ table_impl!(
     TableKind::SelfProgIdTape,
     StorageDevice
 );
+table_impl!(HintTapeTable, TableKind::HintTape, StorageDevice);
+#[cfg(feature = "poseidon2")]
 table_impl!(
     Poseidon2SpongeTable,
     TableKind::Poseidon2Sponge,
@@ -662,12 +1041,44 @@ table_impl!(
     TableKind::TapeCommitments,
     TapeCommitments
 );
+table_impl!(EventCountersTable, TableKind::EventCounters, EventCounters);
+table_impl!(EventEmissionTable, TableKind::EventEmission, EventEmission);
+#[cfg(feature = "poseidon2")]
 table_impl!(Poseidon2Table, TableKind::Poseidon2, Poseidon2State);
+#[cfg(feature = "poseidon2")]
 table_impl!(
     Poseidon2OutputBytesTable,
     TableKind::Poseidon2OutputBytes,
     Poseidon2OutputBytes
 );
+#[cfg(feature = "poseidon2")]
+table_impl!(
+    PublicTapeHashTable,
+    TableKind::PublicTapeHash,
+    PublicTapeHash
+);
+#[cfg(feature = "poseidon2")]
+table_impl!(
+    PrivateTapeHashTable,
+    TableKind::PrivateTapeHash,
+    PublicTapeHash
+);
+#[cfg(feature = "poseidon2")]
+table_impl!(OutputTapeTable, TableKind::OutputTape, StorageDevice);
+#[cfg(feature = "poseidon2")]
+table_impl!(
+    OutputTapeHashTable,
+    TableKind::OutputTapeHash,
+    PublicTapeHash
+);
+#[cfg(feature = "secp256k1")]
+table_impl!(Secp256k1Table, TableKind::Secp256k1, Secp256k1);
+#[cfg(feature = "u256")]
+table_impl!(U256Table, TableKind::U256, U256);
+#[cfg(feature = "memcopy")]
+table_impl!(MemCopyTable, TableKind::MemCopy, MemCopy);
+#[cfg(feature = "goldilocks")]
+table_impl!(GoldilocksTable, TableKind::Goldilocks, Goldilocks);
 table_impl!(SkeletonTable, TableKind::CpuSkeleton, CpuSkeleton);
 table_impl!(AddTable, TableKind::Add, Add);
 table_impl!(BltTakenTable, TableKind::BltTaken, BltTaken);
@@ -704,13 +1115,25 @@ impl Lookups for RangecheckTable {
     fn lookups_with_typed_output() -> CrossTableLookupWithTypedOutput<Self::Row> {
         let register = register::general::columns::rangecheck_looking();
 
-        let looking: Vec<TableWithTypedOutput<_>> = chain![
+        #[cfg_attr(
+            not(any(feature = "secp256k1", feature = "u256", feature = "decode")),
+            allow(unused_mut)
+        )]
+        let mut looking: Vec<TableWithTypedOutput<_>> = chain![
             memory::columns::rangecheck_looking(),
+            memoryinit::columns::rangecheck_looking(),
             cpu::columns::rangecheck_looking(),
             ops::add::columns::rangecheck_looking(),
             register,
+            event_emission::columns::rangecheck_looking(),
         ]
         .collect();
+        #[cfg(feature = "secp256k1")]
+        looking.extend(secp256k1::columns::rangecheck_looking());
+        #[cfg(feature = "u256")]
+        looking.extend(u256::columns::rangecheck_looking());
+        #[cfg(feature = "decode")]
+        looking.extend(decode::columns::rangecheck_looking());
         CrossTableLookupWithTypedOutput::new(looking, vec![rangecheck::columns::lookup()])
     }
 }
@@ -727,6 +1150,32 @@ impl Lookups for XorCpuTable {
     }
 }
 
+pub struct XorByteXorTable;
+
+impl Lookups for XorByteXorTable {
+    type Row = XorView<Column>;
+
+    fn lookups_with_typed_output() -> CrossTableLookupWithTypedOutput<Self::Row> {
+        CrossTableLookupWithTypedOutput::new(xor::columns::lookup_for_xor_byte(), vec![
+            xor_byte::columns::lookup(),
+        ])
+    }
+}
+
+#[cfg(feature = "zbb")]
+pub struct BitCountCpuTable;
+
+#[cfg(feature = "zbb")]
+impl Lookups for BitCountCpuTable {
+    type Row = BitCountCtl<Column>;
+
+    fn lookups_with_typed_output() -> CrossTableLookupWithTypedOutput<Self::Row> {
+        CrossTableLookupWithTypedOutput::new(vec![cpu::columns::lookup_for_bitcount()], vec![
+            crate::bitcount::columns::lookup_for_cpu(),
+        ])
+    }
+}
+
 pub struct IntoMemoryTable;
 
 impl Lookups for IntoMemoryTable {
@@ -734,7 +1183,17 @@ impl Lookups for IntoMemoryTable {
 
     #[allow(clippy::too_many_lines)]
     fn lookups_with_typed_output() -> CrossTableLookupWithTypedOutput<Self::Row> {
-        let tables = chain![
+        #[cfg_attr(
+            not(any(
+                feature = "poseidon2",
+                feature = "secp256k1",
+                feature = "u256",
+                feature = "memcopy",
+                feature = "goldilocks"
+            )),
+            allow(unused_mut)
+        )]
+        let mut tables: Vec<_> = chain![
             [cpu::columns::lookup_for_memory()],
             [
                 TableKind::StorageDevicePrivate,
@@ -744,14 +1203,28 @@ impl Lookups for IntoMemoryTable {
                 TableKind::EventsCommitmentTape,
                 TableKind::CastListCommitmentTape,
                 TableKind::SelfProgIdTape,
+                TableKind::HintTape,
             ]
             .map(storage_device::columns::lookup_for_memory),
             memory_fullword::columns::lookup_for_memory_limb(),
             memory_halfword::columns::lookup_for_memory_limb(),
-            poseidon2_sponge::columns::lookup_for_input_memory(),
-            poseidon2_output_bytes::columns::lookup_for_output_memory(),
+            event_emission::columns::lookup_for_memory(),
         ]
         .collect();
+        #[cfg(feature = "poseidon2")]
+        tables.extend(chain![
+            poseidon2_sponge::columns::lookup_for_input_memory(),
+            poseidon2_output_bytes::columns::lookup_for_output_memory(),
+            [storage_device::columns::output_tape_lookup_for_memory()],
+        ]);
+        #[cfg(feature = "secp256k1")]
+        tables.extend(secp256k1::columns::lookup_for_memory());
+        #[cfg(feature = "u256")]
+        tables.extend(u256::columns::lookup_for_memory());
+        #[cfg(feature = "memcopy")]
+        tables.extend(memcopy::columns::lookup_for_memory());
+        #[cfg(feature = "goldilocks")]
+        tables.extend(goldilocks::columns::lookup_for_memory());
         CrossTableLookupWithTypedOutput::new(tables, vec![memory::columns::lookup_for_cpu()])
     }
 }
@@ -764,14 +1237,32 @@ impl Lookups for MemoryInitMemoryTable {
     fn lookups_with_typed_output() -> CrossTableLookupWithTypedOutput<MemoryInitCtl<Column>> {
         CrossTableLookupWithTypedOutput::new(
             vec![
-                memoryinit::columns::lookup_for_memory(ElfMemoryInitTable::new),
-                memory_zeroinit::columns::lookup_for_memory(),
+                memoryinit::columns::lookup_for_memory(TableKind::ElfMemoryInit),
+                memoryinit::columns::lookup_for_memory(TableKind::LibraryElfMemoryInit),
+                memoryinit::columns::lookup_for_memory(TableKind::MemoryInit),
             ],
             vec![memory::columns::lookup_for_memoryinit()],
         )
     }
 }
 
+pub struct MemoryLayoutTable;
+
+impl Lookups for MemoryLayoutTable {
+    type Row = MemoryLayoutCtl<Column>;
+
+    fn lookups_with_typed_output() -> CrossTableLookupWithTypedOutput<Self::Row> {
+        CrossTableLookupWithTypedOutput::new(
+            vec![memoryinit::columns::lookup_for_layout(
+                TableKind::MemoryInit,
+            )],
+            vec![memoryinit::columns::lookup_for_layout(
+                TableKind::ElfMemoryInit,
+            )],
+        )
+    }
+}
+
 pub struct BitshiftCpuTable;
 
 impl Lookups for BitshiftCpuTable {
@@ -796,7 +1287,10 @@ impl Lookups for InnerCpuTable {
                 blt_taken::columns::lookup_for_program_rom(),
                 cpu::columns::lookup_for_program_rom(),
             ],
-            vec![program_multiplicities::columns::lookup_for_cpu()],
+            vec![
+                program_multiplicities::columns::lookup_for_cpu(),
+                program_multiplicities::columns::library_lookup_for_cpu(),
+            ],
         )
     }
 }
@@ -806,24 +1300,64 @@ pub struct ProgramCpuTable;
 impl Lookups for ProgramCpuTable {
     type Row = ProgramRom<Column>;
 
+    /// With the decode table, the program ROM holds raw words, and the decode
+    /// table stands in for it with the decoded instructions.
     fn lookups_with_typed_output() -> CrossTableLookupWithTypedOutput<Self::Row> {
+        #[cfg(not(feature = "decode"))]
+        let looked = program::columns::lookup_for_ctl();
+        #[cfg(feature = "decode")]
+        let looked = decode::columns::lookup_for_program_mult();
         CrossTableLookupWithTypedOutput::new(
             vec![program_multiplicities::columns::lookup_for_rom()],
-            vec![program::columns::lookup_for_ctl()],
+            vec![looked],
+        )
+    }
+}
+
+pub struct LibraryProgramCpuTable;
+
+impl Lookups for LibraryProgramCpuTable {
+    type Row = ProgramRom<Column>;
+
+    /// The library ROM holds decoded instructions, with or without the decode
+    /// table.
+    fn lookups_with_typed_output() -> CrossTableLookupWithTypedOutput<Self::Row> {
+        CrossTableLookupWithTypedOutput::new(
+            vec![program_multiplicities::columns::library_lookup_for_rom()],
+            vec![program::columns::library_lookup_for_ctl()],
         )
     }
 }
 
+#[cfg(feature = "decode")]
+pub struct DecodeProgramTable;
+
+#[cfg(feature = "decode")]
+impl Lookups for DecodeProgramTable {
+    type Row = ProgramRom<Column>;
+
+    fn lookups_with_typed_output() -> CrossTableLookupWithTypedOutput<Self::Row> {
+        CrossTableLookupWithTypedOutput::new(vec![decode::columns::lookup_for_program_rom()], vec![
+            program::columns::lookup_for_ctl(),
+        ])
+    }
+}
+
 pub struct RangeCheckU8LookupTable;
 impl Lookups for RangeCheckU8LookupTable {
     type Row = RangeCheckCtl<Column>;
 
     fn lookups_with_typed_output() -> CrossTableLookupWithTypedOutput<Self::Row> {
-        let looking: Vec<TableWithTypedOutput<RangeCheckCtl<Column>>> = chain![
+        #[cfg_attr(not(any(feature = "secp256k1", feature = "u256")), allow(unused_mut))]
+        let mut looking: Vec<TableWithTypedOutput<RangeCheckCtl<Column>>> = chain![
             rangecheck_looking(),
             memory::columns::rangecheck_u8_looking(),
         ]
         .collect();
+        #[cfg(feature = "secp256k1")]
+        looking.extend(secp256k1::columns::rangecheck_u8_looking());
+        #[cfg(feature = "u256")]
+        looking.extend(u256::columns::rangecheck_u8_looking());
         CrossTableLookupWithTypedOutput::new(looking, vec![crate::rangecheck_u8::columns::lookup()])
     }
 }
@@ -861,14 +1395,26 @@ impl Lookups for RegisterLookups {
 
     fn lookups_with_typed_output() -> CrossTableLookupWithTypedOutput<Self::Row> {
         CrossTableLookupWithTypedOutput::new(
-            chain![
+            [
                 crate::cpu::columns::register_looking(),
                 ops::add::columns::register_looking(),
                 ops::blt_taken::columns::register_looking(),
                 crate::storage_device::columns::register_looking(),
+                crate::event_emission::columns::register_looking(),
+                #[cfg(feature = "poseidon2")]
                 crate::poseidon2_sponge::columns::register_looking(),
+                #[cfg(feature = "secp256k1")]
+                crate::secp256k1::columns::register_looking(),
+                #[cfg(feature = "u256")]
+                crate::u256::columns::register_looking(),
+                #[cfg(feature = "memcopy")]
+                crate::memcopy::columns::register_looking(),
+                #[cfg(feature = "goldilocks")]
+                crate::goldilocks::columns::register_looking(),
                 vec![crate::register::init::columns::lookup_for_register()],
             ]
+            .into_iter()
+            .flatten()
             .collect(),
             vec![
                 crate::register::general::columns::register_looked(),
@@ -895,6 +1441,9 @@ impl Lookups for StorageDeviceToCpuTable {
                     TableKind::EventsCommitmentTape,
                     TableKind::CastListCommitmentTape,
                     TableKind::SelfProgIdTape,
+                    TableKind::HintTape,
+                    #[cfg(feature = "poseidon2")]
+                    TableKind::OutputTape,
                 ],
                 0..
             )
@@ -905,8 +1454,80 @@ impl Lookups for StorageDeviceToCpuTable {
     }
 }
 
+pub struct EventEmissionCpuTable;
+
+impl Lookups for EventEmissionCpuTable {
+    type Row = EventEmissionCtl<Column>;
+
+    fn lookups_with_typed_output() -> CrossTableLookupWithTypedOutput<Self::Row> {
+        CrossTableLookupWithTypedOutput::new(vec![event_emission::columns::lookup_for_cpu()], vec![
+            cpu::columns::lookup_for_event_emission(),
+        ])
+    }
+}
+
+#[cfg(feature = "secp256k1")]
+pub struct Secp256k1CpuTable;
+
+#[cfg(feature = "secp256k1")]
+impl Lookups for Secp256k1CpuTable {
+    type Row = Secp256k1Ctl<Column>;
+
+    fn lookups_with_typed_output() -> CrossTableLookupWithTypedOutput<Self::Row> {
+        CrossTableLookupWithTypedOutput::new(
+            vec![crate::secp256k1::columns::lookup_for_cpu()],
+            vec![crate::cpu::columns::lookup_for_secp256k1()],
+        )
+    }
+}
+
+#[cfg(feature = "u256")]
+pub struct U256CpuTable;
+
+#[cfg(feature = "u256")]
+impl Lookups for U256CpuTable {
+    type Row = U256Ctl<Column>;
+
+    fn lookups_with_typed_output() -> CrossTableLookupWithTypedOutput<Self::Row> {
+        CrossTableLookupWithTypedOutput::new(vec![crate::u256::columns::lookup_for_cpu()], vec![
+            crate::cpu::columns::lookup_for_u256(),
+        ])
+    }
+}
+
+#[cfg(feature = "memcopy")]
+pub struct MemCopyCpuTable;
+
+#[cfg(feature = "memcopy")]
+impl Lookups for MemCopyCpuTable {
+    type Row = MemCopyCtl<Column>;
+
+    fn lookups_with_typed_output() -> CrossTableLookupWithTypedOutput<Self::Row> {
+        CrossTableLookupWithTypedOutput::new(vec![crate::memcopy::columns::lookup_for_cpu()], vec![
+            crate::cpu::columns::lookup_for_memcopy(),
+        ])
+    }
+}
+
+#[cfg(feature = "goldilocks")]
+pub struct GoldilocksCpuTable;
+
+#[cfg(feature = "goldilocks")]
+impl Lookups for GoldilocksCpuTable {
+    type Row = GoldilocksCtl<Column>;
+
+    fn lookups_with_typed_output() -> CrossTableLookupWithTypedOutput<Self::Row> {
+        CrossTableLookupWithTypedOutput::new(
+            vec![crate::goldilocks::columns::lookup_for_cpu()],
+            vec![crate::cpu::columns::lookup_for_goldilocks()],
+        )
+    }
+}
+
+#[cfg(feature = "poseidon2")]
 pub struct Poseidon2SpongeCpuTable;
 
+#[cfg(feature = "poseidon2")]
 impl Lookups for Poseidon2SpongeCpuTable {
     type Row = Poseidon2SpongeCtl<Column>;
 
@@ -918,21 +1539,30 @@ impl Lookups for Poseidon2SpongeCpuTable {
     }
 }
 
+#[cfg(feature = "poseidon2")]
 pub struct Poseidon2Poseidon2SpongeTable;
 
+#[cfg(feature = "poseidon2")]
 impl Lookups for Poseidon2Poseidon2SpongeTable {
     type Row = Poseidon2StateCtl<Column>;
 
     fn lookups_with_typed_output() -> CrossTableLookupWithTypedOutput<Self::Row> {
         CrossTableLookupWithTypedOutput::new(
             vec![crate::poseidon2::columns::lookup_for_sponge()],
-            vec![crate::poseidon2_sponge::columns::lookup_for_poseidon2()],
+            vec![
+                crate::poseidon2_sponge::columns::lookup_for_poseidon2(),
+                crate::public_tape_hash::columns::lookup_for_poseidon2(),
+                crate::private_tape_hash::columns::lookup_for_poseidon2(),
+                crate::output_tape::columns::lookup_for_poseidon2(),
+            ],
         )
     }
 }
 
+#[cfg(feature = "poseidon2")]
 pub struct Poseidon2OutputBytesPoseidon2SpongeTable;
 
+#[cfg(feature = "poseidon2")]
 impl Lookups for Poseidon2OutputBytesPoseidon2SpongeTable {
     type Row = Poseidon2OutputBytesCtl<Column>;
 
@@ -944,6 +1574,51 @@ impl Lookups for Poseidon2OutputBytesPoseidon2SpongeTable {
     }
 }
 
+#[cfg(feature = "poseidon2")]
+pub struct PublicTapeHashLookupTable;
+
+#[cfg(feature = "poseidon2")]
+impl Lookups for PublicTapeHashLookupTable {
+    type Row = TapeByteCtl<Column>;
+
+    fn lookups_with_typed_output() -> CrossTableLookupWithTypedOutput<Self::Row> {
+        CrossTableLookupWithTypedOutput::new(
+            vec![crate::storage_device::columns::public_tape_lookup_in_tape_hash()],
+            crate::public_tape_hash::columns::lookup_for_public_tape(),
+        )
+    }
+}
+
+#[cfg(feature = "poseidon2")]
+pub struct PrivateTapeHashLookupTable;
+
+#[cfg(feature = "poseidon2")]
+impl Lookups for PrivateTapeHashLookupTable {
+    type Row = TapeByteCtl<Column>;
+
+    fn lookups_with_typed_output() -> CrossTableLookupWithTypedOutput<Self::Row> {
+        CrossTableLookupWithTypedOutput::new(
+            vec![crate::storage_device::columns::private_tape_lookup_in_tape_hash()],
+            crate::private_tape_hash::columns::lookup_for_private_tape(),
+        )
+    }
+}
+
+#[cfg(feature = "poseidon2")]
+pub struct OutputTapeHashLookupTable;
+
+#[cfg(feature = "poseidon2")]
+impl Lookups for OutputTapeHashLookupTable {
+    type Row = TapeByteCtl<Column>;
+
+    fn lookups_with_typed_output() -> CrossTableLookupWithTypedOutput<Self::Row> {
+        CrossTableLookupWithTypedOutput::new(
+            vec![crate::storage_device::columns::output_tape_lookup_in_tape_hash()],
+            crate::output_tape::columns::lookup_for_output_tape(),
+        )
+    }
+}
+
 pub struct EventCommitmentTapeIOLookupTable;
 
 impl Lookups for EventCommitmentTapeIOLookupTable {
@@ -969,3 +1644,33 @@ impl Lookups for CastlistCommitmentTapeIOLookupTable {
         )
     }
 }
+
+pub struct SelfProgIdTapeIOLookupTable;
+
+impl Lookups for SelfProgIdTapeIOLookupTable {
+    type Row = TapeCommitmentCTL<Column>;
+
+    fn lookups_with_typed_output() -> CrossTableLookupWithTypedOutput<Self::Row> {
+        CrossTableLookupWithTypedOutput::new(
+            vec![crate::storage_device::columns::self_prog_id_lookup_in_tape_commitments()],
+            vec![crate::tape_commitments::columns::lookup_for_self_prog_id_tape()],
+        )
+    }
+}
+
+pub struct EventCountersLookupTable;
+
+impl Lookups for EventCountersLookupTable {
+    type Row = EventCounterCtl<Column>;
+
+    fn lookups_with_typed_output() -> CrossTableLookupWithTypedOutput<Self::Row> {
+        let looking: Vec<TableWithTypedOutput<_>> = chain![
+            cpu::columns::lookup_for_event_counters(),
+            [ops::blt_taken::columns::lookup_for_event_counters()],
+        ]
+        .collect();
+        CrossTableLookupWithTypedOutput::new(looking, vec![
+            crate::event_counters::columns::lookup_for_events(),
+        ])
+    }
+}
@@ -0,0 +1,338 @@
+//! Commitments to the fixed columns of tables, made once per config.
+//!
+//! Some tables start with columns that do not depend on the execution, like
+//! the values of the u8 range check, or the shift amounts and multipliers of
+//! the [`Bitshift`] table. [`PREPROCESSED_TABLES`] lists them. Proofs
+//! usually commit to these columns along with the rest of the trace, over and
+//! over. A prover that holds [`PreprocessedCommitments`] commits to them once
+//! per config instead, and every proof only commits to the columns that
+//! follow, see [`StaticTableCommitments::with_preprocessed`].
+//!
+//! Such a proof carries the [digest](PreprocessedCaps::digest) of the
+//! preprocessed commitments, but not the commitments themselves: the verifier
+//! checks the proof against [`PreprocessedCaps`] it knows, either
+//! [saved](PreprocessedCaps::save) by a preprocessing run it trusts, or
+//! computed from the config.
+//!
+//! [`StaticTableCommitments::with_preprocessed`]: super::prover::StaticTableCommitments::with_preprocessed
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use itertools::{chain, Itertools};
+use mozak_runner::vm::ExecutionRecord;
+use plonky2::field::extension::{Extendable, FieldExtension};
+use plonky2::field::polynomial::PolynomialValues;
+use plonky2::field::types::Field;
+use plonky2::fri::oracle::PolynomialBatch;
+use plonky2::fri::structure::{FriBatchInfo, FriInstanceInfo, FriOracleInfo, FriPolynomialInfo};
+use plonky2::hash::hash_types::RichField;
+use plonky2::hash::merkle_tree::MerkleCap;
+use plonky2::plonk::config::{GenericConfig, Hasher};
+use serde::{Deserialize, Serialize};
+use starky::config::StarkConfig;
+use starky::stark::Stark;
+
+use super::mozak_stark::{all_kind, TableKind, TableKindArray};
+use super::prover::StaticTableCommitment;
+use crate::bitshift::columns::Bitshift;
+use crate::bitshift::generation::generate_shift_amount_trace;
+use crate::columns_view::NumberOfColumns;
+use crate::rangecheck_u8::generation::generate_rangecheck_u8_trace;
+use crate::register::generation::generate_register_init_trace;
+use crate::stark::utils::trace_rows_to_poly_values;
+
+/// The tables whose first columns do not depend on the execution, with the
+/// number of those columns.
+pub const PREPROCESSED_TABLES: [(TableKind, usize); 3] = [
+    // The values from 0 to 255.
+    (TableKind::RangeCheckU8, 1),
+    (TableKind::Bitshift, Bitshift::<()>::NUMBER_OF_COLUMNS),
+    // The register addresses.
+    (TableKind::RegisterInit, 1),
+];
+
+/// The number of fixed columns that `kind` starts with.
+#[must_use]
+pub fn num_fixed_columns(kind: TableKind) -> usize {
+    PREPROCESSED_TABLES
+        .iter()
+        .find(|(table, _)| *table == kind)
+        .map_or(0, |&(_, num_columns)| num_columns)
+}
+
+/// The fixed columns of `kind`, which the trace of every execution starts
+/// with.
+///
+/// We get them from the trace of an empty execution, so that they can not
+/// drift apart from trace generation.
+#[must_use]
+pub fn fixed_trace<F: RichField>(kind: TableKind) -> Vec<PolynomialValues<F>> {
+    let mut trace = match kind {
+        TableKind::RangeCheckU8 => trace_rows_to_poly_values(generate_rangecheck_u8_trace::<F>(
+            &[],
+            &[],
+            #[cfg(feature = "secp256k1")]
+            &[],
+            #[cfg(feature = "u256")]
+            &[],
+        )),
+        TableKind::Bitshift => trace_rows_to_poly_values(generate_shift_amount_trace::<F>(&[])),
+        TableKind::RegisterInit => trace_rows_to_poly_values(generate_register_init_trace::<F>(
+            &ExecutionRecord::default(),
+        )),
+        _ => vec![],
+    };
+    trace.truncate(num_fixed_columns(kind));
+    trace
+}
+
+/// Commitments to the fixed columns of the [`PREPROCESSED_TABLES`], for the
+/// prover.
+pub struct PreprocessedCommitments<F, C, const D: usize>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>, {
+    commitments: TableKindArray<Option<StaticTableCommitment<F, C, D>>>,
+    caps: PreprocessedCaps<F, C::Hasher>,
+}
+
+impl<F, C, const D: usize> PreprocessedCommitments<F, C, D>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    #[must_use]
+    pub fn new(config: &StarkConfig) -> Self {
+        let commitments = all_kind!(|kind| (num_fixed_columns(kind) > 0)
+            .then(|| StaticTableCommitment::new(fixed_trace(kind), config)));
+        let caps = PreprocessedCaps {
+            rate_bits: config.fri_config.rate_bits,
+            cap_height: config.fri_config.cap_height,
+            caps: commitments.each_ref().map(|commitment| {
+                commitment.as_ref().map_or_else(
+                    || MerkleCap(vec![]),
+                    |c| c.commitment.merkle_tree.cap.clone(),
+                )
+            }),
+        };
+        Self { commitments, caps }
+    }
+
+    /// Like [`PreprocessedCommitments::new`], but checks the result against
+    /// `known` caps, eg those [loaded](PreprocessedCaps::load) from disk.
+    ///
+    /// # Errors
+    /// Errors if the commitments for `config` do not match `known`.
+    pub fn new_checked(
+        config: &StarkConfig,
+        known: &PreprocessedCaps<F, C::Hasher>,
+    ) -> Result<Self> {
+        let preprocessed = Self::new(config);
+        anyhow::ensure!(
+            preprocessed.caps == *known,
+            "the preprocessed commitments do not match the known ones"
+        );
+        Ok(preprocessed)
+    }
+
+    #[must_use]
+    pub fn caps(&self) -> &PreprocessedCaps<F, C::Hasher> { &self.caps }
+
+    pub(crate) fn get(&self, kind: TableKind) -> Option<&PolynomialBatch<F, C, D>> {
+        self.commitments[kind].as_ref().map(|c| &c.commitment)
+    }
+
+    /// Whether the commitments were made with the FRI parameters of `config`,
+    /// and for the fixed columns that `trace` of `kind` starts with.
+    pub(crate) fn matches(
+        &self,
+        config: &StarkConfig,
+        kind: TableKind,
+        trace: &[PolynomialValues<F>],
+    ) -> bool {
+        self.caps.fits(config)
+            && self.commitments[kind].as_ref().map_or(true, |c| {
+                trace.get(..c.trace.len()) == Some(c.trace.as_slice())
+            })
+    }
+}
+
+/// The Merkle caps of [`PreprocessedCommitments`], which is all that the
+/// verifier needs to know about them.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct PreprocessedCaps<F: RichField, H: Hasher<F>> {
+    pub rate_bits: usize,
+    pub cap_height: usize,
+    /// The caps of the fixed columns, empty for the tables that have none.
+    pub caps: TableKindArray<MerkleCap<F, H>>,
+}
+
+impl<F: RichField, H: Hasher<F>> PreprocessedCaps<F, H> {
+    /// Computes the caps for `config`.
+    #[must_use]
+    pub fn new<C, const D: usize>(config: &StarkConfig) -> Self
+    where
+        F: Extendable<D>,
+        C: GenericConfig<D, F = F, Hasher = H>, {
+        PreprocessedCommitments::<F, C, D>::new(config).caps
+    }
+
+    /// The cap of the fixed columns of `kind`, if it has any.
+    #[must_use]
+    pub fn get(&self, kind: TableKind) -> Option<&MerkleCap<F, H>> {
+        Some(&self.caps[kind]).filter(|cap| !cap.0.is_empty())
+    }
+
+    /// Whether the caps were made with the FRI parameters of `config`.
+    #[must_use]
+    pub fn fits(&self, config: &StarkConfig) -> bool {
+        self.rate_bits == config.fri_config.rate_bits
+            && self.cap_height == config.fri_config.cap_height
+    }
+
+    /// Identifies the caps in the proofs made with them.
+    #[must_use]
+    pub fn digest(&self) -> H::Hash {
+        let params = [self.rate_bits, self.cap_height].map(F::from_canonical_usize);
+        H::hash_no_pad(&chain!(params, self.caps.iter().flat_map(MerkleCap::flatten)).collect_vec())
+    }
+
+    /// Writes the caps to `path`, for verifiers to [load](Self::load).
+    ///
+    /// # Errors
+    /// Errors if the file can not be written.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        fs::write(path, serde_json::to_vec(self)?)
+            .with_context(|| format!("writing the preprocessed caps to {}", path.display()))
+    }
+
+    /// Reads the caps that [`PreprocessedCaps::save`] wrote to `path`.
+    ///
+    /// # Errors
+    /// Errors if the file can not be read, or does not hold caps.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let bytes = fs::read(path)
+            .with_context(|| format!("reading the preprocessed caps from {}", path.display()))?;
+        serde_json::from_slice(&bytes)
+            .with_context(|| format!("parsing the preprocessed caps in {}", path.display()))
+    }
+}
+
+/// The polynomials that the FRI proof of a table opens, when the first
+/// `num_fixed` columns of its trace are committed to apart from the others.
+///
+/// The fixed and the other columns are two oracles, but open together as the
+/// trace, in the order of [`StarkOpeningSet::to_fri_openings`].
+///
+/// [`StarkOpeningSet::to_fri_openings`]: super::proof::StarkOpeningSet::to_fri_openings
+pub(crate) fn fri_instance<F, S, const D: usize>(
+    stark: &S,
+    num_fixed: usize,
+    zeta: F::Extension,
+    g: F,
+    num_ctl_zs: usize,
+    config: &StarkConfig,
+) -> FriInstanceInfo<F, D>
+where
+    F: RichField + Extendable<D>,
+    S: Stark<F, D>, {
+    let num_polys = [
+        num_fixed,
+        S::COLUMNS - num_fixed,
+        num_ctl_zs,
+        stark.num_quotient_polys(config),
+    ];
+    let polys = |oracle: usize| FriPolynomialInfo::from_range(oracle, 0..num_polys[oracle]);
+    let trace = chain!(polys(0), polys(1)).collect_vec();
+    FriInstanceInfo {
+        oracles: num_polys
+            .map(|num_polys| FriOracleInfo {
+                num_polys,
+                blinding: false,
+            })
+            .to_vec(),
+        batches: vec![
+            FriBatchInfo {
+                point: zeta,
+                polynomials: chain!(trace.clone(), polys(2), polys(3)).collect(),
+            },
+            FriBatchInfo {
+                point: zeta.scalar_mul(g),
+                polynomials: chain!(trace, polys(2)).collect(),
+            },
+            FriBatchInfo {
+                point: F::Extension::from_basefield(g.inverse()),
+                polynomials: polys(2),
+            },
+        ],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mozak_runner::code;
+    use mozak_runner::instruction::Instruction;
+    use plonky2::fri::FriConfig;
+    use plonky2::util::timing::TimingTree;
+    use starky::config::StarkConfig;
+    use tempfile::NamedTempFile;
+
+    use super::{PreprocessedCaps, PreprocessedCommitments};
+    use crate::stark::error::VerifierError;
+    use crate::stark::mozak_stark::{MozakStark, PublicInputs};
+    use crate::stark::prover::{prove_with_static_commitments, StaticTableCommitments};
+    use crate::stark::verifier::{verify_proof, verify_proof_with_preprocessed};
+    use crate::test_utils::{fast_test_config, C, D, F};
+    use crate::utils::from_u32;
+
+    #[test]
+    fn prove_with_preprocessed_commitments() -> anyhow::Result<()> {
+        let stark = MozakStark::default();
+        let config = fast_test_config();
+        let (program, record) =
+            code::execute([Instruction::sll(5, 6, 7)], &[], &[(6, 100), (7, 3)]);
+        let preprocessed = PreprocessedCommitments::<F, C, D>::new(&config);
+        let file = NamedTempFile::new()?;
+        preprocessed.caps().save(file.path())?;
+        let known = PreprocessedCaps::load(file.path())?;
+        assert_eq!(&known, preprocessed.caps());
+
+        // Caps made with other FRI parameters do not verify the proofs.
+        let other = PreprocessedCaps::new::<C, D>(&StarkConfig {
+            fri_config: FriConfig {
+                cap_height: config.fri_config.cap_height + 1,
+                ..config.fri_config.clone()
+            },
+            ..config.clone()
+        });
+
+        let static_commitments =
+            StaticTableCommitments::new(&program, &config).with_preprocessed(preprocessed);
+        // The same commitments serve several proofs.
+        for _ in 0..2 {
+            let proof = prove_with_static_commitments(
+                &program,
+                &record,
+                &stark,
+                &config,
+                PublicInputs::new(from_u32(program.entry_point)),
+                Some(&static_commitments),
+                &mut TimingTree::default(),
+            )?;
+            assert_eq!(proof.preprocessed, Some(known.digest()));
+            verify_proof_with_preprocessed(&stark, proof.clone(), &config, Some(&known))?;
+            // Without known caps, the verifier computes them from the config.
+            verify_proof(&stark, proof.clone(), &config)?;
+            assert!(matches!(
+                verify_proof_with_preprocessed(&stark, proof, &config, Some(&other)),
+                Err(VerifierError::PreprocessedMismatch)
+            ));
+        }
+        Ok(())
+    }
+}
@@ -1,8 +1,11 @@
+use anyhow::Result;
 use itertools::{chain, Itertools};
 use mozak_sdk::common::types::ProgramIdentifier;
 use mozak_sdk::core::constants::DIGEST_BYTES;
 use plonky2::batch_fri::oracle::BatchFriOracle;
 use plonky2::field::extension::{Extendable, FieldExtension};
+use plonky2::field::polynomial::PolynomialCoeffs;
+use plonky2::field::types::Field;
 use plonky2::fri::oracle::PolynomialBatch;
 use plonky2::fri::proof::{FriChallenges, FriChallengesTarget, FriProof, FriProofTarget};
 use plonky2::fri::structure::{
@@ -20,14 +23,24 @@ use plonky2_maybe_rayon::*;
 use serde::{Deserialize, Serialize};
 use starky::config::StarkConfig;
 
-use super::mozak_stark::{all_kind, PublicInputs, TableKind, TableKindArray};
-use crate::public_sub_table::PublicSubTableValues;
+use super::mozak_stark::{all_kind, MozakStark, PublicInputs, TableKind, TableKindArray};
+use crate::public_sub_table::{public_value, PublicSubTableValues, PublicValue};
 use crate::stark::permutation::challenge::{GrandProductChallengeSet, GrandProductChallengeTrait};
+use crate::stark::prover::get_library_id;
+use crate::stark::security::ProofConfig;
+use crate::stark::transcript::Transcript;
 
 #[allow(clippy::module_name_repetitions)]
 impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize> AllProof<F, C, D> {
+    /// # Panics
+    ///
+    /// Panics if the proof skipped a table, which has no trace to have a
+    /// degree.
     pub fn degree_bits(&self, config: &StarkConfig) -> TableKindArray<usize> {
-        all_kind!(|kind| self.proofs[kind].recover_degree_bits(config))
+        all_kind!(|kind| {
+            assert!(!self.skipped[kind], "the proof skipped {kind:?}");
+            self.proofs[kind].recover_degree_bits(config)
+        })
     }
 }
 
@@ -60,11 +73,82 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize> S
 
     pub fn num_ctl_zs(&self) -> usize { self.openings.ctl_zs_last.len() }
 
-    /// Computes all Fiat-Shamir challenges used in the STARK proof.
+    /// The stand-in for the proof of a table that was skipped, see
+    /// [`AllProof::skipped`]: no caps and no FRI proof, and openings of
+    /// `num_ctl_zs` cross-table lookup `Z` polynomials that are all zero, as
+    /// those of an empty table would be.
+    #[must_use]
+    pub fn skipped(num_ctl_zs: usize) -> Self {
+        Self {
+            trace_cap: MerkleCap(vec![]),
+            ctl_zs_cap: MerkleCap(vec![]),
+            quotient_polys_cap: MerkleCap(vec![]),
+            openings: StarkOpeningSet {
+                local_values: vec![],
+                next_values: vec![],
+                ctl_zs: vec![F::Extension::ZERO; num_ctl_zs],
+                ctl_zs_next: vec![F::Extension::ZERO; num_ctl_zs],
+                ctl_zs_last: vec![F::ZERO; num_ctl_zs],
+                quotient_polys: vec![],
+            },
+            opening_proof: FriProof {
+                commit_phase_merkle_caps: vec![],
+                query_round_proofs: vec![],
+                final_poly: PolynomialCoeffs { coeffs: vec![] },
+                pow_witness: F::ZERO,
+            },
+        }
+    }
+
+    /// Whether this is [`StarkProof::skipped`] for some number of `Z`
+    /// polynomials.
+    #[must_use]
+    pub fn is_skipped(&self) -> bool {
+        let StarkProof {
+            trace_cap,
+            ctl_zs_cap,
+            quotient_polys_cap,
+            openings:
+                StarkOpeningSet {
+                    local_values,
+                    next_values,
+                    ctl_zs,
+                    ctl_zs_next,
+                    ctl_zs_last,
+                    quotient_polys,
+                },
+            opening_proof:
+                FriProof {
+                    commit_phase_merkle_caps,
+                    query_round_proofs,
+                    final_poly,
+                    pow_witness,
+                },
+        } = self;
+        let num_ctl_zs = ctl_zs_last.len();
+        [trace_cap, ctl_zs_cap, quotient_polys_cap]
+            .iter()
+            .all(|cap| cap.0.is_empty())
+            && local_values.is_empty()
+            && next_values.is_empty()
+            && quotient_polys.is_empty()
+            && ctl_zs.len() == num_ctl_zs
+            && ctl_zs_next.len() == num_ctl_zs
+            && chain!(ctl_zs, ctl_zs_next).all(|z| z.is_zero())
+            && ctl_zs_last.iter().all(|z| z.is_zero())
+            && commit_phase_merkle_caps.is_empty()
+            && query_round_proofs.is_empty()
+            && final_poly.coeffs.is_empty()
+            && pow_witness.is_zero()
+    }
+
+    /// Computes all Fiat-Shamir challenges used in the STARK proof, and
+    /// records them into `transcript`.
     pub(crate) fn get_challenges(
         &self,
         challenger: &mut Challenger<F, C::Hasher>,
         config: &StarkConfig,
+        transcript: &Transcript,
     ) -> StarkProofChallenges<F, D> {
         let degree_bits = self.recover_degree_bits(config);
 
@@ -85,24 +169,33 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize> S
         let num_challenges = config.num_challenges;
 
         challenger.observe_cap(ctl_zs_cap);
+        transcript.observe_cap("ctl zs cap", ctl_zs_cap);
 
         let stark_alphas = challenger.get_n_challenges(num_challenges);
+        transcript.sample("stark alphas", &stark_alphas);
 
         challenger.observe_cap(quotient_polys_cap);
+        transcript.observe_cap("quotient polys cap", quotient_polys_cap);
         let stark_zeta = challenger.get_extension_challenge::<D>();
+        transcript.sample_extension::<F, D>("stark zeta", &[stark_zeta]);
 
-        challenger.observe_openings(&openings.to_fri_openings());
+        let fri_openings = openings.to_fri_openings();
+        challenger.observe_openings(&fri_openings);
+        transcript.observe_openings(&fri_openings);
+
+        let fri_challenges = challenger.fri_challenges::<C, D>(
+            commit_phase_merkle_caps,
+            final_poly,
+            *pow_witness,
+            degree_bits,
+            &config.fri_config,
+        );
+        transcript.record_fri(&self.opening_proof, &fri_challenges);
 
         StarkProofChallenges {
             stark_alphas,
             stark_zeta,
-            fri_challenges: challenger.fri_challenges::<C, D>(
-                commit_phase_merkle_caps,
-                final_poly,
-                *pow_witness,
-                degree_bits,
-                &config.fri_config,
-            ),
+            fri_challenges,
         }
     }
 }
@@ -227,9 +320,13 @@ pub struct StarkOpeningSet<F: RichField + Extendable<D>, const D: usize> {
 }
 
 impl<F: RichField + Extendable<D>, const D: usize> StarkOpeningSet<F, D> {
+    /// Opens the commitments at `zeta` and the points around it. The trace is
+    /// opened as the polynomials of `fixed_commitment`, if any, followed by
+    /// those of `trace_commitment`.
     pub fn new<C: GenericConfig<D, F = F>>(
         zeta: F::Extension,
         g: F,
+        fixed_commitment: Option<&PolynomialBatch<F, C, D>>,
         trace_commitment: &PolynomialBatch<F, C, D>,
         ctl_zs_commitment: &PolynomialBatch<F, C, D>,
         quotient_commitment: &PolynomialBatch<F, C, D>,
@@ -247,10 +344,15 @@ impl<F: RichField + Extendable<D>, const D: usize> StarkOpeningSet<F, D> {
                 .map(|p| p.eval(z))
                 .collect::<Vec<_>>()
         };
+        let eval_trace = |z: F::Extension| {
+            chain!(fixed_commitment, [trace_commitment])
+                .flat_map(|c| eval_commitment(z, c))
+                .collect::<Vec<_>>()
+        };
         let zeta_next = zeta.scalar_mul(g);
         Self {
-            local_values: eval_commitment(zeta, trace_commitment),
-            next_values: eval_commitment(zeta_next, trace_commitment),
+            local_values: eval_trace(zeta),
+            next_values: eval_trace(zeta_next),
             ctl_zs: eval_commitment(zeta, ctl_zs_commitment),
             ctl_zs_next: eval_commitment(zeta_next, ctl_zs_commitment),
             ctl_zs_last: eval_commitment_base(
@@ -370,6 +472,20 @@ pub struct AllProof<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, co
     pub public_inputs: PublicInputs<F>,
     pub public_sub_table_values: TableKindArray<Vec<PublicSubTableValues<F>>>,
     pub program_id: ProgramIdentifier,
+    /// Tables that the prover left out because the execution did not use
+    /// them, see [`MozakStark::skip_unused`]. Their proofs are
+    /// [`StarkProof::skipped`].
+    #[serde(default)]
+    pub skipped: TableKindArray<bool>,
+    /// The [digest](crate::stark::preprocessed::PreprocessedCaps::digest) of
+    /// the preprocessed commitments to the fixed columns of tables, if the
+    /// proof does not commit to those columns itself.
+    #[serde(default)]
+    pub preprocessed: Option<<C::Hasher as Hasher<F>>::Hash>,
+    /// The config that made the proof. Proofs written before proofs recorded
+    /// it have none.
+    #[serde(default)]
+    pub config: Option<ProofConfig>,
 }
 
 #[allow(clippy::module_name_repetitions)]
@@ -384,27 +500,47 @@ pub struct BatchProof<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>,
 }
 
 pub(crate) struct AllProofChallenges<F: RichField + Extendable<D>, const D: usize> {
-    pub stark_challenges: TableKindArray<StarkProofChallenges<F, D>>,
+    /// `None` for the skipped tables.
+    pub stark_challenges: TableKindArray<Option<StarkProofChallenges<F, D>>>,
     pub ctl_challenges: GrandProductChallengeSet<F>,
 }
 
 impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize> AllProof<F, C, D> {
-    /// Computes all Fiat-Shamir challenges used in the STARK proof.
-    pub(crate) fn get_challenges(&self, config: &StarkConfig) -> AllProofChallenges<F, D> {
+    /// Computes all Fiat-Shamir challenges used in the STARK proof, and
+    /// records them into `transcript`.
+    pub(crate) fn get_challenges(
+        &self,
+        config: &StarkConfig,
+        transcript: &Transcript,
+    ) -> AllProofChallenges<F, D> {
         let mut challenger = Challenger::<F, C::Hasher>::new();
 
-        for proof in &self.proofs {
+        for (proof, kind) in &self.proofs.each_ref().with_kind() {
             challenger.observe_cap(&proof.trace_cap);
+            transcript.observe_cap(&format!("trace cap of {kind:?}"), &proof.trace_cap);
         }
+        observe_skipped(&mut challenger, &self.skipped, transcript);
+        observe_preprocessed::<F, C::Hasher>(
+            &mut challenger,
+            self.preprocessed.as_ref(),
+            transcript,
+        );
 
         // TODO: Observe public values.
 
         let ctl_challenges = challenger.get_grand_product_challenge_set(config.num_challenges);
+        transcript.sample_ctl_challenges(&ctl_challenges);
         challenger.compact();
 
         AllProofChallenges {
             stark_challenges: all_kind!(|kind| {
-                self.proofs[kind].get_challenges(&mut challenger.clone(), config)
+                (!self.skipped[kind]).then(|| {
+                    self.proofs[kind].get_challenges(
+                        &mut challenger.clone(),
+                        config,
+                        &transcript.for_table(kind),
+                    )
+                })
             }),
             ctl_challenges,
         }
@@ -418,6 +554,38 @@ impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize> A
     }
 }
 
+/// Adds the tables that a proof skipped to the challenger entropy pool, so
+/// that the cross-table lookup challenges depend on them.
+///
+/// Proofs that skip nothing keep the transcript of the proofs written before
+/// tables could be skipped.
+pub(crate) fn observe_skipped<F: RichField, H: Hasher<F>>(
+    challenger: &mut Challenger<F, H>,
+    skipped: &TableKindArray<bool>,
+    transcript: &Transcript,
+) {
+    if skipped.iter().any(|&skipped| skipped) {
+        let skipped = skipped.iter().map(|&s| F::from_bool(s)).collect_vec();
+        challenger.observe_elements(&skipped);
+        transcript.observe("skipped tables", &skipped);
+    }
+}
+
+/// Adds the digest of the preprocessed commitments that a proof uses, if any,
+/// to the challenger entropy pool, so that the cross-table lookup challenges
+/// depend on the fixed columns too.
+pub(crate) fn observe_preprocessed<F: RichField, H: Hasher<F>>(
+    challenger: &mut Challenger<F, H>,
+    preprocessed: Option<&H::Hash>,
+    transcript: &Transcript,
+) {
+    if let Some(digest) = preprocessed {
+        let digest = digest.to_vec();
+        challenger.observe_elements(&digest);
+        transcript.observe("preprocessed digest", &digest);
+    }
+}
+
 macro_rules! impl_proof_common {
     ($struct_name:ident) => {
         impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
@@ -438,16 +606,58 @@ macro_rules! impl_proof_common {
                 )
             }
 
+            /// Identifies the library linked into the proven program, see
+            /// [`get_library_id`].
+            ///
+            /// Like the program id, it can only be trusted once the proof is
+            /// verified.
+            #[must_use]
+            pub fn library_id(&self) -> ProgramIdentifier {
+                get_library_id::<F, C, D>(
+                    &self.proofs[TableKind::LibraryProgram].trace_cap,
+                    &self.proofs[TableKind::LibraryElfMemoryInit].trace_cap,
+                )
+            }
+
+            /// Returns the public value `V` of the proof, see
+            /// [`PublicValue`].
+            ///
+            /// The value can only be trusted once the proof is verified
+            /// against `mozak_stark`.
+            ///
+            /// # Errors
+            ///
+            /// Errors if `mozak_stark` does not make `V` public, or the proof
+            /// does not carry a well-formed `V`.
+            pub fn public_value<V: PublicValue<F>>(
+                &self,
+                mozak_stark: &MozakStark<F, D>,
+            ) -> Result<V> {
+                public_value(
+                    &mozak_stark.public_sub_tables,
+                    &self.public_sub_table_values,
+                )
+            }
+
             #[allow(dead_code)]
             pub(crate) fn get_program_hash_bytes(&self) -> [F; DIGEST_BYTES] {
                 let entry_point = self.public_inputs.entry_point;
                 let program_rom_trace_cap_hash = self.hash_trace_cap(TableKind::Program);
                 let elf_memory_init_trace_cap_hash = self.hash_trace_cap(TableKind::ElfMemoryInit);
-                let program_hash = <<C as GenericConfig<D>>::InnerHasher as Hasher<F>>::hash_pad(
+                let hash_pad_func = <<C as GenericConfig<D>>::InnerHasher as Hasher<F>>::hash_pad;
+                let library_hash = hash_pad_func(
+                    &chain!(
+                        self.hash_trace_cap(TableKind::LibraryProgram).elements,
+                        self.hash_trace_cap(TableKind::LibraryElfMemoryInit).elements,
+                    )
+                    .collect_vec(),
+                );
+                let program_hash = hash_pad_func(
                     &chain!(
                         [entry_point],
                         program_rom_trace_cap_hash.elements,
                         elf_memory_init_trace_cap_hash.elements,
+                        library_hash.elements,
                     )
                     .collect_vec(),
                 );
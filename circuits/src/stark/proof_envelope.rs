@@ -0,0 +1,196 @@
+//! Versioned serialization of proofs.
+//!
+//! Proofs are persisted as a [`ProofEnvelope`], which records the
+//! [`ProofVersion`] of the format next to the proof. [`read_versioned`] reads
+//! every version listed in [`ProofVersion::ALL`], so that proofs written by
+//! older releases keep verifying. When the serialized form of a proof
+//! changes, add a version, and teach [`read_versioned`] to upgrade the
+//! previous one.
+
+use std::fmt::{self, Display, Formatter};
+
+use anyhow::{bail, Context, Result};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Version of the format in which proofs are persisted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(into = "u32", try_from = "u32")]
+pub enum ProofVersion {
+    /// A bare proof, as written before proofs carried a version.
+    V0,
+    /// A [`ProofEnvelope`] around the proof.
+    V1,
+    /// Proofs list the tables they skipped, see
+    /// [`AllProof::skipped`](super::proof::AllProof::skipped). Earlier
+    /// versions skip none.
+    V2,
+    /// Proofs may use preprocessed commitments, see
+    /// [`AllProof::preprocessed`](super::proof::AllProof::preprocessed).
+    /// Earlier versions use none.
+    V3,
+    /// Proofs record the config that made them, see
+    /// [`AllProof::config`](super::proof::AllProof::config). Earlier versions
+    /// record none.
+    V4,
+}
+
+impl ProofVersion {
+    /// All versions that [`read_versioned`] reads.
+    pub const ALL: [Self; 5] = [Self::V0, Self::V1, Self::V2, Self::V3, Self::V4];
+    /// The version that [`ProofEnvelope::new`] writes.
+    pub const CURRENT: Self = Self::V4;
+}
+
+impl From<ProofVersion> for u32 {
+    fn from(version: ProofVersion) -> Self {
+        match version {
+            ProofVersion::V0 => 0,
+            ProofVersion::V1 => 1,
+            ProofVersion::V2 => 2,
+            ProofVersion::V3 => 3,
+            ProofVersion::V4 => 4,
+        }
+    }
+}
+
+impl TryFrom<u32> for ProofVersion {
+    type Error = String;
+
+    fn try_from(version: u32) -> Result<Self, Self::Error> {
+        Self::ALL
+            .into_iter()
+            .find(|&known| u32::from(known) == version)
+            .ok_or_else(|| {
+                format!(
+                    "unsupported proof version {version}, this release reads versions up to {}",
+                    Self::CURRENT
+                )
+            })
+    }
+}
+
+impl Display for ProofVersion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { write!(f, "{}", u32::from(*self)) }
+}
+
+/// A proof together with the version of its format, see the module docs.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ProofEnvelope<P> {
+    pub version: ProofVersion,
+    pub proof: P,
+}
+
+impl<P> ProofEnvelope<P> {
+    /// Wraps `proof` for writing in the [`ProofVersion::CURRENT`] format.
+    #[must_use]
+    pub fn new(proof: P) -> Self {
+        Self {
+            version: ProofVersion::CURRENT,
+            proof,
+        }
+    }
+}
+
+/// Reads a proof serialized in any of the [`ProofVersion::ALL`] formats, and
+/// returns it together with the version it was read from.
+///
+/// # Errors
+/// Errors if the version is unknown, or the proof does not deserialize.
+pub fn read_versioned<P: DeserializeOwned>(value: Value) -> Result<(ProofVersion, P)> {
+    let Value::Object(mut fields) = value else {
+        bail!("expected a proof object")
+    };
+    let Some(version) = fields.remove("version") else {
+        let proof =
+            serde_json::from_value(Value::Object(fields)).context("reading a version 0 proof")?;
+        return Ok((ProofVersion::V0, proof));
+    };
+    let version: ProofVersion = serde_json::from_value(version)?;
+    let proof = fields
+        .remove("proof")
+        .with_context(|| format!("version {version} proof without a proof"))?;
+    match version {
+        // Bare proofs never have a version field.
+        ProofVersion::V0 => bail!("version 0 proofs are not enveloped"),
+        // Version 1 proofs lack the skipped tables, versions before 3 the
+        // preprocessed commitments, and versions before 4 the config, which
+        // all default to none.
+        ProofVersion::V1 | ProofVersion::V2 | ProofVersion::V3 | ProofVersion::V4 => Ok((
+            version,
+            serde_json::from_value(proof)
+                .with_context(|| format!("reading a version {version} proof"))?,
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mozak_runner::code;
+    use mozak_runner::instruction::Instruction;
+    use plonky2::util::timing::TimingTree;
+    use serde_json::{json, Value};
+
+    use super::{read_versioned, ProofEnvelope, ProofVersion};
+    use crate::stark::mozak_stark::{MozakStark, PublicInputs};
+    use crate::stark::proof::AllProof;
+    use crate::stark::prover::prove;
+    use crate::stark::verifier::verify_proof;
+    use crate::test_utils::{fast_test_config, C, D, F};
+    use crate::utils::from_u32;
+
+    /// Serializes `proof` the way releases writing `version` did.
+    fn write_as(version: ProofVersion, proof: &AllProof<F, C, D>) -> Value {
+        let mut value = match version {
+            ProofVersion::V0 => serde_json::to_value(proof),
+            ProofVersion::V1 | ProofVersion::V2 | ProofVersion::V3 | ProofVersion::V4 =>
+                serde_json::to_value(ProofEnvelope { version, proof }),
+        }
+        .unwrap();
+        let bare = if version == ProofVersion::V0 {
+            &mut value
+        } else {
+            &mut value["proof"]
+        };
+        let bare = bare.as_object_mut().unwrap();
+        if version < ProofVersion::V2 {
+            bare.remove("skipped");
+        }
+        if version < ProofVersion::V3 {
+            bare.remove("preprocessed");
+        }
+        if version < ProofVersion::V4 {
+            bare.remove("config");
+        }
+        value
+    }
+
+    #[test]
+    fn read_all_versions() -> anyhow::Result<()> {
+        let (program, record) = code::execute([Instruction::addi(5, 6, 7)], &[], &[(6, 100)]);
+        let stark = MozakStark::default();
+        let config = fast_test_config();
+        let proof = prove::<F, C, D>(
+            &program,
+            &record,
+            &stark,
+            &config,
+            PublicInputs::new(from_u32(program.entry_point)),
+            &mut TimingTree::default(),
+        )?;
+        for version in ProofVersion::ALL {
+            let (read_version, read_proof): (_, AllProof<F, C, D>) =
+                read_versioned(write_as(version, &proof))?;
+            assert_eq!(read_version, version);
+            verify_proof(&stark, read_proof, &config)?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn reject_unknown_version() {
+        let error = read_versioned::<Value>(json!({ "version": 1000, "proof": {} })).unwrap_err();
+        assert!(error.to_string().contains("unsupported proof version 1000"));
+    }
+}
@@ -0,0 +1,428 @@
+//! Sizes of proofs, and sharing of the Merkle caps they repeat.
+//!
+//! Tables proven with the same parameters often commit to identical
+//! polynomials, eg tables of the same width that are empty and padded to the
+//! same height, and so to identical Merkle caps. [`SharedCapsProof`] stores
+//! each distinct cap once.
+//!
+//! A [`BatchProof`] goes further, and commits to all tables but the public
+//! ones together, in one Merkle tree per table height with a slice of leaves
+//! per table, opened by a single FRI proof. Small tables, like `RegisterInit`
+//! or `RangeCheckU8`, then only add their openings to the proof.
+
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use std::iter::Sum;
+use std::mem::size_of;
+use std::ops::Add;
+
+use anyhow::{ensure, Result};
+use itertools::chain;
+use plonky2::field::extension::Extendable;
+use plonky2::hash::hash_types::RichField;
+use plonky2::hash::merkle_proofs::MerkleProof;
+use plonky2::hash::merkle_tree::MerkleCap;
+use plonky2::plonk::config::{GenericConfig, GenericHashOut, Hasher};
+use serde::{Deserialize, Serialize};
+
+use super::mozak_stark::{TableKind, TableKindArray};
+use super::proof::{AllProof, BatchProof, StarkProof};
+
+/// Size in bytes of the parts of a table proof, counting field elements and
+/// hashes at their in-memory size.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ProofSize {
+    /// The trace, cross-table lookup `Z` and quotient caps.
+    pub caps: usize,
+    pub openings: usize,
+    /// The FRI commit phase caps, query rounds, final polynomial and proof of
+    /// work.
+    pub fri: usize,
+}
+
+impl ProofSize {
+    #[must_use]
+    pub fn total(&self) -> usize { self.caps + self.openings + self.fri }
+}
+
+impl Add for ProofSize {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            caps: self.caps + other.caps,
+            openings: self.openings + other.openings,
+            fri: self.fri + other.fri,
+        }
+    }
+}
+
+impl Sum for ProofSize {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self { iter.fold(Self::default(), Add::add) }
+}
+
+/// Per-table sizes of an [`AllProof`], see [`AllProof::size_report`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProofSizeReport {
+    pub tables: TableKindArray<ProofSize>,
+    /// Number of caps that repeat an earlier one.
+    pub repeated_caps: usize,
+    /// Bytes that [`SharedCapsProof`] saves by storing those caps once.
+    pub repeated_cap_bytes: usize,
+}
+
+impl ProofSizeReport {
+    #[must_use]
+    pub fn total(&self) -> ProofSize { self.tables.iter().copied().sum() }
+}
+
+impl Display for ProofSizeReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let row = |f: &mut Formatter<'_>, name: &str, size: &ProofSize| {
+            writeln!(
+                f,
+                "{name:<20} {:>10} {:>10} {:>10} {:>10}",
+                size.caps,
+                size.openings,
+                size.fri,
+                size.total()
+            )
+        };
+        writeln!(
+            f,
+            "{:<20} {:>10} {:>10} {:>10} {:>10}",
+            "table", "caps", "openings", "fri", "total"
+        )?;
+        for (size, kind) in &self.tables.each_ref().with_kind() {
+            row(f, &format!("{kind:?}"), size)?;
+        }
+        row(f, "all", &self.total())?;
+        write!(
+            f,
+            "{} repeated caps, {} bytes when shared",
+            self.repeated_caps, self.repeated_cap_bytes
+        )
+    }
+}
+
+fn cap_size<F: RichField, H: Hasher<F>>(cap: &MerkleCap<F, H>) -> usize { cap.len() * H::HASH_SIZE }
+
+fn merkle_proof_size<F: RichField, H: Hasher<F>>(proof: &MerkleProof<F, H>) -> usize {
+    proof.siblings.len() * H::HASH_SIZE
+}
+
+impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize> StarkProof<F, C, D> {
+    #[must_use]
+    pub fn size(&self) -> ProofSize {
+        let felt = size_of::<F>();
+        let openings = &self.openings;
+        let fri = &self.opening_proof;
+        let query_rounds: usize = fri
+            .query_round_proofs
+            .iter()
+            .map(|round| {
+                let initial: usize = round
+                    .initial_trees_proof
+                    .evals_proofs
+                    .iter()
+                    .map(|(evals, proof)| evals.len() * felt + merkle_proof_size(proof))
+                    .sum();
+                let steps: usize = round
+                    .steps
+                    .iter()
+                    .map(|step| step.evals.len() * D * felt + merkle_proof_size(&step.merkle_proof))
+                    .sum();
+                initial + steps
+            })
+            .sum();
+        ProofSize {
+            caps: [&self.trace_cap, &self.ctl_zs_cap, &self.quotient_polys_cap]
+                .into_iter()
+                .map(cap_size)
+                .sum(),
+            openings: [
+                &openings.local_values,
+                &openings.next_values,
+                &openings.ctl_zs,
+                &openings.ctl_zs_next,
+                &openings.quotient_polys,
+            ]
+            .into_iter()
+            .map(|values| values.len() * D * felt)
+            .sum::<usize>()
+                + openings.ctl_zs_last.len() * felt,
+            fri: fri
+                .commit_phase_merkle_caps
+                .iter()
+                .map(cap_size)
+                .sum::<usize>()
+                + query_rounds
+                + fri.final_poly.coeffs.len() * D * felt
+                + felt,
+        }
+    }
+}
+
+impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize> AllProof<F, C, D> {
+    /// Reports the size of the proof of each table, and how much sharing
+    /// repeated caps saves.
+    #[must_use]
+    pub fn size_report(&self) -> ProofSizeReport {
+        let shared = SharedCapsProof::new(self.clone());
+        let caps = caps(&self.proofs);
+        ProofSizeReport {
+            tables: self.proofs.each_ref().map(StarkProof::size),
+            repeated_caps: shared.repeats.len(),
+            repeated_cap_bytes: shared
+                .repeats
+                .iter()
+                .map(|&(at, _)| cap_size(caps[at]))
+                .sum(),
+        }
+    }
+}
+
+/// Sizes of a [`BatchProof`], see [`BatchProof::size_report`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BatchProofSizeReport {
+    /// The proofs of the public tables, and the openings of the others.
+    pub tables: TableKindArray<ProofSize>,
+    /// The caps and the FRI proof that all but the public tables share.
+    pub batch: ProofSize,
+}
+
+impl BatchProofSizeReport {
+    #[must_use]
+    pub fn total(&self) -> ProofSize { self.tables.iter().copied().sum::<ProofSize>() + self.batch }
+}
+
+impl Display for BatchProofSizeReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let row = |f: &mut Formatter<'_>, name: &str, size: &ProofSize| {
+            writeln!(
+                f,
+                "{name:<20} {:>10} {:>10} {:>10} {:>10}",
+                size.caps,
+                size.openings,
+                size.fri,
+                size.total()
+            )
+        };
+        writeln!(
+            f,
+            "{:<20} {:>10} {:>10} {:>10} {:>10}",
+            "table", "caps", "openings", "fri", "total"
+        )?;
+        for (size, kind) in &self.tables.each_ref().with_kind() {
+            row(f, &format!("{kind:?}"), size)?;
+        }
+        row(f, "batch", &self.batch)?;
+        let total = self.total();
+        write!(
+            f,
+            "{:<20} {:>10} {:>10} {:>10} {:>10}",
+            "all",
+            total.caps,
+            total.openings,
+            total.fri,
+            total.total()
+        )
+    }
+}
+
+impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize> BatchProof<F, C, D> {
+    /// Reports the size of the proofs of the `public_table_kinds` the proof
+    /// was made with, of the openings of the batched tables, and of what they
+    /// share.
+    #[must_use]
+    pub fn size_report(&self, public_table_kinds: &[TableKind]) -> BatchProofSizeReport {
+        BatchProofSizeReport {
+            tables: self.proofs.each_ref().with_kind().map(|(proof, kind)| {
+                let size = proof.size();
+                if public_table_kinds.contains(&kind) {
+                    size
+                } else {
+                    // Batched tables have no caps or FRI proof of their own.
+                    ProofSize {
+                        openings: size.openings,
+                        ..ProofSize::default()
+                    }
+                }
+            }),
+            batch: self.batch_stark_proof.size(),
+        }
+    }
+}
+
+/// All Merkle caps of `proofs`, table by table, in the order trace, `Z`,
+/// quotient, then FRI commit phase caps.
+fn caps<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>(
+    proofs: &TableKindArray<StarkProof<F, C, D>>,
+) -> Vec<&MerkleCap<F, C::Hasher>> {
+    proofs
+        .iter()
+        .flat_map(|proof| {
+            chain!(
+                [
+                    &proof.trace_cap,
+                    &proof.ctl_zs_cap,
+                    &proof.quotient_polys_cap
+                ],
+                &proof.opening_proof.commit_phase_merkle_caps
+            )
+        })
+        .collect()
+}
+
+fn caps_mut<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>(
+    proofs: &mut TableKindArray<StarkProof<F, C, D>>,
+) -> Vec<&mut MerkleCap<F, C::Hasher>> {
+    proofs
+        .iter_mut()
+        .flat_map(|proof| {
+            chain!(
+                [
+                    &mut proof.trace_cap,
+                    &mut proof.ctl_zs_cap,
+                    &mut proof.quotient_polys_cap
+                ],
+                &mut proof.opening_proof.commit_phase_merkle_caps
+            )
+        })
+        .collect()
+}
+
+/// An [`AllProof`] that stores each distinct Merkle cap once.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(bound = "")]
+pub struct SharedCapsProof<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
+{
+    /// The proof, with every cap that repeats an earlier one left empty.
+    pub proof: AllProof<F, C, D>,
+    /// Pairs of the index of an emptied cap, and of the earlier cap it
+    /// repeats, in the order of all caps of the proof, table by table: trace,
+    /// `Z`, quotient, then FRI commit phase caps.
+    pub repeats: Vec<(usize, usize)>,
+}
+
+impl<F: RichField + Extendable<D>, C: GenericConfig<D, F = F>, const D: usize>
+    SharedCapsProof<F, C, D>
+{
+    #[must_use]
+    pub fn new(mut proof: AllProof<F, C, D>) -> Self {
+        let mut first_seen = HashMap::new();
+        let mut repeats = vec![];
+        for (index, cap) in caps_mut(&mut proof.proofs).into_iter().enumerate() {
+            if cap.is_empty() {
+                continue;
+            }
+            let bytes: Vec<u8> = cap.0.iter().flat_map(GenericHashOut::to_bytes).collect();
+            if let Some(&first) = first_seen.get(&bytes) {
+                repeats.push((index, first));
+                *cap = MerkleCap(vec![]);
+            } else {
+                first_seen.insert(bytes, index);
+            }
+        }
+        Self { proof, repeats }
+    }
+
+    /// Restores the repeated caps.
+    ///
+    /// # Errors
+    /// Errors if a repeat does not refer to an earlier cap, or to an emptied
+    /// cap.
+    pub fn into_proof(self) -> Result<AllProof<F, C, D>> {
+        let Self { mut proof, repeats } = self;
+        let mut caps = caps_mut(&mut proof.proofs);
+        for (at, first) in repeats {
+            ensure!(
+                first < at && at < caps.len(),
+                "cap {at} cannot repeat cap {first}"
+            );
+            ensure!(
+                caps[at].is_empty() && !caps[first].is_empty(),
+                "cap {at} does not repeat non-empty cap {first}"
+            );
+            let cap = caps[first].clone();
+            *caps[at] = cap;
+        }
+        Ok(proof)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mozak_runner::code;
+    use mozak_runner::instruction::Instruction;
+    use plonky2::util::timing::TimingTree;
+
+    use super::SharedCapsProof;
+    use crate::stark::batch_prover::batch_prove;
+    use crate::stark::mozak_stark::{MozakStark, PublicInputs, PUBLIC_TABLE_KINDS};
+    use crate::stark::prover::prove;
+    use crate::stark::verifier::verify_proof;
+    use crate::test_utils::{fast_test_config, C, D, F};
+    use crate::utils::from_u32;
+
+    #[test]
+    fn share_caps() -> anyhow::Result<()> {
+        let (program, record) = code::execute([Instruction::addi(5, 6, 7)], &[], &[(6, 100)]);
+        let stark = MozakStark::default();
+        let config = fast_test_config();
+        let all_proof = prove::<F, C, D>(
+            &program,
+            &record,
+            &stark,
+            &config,
+            PublicInputs::new(from_u32(program.entry_point)),
+            &mut TimingTree::default(),
+        )?;
+        let report = all_proof.size_report();
+        let shared = SharedCapsProof::new(all_proof.clone());
+        assert_eq!(report.repeated_caps, shared.repeats.len());
+        assert_eq!(
+            serde_json::to_value(shared.clone().into_proof()?)?,
+            serde_json::to_value(&all_proof)?
+        );
+
+        let mut forged = shared;
+        forged.repeats.push((0, 1));
+        assert!(forged.into_proof().is_err());
+
+        verify_proof(&stark, all_proof, &config)
+    }
+
+    #[test]
+    fn batching_shrinks_proofs() -> anyhow::Result<()> {
+        let (program, record) = code::execute([Instruction::addi(5, 6, 7)], &[], &[(6, 100)]);
+        let stark = MozakStark::default();
+        let config = fast_test_config();
+        let public_inputs = PublicInputs::new(from_u32(program.entry_point));
+        let all_proof = prove::<F, C, D>(
+            &program,
+            &record,
+            &stark,
+            &config,
+            public_inputs,
+            &mut TimingTree::default(),
+        )?;
+        let (batch_proof, _degree_bits) = batch_prove::<F, C, D>(
+            &program,
+            &record,
+            &stark,
+            &PUBLIC_TABLE_KINDS,
+            &config,
+            public_inputs,
+            &mut TimingTree::default(),
+        )?;
+        let separate = all_proof.size_report();
+        let batched = batch_proof.size_report(&PUBLIC_TABLE_KINDS);
+        for kind in PUBLIC_TABLE_KINDS {
+            assert_eq!(batched.tables[kind], separate.tables[kind]);
+        }
+        assert_eq!(batched.total().openings, separate.total().openings);
+        assert!(batched.total().total() < separate.total().total());
+        Ok(())
+    }
+}
@@ -1,23 +1,28 @@
 #![allow(clippy::too_many_lines)]
 
+use std::borrow::Borrow;
 use std::fmt::Display;
+use std::fs;
+use std::num::NonZeroU64;
+use std::path::Path;
 
 use anyhow::{ensure, Result};
-use itertools::Itertools;
+use itertools::{chain, Itertools};
+use log::log_enabled;
 use log::Level::Debug;
-use log::{debug, log_enabled};
 use mozak_runner::elf::Program;
-use mozak_runner::vm::ExecutionRecord;
+use mozak_runner::state::{RawTapes, State};
+use mozak_runner::vm::{step, ExecutionRecord};
 use mozak_sdk::common::types::ProgramIdentifier;
 use plonky2::field::extension::Extendable;
 use plonky2::field::packable::Packable;
 use plonky2::field::polynomial::PolynomialValues;
 use plonky2::field::types::Field;
 use plonky2::fri::oracle::PolynomialBatch;
-use plonky2::hash::hash_types::RichField;
+use plonky2::hash::hash_types::{HashOut, RichField};
 use plonky2::hash::merkle_tree::MerkleCap;
 use plonky2::iop::challenger::Challenger;
-use plonky2::plonk::config::{AlgebraicHasher, GenericConfig, GenericHashOut, Hasher};
+use plonky2::plonk::config::{GenericConfig, Hasher};
 use plonky2::timed;
 use plonky2::util::log2_strict;
 use plonky2::util::timing::TimingTree;
@@ -25,18 +30,30 @@ use plonky2::util::timing::TimingTree;
 use plonky2_maybe_rayon::*;
 use starky::config::StarkConfig;
 use starky::stark::{LookupConfig, Stark};
+use tracing::{debug, info_span, instrument, Span};
 
+use super::error::ProverError;
 use super::mozak_stark::{
-    all_starks_par, MozakStark, TableKind, TableKindArray, TableKindSetBuilder,
+    all_kind, all_starks_par, MozakStark, TableKind, TableKindArray, TableKindSetBuilder,
+    PUBLIC_TABLE_KINDS,
 };
-use super::proof::{AllProof, StarkOpeningSet, StarkProof};
+use super::preprocessed::{self, PreprocessedCommitments, PREPROCESSED_TABLES};
+use super::proof::{observe_preprocessed, observe_skipped, AllProof, StarkOpeningSet, StarkProof};
+use crate::cpu::columns::ExitCode;
 use crate::cross_table_lookup::ctl_utils::debug_ctl;
 use crate::cross_table_lookup::{cross_table_lookup_data, CtlData};
 use crate::generation::{debug_traces, generate_traces};
+use crate::memoryinit::generation::{
+    generate_elf_memory_init_trace, generate_library_elf_memory_init_trace,
+};
+use crate::program::generation::{generate_library_rom_trace, generate_program_rom_trace};
 use crate::public_sub_table::public_sub_table_data_and_values;
 use crate::stark::mozak_stark::PublicInputs;
 use crate::stark::permutation::challenge::GrandProductChallengeTrait;
 use crate::stark::poly::compute_quotient_polys;
+use crate::stark::transcript::Transcript;
+use crate::stark::utils::trace_rows_to_poly_values;
+use crate::trace_source::Execution;
 
 /// Prove the execution of a given [Program]
 ///
@@ -54,29 +71,48 @@ pub fn prove<F, C, const D: usize>(
     config: &StarkConfig,
     public_inputs: PublicInputs<F>,
     timing: &mut TimingTree,
-) -> Result<AllProof<F, C, D>>
+) -> Result<AllProof<F, C, D>, ProverError>
 where
     F: RichField + Extendable<D>,
-    C: GenericConfig<D, F = F>,
-    <C as GenericConfig<D>>::Hasher: AlgebraicHasher<F>, {
+    C: GenericConfig<D, F = F>, {
+    prove_with_static_commitments(
+        program,
+        record,
+        mozak_stark,
+        config,
+        public_inputs,
+        None,
+        timing,
+    )
+}
+
+/// Like [`prove`], but reuses the given commitments of the tables that only
+/// depend on the [`Program`], instead of committing to them again.
+///
+/// # Errors
+/// Errors if `static_commitments` do not match `program` and `config`, or if
+/// proving fails.
+pub fn prove_with_static_commitments<F, C, const D: usize>(
+    program: &Program,
+    record: &ExecutionRecord<F>,
+    mozak_stark: &MozakStark<F, D>,
+    config: &StarkConfig,
+    public_inputs: PublicInputs<F>,
+    static_commitments: Option<&StaticTableCommitments<F, C, D>>,
+    timing: &mut TimingTree,
+) -> Result<AllProof<F, C, D>, ProverError>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>, {
     debug!("Starting Prove");
     let traces_poly_values = timed!(
         timing,
         "Generate traces",
-        generate_traces(program, record, timing)
+        generate_traces(&Execution::new(program, record), timing)
     );
     debug!("Done with Trace Generation");
     if mozak_stark.debug || std::env::var("MOZAK_STARK_DEBUG").is_ok() {
-        timed!(
-            timing,
-            "Mozak stark debug",
-            debug_traces(&traces_poly_values, mozak_stark, &public_inputs)
-        );
-        timed!(
-            timing,
-            "Mozak CTL debug",
-            debug_ctl(&traces_poly_values, mozak_stark)
-        );
+        debug_all(&traces_poly_values, mozak_stark, &public_inputs, timing);
     }
     timed!(
         timing,
@@ -86,70 +122,434 @@ where
             config,
             public_inputs,
             &traces_poly_values,
+            static_commitments,
             timing,
         )
     )
 }
 
+/// Runs the checks of [`prove`] without proving: generates the traces,
+/// evaluates the constraints of all tables on every row, and checks that the
+/// cross table lookups balance, but skips the commitments and FRI.
+///
+/// This is much faster than proving and catches most bugs, but not all of
+/// them: eg constraints of too high a degree only fail in a real proof.
+///
+/// Returns the number of rows of each table, padding included.
+///
+/// # Panics
+/// Panics if any constraint or cross table lookup fails.
+pub fn prove_dry_run<F, const D: usize>(
+    program: &Program,
+    record: &ExecutionRecord<F>,
+    mozak_stark: &MozakStark<F, D>,
+    public_inputs: &PublicInputs<F>,
+    timing: &mut TimingTree,
+) -> TableKindArray<usize>
+where
+    F: RichField + Extendable<D>, {
+    let traces_poly_values = timed!(
+        timing,
+        "Generate traces",
+        generate_traces(&Execution::new(program, record), timing)
+    );
+    debug_all(&traces_poly_values, mozak_stark, public_inputs, timing);
+    traces_poly_values
+        .each_ref()
+        .map(|trace| trace.first().map_or(0, |column| column.values.len()))
+}
+
+fn debug_all<F, const D: usize>(
+    traces_poly_values: &TableKindArray<Vec<PolynomialValues<F>>>,
+    mozak_stark: &MozakStark<F, D>,
+    public_inputs: &PublicInputs<F>,
+    timing: &mut TimingTree,
+) where
+    F: RichField + Extendable<D>, {
+    timed!(
+        timing,
+        "Mozak stark debug",
+        debug_traces(traces_poly_values, mozak_stark, public_inputs)
+    );
+    timed!(
+        timing,
+        "Mozak CTL debug",
+        debug_ctl(traces_poly_values, mozak_stark)
+    );
+}
+
+/// Trace of a table that does not depend on the execution, together with its
+/// commitment.
+pub(crate) struct StaticTableCommitment<F, C, const D: usize>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>, {
+    pub(crate) trace: Vec<PolynomialValues<F>>,
+    pub(crate) commitment: PolynomialBatch<F, C, D>,
+}
+
+impl<F, C, const D: usize> StaticTableCommitment<F, C, D>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    pub(crate) fn new(trace: Vec<PolynomialValues<F>>, config: &StarkConfig) -> Self {
+        let commitment = PolynomialBatch::from_values(
+            trace.clone(),
+            config.fri_config.rate_bits,
+            false,
+            config.fri_config.cap_height,
+            &mut TimingTree::default(),
+            None,
+        );
+        Self { trace, commitment }
+    }
+}
+
+/// Commitments to the Program ROM and ELF memory init tables, and to those of
+/// the linked library, which only depend on the [`Program`].
+///
+/// When the same ELF is proven many times, compute these once and hand them
+/// to [`prove_with_static_commitments`], so that every proof does not commit
+/// to the same tables again.
+pub struct StaticTableCommitments<F, C, const D: usize>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>, {
+    program_rom: StaticTableCommitment<F, C, D>,
+    elf_memory_init: StaticTableCommitment<F, C, D>,
+    library_program_rom: StaticTableCommitment<F, C, D>,
+    library_elf_memory_init: StaticTableCommitment<F, C, D>,
+    preprocessed: Option<PreprocessedCommitments<F, C, D>>,
+    rate_bits: usize,
+    cap_height: usize,
+}
+
+impl<F, C, const D: usize> StaticTableCommitments<F, C, D>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    #[must_use]
+    pub fn new(program: &Program, config: &StarkConfig) -> Self {
+        Self {
+            program_rom: StaticTableCommitment::new(
+                trace_rows_to_poly_values(generate_program_rom_trace(program)),
+                config,
+            ),
+            elf_memory_init: StaticTableCommitment::new(
+                trace_rows_to_poly_values(generate_elf_memory_init_trace(program)),
+                config,
+            ),
+            library_program_rom: StaticTableCommitment::new(
+                trace_rows_to_poly_values(generate_library_rom_trace(program)),
+                config,
+            ),
+            library_elf_memory_init: StaticTableCommitment::new(
+                trace_rows_to_poly_values(generate_library_elf_memory_init_trace(program)),
+                config,
+            ),
+            preprocessed: None,
+            rate_bits: config.fri_config.rate_bits,
+            cap_height: config.fri_config.cap_height,
+        }
+    }
+
+    /// Also reuses `preprocessed` commitments to the fixed columns of tables,
+    /// so that proofs only commit to the other columns of those tables.
+    #[must_use]
+    pub fn with_preprocessed(self, preprocessed: PreprocessedCommitments<F, C, D>) -> Self {
+        Self {
+            preprocessed: Some(preprocessed),
+            ..self
+        }
+    }
+
+    #[must_use]
+    pub fn preprocessed(&self) -> Option<&PreprocessedCommitments<F, C, D>> {
+        self.preprocessed.as_ref()
+    }
+
+    #[must_use]
+    pub fn program_rom_cap(&self) -> &MerkleCap<F, C::Hasher> {
+        &self.program_rom.commitment.merkle_tree.cap
+    }
+
+    #[must_use]
+    pub fn elf_memory_init_cap(&self) -> &MerkleCap<F, C::Hasher> {
+        &self.elf_memory_init.commitment.merkle_tree.cap
+    }
+
+    /// The program id of proofs made with these commitments.
+    #[must_use]
+    pub fn program_id(&self, entry_point: F) -> ProgramIdentifier {
+        get_program_id::<F, C, D>(
+            entry_point,
+            self.program_rom_cap(),
+            self.elf_memory_init_cap(),
+            &self.library_program_rom.commitment.merkle_tree.cap,
+            &self.library_elf_memory_init.commitment.merkle_tree.cap,
+        )
+    }
+
+    /// The library id of proofs made with these commitments, see
+    /// [`get_library_id`].
+    #[must_use]
+    pub fn library_id(&self) -> ProgramIdentifier {
+        get_library_id::<F, C, D>(
+            &self.library_program_rom.commitment.merkle_tree.cap,
+            &self.library_elf_memory_init.commitment.merkle_tree.cap,
+        )
+    }
+
+    fn get(&self, kind: TableKind) -> Option<&StaticTableCommitment<F, C, D>> {
+        match kind {
+            TableKind::Program => Some(&self.program_rom),
+            TableKind::ElfMemoryInit => Some(&self.elf_memory_init),
+            TableKind::LibraryProgram => Some(&self.library_program_rom),
+            TableKind::LibraryElfMemoryInit => Some(&self.library_elf_memory_init),
+            _ => None,
+        }
+    }
+
+    /// Checks that the commitments were made with the same FRI parameters as
+    /// `config`, and for the same traces as `traces_poly_values`, or for the
+    /// fixed columns they start with.
+    fn validate(
+        &self,
+        config: &StarkConfig,
+        traces_poly_values: &TableKindArray<Vec<PolynomialValues<F>>>,
+    ) -> Result<(), ProverError> {
+        let same_config = self.rate_bits == config.fri_config.rate_bits
+            && self.cap_height == config.fri_config.cap_height;
+        for table in PUBLIC_TABLE_KINDS {
+            let cached = self.get(table).map(|c| &c.trace);
+            if !same_config || cached != Some(&traces_poly_values[table]) {
+                return Err(ProverError::StaticCommitmentMismatch { table });
+            }
+        }
+        if let Some(preprocessed) = &self.preprocessed {
+            for (table, _) in PREPROCESSED_TABLES {
+                if !preprocessed.matches(config, table, &traces_poly_values[table]) {
+                    return Err(ProverError::StaticCommitmentMismatch { table });
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Arguments for [`prove_elf`].
+pub struct ProveArgs {
+    /// Public tape, handed to the guest as is.
+    pub public_tape: Vec<u8>,
+    /// Private tape, handed to the guest as is.
+    pub private_tape: Vec<u8>,
+    /// Stark and FRI security configurations
+    pub config: StarkConfig,
+    /// Cycle budget of the execution, see [`State::with_max_cycles`].
+    pub max_cycles: Option<NonZeroU64>,
+}
+
+impl Default for ProveArgs {
+    fn default() -> Self {
+        Self {
+            public_tape: Vec::new(),
+            private_tape: Vec::new(),
+            config: StarkConfig::standard_fast_config(),
+            max_cycles: None,
+        }
+    }
+}
+
+/// The public values of a proof made by [`prove_elf`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PublicValues {
+    pub program_id: ProgramIdentifier,
+    pub exit_code: ExitCode,
+}
+
+/// Loads, executes and proves the ELF at `path`.
+///
+/// See [`prove_elf`].
+pub fn prove_elf_file<F, C, const D: usize>(
+    path: impl AsRef<Path>,
+    args: ProveArgs,
+) -> Result<(AllProof<F, C, D>, PublicValues), ProverError>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>, {
+    prove_elf(&fs::read(path)?, args)
+}
+
+/// Loads, executes and proves the given ELF in one go.
+///
+/// The self program id tape is filled in with the program id of `elf`, and
+/// checked against the program id of the proof, see
+/// [`SelfProgramId`](crate::tape_commitments::columns::SelfProgramId).
+///
+/// # Errors
+/// Errors if the ELF can not be loaded, the execution fails or traps, or if
+/// proving fails.
+pub fn prove_elf<F, C, const D: usize>(
+    elf: &[u8],
+    ProveArgs {
+        public_tape,
+        private_tape,
+        config,
+        max_cycles,
+    }: ProveArgs,
+) -> Result<(AllProof<F, C, D>, PublicValues), ProverError>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>, {
+    let program = Program::mozak_load_program(elf).map_err(ProverError::LoadElf)?;
+    let entry_point = F::from_canonical_u32(program.entry_point);
+    let static_commitments = StaticTableCommitments::<F, C, D>::new(&program, &config);
+    let self_prog_id = static_commitments.program_id(entry_point);
+
+    let raw_tapes = RawTapes {
+        public_tape,
+        private_tape,
+        self_prog_id_tape: self_prog_id.0 .0,
+        ..RawTapes::default()
+    };
+    let mut state = State::new(program.clone(), raw_tapes);
+    state.max_cycles = max_cycles;
+    let record = step(&program, state).map_err(ProverError::Execution)?;
+    if let Some(report) = &record.last_state.trap {
+        return Err(ProverError::Trapped {
+            clk: record.last_state.clk,
+            report: report.clone(),
+        });
+    }
+
+    let mozak_stark = MozakStark::default();
+    let all_proof = prove_with_static_commitments(
+        &program,
+        &record,
+        &mozak_stark,
+        &config,
+        PublicInputs::for_execution(&program, &record),
+        Some(&static_commitments),
+        &mut TimingTree::default(),
+    )?;
+    let public_values = PublicValues {
+        program_id: all_proof.program_id,
+        exit_code: all_proof
+            .public_value(&mozak_stark)
+            .map_err(ProverError::PublicValue)?,
+    };
+    Ok((all_proof, public_values))
+}
+
 /// Given the traces generated from [`generate_traces`], prove a [`MozakStark`].
 ///
+/// Tables covered by `static_commitments` are not committed to again, nor are
+/// the fixed columns covered by their
+/// [preprocessed commitments](StaticTableCommitments::with_preprocessed).
+/// Unused tables are skipped if `mozak_stark` asks for it, see
+/// [`MozakStark::skip_unused`].
+///
 /// # Errors
-/// Errors if proving fails.
+/// Errors if `static_commitments` do not match the traces and `config`, or
+/// if proving fails.
+#[instrument(name = "prove", skip_all)]
 pub fn prove_with_traces<F, C, const D: usize>(
     mozak_stark: &MozakStark<F, D>,
     config: &StarkConfig,
     public_inputs: PublicInputs<F>,
     traces_poly_values: &TableKindArray<Vec<PolynomialValues<F>>>,
+    static_commitments: Option<&StaticTableCommitments<F, C, D>>,
     timing: &mut TimingTree,
-) -> Result<AllProof<F, C, D>>
+) -> Result<AllProof<F, C, D>, ProverError>
 where
     F: RichField + Extendable<D>,
-    C: GenericConfig<D, F = F>,
-    <C as GenericConfig<D>>::Hasher: AlgebraicHasher<F>, {
+    C: GenericConfig<D, F = F>, {
     let rate_bits = config.fri_config.rate_bits;
     let cap_height = config.fri_config.cap_height;
 
-    let trace_commitments = timed!(
+    for (trace, table) in &traces_poly_values.each_ref().with_kind() {
+        check_trace_fits_config(config, *table, trace)?;
+    }
+    if let Some(static_commitments) = static_commitments {
+        static_commitments.validate(config, traces_poly_values)?;
+    }
+    let cached_commitment = |table: TableKind| {
+        static_commitments
+            .and_then(|s| s.get(table))
+            .map(|c| &c.commitment)
+    };
+    let preprocessed = static_commitments.and_then(StaticTableCommitments::preprocessed);
+    let fixed_commitment = |table: TableKind| preprocessed.and_then(|p| p.get(table));
+    let skipped = skipped_tables(mozak_stark, traces_poly_values);
+
+    // Rayon's worker threads do not inherit the current span.
+    let span = Span::current();
+    let computed_commitments = timed!(
         timing,
         "Compute trace commitments for each table",
         traces_poly_values
             .each_ref()
             .with_kind()
             .par_map(|(trace, table)| {
+                if skipped[table] || cached_commitment(table).is_some() {
+                    return None;
+                }
+                let _span = info_span!(parent: &span, "commit", ?table).entered();
                 let mut timing = TimingTree::default();
-                timed!(
+                let num_fixed = fixed_commitment(table).map_or(0, |c| c.polynomials.len());
+                Some(timed!(
                     timing,
                     &format!("compute trace commitment for {table:?}"),
                     PolynomialBatch::<F, C, D>::from_values(
-                        trace.clone(),
+                        trace[num_fixed..].to_vec(),
                         rate_bits,
                         false,
                         cap_height,
                         &mut timing,
                         None,
                     )
-                )
+                ))
             })
     );
+    let trace_commitments = computed_commitments
+        .each_ref()
+        .with_kind()
+        .map(|(computed, table)| {
+            let commitment = computed.as_ref().or_else(|| cached_commitment(table));
+            assert!(
+                skipped[table] || commitment.is_some(),
+                "every table is either skipped, committed to or cached"
+            );
+            commitment
+        });
+    let fixed_commitments = all_kind!(|kind| fixed_commitment(kind).filter(|_| !skipped[kind]));
+    let preprocessed = preprocessed.map(|p| p.caps().digest());
 
     let trace_caps = trace_commitments
         .each_ref()
-        .map(|c| c.merkle_tree.cap.clone());
+        .map(|c| c.map_or_else(|| MerkleCap(vec![]), |c| c.merkle_tree.cap.clone()));
     // Add trace commitments to the challenger entropy pool.
+    let transcript = &mozak_stark.transcript;
     let mut challenger = Challenger::<F, C::Hasher>::new();
-    for cap in &trace_caps {
+    for (cap, kind) in &trace_caps.each_ref().with_kind() {
         challenger.observe_cap(cap);
+        transcript.observe_cap(&format!("trace cap of {kind:?}"), cap);
     }
+    observe_skipped(&mut challenger, &skipped, transcript);
+    observe_preprocessed::<F, C::Hasher>(&mut challenger, preprocessed.as_ref(), transcript);
 
     let ctl_challenges = challenger.get_grand_product_challenge_set(config.num_challenges);
+    transcript.sample_ctl_challenges(&ctl_challenges);
     let ctl_data_per_table = timed!(
         timing,
         "Compute CTL data for each table",
-        cross_table_lookup_data::<F, D>(
+        info_span!("ctl").in_scope(|| cross_table_lookup_data::<F, D>(
             traces_poly_values,
             &mozak_stark.cross_table_lookups,
             &ctl_challenges
-        )
+        ))
     );
 
     let (public_sub_table_data_per_table, public_sub_table_values) =
@@ -167,6 +567,7 @@ where
             config,
             &public_inputs,
             &trace_commitments,
+            &fixed_commitments,
             &ctl_data_per_table,
             &public_sub_table_data_per_table,
             &mut challenger,
@@ -178,6 +579,8 @@ where
         public_inputs.entry_point,
         &trace_caps[TableKind::Program],
         &trace_caps[TableKind::ElfMemoryInit],
+        &trace_caps[TableKind::LibraryProgram],
+        &trace_caps[TableKind::LibraryElfMemoryInit],
     );
 
     if log_enabled!(Debug) {
@@ -189,32 +592,139 @@ where
         public_inputs,
         public_sub_table_values,
         program_id,
+        skipped,
+        preprocessed,
+        config: Some(config.into()),
+    })
+}
+
+/// The tables that `mozak_stark` leaves out of its proof of
+/// `traces_poly_values`: those it [skips when
+/// unused](MozakStark::skip_unused), and whose filters are zero in every row
+/// of every cross-table lookup they take part in.
+fn skipped_tables<F, const D: usize>(
+    mozak_stark: &MozakStark<F, D>,
+    traces_poly_values: &TableKindArray<Vec<PolynomialValues<F>>>,
+) -> TableKindArray<bool>
+where
+    F: RichField + Extendable<D>, {
+    let filters = mozak_stark
+        .cross_table_lookups
+        .iter()
+        .flat_map(|ctl| &ctl.looking_tables)
+        .map(|table| (table.kind, table.filter_column.to_field::<F>()))
+        .into_group_map();
+    all_kind!(|kind| {
+        let trace = &traces_poly_values[kind];
+        mozak_stark.skip_unused[kind]
+            && mozak_stark.can_skip(kind)
+            && filters.get(&kind).into_iter().flatten().all(|filter| {
+                (0..trace[0].len()).all(|row| filter.eval_table(trace, row).is_zero())
+            })
     })
 }
 
+/// Checks that the FRI parameters of `config` work for `trace`, so that
+/// proving `table` can not fail half way.
+fn check_trace_fits_config<F: RichField>(
+    config: &StarkConfig,
+    table: TableKind,
+    trace: &[PolynomialValues<F>],
+) -> Result<(), ProverError> {
+    let degree_bits = log2_strict(trace[0].len());
+    let rate_bits = config.fri_config.rate_bits;
+    // The low degree extension has to fit into the two-adic subgroup.
+    let max_degree_bits = F::TWO_ADICITY.saturating_sub(rate_bits);
+    if degree_bits > max_degree_bits {
+        return Err(ProverError::TraceTooLarge {
+            table,
+            degree_bits,
+            max_degree_bits,
+        });
+    }
+    let total_arities = config.fri_params(degree_bits).total_arities();
+    if total_arities + config.fri_config.cap_height > degree_bits + rate_bits {
+        return Err(ProverError::BadConfig {
+            table,
+            reason: format!("FRI total reduction arity {total_arities} is too large"),
+        });
+    }
+    Ok(())
+}
+
+/// Identifies a program by its entry point, and the commitments to its ROM
+/// and ELF memory init tables, and to those of its library, see
+/// [`get_library_id`].
+///
+/// A program without a library links the empty one, so a proof can not pass
+/// library code or data off as that of such a program either.
 pub fn get_program_id<F, C, const D: usize>(
     entry_point: F,
     program_trace_cap: &MerkleCap<F, C::Hasher>,
     elf_memory_init_trace_cap: &MerkleCap<F, C::Hasher>,
+    library_program_trace_cap: &MerkleCap<F, C::Hasher>,
+    library_elf_memory_init_trace_cap: &MerkleCap<F, C::Hasher>,
 ) -> ProgramIdentifier
 where
     F: RichField + Extendable<D>,
-    C: GenericConfig<D, F = F>,
-    <C as GenericConfig<D>>::Hasher: AlgebraicHasher<F>, {
+    C: GenericConfig<D, F = F>, {
     let hash_pad_func = <<C as GenericConfig<D>>::InnerHasher as Hasher<F>>::hash_pad;
     let hashout = hash_pad_func(
         &itertools::chain!(
             [entry_point],
             hash_pad_func(&program_trace_cap.flatten()).elements,
             hash_pad_func(&elf_memory_init_trace_cap.flatten()).elements,
+            library_hash::<F, C, D>(library_program_trace_cap, library_elf_memory_init_trace_cap)
+                .elements,
         )
         .collect_vec(),
     );
-    let hashout_bytes: [u8; 32] = hashout.to_bytes().try_into().unwrap();
-    ProgramIdentifier(hashout_bytes.into())
+    ProgramIdentifier::from_u64s(hashout.elements.map(|element| element.to_canonical_u64()))
+}
+
+/// Identifies the library linked into a program, see
+/// [`Program::link`], by the commitments to its ROM and ELF memory init
+/// tables.
+///
+/// Unlike the program id, it does not depend on the program that links the
+/// library, so a verifier can pin the library of a proof while the program
+/// changes. The id of a program without a library does not identify
+/// anything.
+pub fn get_library_id<F, C, const D: usize>(
+    library_program_trace_cap: &MerkleCap<F, C::Hasher>,
+    library_elf_memory_init_trace_cap: &MerkleCap<F, C::Hasher>,
+) -> ProgramIdentifier
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>, {
+    let hashout =
+        library_hash::<F, C, D>(library_program_trace_cap, library_elf_memory_init_trace_cap);
+    ProgramIdentifier::from_u64s(hashout.elements.map(|element| element.to_canonical_u64()))
+}
+
+fn library_hash<F, C, const D: usize>(
+    library_program_trace_cap: &MerkleCap<F, C::Hasher>,
+    library_elf_memory_init_trace_cap: &MerkleCap<F, C::Hasher>,
+) -> HashOut<F>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>, {
+    let hash_pad_func = <<C as GenericConfig<D>>::InnerHasher as Hasher<F>>::hash_pad;
+    hash_pad_func(
+        &itertools::chain!(
+            hash_pad_func(&library_program_trace_cap.flatten()).elements,
+            hash_pad_func(&library_elf_memory_init_trace_cap.flatten()).elements,
+        )
+        .collect_vec(),
+    )
 }
 
-/// Compute proof for a single STARK table, with lookup data.
+/// Compute proof for a single STARK table, with lookup data, and record its
+/// transcript into `transcript`.
+///
+/// With a `fixed_commitment`, the trace of the table is made up of the columns
+/// committed to by `fixed_commitment`, followed by those of
+/// `trace_commitment`.
 ///
 /// # Errors
 /// Errors if FRI parameters are wrongly configured, or if
@@ -225,10 +735,12 @@ pub(crate) fn prove_single_table<F, C, S, const D: usize>(
     stark: &S,
     config: &StarkConfig,
     trace_commitment: &PolynomialBatch<F, C, D>,
+    fixed_commitment: Option<&PolynomialBatch<F, C, D>>,
     public_inputs: &[F],
     ctl_data: &CtlData<F>,
     public_sub_table_data: &CtlData<F>,
     challenger: &mut Challenger<F, C::Hasher>,
+    transcript: &Transcript,
     timing: &mut TimingTree,
 ) -> Result<StarkProof<F, C, D>>
 where
@@ -269,11 +781,20 @@ where
     );
     let ctl_zs_cap = ctl_zs_commitment.merkle_tree.cap.clone();
     challenger.observe_cap(&ctl_zs_cap);
+    transcript.observe_cap("ctl zs cap", &ctl_zs_cap);
 
     let alphas = challenger.get_n_challenges(config.num_challenges);
+    transcript.sample("stark alphas", &alphas);
 
     let get_trace_values_packed = |i_start, step| -> Vec<<F as Packable>::Packing> {
-        trace_commitment.get_lde_values_packed(i_start, step)
+        match fixed_commitment {
+            Some(fixed) => chain!(
+                fixed.get_lde_values_packed(i_start, step),
+                trace_commitment.get_lde_values_packed(i_start, step)
+            )
+            .collect(),
+            None => trace_commitment.get_lde_values_packed(i_start, step),
+        }
     };
 
     let get_ctl_zs_values_packed = |i_start, step| -> Vec<<F as Packable>::Packing> {
@@ -326,8 +847,10 @@ where
     );
     let quotient_polys_cap = quotient_commitment.merkle_tree.cap.clone();
     challenger.observe_cap(&quotient_polys_cap);
+    transcript.observe_cap("quotient polys cap", &quotient_polys_cap);
 
     let zeta = challenger.get_extension_challenge::<D>();
+    transcript.sample_extension::<F, D>("stark zeta", &[zeta]);
     // To avoid leaking witness data, we want to ensure that our opening locations,
     // `zeta` and `g * zeta`, are not in our subgroup `H`. It suffices to check
     // `zeta` only, since `(g * zeta)^n = zeta^n`, where `n` is the order of
@@ -341,41 +864,67 @@ where
     let openings = StarkOpeningSet::new(
         zeta,
         g,
+        fixed_commitment,
         trace_commitment,
         &ctl_zs_commitment,
         &quotient_commitment,
         degree_bits,
     );
 
-    challenger.observe_openings(&openings.to_fri_openings());
+    let fri_openings = openings.to_fri_openings();
+    challenger.observe_openings(&fri_openings);
+    transcript.observe_openings(&fri_openings);
+    // FRI samples its challenges inside `prove_openings`, so we replay them
+    // for the transcript.
+    let fri_challenger = transcript.is_recording().then(|| challenger.clone());
 
-    let initial_merkle_trees = vec![trace_commitment, &ctl_zs_commitment, &quotient_commitment];
+    let initial_merkle_trees = chain!(fixed_commitment, [
+        trace_commitment,
+        &ctl_zs_commitment,
+        &quotient_commitment
+    ])
+    .collect_vec();
+    let num_ctl_zs = ctl_data.len() + public_sub_table_data.len();
+    let fri_instance = match fixed_commitment {
+        Some(fixed) =>
+            preprocessed::fri_instance(stark, fixed.polynomials.len(), zeta, g, num_ctl_zs, config),
+        None => stark.fri_instance(
+            zeta,
+            g,
+            0,
+            vec![],
+            config,
+            Some(&LookupConfig {
+                degree_bits,
+                num_zs: num_ctl_zs,
+            }),
+        ),
+    };
 
     // Make sure that we do not use Starky's lookups.
     assert!(!stark.requires_ctls());
     assert!(!stark.uses_lookups());
-    let num_make_rows_public_data = public_sub_table_data.len();
     let opening_proof = timed!(
         timing,
         format!("{stark}: compute opening proofs").as_str(),
-        PolynomialBatch::prove_openings(
-            &stark.fri_instance(
-                zeta,
-                g,
-                0,
-                vec![],
-                config,
-                Some(&LookupConfig {
-                    degree_bits,
-                    num_zs: ctl_data.len() + num_make_rows_public_data
-                })
-            ),
+        info_span!("fri").in_scope(|| PolynomialBatch::prove_openings(
+            &fri_instance,
             &initial_merkle_trees,
             challenger,
             &fri_params,
             timing,
-        )
+        ))
     );
+    if let Some(mut fri_challenger) = fri_challenger {
+        let fri_challenges = fri_challenger.fri_challenges::<C, D>(
+            &opening_proof.commit_phase_merkle_caps,
+            &opening_proof.final_poly,
+            opening_proof.pow_witness,
+            degree_bits,
+            &config.fri_config,
+        );
+        transcript.record_fri(&opening_proof, &fri_challenges);
+    }
 
     Ok(StarkProof {
         trace_cap: trace_commitment.merkle_tree.cap.clone(),
@@ -389,6 +938,9 @@ where
 /// Given the traces generated from [`generate_traces`] along with their
 /// commitments, prove a [`MozakStark`].
 ///
+/// The trace of a table with one of `fixed_commitments` is made up of the
+/// columns committed to there, followed by those of its trace commitment.
+///
 /// # Errors
 /// Errors if proving fails.
 #[allow(clippy::too_many_arguments)]
@@ -396,52 +948,88 @@ pub fn prove_with_commitments<F, C, const D: usize>(
     mozak_stark: &MozakStark<F, D>,
     config: &StarkConfig,
     public_inputs: &PublicInputs<F>,
-    trace_commitments: &TableKindArray<PolynomialBatch<F, C, D>>,
+    trace_commitments: &TableKindArray<Option<&PolynomialBatch<F, C, D>>>,
+    fixed_commitments: &TableKindArray<Option<&PolynomialBatch<F, C, D>>>,
     ctl_data_per_table: &TableKindArray<CtlData<F>>,
     public_sub_data_per_table: &TableKindArray<CtlData<F>>,
     challenger: &mut Challenger<F, C::Hasher>,
     _timing: &mut TimingTree,
-) -> Result<TableKindArray<StarkProof<F, C, D>>>
+) -> Result<TableKindArray<StarkProof<F, C, D>>, ProverError>
 where
     F: RichField + Extendable<D>,
     C: GenericConfig<D, F = F>, {
-    let cpu_skeleton_stark = [public_inputs.entry_point];
     let public_inputs = TableKindSetBuilder::<&[_]> {
-        cpu_skeleton_stark: &cpu_skeleton_stark,
+        cpu_skeleton_stark: public_inputs.borrow(),
         ..Default::default()
     }
     .build();
     challenger.compact();
     let challenger: &Challenger<F, C::Hasher> = &challenger.clone();
 
-    Ok(all_starks_par!(mozak_stark, |stark, kind| {
+    // Rayon's worker threads do not inherit the current span.
+    let span = Span::current();
+    all_starks_par!(mozak_stark, |stark, kind| {
+        let Some(trace_commitment) = trace_commitments[kind] else {
+            return Ok(StarkProof::skipped(
+                ctl_data_per_table[kind].len() + public_sub_data_per_table[kind].len(),
+            ));
+        };
+        let _span = info_span!(parent: &span, "prove_table", table = ?kind).entered();
         let mut timing = TimingTree::default();
         prove_single_table(
             stark,
             config,
-            &trace_commitments[kind],
+            trace_commitment,
+            fixed_commitments[kind],
             public_inputs[kind],
             &ctl_data_per_table[kind],
             &public_sub_data_per_table[kind],
             &mut challenger.clone(),
+            &mozak_stark.transcript.for_table(kind),
             &mut timing,
         )
-        .unwrap()
-    }))
+        .map_err(|source| ProverError::Table {
+            table: kind,
+            source,
+        })
+    })
+    .transpose()
 }
 
 #[cfg(test)]
 mod tests {
 
-    use mozak_runner::code;
+    use mozak_runner::code::{self, Code};
+    use mozak_runner::decode::ECALL;
+    use mozak_runner::elf::Program;
     use mozak_runner::instruction::{Args, Instruction, Op};
+    use mozak_runner::state::{RawTapes, State};
+    use mozak_runner::vm::{step, ExecutionRecord};
+    use mozak_sdk::core::ecall;
+    use mozak_sdk::core::reg_abi::REG_A0;
+    #[cfg(feature = "poseidon2")]
     use plonky2::field::goldilocks_field::GoldilocksField;
+    #[cfg(feature = "poseidon2")]
     use plonky2::field::types::Field;
+    #[cfg(feature = "poseidon2")]
     use plonky2::hash::poseidon2::Poseidon2Hash;
+    use plonky2::plonk::config::KeccakGoldilocksConfig;
+    #[cfg(feature = "poseidon2")]
     use plonky2::plonk::config::{GenericHashOut, Hasher};
+    use plonky2::util::timing::TimingTree;
 
-    use crate::stark::mozak_stark::MozakStark;
-    use crate::test_utils::{create_poseidon2_test, Poseidon2Test, ProveAndVerify};
+    use super::{prove, prove_dry_run, prove_with_static_commitments, StaticTableCommitments};
+    use crate::stark::error::{ProverError, VerifierError};
+    use crate::stark::mozak_stark::{
+        MozakStark, PublicInputs, TableKind, TableKindArray, SKIPPABLE_TABLE_KINDS,
+    };
+    use crate::stark::verifier::verify_proof;
+    #[cfg(feature = "poseidon2")]
+    use crate::test_utils::{create_poseidon2_test, Poseidon2Test, ScenarioBuilder};
+    use crate::test_utils::{
+        fast_test_config, prove_and_verify_mozak_stark_with_config, ProveAndVerify, C, D, F,
+    };
+    use crate::utils::from_u32;
 
     #[test]
     fn prove_halt() {
@@ -450,15 +1038,203 @@ mod tests {
     }
 
     #[test]
-    fn prove_lui() {
-        let lui = Instruction {
-            op: Op::ADD,
-            args: Args {
-                rd: 1,
-                imm: 0x8000_0000,
-                ..Args::default()
-            },
+    fn prove_halt_keccak() {
+        let (program, record) = code::execute([], &[], &[]);
+        prove_and_verify_mozak_stark_with_config::<KeccakGoldilocksConfig>(
+            &program,
+            &record,
+            &fast_test_config(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn prove_with_cached_static_commitments() {
+        let stark = MozakStark::default();
+        let config = fast_test_config();
+        let add = |imm| Instruction::li(1, imm);
+        let (program, record) = code::execute([add(1)], &[], &[]);
+        let static_commitments = StaticTableCommitments::<F, C, D>::new(&program, &config);
+        let public_inputs = PublicInputs::new(from_u32(program.entry_point));
+        let prove_with = |program: &Program, record: &ExecutionRecord<F>| {
+            prove_with_static_commitments(
+                program,
+                record,
+                &stark,
+                &config,
+                public_inputs,
+                Some(&static_commitments),
+                &mut TimingTree::default(),
+            )
         };
+
+        // The same commitments serve several proofs of the program.
+        for _ in 0..2 {
+            let proof = prove_with(&program, &record).unwrap();
+            assert_eq!(
+                proof.program_id,
+                static_commitments.program_id(public_inputs.entry_point)
+            );
+            verify_proof(&stark, proof, &config).unwrap();
+        }
+
+        // But not of another program.
+        let (other_program, other_record) = code::execute([add(2)], &[], &[]);
+        assert!(matches!(
+            prove_with(&other_program, &other_record),
+            Err(ProverError::StaticCommitmentMismatch {
+                table: TableKind::Program
+            })
+        ));
+    }
+
+    /// A program that calls into a linked library, which reads
+    /// `library_byte` from the library data. The program sets `x6` to `arg`
+    /// first.
+    fn linked_program(arg: u32, library_byte: u8) -> (Program, ExecutionRecord<F>) {
+        let code = |instructions: &[(u32, Instruction)]| {
+            Code(
+                instructions
+                    .iter()
+                    .map(|&(pc, instruction)| (pc, Ok(instruction)))
+                    .collect(),
+            )
+        };
+        let library = Program::create(
+            &[(0x2000, library_byte)],
+            &[],
+            code(&[
+                (0x1000, Instruction::lbu(5, 0, 0x2000)),
+                (0x1004, Instruction::jalr(0, 1, 0).unwrap()),
+            ]),
+        );
+        let program = Program::create(
+            &[],
+            &[],
+            code(&[
+                (0, Instruction::li(6, arg)),
+                (4, Instruction::jal(1, 0x1000).unwrap()),
+                (8, Instruction::li(REG_A0, ecall::HALT)),
+                (12, ECALL),
+            ]),
+        )
+        .link(library)
+        .unwrap();
+        let record = step(&program, State::new(program.clone(), RawTapes::default())).unwrap();
+        assert_eq!(
+            record.last_state.get_register_value(5),
+            u32::from(library_byte)
+        );
+        (program, record)
+    }
+
+    #[test]
+    fn prove_linked_library() {
+        let stark = MozakStark::default();
+        let config = fast_test_config();
+        let (program, record) = linked_program(1, 7);
+        let proof = prove::<F, C, D>(
+            &program,
+            &record,
+            &stark,
+            &config,
+            PublicInputs::for_execution(&program, &record),
+            &mut TimingTree::default(),
+        )
+        .unwrap();
+        let (program_id, library_id) = (proof.program_id, proof.library_id());
+        verify_proof(&stark, proof, &config).unwrap();
+
+        // Another program that links the same library has another program id,
+        // but the same library id.
+        let (other_program, _) = linked_program(2, 7);
+        let static_commitments = StaticTableCommitments::<F, C, D>::new(&other_program, &config);
+        assert_ne!(
+            static_commitments.program_id(from_u32(other_program.entry_point)),
+            program_id
+        );
+        assert_eq!(static_commitments.library_id(), library_id);
+    }
+
+    #[test]
+    fn swapped_library_fails() {
+        let stark = MozakStark::default();
+        let config = fast_test_config();
+        let (program, _) = linked_program(1, 7);
+        let pinned_program_id = StaticTableCommitments::<F, C, D>::new(&program, &config)
+            .program_id(from_u32(program.entry_point));
+
+        // The same program, linked against a library with other data.
+        let (other_program, record) = linked_program(1, 8);
+        let mut proof = prove::<F, C, D>(
+            &other_program,
+            &record,
+            &stark,
+            &config,
+            PublicInputs::for_execution(&other_program, &record),
+            &mut TimingTree::default(),
+        )
+        .unwrap();
+        assert_ne!(proof.program_id, pinned_program_id);
+        proof.program_id = pinned_program_id;
+        assert!(matches!(
+            verify_proof(&stark, proof, &config),
+            Err(VerifierError::ProgramIdMismatch)
+        ));
+    }
+
+    #[test]
+    fn dry_run_halt() {
+        let (program, record) = code::execute([], &[], &[]);
+        let public_inputs = PublicInputs::new(from_u32(program.entry_point));
+        prove_dry_run(
+            &program,
+            &record,
+            &MozakStark::default(),
+            &public_inputs,
+            &mut TimingTree::default(),
+        );
+    }
+
+    /// No table has a minimum height beyond [`MIN_TRACE_LENGTH`], except for
+    /// the fixed ones, like the 256 rows of the u8 range check. In particular,
+    /// the range check table only has a row per distinct value checked.
+    ///
+    /// [`MIN_TRACE_LENGTH`]: crate::generation::MIN_TRACE_LENGTH
+    #[test]
+    fn tiny_programs_stay_tiny() {
+        let (program, record) = code::execute([Instruction::add(5, 5, 6); 10], &[], &[(6, 1)]);
+        let public_inputs = PublicInputs::new(from_u32(program.entry_point));
+        let heights = prove_dry_run(
+            &program,
+            &record,
+            &MozakStark::default(),
+            &public_inputs,
+            &mut TimingTree::default(),
+        );
+        for &(&rows, kind) in &heights.each_ref().with_kind() {
+            assert!(rows <= 256, "{kind:?} has {rows} rows");
+        }
+        assert_eq!(heights[TableKind::RangeCheckU8], 256);
+    }
+
+    #[test]
+    #[should_panic(expected = "debug_api_has_constraint_failed")]
+    fn dry_run_catches_wrong_entry_point() {
+        let (program, record) = code::execute([], &[], &[]);
+        let public_inputs = PublicInputs::new(from_u32(program.entry_point + 4));
+        prove_dry_run(
+            &program,
+            &record,
+            &MozakStark::default(),
+            &public_inputs,
+            &mut TimingTree::default(),
+        );
+    }
+
+    #[test]
+    fn prove_lui() {
+        let lui = Instruction::lui(1, 0x8000_0000).unwrap();
         let (program, record) = code::execute([lui], &[], &[]);
         assert_eq!(record.last_state.get_register_value(1), 0x8000_0000);
         MozakStark::prove_and_verify(&program, &record).unwrap();
@@ -466,18 +1242,7 @@ mod tests {
 
     #[test]
     fn prove_lui_2() {
-        let (program, record) = code::execute(
-            [Instruction {
-                op: Op::ADD,
-                args: Args {
-                    rd: 1,
-                    imm: 0xDEAD_BEEF,
-                    ..Args::default()
-                },
-            }],
-            &[],
-            &[],
-        );
+        let (program, record) = code::execute([Instruction::li(1, 0xDEAD_BEEF)], &[], &[]);
         assert_eq!(record.last_state.get_register_value(1), 0xDEAD_BEEF,);
         MozakStark::prove_and_verify(&program, &record).unwrap();
     }
@@ -501,6 +1266,7 @@ mod tests {
         MozakStark::prove_and_verify(&program, &record).unwrap();
     }
 
+    #[cfg(feature = "poseidon2")]
     fn test_poseidon2(test_data: &[Poseidon2Test]) {
         let (program, record) = create_poseidon2_test(test_data);
         for test_datum in test_data {
@@ -523,6 +1289,7 @@ mod tests {
         MozakStark::prove_and_verify(&program, &record).unwrap();
     }
 
+    #[cfg(feature = "poseidon2")]
     #[test]
     fn prove_poseidon2() {
         test_poseidon2(&[Poseidon2Test {
@@ -550,4 +1317,67 @@ mod tests {
             },
         ]);
     }
+
+    #[cfg(feature = "poseidon2")]
+    #[test]
+    fn prove_poseidon2_xof() {
+        use mozak_sdk::native::poseidon::poseidon2_xof_no_pad;
+
+        let data = b"Mozak squeezes more than one digest";
+        let mut input = data.to_vec();
+        input.resize(input.len().next_multiple_of(8), 0);
+        let mut output = [0; 96];
+        poseidon2_xof_no_pad(&input, &mut output);
+        ScenarioBuilder::default()
+            .poseidon2_xof(data, 1024, 2048, output.len())
+            .expect_memory(2048, &output)
+            .prove_and_verify::<MozakStark<F, D>>()
+            .unwrap();
+    }
+
+    /// Proves the execution with a stark that skips unused tables, verifies
+    /// the proof with the default stark, and returns the skipped tables.
+    fn prove_skipping_unused_tables(
+        program: &Program,
+        record: &ExecutionRecord<F>,
+    ) -> TableKindArray<bool> {
+        let config = fast_test_config();
+        let proof = prove::<F, C, D>(
+            program,
+            record,
+            &MozakStark::default().skipping_unused_tables(),
+            &config,
+            PublicInputs::for_execution(program, record),
+            &mut TimingTree::default(),
+        )
+        .unwrap();
+        let skipped = proof.skipped;
+        verify_proof(&MozakStark::default(), proof, &config).unwrap();
+        skipped
+    }
+
+    #[test]
+    fn skip_unused_tables() {
+        let (program, record) = code::execute([Instruction::li(1, 5)], &[], &[]);
+        let skipped = prove_skipping_unused_tables(&program, &record);
+        for (skipped, kind) in &skipped.with_kind() {
+            assert!(!skipped || SKIPPABLE_TABLE_KINDS.contains(kind), "{kind:?}");
+        }
+        #[cfg(feature = "poseidon2")]
+        assert!(skipped[TableKind::Poseidon2Sponge]);
+    }
+
+    #[cfg(feature = "poseidon2")]
+    #[test]
+    fn used_tables_are_not_skipped() {
+        let (program, record) = create_poseidon2_test(&[Poseidon2Test {
+            data: "💥 Mozak-VM Rocks With Poseidon2".to_string(),
+            input_start_addr: 1024,
+            output_start_addr: 2048,
+        }]);
+        let skipped = prove_skipping_unused_tables(&program, &record);
+        assert!(!skipped[TableKind::Poseidon2]);
+        assert!(!skipped[TableKind::Poseidon2Sponge]);
+        assert!(!skipped[TableKind::Poseidon2OutputBytes]);
+    }
 }
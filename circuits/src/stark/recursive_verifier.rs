@@ -3,9 +3,8 @@ use std::borrow::Borrow;
 use std::fmt::Debug;
 use std::marker::PhantomData;
 
-use anyhow::Result;
+use anyhow::{ensure, Result};
 use itertools::{chain, zip_eq, Itertools};
-use log::info;
 use mozak_sdk::core::constants::DIGEST_BYTES;
 use plonky2::field::extension::Extendable;
 use plonky2::field::types::Field;
@@ -20,19 +19,22 @@ use plonky2::iop::target::Target;
 use plonky2::iop::witness::{PartialWitness, Witness, WitnessWrite};
 use plonky2::plonk::circuit_builder::CircuitBuilder;
 use plonky2::plonk::circuit_data::{
-    CircuitConfig, CircuitData, CommonCircuitData, VerifierCircuitTarget, VerifierOnlyCircuitData,
+    CircuitConfig, CircuitData, CommonCircuitData, VerifierCircuitData, VerifierCircuitTarget,
+    VerifierOnlyCircuitData,
 };
 use plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
 use plonky2::plonk::proof::{ProofWithPublicInputs, ProofWithPublicInputsTarget};
 use plonky2::util::reducing::ReducingFactorTarget;
+use plonky2::util::serialization::DefaultGateSerializer;
 use plonky2::with_context;
 use starky::config::StarkConfig;
 use starky::constraint_consumer::RecursiveConstraintConsumer;
 use starky::evaluation_frame::StarkEvaluationFrame;
 use starky::stark::{LookupConfig, Stark};
+use tracing::info;
 
 use super::mozak_stark::{all_kind, all_starks, TableKindArray};
-use crate::columns_view::{columns_view_impl, NumberOfColumns};
+use crate::columns_view::{ColumnsView, NumberOfColumns};
 use crate::cross_table_lookup::{
     verify_cross_table_lookups_and_public_sub_table_circuit, CrossTableLookup, CtlCheckVarsTarget,
 };
@@ -42,7 +44,7 @@ use crate::public_sub_table::{
 use crate::stark::batch_prover::{
     batch_fri_instances_target, batch_reduction_arity_bits, sort_degree_bits,
 };
-use crate::stark::mozak_stark::{MozakStark, TableKind};
+use crate::stark::mozak_stark::{MozakStark, PublicInputs, TableKind};
 use crate::stark::permutation::challenge::get_grand_product_challenge_set_target;
 use crate::stark::poly::eval_vanishing_poly_circuit;
 use crate::stark::proof::{
@@ -56,25 +58,51 @@ pub const VM_RECURSION_THRESHOLD_DEGREE_BITS: usize = 13;
 /// Public inputs (number of Goldilocks elements) using
 /// `standard_recursion_config`:
 ///   `entry_point`: 1
+///   `max_cycles`: 1
+///   `out_of_gas`: 1
 ///   `Program trace cap`: 16 (hash count with `cap_height` = 4) * 4 (size of a
 ///                          hash) = 64
 ///   `ElfMemoryInit trace cap`: 64
+///   `memory_layout`: 4
+///   `private_tape_digest`: 4, without the `poseidon2` feature 0
+///   `output_tape_digest`: 4, without the `poseidon2` feature 0
+///   `exit_code`: 1
 ///   `event commitment_tape`: 32
 ///   `castlist_commitment_tape`: 32
+///   `self_prog_id`: 32 * 3 (byte, index and multiplicity) = 96
 pub const VM_PUBLIC_INPUT_SIZE: usize = VMRecursiveProofPublicInputs::<()>::NUMBER_OF_COLUMNS;
 pub const VM_RECURSION_CONFIG: CircuitConfig = CircuitConfig::standard_recursion_config();
 
+/// Number of elements of a tape digest among the public inputs, none if tapes
+/// are not hashed.
+#[cfg(feature = "poseidon2")]
+const NUM_TAPE_DIGEST_ELTS: usize = plonky2::hash::hash_types::NUM_HASH_OUT_ELTS;
+#[cfg(not(feature = "poseidon2"))]
+const NUM_TAPE_DIGEST_ELTS: usize = 0;
+
 #[repr(C)]
-#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
 pub struct VMRecursiveProofPublicInputs<T> {
     pub entry_point: T,
+    /// See [`PublicInputs`].
+    pub max_cycles: T,
+    pub out_of_gas: T,
     pub program_hash_as_bytes: [T; DIGEST_BYTES],
+    /// See [`MemoryLayout`](crate::memoryinit::columns::MemoryLayout).
+    pub memory_layout: [T; 4],
+    /// See [`PrivateTapeDigest`](crate::private_tape_hash::columns::PrivateTapeDigest).
+    pub private_tape_digest: [T; NUM_TAPE_DIGEST_ELTS],
+    /// See [`OutputTapeDigest`](crate::output_tape::columns::OutputTapeDigest).
+    pub output_tape_digest: [T; NUM_TAPE_DIGEST_ELTS],
+    /// See [`ExitCode`](crate::cpu::columns::ExitCode).
+    pub exit_code: T,
     pub event_commitment_tape: [T; DIGEST_BYTES],
     pub castlist_commitment_tape: [T; DIGEST_BYTES],
+    /// `[byte, index, multiplicity]` of each byte of the
+    /// [`SelfProgramId`](crate::tape_commitments::columns::SelfProgramId).
+    pub self_prog_id: [[T; 3]; DIGEST_BYTES],
 }
 
-columns_view_impl!(VMRecursiveProofPublicInputs);
-
 #[derive(Eq, PartialEq, Debug)]
 pub struct MozakProofTarget<F, C, const D: usize>
 where
@@ -160,7 +188,15 @@ where
     C: GenericConfig<D, F = F>,
     C::Hasher: AlgebraicHasher<F>,
 {
+    /// # Errors
+    /// Errors if `all_proof` uses [preprocessed
+    /// commitments](super::preprocessed), which the circuit does not verify,
+    /// or if proving fails.
     pub fn prove(&self, all_proof: &AllProof<F, C, D>) -> Result<ProofWithPublicInputs<F, C, D>> {
+        ensure!(
+            all_proof.preprocessed.is_none(),
+            "recursive proofs of proofs with preprocessed commitments are not supported"
+        );
         let mut inputs = PartialWitness::new();
 
         all_kind!(|kind| {
@@ -193,7 +229,7 @@ where
             all_proof.public_inputs.borrow(),
         );
 
-        let program_id_elements = all_proof.program_id.0 .0.map(F::from_canonical_u8);
+        let program_id_elements = all_proof.program_id.inner().map(F::from_canonical_u8);
         inputs.set_target_arr(self.proof.program_id.as_ref(), &program_id_elements);
 
         self.circuit.prove(inputs)
@@ -239,7 +275,7 @@ where
             all_proof.public_inputs.borrow(),
         );
 
-        let program_id_elements = all_proof.program_id.0 .0.map(F::from_canonical_u8);
+        let program_id_elements = all_proof.program_id.inner().map(F::from_canonical_u8);
         inputs.set_target_arr(self.proof.program_id.as_ref(), &program_id_elements);
 
         set_stark_proof_with_pis_target(
@@ -1105,6 +1141,68 @@ where
     }
 }
 
+/// The verifier data of the final recursive VM proofs, ie the ones returned by
+/// [`shrink_to_target_degree_bits_circuit`] with [`VM_RECURSION_CONFIG`] and
+/// [`VM_RECURSION_THRESHOLD_DEGREE_BITS`].
+///
+/// `verifier_only` is the verifier key written next to a recursive proof. The
+/// common data is the same for all programs, so that other plonky2 circuits can
+/// verify Mozak proofs via [`verify_recursive_vm_proof`], or with
+/// `CircuitBuilder::verify_proof` against this data.
+#[must_use]
+pub fn vm_verifier_circuit_data<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+>(
+    verifier_only: VerifierOnlyCircuitData<C, D>,
+) -> VerifierCircuitData<F, C, D>
+where
+    C::Hasher: AlgebraicHasher<F>, {
+    let common = circuit_data_for_recursion::<F, C, D>(
+        &VM_RECURSION_CONFIG,
+        VM_RECURSION_THRESHOLD_DEGREE_BITS,
+        VM_PUBLIC_INPUT_SIZE,
+    )
+    .common;
+    VerifierCircuitData {
+        verifier_only,
+        common,
+    }
+}
+
+/// Serializes `data` with plonky2's default gate serializer, so that circuits
+/// built against another plonky2 can read it back with
+/// `VerifierCircuitData::from_bytes`.
+///
+/// # Errors
+/// Errors if `data` uses a gate that the default gate serializer does not know.
+pub fn vm_verifier_circuit_data_to_bytes<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+>(
+    data: &VerifierCircuitData<F, C, D>,
+) -> Result<Vec<u8>> {
+    data.to_bytes(&DefaultGateSerializer)
+        .map_err(|_| anyhow::Error::msg("VerifierCircuitData serialization failed."))
+}
+
+/// Reads verifier data written by [`vm_verifier_circuit_data_to_bytes`].
+///
+/// # Errors
+/// Errors if `bytes` is not serialized verifier data.
+pub fn vm_verifier_circuit_data_from_bytes<
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    const D: usize,
+>(
+    bytes: Vec<u8>,
+) -> Result<VerifierCircuitData<F, C, D>> {
+    VerifierCircuitData::from_bytes(bytes, &DefaultGateSerializer)
+        .map_err(|_| anyhow::Error::msg("VerifierCircuitData deserialization failed."))
+}
+
 /// Flat hash of trace cap.
 pub fn hash_trace_cap_circuit<F, C, const D: usize>(
     builder: &mut CircuitBuilder<F, D>,
@@ -1124,7 +1222,8 @@ where
 }
 
 /// Compute program hash and convert it
-/// to bytes in circuit
+/// to bytes in circuit, see
+/// [`get_program_id`](crate::stark::prover::get_program_id)
 pub fn get_program_hash_circuit_bytes<F, C, const D: usize>(
     builder: &mut CircuitBuilder<F, D>,
     proofs_target: &TableKindArray<StarkProofWithPublicInputsTarget<D>>,
@@ -1152,12 +1251,33 @@ where
         builder,
         &proofs_target[TableKind::ElfMemoryInit].proof.trace_cap,
     );
-    let entry_point = proofs_target[TableKind::CpuSkeleton].public_inputs.clone();
+    let library_program_rom_trace_cap_hash = hash_trace_cap_circuit::<F, C, D>(
+        builder,
+        &proofs_target[TableKind::LibraryProgram].proof.trace_cap,
+    );
+    let library_elf_memory_init_trace_cap_hash = hash_trace_cap_circuit::<F, C, D>(
+        builder,
+        &proofs_target[TableKind::LibraryElfMemoryInit]
+            .proof
+            .trace_cap,
+    );
+    let library_hash = builder.hash_pad::<C::InnerHasher>(
+        chain!(
+            library_program_rom_trace_cap_hash.elements,
+            library_elf_memory_init_trace_cap_hash.elements,
+        )
+        .collect_vec(),
+    );
+    let public_inputs: &PublicInputs<Target> = proofs_target[TableKind::CpuSkeleton]
+        .public_inputs
+        .as_slice()
+        .into();
     let program_hash = builder.hash_pad::<C::InnerHasher>(
         chain!(
-            entry_point,
+            [public_inputs.entry_point],
             program_rom_trace_cap_hash.elements,
             elf_memory_init_trace_cap_hash.elements,
+            library_hash.elements,
         )
         .collect_vec(),
     );
@@ -1177,9 +1297,8 @@ mod tests {
     use std::panic::AssertUnwindSafe;
 
     use anyhow::Result;
-    use log::info;
     use mozak_runner::code;
-    use mozak_runner::instruction::{Args, Instruction, Op};
+    use mozak_runner::instruction::Instruction;
     use mozak_sdk::core::constants::DIGEST_BYTES;
     use plonky2::field::goldilocks_field::GoldilocksField;
     use plonky2::field::types::Field;
@@ -1188,14 +1307,16 @@ mod tests {
     use plonky2::plonk::circuit_data::CircuitConfig;
     use plonky2::util::timing::TimingTree;
     use starky::config::StarkConfig;
+    use tracing::info;
 
     use crate::stark::batch_prover::batch_prove;
     use crate::stark::batch_verifier::batch_verify_proof;
     use crate::stark::mozak_stark::{MozakStark, PublicInputs, PUBLIC_TABLE_KINDS};
     use crate::stark::prover::prove;
     use crate::stark::recursive_verifier::{
-        recursive_batch_stark_circuit, recursive_mozak_stark_circuit,
-        shrink_to_target_degree_bits_circuit, verify_recursive_vm_proof,
+        circuit_data_for_recursion, recursive_batch_stark_circuit, recursive_mozak_stark_circuit,
+        shrink_to_target_degree_bits_circuit, verify_recursive_vm_proof, vm_verifier_circuit_data,
+        vm_verifier_circuit_data_from_bytes, vm_verifier_circuit_data_to_bytes,
         VMRecursiveProofPublicInputs, VM_PUBLIC_INPUT_SIZE, VM_RECURSION_CONFIG,
         VM_RECURSION_THRESHOLD_DEGREE_BITS,
     };
@@ -1209,22 +1330,9 @@ mod tests {
     fn recursive_verify_mozak_starks() -> Result<()> {
         let stark = S::default();
         let config = StarkConfig::standard_fast_config();
-        let (program, record) = code::execute(
-            [Instruction {
-                op: Op::ADD,
-                args: Args {
-                    rd: 5,
-                    rs1: 6,
-                    rs2: 7,
-                    ..Args::default()
-                },
-            }],
-            &[],
-            &[(6, 100), (7, 200)],
-        );
-        let public_inputs = PublicInputs {
-            entry_point: from_u32(program.entry_point),
-        };
+        let (program, record) =
+            code::execute([Instruction::add(5, 6, 7)], &[], &[(6, 100), (7, 200)]);
+        let public_inputs = PublicInputs::new(from_u32(program.entry_point));
 
         let mozak_proof = prove::<F, C, D>(
             &program,
@@ -1268,22 +1376,9 @@ mod tests {
     fn recursive_verify_batch_starks() -> Result<()> {
         let stark = S::default();
         let config = StarkConfig::standard_fast_config();
-        let (program, record) = code::execute(
-            [Instruction {
-                op: Op::ADD,
-                args: Args {
-                    rd: 5,
-                    rs1: 6,
-                    rs2: 7,
-                    ..Args::default()
-                },
-            }],
-            &[],
-            &[(6, 100), (7, 200)],
-        );
-        let public_inputs = PublicInputs {
-            entry_point: from_u32(program.entry_point),
-        };
+        let (program, record) =
+            code::execute([Instruction::add(5, 6, 7)], &[], &[(6, 100), (7, 200)]);
+        let public_inputs = PublicInputs::new(from_u32(program.entry_point));
 
         let (mozak_proof, degree_bits) = batch_prove::<F, C, D>(
             &program,
@@ -1337,25 +1432,30 @@ mod tests {
         mozak_stark_circuit.circuit.verify(recursive_proof)
     }
 
+    #[test]
+    fn vm_verifier_circuit_data_round_trip() -> Result<()> {
+        let circuit = circuit_data_for_recursion::<F, C, D>(
+            &VM_RECURSION_CONFIG,
+            VM_RECURSION_THRESHOLD_DEGREE_BITS,
+            VM_PUBLIC_INPUT_SIZE,
+        );
+        let data = vm_verifier_circuit_data(circuit.verifier_only.clone());
+        assert_eq!(data, circuit.verifier_data());
+
+        let bytes = vm_verifier_circuit_data_to_bytes(&data)?;
+        assert_eq!(vm_verifier_circuit_data_from_bytes(bytes)?, data);
+        Ok(())
+    }
+
     #[test]
     #[ignore]
     #[allow(clippy::too_many_lines)]
     fn same_circuit_verify_different_vm_proofs() -> Result<()> {
         let stark = S::default();
-        let inst = Instruction {
-            op: Op::ADD,
-            args: Args {
-                rd: 5,
-                rs1: 6,
-                rs2: 7,
-                ..Args::default()
-            },
-        };
+        let inst = Instruction::add(5, 6, 7);
 
         let (program0, record0) = code::execute([inst], &[], &[(6, 100), (7, 200)]);
-        let public_inputs = PublicInputs {
-            entry_point: from_u32(program0.entry_point),
-        };
+        let public_inputs = PublicInputs::new(from_u32(program0.entry_point));
         let stark_config0 = StarkConfig::standard_fast_config();
         let mozak_proof0 = prove::<F, C, D>(
             &program0,
@@ -1367,9 +1467,7 @@ mod tests {
         )?;
 
         let (program1, record1) = code::execute(vec![inst; 128], &[], &[(6, 100), (7, 200)]);
-        let public_inputs = PublicInputs {
-            entry_point: from_u32(program1.entry_point),
-        };
+        let public_inputs = PublicInputs::new(from_u32(program1.entry_point));
         let stark_config1 = StarkConfig::standard_fast_config();
         let mozak_proof1 = prove::<F, C, D>(
             &program1,
@@ -1454,6 +1552,18 @@ mod tests {
         final_circuit0.circuit.verify(final_proof0.clone())?;
         final_circuit1.circuit.verify(final_proof1.clone())?;
 
+        // The exported verifier data verifies the final proofs, without the circuits
+        // that made them.
+        for (final_circuit, final_proof) in [
+            (&final_circuit0, &final_proof0),
+            (&final_circuit1, &final_proof1),
+        ] {
+            let data = vm_verifier_circuit_data(final_circuit.circuit.verifier_only.clone());
+            let data =
+                vm_verifier_circuit_data_from_bytes(vm_verifier_circuit_data_to_bytes(&data)?)?;
+            data.verify(final_proof.clone())?;
+        }
+
         // It is still not possible to verify different VM proofs with the same
         // recursion circuit at this point. But the final proofs now have the same
         // degree bits.
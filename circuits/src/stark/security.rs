@@ -0,0 +1,369 @@
+//! Minimum security policy for proofs from untrusted provers.
+//!
+//! Proofs record the [`StarkConfig`] that made them as a [`ProofConfig`], but
+//! the verifier supplies the config it verifies with, and the proof has to
+//! match it. [`SecurityPolicy::check_proof`] checks both cheaply, so services
+//! that accept third-party proofs can reject weak or malformed ones right
+//! after deserializing, before any hashing or FRI work.
+//!
+//! [`ConfigProfile`] names the configs that we prove with.
+
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use plonky2::field::extension::Extendable;
+use plonky2::field::goldilocks_field::GoldilocksField;
+use plonky2::fri::FriConfig;
+use plonky2::hash::hash_types::RichField;
+use plonky2::plonk::config::GenericConfig;
+use plonky2::util::log2_ceil;
+use serde::{Deserialize, Serialize};
+use starky::config::StarkConfig;
+use starky::stark::Stark;
+
+use super::error::InsecureParameters;
+use super::proof::AllProof;
+use crate::cpu::stark::CpuStark;
+
+/// The parameters of the [`StarkConfig`] that made a proof, as recorded in
+/// [`AllProof::config`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofConfig {
+    pub security_bits: usize,
+    pub num_challenges: usize,
+    pub rate_bits: usize,
+    pub cap_height: usize,
+    pub proof_of_work_bits: u32,
+    pub num_query_rounds: usize,
+}
+
+impl From<&StarkConfig> for ProofConfig {
+    fn from(config: &StarkConfig) -> Self {
+        Self {
+            security_bits: config.security_bits,
+            num_challenges: config.num_challenges,
+            rate_bits: config.fri_config.rate_bits,
+            cap_height: config.fri_config.cap_height,
+            proof_of_work_bits: config.fri_config.proof_of_work_bits,
+            num_query_rounds: config.fri_config.num_query_rounds,
+        }
+    }
+}
+
+impl Display for ProofConfig {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "rate 2^-{}, {} queries, {} bits of proof of work, cap height {}",
+            self.rate_bits, self.num_query_rounds, self.proof_of_work_bits, self.cap_height
+        )
+    }
+}
+
+/// The configs that we prove with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ConfigProfile {
+    /// [`StarkConfig::standard_fast_config`], which meets the default
+    /// [`SecurityPolicy`].
+    #[default]
+    Production,
+    /// A config with next to no security, which proves and verifies quickly.
+    /// Only for tests.
+    Test,
+}
+
+impl ConfigProfile {
+    pub const ALL: [Self; 2] = [Self::Production, Self::Test];
+
+    #[must_use]
+    pub fn config(self) -> StarkConfig {
+        let config = StarkConfig::standard_fast_config();
+        match self {
+            ConfigProfile::Production => config,
+            ConfigProfile::Test => StarkConfig {
+                security_bits: 1,
+                num_challenges: 2,
+                fri_config: FriConfig {
+                    // Plonky2 says: "Having constraints of degree higher than the rate is not
+                    // supported yet." So we automatically set the rate here as required by
+                    // plonky2.
+                    // TODO(Matthias): Change to maximum of constraint degrees of all starks, as
+                    // we accumulate more types of starks.
+                    rate_bits: log2_ceil(
+                        CpuStark::<GoldilocksField, 2>::default().constraint_degree(),
+                    ),
+                    cap_height: 0,
+                    proof_of_work_bits: 0,
+                    num_query_rounds: 5,
+                    ..config.fri_config
+                },
+            },
+        }
+    }
+
+    /// The policy that proofs made with [`ConfigProfile::config`] meet.
+    #[must_use]
+    pub fn policy(self) -> SecurityPolicy {
+        match self {
+            ConfigProfile::Production => SecurityPolicy::default(),
+            ConfigProfile::Test => SecurityPolicy {
+                min_security_bits: 0,
+                ..SecurityPolicy::default()
+            },
+        }
+    }
+
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            ConfigProfile::Production => "production",
+            ConfigProfile::Test => "test",
+        }
+    }
+}
+
+impl Display for ConfigProfile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result { f.write_str(self.name()) }
+}
+
+impl FromStr for ConfigProfile {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|profile| profile.name() == name)
+            .ok_or_else(|| {
+                format!("unknown config profile {name:?}, expected `production` or `test`")
+            })
+    }
+}
+
+/// The weakest parameters a verifier accepts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SecurityPolicy {
+    /// Minimum conjectured bits of security of FRI, ie
+    /// `num_query_rounds * rate_bits + proof_of_work_bits`.
+    pub min_security_bits: usize,
+    /// Maximum height of Merkle caps, which bounds how many hashes a proof
+    /// can make us read per cap.
+    pub max_cap_height: usize,
+}
+
+impl Default for SecurityPolicy {
+    /// The 100 bits of [`StarkConfig::standard_fast_config`].
+    fn default() -> Self {
+        Self {
+            min_security_bits: 100,
+            max_cap_height: 16,
+        }
+    }
+}
+
+impl SecurityPolicy {
+    /// Checks that `config` meets the policy.
+    ///
+    /// # Errors
+    /// Errors if `config` has too few bits of security, or too high caps.
+    pub fn check_config(&self, config: &StarkConfig) -> Result<(), InsecureParameters> {
+        let fri = &config.fri_config;
+        let security_bits = fri.num_query_rounds * fri.rate_bits + fri.proof_of_work_bits as usize;
+        if security_bits < self.min_security_bits {
+            return Err(InsecureParameters::TooFewSecurityBits {
+                num_query_rounds: fri.num_query_rounds,
+                rate_bits: fri.rate_bits,
+                proof_of_work_bits: fri.proof_of_work_bits,
+                security_bits,
+                min_security_bits: self.min_security_bits,
+            });
+        }
+        if fri.cap_height > self.max_cap_height {
+            return Err(InsecureParameters::CapTooHigh {
+                cap_height: fri.cap_height,
+                max_cap_height: self.max_cap_height,
+            });
+        }
+        Ok(())
+    }
+
+    /// Checks that `config` meets the policy, that every table proof in
+    /// `all_proof` that was not skipped was made with its number of FRI
+    /// queries and cap height, and that `all_proof` records that it was made
+    /// with `config`, if it records a config at all.
+    ///
+    /// # Errors
+    /// Errors if `config` does not meet the policy, or `all_proof` does not
+    /// match `config`.
+    pub fn check_proof<F, C, const D: usize>(
+        &self,
+        config: &StarkConfig,
+        all_proof: &AllProof<F, C, D>,
+    ) -> Result<(), InsecureParameters>
+    where
+        F: RichField + Extendable<D>,
+        C: GenericConfig<D, F = F>, {
+        self.check_config(config)?;
+        let num_query_rounds = config.fri_config.num_query_rounds;
+        let cap_len = 1 << config.fri_config.cap_height;
+        for (proof, table) in &all_proof.proofs.each_ref().with_kind() {
+            // Skipped tables have no FRI proof; the verifier checks that
+            // their proofs are empty.
+            if all_proof.skipped[*table] {
+                continue;
+            }
+            let fri_proof = &proof.opening_proof;
+            let actual = fri_proof.query_round_proofs.len();
+            if actual != num_query_rounds {
+                return Err(InsecureParameters::QueryRoundsMismatch {
+                    table: *table,
+                    expected: num_query_rounds,
+                    actual,
+                });
+            }
+            let caps = [
+                &proof.trace_cap,
+                &proof.ctl_zs_cap,
+                &proof.quotient_polys_cap,
+            ]
+            .into_iter()
+            .chain(&fri_proof.commit_phase_merkle_caps);
+            for cap in caps {
+                if cap.len() != cap_len {
+                    return Err(InsecureParameters::CapLengthMismatch {
+                        table: *table,
+                        expected: cap_len,
+                        actual: cap.len(),
+                    });
+                }
+            }
+        }
+        let expected = ProofConfig::from(config);
+        match all_proof.config {
+            Some(recorded) if recorded != expected =>
+                Err(InsecureParameters::ConfigMismatch { recorded, expected }),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mozak_runner::code;
+    use mozak_runner::instruction::Instruction;
+    use plonky2::util::timing::TimingTree;
+    use starky::config::StarkConfig;
+
+    use super::{ConfigProfile, ProofConfig, SecurityPolicy};
+    use crate::stark::error::{InsecureParameters, VerifierError};
+    use crate::stark::mozak_stark::{MozakStark, PublicInputs};
+    use crate::stark::prover::prove;
+    use crate::stark::verifier::verify_proof;
+    use crate::test_utils::{fast_test_config, C, D, F};
+    use crate::utils::from_u32;
+
+    #[test]
+    fn check_config() {
+        let policy = SecurityPolicy::default();
+        policy
+            .check_config(&StarkConfig::standard_fast_config())
+            .unwrap();
+        assert!(matches!(
+            policy.check_config(&fast_test_config()),
+            Err(InsecureParameters::TooFewSecurityBits { .. })
+        ));
+        let mut config = StarkConfig::standard_fast_config();
+        config.fri_config.cap_height = 20;
+        assert_eq!(
+            policy.check_config(&config),
+            Err(InsecureParameters::CapTooHigh {
+                cap_height: 20,
+                max_cap_height: 16
+            })
+        );
+    }
+
+    #[test]
+    fn check_proof_shape() -> anyhow::Result<()> {
+        let (program, record) = code::execute([Instruction::addi(5, 6, 7)], &[], &[(6, 100)]);
+        let config = fast_test_config();
+        let all_proof = prove::<F, C, D>(
+            &program,
+            &record,
+            &MozakStark::default(),
+            &config,
+            PublicInputs::new(from_u32(program.entry_point)),
+            &mut TimingTree::default(),
+        )?;
+        let policy = SecurityPolicy {
+            min_security_bits: 0,
+            ..SecurityPolicy::default()
+        };
+        policy.check_proof(&config, &all_proof)?;
+
+        let mut more_queries = config.clone();
+        more_queries.fri_config.num_query_rounds += 1;
+        assert!(matches!(
+            policy.check_proof(&more_queries, &all_proof),
+            Err(InsecureParameters::QueryRoundsMismatch { .. })
+        ));
+
+        let mut higher_caps = config;
+        higher_caps.fri_config.cap_height += 1;
+        assert!(matches!(
+            policy.check_proof(&higher_caps, &all_proof),
+            Err(InsecureParameters::CapLengthMismatch {
+                expected: 2,
+                actual: 1,
+                ..
+            })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn reject_test_config_proof() -> anyhow::Result<()> {
+        let (program, record) = code::execute([Instruction::addi(5, 6, 7)], &[], &[(6, 100)]);
+        let stark = MozakStark::default();
+        let config = ConfigProfile::Test.config();
+        let all_proof = prove::<F, C, D>(
+            &program,
+            &record,
+            &stark,
+            &config,
+            PublicInputs::new(from_u32(program.entry_point)),
+            &mut TimingTree::default(),
+        )?;
+        assert_eq!(all_proof.config, Some(ProofConfig::from(&config)));
+        ConfigProfile::Test
+            .policy()
+            .check_proof(&config, &all_proof)?;
+
+        // A verifier that expects proofs of the same shape with more proof of
+        // work notices that the proof was made with a test config.
+        let mut more_work = config.clone();
+        more_work.fri_config.proof_of_work_bits += 1;
+        assert!(matches!(
+            ConfigProfile::Test
+                .policy()
+                .check_proof(&more_work, &all_proof),
+            Err(InsecureParameters::ConfigMismatch { .. })
+        ));
+        assert!(matches!(
+            verify_proof(&stark, all_proof, &more_work),
+            Err(VerifierError::ConfigMismatch { .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn parse_config_profiles() {
+        for profile in ConfigProfile::ALL {
+            assert_eq!(profile.to_string().parse(), Ok(profile));
+        }
+        assert!("staging".parse::<ConfigProfile>().is_err());
+        ConfigProfile::Production
+            .policy()
+            .check_config(&ConfigProfile::Production.config())
+            .unwrap();
+    }
+}
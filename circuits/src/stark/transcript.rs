@@ -0,0 +1,308 @@
+//! A record of the Fiat-Shamir transcript of a proof.
+//!
+//! When a [`Transcript`] is [recording](Transcript::recording), the prover and
+//! the verifier log every value they observe into their challengers and every
+//! challenge they sample, labelled with the table and the commitment they
+//! belong to. Proving and verifying the same proof with recording transcripts
+//! has to yield the same entries, so [`Transcript::first_difference`] points
+//! at the first step of a Fiat-Shamir mismatch between the two.
+//!
+//! The prover runs FRI inside [`PolynomialBatch::prove_openings`], so it
+//! records the FRI challenges by replaying them on a copy of its challenger,
+//! from the opening proof it produced.
+//!
+//! [`PolynomialBatch::prove_openings`]: plonky2::fri::oracle::PolynomialBatch::prove_openings
+
+use std::fmt::{self, Display, Formatter};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use itertools::Itertools;
+use plonky2::field::extension::{Extendable, FieldExtension};
+use plonky2::fri::proof::{FriChallenges, FriProof};
+use plonky2::fri::structure::FriOpenings;
+use plonky2::hash::hash_types::RichField;
+use plonky2::hash::merkle_tree::MerkleCap;
+use plonky2::plonk::config::{GenericHashOut, Hasher};
+
+use super::mozak_stark::TableKind;
+use crate::stark::permutation::challenge::GrandProductChallengeSet;
+
+/// Whether a transcript entry went into the challenger or came out of it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Event {
+    Observe,
+    Sample,
+}
+
+/// A single step of the transcript.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Entry {
+    /// The table whose proof the step belongs to, or `None` for the steps
+    /// shared by all tables.
+    pub table: Option<TableKind>,
+    pub label: String,
+    pub event: Event,
+    /// The field elements, in canonical form.
+    pub values: Vec<u64>,
+}
+
+impl Display for Entry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let event = match self.event {
+            Event::Observe => "observe",
+            Event::Sample => "sample",
+        };
+        match self.table {
+            Some(table) => write!(f, "{table:?}")?,
+            None => write!(f, "all")?,
+        }
+        write!(
+            f,
+            " {event} {}: {}",
+            self.label,
+            self.values.iter().join(" ")
+        )
+    }
+}
+
+/// Collects the [`Entry`]s of a transcript.
+///
+/// The default transcript records nothing, and costs nothing. Clones share
+/// their entries, so that the proofs of all tables record into the same
+/// transcript, even from different threads.
+#[derive(Clone, Debug, Default)]
+pub struct Transcript {
+    entries: Option<Arc<Mutex<Vec<Entry>>>>,
+    table: Option<TableKind>,
+}
+
+fn canonical<F: RichField>(values: &[F]) -> Vec<u64> {
+    values.iter().map(F::to_canonical_u64).collect()
+}
+
+fn canonical_extension<F: RichField + Extendable<D>, const D: usize>(
+    values: &[F::Extension],
+) -> Vec<u64> {
+    values
+        .iter()
+        .flat_map(FieldExtension::to_basefield_array)
+        .map(|x| x.to_canonical_u64())
+        .collect()
+}
+
+impl Transcript {
+    /// A transcript that records its entries.
+    #[must_use]
+    pub fn recording() -> Self {
+        Self {
+            entries: Some(Arc::default()),
+            table: None,
+        }
+    }
+
+    #[must_use]
+    pub fn is_recording(&self) -> bool { self.entries.is_some() }
+
+    /// The transcript of the proof of `table`, which shares the entries of
+    /// this one.
+    #[must_use]
+    pub fn for_table(&self, table: TableKind) -> Self {
+        Self {
+            entries: self.entries.clone(),
+            table: Some(table),
+        }
+    }
+
+    fn push(&self, label: &str, event: Event, values: impl FnOnce() -> Vec<u64>) {
+        if let Some(entries) = &self.entries {
+            entries
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push(Entry {
+                    table: self.table,
+                    label: label.to_string(),
+                    event,
+                    values: values(),
+                });
+        }
+    }
+
+    pub fn observe<F: RichField>(&self, label: &str, values: &[F]) {
+        self.push(label, Event::Observe, || canonical(values));
+    }
+
+    pub fn sample<F: RichField>(&self, label: &str, values: &[F]) {
+        self.push(label, Event::Sample, || canonical(values));
+    }
+
+    pub fn sample_extension<F: RichField + Extendable<D>, const D: usize>(
+        &self,
+        label: &str,
+        values: &[F::Extension],
+    ) {
+        self.push(label, Event::Sample, || canonical_extension::<F, D>(values));
+    }
+
+    pub fn observe_cap<F: RichField, H: Hasher<F>>(&self, label: &str, cap: &MerkleCap<F, H>) {
+        self.push(label, Event::Observe, || {
+            canonical(&cap.0.iter().flat_map(GenericHashOut::to_vec).collect_vec())
+        });
+    }
+
+    pub fn observe_openings<F: RichField + Extendable<D>, const D: usize>(
+        &self,
+        openings: &FriOpenings<F, D>,
+    ) {
+        self.push("openings", Event::Observe, || {
+            openings
+                .batches
+                .iter()
+                .flat_map(|batch| canonical_extension::<F, D>(&batch.values))
+                .collect()
+        });
+    }
+
+    /// Records the cross table lookup challenges.
+    pub fn sample_ctl_challenges<F: RichField>(
+        &self,
+        ctl_challenges: &GrandProductChallengeSet<F>,
+    ) {
+        if !self.is_recording() {
+            return;
+        }
+        for (i, challenge) in ctl_challenges.challenges.iter().enumerate() {
+            self.sample(&format!("ctl beta {i}"), &[challenge.beta]);
+            self.sample(&format!("ctl gamma {i}"), &[challenge.gamma]);
+        }
+    }
+
+    /// Records the steps of FRI, in the order in which the challenger takes
+    /// them.
+    pub fn record_fri<F: RichField + Extendable<D>, H: Hasher<F>, const D: usize>(
+        &self,
+        proof: &FriProof<F, H, D>,
+        challenges: &FriChallenges<F, D>,
+    ) {
+        if !self.is_recording() {
+            return;
+        }
+        self.sample_extension::<F, D>("fri alpha", &[challenges.fri_alpha]);
+        for (i, (cap, beta)) in proof
+            .commit_phase_merkle_caps
+            .iter()
+            .zip(&challenges.fri_betas)
+            .enumerate()
+        {
+            self.observe_cap(&format!("fri commit phase cap {i}"), cap);
+            self.sample_extension::<F, D>(&format!("fri beta {i}"), &[*beta]);
+        }
+        self.push("fri final poly", Event::Observe, || {
+            canonical_extension::<F, D>(&proof.final_poly.coeffs)
+        });
+        self.observe("fri pow witness", &[proof.pow_witness]);
+        self.sample("fri pow response", &[challenges.fri_pow_response]);
+        self.push("fri query indices", Event::Sample, || {
+            challenges
+                .fri_query_indices
+                .iter()
+                .map(|&index| index as u64)
+                .collect()
+        });
+    }
+
+    /// The recorded entries: first those shared by all tables, then those of
+    /// each table, in the order of [`TableKind`]. Within each, the entries
+    /// keep the order in which they were recorded.
+    #[must_use]
+    pub fn entries(&self) -> Vec<Entry> {
+        let mut entries = self.entries.as_ref().map_or_else(Vec::new, |entries| {
+            entries
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .clone()
+        });
+        entries.sort_by_key(|entry| entry.table.map(|table| table as usize));
+        entries
+    }
+
+    /// The index into [`Transcript::entries`] of the first entry that differs
+    /// from `other`, or `None` if both transcripts are the same.
+    #[must_use]
+    pub fn first_difference(&self, other: &Self) -> Option<usize> {
+        let (ours, theirs) = (self.entries(), other.entries());
+        ours.iter()
+            .zip(&theirs)
+            .position(|(ours, theirs)| ours != theirs)
+            .or_else(|| (ours.len() != theirs.len()).then(|| ours.len().min(theirs.len())))
+    }
+}
+
+/// One line per entry, for diffing.
+impl Display for Transcript {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.entries()
+            .iter()
+            .try_for_each(|entry| writeln!(f, "{entry}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mozak_runner::code;
+    use mozak_runner::instruction::Instruction;
+    use plonky2::field::extension::Extendable;
+    use plonky2::field::types::Field;
+    use plonky2::util::timing::TimingTree;
+
+    use super::Transcript;
+    use crate::generation::generate_traces;
+    use crate::stark::mozak_stark::{MozakStark, PublicInputs, TableKind};
+    use crate::stark::prover::prove_with_traces;
+    use crate::stark::verifier::verify_proof;
+    use crate::test_utils::{fast_test_config, C, D, F};
+    use crate::trace_source::Execution;
+
+    fn stark() -> MozakStark<F, D> {
+        MozakStark::default().with_transcript(Transcript::recording())
+    }
+
+    #[test]
+    fn prover_and_verifier_agree_on_the_transcript() {
+        let (program, record) =
+            code::execute([Instruction::add(5, 6, 7)], &[], &[(6, 100), (7, 200)]);
+        let config = fast_test_config();
+        let traces = generate_traces(
+            &Execution::new(&program, &record),
+            &mut TimingTree::default(),
+        );
+
+        let (prover, verifier) = (stark(), stark());
+        let mut proof = prove_with_traces::<F, C, D>(
+            &prover,
+            &config,
+            PublicInputs::for_execution(&program, &record),
+            &traces,
+            None,
+            &mut TimingTree::default(),
+        )
+        .unwrap();
+        verify_proof(&verifier, proof.clone(), &config).unwrap();
+        assert!(!prover.transcript.entries().is_empty());
+        assert_eq!(
+            prover.transcript.first_difference(&verifier.transcript),
+            None,
+            "{}",
+            prover.transcript
+        );
+
+        // A different opening changes the transcript of its table.
+        proof.proofs[TableKind::Cpu].openings.local_values[0] +=
+            <F as Extendable<D>>::Extension::ONE;
+        let tampered = stark();
+        let _ = verify_proof(&tampered, proof, &config);
+        assert_ne!(
+            prover.transcript.first_difference(&tampered.transcript),
+            None
+        );
+    }
+}
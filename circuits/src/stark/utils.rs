@@ -1,7 +1,7 @@
-use itertools::Itertools;
+use std::slice::ChunksExact;
+
 use plonky2::field::polynomial::PolynomialValues;
 use plonky2::field::types::Field;
-use plonky2::util::transpose;
 
 #[must_use]
 pub fn trace_to_poly_values<F: Field, Grid: IntoIterator<Item = Vec<F>>>(
@@ -10,16 +10,133 @@ pub fn trace_to_poly_values<F: Field, Grid: IntoIterator<Item = Vec<F>>>(
     trace.into_iter().map(PolynomialValues::new).collect()
 }
 
-/// Transform a given row-major trace to a column-major trace by flipping it
-/// over its diagonal.
-#[must_use]
-pub fn transpose_trace<F: Field, Row: IntoIterator<Item = F>>(trace_rows: Vec<Row>) -> Vec<Vec<F>> {
-    transpose(
-        &trace_rows
-            .into_iter()
-            .map(|row| row.into_iter().collect_vec())
-            .collect_vec(),
-    )
+/// A trace held in a single contiguous allocation, either row-major or
+/// column-major.
+///
+/// Going from row structs to [`PolynomialValues`] through a [`TraceMatrix`]
+/// copies each cell into the buffer once, transposes the buffer in place, and
+/// copies each column out once. The buffer keeps its allocation between
+/// tables, so reusing one matrix to convert every table of a proof only costs
+/// one extra buffer, the size of the largest table.
+#[derive(Clone, Debug, Default)]
+pub struct TraceMatrix<F> {
+    values: Vec<F>,
+    width: usize,
+    height: usize,
+    column_major: bool,
+}
+
+impl<F: Field> TraceMatrix<F> {
+    /// Number of columns of the trace.
+    #[must_use]
+    pub fn width(&self) -> usize { self.width }
+
+    /// Number of rows of the trace.
+    #[must_use]
+    pub fn height(&self) -> usize { self.height }
+
+    /// Replaces the contents with a row-major copy of `trace_rows`.
+    ///
+    /// # Panics
+    /// Panics if the rows do not all have the same width.
+    pub fn load_rows<Row: IntoIterator<Item = F>>(&mut self, trace_rows: Vec<Row>) {
+        self.values.clear();
+        self.height = trace_rows.len();
+        self.width = 0;
+        self.column_major = false;
+        for (i, row) in trace_rows.into_iter().enumerate() {
+            self.values.extend(row);
+            if i == 0 {
+                self.width = self.values.len();
+            }
+            assert_eq!(self.values.len(), (i + 1) * self.width, "ragged row {i}");
+        }
+    }
+
+    /// Replaces the contents with a column-major copy of `trace_cols`.
+    ///
+    /// # Panics
+    /// Panics if the columns do not all have the same length.
+    pub fn load_columns(&mut self, trace_cols: &[PolynomialValues<F>]) {
+        self.values.clear();
+        self.width = trace_cols.len();
+        self.height = trace_cols.first().map_or(0, PolynomialValues::len);
+        self.column_major = true;
+        for (i, col) in trace_cols.iter().enumerate() {
+            assert_eq!(col.len(), self.height, "ragged column {i}");
+            self.values.extend_from_slice(&col.values);
+        }
+    }
+
+    /// Switches between row-major and column-major, in place.
+    pub fn transpose(&mut self) {
+        let (outer, inner) = if self.column_major {
+            (self.width, self.height)
+        } else {
+            (self.height, self.width)
+        };
+        transpose_in_place(&mut self.values, outer, inner);
+        self.column_major = !self.column_major;
+    }
+
+    /// The rows of the trace, transposing first if needed.
+    pub fn rows(&mut self) -> ChunksExact<'_, F> {
+        if self.column_major {
+            self.transpose();
+        }
+        self.values.chunks_exact(self.width.max(1))
+    }
+
+    /// The columns of the trace, transposing first if needed.
+    pub fn columns(&mut self) -> ChunksExact<'_, F> {
+        if !self.column_major {
+            self.transpose();
+        }
+        self.values.chunks_exact(self.height.max(1))
+    }
+
+    /// Converts a row-major trace to the format that `prove` expects, reusing
+    /// this matrix as scratch space.
+    #[must_use]
+    pub fn rows_to_poly_values<Row: IntoIterator<Item = F>>(
+        &mut self,
+        trace_rows: Vec<Row>,
+    ) -> Vec<PolynomialValues<F>> {
+        self.load_rows(trace_rows);
+        self.columns()
+            .map(|col| PolynomialValues::new(col.to_vec()))
+            .collect()
+    }
+}
+
+/// Transposes a row-major `rows` by `cols` matrix into a row-major `cols` by
+/// `rows` matrix, in place.
+///
+/// Every cell but the last moves from index `i` to `i * rows mod (len - 1)`,
+/// so we follow each cycle of that permutation once, and track the visited
+/// cells in a bitset.
+fn transpose_in_place<F: Copy>(values: &mut [F], rows: usize, cols: usize) {
+    debug_assert_eq!(values.len(), rows * cols);
+    if rows <= 1 || cols <= 1 {
+        return;
+    }
+    let last = values.len() - 1;
+    let mut visited = vec![0_u64; values.len().div_ceil(64)];
+    for start in 1..last {
+        if visited[start / 64] & (1 << (start % 64)) != 0 {
+            continue;
+        }
+        let mut i = start;
+        let mut carried = values[start];
+        loop {
+            i = i * rows % last;
+            std::mem::swap(&mut values[i], &mut carried);
+            visited[i / 64] |= 1 << (i % 64);
+            if i == start {
+                break;
+            }
+        }
+    }
 }
 
 /// A helper function to transpose a row-wise trace and put it in the format
@@ -29,5 +146,39 @@ pub fn transpose_trace<F: Field, Row: IntoIterator<Item = F>>(trace_rows: Vec<Ro
 pub fn trace_rows_to_poly_values<F: Field, Row: IntoIterator<Item = F>>(
     trace_rows: Vec<Row>,
 ) -> Vec<PolynomialValues<F>> {
-    trace_to_poly_values(transpose_trace(trace_rows))
+    TraceMatrix::default().rows_to_poly_values(trace_rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::util::transpose;
+
+    use super::*;
+
+    type F = GoldilocksField;
+
+    fn grid(height: usize, width: usize) -> Vec<Vec<F>> {
+        (0..height)
+            .map(|r| {
+                (0..width)
+                    .map(|c| F::from_canonical_usize(r * width + c))
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn matches_out_of_place_transpose() {
+        let mut matrix = TraceMatrix::default();
+        for (height, width) in [(1, 1), (1, 5), (5, 1), (4, 4), (8, 3), (3, 8), (16, 13)] {
+            let rows = grid(height, width);
+            let expected = transpose(&rows);
+            let polys = matrix.rows_to_poly_values(rows.clone());
+            assert_eq!(polys.into_iter().map(|p| p.values).collect_vec(), expected);
+            assert_eq!((matrix.height(), matrix.width()), (height, width));
+            assert_eq!(matrix.rows().map(<[F]>::to_vec).collect_vec(), rows);
+        }
+    }
 }
@@ -1,43 +1,78 @@
 use std::borrow::Borrow;
 
-use anyhow::{ensure, Result};
-use itertools::Itertools;
-use log::debug;
+use anyhow::{anyhow, ensure, Result};
+use itertools::{chain, Itertools};
 use plonky2::field::extension::{Extendable, FieldExtension};
 use plonky2::field::types::Field;
 use plonky2::fri::verifier::verify_fri_proof;
 use plonky2::hash::hash_types::RichField;
-use plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
+use plonky2::hash::merkle_tree::MerkleCap;
+use plonky2::plonk::config::GenericConfig;
 use plonky2::plonk::plonk_common::reduce_with_powers;
 use starky::config::StarkConfig;
 use starky::constraint_consumer::ConstraintConsumer;
 use starky::evaluation_frame::StarkEvaluationFrame;
 use starky::stark::{LookupConfig, Stark};
+use tracing::debug;
 
+use super::error::{VerifierError, VerifierPhase};
 use super::mozak_stark::{all_starks, MozakStark, TableKind, TableKindSetBuilder};
+use super::preprocessed::{self, PreprocessedCaps};
 use super::proof::AllProof;
+use super::security::ProofConfig;
 use crate::cross_table_lookup::{verify_cross_table_lookups_and_public_sub_tables, CtlCheckVars};
-use crate::public_sub_table::reduce_public_sub_tables_values;
+use crate::public_sub_table::{
+    check_public_sub_table_values_shape, reduce_public_sub_tables_values,
+};
 use crate::stark::poly::eval_vanishing_poly;
 use crate::stark::proof::{AllProofChallenges, StarkOpeningSet, StarkProof, StarkProofChallenges};
 use crate::stark::prover::get_program_id;
+use crate::tape_commitments::columns::check_self_program_id;
+
+/// Verifies a proof made by [`prove`](super::prover::prove).
+///
+/// Proofs that use [preprocessed commitments](super::preprocessed) are checked
+/// against the commitments computed from `config`, see
+/// [`verify_proof_with_preprocessed`] to skip computing them.
+///
+/// # Errors
+/// Errors if the proof is invalid, see [`VerifierError`].
+pub fn verify_proof<F, C, const D: usize>(
+    mozak_stark: &MozakStark<F, D>,
+    all_proof: AllProof<F, C, D>,
+    config: &StarkConfig,
+) -> Result<(), VerifierError>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>, {
+    verify_proof_with_preprocessed(mozak_stark, all_proof, config, None)
+}
 
+/// Like [`verify_proof`], but checks proofs that use [preprocessed
+/// commitments](super::preprocessed) against the `known` ones, eg
+/// [loaded](PreprocessedCaps::load) from a trusted preprocessing run.
+///
+/// # Errors
+/// Errors if the proof is invalid, see [`VerifierError`].
 #[allow(clippy::too_many_lines)]
-pub fn verify_proof<F, C, const D: usize>(
+pub fn verify_proof_with_preprocessed<F, C, const D: usize>(
     mozak_stark: &MozakStark<F, D>,
     all_proof: AllProof<F, C, D>,
     config: &StarkConfig,
-) -> Result<()>
+    known: Option<&PreprocessedCaps<F, C::Hasher>>,
+) -> Result<(), VerifierError>
 where
     F: RichField + Extendable<D>,
-    C: GenericConfig<D, F = F>,
-    <C as GenericConfig<D>>::Hasher: AlgebraicHasher<F>, {
+    C: GenericConfig<D, F = F>, {
     debug!("Starting Verify");
 
+    check_config(&all_proof, config)?;
+    let preprocessed = preprocessed_caps(&all_proof, config, known)?;
+
     let AllProofChallenges {
         stark_challenges,
         ctl_challenges,
-    } = all_proof.get_challenges(config);
+    } = all_proof.get_challenges(config, &mozak_stark.transcript);
 
     let ctl_vars_per_table = CtlCheckVars::from_proofs(
         &all_proof.proofs,
@@ -46,6 +81,11 @@ where
         &ctl_challenges,
     );
 
+    check_public_sub_table_values_shape(
+        &mozak_stark.public_sub_tables,
+        &all_proof.public_sub_table_values,
+    )
+    .map_err(VerifierError::PublicSubTableShape)?;
     let reduced_public_sub_tables_values =
         reduce_public_sub_tables_values(&all_proof.public_sub_table_values, &ctl_challenges);
 
@@ -59,18 +99,33 @@ where
         all_proof.public_inputs.entry_point,
         &all_proof.proofs[TableKind::Program].trace_cap,
         &all_proof.proofs[TableKind::ElfMemoryInit].trace_cap,
+        &all_proof.proofs[TableKind::LibraryProgram].trace_cap,
+        &all_proof.proofs[TableKind::LibraryElfMemoryInit].trace_cap,
     );
-    ensure!(program_id == all_proof.program_id);
+    if program_id != all_proof.program_id {
+        return Err(VerifierError::ProgramIdMismatch);
+    }
+    check_self_program_id(
+        &mozak_stark.public_sub_tables,
+        &all_proof.public_sub_table_values,
+        program_id,
+    )
+    .map_err(VerifierError::SelfProgramIdMismatch)?;
 
     all_starks!(mozak_stark, |stark, kind| {
-        verify_stark_proof_with_challenges(
-            stark,
-            &all_proof.proofs[kind],
-            &stark_challenges[kind],
-            public_inputs[kind],
-            &ctl_vars_per_table[kind],
-            config,
-        )?;
+        match &stark_challenges[kind] {
+            Some(challenges) => verify_stark_proof_with_challenges(
+                stark,
+                kind,
+                &all_proof.proofs[kind],
+                preprocessed.as_ref().and_then(|caps| caps.get(kind)),
+                challenges,
+                public_inputs[kind],
+                &ctl_vars_per_table[kind],
+                config,
+            )?,
+            None => verify_skipped(mozak_stark, kind, &all_proof.proofs[kind])?,
+        }
     });
 
     verify_cross_table_lookups_and_public_sub_tables::<F, D>(
@@ -79,12 +134,145 @@ where
         &reduced_public_sub_tables_values,
         &all_proof.all_ctl_zs_last(),
         config,
-    )?;
+    )
+    .map_err(VerifierError::CrossTableLookup)?;
     debug!("Verified");
 
     Ok(())
 }
 
+/// Verifies the proof of a single table of `all_proof`, without checking the
+/// cross table lookups or the program id.
+///
+/// This is meant for debugging: when [`verify_proof`] fails, it narrows the
+/// failure down to the tables whose own proofs do not hold.
+///
+/// # Errors
+/// Errors if the proof of the table is invalid, see [`VerifierError`].
+pub fn verify_single_table<F, C, const D: usize>(
+    mozak_stark: &MozakStark<F, D>,
+    kind: TableKind,
+    all_proof: &AllProof<F, C, D>,
+    config: &StarkConfig,
+) -> Result<(), VerifierError>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>, {
+    debug!("Verifying {kind:?} on its own");
+
+    check_config(all_proof, config)?;
+    let preprocessed = preprocessed_caps(all_proof, config, None)?;
+
+    // The challenges come from the transcript of all tables, so we still need
+    // all of them, even if only one gets checked.
+    let AllProofChallenges {
+        stark_challenges,
+        ctl_challenges,
+    } = all_proof.get_challenges(config, &mozak_stark.transcript);
+
+    let ctl_vars_per_table = CtlCheckVars::from_proofs(
+        &all_proof.proofs,
+        &mozak_stark.cross_table_lookups,
+        &mozak_stark.public_sub_tables,
+        &ctl_challenges,
+    );
+
+    let public_inputs = TableKindSetBuilder::<&[_]> {
+        cpu_skeleton_stark: all_proof.public_inputs.borrow(),
+        ..Default::default()
+    }
+    .build();
+
+    all_starks!(mozak_stark, |stark, table| {
+        if table == kind {
+            return match &stark_challenges[kind] {
+                Some(challenges) => verify_stark_proof_with_challenges(
+                    stark,
+                    kind,
+                    &all_proof.proofs[kind],
+                    preprocessed.as_ref().and_then(|caps| caps.get(kind)),
+                    challenges,
+                    public_inputs[kind],
+                    &ctl_vars_per_table[kind],
+                    config,
+                ),
+                None => verify_skipped(mozak_stark, kind, &all_proof.proofs[kind]),
+            };
+        }
+    });
+    unreachable!("every table kind has a stark")
+}
+
+/// Checks that `all_proof` was made with `config`, if it records its config.
+///
+/// # Errors
+/// Errors if `all_proof` records another config.
+fn check_config<F, C, const D: usize>(
+    all_proof: &AllProof<F, C, D>,
+    config: &StarkConfig,
+) -> Result<(), VerifierError>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>, {
+    let expected = ProofConfig::from(config);
+    match all_proof.config {
+        Some(recorded) if recorded != expected =>
+            Err(VerifierError::ConfigMismatch { recorded, expected }),
+        _ => Ok(()),
+    }
+}
+
+/// The preprocessed commitments that `all_proof` uses, if any: the `known`
+/// ones, or else those computed from `config`.
+///
+/// # Errors
+/// Errors if they were not made with `config`, or if the proof was made with
+/// others.
+fn preprocessed_caps<F, C, const D: usize>(
+    all_proof: &AllProof<F, C, D>,
+    config: &StarkConfig,
+    known: Option<&PreprocessedCaps<F, C::Hasher>>,
+) -> Result<Option<PreprocessedCaps<F, C::Hasher>>, VerifierError>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>, {
+    let Some(digest) = all_proof.preprocessed else {
+        return Ok(None);
+    };
+    let caps = known.map_or_else(
+        || PreprocessedCaps::new::<C, D>(config),
+        PreprocessedCaps::clone,
+    );
+    if !caps.fits(config) || caps.digest() != digest {
+        return Err(VerifierError::PreprocessedMismatch);
+    }
+    Ok(Some(caps))
+}
+
+/// Checks that the proof may skip `kind`, and that the proof of `kind` is
+/// the [stand-in](StarkProof::skipped) of an empty table, which contributes
+/// nothing to the cross-table lookups.
+fn verify_skipped<F, C, const D: usize>(
+    mozak_stark: &MozakStark<F, D>,
+    kind: TableKind,
+    proof: &StarkProof<F, C, D>,
+) -> Result<(), VerifierError>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>, {
+    if !mozak_stark.can_skip(kind) {
+        return Err(VerifierError::UnskippableTable { table: kind });
+    }
+    if !proof.is_skipped() {
+        return Err(VerifierError::Table {
+            table: kind,
+            phase: VerifierPhase::ProofShape,
+            source: anyhow!("the proof of a skipped table has to be empty"),
+        });
+    }
+    Ok(())
+}
+
 pub(crate) fn verify_quotient_polynomials<
     F: RichField + Extendable<D>,
     C: GenericConfig<D, F = F>,
@@ -174,15 +362,25 @@ pub(crate) fn verify_stark_proof_with_challenges<
     const D: usize,
 >(
     stark: &S,
+    table: TableKind,
     proof: &StarkProof<F, C, D>,
+    fixed_cap: Option<&MerkleCap<F, C::Hasher>>,
     challenges: &StarkProofChallenges<F, D>,
     public_inputs: &[F],
     ctl_vars: &[CtlCheckVars<F, F::Extension, F::Extension, D>],
     config: &StarkConfig,
-) -> Result<()>
+) -> Result<(), VerifierError>
 where
 {
-    validate_proof_shape(stark, proof, config, ctl_vars.len())?;
+    let failed = |phase| {
+        move |source| VerifierError::Table {
+            table,
+            phase,
+            source,
+        }
+    };
+    validate_proof_shape(stark, proof, config, ctl_vars.len())
+        .map_err(failed(VerifierPhase::ProofShape))?;
     let degree_bits = proof.recover_degree_bits(config);
     verify_quotient_polynomials(
         stark,
@@ -191,33 +389,49 @@ where
         challenges,
         public_inputs,
         ctl_vars,
-    )?;
+    )
+    .map_err(failed(VerifierPhase::Constraints))?;
 
-    let ctl_zs_last = &proof.openings.ctl_zs_last;
-    let merkle_caps = vec![
+    let num_ctl_zs = proof.openings.ctl_zs_last.len();
+    let merkle_caps = chain!(fixed_cap.cloned(), [
         proof.trace_cap.clone(),
         proof.ctl_zs_cap.clone(),
         proof.quotient_polys_cap.clone(),
-    ];
-
-    verify_fri_proof::<F, C, D>(
-        &stark.fri_instance(
-            challenges.stark_zeta,
-            F::primitive_root_of_unity(degree_bits),
+    ])
+    .collect_vec();
+    let zeta = challenges.stark_zeta;
+    let g = F::primitive_root_of_unity(degree_bits);
+    let fri_instance = match fixed_cap {
+        Some(_) => preprocessed::fri_instance(
+            stark,
+            preprocessed::num_fixed_columns(table),
+            zeta,
+            g,
+            num_ctl_zs,
+            config,
+        ),
+        None => stark.fri_instance(
+            zeta,
+            g,
             0,
             vec![],
             config,
             Some(&LookupConfig {
                 degree_bits,
-                num_zs: ctl_zs_last.len(),
+                num_zs: num_ctl_zs,
             }),
         ),
+    };
+
+    verify_fri_proof::<F, C, D>(
+        &fri_instance,
         &proof.openings.to_fri_openings(),
         &challenges.fri_challenges,
         &merkle_caps,
         &proof.opening_proof,
         &config.fri_params(degree_bits),
-    )?;
+    )
+    .map_err(failed(VerifierPhase::Fri))?;
 
     Ok(())
 }
@@ -284,11 +498,21 @@ fn eval_l_0_and_l_last<F: Field>(log_n: usize, x: F) -> (F, F) {
 
 #[cfg(test)]
 mod tests {
+    use mozak_runner::code;
+    use mozak_runner::instruction::Instruction;
+    use plonky2::field::extension::Extendable;
     use plonky2::field::goldilocks_field::GoldilocksField;
     use plonky2::field::polynomial::PolynomialValues;
-    use plonky2::field::types::Sample;
+    use plonky2::field::types::{Field, Sample};
+    use plonky2::util::timing::TimingTree;
 
-    use crate::stark::verifier::eval_l_0_and_l_last;
+    use crate::stark::error::{VerifierError, VerifierPhase};
+    use crate::stark::mozak_stark::{MozakStark, PublicInputs, TableKind};
+    use crate::stark::proof::StarkProof;
+    use crate::stark::prover::prove;
+    use crate::stark::verifier::{eval_l_0_and_l_last, verify_proof, verify_single_table};
+    use crate::test_utils::{fast_test_config, C, D, F};
+    use crate::utils::from_u32;
 
     #[test]
     fn test_eval_l_0_and_l_last() {
@@ -304,4 +528,69 @@ mod tests {
         assert_eq!(l_first_x, expected_l_first_x);
         assert_eq!(l_last_x, expected_l_last_x);
     }
+
+    #[test]
+    fn verify_single_table_only_checks_that_table() {
+        let stark = MozakStark::default();
+        let config = fast_test_config();
+        let (program, record) = code::execute([Instruction::xori(1, 0, 5)], &[], &[]);
+        let mut all_proof = prove::<F, C, D>(
+            &program,
+            &record,
+            &stark,
+            &config,
+            PublicInputs::new(from_u32(program.entry_point)),
+            &mut TimingTree::default(),
+        )
+        .unwrap();
+        all_proof.proofs[TableKind::Cpu].openings.quotient_polys[0] +=
+            <F as Extendable<D>>::Extension::ONE;
+
+        assert!(matches!(
+            verify_single_table(&stark, TableKind::Cpu, &all_proof, &config),
+            Err(VerifierError::Table {
+                table: TableKind::Cpu,
+                phase: VerifierPhase::Constraints,
+                ..
+            })
+        ));
+        verify_single_table(&stark, TableKind::Memory, &all_proof, &config).unwrap();
+    }
+
+    #[test]
+    fn cpu_can_not_be_skipped() {
+        let stark = MozakStark::default();
+        let config = fast_test_config();
+        let (program, record) = code::execute([Instruction::xori(1, 0, 5)], &[], &[]);
+        let mut all_proof = prove::<F, C, D>(
+            &program,
+            &record,
+            &stark,
+            &config,
+            PublicInputs::new(from_u32(program.entry_point)),
+            &mut TimingTree::default(),
+        )
+        .unwrap();
+        let num_ctl_zs = all_proof.proofs[TableKind::Cpu].num_ctl_zs();
+        all_proof.proofs[TableKind::Cpu] = StarkProof::skipped(num_ctl_zs);
+        all_proof.skipped[TableKind::Cpu] = true;
+
+        assert!(matches!(
+            verify_proof(&stark, all_proof, &config),
+            Err(VerifierError::UnskippableTable {
+                table: TableKind::Cpu
+            })
+        ));
+    }
+
+    #[test]
+    fn parse_table_kind() {
+        assert_eq!("cpu".parse::<TableKind>(), Ok(TableKind::Cpu));
+        assert_eq!(
+            "cpu_skeleton".parse::<TableKind>(),
+            Ok(TableKind::CpuSkeleton)
+        );
+        assert_eq!("RangeCheck".parse::<TableKind>(), Ok(TableKind::RangeCheck));
+        assert!("no-such-table".parse::<TableKind>().is_err());
+    }
 }
@@ -1,30 +1,36 @@
 use core::ops::Add;
 
 use mozak_sdk::core::constants::DIGEST_BYTES;
-use mozak_sdk::core::reg_abi::REG_A1;
+use mozak_sdk::core::reg_abi::{REG_A1, REG_A3};
 
-use crate::columns_view::{columns_view_impl, make_col_map, NumberOfColumns};
+use crate::columns_view::{make_col_map, ColumnNames, ColumnsView, NumberOfColumns};
 use crate::cross_table_lookup::{Column, ColumnWithTypedInput};
 use crate::memory::columns::MemoryCtl;
+#[cfg(feature = "poseidon2")]
+use crate::public_tape_hash::columns::TapeByteCtl;
 use crate::register::RegisterCtl;
+#[cfg(feature = "poseidon2")]
+use crate::stark::mozak_stark::OutputTapeTable;
 use crate::stark::mozak_stark::{
-    CallTapeTable, CastListCommitmentTapeTable, EventsCommitmentTapeTable, SelfProgIdTapeTable,
-    StorageDevicePrivateTable, StorageDevicePublicTable, TableKind, TableWithTypedOutput,
+    CallTapeTable, CastListCommitmentTapeTable, EventsCommitmentTapeTable, HintTapeTable,
+    SelfProgIdTapeTable, StorageDevicePrivateTable, StorageDevicePublicTable, TableKind,
+    TableWithTypedOutput,
 };
 use crate::tape_commitments::columns::TapeCommitmentCTL;
 
 /// Operations (one-hot encoded)
 #[repr(C)]
-#[derive(Clone, Copy, Eq, PartialEq, Debug, Default)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Default, ColumnNames)]
 pub struct Ops<T> {
-    /// Binary filter column to represent a RISC-V SB operation.
+    /// Binary filter column to represent a RISC-V SB operation. On the output
+    /// tape, it represents a RISC-V LB operation instead.
     pub is_memory_store: T,
     /// Binary filter column to represent an storage device operation.
     pub is_storage_device: T,
 }
 
 #[repr(C)]
-#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
 pub struct StorageDevice<T> {
     /// Clock at memory access.
     pub clk: T,
@@ -38,9 +44,14 @@ pub struct StorageDevice<T> {
     pub ops: Ops<T>,
     /// Helper to decrease poly degree
     pub is_lv_and_nv_are_memory_rows: T,
+    /// Tape index: position on the tape of the first byte read for the
+    /// storage device row, and of the byte itself for memory rows.
+    pub tape_index: T,
+    /// Binary filter column for a storage device operation that starts
+    /// reading at an explicit tape index instead of the current read position.
+    pub is_read_at: T,
 }
 
-columns_view_impl!(StorageDevice);
 make_col_map!(StorageDevice);
 
 impl<T: Copy + Add<Output = T>> StorageDevice<T> {
@@ -50,14 +61,14 @@ impl<T: Copy + Add<Output = T>> StorageDevice<T> {
 /// Total number of columns.
 pub const NUM_STORAGE_DEVICE_COLS: usize = StorageDevice::<()>::NUMBER_OF_COLUMNS;
 
-columns_view_impl!(StorageDeviceCtl);
 #[repr(C)]
-#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
 pub struct StorageDeviceCtl<T> {
     pub op: T,
     pub clk: T,
     pub addr: T,
     pub size: T,
+    pub is_read_at: T,
 }
 
 /// Lookup between CPU table and Memory stark table.
@@ -70,6 +81,7 @@ pub fn lookup_for_cpu(kind: TableKind, op: i64) -> TableWithTypedOutput<StorageD
             clk: COL_MAP.clk,
             addr: COL_MAP.addr,
             size: COL_MAP.size,
+            is_read_at: COL_MAP.is_read_at,
         }
         .into_iter()
         .map(Column::from)
@@ -97,6 +109,23 @@ pub fn lookup_for_memory(kind: TableKind) -> TableWithTypedOutput<MemoryCtl<Colu
     }
 }
 
+/// Lookup of the bytes that the guest writes to the output tape into Memory
+/// stark table.
+#[cfg(feature = "poseidon2")]
+#[must_use]
+pub fn output_tape_lookup_for_memory() -> TableWithTypedOutput<MemoryCtl<Column>> {
+    OutputTapeTable::new(
+        MemoryCtl {
+            clk: COL_MAP.clk,
+            is_store: ColumnWithTypedInput::constant(0),
+            is_load: COL_MAP.ops.is_memory_store,
+            value: COL_MAP.value,
+            addr: COL_MAP.addr,
+        },
+        COL_MAP.ops.is_memory_store,
+    )
+}
+
 #[must_use]
 pub fn register_looking() -> Vec<TableWithTypedOutput<RegisterCtl<Column>>> {
     let data = RegisterCtl {
@@ -105,13 +134,24 @@ pub fn register_looking() -> Vec<TableWithTypedOutput<RegisterCtl<Column>>> {
         addr: ColumnWithTypedInput::constant(i64::from(REG_A1)),
         value: COL_MAP.addr,
     };
+    // Reads at an explicit position take their tape index from A3.
+    let read_at = RegisterCtl {
+        clk: COL_MAP.clk,
+        op: ColumnWithTypedInput::constant(1), // read
+        addr: ColumnWithTypedInput::constant(i64::from(REG_A3)),
+        value: COL_MAP.tape_index,
+    };
     vec![
         StorageDevicePrivateTable::new(data, COL_MAP.ops.is_storage_device),
         StorageDevicePublicTable::new(data, COL_MAP.ops.is_storage_device),
+        StorageDevicePublicTable::new(read_at, COL_MAP.is_read_at),
         CallTapeTable::new(data, COL_MAP.ops.is_storage_device),
         EventsCommitmentTapeTable::new(data, COL_MAP.ops.is_storage_device),
         CastListCommitmentTapeTable::new(data, COL_MAP.ops.is_storage_device),
         SelfProgIdTapeTable::new(data, COL_MAP.ops.is_storage_device),
+        HintTapeTable::new(data, COL_MAP.ops.is_storage_device),
+        #[cfg(feature = "poseidon2")]
+        OutputTapeTable::new(data, COL_MAP.ops.is_storage_device),
     ]
 }
 
@@ -134,3 +174,49 @@ pub fn castlist_commitment_lookup_in_tape_commitments(
     };
     CastListCommitmentTapeTable::new(data, COL_MAP.ops.is_memory_store)
 }
+
+#[must_use]
+pub fn self_prog_id_lookup_in_tape_commitments() -> TableWithTypedOutput<TapeCommitmentCTL<Column>>
+{
+    let data = TapeCommitmentCTL {
+        byte: COL_MAP.value,
+        index: i64::try_from(DIGEST_BYTES - 1).unwrap() - COL_MAP.size,
+    };
+    SelfProgIdTapeTable::new(data, COL_MAP.ops.is_memory_store)
+}
+
+/// Every byte read from the public tape is looked up in the sponge over the
+/// tape, at its tape index.
+#[cfg(feature = "poseidon2")]
+#[must_use]
+pub fn public_tape_lookup_in_tape_hash() -> TableWithTypedOutput<TapeByteCtl<Column>> {
+    let data = TapeByteCtl {
+        index: COL_MAP.tape_index,
+        value: COL_MAP.value,
+    };
+    StorageDevicePublicTable::new(data, COL_MAP.ops.is_memory_store)
+}
+
+/// Every byte written to the output tape is looked up in the sponge over the
+/// tape, at its tape index.
+#[cfg(feature = "poseidon2")]
+#[must_use]
+pub fn output_tape_lookup_in_tape_hash() -> TableWithTypedOutput<TapeByteCtl<Column>> {
+    let data = TapeByteCtl {
+        index: COL_MAP.tape_index,
+        value: COL_MAP.value,
+    };
+    OutputTapeTable::new(data, COL_MAP.ops.is_memory_store)
+}
+
+/// Every byte read from the private tape is looked up in the sponge over the
+/// tape, at its tape index.
+#[cfg(feature = "poseidon2")]
+#[must_use]
+pub fn private_tape_lookup_in_tape_hash() -> TableWithTypedOutput<TapeByteCtl<Column>> {
+    let data = TapeByteCtl {
+        index: COL_MAP.tape_index,
+        value: COL_MAP.value,
+    };
+    StorageDevicePrivateTable::new(data, COL_MAP.ops.is_memory_store)
+}
@@ -38,6 +38,8 @@ fn is_storage_device_opcode<F: RichField>(op: StorageDeviceOpcode) -> F {
             | StorageDeviceOpcode::StoreEventsCommitmentTape
             | StorageDeviceOpcode::StoreCastListCommitmentTape
             | StorageDeviceOpcode::StoreSelfProgIdTape
+            | StorageDeviceOpcode::StoreHint
+            | StorageDeviceOpcode::LoadOutputTape
     ))
 }
 
@@ -49,8 +51,13 @@ pub fn generate_storage_trace<F: RichField>(
     pad_mem_trace(
         filter(step_rows, which_tape)
             .flat_map(|s| {
-                let StorageDeviceEntry { op, data, addr }: StorageDeviceEntry =
-                    s.aux.storage_device_entry.clone().unwrap_or_default();
+                let StorageDeviceEntry {
+                    op,
+                    data,
+                    addr,
+                    offset,
+                    is_read_at,
+                }: StorageDeviceEntry = s.aux.storage_device_entry.clone().unwrap_or_default();
                 let len = data.len();
                 chain!(
                     // initial storage-device-element
@@ -63,6 +70,8 @@ pub fn generate_storage_trace<F: RichField>(
                             is_memory_store: F::ZERO,
                         },
                         is_lv_and_nv_are_memory_rows: F::from_bool(false),
+                        tape_index: F::from_canonical_u32(offset),
+                        is_read_at: F::from_bool(is_read_at),
                         ..Default::default()
                     }],
                     // extended memory elements
@@ -79,6 +88,8 @@ pub fn generate_storage_trace<F: RichField>(
                                 is_memory_store: is_storage_device_opcode(op),
                             },
                             is_lv_and_nv_are_memory_rows: F::from_bool(i + 1 != len),
+                            tape_index: F::from_canonical_u32(offset) + F::from_canonical_usize(i),
+                            is_read_at: F::ZERO,
                         }
                     })
                 )
@@ -127,3 +138,17 @@ pub fn generate_self_prog_id_tape_trace<F: RichField>(
 ) -> Vec<StorageDevice<F>> {
     generate_storage_trace(step_rows, StorageDeviceOpcode::StoreSelfProgIdTape)
 }
+
+/// Hints are not committed to anywhere, so unlike the tapes, nothing ties
+/// these rows to the bytes the host handed out.
+#[must_use]
+pub fn generate_hint_tape_trace<F: RichField>(step_rows: &[Row<F>]) -> Vec<StorageDevice<F>> {
+    generate_storage_trace(step_rows, StorageDeviceOpcode::StoreHint)
+}
+
+/// The memory rows of the output tape load the bytes that the guest writes,
+/// rather than store them.
+#[must_use]
+pub fn generate_output_tape_trace<F: RichField>(step_rows: &[Row<F>]) -> Vec<StorageDevice<F>> {
+    generate_storage_trace(step_rows, StorageDeviceOpcode::LoadOutputTape)
+}
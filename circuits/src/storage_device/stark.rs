@@ -30,7 +30,7 @@ const COLUMNS: usize = NUM_STORAGE_DEVICE_COLS;
 const PUBLIC_INPUTS: usize = 0;
 
 // Design description - https://docs.google.com/presentation/d/1J0BJd49BMQh3UR5TrOhe3k67plHxnohFtFVrMpDJ1oc/edit?usp=sharing
-fn generate_constraints<'a, T: Copy>(
+pub(crate) fn generate_constraints<'a, T: Copy>(
     vars: &StarkFrameTyped<StorageDevice<Expr<'a, T>>, NoColumns<Expr<'a, T>>>,
 ) -> ConstraintBuilder<Expr<'a, T>> {
     let lv = vars.local_values;
@@ -40,6 +40,9 @@ fn generate_constraints<'a, T: Copy>(
     constraints.always(lv.ops.is_memory_store.is_binary());
     constraints.always(lv.ops.is_storage_device.is_binary());
     constraints.always(lv.is_executed().is_binary());
+    constraints.always(lv.is_read_at.is_binary());
+    // Only storage device rows can start reading at an explicit tape index.
+    constraints.always(lv.is_read_at * (lv.ops.is_storage_device - 1));
 
     // If nv.is_storage_device() == 1: lv.size == 0, also forces the last row to be
     // size == 0 ! This constraints ensures loop unrolling was done correctly
@@ -64,6 +67,12 @@ fn generate_constraints<'a, T: Copy>(
     //      lv.size === nv.size - 1  <-- next row size is decreased
     constraints.transition(lv.ops.is_storage_device * lv.size * (nv.addr - lv.addr));
     constraints.transition(lv.ops.is_storage_device * lv.size * (nv.size - (lv.size - 1)));
+    // The bytes of one read are consecutive on the tape, starting at the tape
+    // index of the storage device row:
+    //      lv.tape_index == nv.tape_index        <-- for the first memory row
+    //      nv.tape_index == lv.tape_index + 1    <-- between memory rows
+    constraints.transition(lv.ops.is_storage_device * lv.size * (nv.tape_index - lv.tape_index));
+    constraints.transition(lv.is_lv_and_nv_are_memory_rows * (nv.tape_index - (lv.tape_index + 1)));
     // If lv.is_storage_device() == 1 && lv.size == 0:
     //      nv.is_memory() == 0 <-- next op can be only io - since size == 0
     // This one is ensured by:
@@ -115,14 +124,16 @@ impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for StorageDevice
 #[cfg(test)]
 mod tests {
     use itertools::Itertools;
-    use mozak_runner::code::execute_code_with_ro_memory;
+    use mozak_runner::code::{execute_code_with_ro_memory, Code};
     use mozak_runner::decode::ECALL;
-    use mozak_runner::instruction::{Args, Instruction, Op};
-    use mozak_runner::state::RawTapes;
+    use mozak_runner::elf::Program;
+    use mozak_runner::instruction::Instruction;
+    use mozak_runner::state::{HintRequest, RawTapes, State};
     use mozak_runner::test_utils::{u32_extra, u8_extra};
+    use mozak_runner::vm::step;
     use mozak_sdk::core::constants::DIGEST_BYTES;
     use mozak_sdk::core::ecall::{self};
-    use mozak_sdk::core::reg_abi::{REG_A0, REG_A1, REG_A2};
+    use mozak_sdk::core::reg_abi::{REG_A0, REG_A1, REG_A2, REG_A3};
     use plonky2::plonk::config::Poseidon2GoldilocksConfig;
     use proptest::prelude::ProptestConfig;
     use proptest::proptest;
@@ -247,6 +258,37 @@ mod tests {
         Stark::prove_and_verify(&program, &record).unwrap();
     }
 
+    pub fn prove_read_public_at<Stark: ProveAndVerify>(
+        address: u32,
+        public_tape: Vec<u8>,
+        offset: u32,
+    ) {
+        let (program, record) = execute_code_with_ro_memory(
+            // set sys-call PUBLIC_TAPE_READ_AT in x10(or a0)
+            [ECALL],
+            &[],
+            &[(address, 0), (address.wrapping_add(1), 0)],
+            &[
+                (REG_A0, ecall::PUBLIC_TAPE_READ_AT),
+                (REG_A1, address), // A1 - address
+                (REG_A2, 2),       // A2 - size
+                (REG_A3, offset),  // A3 - offset
+            ],
+            RawTapes {
+                public_tape,
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(
+            record.last_state.public_tape.read_index,
+            offset as usize + 2,
+            "The read should have started at the requested offset"
+        );
+
+        Stark::prove_and_verify(&program, &record).unwrap();
+    }
+
     pub fn prove_read_call_tape<Stark: ProveAndVerify>(address: u32, call_tape: Vec<u8>) {
         let (program, record) = execute_code_with_ro_memory(
             // set sys-call IO_READ in x10(or a0)
@@ -361,57 +403,15 @@ mod tests {
     pub fn prove_read_explicit<Stark: ProveAndVerify>(address: u32, content: u8) {
         let (program, record) = execute_code_with_ro_memory(
             [
-                Instruction {
-                    op: Op::ADD,
-                    args: Args {
-                        rd: REG_A1,
-                        imm: address,
-                        ..Args::default()
-                    },
-                },
-                Instruction {
-                    op: Op::ADD,
-                    args: Args {
-                        rd: REG_A2,
-                        imm: 4,
-                        ..Args::default()
-                    },
-                },
+                Instruction::li(REG_A1, address),
+                Instruction::li(REG_A2, 4),
                 // set sys-call IO_READ in x10(or a0)
-                Instruction {
-                    op: Op::ADD,
-                    args: Args {
-                        rd: REG_A0,
-                        imm: ecall::PRIVATE_TAPE,
-                        ..Args::default()
-                    },
-                },
+                Instruction::li(REG_A0, ecall::PRIVATE_TAPE),
                 // add ecall to read
                 ECALL,
-                Instruction {
-                    op: Op::ADD,
-                    args: Args {
-                        rd: REG_A0,
-                        imm: 0,
-                        ..Args::default()
-                    },
-                },
-                Instruction {
-                    op: Op::ADD,
-                    args: Args {
-                        rd: REG_A1,
-                        imm: 0,
-                        ..Args::default()
-                    },
-                },
-                Instruction {
-                    op: Op::ADD,
-                    args: Args {
-                        rd: REG_A2,
-                        imm: 0,
-                        ..Args::default()
-                    },
-                },
+                Instruction::add(REG_A0, 0, 0),
+                Instruction::add(REG_A1, 0, 0),
+                Instruction::add(REG_A2, 0, 0),
             ],
             &[],
             &[
@@ -429,6 +429,30 @@ mod tests {
         Stark::prove_and_verify(&program, &record).unwrap();
     }
 
+    /// Reads one queued hint and one from the provider. Nothing but the guest
+    /// could check them, so any hints prove.
+    pub fn prove_read_hint<Stark: ProveAndVerify>(address: u32, hint: u8) {
+        let code = [
+            Instruction::li(REG_A0, ecall::HINT_READ),
+            Instruction::li(REG_A1, address),
+            Instruction::li(REG_A2, 2),
+            ECALL,
+            Instruction::li(REG_A0, ecall::HALT),
+            ECALL,
+        ];
+        let program = Program::create(
+            &[],
+            &[(address, 0), (address.wrapping_add(1), 0)],
+            Code((0..).step_by(4).zip(code.map(Ok)).collect()),
+        );
+        let state = State::<F>::new(program.clone(), RawTapes::default())
+            .with_hints([hint])
+            .with_hint_provider(move |_: &HintRequest| vec![hint.wrapping_add(1)]);
+        let record = step(&program, state).unwrap();
+        assert_eq!(record.last_state.hint_tape.read_index, 2);
+        Stark::prove_and_verify(&program, &record).unwrap();
+    }
+
     proptest! {
         #![proptest_config(ProptestConfig::with_cases(1))]
         #[test]
@@ -448,6 +472,10 @@ mod tests {
             prove_read_public::<MozakStark<F, D>>(address, vec![content]);
         }
         #[test]
+        fn prove_read_public_at_mozak(address in u32_extra(), content in u8_extra(), offset in 0..3_u32) {
+            prove_read_public_at::<MozakStark<F, D>>(address, vec![content; 4], offset);
+        }
+        #[test]
         fn prove_read_call_tape_zero_size_mozak(address in u32_extra()) {
             prove_read_call_tape_zero_size::<MozakStark<F, D>>(address);
         }
@@ -475,6 +503,11 @@ mod tests {
             prove_cast_list_commitment_tape::<MozakStark<F, D>>(address, [content; 32]);
         }
 
+        #[test]
+        fn prove_read_hint_mozak(address in u32_extra(), hint in u8_extra()) {
+            prove_read_hint::<MozakStark<F, D>>(address, hint);
+        }
+
         #[test]
         fn prove_read_mozak_explicit(address in u32_extra(), content in u8_extra()) {
             prove_read_explicit::<MozakStark<F, D>>(address, content);
@@ -1,30 +1,35 @@
+use anyhow::{anyhow, bail, ensure, Result};
+use mozak_sdk::common::types::ProgramIdentifier;
 use mozak_sdk::core::constants::DIGEST_BYTES;
+use plonky2::hash::hash_types::RichField;
 
-use crate::columns_view::{columns_view_impl, make_col_map};
+use crate::columns_view::{make_col_map, ColumnsView};
+use crate::cross_table_lookup::ColumnWithTypedInput;
 use crate::linear_combination::Column;
-use crate::public_sub_table::PublicSubTable;
-use crate::stark::mozak_stark::{TableWithTypedOutput, TapeCommitmentsTable};
+use crate::public_sub_table::{public_value, PublicSubTable, PublicSubTableValues, PublicValue};
+use crate::stark::mozak_stark::{TableKindArray, TableWithTypedOutput, TapeCommitmentsTable};
 
 make_col_map!(TAPE_COMMITMENTS, TapeCommitments);
-columns_view_impl!(TapeCommitments);
 
 /// This stark table is used to store tape commitments
-/// which we want to make public in final recursive proof.
+/// which we want to make public in final recursive proof, and
+/// the program's own identifier from the self program id tape.
 /// Each commitment is stored as 32 bytes of hash, along with
 /// their indices in each row. Different commitments can
 /// be identified with their corresponding filter.
 /// There is no definite order imposed on the rows of this
 /// table,
 #[repr(C)]
-#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
 pub struct TapeCommitments<T> {
     pub commitment_byte_row: CommitmentByteWithIndex<T>,
     pub castlist_commitment_tape_multiplicity: T,
     pub event_commitment_tape_multiplicity: T,
     pub is_castlist_commitment_tape_row: T,
     pub is_event_commitment_tape_row: T,
+    pub self_prog_id_tape_multiplicity: T,
+    pub is_self_prog_id_tape_row: T,
 }
-columns_view_impl!(CommitmentByteWithIndex);
 
 /// We store indices with the byte so that
 /// we can do CTL against corresponding
@@ -32,7 +37,7 @@ columns_view_impl!(CommitmentByteWithIndex);
 /// stark, while enforcing the original order in which bytes
 /// are to be read.
 #[repr(C)]
-#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
 pub struct CommitmentByteWithIndex<T> {
     pub byte: T,
     pub index: T,
@@ -62,6 +67,17 @@ pub fn lookup_for_event_tape_commitment() -> TableWithTypedOutput<TapeCommitment
     )
 }
 
+#[must_use]
+pub fn lookup_for_self_prog_id_tape() -> TableWithTypedOutput<TapeCommitmentCTL<Column>> {
+    TapeCommitmentsTable::new(
+        TapeCommitmentCTL {
+            byte: TAPE_COMMITMENTS.commitment_byte_row.byte,
+            index: TAPE_COMMITMENTS.commitment_byte_row.index,
+        },
+        TAPE_COMMITMENTS.self_prog_id_tape_multiplicity,
+    )
+}
+
 #[must_use]
 pub fn make_event_commitment_tape_public() -> PublicSubTable {
     PublicSubTable {
@@ -83,3 +99,134 @@ pub fn make_castlist_commitment_tape_public() -> PublicSubTable {
         num_rows: DIGEST_BYTES,
     }
 }
+
+/// Events commitment of the proven execution, as a [`PublicValue`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EventsCommitment(pub [u8; DIGEST_BYTES]);
+
+/// Cast list commitment of the proven execution, as a [`PublicValue`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CastListCommitment(pub [u8; DIGEST_BYTES]);
+
+/// Program identifier that the guest reads from its self program id tape,
+/// as a [`PublicValue`], or `None` if the guest never reads the tape.
+///
+/// Every proof makes it public, together with how often the guest reads each
+/// byte, and the verifier checks it against the program id of the proof.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SelfProgramId(pub Option<ProgramIdentifier>);
+
+/// Checks that the [`SelfProgramId`], if the guest reads it, is
+/// `program_id`.
+///
+/// # Errors
+///
+/// Errors if the self program id is not public, or does not match
+/// `program_id`.
+pub fn check_self_program_id<F: RichField>(
+    public_sub_tables: &[PublicSubTable],
+    public_sub_table_values: &TableKindArray<Vec<PublicSubTableValues<F>>>,
+    program_id: ProgramIdentifier,
+) -> Result<()> {
+    let SelfProgramId(self_prog_id) = public_value(public_sub_tables, public_sub_table_values)?;
+    if let Some(self_prog_id) = self_prog_id {
+        ensure!(
+            self_prog_id == program_id,
+            "self program id {self_prog_id:?} does not match program id {program_id:?}"
+        );
+    }
+    Ok(())
+}
+
+/// Makes the bytes of a commitment public together with their indices, so
+/// that the commitment can be put back together in order.
+fn make_commitment_with_indices_public(
+    filter: ColumnWithTypedInput<TapeCommitments<i64>>,
+) -> PublicSubTable {
+    PublicSubTable {
+        table: TapeCommitmentsTable::new(
+            vec![
+                TAPE_COMMITMENTS.commitment_byte_row.byte,
+                TAPE_COMMITMENTS.commitment_byte_row.index,
+            ],
+            filter,
+        ),
+        num_rows: DIGEST_BYTES,
+    }
+}
+
+/// Puts a commitment back together from rows of bytes with their indices.
+fn commitment_from_rows<F: RichField>(rows: &[Vec<F>]) -> Result<[u8; DIGEST_BYTES]> {
+    let mut commitment = [None; DIGEST_BYTES];
+    for row in rows {
+        let [byte, index] = row[..] else {
+            bail!("expected a byte and its index, got {row:?}")
+        };
+        let byte = u8::try_from(byte.to_canonical_u64())?;
+        let slot = usize::try_from(index.to_canonical_u64())
+            .ok()
+            .and_then(|index| commitment.get_mut(index))
+            .ok_or_else(|| anyhow!("commitment byte index {index} is out of range"))?;
+        ensure!(
+            slot.replace(byte).is_none(),
+            "commitment byte index {index} appears twice"
+        );
+    }
+    ensure!(
+        commitment.iter().all(Option::is_some),
+        "commitment is missing bytes"
+    );
+    Ok(commitment.map(Option::unwrap_or_default))
+}
+
+impl<F: RichField> PublicValue<F> for EventsCommitment {
+    fn public_sub_table() -> PublicSubTable {
+        make_commitment_with_indices_public(TAPE_COMMITMENTS.is_event_commitment_tape_row)
+    }
+
+    fn from_rows(rows: &[Vec<F>]) -> Result<Self> { commitment_from_rows(rows).map(Self) }
+}
+
+impl<F: RichField> PublicValue<F> for SelfProgramId {
+    fn public_sub_table() -> PublicSubTable {
+        PublicSubTable {
+            table: TapeCommitmentsTable::new(
+                vec![
+                    TAPE_COMMITMENTS.commitment_byte_row.byte,
+                    TAPE_COMMITMENTS.commitment_byte_row.index,
+                    TAPE_COMMITMENTS.self_prog_id_tape_multiplicity,
+                ],
+                TAPE_COMMITMENTS.is_self_prog_id_tape_row,
+            ),
+            num_rows: DIGEST_BYTES,
+        }
+    }
+
+    fn from_rows(rows: &[Vec<F>]) -> Result<Self> {
+        // The lookup from the self program id tape ties the multiplicities to
+        // the reads of the guest, so they are all zero if it reads nothing.
+        let mut is_read = false;
+        let rows = rows
+            .iter()
+            .map(|row| match row[..] {
+                [byte, index, multiplicity] => {
+                    is_read |= multiplicity.is_nonzero();
+                    Ok(vec![byte, index])
+                }
+                _ => Err(anyhow!(
+                    "expected a byte, its index and multiplicity, got {row:?}"
+                )),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let program_id = ProgramIdentifier::from(commitment_from_rows(&rows)?);
+        Ok(Self(is_read.then_some(program_id)))
+    }
+}
+
+impl<F: RichField> PublicValue<F> for CastListCommitment {
+    fn public_sub_table() -> PublicSubTable {
+        make_commitment_with_indices_public(TAPE_COMMITMENTS.is_castlist_commitment_tape_row)
+    }
+
+    fn from_rows(rows: &[Vec<F>]) -> Result<Self> { commitment_from_rows(rows).map(Self) }
+}
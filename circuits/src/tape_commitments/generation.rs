@@ -4,6 +4,7 @@ use mozak_runner::vm::{ExecutionRecord, Row};
 use plonky2::hash::hash_types::RichField;
 
 use crate::tape_commitments::columns::{CommitmentByteWithIndex, TapeCommitments};
+use crate::utils::pad_trace_with_default;
 
 #[must_use]
 pub fn num_ecalls<F: RichField>(step_rows: &[Row<F>], which_tape: StorageDeviceOpcode) -> usize {
@@ -30,6 +31,7 @@ pub fn generate_tape_commitment_trace_with_op_code<F: RichField>(
             &execution.last_state.cast_list_commitment_tape,
         StorageDeviceOpcode::StoreEventsCommitmentTape =>
             &execution.last_state.events_commitment_tape,
+        StorageDeviceOpcode::StoreSelfProgIdTape => &execution.last_state.self_prog_id_tape,
         _ => unreachable!(),
     };
     // theoretically, we have no restriction on number of ecalls made,
@@ -50,10 +52,16 @@ pub fn generate_tape_commitment_trace_with_op_code<F: RichField>(
         StorageDeviceOpcode::StoreEventsCommitmentTape
     ));
 
+    let is_self_prog_id_tape_row = F::from_bool(matches!(
+        which_tape_commitment,
+        StorageDeviceOpcode::StoreSelfProgIdTape
+    ));
+
     let castlist_commitment_tape_multiplicity =
         is_castlist_commitment_tape_row * num_tape_commitment_ecalls;
     let event_commitment_tape_multiplicity =
         is_event_commitment_tape_row * num_tape_commitment_ecalls;
+    let self_prog_id_tape_multiplicity = is_self_prog_id_tape_row * num_tape_commitment_ecalls;
 
     tape.iter()
         .enumerate()
@@ -67,6 +75,8 @@ pub fn generate_tape_commitment_trace_with_op_code<F: RichField>(
             castlist_commitment_tape_multiplicity,
             is_castlist_commitment_tape_row,
             is_event_commitment_tape_row,
+            self_prog_id_tape_multiplicity,
+            is_self_prog_id_tape_row,
         })
         .collect_vec()
 }
@@ -79,12 +89,23 @@ pub fn generate_tape_commitments_trace<F: RichField>(
         execution,
         StorageDeviceOpcode::StoreCastListCommitmentTape,
     );
-    log::trace!("{cast_list_commitment_trace:?}");
+    tracing::trace!("{cast_list_commitment_trace:?}");
     let events_commitment_tape_trace = generate_tape_commitment_trace_with_op_code(
         execution,
         StorageDeviceOpcode::StoreEventsCommitmentTape,
     );
-    log::trace!("{events_commitment_tape_trace:?}");
-    // Note that the final trace length is 64, hence no need to pad.
-    chain(cast_list_commitment_trace, events_commitment_tape_trace).collect_vec()
+    tracing::trace!("{events_commitment_tape_trace:?}");
+    let self_prog_id_tape_trace = generate_tape_commitment_trace_with_op_code(
+        execution,
+        StorageDeviceOpcode::StoreSelfProgIdTape,
+    );
+    tracing::trace!("{self_prog_id_tape_trace:?}");
+    pad_trace_with_default(
+        chain!(
+            cast_list_commitment_trace,
+            events_commitment_tape_trace,
+            self_prog_id_tape_trace
+        )
+        .collect_vec(),
+    )
 }
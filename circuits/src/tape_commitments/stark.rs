@@ -22,13 +22,19 @@ fn generate_constraints<'a, T: Copy>(
     let mut constraint = ConstraintBuilder::default();
     constraint.always(lv.is_event_commitment_tape_row.is_binary());
     constraint.always(lv.is_castlist_commitment_tape_row.is_binary());
-    constraint
-        .always((lv.is_castlist_commitment_tape_row + lv.is_event_commitment_tape_row).is_binary());
+    constraint.always(lv.is_self_prog_id_tape_row.is_binary());
+    constraint.always(
+        (lv.is_castlist_commitment_tape_row
+            + lv.is_event_commitment_tape_row
+            + lv.is_self_prog_id_tape_row)
+            .is_binary(),
+    );
     constraint
         .always(lv.event_commitment_tape_multiplicity * (1 - lv.is_event_commitment_tape_row));
     constraint.always(
         lv.castlist_commitment_tape_multiplicity * (1 - lv.is_castlist_commitment_tape_row),
     );
+    constraint.always(lv.self_prog_id_tape_multiplicity * (1 - lv.is_self_prog_id_tape_row));
     constraint
 }
 
@@ -85,7 +91,7 @@ mod tests {
     use itertools::chain;
     use mozak_runner::code;
     use mozak_runner::decode::ECALL;
-    use mozak_runner::instruction::{Args, Instruction, Op};
+    use mozak_runner::instruction::Instruction;
     use mozak_runner::state::RawTapes;
     use mozak_sdk::core::constants::DIGEST_BYTES;
     use mozak_sdk::core::ecall::{self};
@@ -99,12 +105,15 @@ mod tests {
     use starky::stark_testing::test_stark_circuit_constraints;
 
     use super::TapeCommitmentsStark;
+    use crate::stark::error::VerifierError;
     use crate::stark::mozak_stark::{MozakStark, PublicInputs};
+    use crate::stark::proof::AllProof;
     use crate::stark::prover::prove;
     use crate::stark::recursive_verifier::{
         recursive_mozak_stark_circuit, VMRecursiveProofPublicInputs, VM_PUBLIC_INPUT_SIZE,
     };
     use crate::stark::verifier::verify_proof;
+    use crate::tape_commitments::columns::{CastListCommitment, EventsCommitment, SelfProgramId};
     use crate::test_utils::ProveAndVerify;
     use crate::utils::from_u32;
 
@@ -115,37 +124,20 @@ mod tests {
 
     const CAST_LIST_COMMITMENT_ADDRESS: u32 = 0x100;
     const EVENTS_COMMITMENT_ADDRESS: u32 = 0x200;
+    const SELF_PROG_ID_ADDRESS: u32 = 0x300;
 
+    fn read_ecall_code(ecall: u32, address: u32, num_bytes_read: usize) -> Vec<Instruction> {
+        vec![
+            Instruction::li(REG_A0, ecall),
+            Instruction::li(REG_A1, address),
+            Instruction::li(
+                REG_A2,
+                u32::try_from(num_bytes_read).expect("casting to u32 should not fail"),
+            ),
+            ECALL,
+        ]
+    }
     fn read_tape_commitments_code() -> Vec<Instruction> {
-        fn read_ecall_code(ecall: u32, address: u32, num_bytes_read: usize) -> Vec<Instruction> {
-            vec![
-                Instruction {
-                    op: Op::ADD,
-                    args: Args {
-                        rd: REG_A0,
-                        imm: ecall,
-                        ..Default::default()
-                    },
-                },
-                Instruction {
-                    op: Op::ADD,
-                    args: Args {
-                        rd: REG_A1,
-                        imm: address,
-                        ..Default::default()
-                    },
-                },
-                Instruction {
-                    op: Op::ADD,
-                    args: Args {
-                        rd: REG_A2,
-                        imm: u32::try_from(num_bytes_read).expect("casting to u32 should not fail"),
-                        ..Default::default()
-                    },
-                },
-                ECALL,
-            ]
-        }
         let code_ecall_cast_list_commitment_tape = read_ecall_code(
             ecall::CAST_LIST_COMMITMENT_TAPE,
             CAST_LIST_COMMITMENT_ADDRESS,
@@ -206,9 +198,7 @@ mod tests {
         });
         let stark = MozakStark::<F, D>::default();
         let config = StarkConfig::standard_fast_config();
-        let public_inputs = PublicInputs {
-            entry_point: from_u32(program.entry_point),
-        };
+        let public_inputs = PublicInputs::new(from_u32(program.entry_point));
         let mozak_proof = prove::<F, C, D>(
             &program,
             &record,
@@ -247,6 +237,130 @@ mod tests {
         mozak_stark_circuit.circuit.verify(recursive_proof)
     }
 
+    #[test]
+    fn test_tape_commitment_public_values() -> Result<(), anyhow::Error> {
+        let mut rng = rand::thread_rng();
+        // generate tapes with random bytes
+        let cast_list_commitment_tape: [u8; DIGEST_BYTES] = rng.gen();
+        let events_commitment_tape: [u8; DIGEST_BYTES] = rng.gen();
+        let code = read_tape_commitments_code();
+        let (program, record) = code::execute_code_with_ro_memory(code, &[], &[], &[], RawTapes {
+            events_commitment_tape,
+            cast_list_commitment_tape,
+            ..Default::default()
+        });
+        let stark = MozakStark::<F, D>::default()
+            .with_public_value::<EventsCommitment>()
+            .with_public_value::<CastListCommitment>();
+        let config = StarkConfig::standard_fast_config();
+        let public_inputs = PublicInputs::new(from_u32(program.entry_point));
+        let mozak_proof = prove::<F, C, D>(
+            &program,
+            &record,
+            &stark,
+            &config,
+            public_inputs,
+            &mut TimingTree::default(),
+        )?;
+        verify_proof(&stark, mozak_proof.clone(), &config)?;
+
+        assert_eq!(
+            mozak_proof.public_value::<EventsCommitment>(&stark)?,
+            EventsCommitment(events_commitment_tape)
+        );
+        assert_eq!(
+            mozak_proof.public_value::<CastListCommitment>(&stark)?,
+            CastListCommitment(cast_list_commitment_tape)
+        );
+        // The default stark does not check these values, so it can't vouch
+        // for them.
+        assert!(mozak_proof
+            .public_value::<EventsCommitment>(&MozakStark::default())
+            .is_err());
+        Ok(())
+    }
+
+    fn prove_self_prog_id(
+        self_prog_id_tape: [u8; DIGEST_BYTES],
+    ) -> Result<(MozakStark<F, D>, AllProof<F, C, D>), anyhow::Error> {
+        let code = read_ecall_code(ecall::SELF_PROG_ID_TAPE, SELF_PROG_ID_ADDRESS, DIGEST_BYTES);
+        let (program, record) = code::execute_code_with_ro_memory(code, &[], &[], &[], RawTapes {
+            self_prog_id_tape,
+            ..Default::default()
+        });
+        let stark = MozakStark::<F, D>::default();
+        let public_inputs = PublicInputs::new(from_u32(program.entry_point));
+        let proof = prove::<F, C, D>(
+            &program,
+            &record,
+            &stark,
+            &StarkConfig::standard_fast_config(),
+            public_inputs,
+            &mut TimingTree::default(),
+        )?;
+        Ok((stark, proof))
+    }
+
+    #[test]
+    fn test_self_prog_id() -> Result<(), anyhow::Error> {
+        let config = StarkConfig::standard_fast_config();
+        // The program id does not depend on the tapes, so we can learn it from
+        // a first run.
+        let (_, proof) = prove_self_prog_id([0; DIGEST_BYTES])?;
+        let program_id = proof.program_id;
+
+        let (stark, proof) = prove_self_prog_id(program_id.inner())?;
+        assert_eq!(
+            proof.public_value::<SelfProgramId>(&stark)?,
+            SelfProgramId(Some(program_id))
+        );
+        verify_proof(&stark, proof, &config)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_wrong_self_prog_id_fails() -> Result<(), anyhow::Error> {
+        let config = StarkConfig::standard_fast_config();
+        let self_prog_id_tape: [u8; DIGEST_BYTES] = rand::thread_rng().gen();
+        let (stark, proof) = prove_self_prog_id(self_prog_id_tape)?;
+        assert!(matches!(
+            verify_proof(&stark, proof, &config),
+            Err(VerifierError::SelfProgramIdMismatch(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_unread_self_prog_id_is_not_checked() -> Result<(), anyhow::Error> {
+        let config = StarkConfig::standard_fast_config();
+        let self_prog_id_tape: [u8; DIGEST_BYTES] = rand::thread_rng().gen();
+        let (program, record) = code::execute_code_with_ro_memory(
+            read_tape_commitments_code(),
+            &[],
+            &[],
+            &[],
+            RawTapes {
+                self_prog_id_tape,
+                ..Default::default()
+            },
+        );
+        let stark = MozakStark::<F, D>::default();
+        let proof = prove::<F, C, D>(
+            &program,
+            &record,
+            &stark,
+            &config,
+            PublicInputs::new(from_u32(program.entry_point)),
+            &mut TimingTree::default(),
+        )?;
+        assert_eq!(
+            proof.public_value::<SelfProgramId>(&stark)?,
+            SelfProgramId(None)
+        );
+        verify_proof(&stark, proof, &config)?;
+        Ok(())
+    }
+
     #[test]
     fn test_circuit() -> anyhow::Result<()> {
         let stark = S::default();
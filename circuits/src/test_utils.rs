@@ -4,17 +4,21 @@ use mozak_runner::code;
 use mozak_runner::decode::ECALL;
 use mozak_runner::elf::Program;
 use mozak_runner::instruction::{Args, Instruction, Op};
+use mozak_runner::state::RawTapes;
 use mozak_runner::vm::ExecutionRecord;
+#[cfg(feature = "poseidon2")]
+use mozak_sdk::core::constants::DIGEST_BYTES;
+use mozak_sdk::core::constants::EVENT_BYTES;
 use mozak_sdk::core::ecall;
-use mozak_sdk::core::reg_abi::{REG_A0, REG_A1, REG_A2, REG_A3};
+use mozak_sdk::core::reg_abi::{REG_A0, REG_A1, REG_A2};
+#[cfg(feature = "poseidon2")]
+use mozak_sdk::core::reg_abi::{REG_A3, REG_A4};
 use plonky2::field::goldilocks_field::GoldilocksField;
 use plonky2::field::types::Field;
-use plonky2::fri::FriConfig;
 use plonky2::hash::hash_types::{HashOut, RichField};
 use plonky2::hash::poseidon2::Poseidon2Hash;
 use plonky2::plonk::circuit_data::CircuitConfig;
 use plonky2::plonk::config::{GenericConfig, Hasher, Poseidon2GoldilocksConfig};
-use plonky2::util::log2_ceil;
 use plonky2::util::timing::TimingTree;
 use starky::config::StarkConfig;
 use starky::prover::prove as prove_table;
@@ -25,37 +29,53 @@ use crate::bitshift::generation::generate_shift_amount_trace;
 use crate::bitshift::stark::BitshiftStark;
 use crate::cpu::generation::generate_cpu_trace;
 use crate::cpu::stark::CpuStark;
+#[cfg(feature = "decode")]
+use crate::decode::generation::generate_decode_trace;
+use crate::event_emission::generation::generate_event_emission_trace;
+#[cfg(feature = "goldilocks")]
+use crate::goldilocks::generation::generate_goldilocks_trace;
+#[cfg(feature = "memcopy")]
+use crate::memcopy::generation::generate_memcopy_trace;
 use crate::memory::generation::generate_memory_trace;
 use crate::memory::stark::MemoryStark;
 use crate::memory_fullword::generation::generate_fullword_memory_trace;
 use crate::memory_fullword::stark::FullWordMemoryStark;
 use crate::memory_halfword::generation::generate_halfword_memory_trace;
 use crate::memory_halfword::stark::HalfWordMemoryStark;
-use crate::memory_zeroinit::generation::generate_memory_zero_init_trace;
-use crate::memoryinit::generation::generate_memory_init_trace;
+use crate::memoryinit::generation::{generate_elf_memory_init_trace, generate_memory_init_trace};
 use crate::ops;
+#[cfg(feature = "poseidon2")]
 use crate::poseidon2_output_bytes::generation::generate_poseidon2_output_bytes_trace;
+#[cfg(feature = "poseidon2")]
 use crate::poseidon2_sponge::generation::generate_poseidon2_sponge_trace;
+#[cfg(feature = "decode")]
+use crate::program::generation::generate_program_rom_trace;
 use crate::rangecheck::generation::generate_rangecheck_trace;
 use crate::rangecheck::stark::RangeCheckStark;
 use crate::register::general::stark::RegisterStark;
 use crate::register::generation::{generate_register_init_trace, generate_register_trace};
 use crate::register::init::stark::RegisterInitStark;
+#[cfg(feature = "secp256k1")]
+use crate::secp256k1::generation::generate_secp256k1_trace;
 use crate::stark::batch_prover::batch_prove;
 use crate::stark::batch_verifier::batch_verify_proof;
 use crate::stark::mozak_stark::{MozakStark, PublicInputs, PUBLIC_TABLE_KINDS};
 use crate::stark::prover::prove;
+use crate::stark::security::ConfigProfile;
 use crate::stark::utils::trace_rows_to_poly_values;
 use crate::stark::verifier::verify_proof;
+#[cfg(feature = "poseidon2")]
+use crate::storage_device::generation::generate_output_tape_trace;
 use crate::storage_device::generation::{
     generate_call_tape_trace, generate_cast_list_commitment_tape_trace, generate_event_tape_trace,
-    generate_events_commitment_tape_trace, generate_private_tape_trace, generate_public_tape_trace,
-    generate_self_prog_id_tape_trace,
+    generate_events_commitment_tape_trace, generate_hint_tape_trace, generate_private_tape_trace,
+    generate_public_tape_trace, generate_self_prog_id_tape_trace,
 };
 use crate::storage_device::stark::StorageDeviceStark;
 use crate::tape_commitments::generation::generate_tape_commitments_trace;
 use crate::tape_commitments::stark::TapeCommitmentsStark;
-use crate::utils::from_u32;
+#[cfg(feature = "u256")]
+use crate::u256::generation::generate_u256_trace;
 use crate::xor::generation::generate_xor_trace;
 use crate::xor::stark::XorStark;
 
@@ -64,26 +84,9 @@ pub const D: usize = 2;
 pub type C = Poseidon2GoldilocksConfig;
 pub type F = <C as GenericConfig<D>>::F;
 
-/// Test Configuration with 1 bit of security
+/// Test Configuration with 1 bit of security, see [`ConfigProfile::Test`].
 #[must_use]
-pub fn fast_test_config() -> StarkConfig {
-    let config = StarkConfig::standard_fast_config();
-    StarkConfig {
-        security_bits: 1,
-        num_challenges: 2,
-        fri_config: FriConfig {
-            // Plonky2 says: "Having constraints of degree higher than the rate is not supported
-            // yet." So we automatically set the rate here as required by plonky2.
-            // TODO(Matthias): Change to maximum of constraint degrees of all starks, as we
-            // accumulate more types of starks.
-            rate_bits: log2_ceil(S::default().cpu_stark.constraint_degree()),
-            cap_height: 0,
-            proof_of_work_bits: 0,
-            num_query_rounds: 5,
-            ..config.fri_config
-        },
-    }
-}
+pub fn fast_test_config() -> StarkConfig { ConfigProfile::Test.config() }
 
 #[must_use]
 pub const fn fast_test_circuit_config() -> CircuitConfig {
@@ -152,8 +155,9 @@ impl ProveAndVerify for RangeCheckStark<F, D> {
         let add_trace = ops::add::generate(record);
         let blt_trace = ops::blt_taken::generate(record);
 
-        let memory_init = generate_memory_init_trace(program);
-        let memory_zeroinit_rows = generate_memory_zero_init_trace(&record.executed, program);
+        let elf_memory_init_rows = generate_elf_memory_init_trace(program);
+
+        let memory_init_rows = generate_memory_init_trace(&record.executed, program);
 
         let halfword_memory = generate_halfword_memory_trace(&record.executed);
         let fullword_memory = generate_fullword_memory_trace(&record.executed);
@@ -165,12 +169,26 @@ impl ProveAndVerify for RangeCheckStark<F, D> {
         let cast_list_commitment_tape_rows =
             generate_cast_list_commitment_tape_trace(&record.executed);
         let self_prog_id_tape_rows = generate_self_prog_id_tape_trace(&record.executed);
+        let hint_tape_rows = generate_hint_tape_trace(&record.executed);
+        let event_emission_rows = generate_event_emission_trace(&record.executed);
+        #[cfg(feature = "secp256k1")]
+        let secp256k1_rows = generate_secp256k1_trace(&record.executed);
+        #[cfg(feature = "u256")]
+        let u256_rows = generate_u256_trace(&record.executed);
+        #[cfg(feature = "memcopy")]
+        let memcopy_rows = generate_memcopy_trace(&record.executed);
+        #[cfg(feature = "goldilocks")]
+        let goldilocks_rows = generate_goldilocks_trace(&record.executed);
+        #[cfg(feature = "poseidon2")]
         let poseidon2_sponge_trace = generate_poseidon2_sponge_trace(&record.executed);
+        #[cfg(feature = "poseidon2")]
         let poseidon2_output_bytes = generate_poseidon2_output_bytes_trace(&poseidon2_sponge_trace);
+        #[cfg(feature = "poseidon2")]
+        let output_tape_rows = generate_output_tape_trace(&record.executed);
         let memory_trace = generate_memory_trace::<F>(
             &record.executed,
-            &memory_init,
-            &memory_zeroinit_rows,
+            &elf_memory_init_rows,
+            &memory_init_rows,
             &halfword_memory,
             &fullword_memory,
             &private_tape,
@@ -180,15 +198,40 @@ impl ProveAndVerify for RangeCheckStark<F, D> {
             &events_commitment_tape_rows,
             &cast_list_commitment_tape_rows,
             &self_prog_id_tape_rows,
+            &hint_tape_rows,
+            &event_emission_rows,
+            #[cfg(feature = "poseidon2")]
             &poseidon2_sponge_trace,
+            #[cfg(feature = "poseidon2")]
             &poseidon2_output_bytes,
+            #[cfg(feature = "poseidon2")]
+            &output_tape_rows,
+            #[cfg(feature = "secp256k1")]
+            &secp256k1_rows,
+            #[cfg(feature = "u256")]
+            &u256_rows,
+            #[cfg(feature = "memcopy")]
+            &memcopy_rows,
+            #[cfg(feature = "goldilocks")]
+            &goldilocks_rows,
         );
         let register_init = generate_register_init_trace(record);
         let (_, _, register_trace) = generate_register_trace(
             &cpu_trace,
             &add_trace,
             &blt_trace,
+            #[cfg(feature = "poseidon2")]
             &poseidon2_sponge_trace,
+            #[cfg(feature = "poseidon2")]
+            &output_tape_rows,
+            #[cfg(feature = "secp256k1")]
+            &secp256k1_rows,
+            #[cfg(feature = "u256")]
+            &u256_rows,
+            #[cfg(feature = "memcopy")]
+            &memcopy_rows,
+            #[cfg(feature = "goldilocks")]
+            &goldilocks_rows,
             &private_tape,
             &public_tape,
             &call_tape_rows,
@@ -196,6 +239,8 @@ impl ProveAndVerify for RangeCheckStark<F, D> {
             &events_commitment_tape_rows,
             &cast_list_commitment_tape_rows,
             &self_prog_id_tape_rows,
+            &hint_tape_rows,
+            &event_emission_rows,
             &register_init,
         );
         let trace_poly_values = trace_rows_to_poly_values(generate_rangecheck_trace(
@@ -203,7 +248,15 @@ impl ProveAndVerify for RangeCheckStark<F, D> {
             &add_trace,
             &blt_trace,
             &memory_trace,
+            &memory_init_rows,
             &register_trace,
+            &event_emission_rows,
+            #[cfg(feature = "secp256k1")]
+            &secp256k1_rows,
+            #[cfg(feature = "u256")]
+            &u256_rows,
+            #[cfg(feature = "decode")]
+            &generate_decode_trace(&generate_program_rom_trace(program)),
         ));
         let proof = prove_table::<F, C, S, D>(
             stark,
@@ -245,8 +298,9 @@ impl ProveAndVerify for MemoryStark<F, D> {
 
         let stark = S::default();
 
-        let memory_init = generate_memory_init_trace(program);
-        let memory_zeroinit_rows = generate_memory_zero_init_trace(&record.executed, program);
+        let elf_memory_init_rows = generate_elf_memory_init_trace(program);
+
+        let memory_init_rows = generate_memory_init_trace(&record.executed, program);
 
         let halfword_memory = generate_halfword_memory_trace(&record.executed);
         let fullword_memory = generate_fullword_memory_trace(&record.executed);
@@ -258,12 +312,26 @@ impl ProveAndVerify for MemoryStark<F, D> {
         let cast_list_commitment_tape_rows =
             generate_cast_list_commitment_tape_trace(&record.executed);
         let self_prog_id_tape_rows = generate_self_prog_id_tape_trace(&record.executed);
+        let hint_tape_rows = generate_hint_tape_trace(&record.executed);
+        let event_emission_rows = generate_event_emission_trace(&record.executed);
+        #[cfg(feature = "secp256k1")]
+        let secp256k1_rows = generate_secp256k1_trace(&record.executed);
+        #[cfg(feature = "u256")]
+        let u256_rows = generate_u256_trace(&record.executed);
+        #[cfg(feature = "memcopy")]
+        let memcopy_rows = generate_memcopy_trace(&record.executed);
+        #[cfg(feature = "goldilocks")]
+        let goldilocks_rows = generate_goldilocks_trace(&record.executed);
+        #[cfg(feature = "poseidon2")]
         let poseidon2_sponge_trace = generate_poseidon2_sponge_trace(&record.executed);
+        #[cfg(feature = "poseidon2")]
         let poseidon2_output_bytes = generate_poseidon2_output_bytes_trace(&poseidon2_sponge_trace);
+        #[cfg(feature = "poseidon2")]
+        let output_tape_rows = generate_output_tape_trace(&record.executed);
         let trace_poly_values = trace_rows_to_poly_values(generate_memory_trace(
             &record.executed,
-            &memory_init,
-            &memory_zeroinit_rows,
+            &elf_memory_init_rows,
+            &memory_init_rows,
             &halfword_memory,
             &fullword_memory,
             &private_tape,
@@ -273,8 +341,22 @@ impl ProveAndVerify for MemoryStark<F, D> {
             &events_commitment_tape_rows,
             &cast_list_commitment_tape_rows,
             &self_prog_id_tape_rows,
+            &hint_tape_rows,
+            &event_emission_rows,
+            #[cfg(feature = "poseidon2")]
             &poseidon2_sponge_trace,
+            #[cfg(feature = "poseidon2")]
             &poseidon2_output_bytes,
+            #[cfg(feature = "poseidon2")]
+            &output_tape_rows,
+            #[cfg(feature = "secp256k1")]
+            &secp256k1_rows,
+            #[cfg(feature = "u256")]
+            &u256_rows,
+            #[cfg(feature = "memcopy")]
+            &memcopy_rows,
+            #[cfg(feature = "goldilocks")]
+            &goldilocks_rows,
         ));
         let proof = prove_table::<F, C, S, D>(
             stark,
@@ -406,14 +488,38 @@ impl ProveAndVerify for RegisterStark<F, D> {
         let cast_list_commitment_tape_rows =
             generate_cast_list_commitment_tape_trace(&record.executed);
         let self_prog_id_tape_rows = generate_self_prog_id_tape_trace(&record.executed);
+        let hint_tape_rows = generate_hint_tape_trace(&record.executed);
+        let event_emission_rows = generate_event_emission_trace(&record.executed);
+        #[cfg(feature = "secp256k1")]
+        let secp256k1_rows = generate_secp256k1_trace(&record.executed);
+        #[cfg(feature = "u256")]
+        let u256_rows = generate_u256_trace(&record.executed);
+        #[cfg(feature = "memcopy")]
+        let memcopy_rows = generate_memcopy_trace(&record.executed);
+        #[cfg(feature = "goldilocks")]
+        let goldilocks_rows = generate_goldilocks_trace(&record.executed);
+        #[cfg(feature = "poseidon2")]
         let poseidon2_sponge_rows = generate_poseidon2_sponge_trace(&record.executed);
+        #[cfg(feature = "poseidon2")]
+        let output_tape_rows = generate_output_tape_trace(&record.executed);
 
         let register_init = generate_register_init_trace(record);
         let (_, _, trace) = generate_register_trace(
             &cpu_trace,
             &add_trace,
             &blt_trace,
+            #[cfg(feature = "poseidon2")]
             &poseidon2_sponge_rows,
+            #[cfg(feature = "poseidon2")]
+            &output_tape_rows,
+            #[cfg(feature = "secp256k1")]
+            &secp256k1_rows,
+            #[cfg(feature = "u256")]
+            &u256_rows,
+            #[cfg(feature = "memcopy")]
+            &memcopy_rows,
+            #[cfg(feature = "goldilocks")]
+            &goldilocks_rows,
             &private_tape,
             &public_tape,
             &call_tape,
@@ -421,6 +527,8 @@ impl ProveAndVerify for RegisterStark<F, D> {
             &events_commitment_tape_rows,
             &cast_list_commitment_tape_rows,
             &self_prog_id_tape_rows,
+            &hint_tape_rows,
+            &event_emission_rows,
             &register_init,
         );
         let trace_poly_values = trace_rows_to_poly_values(trace);
@@ -475,13 +583,22 @@ pub fn prove_and_verify_mozak_stark(
     program: &Program,
     record: &ExecutionRecord<F>,
     config: &StarkConfig,
+) -> Result<()> {
+    prove_and_verify_mozak_stark_with_config::<C>(program, record, config)
+}
+
+/// Like [`prove_and_verify_mozak_stark`], but with the [`GenericConfig`]
+/// chosen by the caller, e.g. `KeccakGoldilocksConfig` for keccak
+/// transcripts.
+pub fn prove_and_verify_mozak_stark_with_config<Config: GenericConfig<D, F = F>>(
+    program: &Program,
+    record: &ExecutionRecord<F>,
+    config: &StarkConfig,
 ) -> Result<()> {
     let stark = MozakStark::default();
-    let public_inputs = PublicInputs {
-        entry_point: from_u32(program.entry_point),
-    };
+    let public_inputs = PublicInputs::for_execution(program, record);
 
-    let all_proof = prove::<F, C, D>(
+    let all_proof = prove::<F, Config, D>(
         program,
         record,
         &stark,
@@ -489,7 +606,8 @@ pub fn prove_and_verify_mozak_stark(
         public_inputs,
         &mut TimingTree::default(),
     )?;
-    verify_proof(&stark, all_proof, config)
+    verify_proof(&stark, all_proof, config)?;
+    Ok(())
 }
 
 pub fn prove_and_verify_batch_mozak_stark(
@@ -498,9 +616,7 @@ pub fn prove_and_verify_batch_mozak_stark(
     config: &StarkConfig,
 ) -> Result<()> {
     let stark = MozakStark::default();
-    let public_inputs = PublicInputs {
-        entry_point: from_u32(program.entry_point),
-    };
+    let public_inputs = PublicInputs::for_execution(program, record);
 
     let (all_proof, degree_bits) = batch_prove::<F, C, D>(
         program,
@@ -527,65 +643,416 @@ pub fn inv<F: RichField>(x: u64) -> u64 {
         .to_canonical_u64()
 }
 
+#[cfg(feature = "poseidon2")]
 pub struct Poseidon2Test {
     pub data: String,
     pub input_start_addr: u32,
     pub output_start_addr: u32,
 }
 
+#[cfg(feature = "poseidon2")]
 #[must_use]
 pub fn create_poseidon2_test(
     test_data: &[Poseidon2Test],
 ) -> (Program, ExecutionRecord<GoldilocksField>) {
-    let mut instructions = vec![];
-    let mut memory: Vec<(u32, u8)> = vec![];
+    test_data
+        .iter()
+        .fold(ScenarioBuilder::default(), |scenario, test_datum| {
+            scenario.poseidon2(
+                test_datum.data.as_bytes(),
+                test_datum.input_start_addr,
+                test_datum.output_start_addr,
+            )
+        })
+        .build()
+}
 
-    for test_datum in test_data {
-        let mut data_bytes = test_datum.data.as_bytes().to_vec();
-        // VM expects input len to be multiple of RATE bits
-        data_bytes.resize(data_bytes.len().next_multiple_of(8), 0_u8);
-        let data_len = data_bytes.len();
-        let input_memory: Vec<(u32, u8)> =
-            izip!((test_datum.input_start_addr..), data_bytes).collect();
-        memory.extend(input_memory);
-        instructions.extend(&[
+/// Adds the secp256k1 generator `G` to `2G`, and then doubles the result.
+#[cfg(feature = "secp256k1")]
+#[must_use]
+pub fn secp256k1_test() -> (Program, ExecutionRecord<GoldilocksField>) {
+    use mozak_runner::secp256k1::to_point;
+    use num::BigUint;
+
+    let point = |x: &str, y: &str| {
+        let parse = |hex: &str| BigUint::parse_bytes(hex.as_bytes(), 16).unwrap();
+        to_point(&parse(x), &parse(y))
+    };
+    let generator = point(
+        "79BE667EF9DCBBAC55A06295CE870B07029BFCDB2DCE28D959F2815B16F81798",
+        "483ADA7726A3C4655DA4FBFC0E1108A8FD17B448A68554199C47D08FFB10D4B8",
+    );
+    let generator_times_2 = point(
+        "C6047F9441ED7D6D3045406E95C07CD85C778E4B8CEF3CA7ABAC09B95C709EE5",
+        "1AE168FEA63DC339A3C58419466CEAEEF7F632653266D0E1236431A950CFE52A",
+    );
+    let memory: Vec<(u32, u8)> = izip!(0x100.., generator_times_2)
+        .chain(izip!(0x200.., generator))
+        .collect();
+    code::execute(
+        [
+            ECALL,
             Instruction {
                 op: Op::ADD,
                 args: Args {
                     rd: REG_A0,
-                    imm: ecall::POSEIDON2,
-                    ..Args::default()
-                },
-            },
-            Instruction {
-                op: Op::ADD,
-                args: Args {
-                    rd: REG_A1,
-                    imm: test_datum.input_start_addr,
-                    ..Args::default()
-                },
-            },
-            Instruction {
-                op: Op::ADD,
-                args: Args {
-                    rd: REG_A2,
-                    imm: u32::try_from(data_len).expect("don't use very long data"),
+                    imm: ecall::SECP256K1_DOUBLE,
                     ..Args::default()
                 },
             },
-            Instruction {
-                op: Op::ADD,
-                args: Args {
-                    rd: REG_A3,
-                    imm: test_datum.output_start_addr,
-                    ..Args::default()
-                },
+            ECALL,
+        ],
+        &memory,
+        &[
+            (REG_A0, ecall::SECP256K1_ADD),
+            (REG_A1, 0x100),
+            (REG_A2, 0x200),
+        ],
+    )
+}
+
+/// Multiplies `x` by `y` modulo `2^255 - 19`, then by `y` again modulo
+/// `2^256`, and adds `y`.
+#[cfg(feature = "u256")]
+#[must_use]
+pub fn u256_test() -> (Program, ExecutionRecord<GoldilocksField>) {
+    use itertools::chain;
+    use mozak_runner::u256::to_bytes;
+    use mozak_sdk::core::reg_abi::REG_A3;
+    use num::BigUint;
+
+    let parse = |hex: &str| to_bytes(&BigUint::parse_bytes(hex.as_bytes(), 16).unwrap());
+    let x = parse("79BE667EF9DCBBAC55A06295CE870B07029BFCDB2DCE28D959F2815B16F81798");
+    let y = parse("C6047F9441ED7D6D3045406E95C07CD85C778E4B8CEF3CA7ABAC09B95C709EE5");
+    let modulus = parse("7FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFED");
+    let memory: Vec<(u32, u8)> = chain!(
+        izip!(0x100.., x),
+        izip!(0x200.., y),
+        izip!(0x300.., modulus)
+    )
+    .collect();
+    let set_ecall = |imm| Instruction {
+        op: Op::ADD,
+        args: Args {
+            rd: REG_A0,
+            imm,
+            ..Args::default()
+        },
+    };
+    code::execute(
+        [
+            ECALL,
+            set_ecall(ecall::U256_MUL),
+            ECALL,
+            set_ecall(ecall::U256_ADD),
+            ECALL,
+        ],
+        &memory,
+        &[
+            (REG_A0, ecall::U256_MULMOD),
+            (REG_A1, 0x100),
+            (REG_A2, 0x200),
+            (REG_A3, 0x300),
+        ],
+    )
+}
+
+/// Adds `7` to `5`, multiplies the sum by `7`, and inverts the product, all
+/// in place at `0x100`.
+#[cfg(feature = "goldilocks")]
+#[must_use]
+pub fn goldilocks_test() -> (Program, ExecutionRecord<GoldilocksField>) {
+    let set = |rd, imm| Instruction {
+        op: Op::ADD,
+        args: Args {
+            rd,
+            imm,
+            ..Args::default()
+        },
+    };
+    code::execute(
+        [
+            ECALL,
+            set(REG_A0, ecall::GOLDILOCKS_MUL),
+            ECALL,
+            set(REG_A0, ecall::GOLDILOCKS_INVERSE),
+            ECALL,
+        ],
+        &izip!(0x100.., 5_u64.to_le_bytes())
+            .chain(izip!(0x200.., 7_u64.to_le_bytes()))
+            .collect::<Vec<_>>(),
+        &[
+            (REG_A0, ecall::GOLDILOCKS_ADD),
+            (REG_A1, 0x100),
+            (REG_A2, 0x200),
+        ],
+    )
+}
+
+/// Copies six bytes two bytes up, over themselves, sets three bytes, and then
+/// copies none.
+#[cfg(feature = "memcopy")]
+#[must_use]
+pub fn memcopy_test() -> (Program, ExecutionRecord<GoldilocksField>) {
+    use mozak_sdk::core::reg_abi::REG_A3;
+
+    let set = |rd, imm| Instruction {
+        op: Op::ADD,
+        args: Args {
+            rd,
+            imm,
+            ..Args::default()
+        },
+    };
+    code::execute(
+        [
+            ECALL,
+            set(REG_A0, ecall::MEMSET),
+            set(REG_A1, 0x200),
+            set(REG_A2, 0x5A),
+            set(REG_A3, 3),
+            ECALL,
+            set(REG_A0, ecall::MEMCOPY),
+            set(REG_A3, 0),
+            ECALL,
+        ],
+        &izip!(0x100.., 1..=8).collect::<Vec<_>>(),
+        &[
+            (REG_A0, ecall::MEMCOPY),
+            (REG_A1, 0x102),
+            (REG_A2, 0x100),
+            (REG_A3, 6),
+        ],
+    )
+}
+
+/// Emits one event for each pair of an object's address and an event type,
+/// in the given order. The value of the `i`-th event is `i` in every byte.
+#[must_use]
+pub fn event_emission_test(
+    events: &[([u8; 8], u8)],
+) -> (Program, ExecutionRecord<GoldilocksField>) {
+    let event_bytes = u32::try_from(EVENT_BYTES).unwrap();
+    let memory: Vec<(u32, u8)> = izip!((0x100..).step_by(EVENT_BYTES), 0_u8.., events)
+        .flat_map(|(addr, i, (object, event_type))| {
+            let bytes = object.iter().chain([event_type]).copied().chain([i; 32]);
+            izip!(addr.., bytes)
+        })
+        .collect();
+    let next_event = Instruction {
+        op: Op::ADD,
+        args: Args {
+            rd: REG_A1,
+            rs1: REG_A1,
+            imm: event_bytes,
+            ..Args::default()
+        },
+    };
+    let mut instructions = vec![ECALL];
+    for _ in events.iter().skip(1) {
+        instructions.extend([next_event, ECALL]);
+    }
+    code::execute(instructions, &memory, &[
+        (REG_A0, ecall::EMIT_EVENT),
+        (REG_A1, 0x100),
+        (REG_A2, event_bytes),
+    ])
+}
+
+/// Builds a program out of snippets of instructions, e.g. tape reads,
+/// poseidon2 hashes and branches, runs it and checks the expected final
+/// state.
+///
+/// Meant for regression tests of the interactions between tables. The
+/// builder is `Clone` and `Debug`, so that proptest strategies can generate
+/// it, e.g. with `prop_map`.
+///
+/// Code starts at address 0, and the runner appends a halting `ECALL`, which
+/// clobbers `a0`.
+#[derive(Clone, Debug, Default)]
+pub struct ScenarioBuilder {
+    code: Vec<Instruction>,
+    ro_memory: Vec<(u32, u8)>,
+    rw_memory: Vec<(u32, u8)>,
+    registers: Vec<(u8, u32)>,
+    tapes: RawTapes,
+    expected_registers: Vec<(u8, u32)>,
+    expected_memory: Vec<(u32, u8)>,
+}
+
+impl ScenarioBuilder {
+    /// `rd = imm`
+    fn set_register(rd: u8, imm: u32) -> Instruction {
+        Instruction {
+            op: Op::ADD,
+            args: Args {
+                rd,
+                imm,
+                ..Args::default()
             },
+        }
+    }
+
+    /// Address of the next instruction.
+    fn next_pc(&self) -> u32 { u32::try_from(self.code.len() * 4).unwrap() }
+
+    #[must_use]
+    pub fn instructions(mut self, code: impl IntoIterator<Item = Instruction>) -> Self {
+        self.code.extend(code);
+        self
+    }
+
+    /// Sets the initial value of register `reg`.
+    #[must_use]
+    pub fn register(mut self, reg: u8, value: u32) -> Self {
+        self.registers.push((reg, value));
+        self
+    }
+
+    /// Initialises read-write memory from `addr` on with `bytes`.
+    #[must_use]
+    pub fn memory(mut self, addr: u32, bytes: &[u8]) -> Self {
+        self.rw_memory.extend(izip!(addr.., bytes.iter().copied()));
+        self
+    }
+
+    /// Initialises read-only memory from `addr` on with `bytes`.
+    #[must_use]
+    pub fn ro_memory(mut self, addr: u32, bytes: &[u8]) -> Self {
+        self.ro_memory.extend(izip!(addr.., bytes.iter().copied()));
+        self
+    }
+
+    #[must_use]
+    pub fn private_tape(mut self, bytes: &[u8]) -> Self {
+        self.tapes.private_tape = bytes.to_vec();
+        self
+    }
+
+    #[must_use]
+    pub fn public_tape(mut self, bytes: &[u8]) -> Self {
+        self.tapes.public_tape = bytes.to_vec();
+        self
+    }
+
+    /// Reads `len` bytes from the tape of `tape_ecall`, e.g.
+    /// [`ecall::PUBLIC_TAPE`], into memory at `addr`, which is added as
+    /// zeroed read-write memory.
+    #[must_use]
+    pub fn read_tape(self, tape_ecall: u32, addr: u32, len: u32) -> Self {
+        self.memory(addr, &vec![0; len as usize]).instructions([
+            Self::set_register(REG_A0, tape_ecall),
+            Self::set_register(REG_A1, addr),
+            Self::set_register(REG_A2, len),
+            ECALL,
+        ])
+    }
+
+    /// Hashes `data`, zero padded to a multiple of 8 bytes, from memory at
+    /// `input_addr`, and writes the digest to `output_addr`.
+    #[cfg(feature = "poseidon2")]
+    #[must_use]
+    pub fn poseidon2(self, data: &[u8], input_addr: u32, output_addr: u32) -> Self {
+        self.poseidon2_xof(data, input_addr, output_addr, DIGEST_BYTES)
+    }
+
+    /// Like [`Self::poseidon2`], but squeezes `output_len` bytes of output, a
+    /// multiple of [`DIGEST_BYTES`].
+    #[cfg(feature = "poseidon2")]
+    #[must_use]
+    pub fn poseidon2_xof(
+        self,
+        data: &[u8],
+        input_addr: u32,
+        output_addr: u32,
+        output_len: usize,
+    ) -> Self {
+        let mut data = data.to_vec();
+        // VM expects input len to be multiple of RATE bits
+        data.resize(data.len().next_multiple_of(8), 0_u8);
+        let len = u32::try_from(data.len()).expect("don't use very long data");
+        self.memory(input_addr, &data).instructions([
+            Self::set_register(REG_A0, ecall::POSEIDON2),
+            Self::set_register(REG_A1, input_addr),
+            Self::set_register(REG_A2, len),
+            Self::set_register(REG_A3, output_addr),
+            Self::set_register(REG_A4, u32::try_from(output_len).unwrap()),
             ECALL,
-        ]);
+        ])
+    }
+
+    /// Branches with `op`, e.g. [`Op::BEQ`], over the `skipped` instructions
+    /// if the condition on `rs1` and `rs2` holds.
+    #[must_use]
+    pub fn skip_if(
+        self,
+        op: Op,
+        rs1: u8,
+        rs2: u8,
+        skipped: impl IntoIterator<Item = Instruction>,
+    ) -> Self {
+        let skipped: Vec<_> = skipped.into_iter().collect();
+        let target = self.next_pc() + u32::try_from((skipped.len() + 1) * 4).unwrap();
+        self.instructions([Instruction {
+            op,
+            args: Args {
+                rs1,
+                rs2,
+                imm: target,
+                ..Args::default()
+            },
+        }])
+        .instructions(skipped)
+    }
+
+    /// Expects register `reg` to hold `value` after the run.
+    #[must_use]
+    pub fn expect_register(mut self, reg: u8, value: u32) -> Self {
+        self.expected_registers.push((reg, value));
+        self
+    }
+
+    /// Expects memory from `addr` on to hold `bytes` after the run.
+    #[must_use]
+    pub fn expect_memory(mut self, addr: u32, bytes: &[u8]) -> Self {
+        self.expected_memory
+            .extend(izip!(addr.., bytes.iter().copied()));
+        self
+    }
+
+    /// Runs the program, and checks the expected final state.
+    ///
+    /// # Panics
+    /// Panics if the program does not halt, or the final state differs from
+    /// the expectations.
+    #[must_use]
+    pub fn build(&self) -> (Program, ExecutionRecord<GoldilocksField>) {
+        let (program, record) = code::execute_code_with_ro_memory(
+            self.code.iter().copied(),
+            &self.ro_memory,
+            &self.rw_memory,
+            &self.registers,
+            self.tapes.clone(),
+        );
+        let state = &record.last_state;
+        for &(reg, value) in &self.expected_registers {
+            assert_eq!(state.get_register_value(reg), value, "register {reg}");
+        }
+        for &(addr, value) in &self.expected_memory {
+            assert_eq!(state.load_u8(addr), value, "memory at {addr:#x}");
+        }
+        (program, record)
     }
 
-    code::execute(instructions, memory.as_slice(), &[])
+    /// Runs the program, checks the expected final state, and proves and
+    /// verifies the execution with `Stark`.
+    ///
+    /// # Errors
+    /// Errors if proving or verifying fails.
+    pub fn prove_and_verify<Stark: ProveAndVerify>(&self) -> Result<()> {
+        let (program, record) = self.build();
+        Stark::prove_and_verify(&program, &record)
+    }
 }
 
 pub fn hash_str(v: &str) -> HashOut<F> {
@@ -598,3 +1065,54 @@ pub fn hash_branch<F: RichField>(left: &HashOut<F>, right: &HashOut<F>) -> HashO
     let [r0, r1, r2, r3] = right.elements;
     Poseidon2Hash::hash_no_pad(&[l0, l1, l2, l3, r0, r1, r2, r3])
 }
+
+#[cfg(test)]
+mod tests {
+    use mozak_runner::instruction::{Instruction, Op};
+    use mozak_runner::test_utils::u8_extra;
+    use mozak_sdk::core::ecall;
+    use proptest::prelude::ProptestConfig;
+    use proptest::{prop_compose, proptest};
+
+    use super::ScenarioBuilder;
+    use crate::cpu::stark::CpuStark;
+    use crate::stark::mozak_stark::MozakStark;
+    use crate::test_utils::{D, F};
+
+    prop_compose! {
+        /// Reads a byte from the public tape, and sets `x7` if it differs
+        /// from `b`.
+        fn tape_and_branch()(a in u8_extra(), b in u8_extra()) -> ScenarioBuilder {
+            ScenarioBuilder::default()
+                .public_tape(&[a, 1, 2, 3])
+                .read_tape(ecall::PUBLIC_TAPE, 0x100, 4)
+                .instructions([Instruction::lbu(5, 0, 0x100)])
+                .register(6, u32::from(b))
+                .skip_if(Op::BEQ, 5, 6, [Instruction::li(7, 1)])
+                .expect_memory(0x100, &[a, 1, 2, 3])
+                .expect_register(7, u32::from(a != b))
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(4))]
+        #[test]
+        fn prove_scenario_cpu(scenario in tape_and_branch()) {
+            scenario.prove_and_verify::<CpuStark<F, D>>().unwrap();
+        }
+    }
+
+    #[test]
+    fn prove_scenario_mozak() {
+        let scenario = ScenarioBuilder::default()
+            .public_tape(&[7, 1, 2, 3])
+            .read_tape(ecall::PUBLIC_TAPE, 0x100, 4)
+            .register(6, 7)
+            .skip_if(Op::BNE, 0, 6, [Instruction::li(7, 1)])
+            .expect_memory(0x100, &[7, 1, 2, 3])
+            .expect_register(7, 0);
+        #[cfg(feature = "poseidon2")]
+        let scenario = scenario.poseidon2(b"scenario", 0x200, 0x300);
+        scenario.prove_and_verify::<MozakStark<F, D>>().unwrap();
+    }
+}
@@ -0,0 +1,205 @@
+//! Known answer test vectors for cross-implementation checks.
+//!
+//! A [`TestVector`] pins down everything an independent implementation of the
+//! tables (e.g. a port to another proving system) has to reproduce for a
+//! small fixed program: the trace of each table, the challenges drawn from
+//! the transcript, and a hash of the final proof. Replaying the vectors also
+//! catches accidental changes to trace generation or constraints.
+//!
+//! Vectors are stored as JSON under `test_vectors/v{VERSION}`, and are only
+//! meaningful for the default features, which fix the set of tables. Bump
+//! [`VERSION`] when a change to the tables is deliberate, and regenerate them
+//! with `MOZAK_UPDATE_TEST_VECTORS=1`.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{ensure, Context, Result};
+use itertools::{izip, Itertools};
+use mozak_runner::elf::Program;
+use mozak_runner::vm::ExecutionRecord;
+use plonky2::field::extension::FieldExtension;
+use plonky2::field::types::{Field, PrimeField64};
+use plonky2::hash::poseidon2::Poseidon2Hash;
+use plonky2::plonk::config::Hasher;
+use plonky2::util::timing::TimingTree;
+use serde::{Deserialize, Serialize};
+
+use crate::generation::generate_traces;
+use crate::stark::mozak_stark::{all_kind, MozakStark, PublicInputs};
+use crate::stark::prover::prove_with_traces;
+use crate::test_utils::{fast_test_config, C, D, F};
+use crate::trace_source::Execution;
+use crate::utils::from_u32;
+
+/// Version of the test vector format and of the tables they pin down.
+pub const VERSION: u32 = 1;
+
+/// Set to regenerate the stored test vectors instead of checking them.
+pub const UPDATE_ENV_VAR: &str = "MOZAK_UPDATE_TEST_VECTORS";
+
+/// Expected values of a single table.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TableVector {
+    /// Name of the [`TableKind`](crate::stark::mozak_stark::TableKind).
+    pub kind: String,
+    /// Columns of the trace, as canonical field elements.
+    pub trace: Vec<Vec<u64>>,
+    /// Challenges used to combine the constraints of the table.
+    pub stark_alphas: Vec<u64>,
+    /// Point at which the polynomials of the table are opened.
+    pub stark_zeta: [u64; D],
+}
+
+/// Expected values of a proof of a fixed program, see the module docs.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestVector {
+    pub version: u32,
+    pub name: String,
+    pub tables: Vec<TableVector>,
+    /// `(beta, gamma)` pairs of the cross table lookup challenges.
+    pub ctl_challenges: Vec<[u64; 2]>,
+    /// Poseidon2 hash of the proof serialized as JSON.
+    pub proof_hash: [u64; 4],
+}
+
+impl TestVector {
+    /// Proves `record` with [`fast_test_config`], and records the expected
+    /// values along the way.
+    pub fn generate(name: &str, program: &Program, record: &ExecutionRecord<F>) -> Result<Self> {
+        let stark = MozakStark::default();
+        let config = fast_test_config();
+        let traces =
+            generate_traces::<F, D>(&Execution::new(program, record), &mut TimingTree::default());
+        let proof = prove_with_traces::<F, C, D>(
+            &stark,
+            &config,
+            PublicInputs::new(from_u32(program.entry_point)),
+            &traces,
+            None,
+            &mut TimingTree::default(),
+        )?;
+        let challenges = proof.get_challenges(&config, &stark.transcript);
+
+        let canonical = |values: &[F]| values.iter().map(F::to_canonical_u64).collect_vec();
+        let tables = all_kind!(|kind| {
+            let stark_challenges = challenges.stark_challenges[kind]
+                .as_ref()
+                .expect("the default stark skips no table");
+            TableVector {
+                kind: format!("{kind:?}"),
+                trace: traces[kind]
+                    .iter()
+                    .map(|column| canonical(&column.values))
+                    .collect(),
+                stark_alphas: canonical(&stark_challenges.stark_alphas),
+                stark_zeta: stark_challenges
+                    .stark_zeta
+                    .to_basefield_array()
+                    .map(|x| x.to_canonical_u64()),
+            }
+        });
+        let ctl_challenges = challenges
+            .ctl_challenges
+            .challenges
+            .iter()
+            .map(|challenge| [challenge.beta, challenge.gamma].map(|x| x.to_canonical_u64()))
+            .collect();
+        let proof_bytes = serde_json::to_vec(&proof)?;
+        let proof_hash = Poseidon2Hash::hash_no_pad(
+            &proof_bytes
+                .into_iter()
+                .map(F::from_canonical_u8)
+                .collect_vec(),
+        );
+
+        Ok(Self {
+            version: VERSION,
+            name: name.to_string(),
+            tables: tables.0.into_iter().collect(),
+            ctl_challenges,
+            proof_hash: proof_hash.elements.map(|x| x.to_canonical_u64()),
+        })
+    }
+
+    /// Path of the stored test vector called `name`.
+    #[must_use]
+    pub fn path(name: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("test_vectors")
+            .join(format!("v{VERSION}"))
+            .join(format!("{name}.json"))
+    }
+
+    pub fn read(path: &Path) -> Result<Self> {
+        let file = fs::File::open(path).with_context(|| format!("reading {}", path.display()))?;
+        Ok(serde_json::from_reader(file)?)
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let file = fs::File::create(path).with_context(|| format!("writing {}", path.display()))?;
+        Ok(serde_json::to_writer(file, self)?)
+    }
+
+    /// Checks `self` against the `expected` vector, and names the first
+    /// table that differs.
+    pub fn check(&self, expected: &Self) -> Result<()> {
+        ensure!(
+            self.version == expected.version,
+            "{}: expected version {}, got {}",
+            self.name,
+            expected.version,
+            self.version
+        );
+        ensure!(
+            self.tables
+                .iter()
+                .map(|table| &table.kind)
+                .eq(expected.tables.iter().map(|table| &table.kind)),
+            "{}: the set of tables changed, are the default features enabled?",
+            self.name
+        );
+        for (table, expected_table) in izip!(&self.tables, &expected.tables) {
+            ensure!(
+                table.trace == expected_table.trace,
+                "{}: trace of {} table changed",
+                self.name,
+                table.kind
+            );
+            ensure!(
+                table == expected_table,
+                "{}: challenges of {} table changed",
+                self.name,
+                table.kind
+            );
+        }
+        ensure!(
+            self.ctl_challenges == expected.ctl_challenges,
+            "{}: cross table lookup challenges changed",
+            self.name
+        );
+        ensure!(
+            self.proof_hash == expected.proof_hash,
+            "{}: proof changed",
+            self.name
+        );
+        Ok(())
+    }
+}
+
+/// Checks a proof of `record` against the stored test vector called `name`.
+///
+/// The vector is (re)written instead, when it does not exist yet or
+/// [`UPDATE_ENV_VAR`] is set.
+pub fn check_test_vector(name: &str, program: &Program, record: &ExecutionRecord<F>) -> Result<()> {
+    let vector = TestVector::generate(name, program, record)?;
+    let path = TestVector::path(name);
+    if std::env::var_os(UPDATE_ENV_VAR).is_some() || !path.exists() {
+        tracing::info!("writing test vector {}", path.display());
+        return vector.write(&path);
+    }
+    vector.check(&TestVector::read(&path)?)
+}
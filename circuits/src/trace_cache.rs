@@ -0,0 +1,299 @@
+//! An on-disk cache of generated traces, for iterating on the prover with the
+//! same guest and inputs without executing and generating traces every time.
+//!
+//! Entries are keyed by a [`TraceCacheKey`], a hash of everything the traces
+//! depend on: the program, the tapes, the cycle budget and the set of tables,
+//! which the enabled features decide. Each table is stored in a file of its
+//! own, as rkyv compressed with zstd, in a directory per crate version, so
+//! that a new version of the crate never reads the entries of an old one.
+
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+use std::fs;
+use std::io::ErrorKind;
+use std::num::NonZeroU64;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, ensure, Result};
+use itertools::Itertools;
+use log::warn;
+use mozak_runner::elf::Program;
+use mozak_runner::state::RawTapes;
+use plonky2::field::polynomial::PolynomialValues;
+use plonky2::hash::hash_types::RichField;
+use plonky2::hash::hashing::hash_n_to_hash_no_pad;
+use plonky2::hash::poseidon2::Poseidon2Permutation;
+use rkyv::rancor::Failure;
+use rkyv::util::AlignedVec;
+
+use crate::stark::mozak_stark::{all_kind, TableKind, TableKindArray};
+
+/// Prefix of the directory of the entries of a version of the crate.
+const VERSION_PREFIX: &str = "mozak-traces-v";
+
+/// Traces of all tables, as generated by
+/// [`generate_traces`](crate::generation::generate_traces).
+pub type Traces<F> = TableKindArray<Vec<PolynomialValues<F>>>;
+
+/// Identifies the traces of an execution.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TraceCacheKey(pub [u64; 4]);
+
+impl TraceCacheKey {
+    /// Poseidon2 hash of the program, the tapes and the cycle budget of an
+    /// execution, and of the names of the tables.
+    ///
+    /// The program and tapes are encoded as JSON, with the memory and code
+    /// sorted by address, and the JSON packed into 32-bit words.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the encoding is longer than `u32::MAX` bytes.
+    #[must_use]
+    pub fn new<F: RichField>(
+        program: &Program,
+        tapes: &RawTapes,
+        max_cycles: Option<NonZeroU64>,
+    ) -> Self {
+        let sorted = |data: &HashMap<u32, u8>| data.iter().sorted().collect_vec();
+        let program = (
+            program.entry_point,
+            sorted(&program.ro_memory.0),
+            sorted(&program.rw_memory.0),
+            program
+                .ro_code
+                .iter()
+                .sorted_by_key(|(&pc, _)| pc)
+                .collect_vec(),
+            &program.guard_regions,
+            &program.heap,
+            &program.stack,
+            // Which part of the image is the library decides how the traces
+            // split it between the program and the library tables.
+            program.library.as_deref().map(|library| {
+                (
+                    sorted(&library.ro_memory.0),
+                    sorted(&library.rw_memory.0),
+                    library.ro_code.keys().sorted().collect_vec(),
+                )
+            }),
+        );
+        let tapes = (
+            &tapes.private_tape,
+            &tapes.public_tape,
+            &tapes.call_tape,
+            &tapes.event_tape,
+            tapes.events_commitment_tape,
+            tapes.cast_list_commitment_tape,
+            tapes.self_prog_id_tape,
+        );
+        let tables = all_kind!(|kind| format!("{kind:?}"));
+        let bytes = serde_json::to_vec(&(program, tapes, max_cycles, tables.iter().collect_vec()))
+            .expect("programs and tapes encode as JSON");
+        let len = u32::try_from(bytes.len()).expect("trace cache keys fit into u32 bytes");
+        let elements = std::iter::once(len)
+            .chain(bytes.chunks(4).map(|chunk| {
+                let mut word = [0; 4];
+                word[..chunk.len()].copy_from_slice(chunk);
+                u32::from_le_bytes(word)
+            }))
+            .map(F::from_canonical_u32)
+            .collect_vec();
+        let hash = hash_n_to_hash_no_pad::<F, Poseidon2Permutation<F>>(&elements);
+        Self(hash.elements.map(|element| element.to_canonical_u64()))
+    }
+}
+
+impl Display for TraceCacheKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.0.iter().try_for_each(|word| write!(f, "{word:016x}"))
+    }
+}
+
+/// A directory of cached traces.
+#[derive(Clone, Debug)]
+pub struct TraceCache {
+    /// The directory of the entries of this version of the crate.
+    dir: PathBuf,
+}
+
+impl TraceCache {
+    /// Opens the cache in `root`, creating it if needed, and removes the
+    /// entries of other versions of the crate from it.
+    ///
+    /// # Errors
+    /// Errors if `root` cannot be created or listed.
+    pub fn open(root: impl AsRef<Path>) -> Result<Self> {
+        let root = root.as_ref();
+        let version = format!("{VERSION_PREFIX}{}", env!("CARGO_PKG_VERSION"));
+        fs::create_dir_all(root.join(&version))?;
+        for entry in fs::read_dir(root)? {
+            let entry = entry?;
+            let name = entry.file_name();
+            if name != version.as_str() && name.to_string_lossy().starts_with(VERSION_PREFIX) {
+                fs::remove_dir_all(entry.path())?;
+            }
+        }
+        Ok(Self {
+            dir: root.join(&version),
+        })
+    }
+
+    fn table_path(dir: &Path, kind: TableKind) -> PathBuf { dir.join(format!("{kind:?}.rkyv.zst")) }
+
+    /// The traces stored under `key`, if any.
+    ///
+    /// # Errors
+    /// Errors if an entry exists but cannot be read or decoded.
+    pub fn load<F: RichField>(&self, key: &TraceCacheKey) -> Result<Option<Traces<F>>> {
+        let dir = self.dir.join(key.to_string());
+        if !dir.is_dir() {
+            return Ok(None);
+        }
+        let traces = all_kind!(|kind| {
+            let path = Self::table_path(&dir, kind);
+            fs::read(&path)
+                .map_err(anyhow::Error::from)
+                .and_then(|compressed| {
+                    let mut bytes = AlignedVec::new();
+                    bytes.extend_from_slice(&zstd::decode_all(compressed.as_slice())?);
+                    let columns = rkyv::from_bytes::<Vec<Vec<u64>>, Failure>(&bytes)
+                        .map_err(|_| anyhow!("malformed trace in {}", path.display()))?;
+                    columns
+                        .into_iter()
+                        .map(|column| {
+                            ensure!(
+                                column.iter().all(|&value| value < F::ORDER),
+                                "non-canonical value in {}",
+                                path.display()
+                            );
+                            Ok(PolynomialValues::new(
+                                column.into_iter().map(F::from_canonical_u64).collect(),
+                            ))
+                        })
+                        .collect::<Result<Vec<_>>>()
+                })
+        });
+        traces.transpose().map(Some)
+    }
+
+    /// Stores `traces` under `key`, replacing any earlier entry.
+    ///
+    /// The tables are written to a scratch directory first, and moved into
+    /// place together, so that an interrupted store leaves no partial entry.
+    ///
+    /// # Errors
+    /// Errors if writing the entry fails.
+    pub fn store<F: RichField>(&self, key: &TraceCacheKey, traces: &Traces<F>) -> Result<()> {
+        let dir = self.dir.join(key.to_string());
+        let scratch = self.dir.join(format!(".{key}.{}.tmp", std::process::id()));
+        fs::create_dir_all(&scratch)?;
+        for (trace, kind) in &traces.each_ref().with_kind() {
+            let columns = trace
+                .iter()
+                .map(|column| {
+                    column
+                        .values
+                        .iter()
+                        .map(|value| value.to_canonical_u64())
+                        .collect_vec()
+                })
+                .collect_vec();
+            let bytes = rkyv::to_bytes::<_, 256, Failure>(&columns)
+                .map_err(|_| anyhow!("failed to encode the {kind:?} trace"))?;
+            let compressed = zstd::encode_all(bytes.as_slice(), zstd::DEFAULT_COMPRESSION_LEVEL)?;
+            fs::write(Self::table_path(&scratch, *kind), compressed)?;
+        }
+        match fs::remove_dir_all(&dir) {
+            Err(error) if error.kind() != ErrorKind::NotFound => return Err(error.into()),
+            _ => {}
+        }
+        fs::rename(&scratch, &dir)?;
+        Ok(())
+    }
+
+    /// The traces stored under `key`, or else the ones of `generate`, which
+    /// are then stored.
+    ///
+    /// A malformed entry is replaced, since the cache only saves time.
+    ///
+    /// # Errors
+    /// Errors if storing the generated traces fails.
+    pub fn get_or_generate<F: RichField>(
+        &self,
+        key: &TraceCacheKey,
+        generate: impl FnOnce() -> Traces<F>,
+    ) -> Result<Traces<F>> {
+        match self.load(key) {
+            Ok(Some(traces)) => return Ok(traces),
+            Ok(None) => {}
+            Err(error) => warn!("Discarding trace cache entry {key}: {error}"),
+        }
+        let traces = generate();
+        self.store(key, &traces)?;
+        Ok(traces)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::num::NonZeroU64;
+
+    use mozak_runner::code;
+    use mozak_runner::instruction::{Args, Instruction, Op};
+    use mozak_runner::state::RawTapes;
+    use plonky2::util::timing::TimingTree;
+    use tempfile::TempDir;
+
+    use super::{TraceCache, TraceCacheKey};
+    use crate::generation::generate_traces;
+    use crate::test_utils::{D, F};
+    use crate::trace_source::Execution;
+
+    #[test]
+    fn round_trip() -> anyhow::Result<()> {
+        let (program, record) = code::execute(
+            [Instruction::new(Op::ADD, Args {
+                rd: 5,
+                rs1: 6,
+                imm: 7,
+                ..Args::default()
+            })],
+            &[(0x100, 1)],
+            &[(6, 100)],
+        );
+        let tapes = RawTapes::default();
+        let key = TraceCacheKey::new::<F>(&program, &tapes, None);
+        assert_eq!(key, TraceCacheKey::new::<F>(&program, &tapes, None));
+        assert_ne!(
+            key,
+            TraceCacheKey::new::<F>(&program, &tapes, NonZeroU64::new(10))
+        );
+        let other_tapes = RawTapes {
+            public_tape: vec![1],
+            ..RawTapes::default()
+        };
+        assert_ne!(key, TraceCacheKey::new::<F>(&program, &other_tapes, None));
+
+        let root = TempDir::new()?;
+        std::fs::create_dir(root.path().join("mozak-traces-v0.0.0"))?;
+        let cache = TraceCache::open(root.path())?;
+        assert!(!root.path().join("mozak-traces-v0.0.0").exists());
+        assert!(cache.load::<F>(&key)?.is_none());
+
+        let generated = Cell::new(0);
+        let generate = || {
+            generated.set(generated.get() + 1);
+            generate_traces::<F, D>(
+                &Execution::new(&program, &record),
+                &mut TimingTree::default(),
+            )
+        };
+        let traces = cache.get_or_generate(&key, generate)?;
+        let cached = cache.get_or_generate(&key, generate)?;
+        assert_eq!(generated.get(), 1);
+        assert_eq!(cached, traces);
+        Ok(())
+    }
+}
@@ -0,0 +1,116 @@
+//! Where [`generate_traces`](crate::generation::generate_traces) takes its
+//! execution from.
+//!
+//! Trace generation only needs a program and the record of its execution.
+//! A [`TraceSource`] hands out both by reference, so that a source can keep
+//! them however suits it: [`Execution`] borrows those of a live run, and
+//! [`Synthetic`] executes a generated program of a requested length, for
+//! benchmarks.
+//!
+//! The record itself stays in memory, see
+//! [`ExecutionRecord`](mozak_runner::vm::ExecutionRecord): a source backed by
+//! a recorded file implements this trait by loading the record, and the
+//! generators read the same rows either way.
+
+use mozak_runner::code;
+use mozak_runner::elf::Program;
+use mozak_runner::instruction::{Args, Instruction, Op};
+use mozak_runner::vm::ExecutionRecord;
+use plonky2::field::goldilocks_field::GoldilocksField;
+use plonky2::hash::hash_types::RichField;
+
+/// A program together with the record of its execution.
+pub trait TraceSource<F: RichField> {
+    fn program(&self) -> &Program;
+
+    fn record(&self) -> &ExecutionRecord<F>;
+}
+
+/// An execution that the caller ran, eg with
+/// [`step`](mozak_runner::vm::step).
+#[derive(Clone, Copy, Debug)]
+pub struct Execution<'a, F: RichField> {
+    pub program: &'a Program,
+    pub record: &'a ExecutionRecord<F>,
+}
+
+impl<'a, F: RichField> Execution<'a, F> {
+    #[must_use]
+    pub fn new(program: &'a Program, record: &'a ExecutionRecord<F>) -> Self {
+        Self { program, record }
+    }
+}
+
+impl<F: RichField> TraceSource<F> for Execution<'_, F> {
+    fn program(&self) -> &Program { self.program }
+
+    fn record(&self) -> &ExecutionRecord<F> { self.record }
+}
+
+/// A generated program, executed on construction.
+#[derive(Debug)]
+pub struct Synthetic {
+    program: Program,
+    record: ExecutionRecord<GoldilocksField>,
+}
+
+impl Synthetic {
+    /// A loop that counts a register down from `iterations` to zero, with an
+    /// `ADD` and a `BLT` per iteration.
+    #[must_use]
+    pub fn countdown(iterations: u32) -> Self {
+        let instructions = [
+            Instruction {
+                op: Op::ADD,
+                args: Args {
+                    rd: 1,
+                    rs1: 1,
+                    imm: 1_u32.wrapping_neg(),
+                    ..Args::default()
+                },
+            },
+            Instruction {
+                op: Op::BLT,
+                args: Args {
+                    rs1: 0,
+                    rs2: 1,
+                    imm: 0,
+                    ..Args::default()
+                },
+            },
+        ];
+        let (program, record) = code::execute(instructions, &[], &[(1, iterations)]);
+        Self { program, record }
+    }
+}
+
+impl TraceSource<GoldilocksField> for Synthetic {
+    fn program(&self) -> &Program { &self.program }
+
+    fn record(&self) -> &ExecutionRecord<GoldilocksField> { &self.record }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::util::timing::TimingTree;
+
+    use super::{Execution, Synthetic, TraceSource};
+    use crate::generation::generate_traces;
+    use crate::test_utils::{D, F};
+
+    #[test]
+    fn countdown_runs_two_instructions_per_iteration() {
+        let source = Synthetic::countdown(10);
+        // Both instructions of each iteration, and the two that halt.
+        assert_eq!(source.record().executed.len(), 2 * 10 + 2);
+    }
+
+    #[test]
+    fn sources_of_the_same_execution_generate_the_same_traces() {
+        let source = Synthetic::countdown(10);
+        let execution = Execution::new(source.program(), source.record());
+        let traces = generate_traces::<F, D>(&source, &mut TimingTree::default());
+        let replayed = generate_traces::<F, D>(&execution, &mut TimingTree::default());
+        assert_eq!(traces, replayed);
+    }
+}
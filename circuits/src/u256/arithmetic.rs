@@ -0,0 +1,41 @@
+//! The identities of a row, in 16-bit limbs, see [`crate::limbs`].
+//!
+//! A row shows `a + b = q M + r` or `a b = q M + r`, for a modulus `M` that is
+//! either `m` or `2^256`, and the `result` `r`. Both sides stay below `2^512`,
+//! so a quotient of 64 bytes always exists. Multiplying out gives 48 limbs of
+//! less than `2^37` in absolute value, and the carries between them are range
+//! checked.
+
+use super::columns::U256;
+use crate::limbs::{add, limbs, mul, scale, sub, Limb};
+
+/// Limbs of the modulus, with `2^256` as a single extra top limb.
+pub fn modulus<E: Limb>(row: &U256<E>) -> Vec<E> {
+    let mut modulus = scale(&limbs(&row.m), row.is_mulmod);
+    modulus.push(row.is_wrapping());
+    modulus
+}
+
+/// Limbs of the sum or product, minus the quotient times the modulus, minus
+/// the result. On padding rows all of them are zero.
+pub fn identity<E: Limb>(row: &U256<E>) -> Vec<E> {
+    let (a, b) = (limbs(&row.a), limbs(&row.b));
+    let unreduced = add(
+        &scale(&add(&a, &b), row.is_add),
+        &scale(&mul(&a, &b), row.is_mul + row.is_mulmod),
+    );
+    sub(
+        &sub(&unreduced, &mul(&limbs(&row.quotient), &modulus(row))),
+        &limbs(&row.result),
+    )
+}
+
+/// Limbs of `result + slack + 1 - m` when multiplying modulo `m`, and zero
+/// otherwise.
+pub fn slack<E: Limb>(row: &U256<E>) -> Vec<E> {
+    let sum = add(&limbs(&row.result), &limbs(&row.slack));
+    scale(
+        &sub(&add(&sum, &[E::from(1)]), &limbs(&row.m)),
+        row.is_mulmod,
+    )
+}
@@ -0,0 +1,146 @@
+use core::ops::Add;
+
+use itertools::{chain, izip};
+pub use mozak_sdk::core::constants::U256_BYTES;
+use mozak_sdk::core::reg_abi::{REG_A1, REG_A2, REG_A3};
+
+use crate::columns_view::{make_col_map, ColumnsView, NumberOfColumns};
+use crate::cross_table_lookup::ColumnWithTypedInput;
+use crate::linear_combination::Column;
+use crate::memory::columns::MemoryCtl;
+use crate::rangecheck::columns::RangeCheckCtl;
+use crate::register::RegisterCtl;
+use crate::stark::mozak_stark::{TableWithTypedOutput, U256Table};
+
+/// Operands are multiplied as polynomials in 16-bit limbs.
+pub const U256_LIMBS: usize = U256_BYTES / 2;
+/// Quotients of products by a modulus of one are as wide as the product.
+pub const QUOTIENT_BYTES: usize = 2 * U256_BYTES;
+/// One carry between each pair of adjacent limbs of the quotient times the
+/// modulus, which has an extra limb for `2^256`.
+pub const CARRIES: usize = QUOTIENT_BYTES / 2 + U256_LIMBS - 1;
+/// Carries can be negative, so we range check them with this offset.
+pub const CARRY_OFFSET: i64 = 1 << 31;
+
+make_col_map!(U256);
+/// A row adds or multiplies `a` and `b`, reduces the sum or product modulo
+/// `2^256` or `m`, and stores the `result` over `a`.
+#[repr(C)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
+pub struct U256<T> {
+    pub is_add: T,
+    pub is_mul: T,
+    pub is_mulmod: T,
+    pub clk: T,
+    pub a_addr: T,
+    pub b_addr: T,
+    pub m_addr: T,
+    pub a: [T; U256_BYTES],
+    pub b: [T; U256_BYTES],
+    /// Zero unless multiplying modulo `m`.
+    pub m: [T; U256_BYTES],
+    pub result: [T; U256_BYTES],
+    /// The sum or product equals `quotient` times the modulus plus `result`,
+    /// limb by limb up to `carries`.
+    pub quotient: [T; QUOTIENT_BYTES],
+    pub carries: [T; CARRIES],
+    /// When multiplying modulo `m`, the `result` adds up with `slack` to
+    /// `m - 1`, so it is reduced.
+    pub slack: [T; U256_BYTES],
+    pub slack_carries: [T; U256_LIMBS - 1],
+}
+
+pub const NUM_U256_COLS: usize = U256::<()>::NUMBER_OF_COLUMNS;
+
+impl<T: Copy + Add<Output = T>> U256<T> {
+    pub fn is_executed(&self) -> T { self.is_add + self.is_mul + self.is_mulmod }
+
+    /// Whether the modulus is `2^256`.
+    pub fn is_wrapping(&self) -> T { self.is_add + self.is_mul }
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
+pub struct U256Ctl<T> {
+    pub clk: T,
+    pub is_mul: T,
+    pub is_mulmod: T,
+}
+
+#[must_use]
+pub fn lookup_for_cpu() -> TableWithTypedOutput<U256Ctl<Column>> {
+    U256Table::new(
+        U256Ctl {
+            clk: COL_MAP.clk,
+            is_mul: COL_MAP.is_mul,
+            is_mulmod: COL_MAP.is_mulmod,
+        },
+        COL_MAP.is_executed(),
+    )
+}
+
+#[must_use]
+pub fn register_looking() -> Vec<TableWithTypedOutput<RegisterCtl<Column>>> {
+    let is_read = ColumnWithTypedInput::constant(1);
+    [
+        (COL_MAP.a_addr, REG_A1, COL_MAP.is_executed()),
+        (COL_MAP.b_addr, REG_A2, COL_MAP.is_executed()),
+        (COL_MAP.m_addr, REG_A3, COL_MAP.is_mulmod),
+    ]
+    .into_iter()
+    .map(|(value, reg, filter)| {
+        U256Table::new(
+            RegisterCtl {
+                clk: COL_MAP.clk,
+                op: is_read,
+                value,
+                addr: ColumnWithTypedInput::constant(reg.into()),
+            },
+            filter,
+        )
+    })
+    .collect()
+}
+
+/// Loads of `a`, `b` and `m`, and the store of the result over `a`. Loads
+/// come before stores in the same cycle, so they see the old `a`.
+pub fn lookup_for_memory() -> impl Iterator<Item = TableWithTypedOutput<MemoryCtl<Column>>> {
+    let access = |addr, value: [_; U256_BYTES], is_store, filter| {
+        izip!(0.., value).map(move |(i, value)| {
+            U256Table::new(
+                MemoryCtl {
+                    clk: COL_MAP.clk,
+                    is_store: ColumnWithTypedInput::constant(is_store),
+                    is_load: ColumnWithTypedInput::constant(1 - is_store),
+                    value,
+                    addr: addr + i,
+                },
+                filter,
+            )
+        })
+    };
+    chain!(
+        access(COL_MAP.a_addr, COL_MAP.a, 0, COL_MAP.is_executed()),
+        access(COL_MAP.b_addr, COL_MAP.b, 0, COL_MAP.is_executed()),
+        access(COL_MAP.m_addr, COL_MAP.m, 0, COL_MAP.is_mulmod),
+        access(COL_MAP.a_addr, COL_MAP.result, 1, COL_MAP.is_executed()),
+    )
+}
+
+/// The operands and the result are range checked by the memory table, so only
+/// the witnesses are left.
+#[must_use]
+pub fn rangecheck_u8_looking() -> Vec<TableWithTypedOutput<RangeCheckCtl<Column>>> {
+    chain!(COL_MAP.quotient, COL_MAP.slack)
+        .map(|byte| U256Table::new(RangeCheckCtl(byte), COL_MAP.is_executed()))
+        .collect()
+}
+
+#[must_use]
+pub fn rangecheck_looking() -> Vec<TableWithTypedOutput<RangeCheckCtl<Column>>> {
+    COL_MAP
+        .carries
+        .into_iter()
+        .map(|carry| U256Table::new(RangeCheckCtl(carry + CARRY_OFFSET), COL_MAP.is_executed()))
+        .collect()
+}
@@ -0,0 +1,52 @@
+use mozak_runner::u256::{modulus, to_biguint, unreduced, Entry, Operation};
+use mozak_runner::vm::Row;
+use num::bigint::BigUint;
+use num::Zero;
+use plonky2::hash::hash_types::RichField;
+
+use super::arithmetic::{identity, slack};
+use super::columns::{U256, U256_BYTES};
+use crate::limbs::{carries, to_bytes};
+use crate::utils::pad_trace_with_default;
+
+fn generate_row<F: RichField>(clk: u64, entry: &Entry) -> U256<F> {
+    let bytes = |value: [u8; U256_BYTES]| value.map(F::from_canonical_u8);
+    let is_mulmod = entry.operation == Operation::MulMod;
+    let quotient =
+        unreduced(entry.operation, &entry.a, &entry.b) / modulus(entry.operation, &entry.m);
+    let slack_value = if is_mulmod {
+        to_biguint(&entry.m) - 1_u32 - to_biguint(&entry.result)
+    } else {
+        BigUint::zero()
+    };
+    let mut row = U256 {
+        is_add: F::from_bool(entry.operation == Operation::Add),
+        is_mul: F::from_bool(entry.operation == Operation::Mul),
+        is_mulmod: F::from_bool(is_mulmod),
+        clk: F::from_canonical_u64(clk),
+        a_addr: F::from_canonical_u32(entry.a_addr),
+        b_addr: F::from_canonical_u32(entry.b_addr),
+        m_addr: F::from_canonical_u32(entry.m_addr),
+        a: bytes(entry.a),
+        b: bytes(entry.b),
+        m: bytes(entry.m),
+        result: bytes(entry.result),
+        quotient: to_bytes(&quotient),
+        slack: to_bytes(&slack_value),
+        ..Default::default()
+    };
+    let limbs = row.map(|f| i128::from(f.to_canonical_u64()));
+    row.carries = carries(&identity(&limbs)).try_into().unwrap();
+    row.slack_carries = carries(&slack(&limbs)).try_into().unwrap();
+    row
+}
+
+#[must_use]
+pub fn generate_u256_trace<F: RichField>(step_rows: &[Row<F>]) -> Vec<U256<F>> {
+    pad_trace_with_default(
+        step_rows
+            .iter()
+            .filter_map(|row| Some(generate_row(row.state.clk, row.aux.u256.as_ref()?)))
+            .collect(),
+    )
+}
@@ -0,0 +1,4 @@
+pub mod arithmetic;
+pub mod columns;
+pub mod generation;
+pub mod stark;
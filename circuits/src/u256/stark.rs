@@ -0,0 +1,183 @@
+use std::marker::PhantomData;
+
+use expr::{Expr, ExprBuilder, StarkFrameTyped};
+use mozak_circuits_derive::StarkNameDisplay;
+use plonky2::field::extension::{Extendable, FieldExtension};
+use plonky2::field::packed::PackedField;
+use plonky2::hash::hash_types::RichField;
+use plonky2::iop::ext_target::ExtensionTarget;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use starky::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsumer};
+use starky::evaluation_frame::StarkFrame;
+use starky::stark::Stark;
+
+use super::arithmetic::{identity, slack};
+use super::columns::{NUM_U256_COLS, U256};
+use crate::columns_view::HasNamedColumns;
+use crate::expr::{build_ext, build_packed, ConstraintBuilder};
+use crate::limbs::carry_constraints;
+use crate::unstark::NoColumns;
+
+#[derive(Copy, Clone, Default, StarkNameDisplay)]
+#[allow(clippy::module_name_repetitions)]
+pub struct U256Stark<F, const D: usize> {
+    pub _f: PhantomData<F>,
+}
+
+impl<F, const D: usize> HasNamedColumns for U256Stark<F, D> {
+    type Columns = U256<F>;
+}
+
+const COLUMNS: usize = NUM_U256_COLS;
+const PUBLIC_INPUTS: usize = 0;
+
+fn generate_constraints<'a, T: Copy>(
+    vars: &StarkFrameTyped<U256<Expr<'a, T>>, NoColumns<Expr<'a, T>>>,
+) -> ConstraintBuilder<Expr<'a, T>> {
+    let lv = vars.local_values;
+    let mut constraints = ConstraintBuilder::default();
+
+    constraints.always(lv.is_add.is_binary());
+    constraints.always(lv.is_mul.is_binary());
+    constraints.always(lv.is_mulmod.is_binary());
+    constraints.always(lv.is_executed().is_binary());
+
+    for constraint in carry_constraints(&identity(&lv), &lv.carries) {
+        constraints.always(constraint);
+    }
+
+    // The result adds up with a non-negative slack to `m - 1`, so it is
+    // reduced, and `m` is not zero. Limbs of the sum are below `2^17`, so the
+    // carries are bits.
+    for constraint in carry_constraints(&slack(&lv), &lv.slack_carries) {
+        constraints.always(constraint);
+    }
+    for carry in lv.slack_carries {
+        constraints.always(carry.is_binary());
+    }
+
+    constraints
+}
+
+impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for U256Stark<F, D> {
+    type EvaluationFrame<FE, P, const D2: usize> = StarkFrame<P, P::Scalar, COLUMNS, PUBLIC_INPUTS>
+    where
+        FE: FieldExtension<D2, BaseField = F>,
+        P: PackedField<Scalar = FE>;
+    type EvaluationFrameTarget =
+        StarkFrame<ExtensionTarget<D>, ExtensionTarget<D>, COLUMNS, PUBLIC_INPUTS>;
+
+    fn eval_packed_generic<FE, P, const D2: usize>(
+        &self,
+        vars: &Self::EvaluationFrame<FE, P, D2>,
+        consumer: &mut ConstraintConsumer<P>,
+    ) where
+        FE: FieldExtension<D2, BaseField = F>,
+        P: PackedField<Scalar = FE>, {
+        let eb = ExprBuilder::default();
+        let constraints = generate_constraints(&eb.to_typed_starkframe(vars));
+        build_packed(constraints, consumer);
+    }
+
+    fn eval_ext_circuit(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        vars: &Self::EvaluationFrameTarget,
+        consumer: &mut RecursiveConstraintConsumer<F, D>,
+    ) {
+        let eb = ExprBuilder::default();
+        let constraints = generate_constraints(&eb.to_typed_starkframe(vars));
+        build_ext(constraints, builder, consumer);
+    }
+
+    fn constraint_degree(&self) -> usize { 3 }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+    use itertools::Itertools;
+    use num::BigUint;
+    use plonky2::field::types::{Field, PrimeField64};
+    use plonky2::plonk::config::{GenericConfig, Poseidon2GoldilocksConfig};
+    use plonky2::util::timing::TimingTree;
+    use starky::prover::prove;
+    use starky::stark_testing::{test_stark_circuit_constraints, test_stark_low_degree};
+    use starky::verifier::verify_stark_proof;
+
+    use super::U256Stark;
+    use crate::generation::debug_single_trace;
+    use crate::limbs::{carries, to_bytes};
+    use crate::stark::mozak_stark::MozakStark;
+    use crate::stark::utils::trace_rows_to_poly_values;
+    use crate::test_utils::{fast_test_config, u256_test, ProveAndVerify};
+    use crate::u256::arithmetic::identity;
+    use crate::u256::generation::generate_u256_trace;
+
+    const D: usize = 2;
+    type C = Poseidon2GoldilocksConfig;
+    type F = <C as GenericConfig<D>>::F;
+    type S = U256Stark<F, D>;
+
+    #[test]
+    fn prove_add_mul_and_mulmod() -> Result<()> {
+        let (_program, record) = u256_test();
+        let config = fast_test_config();
+        let stark = S::default();
+        let trace = trace_rows_to_poly_values(generate_u256_trace(&record.executed));
+        let proof = prove::<F, C, S, D>(stark, &config, trace, &[], &mut TimingTree::default())?;
+        verify_stark_proof(stark, proof, &config)
+    }
+
+    #[test]
+    #[should_panic(expected = "debug_api_has_constraint_failed")]
+    fn wrong_result_fails() {
+        let (_program, record) = u256_test();
+        let mut rows = generate_u256_trace(&record.executed);
+        rows[0].result[0] += F::ONE;
+        debug_single_trace::<F, D, _>(&S::default(), &trace_rows_to_poly_values(rows), &[]);
+    }
+
+    /// Adding `m` to the result of a modular multiplication, and taking one
+    /// off the quotient, keeps the identity, but not the reduction.
+    #[test]
+    #[should_panic(expected = "debug_api_has_constraint_failed")]
+    fn unreduced_mulmod_fails() {
+        let (_program, record) = u256_test();
+        let mut rows = generate_u256_trace(&record.executed);
+        let row = rows.iter_mut().find(|row| row.is_mulmod.is_one()).unwrap();
+        let value = |bytes: &[F]| {
+            BigUint::from_bytes_le(
+                &bytes
+                    .iter()
+                    .map(|byte| u8::try_from(byte.to_canonical_u64()).unwrap())
+                    .collect_vec(),
+            )
+        };
+        row.result = to_bytes(&(value(&row.result) + value(&row.m)));
+        row.quotient = to_bytes(&(value(&row.quotient) - 1_u32));
+        row.carries = carries(&identity(&row.map(|f| i128::from(f.to_canonical_u64()))))
+            .try_into()
+            .unwrap();
+        debug_single_trace::<F, D, _>(&S::default(), &trace_rows_to_poly_values(rows), &[]);
+    }
+
+    #[test]
+    fn prove_add_mul_and_mulmod_with_mozak_stark() {
+        let (program, record) = u256_test();
+        MozakStark::prove_and_verify(&program, &record).unwrap();
+    }
+
+    #[test]
+    fn test_degree() -> Result<()> {
+        let stark = S::default();
+        test_stark_low_degree(stark)
+    }
+
+    #[test]
+    fn test_circuit() -> Result<()> {
+        let stark = S::default();
+        test_stark_circuit_constraints::<F, C, S, D>(stark)?;
+        Ok(())
+    }
+}
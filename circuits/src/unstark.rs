@@ -10,7 +10,7 @@ use starky::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsume
 use starky::evaluation_frame::StarkFrame;
 use starky::stark::Stark;
 
-use crate::columns_view::{columns_view_impl, HasNamedColumns, NumberOfColumns};
+use crate::columns_view::{ColumnsView, HasNamedColumns, NumberOfColumns};
 
 /// Template for a STARK with zero internal constraints. Use this if the STARK
 /// itself does not need any built-in constraints, but rely on cross table
@@ -66,8 +66,7 @@ impl<
 }
 
 #[repr(C)]
-#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
 pub struct NoColumns<T> {
     _phantom: PhantomData<T>,
 }
-columns_view_impl!(NoColumns);
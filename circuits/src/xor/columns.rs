@@ -1,9 +1,9 @@
-use crate::columns_view::{columns_view_impl, make_col_map};
+use crate::columns_view::{make_col_map, ColumnsView};
 use crate::linear_combination::Column;
 use crate::stark::mozak_stark::{TableWithTypedOutput, XorTable};
 
 #[repr(C)]
-#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
 pub struct XorColumnsView<T> {
     /// This column indicates if the row has a corresponding execution row
     /// in the CPU table or if it is a dummy row (which is used to fill the
@@ -12,23 +12,40 @@ pub struct XorColumnsView<T> {
     /// This column contains the values in the corresponding row from the CPU
     /// table.
     pub execution: XorView<T>,
-    /// This column contains the decomposed limbs of the execution value.
-    pub limbs: XorView<[T; 32]>,
+    /// This column contains the byte limbs of the execution value, least
+    /// significant first.
+    pub limbs: XorView<[T; 4]>,
 }
-columns_view_impl!(XorColumnsView);
 make_col_map!(XorColumnsView);
 
 #[repr(C)]
-#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
 pub struct XorView<T> {
     pub a: T,
     pub b: T,
     pub out: T,
 }
-columns_view_impl!(XorView);
 
 /// Lookup between CPU table and Xor stark table.
 #[must_use]
 pub fn lookup_for_cpu() -> TableWithTypedOutput<XorView<Column>> {
     XorTable::new(COL_MAP.execution, COL_MAP.is_execution_row)
 }
+
+/// Lookups of each byte limb into the `XorByte` table, which checks that the
+/// limbs are bytes, and that the output limb is the Xor of the input limbs.
+#[must_use]
+pub fn lookup_for_xor_byte() -> Vec<TableWithTypedOutput<XorView<Column>>> {
+    (0..4)
+        .map(|i| {
+            XorTable::new(
+                XorView {
+                    a: COL_MAP.limbs.a[i],
+                    b: COL_MAP.limbs.b[i],
+                    out: COL_MAP.limbs.out[i],
+                },
+                COL_MAP.is_execution_row,
+            )
+        })
+        .collect()
+}
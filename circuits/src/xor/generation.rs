@@ -1,4 +1,3 @@
-use bitfield::Bit;
 use itertools::Itertools;
 use plonky2::hash::hash_types::RichField;
 
@@ -15,12 +14,11 @@ fn filter_xor_trace<F: RichField>(
         .map(|row| row.xor)
 }
 
-fn to_bits<F: RichField>(val: F) -> [F; u32::BITS as usize] {
-    (0_usize..32)
-        .map(|j| F::from_bool(val.to_canonical_u64().bit(j)))
-        .collect_vec()
-        .try_into()
+fn to_bytes<F: RichField>(val: F) -> [F; 4] {
+    u32::try_from(val.to_canonical_u64())
         .unwrap()
+        .to_le_bytes()
+        .map(F::from_canonical_u8)
 }
 
 #[must_use]
@@ -30,7 +28,7 @@ pub fn generate_xor_trace<F: RichField>(cpu_trace: &[CpuState<F>]) -> Vec<XorCol
             .map(|execution| XorColumnsView {
                 is_execution_row: F::ONE,
                 execution,
-                limbs: execution.map(to_bits),
+                limbs: execution.map(to_bytes),
             })
             .collect_vec()
     })
@@ -3,6 +3,8 @@
 //! Using this XOR table, we can then construct the other
 //! bitwise operations, such as `AND` and `OR`.
 //! It is used from the CPU STARK with the Cross Table Lookup (CTL) technique.
+//! It splits its operands into bytes, and looks each of them up in the
+//! [`XorByte`](crate::xor_byte) table.
 
 pub mod columns;
 pub mod generation;
@@ -1,7 +1,7 @@
 use std::marker::PhantomData;
 
 use expr::{Expr, ExprBuilder, StarkFrameTyped};
-use itertools::{chain, izip};
+use itertools::izip;
 use mozak_circuits_derive::StarkNameDisplay;
 use plonky2::field::extension::{Extendable, FieldExtension};
 use plonky2::field::packed::PackedField;
@@ -36,23 +36,11 @@ fn generate_constraints<'a, T: Copy>(
     let lv = vars.local_values;
     let mut constraints = ConstraintBuilder::default();
 
-    // We first convert both input and output to bit representation
-    // We then work with the bit representations to check the Xor result.
-
-    // Check: bit representation of inputs and output contains either 0 or 1.
-    for bit_value in chain!(lv.limbs.a, lv.limbs.b, lv.limbs.out) {
-        constraints.always(bit_value.is_binary());
-    }
-
-    // Check: bit representation of inputs and output were generated correctly.
+    // Check: the byte limbs of inputs and output were generated correctly.
+    // The lookups into the `XorByte` table check that each limb is a byte,
+    // and that the output limbs are the Xor of the input limbs.
     for (opx, opx_limbs) in izip![lv.execution, lv.limbs] {
-        constraints.always(Expr::reduce_with_powers(opx_limbs, 2) - opx);
-    }
-
-    // Check: output bit representation is Xor of input a and b bit representations
-    for (a, b, out) in izip!(lv.limbs.a, lv.limbs.b, lv.limbs.out) {
-        // Xor behaves like addition in binary field, i.e. addition with wrap-around:
-        constraints.always((a + b - out) * (a + b - 2 - out));
+        constraints.always(Expr::reduce_with_powers(opx_limbs, 256) - opx);
     }
 
     constraints
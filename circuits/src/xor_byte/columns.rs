@@ -0,0 +1,32 @@
+use crate::columns_view::{make_col_map, ColumnsView};
+use crate::linear_combination::Column;
+use crate::linear_combination_typed::ColumnWithTypedInput;
+use crate::stark::mozak_stark::{TableWithTypedOutput, XorByteTable};
+use crate::xor::columns::XorView;
+
+#[repr(C)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, ColumnsView)]
+pub struct XorByte<T> {
+    /// The bits of the first input byte, least significant first.
+    pub a_bits: [T; 8],
+    /// The bits of the second input byte, least significant first.
+    pub b_bits: [T; 8],
+    /// The `XOR` of both input bytes.
+    pub out: T,
+    /// How often the `XOR` table looks up this row.
+    pub multiplicity: T,
+}
+make_col_map!(XorByte);
+
+/// Lookup between the `XOR` table and this table.
+#[must_use]
+pub fn lookup() -> TableWithTypedOutput<XorView<Column>> {
+    XorByteTable::new(
+        XorView {
+            a: ColumnWithTypedInput::reduce_with_powers(COL_MAP.a_bits, 2),
+            b: ColumnWithTypedInput::reduce_with_powers(COL_MAP.b_bits, 2),
+            out: COL_MAP.out,
+        },
+        COL_MAP.multiplicity,
+    )
+}
@@ -0,0 +1,35 @@
+use bitfield::Bit;
+use itertools::{izip, Itertools};
+use plonky2::hash::hash_types::RichField;
+
+use crate::utils::pad_trace_with_default;
+use crate::xor::columns::XorColumnsView;
+use crate::xor_byte::columns::XorByte;
+
+fn to_bits<F: RichField>(byte: u8) -> [F; 8] { std::array::from_fn(|i| F::from_bool(byte.bit(i))) }
+
+/// Generates a row for each byte pair that `xor_trace` looks up, with the
+/// number of lookups as multiplicity.
+#[must_use]
+pub fn generate_xor_byte_trace<F: RichField>(xor_trace: &[XorColumnsView<F>]) -> Vec<XorByte<F>> {
+    pad_trace_with_default(
+        xor_trace
+            .iter()
+            .filter(|row| row.is_execution_row.is_one())
+            .flat_map(|row| izip!(row.limbs.a, row.limbs.b))
+            .map(|(a, b)| {
+                let byte = |x: F| u8::try_from(x.to_canonical_u64()).unwrap();
+                (byte(a), byte(b))
+            })
+            .counts()
+            .into_iter()
+            .sorted()
+            .map(|((a, b), multiplicity)| XorByte {
+                a_bits: to_bits(a),
+                b_bits: to_bits(b),
+                out: F::from_canonical_u8(a ^ b),
+                multiplicity: F::from_canonical_usize(multiplicity),
+            })
+            .collect(),
+    )
+}
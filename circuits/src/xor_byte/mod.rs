@@ -0,0 +1,12 @@
+//! This module contains the **`XorByte` STARK Table**.
+//! Each row holds the `XOR` of two bytes, checked via their bits. The `XOR`
+//! table looks up each byte limb of its operands here, so that it only needs
+//! four limbs per operand instead of 32 bits.
+//!
+//! Only the byte pairs that the `XOR` table uses get a row, with the number
+//! of uses as multiplicity. Every row is checked on its own, so the table
+//! does not have to enumerate all 256 by 256 pairs.
+
+pub mod columns;
+pub mod generation;
+pub mod stark;
@@ -1,6 +1,7 @@
 use std::marker::PhantomData;
 
 use expr::{Expr, ExprBuilder, StarkFrameTyped};
+use itertools::{chain, izip};
 use mozak_circuits_derive::StarkNameDisplay;
 use plonky2::field::extension::{Extendable, FieldExtension};
 use plonky2::field::packed::PackedField;
@@ -11,36 +12,43 @@ use starky::constraint_consumer::{ConstraintConsumer, RecursiveConstraintConsume
 use starky::evaluation_frame::StarkFrame;
 use starky::stark::Stark;
 
-use super::columns::MemoryZeroInit;
+use super::columns::XorByte;
 use crate::columns_view::{HasNamedColumns, NumberOfColumns};
 use crate::expr::{build_ext, build_packed, ConstraintBuilder};
 use crate::unstark::NoColumns;
 
 #[derive(Clone, Copy, Default, StarkNameDisplay)]
 #[allow(clippy::module_name_repetitions)]
-pub struct MemoryZeroInitStark<F, const D: usize> {
+pub struct XorByteStark<F, const D: usize> {
     pub _f: PhantomData<F>,
 }
 
-impl<F, const D: usize> HasNamedColumns for MemoryZeroInitStark<F, D> {
-    type Columns = MemoryZeroInit<F>;
+impl<F, const D: usize> HasNamedColumns for XorByteStark<F, D> {
+    type Columns = XorByte<F>;
 }
 
-const COLUMNS: usize = MemoryZeroInit::<()>::NUMBER_OF_COLUMNS;
+const COLUMNS: usize = XorByte::<()>::NUMBER_OF_COLUMNS;
 const PUBLIC_INPUTS: usize = 0;
 
 fn generate_constraints<'a, T: Copy>(
-    vars: &StarkFrameTyped<MemoryZeroInit<Expr<'a, T>>, NoColumns<Expr<'a, T>>>,
+    vars: &StarkFrameTyped<XorByte<Expr<'a, T>>, NoColumns<Expr<'a, T>>>,
 ) -> ConstraintBuilder<Expr<'a, T>> {
     let lv = vars.local_values;
     let mut constraints = ConstraintBuilder::default();
 
-    constraints.always(lv.filter.is_binary());
+    // Check: the inputs are bytes.
+    for bit in chain!(lv.a_bits, lv.b_bits) {
+        constraints.always(bit.is_binary());
+    }
+
+    // Check: the output is the Xor of the inputs, bit by bit.
+    let out_bits = izip!(lv.a_bits, lv.b_bits).map(|(a, b)| a + b - 2 * a * b);
+    constraints.always(Expr::reduce_with_powers(out_bits, 2) - lv.out);
 
     constraints
 }
 
-impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for MemoryZeroInitStark<F, D> {
+impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for XorByteStark<F, D> {
     type EvaluationFrame<FE, P, const D2: usize> = StarkFrame<P, P::Scalar, COLUMNS, PUBLIC_INPUTS>
 
     where
@@ -56,42 +64,39 @@ impl<F: RichField + Extendable<D>, const D: usize> Stark<F, D> for MemoryZeroIni
     ) where
         FE: FieldExtension<D2, BaseField = F>,
         P: PackedField<Scalar = FE>, {
-        let eb = ExprBuilder::default();
-        let constraints = generate_constraints(&eb.to_typed_starkframe(vars));
+        let expr_builder = ExprBuilder::default();
+        let constraints = generate_constraints(&expr_builder.to_typed_starkframe(vars));
         build_packed(constraints, consumer);
     }
 
+    fn constraint_degree(&self) -> usize { 3 }
+
     fn eval_ext_circuit(
         &self,
-        builder: &mut CircuitBuilder<F, D>,
+        circuit_builder: &mut CircuitBuilder<F, D>,
         vars: &Self::EvaluationFrameTarget,
         consumer: &mut RecursiveConstraintConsumer<F, D>,
     ) {
-        let eb = ExprBuilder::default();
-        let constraints = generate_constraints(&eb.to_typed_starkframe(vars));
-        build_ext(constraints, builder, consumer);
+        let expr_builder = ExprBuilder::default();
+        let constraints = generate_constraints(&expr_builder.to_typed_starkframe(vars));
+        build_ext(constraints, circuit_builder, consumer);
     }
-
-    fn constraint_degree(&self) -> usize { 3 }
 }
 
 #[cfg(test)]
 mod tests {
-    use plonky2::plonk::config::{GenericConfig, Poseidon2GoldilocksConfig};
-    use starky::stark_testing::test_stark_circuit_constraints;
+    use starky::stark_testing::{test_stark_circuit_constraints, test_stark_low_degree};
 
-    use super::*;
+    use super::XorByteStark;
+    use crate::test_utils::{C, D, F};
 
-    const D: usize = 2;
-    type C = Poseidon2GoldilocksConfig;
-    type F = <C as GenericConfig<D>>::F;
-    type S = MemoryZeroInitStark<F, D>;
+    type S = XorByteStark<F, D>;
 
     #[test]
-    fn test_circuit() -> anyhow::Result<()> {
-        let stark = S::default();
-        test_stark_circuit_constraints::<F, C, S, D>(stark)?;
+    fn test_degree() -> anyhow::Result<()> { test_stark_low_degree(S::default()) }
 
-        Ok(())
+    #[test]
+    fn test_circuit() -> anyhow::Result<()> {
+        test_stark_circuit_constraints::<F, C, S, D>(S::default())
     }
 }
@@ -0,0 +1,72 @@
+use anyhow::Result;
+use mozak_circuits::test_utils::prove_and_verify_mozak_stark;
+use mozak_examples::ECDSA_ELF;
+use mozak_runner::elf::Program;
+use mozak_runner::instruction::Op;
+use mozak_runner::state::{RawTapes, State};
+use mozak_runner::vm::{step, ExecutionRecord};
+use mozak_sdk::core::ecall;
+use mozak_sdk::core::reg_abi::{REG_A0, REG_A1};
+use mozak_sdk::crypto::{public_key, sign, PublicKey, Signature};
+use plonky2::field::goldilocks_field::GoldilocksField;
+use starky::config::StarkConfig;
+
+const SECRET_KEY: [u8; 32] = [7; 32];
+const MESSAGE: &[u8] = b"transfer 10 tokens to wallet 42";
+
+/// Runs the ECDSA guest program on a public tape laid out as it expects.
+fn run(
+    pubkey: &PublicKey,
+    msg: &[u8],
+    sig: &Signature,
+) -> Result<(Program, ExecutionRecord<GoldilocksField>)> {
+    let _ = env_logger::try_init();
+    let program = Program::vanilla_load_elf(ECDSA_ELF)?;
+    let public_tape = [
+        &pubkey.0 .0[..],
+        &sig.r,
+        &sig.s,
+        &u32::try_from(msg.len())?.to_le_bytes(),
+        msg,
+    ]
+    .concat();
+    let state = State::new(program.clone(), RawTapes {
+        public_tape,
+        ..RawTapes::default()
+    });
+    let record = step(&program, state)?;
+    Ok((program, record))
+}
+
+/// The conditions the guest passed to its provable assertions.
+fn prove_asserts(record: &ExecutionRecord<GoldilocksField>) -> Vec<bool> {
+    record
+        .executed
+        .iter()
+        .filter(|row| {
+            row.instruction.op == Op::ECALL
+                && row.state.get_register_value(REG_A0) == ecall::PROVE_ASSERT
+        })
+        .map(|row| row.state.get_register_value(REG_A1) == 1)
+        .collect()
+}
+
+#[test]
+fn prove_valid_signature() -> Result<()> {
+    let pubkey = public_key(&SECRET_KEY);
+    let sig = sign(&SECRET_KEY, MESSAGE);
+    let (program, record) = run(&pubkey, MESSAGE, &sig)?;
+    assert_eq!(prove_asserts(&record), vec![true]);
+    prove_and_verify_mozak_stark(&program, &record, &StarkConfig::standard_fast_config())
+}
+
+/// The guest still runs to completion on a forged signature, but its
+/// provable assertion fails, so the execution cannot be proven.
+#[test]
+fn forged_signature_fails_assertion() -> Result<()> {
+    let pubkey = public_key(&SECRET_KEY);
+    let sig = sign(&SECRET_KEY, b"transfer 1000 tokens to wallet 42");
+    let (_program, record) = run(&pubkey, MESSAGE, &sig)?;
+    assert_eq!(prove_asserts(&record), vec![false]);
+    Ok(())
+}
@@ -0,0 +1,175 @@
+//! Conformance report against the whole riscv-tests suite.
+//!
+//! Unlike `riscv_tests.rs`, which pins one test per instruction, this harness
+//! discovers every `rv32ui-p-*` and `rv32um-p-*` ELF in the test data
+//! directory, runs each of them through the runner, proves a subset, and
+//! prints a pass/fail line per test before asserting on the outcome.
+//!
+//! The ELFs are pre-bundled under `riscv-testdata/testdata`, and regenerated
+//! from upstream with `update_testdata`. Set `RISCV_TESTS_DIR` to run a
+//! different build of the suite, and `RISCV_TESTS_PROVE=all` to prove every
+//! test instead of the default subset.
+//!
+//! The suite is slow, so it is ignored by default:
+//!
+//! ```sh
+//! cargo test --features test --test riscv_conformance -- --ignored --nocapture
+//! ```
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::PathBuf;
+
+use anyhow::{ensure, Result};
+use mozak_circuits::test_utils::prove_and_verify_mozak_stark;
+use mozak_runner::elf::Program;
+use mozak_runner::state::State;
+use mozak_runner::vm::{step, ExecutionRecord};
+use plonky2::field::goldilocks_field::GoldilocksField;
+use starky::config::StarkConfig;
+
+/// Test suites, as prefixes of ELF names.
+const SUITES: [&str; 2] = ["rv32ui-p-", "rv32um-p-"];
+
+/// Tests that are proven by default. Together they exercise every table.
+const PROVEN_BY_DEFAULT: &[&str] = &[
+    "rv32ui-p-add",
+    "rv32ui-p-beq",
+    "rv32ui-p-bltu",
+    "rv32ui-p-jalr",
+    "rv32ui-p-lb",
+    "rv32ui-p-lw",
+    "rv32ui-p-sh",
+    "rv32ui-p-sra",
+    "rv32ui-p-xor",
+    "rv32um-p-div",
+    "rv32um-p-mulhsu",
+];
+
+/// Tests of behaviour the VM deliberately does not support, with the reason.
+const EXPECTED_FAILURES: &[(&str, &str)] = &[(
+    "rv32ui-p-fence_i",
+    "self-modifying code; the VM has a modified Harvard architecture",
+)];
+
+#[derive(Debug)]
+enum Outcome {
+    Pass,
+    Fail(String),
+    Skipped,
+}
+
+impl std::fmt::Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Outcome::Pass => write!(f, "pass"),
+            Outcome::Fail(reason) => write!(f, "FAIL ({reason})"),
+            Outcome::Skipped => write!(f, "-"),
+        }
+    }
+}
+
+impl Outcome {
+    fn catch(f: impl FnOnce() -> Result<()>) -> Self {
+        match catch_unwind(AssertUnwindSafe(f)) {
+            Ok(Ok(())) => Outcome::Pass,
+            Ok(Err(e)) => Outcome::Fail(format!("{e:#}")),
+            Err(panic) => Outcome::Fail(
+                panic
+                    .downcast_ref::<String>()
+                    .cloned()
+                    .or_else(|| panic.downcast_ref::<&str>().map(ToString::to_string))
+                    .unwrap_or_else(|| "panicked".to_string()),
+            ),
+        }
+    }
+
+    fn passed(&self) -> bool { !matches!(self, Outcome::Fail(_)) }
+}
+
+fn testdata_dir() -> PathBuf {
+    std::env::var_os("RISCV_TESTS_DIR").map_or_else(
+        || PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../riscv-testdata/testdata"),
+        PathBuf::from,
+    )
+}
+
+/// Runs `elf`, and checks it halted via `RVTEST_PASS`, i.e. with an `exit(0)`
+/// ecall.
+fn run(elf: &[u8]) -> Result<(Program, ExecutionRecord<GoldilocksField>)> {
+    let program = Program::vanilla_load_elf(elf)?;
+    let state = State::<GoldilocksField>::from(program.clone());
+    let record = step(&program, state)?;
+    let state = &record.last_state;
+    ensure!(state.has_halted(), "did not halt");
+    ensure!(
+        state.get_register_value(17) == 93,
+        "halted without exit ecall"
+    );
+    let test_case = state.get_register_value(10);
+    ensure!(test_case == 0, "failed test case {}", test_case >> 1);
+    Ok((program, record))
+}
+
+#[test]
+#[ignore]
+fn riscv_tests_conformance() -> Result<()> {
+    let _ = env_logger::try_init();
+    let prove_all = std::env::var("RISCV_TESTS_PROVE").is_ok_and(|v| v == "all");
+    let config = StarkConfig::standard_fast_config();
+
+    let dir = testdata_dir();
+    let mut names: Vec<String> = std::fs::read_dir(&dir)?
+        .map(|entry| Ok(entry?.file_name().to_string_lossy().into_owned()))
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .filter(|name| SUITES.iter().any(|suite| name.starts_with(suite)))
+        .filter(|name| !name.ends_with(".dump"))
+        .collect();
+    names.sort();
+    ensure!(
+        !names.is_empty(),
+        "no riscv-tests found in {}",
+        dir.display()
+    );
+
+    let mut failures = vec![];
+    println!("{:<24} {:<40} prove", "test", "run");
+    for name in &names {
+        let elf = std::fs::read(dir.join(name))?;
+        let mut record = None;
+        let run_outcome = Outcome::catch(|| {
+            record = Some(run(&elf)?);
+            Ok(())
+        });
+        let prove_outcome = match record {
+            Some((program, record)) if prove_all || PROVEN_BY_DEFAULT.contains(&name.as_str()) =>
+                Outcome::catch(|| prove_and_verify_mozak_stark(&program, &record, &config)),
+            _ => Outcome::Skipped,
+        };
+
+        let expected_failure = EXPECTED_FAILURES
+            .iter()
+            .find(|(expected, _)| *expected == name.as_str());
+        let passed = run_outcome.passed() && prove_outcome.passed();
+        let note = match (expected_failure, passed) {
+            (Some((_, reason)), false) => format!("  expected: {reason}"),
+            (Some(_), true) => "  unexpectedly passed".to_string(),
+            (None, _) => String::new(),
+        };
+        println!(
+            "{name:<24} {:<40} {prove_outcome}{note}",
+            run_outcome.to_string()
+        );
+        if !passed && expected_failure.is_none() {
+            failures.push(name.as_str());
+        }
+    }
+
+    println!(
+        "{} of {} tests passed or failed as expected",
+        names.len() - failures.len(),
+        names.len()
+    );
+    ensure!(failures.is_empty(), "failing riscv-tests: {failures:?}");
+    Ok(())
+}
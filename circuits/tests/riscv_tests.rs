@@ -52,6 +52,21 @@ macro_rules! test_elf {
     };
 }
 
+/// The test environment issues a `fence` before it reports a pass, which the
+/// VM runs, and proves, as a no-op.
+#[test]
+fn executes_fence() -> Result<()> {
+    let program =
+        Program::vanilla_load_elf(include_bytes!("../../riscv-testdata/testdata/rv32ui-p-add"))?;
+    let record = step(&program, State::<GoldilocksField>::from(program.clone()))?;
+    let opcode = |pc: u32| program.ro_memory[&pc] & 0x7f;
+    assert!(record
+        .executed
+        .iter()
+        .any(|row| opcode(row.state.get_pc()) == 0b000_1111));
+    Ok(())
+}
+
 // Base instruction set
 test_elf!(add, "rv32ui-p-add");
 test_elf!(addi, "rv32ui-p-addi");
@@ -0,0 +1,80 @@
+//! Replays the known answer test vectors, see
+//! [`mozak_circuits::test_vectors`].
+//!
+//! Vectors that do not exist yet are written on the first run. To regenerate
+//! all of them after a deliberate change to the tables:
+//!
+//! ```sh
+//! MOZAK_UPDATE_TEST_VECTORS=1 cargo test --features test --test test_vectors
+//! ```
+
+use anyhow::Result;
+use mozak_circuits::test_vectors::check_test_vector;
+use mozak_runner::code;
+use mozak_runner::decode::ECALL;
+use mozak_runner::instruction::{Args, Instruction, Op};
+use mozak_runner::state::RawTapes;
+use mozak_sdk::core::ecall;
+use mozak_sdk::core::reg_abi::{REG_A0, REG_A1, REG_A2};
+
+#[test]
+fn arithmetic() -> Result<()> {
+    let (program, record) = code::execute(
+        [
+            Instruction::add(5, 6, 7),
+            Instruction::mul(8, 5, 6),
+            Instruction {
+                op: Op::SRL,
+                args: Args {
+                    rd: 9,
+                    rs1: 8,
+                    imm: 3,
+                    ..Args::default()
+                },
+            },
+            Instruction::xor(10, 9, 7),
+        ],
+        &[],
+        &[(6, 100), (7, 0xFFFF_FF00)],
+    );
+    check_test_vector("arithmetic", &program, &record)
+}
+
+#[test]
+fn memory() -> Result<()> {
+    let address = 0x400;
+    let (program, record) = code::execute(
+        [
+            Instruction::sw(5, 0, address),
+            Instruction::lh(6, 0, address + 2),
+            Instruction::sb(6, 0, address + 1),
+            Instruction::lw(7, 0, address),
+        ],
+        &[
+            (address, 0),
+            (address + 1, 0),
+            (address + 2, 0),
+            (address + 3, 0),
+        ],
+        &[(5, 0xDEAD_BEEF)],
+    );
+    check_test_vector("memory", &program, &record)
+}
+
+#[test]
+fn public_tape() -> Result<()> {
+    let address = 0x400;
+    let (program, record) = code::execute_code_with_ro_memory(
+        [ECALL],
+        &[],
+        &(address..address + 4)
+            .map(|addr| (addr, 0))
+            .collect::<Vec<_>>(),
+        &[(REG_A0, ecall::PUBLIC_TAPE), (REG_A1, address), (REG_A2, 4)],
+        RawTapes {
+            public_tape: b"mozak".to_vec(),
+            ..RawTapes::default()
+        },
+    );
+    check_test_vector("public_tape", &program, &record)
+}
@@ -1,11 +1,14 @@
+use std::path::PathBuf;
 use std::time::Duration;
 
 use anyhow::Result;
 use clap::{Args as Args_, Subcommand};
 
+use super::int_math::IntMathBench;
 use super::nop::NopBench;
 use super::omni::OmniBench;
 use super::poseidon2::Poseidon2Bench;
+use super::soak::{soak_bench, SOAK_MIN_CYCLES};
 use super::sort::{
     BatchStarksSortBench, BatchStarksSortBenchRecursive, SortBench, SortBenchRecursive,
 };
@@ -70,6 +73,20 @@ pub enum BenchFunction {
     VectorAllocBench {
         n: u32,
     },
+    /// Compares cycle counts of generic `u64` arithmetic against
+    /// `mozak_sdk::math`, and proves the latter.
+    IntMathBench {
+        n: u32,
+    },
+    /// Proves and verifies a sort running for at least `min_cycles` cycles
+    /// with the production config, and reports time and peak memory.
+    SoakBench {
+        #[arg(default_value_t = SOAK_MIN_CYCLES)]
+        min_cycles: usize,
+        /// Where to write the JSON report.
+        #[arg(long)]
+        report: Option<PathBuf>,
+    },
 }
 
 impl BenchArgs {
@@ -85,6 +102,9 @@ impl BenchArgs {
             BenchFunction::BatchStarksSortBenchRecursive { n } =>
                 BatchStarksSortBenchRecursive.bench(n),
             BenchFunction::VectorAllocBench { n } => VectorAllocBench.bench(n),
+            BenchFunction::IntMathBench { n } => IntMathBench.bench(n),
+            BenchFunction::SoakBench { min_cycles, report } =>
+                soak_bench(*min_cycles, report.as_deref()),
         }
     }
 }
@@ -0,0 +1,75 @@
+use anyhow::Result;
+use mozak_circuits::test_utils::{prove_and_verify_mozak_stark, F};
+use mozak_examples::INT_MATH_ELF;
+use mozak_runner::elf::Program;
+use mozak_runner::state::{RawTapes, State};
+use mozak_runner::vm::{step, ExecutionRecord};
+use starky::config::StarkConfig;
+
+use super::benches::Bench;
+
+/// Runs `INT_MATH_ELF` for `n` iterations, either with generic `u64`
+/// arithmetic or with `mozak_sdk::math`.
+pub fn int_math_run(n: u32, tuned: bool) -> Result<(Program, ExecutionRecord<F>)> {
+    let program = Program::vanilla_load_elf(INT_MATH_ELF)?;
+    let mut public_tape = n.to_le_bytes().to_vec();
+    public_tape.push(u8::from(tuned));
+    let raw_tapes = RawTapes {
+        public_tape,
+        ..Default::default()
+    };
+    let state = State::new(program.clone(), raw_tapes);
+    let record = step(&program, state)?;
+    Ok((program, record))
+}
+
+/// Reports the cycle counts of both variants, and returns the tuned one to be
+/// proven.
+pub fn int_math_prepare(n: u32) -> Result<(Program, ExecutionRecord<F>)> {
+    let (_, generic) = int_math_run(n, false)?;
+    let (program, tuned) = int_math_run(n, true)?;
+    println!(
+        "Cycles with generic arithmetic: {}, with mozak_sdk::math: {}",
+        generic.executed.len(),
+        tuned.executed.len()
+    );
+    Ok((program, tuned))
+}
+
+pub fn int_math_execute(result: Result<(Program, ExecutionRecord<F>)>) -> Result<()> {
+    let (program, record) = result?;
+    prove_and_verify_mozak_stark(&program, &record, &StarkConfig::standard_fast_config())
+}
+
+pub(crate) struct IntMathBench;
+
+impl Bench for IntMathBench {
+    type Args = u32;
+    type Prepared = Result<(Program, ExecutionRecord<F>)>;
+
+    fn prepare(&self, args: &Self::Args) -> Self::Prepared { int_math_prepare(*args) }
+
+    fn execute(&self, prepared: Self::Prepared) -> Result<()> { int_math_execute(prepared) }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use super::{int_math_execute, int_math_prepare, int_math_run};
+
+    #[test]
+    fn test_int_math_bench() -> Result<()> {
+        let n = 10;
+        int_math_execute(int_math_prepare(n))
+    }
+
+    #[test]
+    fn test_tuned_math_is_cheaper() -> Result<()> {
+        let n = 10;
+        let (_, generic) = int_math_run(n, false)?;
+        let (_, tuned) = int_math_run(n, true)?;
+        assert!(tuned.executed.len() < generic.executed.len());
+        Ok(())
+    }
+}
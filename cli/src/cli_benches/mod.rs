@@ -1,7 +1,9 @@
 pub mod benches;
+pub mod int_math;
 pub mod nop;
 pub mod omni;
 pub mod poseidon2;
+pub mod soak;
 pub mod sort;
 pub mod vector_alloc;
 pub mod xor;
@@ -0,0 +1,134 @@
+//! Soak test: proves a guest that runs for millions of cycles with the
+//! production configuration, and reports how long that took and how much
+//! memory it needed.
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use log::info;
+use mozak_circuits::stark::mozak_stark::{MozakStark, PublicInputs};
+use mozak_circuits::stark::prover::prove;
+use mozak_circuits::stark::verifier::verify_proof;
+use mozak_circuits::test_utils::{C, D, F};
+use plonky2::field::types::Field;
+use plonky2::util::timing::TimingTree;
+use serde::Serialize;
+use starky::config::StarkConfig;
+
+use super::sort::sort_prepare;
+
+/// Default number of cycles a soak run has to reach.
+pub const SOAK_MIN_CYCLES: usize = 1 << 22;
+
+/// What a soak run measured, written out as JSON.
+#[derive(Clone, Debug, Serialize)]
+pub struct SoakReport {
+    /// Number of elements the sort guest sorted.
+    pub n: u32,
+    /// Number of executed cycles.
+    pub cycles: usize,
+    pub execute_secs: f64,
+    pub prove_secs: f64,
+    pub verify_secs: f64,
+    /// Peak resident set size of the process, if the platform reports it.
+    pub peak_rss_kib: Option<u64>,
+}
+
+/// Peak resident set size of this process, from `VmHWM` in
+/// `/proc/self/status`. Only available on Linux.
+fn peak_rss_kib() -> Option<u64> {
+    fs::read_to_string("/proc/self/status")
+        .ok()?
+        .lines()
+        .find_map(|line| line.strip_prefix("VmHWM:"))?
+        .trim()
+        .strip_suffix("kB")?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// Sorts ever longer vectors with `MOZAK_SORT_ELF` until the execution takes
+/// at least `min_cycles` cycles, then proves and verifies that execution.
+///
+/// If `report` is given, the [`SoakReport`] is also written there as JSON.
+pub fn soak(min_cycles: usize, report: Option<&Path>) -> Result<SoakReport> {
+    let start = Instant::now();
+    let mut n = 16;
+    let (program, record) = loop {
+        let (program, record) = sort_prepare(n)?;
+        info!("n = {n}: {} cycles", record.executed.len());
+        if record.executed.len() >= min_cycles {
+            break (program, record);
+        }
+        n *= 2;
+    };
+    let execute_secs = start.elapsed().as_secs_f64();
+
+    let stark = MozakStark::default();
+    let config = StarkConfig::standard_fast_config();
+    let public_inputs = PublicInputs::new(F::from_canonical_u32(program.entry_point));
+    let start = Instant::now();
+    let proof = prove::<F, C, D>(
+        &program,
+        &record,
+        &stark,
+        &config,
+        public_inputs,
+        &mut TimingTree::default(),
+    )?;
+    let prove_secs = start.elapsed().as_secs_f64();
+
+    let start = Instant::now();
+    verify_proof(&stark, proof, &config)?;
+    let verify_secs = start.elapsed().as_secs_f64();
+
+    let soak_report = SoakReport {
+        n,
+        cycles: record.executed.len(),
+        execute_secs,
+        prove_secs,
+        verify_secs,
+        peak_rss_kib: peak_rss_kib(),
+    };
+    info!("{soak_report:?}");
+    if let Some(report) = report {
+        fs::write(report, serde_json::to_string_pretty(&soak_report)?)?;
+    }
+    Ok(soak_report)
+}
+
+/// Runs [`soak`], and returns the time it took to prove.
+pub fn soak_bench(min_cycles: usize, report: Option<&Path>) -> Result<Duration> {
+    Ok(Duration::from_secs_f64(
+        soak(min_cycles, report)?.prove_secs,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use anyhow::Result;
+
+    use super::{soak, SOAK_MIN_CYCLES};
+
+    /// Run with `cargo test --release --features bench -- --ignored soak`.
+    /// The report goes to `$MOZAK_SOAK_REPORT`, or `soak-report.json`.
+    #[test]
+    #[ignore = "takes hours, run nightly"]
+    fn test_soak() -> Result<()> {
+        let report = std::env::var_os("MOZAK_SOAK_REPORT")
+            .map_or_else(|| PathBuf::from("soak-report.json"), PathBuf::from);
+        let soak_report = soak(SOAK_MIN_CYCLES, Some(&report))?;
+        assert!(soak_report.cycles >= SOAK_MIN_CYCLES);
+        Ok(())
+    }
+
+    #[test]
+    fn test_small_soak() -> Result<()> {
+        soak(1 << 10, None)?;
+        Ok(())
+    }
+}
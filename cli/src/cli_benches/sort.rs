@@ -47,9 +47,7 @@ pub fn sort_recursive_prepare(
     let mozak_stark = MozakStark::default();
     let stark_config = StarkConfig::standard_fast_config();
     let (program, record) = sort_prepare(n)?;
-    let public_inputs = PublicInputs {
-        entry_point: F::from_canonical_u32(program.entry_point),
-    };
+    let public_inputs = PublicInputs::new(F::from_canonical_u32(program.entry_point));
     let mozak_proof = prove::<F, C, D>(
         &program,
         &record,
@@ -90,9 +88,7 @@ pub fn batch_starks_sort_recursive_prepare(
     let mozak_stark = MozakStark::default();
     let stark_config = StarkConfig::standard_fast_config();
     let (program, record) = sort_prepare(n)?;
-    let public_inputs = PublicInputs {
-        entry_point: F::from_canonical_u32(program.entry_point),
-    };
+    let public_inputs = PublicInputs::new(F::from_canonical_u32(program.entry_point));
     let (mozak_proof, degree_bits) = batch_prove::<F, C, D>(
         &program,
         &record,
@@ -0,0 +1,50 @@
+//! Optional zstd compression of the proofs and traces written by the CLI.
+//!
+//! Readers detect compressed input on their own, so only writers need to be
+//! told whether to compress.
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+
+use anyhow::Result;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Magic number at the start of every zstd frame.
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// Serializes `value` as JSON into `writer`, compressed with zstd if
+/// `compress` is set.
+///
+/// # Errors
+///
+/// Errors if serialization or writing fails.
+pub fn write_json<W: Write, T: Serialize>(writer: W, value: &T, compress: bool) -> Result<()> {
+    if compress {
+        let mut encoder = zstd::Encoder::new(writer, zstd::DEFAULT_COMPRESSION_LEVEL)?;
+        serde_json::to_writer(&mut encoder, value)?;
+        encoder.finish()?;
+    } else {
+        let mut writer = BufWriter::new(writer);
+        serde_json::to_writer(&mut writer, value)?;
+        writer.flush()?;
+    }
+    Ok(())
+}
+
+/// Deserializes JSON from `reader`, which may be compressed with zstd.
+///
+/// Compressed input is decompressed frame by frame as it is deserialized, so
+/// neither the compressed nor the decompressed bytes are held in memory as a
+/// whole.
+///
+/// # Errors
+///
+/// Errors if reading, decompression or deserialization fails.
+pub fn read_json<R: Read, T: DeserializeOwned>(reader: R) -> Result<T> {
+    let mut reader = BufReader::new(reader);
+    if reader.fill_buf()?.starts_with(&ZSTD_MAGIC) {
+        let decoder = zstd::Decoder::with_buffer(reader)?;
+        Ok(serde_json::from_reader(BufReader::new(decoder))?)
+    } else {
+        Ok(serde_json::from_reader(reader)?)
+    }
+}
@@ -0,0 +1,268 @@
+//! The command loop of `mozak-cli debug`, on top of the
+//! [`Debugger`](mozak_runner::debugger::Debugger) of the runner.
+use std::io::{BufRead, Write};
+use std::ops::Range;
+
+use anyhow::{anyhow, bail, ensure, Result};
+use itertools::Itertools;
+use mozak_runner::debugger::{
+    Access, Debugger, Stop, Symbols, WatchHit, Watchpoint, REGISTER_NAMES,
+};
+use mozak_runner::vm::disasm::disassemble;
+use plonky2::hash::hash_types::RichField;
+
+const HELP: &str = "\
+break <pc|symbol>   (b)  stop before executing the instruction at pc
+delete <pc|symbol>  (d)  remove a breakpoint
+breakpoints         (bl) list breakpoints
+step [n]            (s)  execute n instructions, 1 by default
+continue            (c)  execute until a breakpoint, a watchpoint or halt
+watch <range> [rw]       stop after reads (r), writes (w) or both (rw,
+                         the default) of the addresses in start..end, or of
+                         a single address
+trace <range> [rw]       only record the accesses, see hits
+watchpoints         (wl) list watchpoints
+unwatch <id>             remove a watchpoint
+hits                     print the accesses watchpoints caught
+regs                (r)  print the registers
+mem <addr> [len]    (x)  print len bytes of memory, 16 by default
+where               (w)  print the pc and the next instruction
+help                (h)  print this help
+quit                (q)  stop debugging";
+
+/// Parses a hexadecimal address with a `0x` prefix, a decimal one, or the
+/// name of a symbol. Numbers may group digits with `_`.
+fn parse_address(symbols: &Symbols, arg: Option<&str>) -> Result<u32> {
+    let arg = arg.ok_or_else(|| anyhow!("expected an address or a symbol"))?;
+    if let Some(hex) = arg.strip_prefix("0x") {
+        return Ok(u32::from_str_radix(&hex.replace('_', ""), 16)?);
+    }
+    arg.replace('_', "")
+        .parse()
+        .ok()
+        .or_else(|| symbols.address_of(arg))
+        .ok_or_else(|| anyhow!("unknown symbol {arg}"))
+}
+
+/// Parses `start..end`, or a single address.
+///
+/// # Errors
+/// Errors if an address is malformed or an unknown symbol, or if the range
+/// is empty.
+pub fn parse_range(symbols: &Symbols, arg: Option<&str>) -> Result<Range<u32>> {
+    let arg = arg.ok_or_else(|| anyhow!("expected an address range"))?;
+    let range = match arg.split_once("..") {
+        Some((start, end)) =>
+            parse_address(symbols, Some(start))?..parse_address(symbols, Some(end))?,
+        None => {
+            let addr = parse_address(symbols, Some(arg))?;
+            addr..addr.saturating_add(1)
+        }
+    };
+    ensure!(!range.is_empty(), "empty range {arg}");
+    Ok(range)
+}
+
+fn parse_access(arg: Option<&str>) -> Result<Access> {
+    match arg {
+        Some("r") => Ok(Access::Read),
+        Some("w") => Ok(Access::Write),
+        Some("rw") | None => Ok(Access::ReadWrite),
+        Some(other) => bail!("expected r, w or rw, not {other}"),
+    }
+}
+
+fn access_name(access: Access) -> &'static str {
+    match access {
+        Access::Read => "read",
+        Access::Write => "write",
+        Access::ReadWrite => "read/write",
+    }
+}
+
+fn describe_hit(symbols: &Symbols, hit: &WatchHit) -> String {
+    format!(
+        "{} of {:#x} by {} at clk {}",
+        access_name(hit.access),
+        hit.addr,
+        location(symbols, hit.pc),
+        hit.clk
+    )
+}
+
+fn location(symbols: &Symbols, pc: u32) -> String {
+    match symbols.locate(pc) {
+        Some((name, 0)) => format!("{pc:#x} <{name}>"),
+        Some((name, offset)) => format!("{pc:#x} <{name}+{offset:#x}>"),
+        None => format!("{pc:#x}"),
+    }
+}
+
+fn print_where<F: RichField, W: Write>(
+    debugger: &Debugger<F>,
+    symbols: &Symbols,
+    output: &mut W,
+) -> Result<()> {
+    let state = debugger.state();
+    if state.has_halted() {
+        writeln!(output, "halted at clk {}", state.clk)?;
+        return Ok(());
+    }
+    let instruction = match debugger.next_instruction() {
+        Some(Ok(instruction)) => disassemble(state.get_pc(), instruction),
+        Some(Err(error)) => format!("undecodable {:#x}", error.instruction),
+        None => "no instruction".to_string(),
+    };
+    writeln!(
+        output,
+        "clk {} at {}: {instruction}",
+        state.clk,
+        location(symbols, state.get_pc())
+    )?;
+    Ok(())
+}
+
+/// Executes one command, and returns whether to quit.
+fn execute<F: RichField, W: Write>(
+    debugger: &mut Debugger<F>,
+    symbols: &Symbols,
+    line: &str,
+    output: &mut W,
+) -> Result<bool> {
+    let mut words = line.split_whitespace();
+    let Some(command) = words.next() else {
+        return Ok(false);
+    };
+    match command {
+        "break" | "b" => {
+            let pc = parse_address(symbols, words.next())?;
+            if debugger.add_breakpoint(pc) {
+                writeln!(output, "breakpoint at {}", location(symbols, pc))?;
+            }
+        }
+        "delete" | "d" => {
+            let pc = parse_address(symbols, words.next())?;
+            if !debugger.remove_breakpoint(pc) {
+                bail!("no breakpoint at {pc:#x}");
+            }
+        }
+        "breakpoints" | "bl" =>
+            for pc in debugger.breakpoints() {
+                writeln!(output, "{}", location(symbols, pc))?;
+            },
+        "step" | "s" | "continue" | "c" => {
+            let stop = if matches!(command, "continue" | "c") {
+                debugger.resume()?
+            } else {
+                debugger.step(words.next().map_or(Ok(1), str::parse)?)?
+            };
+            match stop {
+                Stop::Breakpoint(pc) => writeln!(output, "breakpoint {}", location(symbols, pc))?,
+                Stop::Watchpoint(hit) =>
+                    writeln!(output, "watchpoint: {}", describe_hit(symbols, &hit))?,
+                Stop::Stepped | Stop::Halted => {}
+            }
+            print_where(debugger, symbols, output)?;
+        }
+        "watch" | "trace" => {
+            let range = parse_range(symbols, words.next())?;
+            let access = parse_access(words.next())?;
+            let id = debugger.add_watchpoint(Watchpoint {
+                range: range.clone(),
+                access,
+                pause: command == "watch",
+            });
+            writeln!(
+                output,
+                "watchpoint {id} on {} of {:#x}..{:#x}",
+                access_name(access),
+                range.start,
+                range.end
+            )?;
+        }
+        "watchpoints" | "wl" =>
+            for (id, watchpoint) in debugger.watchpoints() {
+                writeln!(
+                    output,
+                    "{id}: {} of {:#x}..{:#x}{}",
+                    access_name(watchpoint.access),
+                    watchpoint.range.start,
+                    watchpoint.range.end,
+                    if watchpoint.pause { "" } else { ", traced" }
+                )?;
+            },
+        "unwatch" => {
+            let id = words
+                .next()
+                .ok_or_else(|| anyhow!("expected a watchpoint id"))?
+                .parse()?;
+            if debugger.remove_watchpoint(id).is_none() {
+                bail!("no watchpoint {id}");
+            }
+        }
+        "hits" =>
+            for hit in debugger.watch_hits() {
+                writeln!(output, "{}", describe_hit(symbols, hit))?;
+            },
+        "regs" | "r" =>
+            for chunk in &(0_u8..32).chunks(4) {
+                let line = chunk
+                    .map(|index| {
+                        format!(
+                            "{:>4} {:#010x}",
+                            REGISTER_NAMES[usize::from(index)],
+                            debugger.register(index)
+                        )
+                    })
+                    .join("  ");
+                writeln!(output, "{line}")?;
+            },
+        "mem" | "x" => {
+            let addr = parse_address(symbols, words.next())?;
+            let len = words.next().map_or(Ok(16), str::parse)?;
+            let bytes = debugger.read_memory(addr, len);
+            for (row, chunk) in (0_u32..).zip(bytes.chunks(16)) {
+                writeln!(
+                    output,
+                    "{:#010x}: {:02x}",
+                    addr.wrapping_add(16 * row),
+                    chunk.iter().format(" ")
+                )?;
+            }
+        }
+        "where" | "w" => print_where(debugger, symbols, output)?,
+        "help" | "h" => writeln!(output, "{HELP}")?,
+        "quit" | "q" => return Ok(true),
+        _ => bail!("unknown command {command}, try help"),
+    }
+    Ok(false)
+}
+
+/// Reads commands from `input` until `quit` or the end of `input`, and
+/// executes them with `debugger`. Errors of single commands, including
+/// failing instructions, are printed and do not end the loop.
+///
+/// # Errors
+///
+/// Errors if reading `input` or writing `output` fails.
+pub fn debug_repl<F: RichField, R: BufRead, W: Write>(
+    debugger: &mut Debugger<F>,
+    symbols: &Symbols,
+    input: R,
+    mut output: W,
+) -> Result<()> {
+    print_where(debugger, symbols, &mut output)?;
+    let mut lines = input.lines();
+    loop {
+        write!(output, "(mozak) ")?;
+        output.flush()?;
+        let Some(line) = lines.next().transpose()? else {
+            return Ok(());
+        };
+        match execute(debugger, symbols, &line, &mut output) {
+            Ok(true) => return Ok(()),
+            Ok(false) => {}
+            Err(error) => writeln!(output, "error: {error}")?,
+        }
+    }
+}
@@ -0,0 +1,264 @@
+//! A GDB remote serial protocol server on top of the
+//! [`Debugger`](mozak_runner::debugger::Debugger) of the runner, so that gdb
+//! or lldb can attach to a guest execution with eg `target remote :9001`.
+//!
+//! The server only exposes registers, memory, breakpoints, watchpoints and
+//! execution control. The debugger reads symbols and DWARF info from the ELF
+//! itself, so source-level debugging needs a guest built with debug info.
+use std::marker::PhantomData;
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+
+use anyhow::{anyhow, Result};
+use gdbstub::common::Signal;
+use gdbstub::conn::{Connection, ConnectionExt};
+use gdbstub::stub::run_blocking::{BlockingEventLoop, Event, WaitForStopReasonError};
+use gdbstub::stub::{DisconnectReason, GdbStub, SingleThreadStopReason};
+use gdbstub::target::ext::base::singlethread::{
+    SingleThreadBase, SingleThreadResume, SingleThreadResumeOps, SingleThreadSingleStep,
+    SingleThreadSingleStepOps,
+};
+use gdbstub::target::ext::base::BaseOps;
+use gdbstub::target::ext::breakpoints::{
+    Breakpoints, BreakpointsOps, HwWatchpoint, HwWatchpointOps, SwBreakpoint, SwBreakpointOps,
+    WatchKind,
+};
+use gdbstub::target::{Target, TargetError, TargetResult};
+use gdbstub_arch::riscv::reg::RiscvCoreRegs;
+use gdbstub_arch::riscv::Riscv32;
+use log::warn;
+use mozak_runner::debugger::{Access, Debugger, Stop, Watchpoint};
+use mozak_sdk::core::reg_abi::REG_A1;
+use plonky2::hash::hash_types::RichField;
+
+/// Instructions to execute between checks for an interrupt from gdb.
+const INTERRUPT_POLL_STEPS: u64 = 1024;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Resume {
+    Continue,
+    Step,
+}
+
+/// A guest execution that gdb can attach to.
+pub struct GdbTarget<'a, F: RichField> {
+    pub debugger: Debugger<'a, F>,
+    resume: Resume,
+}
+
+impl<'a, F: RichField> GdbTarget<'a, F> {
+    #[must_use]
+    pub fn new(debugger: Debugger<'a, F>) -> Self {
+        Self {
+            debugger,
+            resume: Resume::Continue,
+        }
+    }
+
+    fn stop_reason(&self, stop: Result<Stop>) -> SingleThreadStopReason<u32> {
+        match stop {
+            Ok(Stop::Stepped) => SingleThreadStopReason::DoneStep,
+            Ok(Stop::Breakpoint(_)) => SingleThreadStopReason::SwBreak(()),
+            Ok(Stop::Watchpoint(hit)) => SingleThreadStopReason::Watch {
+                tid: (),
+                kind: match hit.access {
+                    Access::Read => WatchKind::Read,
+                    Access::Write => WatchKind::Write,
+                    Access::ReadWrite => WatchKind::ReadWrite,
+                },
+                addr: hit.addr,
+            },
+            Ok(Stop::Halted) if self.debugger.state().trap.is_some() =>
+                SingleThreadStopReason::Terminated(Signal::SIGABRT),
+            Ok(Stop::Halted) =>
+                SingleThreadStopReason::Exited(self.debugger.register(REG_A1).to_le_bytes()[0]),
+            // The state is left as it was before the failing instruction, so
+            // gdb can still inspect it.
+            Err(error) => {
+                warn!("{error}");
+                SingleThreadStopReason::Signal(Signal::SIGSEGV)
+            }
+        }
+    }
+}
+
+impl<F: RichField> Target for GdbTarget<'_, F> {
+    type Arch = Riscv32;
+    type Error = anyhow::Error;
+
+    fn base_ops(&mut self) -> BaseOps<'_, Self::Arch, Self::Error> { BaseOps::SingleThread(self) }
+
+    fn support_breakpoints(&mut self) -> Option<BreakpointsOps<'_, Self>> { Some(self) }
+}
+
+impl<F: RichField> SingleThreadBase for GdbTarget<'_, F> {
+    fn read_registers(&mut self, regs: &mut RiscvCoreRegs<u32>) -> TargetResult<(), Self> {
+        for (index, x) in (0..).zip(regs.x.iter_mut()) {
+            *x = self.debugger.register(index);
+        }
+        regs.pc = self.debugger.state().get_pc();
+        Ok(())
+    }
+
+    fn write_registers(&mut self, regs: &RiscvCoreRegs<u32>) -> TargetResult<(), Self> {
+        for (index, &x) in (0..).zip(&regs.x) {
+            self.debugger.set_register(index, x);
+        }
+        self.debugger.set_pc(regs.pc);
+        Ok(())
+    }
+
+    fn read_addrs(&mut self, start_addr: u32, data: &mut [u8]) -> TargetResult<usize, Self> {
+        let len = u32::try_from(data.len()).map_err(|_| TargetError::NonFatal)?;
+        data.copy_from_slice(&self.debugger.read_memory(start_addr, len));
+        Ok(data.len())
+    }
+
+    fn write_addrs(&mut self, start_addr: u32, data: &[u8]) -> TargetResult<(), Self> {
+        self.debugger
+            .write_memory(start_addr, data)
+            .map_err(|_| TargetError::NonFatal)
+    }
+
+    fn support_resume(&mut self) -> Option<SingleThreadResumeOps<'_, Self>> { Some(self) }
+}
+
+/// Signals have no meaning for the guest, so resuming ignores them.
+impl<F: RichField> SingleThreadResume for GdbTarget<'_, F> {
+    fn resume(&mut self, _signal: Option<Signal>) -> Result<(), Self::Error> {
+        self.resume = Resume::Continue;
+        Ok(())
+    }
+
+    fn support_single_step(&mut self) -> Option<SingleThreadSingleStepOps<'_, Self>> { Some(self) }
+}
+
+impl<F: RichField> SingleThreadSingleStep for GdbTarget<'_, F> {
+    fn step(&mut self, _signal: Option<Signal>) -> Result<(), Self::Error> {
+        self.resume = Resume::Step;
+        Ok(())
+    }
+}
+
+impl<F: RichField> Breakpoints for GdbTarget<'_, F> {
+    fn support_sw_breakpoint(&mut self) -> Option<SwBreakpointOps<'_, Self>> { Some(self) }
+
+    fn support_hw_watchpoint(&mut self) -> Option<HwWatchpointOps<'_, Self>> { Some(self) }
+}
+
+impl<F: RichField> SwBreakpoint for GdbTarget<'_, F> {
+    fn add_sw_breakpoint(&mut self, addr: u32, _kind: usize) -> TargetResult<bool, Self> {
+        self.debugger.add_breakpoint(addr);
+        Ok(true)
+    }
+
+    fn remove_sw_breakpoint(&mut self, addr: u32, _kind: usize) -> TargetResult<bool, Self> {
+        Ok(self.debugger.remove_breakpoint(addr))
+    }
+}
+
+fn watchpoint(addr: u32, len: u32, kind: WatchKind) -> Watchpoint {
+    Watchpoint {
+        range: addr..addr.saturating_add(len),
+        access: match kind {
+            WatchKind::Read => Access::Read,
+            WatchKind::Write => Access::Write,
+            WatchKind::ReadWrite => Access::ReadWrite,
+        },
+        pause: true,
+    }
+}
+
+impl<F: RichField> HwWatchpoint for GdbTarget<'_, F> {
+    fn add_hw_watchpoint(
+        &mut self,
+        addr: u32,
+        len: u32,
+        kind: WatchKind,
+    ) -> TargetResult<bool, Self> {
+        self.debugger.add_watchpoint(watchpoint(addr, len, kind));
+        Ok(true)
+    }
+
+    fn remove_hw_watchpoint(
+        &mut self,
+        addr: u32,
+        len: u32,
+        kind: WatchKind,
+    ) -> TargetResult<bool, Self> {
+        let watchpoint = watchpoint(addr, len, kind);
+        let id = self
+            .debugger
+            .watchpoints()
+            .find_map(|(id, existing)| (*existing == watchpoint).then_some(id));
+        Ok(id.is_some_and(|id| self.debugger.remove_watchpoint(id).is_some()))
+    }
+}
+
+struct EventLoop<'a, F>(PhantomData<&'a F>);
+
+impl<'a, F: RichField> BlockingEventLoop for EventLoop<'a, F> {
+    type Connection = TcpStream;
+    type StopReason = SingleThreadStopReason<u32>;
+    type Target = GdbTarget<'a, F>;
+
+    fn wait_for_stop_reason(
+        target: &mut Self::Target,
+        conn: &mut Self::Connection,
+    ) -> Result<
+        Event<Self::StopReason>,
+        WaitForStopReasonError<
+            <Self::Target as Target>::Error,
+            <Self::Connection as Connection>::Error,
+        >,
+    > {
+        let stop = match target.resume {
+            Resume::Step => target.debugger.step(1),
+            // Execute in slices, so that gdb can interrupt an endless loop.
+            Resume::Continue => loop {
+                let stop = target.debugger.step(INTERRUPT_POLL_STEPS);
+                if !matches!(stop, Ok(Stop::Stepped)) {
+                    break stop;
+                }
+                if conn.peek().map_or(true, |byte| byte.is_some()) {
+                    let byte = conn.read().map_err(WaitForStopReasonError::Connection)?;
+                    return Ok(Event::IncomingData(byte));
+                }
+            },
+        };
+        Ok(Event::TargetStopped(target.stop_reason(stop)))
+    }
+
+    fn on_interrupt(
+        _target: &mut Self::Target,
+    ) -> Result<Option<Self::StopReason>, <Self::Target as Target>::Error> {
+        Ok(Some(SingleThreadStopReason::Signal(Signal::SIGINT)))
+    }
+}
+
+/// Waits for gdb to connect on `addr`, and serves the session until gdb
+/// disconnects or the guest halts.
+///
+/// # Errors
+///
+/// Errors if listening fails, or the session fails with an error of the
+/// connection or of the protocol.
+pub fn serve_gdb<F: RichField>(debugger: Debugger<'_, F>, addr: impl ToSocketAddrs) -> Result<()> {
+    let listener = TcpListener::bind(addr)?;
+    eprintln!(
+        "Waiting for gdb, attach with `target remote {}`",
+        listener.local_addr()?
+    );
+    let (stream, peer) = listener.accept()?;
+    eprintln!("gdb connected from {peer}");
+    let mut target = GdbTarget::new(debugger);
+    let reason = GdbStub::new(stream)
+        .run_blocking::<EventLoop<F>>(&mut target)
+        .map_err(|error| anyhow!("gdb session failed: {error}"))?;
+    match reason {
+        DisconnectReason::TargetExited(code) => eprintln!("Guest halted with exit code {code}"),
+        DisconnectReason::TargetTerminated(signal) => eprintln!("Guest trapped ({signal:?})"),
+        DisconnectReason::Disconnect => eprintln!("gdb disconnected"),
+        DisconnectReason::Kill => eprintln!("gdb killed the guest"),
+    }
+    Ok(())
+}
@@ -1,6 +1,9 @@
 #[cfg(feature = "bench")]
 pub mod cli_benches;
+pub mod compression;
+pub mod debugger;
+pub mod gdb;
 pub mod runner;
 #[cfg(test)]
 mod tests;
-mod trace_utils;
+pub mod trace_diff;
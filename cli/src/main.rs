@@ -3,43 +3,64 @@
 
 use std::collections::HashMap;
 use std::io::{Read, Write};
+use std::num::NonZeroU64;
 use std::path::PathBuf;
 
-use anyhow::Result;
-use clap::{Parser, Subcommand};
+use anyhow::{ensure, Result};
+use clap::{Parser, Subcommand, ValueEnum};
 use clap_derive::Args;
 use clio::{Input, Output};
 use itertools::Itertools;
 use log::debug;
+use mozak_circuits::cost::{CostModel, MachineProfile};
+use mozak_circuits::cpu::columns::ExitCode;
+use mozak_circuits::generation::generate_traces;
 use mozak_circuits::memoryinit::generation::generate_elf_memory_init_trace;
 use mozak_circuits::program::generation::generate_program_rom_trace;
 use mozak_circuits::stark::batch_prover::batch_prove;
+use mozak_circuits::stark::ctl_graph::{ctl_edges, to_dot};
 use mozak_circuits::stark::mozak_stark::{
-    MozakStark, PublicInputs, TableKindArray, PUBLIC_TABLE_KINDS,
+    MozakStark, PublicInputs, TableKind, TableKindArray, PUBLIC_TABLE_KINDS,
 };
 use mozak_circuits::stark::proof::{AllProof, BatchProof};
-use mozak_circuits::stark::prover::prove;
+use mozak_circuits::stark::proof_envelope::{read_versioned, ProofEnvelope};
+use mozak_circuits::stark::prover::{prove, prove_with_traces};
 use mozak_circuits::stark::recursive_verifier::{
     circuit_data_for_recursion, recursive_batch_stark_circuit, recursive_mozak_stark_circuit,
-    shrink_to_target_degree_bits_circuit, VMRecursiveProofPublicInputs, VM_PUBLIC_INPUT_SIZE,
+    shrink_to_target_degree_bits_circuit, vm_verifier_circuit_data,
+    vm_verifier_circuit_data_to_bytes, VMRecursiveProofPublicInputs, VM_PUBLIC_INPUT_SIZE,
     VM_RECURSION_CONFIG, VM_RECURSION_THRESHOLD_DEGREE_BITS,
 };
+use mozak_circuits::stark::security::ConfigProfile;
+use mozak_circuits::stark::transcript::Transcript;
 use mozak_circuits::stark::utils::trace_rows_to_poly_values;
-use mozak_circuits::stark::verifier::verify_proof;
+use mozak_circuits::stark::verifier::{verify_proof, verify_single_table};
 use mozak_circuits::storage_device::generation::generate_call_tape_trace;
 use mozak_circuits::test_utils::{prove_and_verify_mozak_stark, C, D, F, S};
+use mozak_circuits::trace_cache::{TraceCache, TraceCacheKey};
+use mozak_circuits::trace_source::Execution;
 #[cfg(feature = "bench")]
 use mozak_cli::cli_benches::benches::BenchArgs;
+use mozak_cli::compression::{read_json, write_json};
+use mozak_cli::debugger::{debug_repl, parse_range};
+use mozak_cli::gdb::serve_gdb;
 use mozak_cli::runner::{
-    deserialize_system_tape, get_self_prog_id, load_program, raw_tapes_from_system_tape,
+    self, deserialize_system_tape, ensure_no_trap, get_self_prog_id, load_linked_program,
+    load_program, print_memory_stats, raw_tapes_from_system_tape, write_output_tape,
 };
+use mozak_cli::trace_diff::{diff_traces, Traces};
 use mozak_node::types::{Attestation, Transaction};
+use mozak_runner::debugger::{Debugger, Symbols};
+use mozak_runner::elf::Program;
+use mozak_runner::spike::commits;
 use mozak_runner::state::State;
-use mozak_runner::vm::step;
+use mozak_runner::vm::disasm::disassemble_program;
+use mozak_runner::vm::{step, ExecutionRecord};
 use mozak_sdk::common::types::{CrossProgramCall, ProgramIdentifier, SystemTape};
 use plonky2::field::types::Field;
 use plonky2::fri::oracle::PolynomialBatch;
 use plonky2::plonk::circuit_data::VerifierOnlyCircuitData;
+use plonky2::plonk::config::{GenericConfig, KeccakGoldilocksConfig};
 use plonky2::plonk::proof::ProofWithPublicInputs;
 use plonky2::util::timing::TimingTree;
 use starky::config::StarkConfig;
@@ -63,38 +84,205 @@ pub struct RunArgs {
     elf: Input,
     #[arg(long)]
     system_tape: Option<Input>,
+    /// After execution, print memory access statistics and the given number
+    /// of most accessed addresses.
+    #[arg(long, value_name = "TOP_N")]
+    mem_stats: Option<usize>,
+}
+
+#[derive(Clone, Debug, Args)]
+pub struct RunCommandArgs {
+    #[clap(flatten)]
+    run: RunArgs,
+    /// After execution, also generate the traces and check the constraints
+    /// and cross table lookups of all tables, without proving. Prints the
+    /// number of rows of each table.
+    #[arg(long)]
+    check_constraints: bool,
+    /// ELF of a library to link into the program, see `Program::link`.
+    #[arg(long)]
+    library: Option<Input>,
+    /// Write the bytes that the guest wrote to its output tape to this file.
+    #[arg(long)]
+    output_tape: Option<Output>,
+}
+
+/// Hasher used for the Merkle commitments and the Fiat-Shamir transcript of
+/// a proof.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum HasherKind {
+    #[default]
+    Poseidon2,
+    /// Cheaper to verify on-chain, but can not be verified recursively.
+    Keccak,
 }
 
 #[derive(Clone, Debug, Args)]
 pub struct ProveArgs {
     elf: Input,
     proof: Output,
+    /// Also write a proof that commits to all tables but the public ones
+    /// together, which is smaller than the proof of every table on its own.
     #[arg(long)]
     batch_proof: Option<Output>,
     #[arg(long)]
     system_tape: Option<Input>,
     recursive_proof: Option<Output>,
+    #[arg(long, value_enum, default_value_t)]
+    hasher: HasherKind,
+    /// Config to prove with: `production`, or `test` for quick proofs with
+    /// next to no security. The proof records the config, and verifiers
+    /// reject it unless they expect the same.
+    #[arg(long, default_value_t)]
+    config_profile: ConfigProfile,
+    /// Compress the proof and the batch proof with zstd.
+    #[arg(long)]
+    compress: bool,
+    /// Print the size of the proof of each table, and how much sharing
+    /// repeated Merkle caps would save. With `--batch-proof`, also print the
+    /// size of the batch proof.
+    #[arg(long)]
+    report_size: bool,
+    /// Halt the guest out of gas after this many instructions. The proof
+    /// shows whether it halted or ran out of gas.
+    #[arg(long)]
+    max_cycles: Option<NonZeroU64>,
+    /// Directory to cache generated traces in, keyed by the ELF, the system
+    /// tape and the cycle budget. The guest still executes, but proving the
+    /// same execution again skips trace generation.
+    #[arg(long)]
+    trace_cache: Option<PathBuf>,
+    /// Leave the precompile tables that the guest does not use out of the
+    /// proof. The recursive proof needs every table.
+    #[arg(long, conflicts_with = "recursive_proof")]
+    skip_unused_tables: bool,
+    /// ELF of a library to link into the program. The proof commits to it
+    /// separately from the program, and the library id identifies it.
+    #[arg(long)]
+    library: Option<Input>,
+    /// Write the bytes that the guest wrote to its output tape to this file.
+    /// The proof commits to their digest.
+    #[arg(long)]
+    output_tape: Option<Output>,
+    /// Write the Fiat-Shamir transcript of the proof to this file: every
+    /// value the prover observes and every challenge it samples, one per
+    /// line, to diff against `verify --transcript`.
+    #[arg(long)]
+    transcript: Option<Output>,
+}
+
+#[derive(Clone, Debug, Args)]
+pub struct DumpTraceArgs {
+    #[clap(flatten)]
+    run: RunArgs,
+    /// Output file path of the traces of all tables, as JSON.
+    trace: Output,
+    /// Compress the traces with zstd.
+    #[arg(long)]
+    compress: bool,
 }
 
 #[derive(Clone, Debug, Subcommand)]
 enum Command {
     /// Decode a given ELF and prints the program
     Decode { elf: Input },
+    /// Disassemble the code of a given ELF, with the names of its symbols.
+    Disasm {
+        elf: Input,
+        /// Only disassemble the addresses in start..end, or the instruction
+        /// at a single address. Addresses may be symbols.
+        #[arg(long)]
+        range: Option<String>,
+    },
     /// Decode and execute a given ELF. Prints the final state of
     /// the registers
-    Run(RunArgs),
+    Run(RunCommandArgs),
+    /// Execute a given ELF interactively: stop at breakpoints by pc or
+    /// symbol, single step, and inspect registers and memory. Type `help` at
+    /// the prompt for the commands.
+    Debug {
+        elf: Input,
+        #[arg(long)]
+        system_tape: Option<Input>,
+        /// Instead of prompting, wait for gdb or lldb to attach on this
+        /// address, eg `127.0.0.1:9001`.
+        #[arg(long, value_name = "ADDR")]
+        gdb: Option<String>,
+    },
     /// Prove and verify the execution of a given ELF
     ProveAndVerify(RunArgs),
+    /// Execute a given ELF, and check the constraints and cross table lookups
+    /// of all tables without proving. Much faster than proving. Same as
+    /// `run --check-constraints`.
+    DryRun(RunArgs),
     /// Prove the execution of given ELF and write proof to file.
     Prove(ProveArgs),
-    /// Verify the given proof from file.
-    Verify { proof: Input },
+    /// Execute a given ELF and write the traces of all tables to file.
+    DumpTrace(DumpTraceArgs),
+    /// Execute a given ELF and write a log of the instructions it executes in
+    /// the format of `spike -l --log-commits`, for diffing against Spike.
+    SpikeLog {
+        #[clap(flatten)]
+        run: RunArgs,
+        log: Output,
+    },
+    /// Execute a given ELF and estimate what it costs to prove: the rows each
+    /// operation adds to the largest tables, and the proving time.
+    Cost {
+        #[clap(flatten)]
+        run: RunArgs,
+        /// JSON file with the `nanos_per_cell` of the proving machine.
+        /// Defaults to a rough figure for a single laptop core.
+        #[arg(long)]
+        profile: Option<Input>,
+        /// Number of operations to list, costliest first.
+        #[arg(long, default_value_t = 20)]
+        top: usize,
+    },
+    /// Compare two trace dumps, compressed or not, and report the rows and
+    /// columns of each table that differ.
+    DiffTraces { old: Input, new: Input },
+    /// Verify the given proof from file, compressed or not.
+    Verify {
+        proof: Input,
+        /// Hasher the proof was generated with.
+        #[arg(long, value_enum, default_value_t)]
+        hasher: HasherKind,
+        /// Config the proof has to be made with, and the security policy it
+        /// has to meet, see `prove --config-profile`.
+        #[arg(long, default_value_t)]
+        config_profile: ConfigProfile,
+        /// Only verify the proof of this table, eg `cpu`, without the cross
+        /// table lookups. Meant for debugging proofs that fail to verify.
+        #[arg(long)]
+        table: Option<TableKind>,
+        /// Write the Fiat-Shamir transcript that the verifier recomputes to
+        /// this file, even if the proof does not verify, to diff against
+        /// `prove --transcript`.
+        #[arg(long)]
+        transcript: Option<Output>,
+    },
+    /// Print the cross table lookups between the tables, one line per
+    /// looking and looked table.
+    CtlGraph {
+        /// Print a graphviz graph instead, eg for `dot -Tsvg`.
+        #[arg(long)]
+        dot: bool,
+    },
     /// Verify the given recursive proof from file.
     VerifyRecursiveProof {
         proof: Input,
         verifier_key: Input,
         program_id: String,
     },
+    /// Emits the plonky2 `VerifierCircuitData` of recursive proofs, for
+    /// verifying them inside other plonky2 circuits.
+    ExportVerifierCircuitData {
+        /// Verifier key of the recursive proof.
+        verifier_key: Input,
+        /// Output file path of the serialized `VerifierCircuitData`.
+        circuit_data: Output,
+    },
     /// Builds a transaction bundle.
     BundleTransaction {
         /// System tape generated from native execution.
@@ -115,6 +303,112 @@ enum Command {
     Bench(BenchArgs),
 }
 
+/// Proves `record` with the hasher of `Config`, from `traces` if given, and
+/// writes the proof (and optionally the batch proof) as JSON, compressed if
+/// `compress` is set, and prints their size reports if `report_size` is set.
+///
+/// Returns the proofs, so they can be wrapped recursively.
+#[allow(clippy::type_complexity)]
+#[allow(clippy::too_many_arguments)]
+fn write_proofs<Config: GenericConfig<D, F = F>>(
+    program: &Program,
+    record: &ExecutionRecord<F>,
+    stark: &S,
+    config: &StarkConfig,
+    public_inputs: PublicInputs<F>,
+    traces: Option<&Traces<F>>,
+    proof: Output,
+    batch_proof: Option<Output>,
+    compress: bool,
+    report_size: bool,
+) -> Result<(
+    AllProof<F, Config, D>,
+    Option<(BatchProof<F, Config, D>, TableKindArray<usize>)>,
+)> {
+    let all_proof = match traces {
+        Some(traces) => prove_with_traces::<F, Config, D>(
+            stark,
+            config,
+            public_inputs,
+            traces,
+            None,
+            &mut TimingTree::default(),
+        )?,
+        None => prove::<F, Config, D>(
+            program,
+            record,
+            stark,
+            config,
+            public_inputs,
+            &mut TimingTree::default(),
+        )?,
+    };
+    if report_size {
+        println!("{}", all_proof.size_report());
+    }
+    write_json(proof, &ProofEnvelope::new(&all_proof), compress)?;
+
+    let batch = if let Some(batch_proof_output) = batch_proof {
+        let (batch_all_proof, degree_bits) = batch_prove::<F, Config, D>(
+            program,
+            record,
+            stark,
+            &PUBLIC_TABLE_KINDS,
+            config,
+            public_inputs,
+            &mut TimingTree::default(),
+        )?;
+        if report_size {
+            println!("{}", batch_all_proof.size_report(&PUBLIC_TABLE_KINDS));
+        }
+        write_json(
+            batch_proof_output,
+            &ProofEnvelope::new(&batch_all_proof),
+            compress,
+        )?;
+        Some((batch_all_proof, degree_bits))
+    } else {
+        None
+    };
+    Ok((all_proof, batch))
+}
+
+/// Verifies the proof in `proof` with the config of `profile`, or only the
+/// proof of `table` if given, and returns the exit code it claims.
+fn verify_proof_file<Config: GenericConfig<D, F = F>>(
+    proof: Input,
+    stark: &S,
+    table: Option<TableKind>,
+    profile: ConfigProfile,
+) -> Result<ExitCode> {
+    let config = &profile.config();
+    let (version, all_proof): (_, AllProof<F, Config, D>) = read_versioned(read_json(proof)?)?;
+    debug!("Read a version {version} proof");
+    profile.policy().check_proof(config, &all_proof)?;
+    let exit_code = all_proof.public_value(stark)?;
+    match table {
+        Some(kind) => verify_single_table(stark, kind, &all_proof, config)?,
+        None => verify_proof(stark, all_proof, config)?,
+    }
+    Ok(exit_code)
+}
+
+/// Makes `stark` record its transcript if it is to be written to `output`.
+fn recording_transcript(stark: S, output: Option<&Output>) -> S {
+    match output {
+        Some(_) => stark.with_transcript(Transcript::recording()),
+        None => stark,
+    }
+}
+
+/// Writes the transcript that `stark` recorded to `output`, if given.
+fn write_transcript(stark: &S, output: Option<Output>) -> Result<()> {
+    if let Some(mut output) = output {
+        write!(output, "{}", stark.transcript)?;
+    }
+    Ok(())
+}
+
 /// Run me eg like `cargo run -- -vvv run vm/tests/testdata/rv32ui-p-addi
 /// iotape.txt`
 #[allow(clippy::too_many_lines)]
@@ -129,14 +423,68 @@ fn main() -> Result<()> {
             let program = load_program(elf)?;
             debug!("{program:?}");
         }
-        Command::Run(RunArgs { elf, system_tape }) => {
-            let program = load_program(elf).unwrap();
+        Command::Run(RunCommandArgs {
+            run:
+                RunArgs {
+                    elf,
+                    system_tape,
+                    mem_stats,
+                },
+            check_constraints,
+            library,
+            output_tape,
+        }) => {
+            let program = load_linked_program(elf, library)?;
             let self_prog_id = get_self_prog_id::<F, C, D>(&program, &config);
             let raw_tapes = raw_tapes_from_system_tape(system_tape, self_prog_id);
             let state: State<F> = State::new(program.clone(), raw_tapes);
-            step(&program, state)?;
+            let record = step(&program, state)?;
+            print_memory_stats(&record, mem_stats);
+            ensure_no_trap(&record)?;
+            write_output_tape(&record, output_tape)?;
+            if check_constraints {
+                runner::check_constraints::<F, D>(&program, &record)?;
+            }
+        }
+        Command::Disasm { mut elf, range } => {
+            let mut elf_bytes = vec![];
+            elf.read_to_end(&mut elf_bytes)?;
+            let program = Program::mozak_load_program(&elf_bytes)?;
+            let symbols = Symbols::from_elf(&elf_bytes)?;
+            let range = match range {
+                Some(range) => parse_range(&symbols, Some(&range))?,
+                None => 0..u32::MAX,
+            };
+            print!("{}", disassemble_program(&program, &symbols, range));
+        }
+        Command::Debug {
+            mut elf,
+            system_tape,
+            gdb,
+        } => {
+            let mut elf_bytes = vec![];
+            elf.read_to_end(&mut elf_bytes)?;
+            let program = Program::mozak_load_program(&elf_bytes)?;
+            let symbols = Symbols::from_elf(&elf_bytes)?;
+            let self_prog_id = get_self_prog_id::<F, C, D>(&program, &config);
+            let raw_tapes = raw_tapes_from_system_tape(system_tape, self_prog_id);
+            let state: State<F> = State::new(program.clone(), raw_tapes);
+            let mut debugger = Debugger::new(&program, state);
+            match gdb {
+                Some(addr) => serve_gdb(debugger, addr)?,
+                None => debug_repl(
+                    &mut debugger,
+                    &symbols,
+                    std::io::stdin().lock(),
+                    std::io::stdout(),
+                )?,
+            }
         }
-        Command::ProveAndVerify(RunArgs { elf, system_tape }) => {
+        Command::ProveAndVerify(RunArgs {
+            elf,
+            system_tape,
+            mem_stats,
+        }) => {
             let program = load_program(elf).unwrap();
             let self_prog_id = get_self_prog_id::<F, C, D>(&program, &config);
 
@@ -144,135 +492,289 @@ fn main() -> Result<()> {
 
             let state = State::new(program.clone(), raw_tapes);
             let record = step(&program, state)?;
+            print_memory_stats(&record, mem_stats);
+            ensure_no_trap(&record)?;
             prove_and_verify_mozak_stark(&program, &record, &config)?;
         }
-        Command::Prove(ProveArgs {
+        Command::DryRun(RunArgs {
             elf,
             system_tape,
-            mut proof,
-            recursive_proof,
-            batch_proof,
+            mem_stats,
         }) => {
             let program = load_program(elf).unwrap();
             let self_prog_id = get_self_prog_id::<F, C, D>(&program, &config);
             let raw_tapes = raw_tapes_from_system_tape(system_tape, self_prog_id);
             let state = State::new(program.clone(), raw_tapes);
             let record = step(&program, state)?;
+            print_memory_stats(&record, mem_stats);
+            ensure_no_trap(&record)?;
+            runner::check_constraints::<F, D>(&program, &record)?;
+        }
+        Command::DumpTrace(DumpTraceArgs {
+            run:
+                RunArgs {
+                    elf,
+                    system_tape,
+                    mem_stats,
+                },
+            trace,
+            compress,
+        }) => {
+            let program = load_program(elf).unwrap();
+            let self_prog_id = get_self_prog_id::<F, C, D>(&program, &config);
+            let raw_tapes = raw_tapes_from_system_tape(system_tape, self_prog_id);
+            let state = State::new(program.clone(), raw_tapes);
+            let record = step(&program, state)?;
+            print_memory_stats(&record, mem_stats);
+            ensure_no_trap(&record)?;
+            let traces = generate_traces::<F, D>(
+                &Execution::new(&program, &record),
+                &mut TimingTree::default(),
+            );
+            write_json(trace, &traces, compress)?;
+        }
+        Command::SpikeLog {
+            run:
+                RunArgs {
+                    elf,
+                    system_tape,
+                    mem_stats,
+                },
+            mut log,
+        } => {
+            let program = load_program(elf).unwrap();
+            let self_prog_id = get_self_prog_id::<F, C, D>(&program, &config);
+            let raw_tapes = raw_tapes_from_system_tape(system_tape, self_prog_id);
+            let state = State::new(program.clone(), raw_tapes);
+            let record = step(&program, state)?;
+            print_memory_stats(&record, mem_stats);
+            for commit in commits(&program, &record) {
+                writeln!(log, "{commit}")?;
+            }
+        }
+        Command::Cost {
+            run:
+                RunArgs {
+                    elf,
+                    system_tape,
+                    mem_stats,
+                },
+            profile,
+            top,
+        } => {
+            let program = load_program(elf).unwrap();
+            let self_prog_id = get_self_prog_id::<F, C, D>(&program, &config);
+            let raw_tapes = raw_tapes_from_system_tape(system_tape, self_prog_id);
+            let state = State::new(program.clone(), raw_tapes);
+            let record = step(&program, state)?;
+            print_memory_stats(&record, mem_stats);
+            let profile: MachineProfile = match profile {
+                Some(profile) => read_json(profile)?,
+                None => MachineProfile::default(),
+            };
+            let report = CostModel::new(&MozakStark::<F, D>::default()).report(&record);
+            println!("{}", report.display(top, &profile));
+        }
+        Command::DiffTraces { old, new } => {
+            let old: Traces<F> = read_json(old)?;
+            let new: Traces<F> = read_json(new)?;
+            let diffs = diff_traces(&old, &new);
+            for diff in &diffs {
+                println!("{diff}");
+            }
+            ensure!(diffs.is_empty(), "traces differ in {} tables", diffs.len());
+        }
+        Command::Prove(ProveArgs {
+            elf,
+            system_tape,
+            proof,
+            recursive_proof,
+            batch_proof,
+            hasher,
+            config_profile,
+            compress,
+            report_size,
+            max_cycles,
+            trace_cache,
+            skip_unused_tables,
+            library,
+            output_tape,
+            transcript,
+        }) => {
+            let config = config_profile.config();
+            let linked = library.is_some();
+            let program = load_linked_program(elf, library)?;
+            // The program id depends on the hasher, so the guest has to read the
+            // one of the config that proves it.
+            let self_prog_id = match hasher {
+                HasherKind::Keccak =>
+                    get_self_prog_id::<F, KeccakGoldilocksConfig, D>(&program, &config),
+                HasherKind::Poseidon2 => get_self_prog_id::<F, C, D>(&program, &config),
+            };
+            let raw_tapes = raw_tapes_from_system_tape(system_tape, self_prog_id);
+            let trace_cache_key = TraceCacheKey::new::<F>(&program, &raw_tapes, max_cycles);
+            let mut state = State::new(program.clone(), raw_tapes);
+            state.max_cycles = max_cycles;
+            let record = step(&program, state)?;
+            ensure_no_trap(&record)?;
+            write_output_tape(&record, output_tape)?;
+            if record.last_state.out_of_gas {
+                eprintln!("Guest ran out of gas at clk {}", record.last_state.clk);
+            }
             let stark = if cli.debug {
                 MozakStark::default_debug()
             } else {
                 MozakStark::default()
             };
-            let public_inputs = PublicInputs {
-                entry_point: F::from_canonical_u32(program.entry_point),
+            let stark = if skip_unused_tables {
+                stark.skipping_unused_tables()
+            } else {
+                stark
             };
+            let stark = recording_transcript(stark, transcript.as_ref());
+            let public_inputs = PublicInputs::for_execution(&program, &record);
+            let traces = trace_cache
+                .map(|dir| {
+                    TraceCache::open(dir)?.get_or_generate(&trace_cache_key, || {
+                        generate_traces::<F, D>(
+                            &Execution::new(&program, &record),
+                            &mut TimingTree::default(),
+                        )
+                    })
+                })
+                .transpose()?;
 
-            let all_proof = prove::<F, C, D>(
-                &program,
-                &record,
-                &stark,
-                &config,
-                public_inputs,
-                &mut TimingTree::default(),
-            )?;
+            match hasher {
+                HasherKind::Keccak => {
+                    // Recursion needs an algebraic hasher to verify the proof in-circuit.
+                    ensure!(
+                        recursive_proof.is_none(),
+                        "recursive proofs are only supported with `--hasher poseidon2`"
+                    );
+                    let (all_proof, _) = write_proofs::<KeccakGoldilocksConfig>(
+                        &program,
+                        &record,
+                        &stark,
+                        &config,
+                        public_inputs,
+                        traces.as_ref(),
+                        proof,
+                        batch_proof,
+                        compress,
+                        report_size,
+                    )?;
+                    if linked {
+                        println!("library id: {:?}", all_proof.library_id());
+                    }
+                }
+                HasherKind::Poseidon2 => {
+                    let (all_proof, batch) = write_proofs::<C>(
+                        &program,
+                        &record,
+                        &stark,
+                        &config,
+                        public_inputs,
+                        traces.as_ref(),
+                        proof,
+                        batch_proof,
+                        compress,
+                        report_size,
+                    )?;
+                    if linked {
+                        println!("library id: {:?}", all_proof.library_id());
+                    }
 
-            let serialized = serde_json::to_string(&all_proof).unwrap();
-            proof.write_all(serialized.as_bytes())?;
+                    // Generate recursive proof
+                    if let Some(mut recursive_proof_output) = recursive_proof {
+                        let (verifier_only, common, recursive_all_proof) =
+                            if let Some((batch_proof, degree_bits)) = batch {
+                                let recursive_circuit = recursive_batch_stark_circuit(
+                                    &stark,
+                                    &degree_bits,
+                                    &PUBLIC_TABLE_KINDS,
+                                    &VM_RECURSION_CONFIG,
+                                    &config,
+                                );
+                                let verifier_only = recursive_circuit.circuit.verifier_only.clone();
+                                let common = recursive_circuit.circuit.common.clone();
+                                let recursive_proof =
+                                    recursive_circuit.prove(&batch_proof).unwrap();
+                                (verifier_only, common, recursive_proof)
+                            } else {
+                                let degree_bits = all_proof.degree_bits(&config);
+                                let recursive_circuit = recursive_mozak_stark_circuit::<F, C, D>(
+                                    &stark,
+                                    &degree_bits,
+                                    &VM_RECURSION_CONFIG,
+                                    &config,
+                                );
+                                let verifier_only = recursive_circuit.circuit.verifier_only.clone();
+                                let common = recursive_circuit.circuit.common.clone();
+                                let recursive_proof = recursive_circuit.prove(&all_proof).unwrap();
+                                (verifier_only, common, recursive_proof)
+                            };
 
-            let mut batch_all_proof: Option<BatchProof<F, C, D>> = None;
-            let mut batch_degree_bits: Option<TableKindArray<usize>> = None;
-            if let Some(mut batch_proof_output) = batch_proof {
-                let (proof, degree_bits) = batch_prove::<F, C, D>(
-                    &program,
-                    &record,
-                    &stark,
-                    &PUBLIC_TABLE_KINDS,
-                    &config,
-                    public_inputs,
-                    &mut TimingTree::default(),
-                )?;
-                batch_all_proof = Some(proof);
-                batch_degree_bits = Some(degree_bits);
-                let serialized = serde_json::to_string(&batch_all_proof).unwrap();
-                batch_proof_output.write_all(serialized.as_bytes())?;
-            }
+                        println!(
+                            "Recursive proof size: {}",
+                            recursive_all_proof.to_bytes().len()
+                        );
+                        let public_inputs_array: [F; VM_PUBLIC_INPUT_SIZE] = recursive_all_proof
+                            .public_inputs
+                            .clone()
+                            .try_into()
+                            .unwrap();
 
-            // Generate recursive proof
-            if let Some(mut recursive_proof_output) = recursive_proof {
-                let (verifier_only, common, recursive_all_proof) =
-                    if let Some(batch_proof) = batch_all_proof {
-                        let degree_bits = batch_degree_bits.unwrap();
-                        let recursive_circuit = recursive_batch_stark_circuit(
-                            &stark,
-                            &degree_bits,
-                            &PUBLIC_TABLE_KINDS,
-                            &VM_RECURSION_CONFIG,
-                            &config,
+                        let public_inputs: VMRecursiveProofPublicInputs<F> =
+                            public_inputs_array.into();
+                        debug_assert_eq!(
+                            public_inputs.program_hash_as_bytes.to_vec(),
+                            self_prog_id
+                                .inner()
+                                .into_iter()
+                                .map(F::from_canonical_u8)
+                                .collect_vec()
                         );
-                        let verifier_only = recursive_circuit.circuit.verifier_only.clone();
-                        let common = recursive_circuit.circuit.common.clone();
-                        let recursive_proof = recursive_circuit.prove(&batch_proof).unwrap();
-                        (verifier_only, common, recursive_proof)
-                    } else {
-                        let degree_bits = all_proof.degree_bits(&config);
-                        let recursive_circuit = recursive_mozak_stark_circuit::<F, C, D>(
-                            &stark,
-                            &degree_bits,
+
+                        let (final_circuit, final_proof) = shrink_to_target_degree_bits_circuit(
+                            &verifier_only,
+                            &common,
                             &VM_RECURSION_CONFIG,
-                            &config,
+                            VM_RECURSION_THRESHOLD_DEGREE_BITS,
+                            &recursive_all_proof,
+                        )?;
+                        assert_eq!(
+                            final_circuit.circuit.common.num_public_inputs,
+                            VM_PUBLIC_INPUT_SIZE
                         );
-                        let verifier_only = recursive_circuit.circuit.verifier_only.clone();
-                        let common = recursive_circuit.circuit.common.clone();
-                        let recursive_proof = recursive_circuit.prove(&all_proof).unwrap();
-                        (verifier_only, common, recursive_proof)
-                    };
-
-                println!(
-                    "Recursive proof size: {}",
-                    recursive_all_proof.to_bytes().len()
-                );
-                let public_inputs_array: [F; VM_PUBLIC_INPUT_SIZE] = recursive_all_proof
-                    .public_inputs
-                    .clone()
-                    .try_into()
-                    .unwrap();
-
-                let public_inputs: VMRecursiveProofPublicInputs<F> = public_inputs_array.into();
-                debug_assert_eq!(
-                    public_inputs.program_hash_as_bytes.to_vec(),
-                    self_prog_id
-                        .inner()
-                        .into_iter()
-                        .map(F::from_canonical_u8)
-                        .collect_vec()
-                );
-
-                let (final_circuit, final_proof) = shrink_to_target_degree_bits_circuit(
-                    &verifier_only,
-                    &common,
-                    &VM_RECURSION_CONFIG,
-                    VM_RECURSION_THRESHOLD_DEGREE_BITS,
-                    &recursive_all_proof,
-                )?;
-                assert_eq!(
-                    final_circuit.circuit.common.num_public_inputs,
-                    VM_PUBLIC_INPUT_SIZE
-                );
 
-                let s = final_proof.to_bytes();
-                recursive_proof_output.write_all(&s)?;
+                        let s = final_proof.to_bytes();
+                        recursive_proof_output.write_all(&s)?;
 
-                // Generate the verifier key file
-                let mut vk_output_path = recursive_proof_output.path().clone();
-                vk_output_path.set_extension("vk");
-                let mut vk_output = vk_output_path.create()?;
+                        // Generate the verifier key file
+                        let mut vk_output_path = recursive_proof_output.path().clone();
+                        vk_output_path.set_extension("vk");
+                        let mut vk_output = vk_output_path.create()?;
 
-                let bytes = final_circuit.circuit.verifier_only.to_bytes().unwrap();
-                vk_output.write_all(&bytes)?;
+                        let bytes = final_circuit.circuit.verifier_only.to_bytes().unwrap();
+                        vk_output.write_all(&bytes)?;
+                    }
+                }
             }
+            write_transcript(&stark, transcript)?;
 
             debug!("proof generated successfully!");
         }
+        Command::ExportVerifierCircuitData {
+            mut verifier_key,
+            mut circuit_data,
+        } => {
+            let mut vk_buffer: Vec<u8> = vec![];
+            verifier_key.read_to_end(&mut vk_buffer)?;
+            let verifier_only = VerifierOnlyCircuitData::from_bytes(vk_buffer).unwrap();
+            let data = vm_verifier_circuit_data::<F, C, D>(verifier_only);
+            circuit_data.write_all(&vm_verifier_circuit_data_to_bytes(&data)?)?;
+            println!("Verifier circuit data exported successfully!");
+        }
         Command::BundleTransaction {
             system_tape: system_tape_path,
             bundle,
@@ -397,13 +899,41 @@ fn main() -> Result<()> {
             println!("Transaction bundled: {transaction:?}");
         }
 
-        Command::Verify { mut proof } => {
+        Command::Verify {
+            proof,
+            hasher,
+            config_profile,
+            table,
+            transcript,
+        } => {
+            let stark = recording_transcript(S::default(), transcript.as_ref());
+            let verified = match hasher {
+                HasherKind::Poseidon2 =>
+                    verify_proof_file::<C>(proof, &stark, table, config_profile),
+                HasherKind::Keccak => verify_proof_file::<KeccakGoldilocksConfig>(
+                    proof,
+                    &stark,
+                    table,
+                    config_profile,
+                ),
+            };
+            write_transcript(&stark, transcript)?;
+            let ExitCode(exit_code) = verified?;
+            match table {
+                Some(kind) => println!("{kind:?} proof verified successfully!"),
+                None => println!("proof verified successfully!"),
+            }
+            println!("exit code: {exit_code}");
+        }
+        Command::CtlGraph { dot } => {
             let stark = S::default();
-            let mut buffer: Vec<u8> = vec![];
-            proof.read_to_end(&mut buffer)?;
-            let all_proof: AllProof<F, C, D> = serde_json::from_slice(&buffer)?;
-            verify_proof(&stark, all_proof, &config)?;
-            println!("proof verified successfully!");
+            if dot {
+                print!("{}", to_dot(&stark.cross_table_lookups));
+            } else {
+                for edge in ctl_edges(&stark.cross_table_lookups) {
+                    println!("{edge}");
+                }
+            }
         }
         Command::VerifyRecursiveProof {
             mut proof,
@@ -439,6 +969,7 @@ fn main() -> Result<()> {
                     .collect_vec()
             );
             println!("Public Inputs: {:?}", proof.public_inputs);
+            println!("Exit code: {}", public_inputs.exit_code);
             println!("Verifier Key: {:?}", circuit.verifier_only);
 
             circuit.verify(proof.clone())?;
@@ -1,28 +1,29 @@
 //! Utility functions that helps the CLI to interact with the
 //! [Mozak runner crate](mozak_runner).
 use std::collections::BTreeSet;
+use std::panic::{catch_unwind, AssertUnwindSafe};
 
-use anyhow::Result;
+use anyhow::{anyhow, bail, Result};
 use itertools::{izip, Itertools};
 use log::debug;
-use mozak_circuits::memoryinit::generation::generate_elf_memory_init_trace;
-use mozak_circuits::program::generation::generate_program_rom_trace;
-use mozak_circuits::stark::prover::get_program_id;
+use mozak_circuits::stark::mozak_stark::{MozakStark, PublicInputs};
+use mozak_circuits::stark::prover::{prove_dry_run, StaticTableCommitments};
 use mozak_runner::elf::Program;
-use mozak_runner::state::RawTapes;
+use mozak_runner::memory_stats::MemoryAccessStats;
+use mozak_runner::state::{PrivateTapes, RawTapes};
+use mozak_runner::vm::ExecutionRecord;
 use mozak_sdk::common::merkle::merkleize;
 use mozak_sdk::common::types::{
     CanonicalOrderedTemporalHints, Poseidon2Hash, ProgramIdentifier, SystemTape,
 };
 use plonky2::field::extension::Extendable;
 use plonky2::hash::hash_types::RichField;
-use plonky2::plonk::config::{AlgebraicHasher, GenericConfig};
+use plonky2::plonk::config::GenericConfig;
+use plonky2::util::timing::TimingTree;
 use rkyv::rancor::{Panic, Strategy};
 use rkyv::ser::AllocSerializer;
 use starky::config::StarkConfig;
 
-use crate::trace_utils::get_trace_merkle_cap;
-
 pub fn load_program<F: std::io::Read>(mut elf: F) -> Result<Program> {
     let mut elf_bytes = Vec::new();
     let bytes_read = elf.read_to_end(&mut elf_bytes)?;
@@ -30,6 +31,89 @@ pub fn load_program<F: std::io::Read>(mut elf: F) -> Result<Program> {
     Program::mozak_load_program(&elf_bytes)
 }
 
+/// Loads the program in `elf`, and links the library in `library` into it,
+/// if given, see [`Program::link`].
+///
+/// # Errors
+///
+/// Errors if either ELF is invalid, or if the library overlaps the program.
+pub fn load_linked_program<F: std::io::Read>(elf: F, library: Option<F>) -> Result<Program> {
+    let program = load_program(elf)?;
+    match library {
+        Some(library) => program.link(load_program(library)?),
+        None => Ok(program),
+    }
+}
+
+/// Surfaces a failed guest assertion, see [`mozak_sdk::core::trap`].
+///
+/// # Errors
+///
+/// Errors if the execution halted with a trap instead of a regular halt.
+pub fn ensure_no_trap<F: RichField>(record: &ExecutionRecord<F>) -> Result<()> {
+    if let Some(report) = &record.last_state.trap {
+        bail!("guest trapped at clk {}: {report}", record.last_state.clk);
+    }
+    Ok(())
+}
+
+/// Writes the bytes that the guest wrote to its output tape with
+/// [`IO_WRITE`](mozak_sdk::core::ecall::IO_WRITE), if `output` is given.
+///
+/// # Errors
+///
+/// Errors if writing to `output` fails.
+pub fn write_output_tape<F: RichField>(
+    record: &ExecutionRecord<F>,
+    output: Option<impl std::io::Write>,
+) -> Result<()> {
+    if let Some(mut output) = output {
+        output.write_all(&record.last_state.output_tape.iter().copied().collect_vec())?;
+    }
+    Ok(())
+}
+
+/// Prints the memory access totals and the `top` hottest addresses of an
+/// execution, if `top` is given.
+pub fn print_memory_stats<F: RichField>(record: &ExecutionRecord<F>, top: Option<usize>) {
+    if let Some(top) = top {
+        println!("{}", MemoryAccessStats::from_record(record).report(top));
+    }
+}
+
+/// Generates the traces of `record` and checks the constraints and cross
+/// table lookups of all tables, without proving, see [`prove_dry_run`].
+/// Prints the number of rows of each table, and whether the checks pass.
+///
+/// # Errors
+///
+/// Errors if any constraint or cross table lookup fails. The failing rows are
+/// logged.
+pub fn check_constraints<F, const D: usize>(
+    program: &Program,
+    record: &ExecutionRecord<F>,
+) -> Result<()>
+where
+    F: RichField + Extendable<D>, {
+    let public_inputs = PublicInputs::new(F::from_canonical_u32(program.entry_point));
+    // The checks panic on the first failure, which we report as an error.
+    let rows = catch_unwind(AssertUnwindSafe(|| {
+        prove_dry_run(
+            program,
+            record,
+            &MozakStark::default(),
+            &public_inputs,
+            &mut TimingTree::default(),
+        )
+    }))
+    .map_err(|_| anyhow!("constraints or cross table lookups failed, see the log"))?;
+    for (rows, kind) in rows.with_kind().iter() {
+        println!("{:<24} {rows:>10} rows", format!("{kind:?}"));
+    }
+    println!("All constraints and cross table lookups hold.");
+    Ok(())
+}
+
 /// Deserializes a serde JSON serialized system tape binary file into a
 /// [`SystemTape`].
 ///
@@ -119,16 +203,19 @@ pub fn raw_tapes_from_system_tape<F: std::io::Read>(
             length_prefixed_bytes(tape_bytes, dgb_string)
         }
 
+        // Each program of the execution only gets to read its own private
+        // tape, which is empty if nobody wrote to it.
+        let mut private_tapes = sys.private_input_tape.writer.clone();
+        private_tapes.entry(self_prog_id).or_default();
+        let private_tapes = PrivateTapes(
+            private_tapes
+                .into_iter()
+                .map(|(program, tape)| (program, length_prefixed_bytes(tape.0, "PRIVATE_TAPE")))
+                .collect(),
+        );
+
         RawTapes {
-            private_tape: length_prefixed_bytes(
-                sys.private_input_tape
-                    .writer
-                    .get(&self_prog_id)
-                    .cloned()
-                    .unwrap_or_default()
-                    .0,
-                "PRIVATE_TAPE",
-            ),
+            private_tape: vec![],
             public_tape: length_prefixed_bytes(
                 sys.public_input_tape
                     .writer
@@ -144,25 +231,23 @@ pub fn raw_tapes_from_system_tape<F: std::io::Read>(
             events_commitment_tape,
             cast_list_commitment_tape,
         }
+        .with_private_tape_from(&private_tapes)
     }
 }
 
-/// Computes `[ProgramIdentifer]` from hash of entry point and merkle caps
-/// of `ElfMemoryInit` and `ProgramRom` tables.
+/// Computes the [`ProgramIdentifier`] of proofs of `program` made with the
+/// config `C`, see
+/// [`get_program_id`](mozak_circuits::stark::prover::get_program_id).
+///
+/// The id depends on the hasher of `C`, so it has to match the config that
+/// proves the execution.
 pub fn get_self_prog_id<F, C, const D: usize>(
     program: &Program,
     config: &StarkConfig,
 ) -> ProgramIdentifier
 where
     F: RichField + Extendable<D>,
-    C: GenericConfig<D, F = F>,
-    C::Hasher: AlgebraicHasher<F>, {
-    let entry_point = F::from_canonical_u32(program.entry_point);
-
-    let elf_memory_init_trace = generate_elf_memory_init_trace::<F>(program);
-    let program_rom_trace = generate_program_rom_trace::<F>(program);
-
-    let elf_memory_init_cap = get_trace_merkle_cap::<F, C, D, _>(elf_memory_init_trace, config);
-    let program_cap = get_trace_merkle_cap::<F, C, D, _>(program_rom_trace, config);
-    get_program_id::<F, C, D>(entry_point, &program_cap, &elf_memory_init_cap)
+    C: GenericConfig<D, F = F>, {
+    StaticTableCommitments::<F, C, D>::new(program, config)
+        .program_id(F::from_canonical_u32(program.entry_point))
 }
@@ -0,0 +1,22 @@
+use crate::compression::{read_json, write_json};
+
+fn round_trip(compress: bool) -> (Vec<u8>, Vec<u64>) {
+    let value: Vec<u64> = (0..1024).collect();
+    let mut bytes = vec![];
+    write_json(&mut bytes, &value, compress).unwrap();
+    let read: Vec<u64> = read_json(bytes.as_slice()).unwrap();
+    assert_eq!(read, value);
+    (bytes, value)
+}
+
+#[test]
+fn round_trip_plain() {
+    let (bytes, value) = round_trip(false);
+    assert_eq!(bytes, serde_json::to_vec(&value).unwrap());
+}
+
+#[test]
+fn round_trip_compressed() {
+    let (bytes, value) = round_trip(true);
+    assert!(bytes.len() < serde_json::to_vec(&value).unwrap().len());
+}
@@ -0,0 +1,53 @@
+use mozak_runner::code;
+use mozak_runner::debugger::{Debugger, Symbols};
+use mozak_runner::instruction::Instruction;
+
+use crate::debugger::debug_repl;
+
+fn session(commands: &str) -> String {
+    let add = |imm| Instruction::addi(5, 5, imm);
+    let (program, record) = code::execute([add(1), add(2), add(3)], &[(0x100, 0xab)], &[]);
+    let mut debugger = Debugger::new(&program, record.executed[0].state.clone());
+    let mut symbols = Symbols::default();
+    symbols.insert("second", 4);
+    let mut output = vec![];
+    debug_repl(&mut debugger, &symbols, commands.as_bytes(), &mut output).unwrap();
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn break_by_symbol_and_inspect() {
+    let output = session("b second+4\nb 0x8\nc\nr\nx 0x100 2\nq\n");
+    assert!(output.contains("error: unknown symbol second+4"));
+    assert!(output.contains("breakpoint 0x8 <second+0x4>"));
+    assert!(output.contains("  t0 0x00000003"));
+    assert!(output.contains("0x00000100: ab 00"));
+}
+
+#[test]
+fn step_until_halt() {
+    let output = session("s 2\nw\nc\nc\n");
+    assert!(output.contains(" at 0x8 <second+0x4>: "));
+    assert_eq!(output.matches("halted at clk").count(), 2);
+}
+
+#[test]
+fn watch_memory() {
+    let store = |imm| Instruction::sb(5, 0, imm);
+    let (program, record) = code::execute([store(0x100), store(0x200)], &[], &[(5, 1)]);
+    let mut debugger = Debugger::new(&program, record.executed[0].state.clone());
+    let mut output = vec![];
+    let commands = "trace 0x100 w\nwatch 0x1f0..0x2_00 r\nwatch 512..0x300\nc\nhits\nwl\n";
+    debug_repl(
+        &mut debugger,
+        &Symbols::default(),
+        commands.as_bytes(),
+        &mut output,
+    )
+    .unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("watchpoint 2 on read/write of 0x200..0x300"));
+    assert!(output.contains("watchpoint: write of 0x200 by 0x4 at clk"));
+    assert!(output.contains("write of 0x100 by 0x0 at clk"));
+    assert!(output.contains("0: write of 0x100..0x101, traced"));
+}
@@ -0,0 +1,33 @@
+use gdbstub::target::ext::base::singlethread::SingleThreadBase;
+use gdbstub::target::ext::breakpoints::SwBreakpoint;
+use gdbstub_arch::riscv::reg::RiscvCoreRegs;
+use mozak_runner::code;
+use mozak_runner::debugger::{Debugger, Stop};
+use mozak_runner::instruction::Instruction;
+
+use crate::gdb::GdbTarget;
+
+#[test]
+fn registers_memory_and_breakpoints() {
+    let add = Instruction::addi(5, 5, 1);
+    let (program, record) = code::execute([add, add], &[(0x100, 7)], &[(5, 10)]);
+    let mut target = GdbTarget::new(Debugger::new(&program, record.executed[0].state.clone()));
+
+    let mut regs = RiscvCoreRegs::default();
+    target.read_registers(&mut regs).unwrap();
+    assert_eq!((regs.x[5], regs.pc), (10, 0));
+    regs.x[6] = 3;
+    target.write_registers(&regs).unwrap();
+    assert_eq!(target.debugger.register(6), 3);
+
+    let mut data = [0; 2];
+    assert_eq!(target.read_addrs(0x100, &mut data).unwrap(), 2);
+    assert_eq!(data, [7, 0]);
+    target.write_addrs(0x101, &[9]).unwrap();
+    assert_eq!(target.debugger.read_memory(0x100, 2), vec![7, 9]);
+
+    assert!(target.add_sw_breakpoint(4, 4).unwrap());
+    assert_eq!(target.debugger.resume().unwrap(), Stop::Breakpoint(4));
+    assert!(target.remove_sw_breakpoint(4, 4).unwrap());
+    assert!(!target.remove_sw_breakpoint(4, 4).unwrap());
+}
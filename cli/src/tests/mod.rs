@@ -1 +1,6 @@
+mod compression;
+mod debugger;
+mod gdb;
 mod integration_test;
+mod self_prog_id;
+mod trace_diff;
@@ -0,0 +1,64 @@
+use mozak_circuits::stark::mozak_stark::{MozakStark, PublicInputs};
+use mozak_circuits::stark::prover::prove;
+use mozak_circuits::stark::verifier::verify_proof;
+use mozak_runner::code;
+use mozak_runner::decode::ECALL;
+use mozak_runner::elf::Program;
+use mozak_runner::instruction::Instruction;
+use mozak_runner::state::RawTapes;
+use mozak_runner::vm::ExecutionRecord;
+use mozak_sdk::core::constants::DIGEST_BYTES;
+use mozak_sdk::core::ecall;
+use mozak_sdk::core::reg_abi::{REG_A0, REG_A1, REG_A2};
+use plonky2::field::goldilocks_field::GoldilocksField;
+use plonky2::field::types::Field;
+use plonky2::plonk::config::{KeccakGoldilocksConfig, Poseidon2GoldilocksConfig};
+use plonky2::util::timing::TimingTree;
+use starky::config::StarkConfig;
+
+use crate::runner::get_self_prog_id;
+
+type F = GoldilocksField;
+const D: usize = 2;
+
+/// Runs a guest that reads its self program id from `self_prog_id_tape`.
+fn read_self_prog_id(self_prog_id_tape: [u8; DIGEST_BYTES]) -> (Program, ExecutionRecord<F>) {
+    let code = [
+        Instruction::li(REG_A0, ecall::SELF_PROG_ID_TAPE),
+        Instruction::li(REG_A1, 0x300),
+        Instruction::li(REG_A2, u32::try_from(DIGEST_BYTES).unwrap()),
+        ECALL,
+    ];
+    code::execute_code_with_ro_memory(code, &[], &[], &[], RawTapes {
+        self_prog_id_tape,
+        ..Default::default()
+    })
+}
+
+/// The program id depends on the hasher, so a keccak proof only verifies if
+/// the guest read the keccak program id.
+#[test]
+fn prove_self_prog_id_with_keccak() -> anyhow::Result<()> {
+    type C = KeccakGoldilocksConfig;
+    let config = StarkConfig::standard_fast_config();
+    let (program, _) = read_self_prog_id([0; DIGEST_BYTES]);
+    let self_prog_id = get_self_prog_id::<F, C, D>(&program, &config);
+    assert_ne!(
+        self_prog_id,
+        get_self_prog_id::<F, Poseidon2GoldilocksConfig, D>(&program, &config)
+    );
+
+    let (program, record) = read_self_prog_id(self_prog_id.inner());
+    let stark = MozakStark::<F, D>::default();
+    let proof = prove::<F, C, D>(
+        &program,
+        &record,
+        &stark,
+        &config,
+        PublicInputs::new(F::from_canonical_u32(program.entry_point)),
+        &mut TimingTree::default(),
+    )?;
+    assert_eq!(proof.program_id, self_prog_id);
+    verify_proof(&stark, proof, &config)?;
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use mozak_circuits::stark::mozak_stark::TableKind;
+use mozak_circuits::test_utils::F;
+use plonky2::field::polynomial::PolynomialValues;
+use plonky2::field::types::Field;
+
+use crate::trace_diff::{diff_traces, ColumnDiff, TableDiff, Traces};
+
+fn traces(cpu: &[&[u64]]) -> Traces<F> {
+    let mut traces = Traces::<F>::default();
+    traces[TableKind::Cpu] = cpu
+        .iter()
+        .map(|column| {
+            PolynomialValues::new(column.iter().map(|&v| F::from_canonical_u64(v)).collect())
+        })
+        .collect();
+    traces
+}
+
+#[test]
+fn equal_traces_have_no_diff() {
+    let trace = traces(&[&[1, 2, 3, 4], &[5, 6, 7, 8]]);
+    assert_eq!(diff_traces(&trace, &trace), vec![]);
+}
+
+#[test]
+fn reports_differing_rows_and_columns() {
+    let old = traces(&[&[1, 2, 3, 4], &[5, 6, 7, 8]]);
+    let new = traces(&[&[1, 2, 3, 4], &[0, 0, 7, 0], &[9, 9, 9, 9]]);
+    let diff = diff_traces(&old, &new);
+    assert_eq!(diff, vec![TableDiff {
+        kind: TableKind::Cpu,
+        old_shape: (2, 4),
+        new_shape: (3, 4),
+        columns: vec![
+            ColumnDiff {
+                column: 1,
+                rows: vec![0, 1, 3],
+            },
+            ColumnDiff {
+                column: 2,
+                rows: vec![0, 1, 2, 3],
+            },
+        ],
+    }]);
+    assert_eq!(
+        diff[0].to_string(),
+        "Cpu: 2 columns differ (old 2x4, new 3x4)\n  column 1: 3 rows differ: 0..=1, 3\n  \
+         column 2: 4 rows differ: 0..=3"
+    );
+}
+
+#[test]
+fn reports_rows_only_one_trace_has() {
+    let old = traces(&[&[1, 2]]);
+    let new = traces(&[&[1, 2, 0, 0]]);
+    assert_eq!(diff_traces(&old, &new)[0].columns, vec![ColumnDiff {
+        column: 0,
+        rows: vec![2, 3],
+    }]);
+}
@@ -0,0 +1,134 @@
+//! Differences between two trace dumps, as written by `dump-trace`.
+//!
+//! Tables are matched by kind and columns by index, so dumps from two
+//! revisions can be compared as long as they agree on the set of tables.
+use std::fmt::{self, Display, Formatter};
+
+use itertools::Itertools;
+use mozak_circuits::stark::mozak_stark::{TableKind, TableKindArray};
+use plonky2::field::polynomial::PolynomialValues;
+use plonky2::field::types::Field;
+
+/// The traces of all tables, column by column.
+pub type Traces<F> = TableKindArray<Vec<PolynomialValues<F>>>;
+
+/// The rows in which a column differs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ColumnDiff {
+    pub column: usize,
+    /// Rows where the values differ, or that only one of the traces has.
+    pub rows: Vec<usize>,
+}
+
+/// The columns in which a table differs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TableDiff {
+    pub kind: TableKind,
+    /// Number of columns and rows of the old table.
+    pub old_shape: (usize, usize),
+    /// Number of columns and rows of the new table.
+    pub new_shape: (usize, usize),
+    pub columns: Vec<ColumnDiff>,
+}
+
+fn shape<F: Field>(table: &[PolynomialValues<F>]) -> (usize, usize) {
+    (
+        table.len(),
+        table.first().map_or(0, |column| column.values.len()),
+    )
+}
+
+/// The values of `column`, or none if `table` is narrower.
+fn values<F: Field>(table: &[PolynomialValues<F>], column: usize) -> &[F] {
+    table
+        .get(column)
+        .map_or(&[][..], |column| &column.values[..])
+}
+
+fn diff_column<F: Field>(column: usize, old: &[F], new: &[F]) -> Option<ColumnDiff> {
+    let rows = (0..old.len().max(new.len()))
+        .filter(|&row| old.get(row) != new.get(row))
+        .collect_vec();
+    (!rows.is_empty()).then_some(ColumnDiff { column, rows })
+}
+
+fn diff_table<F: Field>(
+    kind: TableKind,
+    old: &[PolynomialValues<F>],
+    new: &[PolynomialValues<F>],
+) -> Option<TableDiff> {
+    let columns = (0..old.len().max(new.len()))
+        .filter_map(|column| diff_column(column, values(old, column), values(new, column)))
+        .collect_vec();
+    (!columns.is_empty()).then(|| TableDiff {
+        kind,
+        old_shape: shape(old),
+        new_shape: shape(new),
+        columns,
+    })
+}
+
+/// Compares `old` and `new` table by table, and column by column.
+///
+/// Returns the tables that differ, in the order of their kinds. Empty if the
+/// traces are equal.
+#[must_use]
+pub fn diff_traces<F: Field>(old: &Traces<F>, new: &Traces<F>) -> Vec<TableDiff> {
+    old.each_ref()
+        .with_kind()
+        .iter()
+        .zip(new)
+        .filter_map(|((old, kind), new)| diff_table(*kind, old, new))
+        .collect()
+}
+
+/// Writes `rows` as comma separated runs, eg `0..=3, 7`.
+fn write_rows(f: &mut Formatter<'_>, rows: &[usize]) -> fmt::Result {
+    let runs = rows
+        .iter()
+        .map(|&row| (row, row))
+        .coalesce(|(start, end), (next, _)| {
+            if next == end + 1 {
+                Ok((start, next))
+            } else {
+                Err(((start, end), (next, next)))
+            }
+        })
+        .map(|(start, end)| {
+            if start == end {
+                start.to_string()
+            } else {
+                format!("{start}..={end}")
+            }
+        });
+    write!(f, "{}", runs.format(", "))
+}
+
+impl Display for ColumnDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "column {}: {} rows differ: ",
+            self.column,
+            self.rows.len()
+        )?;
+        write_rows(f, &self.rows)
+    }
+}
+
+impl Display for TableDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let (old_columns, old_rows) = self.old_shape;
+        let (new_columns, new_rows) = self.new_shape;
+        write!(
+            f,
+            "{:?}: {} columns differ (old {old_columns}x{old_rows}, new {new_columns}x{new_rows})",
+            self.kind,
+            self.columns.len(),
+        )?;
+        for column in &self.columns {
+            write!(f, "\n  {column}")?;
+        }
+        Ok(())
+    }
+}
@@ -32,6 +32,7 @@ macro_rules! ecrate {
 
 const CRATES: &[Crate] = &[
     ecrate!("bss-tester", "BSS_ELF"),
+    ecrate!("ecdsa", "ECDSA_ELF"),
     ecrate!("fibonacci", "FIBONACCI_ELF"),
     ecrate!("memory-access", "MEMORY_ACCESS_ELF"),
     ecrate!("min-max", "MIN_MAX_ELF"),
@@ -44,6 +45,7 @@ const CRATES: &[Crate] = &[
     ecrate!("token", "TOKENBIN"),
     ecrate!("wallet", "WALLETBIN"),
     ecrate!("inputtape", "INPUTTAPEBIN"),
+    ecrate!("int-math", "INT_MATH_ELF"),
     ecrate!("vector-alloc", "VECTOR_ALLOC_ELF"),
 ];
 const CARGO_MANIFEST_DIR: &str = env!("CARGO_MANIFEST_DIR");
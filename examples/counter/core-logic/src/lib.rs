@@ -1,8 +1,6 @@
 #![feature(restricted_std)]
 extern crate alloc;
 
-use core::panic;
-
 use mozak_sdk::common::types::{Event, EventType, StateObject};
 use rkyv::rancor::{Panic, Strategy};
 use rkyv::{Archive, Deserialize, Serialize};
@@ -18,44 +16,13 @@ impl<'a> From<&'a StateObject> for &'a ArchivedCounter {
     }
 }
 
-#[derive(Archive, Deserialize, Serialize, PartialEq, Clone)]
-#[cfg_attr(not(target_os = "mozakvm"), derive(Debug))]
-pub enum MethodArgs {
-    IncreaseCounter(StateObject),
-    DecreaseCounter(StateObject),
-}
+pub struct CounterContract;
 
-#[derive(Archive, Default, Deserialize, Serialize, PartialEq, Clone)]
-#[cfg_attr(not(target_os = "mozakvm"), derive(Debug))]
-pub enum MethodReturns {
-    #[default]
-    Default,
-    IncreaseCounter(StateObject),
-    DecreaseCounter(StateObject),
-}
+#[mozak_sdk::contract]
+impl CounterContract {
+    pub fn increase_counter(object: StateObject) -> StateObject { mutate_counter(object, 1) }
 
-impl From<MethodReturns> for StateObject {
-    fn from(value: MethodReturns) -> Self {
-        match value {
-            MethodReturns::IncreaseCounter(object) => object,
-            MethodReturns::DecreaseCounter(object) => object,
-            _ => panic!(),
-        }
-    }
-}
-
-#[allow(dead_code)]
-pub fn dispatch(args: MethodArgs) -> MethodReturns {
-    match args {
-        MethodArgs::IncreaseCounter(object) => {
-            let new_object = mutate_counter(object, 1);
-            MethodReturns::IncreaseCounter(new_object)
-        }
-        MethodArgs::DecreaseCounter(object) => {
-            let new_object = mutate_counter(object, -1);
-            MethodReturns::DecreaseCounter(new_object)
-        }
-    }
+    pub fn decrease_counter(object: StateObject) -> StateObject { mutate_counter(object, -1) }
 }
 
 #[allow(dead_code)]
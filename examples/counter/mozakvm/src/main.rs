@@ -2,14 +2,7 @@
 #![allow(unused_attributes)]
 #![feature(restricted_std)]
 
-use counter_core_logic::{dispatch, MethodArgs, MethodReturns};
-use mozak_sdk::call_receive;
-
-pub fn main() {
-    while let Some((_caller, argument, return_)) = call_receive::<MethodArgs, MethodReturns>() {
-        assert!(dispatch(argument) == return_);
-    }
-}
+pub fn main() { counter_core_logic::serve_calls(); }
 
 // We define `main()` to be the program's entry point.
 mozak_sdk::entry!(main);
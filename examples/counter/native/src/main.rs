@@ -1,4 +1,4 @@
-use counter_core_logic::{dispatch, Counter, MethodArgs};
+use counter_core_logic::{calls, Counter};
 use counter_elf_data::COUNTER_SELF_PROG_ID;
 use mozak_sdk::common::types::{ProgramIdentifier, StateAddress, StateObject};
 use rkyv::rancor::Panic;
@@ -16,31 +16,16 @@ fn main() {
     };
 
     // increase counter by 1
-    let new_object1: StateObject = mozak_sdk::call_send(
-        counter_program,
-        MethodArgs::IncreaseCounter(state_object.clone()),
-        dispatch,
-    )
-    .into();
+    let new_object1: StateObject = calls::increase_counter(counter_program, state_object.clone());
 
     // increase counter by 1
-    let new_object2: StateObject = mozak_sdk::call_send(
-        counter_program,
-        MethodArgs::IncreaseCounter(new_object1),
-        dispatch,
-    )
-    .into();
+    let new_object2: StateObject = calls::increase_counter(counter_program, new_object1);
 
     let counter = unsafe { rkyv::access_unchecked::<Counter>(&new_object2.data) };
     println!("Counter State after two increments: {}", counter.0);
 
     // decrease counter by 1
-    let new_object3: StateObject = mozak_sdk::call_send(
-        counter_program,
-        MethodArgs::DecreaseCounter(new_object2),
-        dispatch,
-    )
-    .into();
+    let new_object3: StateObject = calls::decrease_counter(counter_program, new_object2);
 
     let counter = unsafe { rkyv::access_unchecked::<Counter>(&new_object3.data) };
     println!("Counter state after decrement: {}", counter.0);
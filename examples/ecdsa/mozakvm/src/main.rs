@@ -0,0 +1,38 @@
+#![no_main]
+#![allow(unused_attributes)]
+#![feature(restricted_std)]
+
+//! Verifies a secp256k1 ECDSA signature with `mozak_sdk::crypto`, so that the
+//! proof attests to it.
+//!
+//! The public tape holds the public key (64 bytes), the signature (`r` and
+//! then `s`, 32 bytes each), the length of the message as a little endian
+//! `u32`, and then the message.
+
+use mozak_sdk::core::constants::SECP256K1_POINT_BYTES;
+use mozak_sdk::core::ecall::ioread_public;
+use mozak_sdk::crypto::secp256k1::Point;
+use mozak_sdk::crypto::{verify_signature, PublicKey, Signature};
+use mozak_sdk::prove_assert;
+
+pub fn main() {
+    let mut pubkey = [0; SECP256K1_POINT_BYTES];
+    let mut r = [0; 32];
+    let mut s = [0; 32];
+    let mut msg_len = [0; 4];
+    ioread_public(&mut pubkey);
+    ioread_public(&mut r);
+    ioread_public(&mut s);
+    ioread_public(&mut msg_len);
+    let mut msg = vec![0; u32::from_le_bytes(msg_len) as usize];
+    ioread_public(&mut msg);
+
+    prove_assert!(verify_signature(
+        &PublicKey(Point(pubkey)),
+        &msg,
+        &Signature { r, s }
+    ));
+}
+
+// We define `main()` to be the program's entry point.
+mozak_sdk::entry!(main);
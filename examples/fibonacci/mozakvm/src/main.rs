@@ -19,7 +19,7 @@ pub fn main() {
     let (high, low) = fibonacci(40);
     assert!(low == 63245986);
     assert_eq!(high, 0);
-    mozak_sdk::core::env::write(&high.to_le_bytes());
+    mozak_sdk::io_write!(high.to_le_bytes());
 }
 
 mozak_sdk::entry!(main);
@@ -0,0 +1,66 @@
+#![cfg_attr(target_os = "mozakvm", no_main)]
+#![cfg_attr(not(feature = "std"), no_std)]
+
+//! Runs the same `u64` arithmetic either with the generic operators, or with
+//! `mozak_sdk::math`, to compare their cycle counts.
+//!
+//! The public tape holds the number of iterations as a little endian `u32`,
+//! followed by one byte selecting the variant: 0 for generic, 1 for tuned.
+
+#[cfg(target_os = "mozakvm")]
+use {
+    core::hint::black_box,
+    mozak_sdk::core::ecall::ioread_public,
+    mozak_sdk::math::{mulhi_u64, DivU64},
+};
+
+#[cfg(target_os = "mozakvm")]
+const BY_TEN: DivU64 = DivU64::new(10);
+#[cfg(target_os = "mozakvm")]
+const SEED: u64 = 0xdead_beef_feed_cafe;
+
+/// Sums the decimal digits of a sequence of pseudo random `u64`s.
+#[cfg(target_os = "mozakvm")]
+fn generic(n: u32) -> u64 {
+    let mut x = SEED;
+    let mut sum = 0;
+    for _ in 0..n {
+        x = ((u128::from(x) * u128::from(SEED)) >> 64) as u64 ^ x;
+        let mut y = black_box(x);
+        while y != 0 {
+            sum += y % 10;
+            y /= 10;
+        }
+    }
+    sum
+}
+
+/// Same as `generic`, with `mozak_sdk::math`.
+#[cfg(target_os = "mozakvm")]
+fn tuned(n: u32) -> u64 {
+    let mut x = SEED;
+    let mut sum = 0;
+    for _ in 0..n {
+        x = mulhi_u64(x, SEED) ^ x;
+        let mut y = black_box(x);
+        while y != 0 {
+            let (q, r) = BY_TEN.div_rem(y);
+            sum += r;
+            y = q;
+        }
+    }
+    sum
+}
+
+fn main() {
+    #[cfg(target_os = "mozakvm")]
+    {
+        let mut bytes = [0u8; 5];
+        ioread_public(&mut bytes);
+        let n = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        let sum = if bytes[4] == 0 { generic(n) } else { tuned(n) };
+        black_box(sum);
+    }
+}
+
+mozak_sdk::entry!(main);
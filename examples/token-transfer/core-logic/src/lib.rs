@@ -0,0 +1,105 @@
+#![no_std]
+
+//! A ledger of token balances, and transfers between its accounts.
+//!
+//! Both the guest and the native side use this crate: the guest to check a
+//! transfer inside the VM, and the native side to lay out the tapes of the
+//! guest and to compute the ledger the guest should arrive at.
+
+/// Number of accounts of a [`Ledger`].
+pub const ACCOUNTS: usize = 4;
+
+/// Size of an encoded [`Ledger`]: a little endian `u64` per account.
+pub const LEDGER_BYTES: usize = ACCOUNTS * 8;
+
+/// Size of an encoded [`Transfer`]: the accounts it debits and credits, one
+/// byte each, and the amount as a little endian `u64`.
+pub const TRANSFER_BYTES: usize = 2 + 8;
+
+/// Balances of all accounts.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(not(target_os = "mozakvm"), derive(Debug))]
+pub struct Ledger(pub [u64; ACCOUNTS]);
+
+/// Moves `amount` tokens from account `from` to account `to`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(not(target_os = "mozakvm"), derive(Debug))]
+pub struct Transfer {
+    pub from: u8,
+    pub to: u8,
+    pub amount: u64,
+}
+
+/// Why a [`Transfer`] cannot be applied to a [`Ledger`].
+#[derive(Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(not(target_os = "mozakvm"), derive(Debug))]
+pub enum TransferError {
+    UnknownAccount,
+    SameAccount,
+    InsufficientFunds,
+    Overflow,
+}
+
+impl Ledger {
+    #[must_use]
+    pub fn from_bytes(bytes: &[u8; LEDGER_BYTES]) -> Self {
+        let mut balances = [0; ACCOUNTS];
+        for (balance, chunk) in balances.iter_mut().zip(bytes.chunks_exact(8)) {
+            *balance = u64::from_le_bytes(chunk.try_into().unwrap());
+        }
+        Self(balances)
+    }
+
+    #[must_use]
+    pub fn to_bytes(&self) -> [u8; LEDGER_BYTES] {
+        let mut bytes = [0; LEDGER_BYTES];
+        for (chunk, balance) in bytes.chunks_exact_mut(8).zip(self.0) {
+            chunk.copy_from_slice(&balance.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// The ledger after `transfer`.
+    ///
+    /// # Errors
+    /// Errors if an account of `transfer` does not exist, if it debits and
+    /// credits the same account, or if the debited account holds less than
+    /// the amount.
+    pub fn apply(&self, transfer: &Transfer) -> Result<Self, TransferError> {
+        let (from, to) = (usize::from(transfer.from), usize::from(transfer.to));
+        if from >= ACCOUNTS || to >= ACCOUNTS {
+            return Err(TransferError::UnknownAccount);
+        }
+        if from == to {
+            return Err(TransferError::SameAccount);
+        }
+        let mut balances = self.0;
+        balances[from] = balances[from]
+            .checked_sub(transfer.amount)
+            .ok_or(TransferError::InsufficientFunds)?;
+        balances[to] = balances[to]
+            .checked_add(transfer.amount)
+            .ok_or(TransferError::Overflow)?;
+        Ok(Self(balances))
+    }
+}
+
+impl Transfer {
+    #[must_use]
+    pub fn from_bytes(bytes: &[u8; TRANSFER_BYTES]) -> Self {
+        Self {
+            from: bytes[0],
+            to: bytes[1],
+            amount: u64::from_le_bytes(bytes[2..].try_into().unwrap()),
+        }
+    }
+
+    #[must_use]
+    pub fn to_bytes(&self) -> [u8; TRANSFER_BYTES] {
+        let mut bytes = [0; TRANSFER_BYTES];
+        bytes[0] = self.from;
+        bytes[1] = self.to;
+        bytes[2..].copy_from_slice(&self.amount.to_le_bytes());
+        bytes
+    }
+}
@@ -0,0 +1,27 @@
+#![cfg_attr(target_os = "mozakvm", no_main)]
+#![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(feature = "std", feature(restricted_std))]
+
+//! Proves that a transfer between the accounts of a ledger is valid.
+//!
+//! The private tape holds the ledger before the transfer, and then the
+//! transfer. The public tape holds the ledger after it. A proof of an
+//! execution attests that some valid transfer takes some ledger to the public
+//! one, without revealing which.
+
+use mozak_sdk::prove_assert;
+use token_transfer_core_logic::{Ledger, Transfer, LEDGER_BYTES, TRANSFER_BYTES};
+
+pub fn main() {
+    let mut before = [0; LEDGER_BYTES];
+    let mut transfer = [0; TRANSFER_BYTES];
+    let mut after = [0; LEDGER_BYTES];
+    mozak_sdk::io_read!(private, &mut before);
+    mozak_sdk::io_read!(private, &mut transfer);
+    mozak_sdk::io_read!(public, &mut after);
+
+    let transferred = Ledger::from_bytes(&before).apply(&Transfer::from_bytes(&transfer));
+    prove_assert!(transferred == Ok(Ledger::from_bytes(&after)));
+}
+
+mozak_sdk::entry!(main);
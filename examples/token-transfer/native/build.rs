@@ -0,0 +1,48 @@
+//! Cross-compiles the guest in `../mozakvm`, and embeds its ELF as
+//! `TOKEN_TRANSFER_ELF`.
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::Command;
+
+const GUEST_DIR: &str = "../mozakvm";
+const ELF_PATH: &str =
+    "../mozakvm/target/riscv32im-mozak-mozakvm-elf/mozak-release/token-transfer-mozakvm";
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed={GUEST_DIR}/src");
+    println!("cargo:rerun-if-changed=../core-logic/src");
+
+    let out_dir = std::env::var_os("OUT_DIR").unwrap();
+    let dest = Path::new(&out_dir).join("elf.rs");
+    // Clippy does not need the ELF, and building it is slow.
+    if cfg!(feature = "cargo-clippy") {
+        fs::write(dest, "pub const TOKEN_TRANSFER_ELF: &[u8] = &[];\n").unwrap();
+        return;
+    }
+
+    let output = Command::new("cargo")
+        .args(["mozakvm-build"])
+        .current_dir(GUEST_DIR)
+        .env_clear()
+        .envs(std::env::vars().filter(|x| !x.0.starts_with("CARGO_")))
+        .output()
+        .expect("cargo command failed to run");
+    if !output.status.success() {
+        io::stdout().write_all(&output.stdout).unwrap();
+        io::stderr().write_all(&output.stderr).unwrap();
+        panic!("cargo mozakvm-build {GUEST_DIR} failed.");
+    }
+    let elf = fs::canonicalize(ELF_PATH).expect("the guest ELF exists after building it");
+    fs::write(
+        dest,
+        format!(
+            "pub const TOKEN_TRANSFER_ELF: &[u8] = include_bytes!(r\"{}\");\n",
+            elf.display()
+        ),
+    )
+    .unwrap();
+    println!("cargo:rerun-if-changed={ELF_PATH}");
+}
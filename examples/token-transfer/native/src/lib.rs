@@ -0,0 +1,15 @@
+//! The native side of the token transfer example: the guest ELF, and the
+//! tapes it reads.
+
+use token_transfer_core_logic::{Ledger, Transfer};
+
+// The ELF of the guest in `../mozakvm`, cross-compiled by `build.rs`.
+include!(concat!(env!("OUT_DIR"), "/elf.rs"));
+
+/// The private and public tapes of the guest, for `transfer` of tokens in
+/// `before`, and the ledger it claims to arrive at.
+#[must_use]
+pub fn tapes(before: &Ledger, transfer: &Transfer, after: &Ledger) -> (Vec<u8>, Vec<u8>) {
+    let private = [&before.to_bytes()[..], &transfer.to_bytes()].concat();
+    (private, after.to_bytes().to_vec())
+}
@@ -0,0 +1,19 @@
+//! Applies a transfer natively, and prints the tapes the guest reads to
+//! check it.
+
+use token_transfer_core_logic::{Ledger, Transfer};
+use token_transfer_native::tapes;
+
+fn main() {
+    let before = Ledger([100, 20, 0, 7]);
+    let transfer = Transfer {
+        from: 0,
+        to: 2,
+        amount: 30,
+    };
+    let after = before.apply(&transfer).expect("the transfer is valid");
+    let (private, public) = tapes(&before, &transfer, &after);
+    println!("ledger after the transfer: {after:?}");
+    println!("private tape: {private:02x?}");
+    println!("public tape: {public:02x?}");
+}
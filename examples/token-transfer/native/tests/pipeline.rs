@@ -0,0 +1,88 @@
+//! Runs the guest end to end: load its ELF, execute it on the tapes of a
+//! transfer, and prove and verify the execution.
+
+use anyhow::Result;
+use mozak_circuits::test_utils::{fast_test_config, prove_and_verify_mozak_stark, F};
+use mozak_runner::elf::Program;
+use mozak_runner::instruction::Op;
+use mozak_runner::state::{RawTapes, State};
+use mozak_runner::vm::{step, ExecutionRecord};
+use mozak_sdk::core::ecall;
+use mozak_sdk::core::reg_abi::{REG_A0, REG_A1};
+use token_transfer_core_logic::{Ledger, Transfer, TransferError};
+use token_transfer_native::{tapes, TOKEN_TRANSFER_ELF};
+
+const BEFORE: Ledger = Ledger([100, 20, 0, 7]);
+
+/// Executes the guest on the tapes of `transfer`, claiming it arrives at
+/// `after`.
+fn run(transfer: &Transfer, after: &Ledger) -> Result<(Program, ExecutionRecord<F>)> {
+    let program = Program::vanilla_load_elf(TOKEN_TRANSFER_ELF)?;
+    let (private_tape, public_tape) = tapes(&BEFORE, transfer, after);
+    let state = State::new(program.clone(), RawTapes {
+        private_tape,
+        public_tape,
+        ..RawTapes::default()
+    });
+    let record = step(&program, state)?;
+    Ok((program, record))
+}
+
+/// The conditions the guest passed to its provable assertions.
+fn prove_asserts(record: &ExecutionRecord<F>) -> Vec<bool> {
+    record
+        .executed
+        .iter()
+        .filter(|row| {
+            row.instruction.op == Op::ECALL
+                && row.state.get_register_value(REG_A0) == ecall::PROVE_ASSERT
+        })
+        .map(|row| row.state.get_register_value(REG_A1) == 1)
+        .collect()
+}
+
+#[test]
+fn prove_transfer() -> Result<()> {
+    let transfer = Transfer {
+        from: 0,
+        to: 2,
+        amount: 30,
+    };
+    let after = BEFORE.apply(&transfer).unwrap();
+    assert_eq!(after, Ledger([70, 20, 30, 7]));
+
+    let (program, record) = run(&transfer, &after)?;
+    assert_eq!(prove_asserts(&record), vec![true]);
+    prove_and_verify_mozak_stark(&program, &record, &fast_test_config())
+}
+
+/// Claiming a ledger the transfer does not arrive at runs to completion, but
+/// fails the provable assertion, so the execution cannot be proven.
+#[test]
+fn wrong_ledger_fails_assertion() -> Result<()> {
+    let transfer = Transfer {
+        from: 1,
+        to: 3,
+        amount: 5,
+    };
+    let (_program, record) = run(&transfer, &Ledger([100, 20, 0, 12]))?;
+    assert_eq!(prove_asserts(&record), vec![false]);
+    Ok(())
+}
+
+#[test]
+fn overdraft_fails_assertion() -> Result<()> {
+    let transfer = Transfer {
+        from: 1,
+        to: 0,
+        amount: 21,
+    };
+    assert_eq!(
+        BEFORE.apply(&transfer),
+        Err(TransferError::InsufficientFunds)
+    );
+    // Whatever ledger the prover claims.
+    let (_program, record) = run(&transfer, &Ledger([121, 0, 0, 7]))?;
+    assert_eq!(prove_asserts(&record), vec![false]);
+    Ok(())
+}
@@ -497,7 +497,7 @@ pub mod test_data {
 
         #[must_use]
         pub fn pid(&self) -> ProgramIdentifier {
-            ProgramIdentifier(self.program_hash_val.map(|x| x.to_canonical_u64()).into())
+            ProgramIdentifier::from_u64s(self.program_hash_val.map(|x| x.to_canonical_u64()))
         }
     }
 
@@ -12,6 +12,7 @@ pub mod match_delta;
 pub mod merge;
 pub mod state_update;
 pub mod verify_block;
+pub mod verify_chain;
 pub mod verify_program;
 pub mod verify_tx;
 
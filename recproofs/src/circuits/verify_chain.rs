@@ -0,0 +1,356 @@
+//! Circuits for proof-carrying data: chains of proofs where each link
+//! verifies the previous one.
+//!
+//! Every chain proof verifies one link proof, typically a recursive VM proof,
+//! as well as the previous chain proof. It commits to the whole chain through
+//! a digest, which hashes the previous digest with the link's verifier and
+//! public inputs, so a single proof attests to a sequence of state
+//! transitions in order.
+
+use std::marker::PhantomData;
+
+use anyhow::Result;
+use plonky2::field::extension::Extendable;
+use plonky2::hash::hash_types::{HashOut, RichField};
+use plonky2::iop::witness::PartialWitness;
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::plonk::circuit_data::{
+    CircuitConfig, CircuitData, CommonCircuitData, VerifierOnlyCircuitData,
+};
+use plonky2::plonk::config::{AlgebraicHasher, GenericConfig, Hasher};
+use plonky2::plonk::proof::ProofWithPublicInputs;
+
+pub mod core;
+
+#[derive(Clone, Debug)]
+pub struct Indices {
+    pub chain: core::PublicIndices,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Base;
+
+#[derive(Copy, Clone, Debug)]
+pub struct Link;
+
+pub type Proof<T, F, C, const D: usize> = super::Proof<T, Indices, F, C, D>;
+
+pub type BaseProof<F, C, const D: usize> = Proof<Base, F, C, D>;
+
+pub type LinkProof<F, C, const D: usize> = Proof<Link, F, C, D>;
+
+pub enum BaseOrLinkRef<'a, F, C, const D: usize>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>, {
+    Base(&'a Proof<Base, F, C, D>),
+    Link(&'a Proof<Link, F, C, D>),
+}
+
+impl<'a, F, C, const D: usize> Clone for BaseOrLinkRef<'a, F, C, D>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    fn clone(&self) -> Self { *self }
+}
+
+impl<'a, F, C, const D: usize> Copy for BaseOrLinkRef<'a, F, C, D>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+}
+
+impl<'a, F, C, const D: usize> From<&'a Proof<Base, F, C, D>> for BaseOrLinkRef<'a, F, C, D>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    fn from(value: &'a Proof<Base, F, C, D>) -> Self { Self::Base(value) }
+}
+
+impl<'a, F, C, const D: usize> From<&'a Proof<Link, F, C, D>> for BaseOrLinkRef<'a, F, C, D>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    fn from(value: &'a Proof<Link, F, C, D>) -> Self { Self::Link(value) }
+}
+
+impl<'a, F, C, const D: usize> BaseOrLinkRef<'a, F, C, D>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+{
+    pub const fn proof(&self) -> &ProofWithPublicInputs<F, C, D> {
+        match self {
+            Self::Base(b) => &b.proof,
+            Self::Link(l) => &l.proof,
+        }
+    }
+}
+
+impl<T, F, C, const D: usize> Proof<T, F, C, D>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    C::Hasher: Hasher<F, Hash = HashOut<F>>,
+{
+    pub fn verifier(&self) -> VerifierOnlyCircuitData<C, D> {
+        self.indices
+            .chain
+            .verifier
+            .get_field(&self.proof.public_inputs)
+    }
+
+    pub fn digest(&self) -> HashOut<F> {
+        self.indices
+            .chain
+            .digest
+            .get_field(&self.proof.public_inputs)
+    }
+
+    pub fn length(&self) -> u64 {
+        self.indices
+            .chain
+            .length
+            .get_field(&self.proof.public_inputs)
+            .to_canonical_u64()
+    }
+}
+
+pub struct Circuit<F, C, const D: usize>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>, {
+    /// The link verifier
+    pub link: core::LinkVerifierSubCircuit<D>,
+
+    /// The chain verifier
+    pub chain: core::SubCircuit<F, C, D>,
+
+    pub circuit: CircuitData<F, C, D>,
+}
+
+impl<F, C, const D: usize> Circuit<F, C, D>
+where
+    F: RichField + Extendable<D>,
+    C: 'static + GenericConfig<D, F = F>,
+    <C as GenericConfig<D>>::Hasher: AlgebraicHasher<F>,
+{
+    /// Builds a chain circuit whose links are proofs of circuits sharing
+    /// `link_circuit_common`.
+    ///
+    /// The chain circuit itself is padded to `2^recursion_degree_bits` gates,
+    /// which must be enough to verify a link and a previous chain proof.
+    #[must_use]
+    pub fn new(
+        circuit_config: &CircuitConfig,
+        recursion_degree_bits: usize,
+        link_circuit_common: &CommonCircuitData<F, D>,
+    ) -> Self {
+        let mut builder = CircuitBuilder::<F, D>::new(circuit_config.clone());
+
+        let chain_inputs = core::SubCircuitInputs::default(&mut builder);
+
+        let link_targets =
+            core::LinkVerifierTargets::build_targets::<F, C>(&mut builder, link_circuit_common);
+        let chain = chain_inputs.build::<F, C, D>(&mut builder, recursion_degree_bits);
+
+        let digest = link_targets.digest(&mut builder, chain.prev_digest);
+        builder.connect_hashes(digest, chain.inputs.digest);
+
+        let circuit = builder.build();
+        assert_eq!(
+            circuit.common, chain.dummy.common,
+            "chain circuit does not fit in 2^{recursion_degree_bits} gates"
+        );
+
+        let public_inputs = &circuit.prover_only.public_inputs;
+        let link = link_targets.build(public_inputs);
+
+        Self {
+            link,
+            chain,
+            circuit,
+        }
+    }
+
+    fn indices(&self) -> Indices {
+        Indices {
+            chain: self.chain.indices.clone(),
+        }
+    }
+
+    pub fn prove_base(&self) -> Result<BaseProof<F, C, D>> {
+        let proof = self.chain.prove_base(&self.circuit.verifier_only)?;
+        Ok(BaseProof {
+            proof,
+            tag: PhantomData,
+            indices: self.indices(),
+        })
+    }
+
+    pub fn verify_base(&self, base_proof: BaseProof<F, C, D>) -> Result<()> {
+        self.chain.verify_base(base_proof.proof)
+    }
+
+    /// Extends the chain proven by `prev_proof` with `link_proof`.
+    pub fn prove<'a>(
+        &self,
+        link_verifier: &VerifierOnlyCircuitData<C, D>,
+        link_proof: &ProofWithPublicInputs<F, C, D>,
+        prev_proof: impl Into<BaseOrLinkRef<'a, F, C, D>>,
+    ) -> Result<LinkProof<F, C, D>> {
+        let mut inputs = PartialWitness::new();
+        self.link
+            .set_witness(&mut inputs, link_verifier, link_proof);
+        self.chain
+            .set_witness(&mut inputs, prev_proof.into().proof());
+        let proof = self.circuit.prove(inputs)?;
+        Ok(LinkProof {
+            proof,
+            tag: PhantomData,
+            indices: self.indices(),
+        })
+    }
+
+    pub fn verify(&self, proof: LinkProof<F, C, D>) -> Result<()> {
+        self.circuit.verify(proof.proof)
+    }
+}
+
+#[cfg(test)]
+pub mod test {
+    use plonky2::gates::noop::NoopGate;
+    use plonky2::iop::target::Target;
+    use plonky2::iop::witness::WitnessWrite;
+
+    use super::*;
+    use crate::circuits::verify_chain::core::link_digest;
+    use crate::test_utils::{make_fs, C, CONFIG, D, F, ZERO_HASH};
+
+    /// Plonky2's recursion threshold is 2^12 gates.
+    const RECURSION_THRESHOLD_DEGREE_BITS: usize = 12;
+
+    /// A stand-in for a recursive VM proof, exposing a state transition.
+    pub struct DummyLinkCircuit {
+        pub old_state: [Target; 4],
+        pub new_state: [Target; 4],
+        pub circuit: CircuitData<F, C, D>,
+    }
+
+    impl DummyLinkCircuit {
+        #[must_use]
+        pub fn new(circuit_config: &CircuitConfig) -> Self {
+            let mut builder = CircuitBuilder::<F, D>::new(circuit_config.clone());
+            let old_state = builder.add_virtual_target_arr();
+            let new_state = builder.add_virtual_target_arr();
+            builder.register_public_inputs(&old_state);
+            builder.register_public_inputs(&new_state);
+
+            // Make sure we have enough gates to match.
+            builder.add_gate(NoopGate, vec![]);
+            while builder.num_gates() < (1 << 3) {
+                builder.add_gate(NoopGate, vec![]);
+            }
+
+            let circuit = builder.build();
+            Self {
+                old_state,
+                new_state,
+                circuit,
+            }
+        }
+
+        pub fn prove(
+            &self,
+            old_state: [F; 4],
+            new_state: [F; 4],
+        ) -> Result<ProofWithPublicInputs<F, C, D>> {
+            let mut inputs = PartialWitness::new();
+            inputs.set_target_arr(&self.old_state, &old_state);
+            inputs.set_target_arr(&self.new_state, &new_state);
+            self.circuit.prove(inputs)
+        }
+    }
+
+    #[tested_fixture::tested_fixture(LINK)]
+    fn build_link() -> DummyLinkCircuit { DummyLinkCircuit::new(&CONFIG) }
+
+    #[tested_fixture::tested_fixture(CIRCUIT)]
+    fn build_circuit() -> Circuit<F, C, D> {
+        Circuit::new(
+            &CONFIG,
+            RECURSION_THRESHOLD_DEGREE_BITS,
+            &LINK.circuit.common,
+        )
+    }
+
+    const STATES: [[F; 4]; 4] = [
+        make_fs([0, 0, 0, 0]),
+        make_fs([1, 2, 3, 4]),
+        make_fs([5, 6, 7, 8]),
+        make_fs([9, 10, 11, 12]),
+    ];
+
+    #[tested_fixture::tested_fixture(BASE_PROOF: BaseProof<F, C, D>)]
+    fn verify_base() -> Result<BaseProof<F, C, D>> {
+        let proof = CIRCUIT.prove_base()?;
+        assert_eq!(proof.digest(), ZERO_HASH);
+        assert_eq!(proof.length(), 0);
+        CIRCUIT.verify_base(proof.clone())?;
+        Ok(proof)
+    }
+
+    #[test]
+    fn verify_three_links() -> Result<()> {
+        let mut expected_digest = ZERO_HASH;
+        let mut prev: Option<LinkProof<F, C, D>> = None;
+        for (i, states) in STATES.windows(2).enumerate() {
+            let link_proof = LINK.prove(states[0], states[1])?;
+            expected_digest = link_digest(
+                expected_digest,
+                &LINK.circuit.verifier_only,
+                &link_proof.public_inputs,
+            );
+
+            let proof = match &prev {
+                None => CIRCUIT.prove(&LINK.circuit.verifier_only, &link_proof, *BASE_PROOF)?,
+                Some(prev) => CIRCUIT.prove(&LINK.circuit.verifier_only, &link_proof, prev)?,
+            };
+            assert_eq!(proof.digest(), expected_digest);
+            assert_eq!(proof.length(), i as u64 + 1);
+            assert_eq!(proof.verifier(), CIRCUIT.circuit.verifier_only);
+            CIRCUIT.verify(proof.clone())?;
+            prev = Some(proof);
+        }
+        assert_eq!(prev.unwrap().length(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn order_matters() -> Result<()> {
+        let first = LINK.prove(STATES[0], STATES[1])?;
+        let second = LINK.prove(STATES[1], STATES[2])?;
+        let vk = &LINK.circuit.verifier_only;
+
+        let forward = CIRCUIT.prove(vk, &first, *BASE_PROOF)?;
+        let forward = CIRCUIT.prove(vk, &second, &forward)?;
+        let backward = CIRCUIT.prove(vk, &second, *BASE_PROOF)?;
+        let backward = CIRCUIT.prove(vk, &first, &backward)?;
+        assert_ne!(forward.digest(), backward.digest());
+        Ok(())
+    }
+
+    #[test]
+    #[should_panic(expected = "was set twice with different values")]
+    fn bad_link_wrong_verifier() {
+        let link_proof = LINK.prove(STATES[0], STATES[1]).unwrap();
+        let proof = CIRCUIT
+            .prove(&CIRCUIT.circuit.verifier_only, &link_proof, *BASE_PROOF)
+            .unwrap();
+        CIRCUIT.verify(proof).unwrap();
+    }
+}
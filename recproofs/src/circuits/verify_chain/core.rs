@@ -0,0 +1,323 @@
+use itertools::chain;
+use plonky2::field::extension::Extendable;
+use plonky2::gates::noop::NoopGate;
+use plonky2::hash::hash_types::{HashOut, HashOutTarget, RichField};
+use plonky2::hash::poseidon2::Poseidon2Hash;
+use plonky2::iop::target::Target;
+use plonky2::iop::witness::{PartialWitness, Witness, WitnessWrite};
+use plonky2::plonk::circuit_builder::CircuitBuilder;
+use plonky2::plonk::circuit_data::{
+    CircuitData, CommonCircuitData, VerifierCircuitTarget, VerifierOnlyCircuitData,
+};
+use plonky2::plonk::config::{AlgebraicHasher, GenericConfig, Hasher};
+use plonky2::plonk::proof::{ProofWithPublicInputs, ProofWithPublicInputsTarget};
+
+use crate::indices::{HashOutTargetIndex, TargetIndex, VerifierCircuitTargetIndex};
+use crate::{circuit_data_for_recursion, dummy_circuit, select_hash, select_verifier};
+
+/// Computes the chain digest after appending a link, natively.
+///
+/// This mirrors the in-circuit computation of [`LinkVerifierTargets`] and
+/// [`SubCircuit`], so a verifier can recompute the expected digest of a chain
+/// from its links.
+pub fn link_digest<F, C, const D: usize>(
+    prev_digest: HashOut<F>,
+    link_verifier: &VerifierOnlyCircuitData<C, D>,
+    link_public_inputs: &[F],
+) -> HashOut<F>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    C::Hasher: Hasher<F, Hash = HashOut<F>>, {
+    let verifier_hash = Poseidon2Hash::hash_no_pad(
+        &chain!(
+            [&link_verifier.circuit_digest],
+            &link_verifier.constants_sigmas_cap.0,
+        )
+        .flat_map(|v| v.elements)
+        .collect::<Vec<_>>(),
+    );
+    let public_inputs_hash = Poseidon2Hash::hash_no_pad(link_public_inputs);
+    Poseidon2Hash::hash_no_pad(
+        &chain!(
+            prev_digest.elements,
+            verifier_hash.elements,
+            public_inputs_hash.elements
+        )
+        .collect::<Vec<_>>(),
+    )
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct PublicIndices {
+    /// The self-recursion verifier
+    pub verifier: VerifierCircuitTargetIndex,
+
+    /// The indices of each of the elements of the chain digest
+    pub digest: HashOutTargetIndex,
+
+    /// The index of the number of links in the chain
+    pub length: TargetIndex,
+}
+
+pub struct SubCircuitInputs {
+    /// The recursive verifier
+    pub verifier: VerifierCircuitTarget,
+
+    /// The digest of the chain up to and including this link
+    pub digest: HashOutTarget,
+
+    /// The number of links in the chain
+    pub length: Target,
+}
+
+pub struct SubCircuit<F, C, const D: usize>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>, {
+    /// The dummy circuit
+    pub dummy: CircuitData<F, C, D>,
+
+    /// The public inputs
+    pub inputs: SubCircuitInputs,
+
+    /// The previous chain proof
+    pub prev_proof: ProofWithPublicInputsTarget<D>,
+
+    /// The digest of the previous chain proof, or zero for the base
+    pub prev_digest: HashOutTarget,
+
+    /// The indicies of the public inputs
+    pub indices: PublicIndices,
+}
+
+impl SubCircuitInputs {
+    pub fn default<F, const D: usize>(builder: &mut CircuitBuilder<F, D>) -> Self
+    where
+        F: RichField + Extendable<D>, {
+        let verifier = builder.add_virtual_verifier_data(builder.config.fri_config.cap_height);
+        let digest = builder.add_virtual_hash();
+        let length = builder.add_virtual_target();
+
+        let v = Self {
+            verifier,
+            digest,
+            length,
+        };
+        v.register_inputs(builder);
+        v
+    }
+
+    fn register_inputs<F, const D: usize>(&self, builder: &mut CircuitBuilder<F, D>)
+    where
+        F: RichField + Extendable<D>, {
+        builder.register_public_inputs(&self.verifier.circuit_digest.elements);
+        for i in 0..builder.config.fri_config.num_cap_elements() {
+            builder.register_public_inputs(&self.verifier.constants_sigmas_cap.0[i].elements);
+        }
+        builder.register_public_inputs(&self.digest.elements);
+        builder.register_public_input(self.length);
+    }
+
+    #[must_use]
+    pub fn build<F, C, const D: usize>(
+        self,
+        builder: &mut CircuitBuilder<F, D>,
+        recursion_degree_bits: usize,
+    ) -> SubCircuit<F, C, D>
+    where
+        F: RichField + Extendable<D>,
+        C: 'static + GenericConfig<D, F = F>,
+        C::Hasher: AlgebraicHasher<F>, {
+        let common = circuit_data_for_recursion::<F, C, D>(
+            &builder.config,
+            recursion_degree_bits,
+            builder.num_public_inputs(),
+        )
+        .common;
+
+        let dummy = dummy_circuit::<C, D>(&common, |builder| self.register_inputs(builder));
+
+        let prev_proof = builder.add_virtual_proof_with_pis(&common);
+
+        let public_inputs = builder.public_inputs();
+        let indices = PublicIndices {
+            verifier: VerifierCircuitTargetIndex::new(public_inputs, &self.verifier),
+            digest: HashOutTargetIndex::new(public_inputs, self.digest),
+            length: TargetIndex::new(public_inputs, self.length),
+        };
+        let prev_length = indices.length.get_target(&prev_proof.public_inputs);
+
+        let non_base = builder.is_nonzero(prev_length);
+
+        // Connect previous verifier data to current one. This guarantees that every
+        // proof in the chain uses the same verifier data.
+        let prev_verifier = indices.verifier.get_target(&prev_proof.public_inputs);
+        builder.connect_verifier_data(&self.verifier, &prev_verifier);
+
+        let dummy_verifier = builder.constant_verifier_data(&dummy.verifier_only);
+        let verifier_calc = select_verifier(builder, non_base, &self.verifier, &dummy_verifier);
+        builder.verify_proof::<C>(&prev_proof, &verifier_calc, &common);
+
+        // Connect lengths
+        let length_calc = builder.add_const(prev_length, F::ONE);
+        builder.connect(self.length, length_calc);
+
+        // Chains start from the zero digest, whatever the base proof claims
+        let prev_digest = indices.digest.get_target(&prev_proof.public_inputs);
+        let zero_digest = HashOutTarget {
+            elements: [builder.zero(); 4],
+        };
+        let prev_digest = select_hash(builder, non_base, prev_digest, zero_digest);
+
+        // Make sure we have enough gates to match `common_data`.
+        while builder.num_gates() < (common.degree() / 2) {
+            builder.add_gate(NoopGate, vec![]);
+        }
+        // Make sure we have every gate to match `common_data`.
+        for g in &common.gates {
+            builder.add_gate_to_gate_set(g.clone());
+        }
+
+        SubCircuit {
+            dummy,
+            inputs: self,
+            prev_proof,
+            prev_digest,
+            indices,
+        }
+    }
+}
+
+impl<F, C, const D: usize> SubCircuit<F, C, D>
+where
+    F: RichField + Extendable<D>,
+    C: GenericConfig<D, F = F>,
+    <C as GenericConfig<D>>::Hasher: AlgebraicHasher<F>,
+{
+    pub fn prove_base(
+        &self,
+        verifier: &VerifierOnlyCircuitData<C, D>,
+    ) -> anyhow::Result<ProofWithPublicInputs<F, C, D>> {
+        let mut dummy_inputs = PartialWitness::new();
+
+        // Set the base inputs
+        dummy_inputs.set_verifier_data_target(&self.inputs.verifier, verifier);
+        dummy_inputs.set_hash_target(self.inputs.digest, HashOut::ZERO);
+        dummy_inputs.set_target(self.inputs.length, F::ZERO);
+
+        // Zero out all other inputs
+        for i in 0..self.dummy.common.num_public_inputs {
+            let target = self.dummy.prover_only.public_inputs[i];
+            if dummy_inputs.try_get_target(target).is_none() {
+                dummy_inputs.set_target(target, F::ZERO);
+            }
+        }
+
+        self.dummy.prove(dummy_inputs)
+    }
+
+    pub fn verify_base(&self, base_proof: ProofWithPublicInputs<F, C, D>) -> anyhow::Result<()> {
+        self.dummy.verify(base_proof)
+    }
+
+    pub fn set_witness(
+        &self,
+        inputs: &mut PartialWitness<F>,
+        prev_proof: &ProofWithPublicInputs<F, C, D>,
+    ) {
+        inputs.set_proof_with_pis_target(&self.prev_proof, prev_proof);
+    }
+}
+
+pub struct LinkVerifierTargets<const D: usize> {
+    /// The link verifier
+    pub link_verifier: VerifierCircuitTarget,
+
+    /// The link proof
+    pub link_proof: ProofWithPublicInputsTarget<D>,
+
+    /// The hash of the link verifier
+    pub link_verifier_hash: HashOutTarget,
+
+    /// The hash of the public inputs of the link proof
+    pub public_inputs_hash: HashOutTarget,
+}
+
+pub struct LinkVerifierSubCircuit<const D: usize> {
+    pub targets: LinkVerifierTargets<D>,
+}
+
+impl<const D: usize> LinkVerifierTargets<D> {
+    #[must_use]
+    pub fn build_targets<F, C>(
+        builder: &mut CircuitBuilder<F, D>,
+        link_circuit_common: &CommonCircuitData<F, D>,
+    ) -> Self
+    where
+        F: RichField + Extendable<D>,
+        C: GenericConfig<D, F = F>,
+        <C as GenericConfig<D>>::Hasher: AlgebraicHasher<F>, {
+        let link_proof = builder.add_virtual_proof_with_pis(link_circuit_common);
+        let link_verifier =
+            builder.add_virtual_verifier_data(link_circuit_common.config.fri_config.cap_height);
+        builder.verify_proof::<C>(&link_proof, &link_verifier, link_circuit_common);
+
+        let link_verifier_hash = builder.hash_n_to_hash_no_pad::<Poseidon2Hash>(
+            chain!(
+                [&link_verifier.circuit_digest],
+                &link_verifier.constants_sigmas_cap.0,
+            )
+            .flat_map(|v| &v.elements)
+            .copied()
+            .collect(),
+        );
+        let public_inputs_hash =
+            builder.hash_n_to_hash_no_pad::<Poseidon2Hash>(link_proof.public_inputs.clone());
+
+        Self {
+            link_verifier,
+            link_proof,
+            link_verifier_hash,
+            public_inputs_hash,
+        }
+    }
+
+    /// Computes the digest of the chain extended by this link.
+    pub fn digest<F>(
+        &self,
+        builder: &mut CircuitBuilder<F, D>,
+        prev_digest: HashOutTarget,
+    ) -> HashOutTarget
+    where
+        F: RichField + Extendable<D>, {
+        builder.hash_n_to_hash_no_pad::<Poseidon2Hash>(
+            chain!(
+                prev_digest.elements,
+                self.link_verifier_hash.elements,
+                self.public_inputs_hash.elements
+            )
+            .collect(),
+        )
+    }
+
+    #[must_use]
+    pub fn build(self, _public_inputs: &[Target]) -> LinkVerifierSubCircuit<D> {
+        LinkVerifierSubCircuit { targets: self }
+    }
+}
+
+impl<const D: usize> LinkVerifierSubCircuit<D> {
+    pub fn set_witness<F, C>(
+        &self,
+        inputs: &mut PartialWitness<F>,
+        link_verifier: &VerifierOnlyCircuitData<C, D>,
+        link_proof: &ProofWithPublicInputs<F, C, D>,
+    ) where
+        F: RichField + Extendable<D>,
+        C: GenericConfig<D, F = F>,
+        <C as GenericConfig<D>>::Hasher: AlgebraicHasher<F>, {
+        inputs.set_verifier_data_target(&self.targets.link_verifier, link_verifier);
+        inputs.set_proof_with_pis_target(&self.targets.link_proof, link_proof);
+    }
+}
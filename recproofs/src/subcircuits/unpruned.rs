@@ -276,6 +276,8 @@ impl BranchSubCircuit<PartialAllowed> {
 mod test {
     use anyhow::Result;
     use array_util::ArrayExt;
+    use mozak_sdk::common::sparse_merkle::SparseMerkleTree;
+    use plonky2::field::types::PrimeField64;
     use plonky2::plonk::circuit_data::{CircuitConfig, CircuitData};
     use plonky2::plonk::proof::ProofWithPublicInputs;
 
@@ -773,6 +775,24 @@ mod test {
         Ok(())
     }
 
+    /// Byte-wise trees have the roots of the sparse Merkle trees of the SDK.
+    #[test]
+    fn vm_branch_matches_sdk_sparse_merkle_tree() -> Result<()> {
+        let [(hash_0, proof_0), (hash_2, proof_2)] = &*LEFT_VM_BRANCH_PROOFS;
+        let (root, _) = verify_branch_helper(*VM_BRANCH_2, hash_0, proof_0, hash_2, proof_2)?;
+
+        let to_sdk = |hash: HashOut<F>| {
+            mozak_sdk::common::types::Poseidon2Hash::from(
+                hash.elements.map(|element| element.to_canonical_u64()),
+            )
+        };
+        let mut tree = SparseMerkleTree::new(2);
+        tree.update(0, to_sdk(NON_ZERO_VALUES[0]));
+        tree.update(2, to_sdk(NON_ZERO_VALUES[1]));
+        assert_eq!(tree.root(), to_sdk(root));
+        Ok(())
+    }
+
     #[test]
     #[should_panic(expected = "was set twice with different values")]
     fn bad_wrong_hash_vm_branch() {
@@ -0,0 +1,443 @@
+//! Step-by-step execution with breakpoints and watchpoints, for interactive
+//! debugging.
+//!
+//! [`Debugger`] drives the same [`step_once`] as [`step`](crate::vm::step),
+//! but stops wherever it is told to, so that frontends, like the CLI's
+//! `debug` command or an IDE, can inspect the [`State`] in between.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Range;
+
+use anyhow::Result;
+use elf::endian::LittleEndian;
+use elf::ElfBytes;
+use plonky2::hash::hash_types::RichField;
+
+use crate::elf::Program;
+use crate::instruction::{DecodingError, Instruction};
+use crate::memory_stats::reads_and_writes;
+use crate::state::State;
+use crate::vm::{step_once, Row};
+
+/// ABI names of the registers, by index.
+pub const REGISTER_NAMES: [&str; 32] = [
+    "zero", "ra", "sp", "gp", "tp", "t0", "t1", "t2", "s0", "s1", "a0", "a1", "a2", "a3", "a4",
+    "a5", "a6", "a7", "s2", "s3", "s4", "s5", "s6", "s7", "s8", "s9", "s10", "s11", "t3", "t4",
+    "t5", "t6",
+];
+
+/// The function symbols of an ELF, to set breakpoints by name and to tell
+/// which function a pc is in.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Symbols {
+    by_address: BTreeMap<u32, String>,
+    by_name: BTreeMap<String, u32>,
+}
+
+impl Symbols {
+    /// Reads the function symbols of the ELF in `input`. Stripped ELFs have
+    /// none.
+    ///
+    /// # Errors
+    /// Errors if the ELF or its symbol table does not parse.
+    pub fn from_elf(input: &[u8]) -> Result<Self> {
+        let elf = ElfBytes::<LittleEndian>::minimal_parse(input)?;
+        let mut symbols = Self::default();
+        if let Some((table, names)) = elf.symbol_table()? {
+            for symbol in table
+                .iter()
+                .filter(|symbol| symbol.st_symtype() == elf::abi::STT_FUNC)
+            {
+                let name = names.get(symbol.st_name.try_into()?)?;
+                symbols.insert(name, symbol.st_value.try_into()?);
+            }
+        }
+        Ok(symbols)
+    }
+
+    pub fn insert(&mut self, name: &str, address: u32) {
+        self.by_address.insert(address, name.to_string());
+        self.by_name.insert(name.to_string(), address);
+    }
+
+    #[must_use]
+    pub fn address_of(&self, name: &str) -> Option<u32> { self.by_name.get(name).copied() }
+
+    /// The symbol at or below `pc`, and the offset of `pc` from it.
+    #[must_use]
+    pub fn locate(&self, pc: u32) -> Option<(&str, u32)> {
+        self.by_address
+            .range(..=pc)
+            .next_back()
+            .map(|(&address, name)| (name.as_str(), pc - address))
+    }
+}
+
+/// The kinds of memory access a [`Watchpoint`] catches.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Access {
+    Read,
+    Write,
+    ReadWrite,
+}
+
+impl Access {
+    #[must_use]
+    pub fn catches(self, access: Access) -> bool { self == Access::ReadWrite || self == access }
+}
+
+/// Watches the byte addresses in `range` for accesses.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Watchpoint {
+    pub range: Range<u32>,
+    pub access: Access,
+    /// Whether execution stops after a caught access, or only records it in
+    /// [`Debugger::watch_hits`].
+    pub pause: bool,
+}
+
+/// An access that a [`Watchpoint`] caught.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WatchHit {
+    pub addr: u32,
+    /// Either [`Access::Read`] or [`Access::Write`].
+    pub access: Access,
+    /// The pc of the accessing instruction.
+    pub pc: u32,
+    /// The clock of the accessing instruction.
+    pub clk: u64,
+}
+
+/// Why [`Debugger::step`] or [`Debugger::resume`] stopped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stop {
+    /// After executing the requested number of instructions.
+    Stepped,
+    /// Before executing the instruction at a breakpoint.
+    Breakpoint(u32),
+    /// After executing an instruction whose access a pausing watchpoint
+    /// caught. The hit is the first such access of the instruction.
+    Watchpoint(WatchHit),
+    /// The program halted.
+    Halted,
+}
+
+/// Executes a program one instruction at a time, stopping at breakpoints and
+/// watchpoints.
+pub struct Debugger<'a, F: RichField> {
+    program: &'a Program,
+    state: State<F>,
+    breakpoints: BTreeSet<u32>,
+    watchpoints: BTreeMap<usize, Watchpoint>,
+    next_watchpoint: usize,
+    watch_hits: Vec<WatchHit>,
+    last_row: Option<Row<F>>,
+}
+
+impl<'a, F: RichField> Debugger<'a, F> {
+    #[must_use]
+    pub fn new(program: &'a Program, state: State<F>) -> Self {
+        Self {
+            program,
+            state,
+            breakpoints: BTreeSet::new(),
+            watchpoints: BTreeMap::new(),
+            next_watchpoint: 0,
+            watch_hits: vec![],
+            last_row: None,
+        }
+    }
+
+    #[must_use]
+    pub fn program(&self) -> &Program { self.program }
+
+    /// The state just before executing the next instruction.
+    #[must_use]
+    pub fn state(&self) -> &State<F> { &self.state }
+
+    /// The last executed instruction, with the state before it and its
+    /// auxiliary information.
+    #[must_use]
+    pub fn last_row(&self) -> Option<&Row<F>> { self.last_row.as_ref() }
+
+    /// The instruction that executes next.
+    #[must_use]
+    pub fn next_instruction(&self) -> Option<&Result<Instruction, DecodingError>> {
+        self.state.current_instruction(self.program)
+    }
+
+    #[must_use]
+    pub fn register(&self, index: u8) -> u32 { self.state.get_register_value(index) }
+
+    /// Reads `len` bytes of memory starting at `addr`, wrapping around the
+    /// address space.
+    #[must_use]
+    pub fn read_memory(&self, addr: u32, len: u32) -> Vec<u8> {
+        (0..len)
+            .map(|i| self.state.load_u8(addr.wrapping_add(i)))
+            .collect()
+    }
+
+    /// Sets register `index`. Writes to the zero register are ignored.
+    pub fn set_register(&mut self, index: u8, value: u32) {
+        self.state = self.state.clone().set_register_value(index, value);
+    }
+
+    /// Moves the pc, so that the instruction at `pc` executes next.
+    pub fn set_pc(&mut self, pc: u32) { self.state = self.state.clone().set_pc(pc); }
+
+    /// Writes `bytes` to memory starting at `addr`, all or nothing.
+    ///
+    /// # Errors
+    /// Errors if any of the bytes is read only.
+    pub fn write_memory(&mut self, addr: u32, bytes: &[u8]) -> Result<()> {
+        let mut state = self.state.clone();
+        for (i, &byte) in (0_u32..).zip(bytes) {
+            state = state.store_u8(addr.wrapping_add(i), byte)?;
+        }
+        self.state = state;
+        Ok(())
+    }
+
+    /// Returns whether the breakpoint is new.
+    pub fn add_breakpoint(&mut self, pc: u32) -> bool { self.breakpoints.insert(pc) }
+
+    /// Returns whether there was a breakpoint.
+    pub fn remove_breakpoint(&mut self, pc: u32) -> bool { self.breakpoints.remove(&pc) }
+
+    pub fn breakpoints(&self) -> impl Iterator<Item = u32> + '_ { self.breakpoints.iter().copied() }
+
+    /// Adds a watchpoint, and returns its id.
+    pub fn add_watchpoint(&mut self, watchpoint: Watchpoint) -> usize {
+        let id = self.next_watchpoint;
+        self.next_watchpoint += 1;
+        self.watchpoints.insert(id, watchpoint);
+        id
+    }
+
+    pub fn remove_watchpoint(&mut self, id: usize) -> Option<Watchpoint> {
+        self.watchpoints.remove(&id)
+    }
+
+    pub fn watchpoints(&self) -> impl Iterator<Item = (usize, &Watchpoint)> + '_ {
+        self.watchpoints
+            .iter()
+            .map(|(&id, watchpoint)| (id, watchpoint))
+    }
+
+    /// All accesses that watchpoints caught so far, in execution order.
+    #[must_use]
+    pub fn watch_hits(&self) -> &[WatchHit] { &self.watch_hits }
+
+    /// Records the accesses of `row` that watchpoints catch, and returns the
+    /// first one that a pausing watchpoint caught.
+    fn watch(&mut self, row: &Row<F>) -> Option<WatchHit> {
+        let (reads, writes) = reads_and_writes(row);
+        let mut pause = None;
+        for (access, addrs) in [(Access::Read, reads), (Access::Write, writes)] {
+            for &addr in addrs {
+                let mut catching = self
+                    .watchpoints
+                    .values()
+                    .filter(|watchpoint| {
+                        watchpoint.range.contains(&addr) && watchpoint.access.catches(access)
+                    })
+                    .peekable();
+                if catching.peek().is_none() {
+                    continue;
+                }
+                let pauses = catching.any(|watchpoint| watchpoint.pause);
+                let hit = WatchHit {
+                    addr,
+                    access,
+                    pc: row.state.get_pc(),
+                    clk: row.state.clk,
+                };
+                self.watch_hits.push(hit);
+                if pauses {
+                    pause = pause.or(Some(hit));
+                }
+            }
+        }
+        pause
+    }
+
+    fn execute_one(&mut self) -> Result<Option<WatchHit>> {
+        let (row, next) = step_once(self.program, self.state.clone())?;
+        let hit = self.watch(&row);
+        self.state = next;
+        self.last_row = Some(row);
+        Ok(hit)
+    }
+
+    /// Executes up to `count` instructions, stopping early at a breakpoint, a
+    /// pausing watchpoint, or when the program halts.
+    ///
+    /// # Errors
+    /// Errors if an instruction fails to execute, see [`step_once`].
+    pub fn step(&mut self, count: u64) -> Result<Stop> {
+        for executed in 0..count {
+            if self.state.has_halted() {
+                return Ok(Stop::Halted);
+            }
+            if executed > 0 && self.breakpoints.contains(&self.state.get_pc()) {
+                return Ok(Stop::Breakpoint(self.state.get_pc()));
+            }
+            if let Some(hit) = self.execute_one()? {
+                return Ok(Stop::Watchpoint(hit));
+            }
+        }
+        Ok(if self.state.has_halted() {
+            Stop::Halted
+        } else {
+            Stop::Stepped
+        })
+    }
+
+    /// Executes until the next breakpoint or pausing watchpoint, or until the
+    /// program halts. The
+    /// instruction at the current pc always executes, so that resuming from
+    /// a breakpoint makes progress.
+    ///
+    /// # Errors
+    /// Errors if an instruction fails to execute, see [`step_once`].
+    pub fn resume(&mut self) -> Result<Stop> {
+        if self.state.has_halted() {
+            return Ok(Stop::Halted);
+        }
+        loop {
+            if let Some(hit) = self.execute_one()? {
+                return Ok(Stop::Watchpoint(hit));
+            }
+            if self.state.has_halted() {
+                return Ok(Stop::Halted);
+            }
+            let pc = self.state.get_pc();
+            if self.breakpoints.contains(&pc) {
+                return Ok(Stop::Breakpoint(pc));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mozak_sdk::core::reg_abi::REG_A1;
+
+    use super::{Access, Debugger, Stop, Symbols, WatchHit, Watchpoint};
+    use crate::code;
+    use crate::instruction::Instruction;
+
+    fn add(rd: u8, imm: u32) -> Instruction { Instruction::addi(rd, rd, imm) }
+
+    #[test]
+    fn breakpoints_and_steps() {
+        let (program, record) = code::execute([add(5, 1), add(5, 2), add(5, 3)], &[], &[]);
+        let mut debugger = Debugger::new(&program, record.executed[0].state.clone());
+        assert!(debugger.add_breakpoint(8));
+        assert!(!debugger.add_breakpoint(8));
+
+        assert_eq!(debugger.resume().unwrap(), Stop::Breakpoint(8));
+        assert_eq!(debugger.register(5), 3);
+        assert_eq!(debugger.last_row().unwrap().state.get_pc(), 4);
+
+        // Stepping from a breakpoint executes it.
+        assert_eq!(debugger.step(1).unwrap(), Stop::Stepped);
+        assert_eq!(debugger.register(5), 6);
+
+        assert!(debugger.remove_breakpoint(8));
+        assert_eq!(debugger.resume().unwrap(), Stop::Halted);
+        assert_eq!(debugger.state().clk, record.last_state.clk);
+        assert_eq!(debugger.step(1).unwrap(), Stop::Halted);
+    }
+
+    #[test]
+    fn step_stops_at_breakpoint() {
+        let (program, record) = code::execute([add(5, 1), add(5, 2), add(5, 3)], &[], &[]);
+        let mut debugger = Debugger::new(&program, record.executed[0].state.clone());
+        debugger.add_breakpoint(8);
+        assert_eq!(debugger.step(10).unwrap(), Stop::Breakpoint(8));
+        assert_eq!(debugger.state().get_pc(), 8);
+    }
+
+    #[test]
+    fn read_memory() {
+        let (program, record) = code::execute([], &[(0x100, 7), (0x101, 8)], &[(REG_A1, 9)]);
+        let debugger = Debugger::new(&program, record.executed[0].state.clone());
+        assert_eq!(debugger.read_memory(0x100, 3), vec![7, 8, 0]);
+        assert_eq!(debugger.register(REG_A1), 9);
+    }
+
+    #[test]
+    fn write_state() {
+        let (program, record) = code::execute([add(5, 1)], &[], &[]);
+        let mut debugger = Debugger::new(&program, record.executed[0].state.clone());
+        debugger.set_register(5, 41);
+        debugger.set_register(0, 1);
+        debugger.write_memory(0x100, &[1, 2]).unwrap();
+        assert_eq!(debugger.step(1).unwrap(), Stop::Stepped);
+        assert_eq!(debugger.register(5), 42);
+        assert_eq!(debugger.register(0), 0);
+        assert_eq!(debugger.read_memory(0x100, 2), vec![1, 2]);
+
+        debugger.set_pc(0);
+        assert_eq!(debugger.step(1).unwrap(), Stop::Stepped);
+        assert_eq!(debugger.register(5), 43);
+    }
+
+    #[test]
+    fn watchpoints() {
+        let store = |offset| Instruction::sb(6, 0, offset);
+        let load = Instruction::lbu(7, 0, 0x102);
+        let instructions = [store(0x100), store(0x102), load, store(0x104)];
+        let (program, record) = code::execute(instructions, &[], &[(6, 0xff)]);
+        let mut debugger = Debugger::new(&program, record.executed[0].state.clone());
+        let logging = debugger.add_watchpoint(Watchpoint {
+            range: 0x100..0x104,
+            access: Access::ReadWrite,
+            pause: false,
+        });
+        debugger.add_watchpoint(Watchpoint {
+            range: 0x102..0x103,
+            access: Access::Read,
+            pause: true,
+        });
+
+        let read = WatchHit {
+            addr: 0x102,
+            access: Access::Read,
+            pc: 8,
+            clk: record.executed[2].state.clk,
+        };
+        assert_eq!(debugger.resume().unwrap(), Stop::Watchpoint(read));
+        assert_eq!(debugger.state().get_pc(), 12);
+        assert_eq!(debugger.register(7), 0xff);
+        assert_eq!(
+            debugger
+                .watch_hits()
+                .iter()
+                .map(|hit| (hit.addr, hit.access))
+                .collect::<Vec<_>>(),
+            vec![
+                (0x100, Access::Write),
+                (0x102, Access::Write),
+                (0x102, Access::Read)
+            ]
+        );
+
+        assert!(debugger.remove_watchpoint(logging).is_some());
+        assert_eq!(debugger.resume().unwrap(), Stop::Halted);
+        assert_eq!(debugger.watch_hits().len(), 3);
+    }
+
+    #[test]
+    fn locate_symbols() {
+        let mut symbols = Symbols::default();
+        symbols.insert("_start", 0x100);
+        symbols.insert("main", 0x200);
+        assert_eq!(symbols.address_of("main"), Some(0x200));
+        assert_eq!(symbols.locate(0x208), Some(("main", 8)));
+        assert_eq!(symbols.locate(0x1fc), Some(("_start", 0xfc)));
+        assert_eq!(symbols.locate(0x10), None);
+    }
+}
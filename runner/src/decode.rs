@@ -1,6 +1,6 @@
 use bitfield::{bitfield, BitRange};
-use log::warn;
 use mozak_sdk::core::reg_abi::{REG_A0, REG_A1, REG_ZERO};
+use tracing::warn;
 
 use crate::instruction::{Args, DecodingError, Instruction, Op, NOP};
 
@@ -171,6 +171,12 @@ pub fn decode_instruction(pc: u32, word: u32) -> Result<Instruction, DecodingErr
             (0x1, 0x01) => (Op::MULH, rtype),
             (0x2, 0x01) => (Op::MULHSU, rtype),
             (0x3, 0x01) => (Op::MULHU, rtype),
+            #[cfg(feature = "zbb")]
+            (0x7, 0x20) => (Op::ANDN, rtype),
+            #[cfg(feature = "zbb")]
+            (0x6, 0x20) => (Op::ORN, rtype),
+            #[cfg(feature = "zbb")]
+            (0x4, 0x20) => (Op::XNOR, rtype),
             _ => return default(),
         },
         0b000_0011 => match bf.funct3() {
@@ -195,6 +201,21 @@ pub fn decode_instruction(pc: u32, word: u32) -> Result<Instruction, DecodingErr
                 imm: 1 << itype.imm,
                 ..itype
             }),
+            // `clz`, `ctz` and `cpop` only differ in the `rs2` field, which we
+            // keep as the immediate, so that the circuits can tell them apart
+            // by a single column.
+            #[cfg(feature = "zbb")]
+            0x1 if (0x600..=0x602).contains(&itype.imm) => {
+                let op = match itype.imm & 0b11 {
+                    0 => Op::CLZ,
+                    1 => Op::CTZ,
+                    _ => Op::CPOP,
+                };
+                (op, Args {
+                    imm: itype.imm & 0b11,
+                    ..itype
+                })
+            }
             // For RISC-V it's SLTI, but we handle it as SLT.
             0x2 => (Op::SLT, itype),
             // For RISC-V it's SLTIU, but we handle it as SLTU.
@@ -240,6 +261,17 @@ pub fn decode_instruction(pc: u32, word: u32) -> Result<Instruction, DecodingErr
             // For RISC-V this would be (Op::CSRRW, itype),
             // but so far we implemented it as a no-op.
             (0x1, _) => nop,
+            // `rdcycle` and `rdinstret`, ie `CSRRS rd, cycle/instret, x0`.
+            (0x2, 0xC00 | 0xC02) if rs1 == REG_ZERO => (Op::RDCYCLE, Args {
+                rd,
+                ..Default::default()
+            }),
+            // `rdcycleh` and `rdinstreth`: our clock always fits into 32 bits, so the
+            // upper half is zero.
+            (0x2, 0xC80 | 0xC82) if rs1 == REG_ZERO => (Op::ADD, Args {
+                rd,
+                ..Default::default()
+            }),
             // For RISC-V this would be (Op::CSRRS, itype),
             // but so far we implemented it as a no-op.
             (0x2, _) => nop,
@@ -269,9 +301,17 @@ pub fn decode_instruction(pc: u32, word: u32) -> Result<Instruction, DecodingErr
         // AUIPC in RISC-V; but our ADD instruction is general enough to express the same semantics
         // without a new op-code.
         0b001_0111 => (Op::ADD, utype_absolute),
-        // For RISC-V this would be (Op::FENCE, itype)
-        // but so far we implemented it as a no-op.
-        0b000_1111 => nop,
+        // FENCE and FENCE.I. The VM runs a single hart, and code can not be
+        // modified, so there is nothing to order or to flush: both are no-ops.
+        0b000_1111 => match bf.funct3() {
+            0x0 | 0x1 => nop,
+            _ => return default(),
+        },
+        // custom-0, which RISC-V leaves to extensions.
+        0b000_1011 => match (bf.funct3(), bf.funct7()) {
+            (0x0, 0x00) => (Op::MADD, rtype),
+            _ => return default(),
+        },
         _ => return default(),
     };
 
@@ -1034,11 +1074,18 @@ mod tests {
     }
 
     #[test_case(0x0ff0_000f, 0, 0, 255; "fence, iorw, iorw")]
+    #[test_case(0x0330_000f, 0, 0, 51; "fence, rw, rw")]
+    #[test_case(0x0000_100f, 0, 0, 0; "fence.i")]
     fn fence(word: u32, _rd: u8, _rs1: u8, _imm: i32) {
         let ins: Instruction = decode_instruction(0, word);
         assert_eq!(ins, NOP);
     }
 
+    #[test]
+    fn reserved_misc_mem_is_unknown() {
+        assert!(super::decode_instruction(0, 0x0000_200f).is_err());
+    }
+
     #[test_case(0x3020_0073; "mret")]
     fn mret(word: u32) {
         let ins: Instruction = decode_instruction(0, word);
@@ -1051,6 +1098,85 @@ mod tests {
         assert_eq!(ins, NOP);
     }
 
+    #[test_case(0xC000_2573, 10; "rdcycle a0")]
+    #[test_case(0xC020_2573, 10; "rdinstret a0")]
+    fn rdcycle(word: u32, rd: u8) {
+        let ins: Instruction = decode_instruction(0, word);
+        let match_ins = Instruction {
+            op: Op::RDCYCLE,
+            args: Args {
+                rd,
+                ..Default::default()
+            },
+        };
+        assert_eq!(ins, match_ins);
+    }
+
+    #[test_case(0xC800_25F3, 11; "rdcycleh a1")]
+    #[test_case(0xC820_25F3, 11; "rdinstreth a1")]
+    fn rdcycleh(word: u32, rd: u8) {
+        let ins: Instruction = decode_instruction(0, word);
+        let match_ins = Instruction {
+            op: Op::ADD,
+            args: Args {
+                rd,
+                ..Default::default()
+            },
+        };
+        assert_eq!(ins, match_ins);
+    }
+
+    #[test_case(0x00C5_850B, 10, 11, 12; "madd a0, a1, a2")]
+    fn madd(word: u32, rd: u8, rs1: u8, rs2: u8) {
+        let ins: Instruction = decode_instruction(0, word);
+        let match_ins = Instruction {
+            op: Op::MADD,
+            args: Args {
+                rd,
+                rs1,
+                rs2,
+                ..Default::default()
+            },
+        };
+        assert_eq!(ins, match_ins);
+    }
+
+    #[cfg(feature = "zbb")]
+    #[test_case(0x40C5_F533, Op::ANDN, 10, 11, 12; "andn a0, a1, a2")]
+    #[test_case(0x40C5_E533, Op::ORN, 10, 11, 12; "orn a0, a1, a2")]
+    #[test_case(0x40C5_C533, Op::XNOR, 10, 11, 12; "xnor a0, a1, a2")]
+    fn zbb_register(word: u32, op: Op, rd: u8, rs1: u8, rs2: u8) {
+        let ins: Instruction = decode_instruction(0, word);
+        let match_ins = Instruction {
+            op,
+            args: Args {
+                rd,
+                rs1,
+                rs2,
+                ..Default::default()
+            },
+        };
+        assert_eq!(ins, match_ins);
+    }
+
+    #[cfg(feature = "zbb")]
+    #[test_case(0x6005_9513, Op::CLZ, 10, 11, 0; "clz a0, a1")]
+    #[test_case(0x6015_9513, Op::CTZ, 10, 11, 1; "ctz a0, a1")]
+    #[test_case(0x6025_9513, Op::CPOP, 10, 11, 2; "cpop a0, a1")]
+    fn zbb_count(word: u32, op: Op, rd: u8, rs1: u8, imm: u32) {
+        let ins: Instruction = decode_instruction(0, word);
+        let match_ins = Instruction {
+            op,
+            args: Args {
+                rd,
+                rs1,
+                imm,
+                ..Default::default()
+            },
+        };
+        assert_eq!(ins, match_ins);
+    }
+
     #[test_case(0x3052_9073, 0, 5, 773; "csrrw, mtvec, t0")]
     fn csrrw(word: u32, _rd: u8, _rs1: u8, _imm: u32) {
         let ins: Instruction = decode_instruction(0, word);
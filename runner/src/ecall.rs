@@ -2,11 +2,22 @@
 
 use std::str::from_utf8;
 
+use anyhow::{anyhow, Result};
+use mozak_sdk::core::constants::{EVENT_BYTES, EVENT_TYPES, EVENT_TYPE_OFFSET};
 use mozak_sdk::core::ecall;
-use mozak_sdk::core::reg_abi::{REG_A0, REG_A1, REG_A2};
+use mozak_sdk::core::reg_abi::{REG_A0, REG_A1, REG_A2, REG_A3};
+use mozak_sdk::core::trap::TrapReport;
 use plonky2::hash::hash_types::RichField;
 
-use crate::state::{read_bytes, Aux, State, StorageDeviceEntry, StorageDeviceOpcode};
+#[cfg(feature = "goldilocks")]
+use crate::goldilocks;
+#[cfg(feature = "memcopy")]
+use crate::memcopy;
+use crate::state::{
+    read_bytes, Aux, EventEntry, HintRequest, State, StorageDeviceEntry, StorageDeviceOpcode,
+};
+#[cfg(feature = "u256")]
+use crate::u256;
 
 impl<F: RichField> State<F> {
     fn ecall_halt(self) -> (Aux<F>, Self) {
@@ -28,8 +39,16 @@ impl<F: RichField> State<F> {
     fn ecall_read(mut self, op: StorageDeviceOpcode) -> (Aux<F>, Self) {
         let buffer_start = self.get_register_value(REG_A1);
         let num_bytes_requested = self.get_register_value(REG_A2);
-        log::trace!("ECALL {:?}", op);
+        tracing::trace!("ECALL {:?}", op);
 
+        let offset = match op {
+            StorageDeviceOpcode::StorePublic => self.public_tape.read_index,
+            StorageDeviceOpcode::StorePrivate => self.private_tape.read_index,
+            StorageDeviceOpcode::StoreCallTape => self.call_tape.read_index,
+            StorageDeviceOpcode::StoreEventTape => self.event_tape.read_index,
+            StorageDeviceOpcode::StoreHint => self.hint_tape.read_index,
+            _ => 0,
+        };
         let data = match op {
             StorageDeviceOpcode::StorePublic => read_bytes(
                 &self.public_tape.data,
@@ -66,7 +85,19 @@ impl<F: RichField> State<F> {
                 &mut 0,
                 num_bytes_requested as usize,
             ),
-            StorageDeviceOpcode::None => panic!(),
+            StorageDeviceOpcode::StoreHint => {
+                let data: Vec<u8> = self
+                    .hint_tape
+                    .data
+                    .iter()
+                    .skip(self.hint_tape.read_index)
+                    .take(num_bytes_requested as usize)
+                    .copied()
+                    .collect();
+                self.hint_tape.read_index += data.len();
+                data
+            }
+            StorageDeviceOpcode::None | StorageDeviceOpcode::LoadOutputTape => panic!(),
         };
         let data_len = u32::try_from(data.len()).expect("cannot fit data.len() into u32");
         let mem_addresses_used: Vec<u32> = (0..data_len)
@@ -80,6 +111,8 @@ impl<F: RichField> State<F> {
                     addr: buffer_start,
                     op,
                     data: data.clone(),
+                    offset: u32::try_from(offset).expect("cannot fit offset into u32"),
+                    is_read_at: false,
                 }),
                 ..Default::default()
             },
@@ -96,6 +129,74 @@ impl<F: RichField> State<F> {
         )
     }
 
+    /// Reads from the public tape starting at the offset in `a3`, and leaves
+    /// the read position right after the bytes read.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the offset lies beyond the end of the public tape.
+    fn ecall_public_tape_read_at(mut self) -> (Aux<F>, Self) {
+        let offset = self.get_register_value(REG_A3) as usize;
+        assert!(
+            offset <= self.public_tape.data.len(),
+            "cannot read the public tape at {offset}, past its end"
+        );
+        self.public_tape.read_index = offset;
+        let (mut aux, state) = self.ecall_read(StorageDeviceOpcode::StorePublic);
+        if let Some(entry) = aux.storage_device_entry.as_mut() {
+            entry.is_read_at = true;
+        }
+        (aux, state)
+    }
+
+    /// Reads `a2` hints into `a1`, like a tape. Hints beyond the queued ones
+    /// come from the [`HintProvider`](crate::state::HintProvider), if any;
+    /// without one, the read comes up short.
+    fn ecall_hint_read(mut self) -> (Aux<F>, Self) {
+        let num_bytes_requested = self.get_register_value(REG_A2) as usize;
+        let missing = (self.hint_tape.read_index + num_bytes_requested)
+            .saturating_sub(self.hint_tape.data.len());
+        if let Some(provider) = self.hint_tape.provider.clone().filter(|_| missing > 0) {
+            let hints = provider.provide(&HintRequest {
+                clk: self.clk,
+                len: missing,
+                query: self.get_register_value(REG_A3),
+                memory: &self.memory,
+            });
+            self.hint_tape.data.extend(hints);
+        }
+        self.ecall_read(StorageDeviceOpcode::StoreHint)
+    }
+
+    /// Appends the `a2` bytes at `a1` to the output tape.
+    fn ecall_io_write(mut self) -> (Aux<F>, Self) {
+        let buffer_start = self.get_register_value(REG_A1);
+        let num_bytes = self.get_register_value(REG_A2);
+        let mem_addresses_used: Vec<u32> = (0..num_bytes)
+            .map(|i| buffer_start.wrapping_add(i))
+            .collect();
+        let data: Vec<u8> = mem_addresses_used
+            .iter()
+            .map(|&addr| self.load_u8(addr))
+            .collect();
+        let offset = u32::try_from(self.output_tape.len()).expect("cannot fit offset into u32");
+        self.output_tape.extend(data.iter().copied());
+        (
+            Aux {
+                mem_addresses_used,
+                storage_device_entry: Some(StorageDeviceEntry {
+                    addr: buffer_start,
+                    op: StorageDeviceOpcode::LoadOutputTape,
+                    data,
+                    offset,
+                    is_read_at: false,
+                }),
+                ..Default::default()
+            },
+            self.bump_pc(),
+        )
+    }
+
     /// # Panics
     ///
     /// Panics if Vec<u8> to string conversion fails.
@@ -112,6 +213,93 @@ impl<F: RichField> State<F> {
         );
     }
 
+    /// Halts the VM on a failed guest assertion and records the
+    /// [`TrapReport`] the guest wrote, so that callers can surface it.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the guest did not provide a well-formed report.
+    fn ecall_trap(mut self) -> Result<(Aux<F>, Self)> {
+        let code = self.get_register_value(REG_A1);
+        let report_ptr = self.get_register_value(REG_A2);
+        let report_len = self.get_register_value(REG_A3);
+        let report_bytes: Vec<u8> = (0..report_len)
+            .map(|i| self.load_u8(report_ptr.wrapping_add(i)))
+            .collect();
+        let report = TrapReport::from_bytes(code, &report_bytes).ok_or_else(|| {
+            anyhow!(
+                "guest trapped with code {code} at clk {}, but its report at {report_ptr:#x} \
+                 is malformed",
+                self.clk
+            )
+        })?;
+        tracing::error!("VM trapped at clk {}: {report}", self.clk);
+        self.trap = Some(report);
+        Ok(self.ecall_halt())
+    }
+
+    /// Checks a provable assertion. A failure only gets logged: the CPU table
+    /// constrains the condition, so such an execution can not be proven.
+    fn ecall_prove_assert(self) -> (Aux<F>, Self) {
+        let cond = self.get_register_value(REG_A1);
+        if cond != 1 {
+            tracing::error!("provable assertion failed at clk {}", self.clk);
+        }
+        (Aux::default(), self.bump_pc())
+    }
+
+    /// Emits the event at `a1`, which is `a2` bytes long.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the event is not [`EVENT_BYTES`] long, or has an unknown
+    /// event type.
+    fn ecall_emit_event(self) -> (Aux<F>, Self) {
+        let addr = self.get_register_value(REG_A1);
+        let len = self.get_register_value(REG_A2);
+        assert_eq!(
+            len as usize, EVENT_BYTES,
+            "events are {EVENT_BYTES} bytes long, but got {len} bytes"
+        );
+        let mem_addresses_used: Vec<u32> = (0..len).map(|i| addr.wrapping_add(i)).collect();
+        let event = core::array::from_fn(|i| self.load_u8(mem_addresses_used[i]));
+        assert!(
+            usize::from(event[EVENT_TYPE_OFFSET]) < EVENT_TYPES,
+            "unknown event type {}",
+            event[EVENT_TYPE_OFFSET]
+        );
+        (
+            Aux {
+                mem_addresses_used,
+                event: Some(EventEntry { addr, event }),
+                ..Aux::default()
+            },
+            self.bump_pc(),
+        )
+    }
+
+    /// Moves the program break up to the address in `a1`, which maps the heap
+    /// below it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new break lies below the current one, or past the end of
+    /// the heap.
+    fn ecall_sbrk(mut self) -> (Aux<F>, Self) {
+        let new_break = self.get_register_value(REG_A1);
+        if let Some(unmapped) = &mut self.unmapped_heap {
+            assert!(
+                (unmapped.start..=unmapped.end).contains(&new_break),
+                "cannot move the break from {:#x} to {new_break:#x}, outside of {:#x}..={:#x}",
+                unmapped.start,
+                unmapped.start,
+                unmapped.end
+            );
+            unmapped.start = new_break;
+        }
+        (Aux::default(), self.bump_pc())
+    }
+
     /// Outputs the VM trace log at `clk`. Useful for debugging.
     /// # Panics
     ///
@@ -123,24 +311,28 @@ impl<F: RichField> State<F> {
         for addr in msg_ptr..(msg_ptr + msg_len) {
             msg_vec.push(self.load_u8(addr));
         }
-        log::debug!(
+        tracing::debug!(
             "VM TRACE LOG: {}",
             from_utf8(&msg_vec).expect("A valid utf8 VM trace log message should be provided")
         );
         (Aux::default(), self.bump_pc())
     }
 
-    #[must_use]
-    pub fn ecall(self) -> (Aux<F>, Self) {
-        log::trace!(
+    /// # Errors
+    ///
+    /// Errors if the guest trapped without a well-formed [`TrapReport`], or
+    /// asked for a secp256k1 operation with an undefined slope.
+    pub fn ecall(self) -> Result<(Aux<F>, Self)> {
+        tracing::trace!(
             "ecall '{}' at clk: {}",
             ecall::log(self.get_register_value(REG_A0)),
             self.clk
         );
-        match self.get_register_value(REG_A0) {
+        Ok(match self.get_register_value(REG_A0) {
             ecall::HALT => self.ecall_halt(),
             ecall::PRIVATE_TAPE => self.ecall_read(StorageDeviceOpcode::StorePrivate),
             ecall::PUBLIC_TAPE => self.ecall_read(StorageDeviceOpcode::StorePublic),
+            ecall::PUBLIC_TAPE_READ_AT => self.ecall_public_tape_read_at(),
             ecall::CALL_TAPE => self.ecall_read(StorageDeviceOpcode::StoreCallTape),
             ecall::EVENT_TAPE => self.ecall_read(StorageDeviceOpcode::StoreEventTape),
             ecall::EVENTS_COMMITMENT_TAPE =>
@@ -149,9 +341,50 @@ impl<F: RichField> State<F> {
                 self.ecall_read(StorageDeviceOpcode::StoreCastListCommitmentTape),
             ecall::SELF_PROG_ID_TAPE => self.ecall_read(StorageDeviceOpcode::StoreSelfProgIdTape),
             ecall::PANIC => self.ecall_panic(),
+            #[cfg(feature = "poseidon2")]
             ecall::POSEIDON2 => self.ecall_poseidon2(),
+            #[cfg(not(feature = "poseidon2"))]
+            ecall::POSEIDON2 => panic!("the poseidon2 ecall was excluded from this build"),
             ecall::VM_TRACE_LOG => self.ecall_trace_log(),
+            ecall::TRAP => return self.ecall_trap(),
+            ecall::PROVE_ASSERT => self.ecall_prove_assert(),
+            #[cfg(feature = "secp256k1")]
+            ecall::SECP256K1_ADD => return self.ecall_secp256k1(false),
+            #[cfg(feature = "secp256k1")]
+            ecall::SECP256K1_DOUBLE => return self.ecall_secp256k1(true),
+            #[cfg(not(feature = "secp256k1"))]
+            ecall::SECP256K1_ADD | ecall::SECP256K1_DOUBLE =>
+                panic!("the secp256k1 ecalls were excluded from this build"),
+            ecall::EMIT_EVENT => self.ecall_emit_event(),
+            #[cfg(feature = "u256")]
+            ecall::U256_ADD => self.ecall_u256(u256::Operation::Add),
+            #[cfg(feature = "u256")]
+            ecall::U256_MUL => self.ecall_u256(u256::Operation::Mul),
+            #[cfg(feature = "u256")]
+            ecall::U256_MULMOD => self.ecall_u256(u256::Operation::MulMod),
+            #[cfg(not(feature = "u256"))]
+            ecall::U256_ADD | ecall::U256_MUL | ecall::U256_MULMOD =>
+                panic!("the u256 ecalls were excluded from this build"),
+            #[cfg(feature = "memcopy")]
+            ecall::MEMCOPY => self.ecall_memcopy(memcopy::Operation::Copy),
+            #[cfg(feature = "memcopy")]
+            ecall::MEMSET => self.ecall_memcopy(memcopy::Operation::Set),
+            #[cfg(not(feature = "memcopy"))]
+            ecall::MEMCOPY | ecall::MEMSET =>
+                panic!("the memcopy ecalls were excluded from this build"),
+            #[cfg(feature = "goldilocks")]
+            ecall::GOLDILOCKS_ADD => self.ecall_goldilocks(goldilocks::Operation::Add),
+            #[cfg(feature = "goldilocks")]
+            ecall::GOLDILOCKS_MUL => self.ecall_goldilocks(goldilocks::Operation::Mul),
+            #[cfg(feature = "goldilocks")]
+            ecall::GOLDILOCKS_INVERSE => self.ecall_goldilocks(goldilocks::Operation::Inverse),
+            #[cfg(not(feature = "goldilocks"))]
+            ecall::GOLDILOCKS_ADD | ecall::GOLDILOCKS_MUL | ecall::GOLDILOCKS_INVERSE =>
+                panic!("the Goldilocks ecalls were excluded from this build"),
+            ecall::SBRK => self.ecall_sbrk(),
+            ecall::IO_WRITE => self.ecall_io_write(),
+            ecall::HINT_READ => self.ecall_hint_read(),
             _ => (Aux::default(), self.bump_pc()),
-        }
+        })
     }
 }
@@ -1,7 +1,8 @@
-use std::cmp::{max, min};
+use std::collections::HashSet;
 use std::iter::repeat;
+use std::ops::Range;
 
-use anyhow::{anyhow, ensure, Result};
+use anyhow::{anyhow, bail, ensure, Result};
 use elf::endian::LittleEndian;
 use elf::file::Class;
 use elf::segment::{ProgramHeader, SegmentTable};
@@ -28,6 +29,40 @@ pub struct Program {
 
     /// Executable code of the ELF, read only
     pub ro_code: Code,
+
+    /// Address ranges that no instruction may access, like a guard page
+    /// below the stack.
+    ///
+    /// Loaded from `PT_LOAD` segments without any of the read, write or
+    /// execute flags, so a linker script can place them via `PHDRS`.
+    #[serde(default)]
+    pub guard_regions: Vec<Range<u32>>,
+
+    /// Address range the guest heap grows into, via the `SBRK` ecall.
+    /// Accessing the part of it above the current program break traps.
+    ///
+    /// Loaded from the `__heap_start` and `__heap_end` symbols, which a linker
+    /// script can define, e.g. as `_end` and the bottom of the stack. Without
+    /// a heap region, the break may move anywhere and nothing traps.
+    #[serde(default)]
+    pub heap: Option<Range<u32>>,
+
+    /// Address range of the guest stack.
+    ///
+    /// Loaded from the `__stack_start` and `__stack_end` symbols, like
+    /// [`Program::heap`]. A stack region declares the memory layout of the
+    /// program: only the ELF image, the stack and the heap are mapped then,
+    /// see [`Program::is_mapped`]. Without one, all memory is mapped.
+    #[serde(default)]
+    pub stack: Option<Range<u32>>,
+
+    /// The library linked in with [`Program::link`].
+    ///
+    /// Its image is part of the fields above, too, but the proof commits to
+    /// it separately, so that its commitment stays the same for every program
+    /// that links it.
+    #[serde(default)]
+    pub library: Option<Box<Program>>,
 }
 
 /// Memory of RISC-V Program
@@ -56,6 +91,10 @@ impl From<HashMap<u32, u32>> for Program {
             ro_code: Code::from(&image),
             ro_memory: Data::default(),
             rw_memory: Data(image),
+            guard_regions: Vec::new(),
+            heap: None,
+            stack: None,
+            library: None,
         }
     }
 }
@@ -90,6 +129,56 @@ impl From<HashMap<u32, u32>> for Data {
     }
 }
 
+/// A `PT_LOAD` segment of an ELF.
+///
+/// In memory, the segment spans `mem_size` bytes from `vaddr`. The first
+/// `file_size` of them come from the ELF at `offset`, and the rest, eg
+/// `.bss`, are zero-initialised.
+struct LoadSegment {
+    vaddr: u32,
+    offset: usize,
+    file_size: usize,
+    mem_size: usize,
+}
+
+impl LoadSegment {
+    fn parse(program_header: &ProgramHeader, input_len: usize) -> Result<Self> {
+        let segment = LoadSegment {
+            vaddr: program_header.p_vaddr.try_into()?,
+            offset: program_header.p_offset.try_into()?,
+            file_size: program_header.p_filesz.try_into()?,
+            mem_size: program_header.p_memsz.try_into()?,
+        };
+        ensure!(
+            segment.file_size <= segment.mem_size,
+            "Segment at {:#x} has more bytes in the file than in memory",
+            segment.vaddr
+        );
+        ensure!(
+            segment
+                .offset
+                .checked_add(segment.file_size)
+                .is_some_and(|end| end <= input_len),
+            "Segment at {:#x} extends past the end of the file",
+            segment.vaddr
+        );
+        ensure!(
+            u64::try_from(segment.mem_size)? <= (1 << 32) - u64::from(segment.vaddr),
+            "Segment at {:#x} extends past the end of memory",
+            segment.vaddr
+        );
+        Ok(segment)
+    }
+
+    /// The bytes of the segment in memory, by address, including the
+    /// zero-initialised ones past `file_size`.
+    fn bytes(self, input: &[u8]) -> impl Iterator<Item = (u32, u8)> + '_ {
+        let file_bytes = input[self.offset..][..self.file_size].iter().copied();
+        let zero_init = repeat(0_u8).take(self.mem_size - self.file_size);
+        (self.vaddr..=u32::MAX).zip(chain!(file_bytes, zero_init))
+    }
+}
+
 impl Program {
     /// Vanilla load-elf - NOT expect "_mozak_*" symbols in link. Maybe we
     /// should rename it later, with `vanilla_` prefix
@@ -185,12 +274,63 @@ impl Program {
             &segments,
         ));
 
+        let guard_regions = segments
+            .iter()
+            .filter(|program_header| {
+                program_header.p_type == elf::abi::PT_LOAD
+                    && program_header.p_flags & (elf::abi::PF_R | elf::abi::PF_W | elf::abi::PF_X)
+                        == elf::abi::PF_NONE
+            })
+            .map(|program_header| -> anyhow::Result<_> {
+                let vaddr: u32 = program_header.p_vaddr.try_into()?;
+                let mem_size: u32 = program_header.p_memsz.try_into()?;
+                Ok(vaddr..vaddr.saturating_add(mem_size))
+            })
+            .try_collect()
+            .expect("extract guard regions should always succeed");
+
+        // Symbols are optional, so a malformed symbol table just means there are
+        // no heap and stack regions.
+        let heap = Program::extract_region(input, "__heap_start", "__heap_end")
+            .ok()
+            .flatten();
+        let stack = Program::extract_region(input, "__stack_start", "__stack_end")
+            .ok()
+            .flatten();
+
         Program {
             entry_point,
             ro_memory,
             rw_memory,
             ro_code,
+            guard_regions,
+            heap,
+            stack,
+            library: None,
+        }
+    }
+
+    /// Reads a region from the symbols `start_symbol` and `end_symbol`, if the
+    /// ELF defines both.
+    fn extract_region(
+        input: &[u8],
+        start_symbol: &str,
+        end_symbol: &str,
+    ) -> Result<Option<Range<u32>>> {
+        let elf = ElfBytes::<LittleEndian>::minimal_parse(input)?;
+        let Some((table, names)) = elf.symbol_table()? else {
+            return Ok(None);
+        };
+        let (mut start, mut end) = (None, None);
+        for symbol in table.iter() {
+            let name = names.get(symbol.st_name.try_into()?)?;
+            if name == start_symbol {
+                start = Some(u32::try_from(symbol.st_value)?);
+            } else if name == end_symbol {
+                end = Some(u32::try_from(symbol.st_value)?);
+            }
         }
+        Ok(start.zip(end).map(|(start, end)| start..end))
     }
 
     fn extract_elf_data(
@@ -200,20 +340,12 @@ impl Program {
     ) -> HashMap<u32, u8> {
         segments
             .iter()
-            .filter(|program_header| check_program_flags(program_header.p_flags, program_header))
-            .map(|program_header| -> anyhow::Result<_> {
-                let file_size: usize = program_header.p_filesz.try_into()?;
-                let mem_size: usize = program_header.p_memsz.try_into()?;
-                let vaddr: u32 = program_header.p_vaddr.try_into()?;
-                let offset = program_header.p_offset.try_into()?;
-
-                let min_size = min(file_size, mem_size);
-                let max_size = max(file_size, mem_size);
-                Ok((vaddr..).zip(
-                    chain!(&input[offset..][..min_size], repeat(&0u8))
-                        .take(max_size)
-                        .copied(),
-                ))
+            .filter(|program_header| {
+                program_header.p_type == elf::abi::PT_LOAD
+                    && check_program_flags(program_header.p_flags, program_header)
+            })
+            .map(|program_header| {
+                LoadSegment::parse(&program_header, input.len()).map(|segment| segment.bytes(input))
             })
             .flatten_ok()
             .try_collect()
@@ -246,6 +378,101 @@ impl Program {
             ..Default::default()
         }
     }
+
+    /// Adds a region that no instruction may access, see
+    /// [`Program::guard_regions`].
+    #[must_use]
+    pub fn with_guard_region(mut self, region: Range<u32>) -> Program {
+        self.guard_regions.push(region);
+        self
+    }
+
+    /// Sets the region the heap grows into, see [`Program::heap`].
+    #[must_use]
+    pub fn with_heap(mut self, heap: Range<u32>) -> Program {
+        self.heap = Some(heap);
+        self
+    }
+
+    /// Sets the stack region, and with it the memory layout, see
+    /// [`Program::stack`].
+    #[must_use]
+    pub fn with_stack(mut self, stack: Range<u32>) -> Program {
+        self.stack = Some(stack);
+        self
+    }
+
+    /// Links `library` into the program, e.g. a runtime library that many
+    /// programs share. The entry point, the heap and the stack stay those of
+    /// the program.
+    ///
+    /// # Errors
+    /// Errors if either side links a library already, or if the two images
+    /// overlap.
+    pub fn link(self, library: Program) -> Result<Program> {
+        ensure!(
+            self.library.is_none() && library.library.is_none(),
+            "a program can only link a single library"
+        );
+        let occupied: HashSet<u32> = self.addresses().collect();
+        if let Some(addr) = library.addresses().find(|addr| occupied.contains(addr)) {
+            bail!("the library overlaps the program at {addr:#x}");
+        }
+        Ok(Program {
+            entry_point: self.entry_point,
+            ro_memory: Data(self.ro_memory.0.union(library.ro_memory.0.clone())),
+            rw_memory: Data(self.rw_memory.0.union(library.rw_memory.0.clone())),
+            ro_code: Code(self.ro_code.0.union(library.ro_code.0.clone())),
+            guard_regions: chain!(self.guard_regions, library.guard_regions.clone()).collect(),
+            heap: self.heap,
+            stack: self.stack,
+            library: Some(Box::new(library)),
+        })
+    }
+
+    /// Whether the code or data at `addr` belongs to the linked library.
+    #[must_use]
+    pub fn in_library(&self, addr: u32) -> bool {
+        self.library.as_ref().is_some_and(|library| {
+            library.ro_code.contains_key(&addr)
+                || library.ro_memory.contains_key(&addr)
+                || library.rw_memory.contains_key(&addr)
+        })
+    }
+
+    /// Every address that the code or the data of the program occupies.
+    fn addresses(&self) -> impl Iterator<Item = u32> + '_ {
+        chain!(
+            self.ro_code
+                .keys()
+                .flat_map(|&pc| (0..4).map(move |i| pc.wrapping_add(i))),
+            self.ro_memory.keys().copied(),
+            self.rw_memory.keys().copied(),
+        )
+    }
+
+    /// Whether instructions may access `addr`, see [`Program::stack`].
+    ///
+    /// The part of the heap above the program break counts as mapped here,
+    /// but accessing it traps all the same.
+    #[must_use]
+    pub fn is_mapped(&self, addr: u32) -> bool {
+        let Some(stack) = &self.stack else {
+            return true;
+        };
+        stack.contains(&addr)
+            || self.heap.as_ref().is_some_and(|heap| heap.contains(&addr))
+            || self.ro_memory.contains_key(&addr)
+            || self.rw_memory.contains_key(&addr)
+    }
+
+    /// Returns the guard region containing `addr`, if any.
+    #[must_use]
+    pub fn guard_region_of(&self, addr: u32) -> Option<&Range<u32>> {
+        self.guard_regions
+            .iter()
+            .find(|region| region.contains(&addr))
+    }
 }
 
 #[cfg(test)]
@@ -260,8 +487,114 @@ mod test {
         assert_eq!(program, deserialized);
     }
 
+    #[test]
+    fn link_library() {
+        let program = Program::create(&[(0x100, 1)], &[(0x104, 2)], Code::default());
+        let library = Program::create(&[(0x200, 3)], &[], Code::default());
+        let linked = program.link(library.clone()).unwrap();
+        assert_eq!(linked.ro_memory.get(&0x200), Some(&3));
+        assert_eq!(linked.rw_memory.get(&0x104), Some(&2));
+        assert!(linked.in_library(0x200));
+        assert!(!linked.in_library(0x100));
+        assert_eq!(linked.library.as_deref(), Some(&library));
+        assert!(linked.link(Program::default()).is_err());
+    }
+
+    #[test]
+    fn link_overlapping_library() {
+        let program = Program::create(&[(0x100, 1)], &[], Code::default());
+        let library = Program::create(&[], &[(0x100, 2)], Code::default());
+        assert!(program.link(library).is_err());
+    }
+
+    #[test]
+    fn mapped_memory() {
+        let program = Program::create(&[(0x100, 1)], &[(0x104, 2)], Code::default());
+        assert!(program.is_mapped(0x8000));
+        let program = program.with_stack(0x8000..0x9000).with_heap(0x4000..0x5000);
+        for addr in [0x100, 0x104, 0x4000, 0x8FFF] {
+            assert!(program.is_mapped(addr), "{addr:#x}");
+        }
+        for addr in [0, 0x101, 0x5000, 0x9000, u32::MAX] {
+            assert!(!program.is_mapped(addr), "{addr:#x}");
+        }
+    }
+
     #[test]
     fn test_mozak_load_program_default() {
         Program::mozak_load_program(mozak_examples::EMPTY_ELF).unwrap();
     }
+
+    /// A minimal RISC-V ELF with one `PT_LOAD` segment at `vaddr`, that has
+    /// `file_bytes` in the file, followed by `trailing` bytes that do not
+    /// belong to the segment.
+    fn elf_with_segment(
+        flags: u32,
+        vaddr: u32,
+        file_bytes: &[u8],
+        mem_size: u32,
+        trailing: &[u8],
+    ) -> Vec<u8> {
+        const EHDR_SIZE: u32 = 52;
+        const PHDR_SIZE: u32 = 32;
+        let mut bytes = vec![0x7f, b'E', b'L', b'F', 1, 1, 1];
+        bytes.resize(16, 0);
+        // The ELF header, with the program header right after it ..
+        for half in [elf::abi::ET_EXEC, elf::abi::EM_RISCV] {
+            bytes.extend(half.to_le_bytes());
+        }
+        for word in [1, vaddr, EHDR_SIZE, 0, 0] {
+            bytes.extend(word.to_le_bytes());
+        }
+        for half in [52_u16, 32, 1, 40, 0, 0] {
+            bytes.extend(half.to_le_bytes());
+        }
+        // .. and the segment right after that.
+        let file_size = u32::try_from(file_bytes.len()).unwrap();
+        for word in [
+            elf::abi::PT_LOAD,
+            EHDR_SIZE + PHDR_SIZE,
+            vaddr,
+            vaddr,
+            file_size,
+            mem_size,
+            flags,
+            4,
+        ] {
+            bytes.extend(word.to_le_bytes());
+        }
+        bytes.extend(file_bytes);
+        bytes.extend(trailing);
+        bytes
+    }
+
+    #[test]
+    fn test_load_large_bss() {
+        const BSS_SIZE: u32 = 1 << 16;
+        let elf = elf_with_segment(
+            elf::abi::PF_R | elf::abi::PF_W,
+            0x1000,
+            &[1, 2, 3, 4],
+            4 + BSS_SIZE,
+            &[0xff; 4],
+        );
+        let program = Program::vanilla_load_elf(&elf).unwrap();
+        let rw_memory = &program.rw_memory;
+        assert_eq!(rw_memory.len(), 4 + BSS_SIZE as usize);
+        assert_eq!(
+            (0x1000..0x1004).map(|addr| rw_memory[&addr]).collect_vec(),
+            [1, 2, 3, 4]
+        );
+        // Bytes in the file past `p_filesz` are not part of the segment.
+        assert!((0x1004..0x1004 + BSS_SIZE).all(|addr| rw_memory[&addr] == 0));
+        assert!(!rw_memory.contains_key(&(0x1004 + BSS_SIZE)));
+        assert!(program.ro_memory.is_empty());
+    }
+
+    #[test]
+    #[should_panic = "more bytes in the file than in memory"]
+    fn test_file_size_beyond_mem_size() {
+        let elf = elf_with_segment(elf::abi::PF_R, 0x1000, &[1, 2, 3, 4], 2, &[]);
+        let _ = Program::vanilla_load_elf(&elf);
+    }
 }
@@ -0,0 +1,194 @@
+//! The Goldilocks field addition, multiplication and inversion ecalls.
+//!
+//! Operands are passed by pointers to [`GOLDILOCKS_BYTES`] little-endian bytes,
+//! which must hold a canonical field element. The result overwrites the first
+//! operand.
+
+use itertools::{chain, izip};
+use mozak_sdk::core::constants::GOLDILOCKS_BYTES;
+use mozak_sdk::core::reg_abi::{REG_A1, REG_A2};
+use plonky2::field::goldilocks_field::GoldilocksField;
+use plonky2::field::types::{Field, Field64, PrimeField64};
+use plonky2::hash::hash_types::RichField;
+
+use crate::state::{Aux, State};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    /// `a + b`.
+    Add,
+    /// `a * b`.
+    Mul,
+    /// `1 / a`.
+    Inverse,
+}
+
+impl Operation {
+    /// Whether the operation has a second operand `b`.
+    #[must_use]
+    pub fn is_binary(self) -> bool { self != Self::Inverse }
+}
+
+/// The field element with canonical representative `value`.
+///
+/// # Panics
+///
+/// Panics if `value` is not canonical, ie not below the field order.
+#[must_use]
+pub fn to_field(value: u64) -> GoldilocksField {
+    assert!(
+        value < GoldilocksField::ORDER,
+        "{value:#x} is not a canonical Goldilocks element"
+    );
+    GoldilocksField::from_canonical_u64(value)
+}
+
+/// Applies `operation` to `a` and `b`, where `b` is ignored for
+/// [`Operation::Inverse`].
+///
+/// # Panics
+///
+/// Panics if an operand is not canonical, or when inverting zero.
+#[must_use]
+pub fn apply(operation: Operation, a: u64, b: u64) -> u64 {
+    let a = to_field(a);
+    match operation {
+        Operation::Add => a + to_field(b),
+        Operation::Mul => a * to_field(b),
+        Operation::Inverse => a.try_inverse().expect("Goldilocks inverse of zero"),
+    }
+    .to_canonical_u64()
+}
+
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub operation: Operation,
+    pub a_addr: u32,
+    /// Zero for [`Operation::Inverse`].
+    pub b_addr: u32,
+    pub a: u64,
+    /// Zero for [`Operation::Inverse`].
+    pub b: u64,
+    pub result: u64,
+}
+
+impl<F: RichField> State<F> {
+    fn load_goldilocks(&self, addr: u32) -> u64 {
+        let mut value = [0; GOLDILOCKS_BYTES];
+        for (i, byte) in izip!(0_u32.., &mut value) {
+            *byte = self.load_u8(addr.wrapping_add(i));
+        }
+        u64::from_le_bytes(value)
+    }
+
+    /// Applies `operation` to the field elements at `a1` and `a2`, or only to
+    /// the one at `a1` for [`Operation::Inverse`], and writes the result over
+    /// the one at `a1`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an operand is not canonical, or when inverting zero.
+    #[must_use]
+    pub fn ecall_goldilocks(self, operation: Operation) -> (Aux<F>, Self) {
+        let is_binary = operation.is_binary();
+        let a_addr = self.get_register_value(REG_A1);
+        let b_addr = if is_binary {
+            self.get_register_value(REG_A2)
+        } else {
+            0
+        };
+        let a = self.load_goldilocks(a_addr);
+        let b = if is_binary {
+            self.load_goldilocks(b_addr)
+        } else {
+            0
+        };
+        let result = apply(operation, a, b);
+
+        let addresses = |addr: u32| {
+            (0..)
+                .take(GOLDILOCKS_BYTES)
+                .map(move |i| addr.wrapping_add(i))
+        };
+        let mem_addresses_used: Vec<u32> = chain!(
+            addresses(a_addr),
+            is_binary.then(|| addresses(b_addr)).into_iter().flatten(),
+            addresses(a_addr),
+        )
+        .collect();
+        (
+            Aux {
+                mem_addresses_used,
+                goldilocks: Some(Entry {
+                    operation,
+                    a_addr,
+                    b_addr,
+                    a,
+                    b,
+                    result,
+                }),
+                ..Default::default()
+            },
+            izip!(0.., result.to_le_bytes())
+                .fold(self, |updated_self, (i, byte)| {
+                    updated_self.store_u8(a_addr.wrapping_add(i), byte).unwrap()
+                })
+                .bump_pc(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mozak_sdk::core::ecall;
+    use mozak_sdk::core::reg_abi::{REG_A0, REG_A1, REG_A2};
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use plonky2::field::types::Field64;
+
+    use crate::code;
+    use crate::decode::ECALL;
+
+    const MINUS_ONE: u64 = GoldilocksField::ORDER - 1;
+
+    fn run(op: u32, a: u64, b: u64) -> u64 {
+        let memory: Vec<(u32, u8)> = (0x100..)
+            .zip(a.to_le_bytes())
+            .chain((0x200..).zip(b.to_le_bytes()))
+            .collect();
+        let (_program, record) = code::execute([ECALL], &memory, &[
+            (REG_A0, op),
+            (REG_A1, 0x100),
+            (REG_A2, 0x200),
+        ]);
+        assert_eq!(record.last_state.load_goldilocks(0x200), b);
+        record.last_state.load_goldilocks(0x100)
+    }
+
+    #[test]
+    fn add_wraps() {
+        assert_eq!(run(ecall::GOLDILOCKS_ADD, MINUS_ONE, 2), 1);
+    }
+
+    #[test]
+    fn mul_wraps() {
+        assert_eq!(run(ecall::GOLDILOCKS_MUL, MINUS_ONE, MINUS_ONE), 1);
+        assert_eq!(run(ecall::GOLDILOCKS_MUL, 1 << 32, 1 << 32), (1 << 32) - 1);
+    }
+
+    #[test]
+    fn inverse() {
+        assert_eq!(run(ecall::GOLDILOCKS_INVERSE, MINUS_ONE, 0), MINUS_ONE);
+        assert_eq!(
+            run(ecall::GOLDILOCKS_INVERSE, 2, 0),
+            (GoldilocksField::ORDER + 1) / 2
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Goldilocks inverse of zero")]
+    fn inverse_of_zero() { let _ = run(ecall::GOLDILOCKS_INVERSE, 0, 0); }
+
+    #[test]
+    #[should_panic(expected = "not a canonical Goldilocks element")]
+    fn non_canonical_operand() { let _ = run(ecall::GOLDILOCKS_ADD, 1, u64::MAX); }
+}
@@ -95,6 +95,31 @@ pub enum Op {
     REM,
     /// Remainder (U): rd = unsigned rs1 % unsigned rs2
     REMU,
+
+    // Zicntr Counters
+    /// Read Cycle Counter: rd = clk
+    /// Also used for `rdinstret`, because we retire exactly one instruction
+    /// per cycle.
+    RDCYCLE,
+
+    // Mozak extension, in the custom-0 opcode space
+    /// Multiply-Add: rd = rs1 * rs2 + rd
+    /// Only the lower 32 bits of the result are kept.
+    MADD,
+
+    // Zbb Basic Bit-Manipulation Extension, a subset
+    /// AND with inverted operand: rd = rs1 & !rs2
+    ANDN,
+    /// OR with inverted operand: rd = rs1 | !rs2
+    ORN,
+    /// Exclusive NOR: rd = !(rs1 ^ rs2)
+    XNOR,
+    /// Count Leading Zeros: rd = number of leading zero bits of rs1
+    CLZ,
+    /// Count Trailing Zeros: rd = number of trailing zero bits of rs1
+    CTZ,
+    /// Count Population: rd = number of set bits of rs1
+    CPOP,
 }
 
 /// NOP Instruction in RISC-V is encoded as ADDI x0, x0, 0.
@@ -123,8 +148,399 @@ impl Instruction {
     pub fn new(op: Op, args: Args) -> Self { Instruction { op, args } }
 }
 
+/// An immediate that no [`Instruction`] of its [`Op`] can have.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum InstructionError {
+    /// The immediate does not fit into the bits the op has for it.
+    ImmediateOutOfRange { op: Op, imm: u32 },
+    /// A jump or branch target that is not aligned to an instruction.
+    MisalignedTarget { op: Op, target: u32 },
+}
+
+impl std::fmt::Display for InstructionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ImmediateOutOfRange { op, imm } =>
+                write!(f, "immediate {imm:#x} is out of range for {op:?}"),
+            Self::MisalignedTarget { op, target } =>
+                write!(f, "target {target:#x} of {op:?} is not aligned to 4 bytes"),
+        }
+    }
+}
+
+impl std::error::Error for InstructionError {}
+
+/// # Panics
+///
+/// Panics if `reg` is not one of the 32 registers.
+fn reg(reg: u8) -> u8 {
+    assert!(reg < 32, "register x{reg} does not exist");
+    reg
+}
+
+/// `imm` if it is a sign-extended 12-bit immediate, like the one of `jalr`.
+fn imm12(op: Op, imm: u32) -> Result<u32, InstructionError> {
+    #[allow(clippy::cast_possible_wrap)]
+    let fits = (-2048..2048).contains(&(imm as i32));
+    fits.then_some(imm)
+        .ok_or(InstructionError::ImmediateOutOfRange { op, imm })
+}
+
+fn shamt(op: Op, shamt: u32) -> Result<u32, InstructionError> {
+    (shamt < 32)
+        .then_some(shamt)
+        .ok_or(InstructionError::ImmediateOutOfRange { op, imm: shamt })
+}
+
+fn target(op: Op, target: u32) -> Result<u32, InstructionError> {
+    (target % 4 == 0)
+        .then_some(target)
+        .ok_or(InstructionError::MisalignedTarget { op, target })
+}
+
+macro_rules! register_ops {
+    ($($name:ident => $op:ident),* $(,)?) => {$(
+        #[doc = concat!("`", stringify!($name), " rd, rs1, rs2`")]
+        ///
+        /// # Panics
+        ///
+        /// Panics if a register does not exist.
+        #[must_use]
+        pub fn $name(rd: u8, rs1: u8, rs2: u8) -> Self {
+            Self::new(Op::$op, Args {
+                rd: reg(rd),
+                rs1: reg(rs1),
+                rs2: reg(rs2),
+                imm: 0,
+            })
+        }
+    )*};
+}
+
+macro_rules! immediate_ops {
+    ($($name:ident => $op:ident),* $(,)?) => {$(
+        #[doc = concat!("`", stringify!($name), " rd, rs1, imm`, with any 32-bit immediate.")]
+        ///
+        /// # Panics
+        ///
+        /// Panics if a register does not exist.
+        #[must_use]
+        pub fn $name(rd: u8, rs1: u8, imm: u32) -> Self {
+            Self::new(Op::$op, Args {
+                rd: reg(rd),
+                rs1: reg(rs1),
+                rs2: 0,
+                imm,
+            })
+        }
+    )*};
+}
+
+macro_rules! load_ops {
+    ($($name:ident => $op:ident),* $(,)?) => {$(
+        #[doc = concat!("`", stringify!($name), " rd, offset(base)`")]
+        ///
+        /// # Panics
+        ///
+        /// Panics if a register does not exist.
+        #[must_use]
+        pub fn $name(rd: u8, base: u8, offset: u32) -> Self {
+            // Loads keep their base in `rs2`, see `decode_instruction`.
+            Self::new(Op::$op, Args {
+                rd: reg(rd),
+                rs1: 0,
+                rs2: reg(base),
+                imm: offset,
+            })
+        }
+    )*};
+}
+
+macro_rules! store_ops {
+    ($($name:ident => $op:ident),* $(,)?) => {$(
+        #[doc = concat!("`", stringify!($name), " value, offset(base)`")]
+        ///
+        /// # Panics
+        ///
+        /// Panics if a register does not exist.
+        #[must_use]
+        pub fn $name(value: u8, base: u8, offset: u32) -> Self {
+            // Stores keep their value in `rs1` and their base in `rs2`, see
+            // `decode_instruction`.
+            Self::new(Op::$op, Args {
+                rd: 0,
+                rs1: reg(value),
+                rs2: reg(base),
+                imm: offset,
+            })
+        }
+    )*};
+}
+
+macro_rules! branch_ops {
+    ($($name:ident => $op:ident),* $(,)?) => {$(
+        #[doc = concat!("`", stringify!($name), " rs1, rs2, target`, with an absolute target.")]
+        ///
+        /// # Errors
+        ///
+        /// Errors if `target` is not aligned to 4 bytes.
+        ///
+        /// # Panics
+        ///
+        /// Panics if a register does not exist.
+        pub fn $name(rs1: u8, rs2: u8, target: u32) -> Result<Self, InstructionError> {
+            Ok(Self::new(Op::$op, Args {
+                rd: 0,
+                rs1: reg(rs1),
+                rs2: reg(rs2),
+                imm: self::target(Op::$op, target)?,
+            }))
+        }
+    )*};
+}
+
+/// Constructors named after the RISC-V mnemonics, which check their
+/// arguments and lay them out the way [`decode_instruction`] does.
+///
+/// Unlike in RISC-V, immediates of arithmetic, loads and stores are whole
+/// 32-bit words, which is what eg `lui` followed by `addi` decodes to. Jump and
+/// branch targets are absolute.
+///
+/// [`decode_instruction`]: crate::decode::decode_instruction
+impl Instruction {
+    register_ops! {
+        add => ADD, sub => SUB, xor => XOR, or => OR, and => AND,
+        sll => SLL, srl => SRL, sra => SRA, slt => SLT, sltu => SLTU,
+        mul => MUL, mulh => MULH, mulhu => MULHU, mulhsu => MULHSU,
+        div => DIV, divu => DIVU, rem => REM, remu => REMU,
+        madd => MADD, andn => ANDN, orn => ORN, xnor => XNOR,
+    }
+
+    immediate_ops! {
+        addi => ADD, xori => XOR, ori => OR, andi => AND, slti => SLT, sltiu => SLTU,
+    }
+
+    load_ops! { lb => LB, lh => LH, lw => LW, lbu => LBU, lhu => LHU }
+
+    store_ops! { sb => SB, sh => SH, sw => SW }
+
+    branch_ops! {
+        beq => BEQ, bne => BNE, blt => BLT, bge => BGE, bltu => BLTU, bgeu => BGEU,
+    }
+
+    /// `li rd, imm`, as a single `addi rd, zero, imm`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rd` does not exist.
+    #[must_use]
+    pub fn li(rd: u8, imm: u32) -> Self { Self::addi(rd, 0, imm) }
+
+    /// `lui rd, imm`, where `imm` is the value loaded, not its upper 20 bits.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the lower 12 bits of `imm` are not zero.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rd` does not exist.
+    pub fn lui(rd: u8, imm: u32) -> Result<Self, InstructionError> {
+        if imm & 0xfff != 0 {
+            return Err(InstructionError::ImmediateOutOfRange { op: Op::ADD, imm });
+        }
+        Ok(Self::li(rd, imm))
+    }
+
+    /// `slli rd, rs1, shamt`, which we handle as a multiplication by
+    /// `1 << shamt`.
+    ///
+    /// # Errors
+    ///
+    /// Errors if `shamt` is not below 32.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a register does not exist.
+    pub fn slli(rd: u8, rs1: u8, shamt: u32) -> Result<Self, InstructionError> {
+        Ok(Self::new(Op::MUL, Args {
+            rd: reg(rd),
+            rs1: reg(rs1),
+            rs2: 0,
+            imm: 1 << self::shamt(Op::SLL, shamt)?,
+        }))
+    }
+
+    /// `srli rd, rs1, shamt`, which we handle as a division by `1 << shamt`.
+    ///
+    /// # Errors
+    ///
+    /// Errors if `shamt` is not below 32.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a register does not exist.
+    pub fn srli(rd: u8, rs1: u8, shamt: u32) -> Result<Self, InstructionError> {
+        Ok(Self::new(Op::DIVU, Args {
+            rd: reg(rd),
+            rs1: reg(rs1),
+            rs2: 0,
+            imm: 1 << self::shamt(Op::SRL, shamt)?,
+        }))
+    }
+
+    /// `srai rd, rs1, shamt`
+    ///
+    /// # Errors
+    ///
+    /// Errors if `shamt` is not below 32.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a register does not exist.
+    pub fn srai(rd: u8, rs1: u8, shamt: u32) -> Result<Self, InstructionError> {
+        Ok(Self::new(Op::SRA, Args {
+            rd: reg(rd),
+            rs1: reg(rs1),
+            rs2: 0,
+            imm: self::shamt(Op::SRA, shamt)?,
+        }))
+    }
+
+    /// `jal rd, target`, which we handle as a `jalr` from the zero register.
+    ///
+    /// # Errors
+    ///
+    /// Errors if `target` is not aligned to 4 bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rd` does not exist.
+    pub fn jal(rd: u8, target: u32) -> Result<Self, InstructionError> {
+        Ok(Self::new(Op::JALR, Args {
+            rd: reg(rd),
+            rs1: 0,
+            rs2: 0,
+            imm: self::target(Op::JALR, target)?,
+        }))
+    }
+
+    /// `jalr rd, imm(rs1)`
+    ///
+    /// # Errors
+    ///
+    /// Errors if `imm` is not a sign-extended 12-bit immediate.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a register does not exist.
+    pub fn jalr(rd: u8, rs1: u8, imm: u32) -> Result<Self, InstructionError> {
+        Ok(Self::new(Op::JALR, Args {
+            rd: reg(rd),
+            rs1: reg(rs1),
+            rs2: 0,
+            imm: imm12(Op::JALR, imm)?,
+        }))
+    }
+
+    /// `rdcycle rd`
+    ///
+    /// # Panics
+    ///
+    /// Panics if `rd` does not exist.
+    #[must_use]
+    pub fn rdcycle(rd: u8) -> Self {
+        Self::new(Op::RDCYCLE, Args {
+            rd: reg(rd),
+            ..Args::default()
+        })
+    }
+
+    /// `clz rd, rs1`, `ctz rd, rs1` or `cpop rd, rs1`, which keep the
+    /// distinguishing `rs2` field of their encoding as the immediate.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `op` is not one of them, or if a register does not exist.
+    #[must_use]
+    pub fn count(op: Op, rd: u8, rs1: u8) -> Self {
+        let imm = match op {
+            Op::CLZ => 0,
+            Op::CTZ => 1,
+            Op::CPOP => 2,
+            _ => panic!("{op:?} does not count bits"),
+        };
+        Self::new(op, Args {
+            rd: reg(rd),
+            rs1: reg(rs1),
+            rs2: 0,
+            imm,
+        })
+    }
+}
+
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
 pub struct DecodingError {
     pub pc: u32,
     pub instruction: u32,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Instruction, InstructionError, Op};
+    use crate::decode::decode_instruction;
+
+    #[test]
+    fn constructors_match_decoding() {
+        // addi x1, x2, -1
+        assert_eq!(
+            decode_instruction(0, 0xfff1_0093),
+            Ok(Instruction::addi(1, 2, u32::MAX))
+        );
+        // sw x5, 8(x6)
+        assert_eq!(
+            decode_instruction(0, 0x0053_2423),
+            Ok(Instruction::sw(5, 6, 8))
+        );
+        // lw x5, 8(x6)
+        assert_eq!(
+            decode_instruction(0, 0x0083_2283),
+            Ok(Instruction::lw(5, 6, 8))
+        );
+        // slli x1, x1, 3
+        assert_eq!(
+            decode_instruction(0, 0x0030_9093),
+            Ok(Instruction::slli(1, 1, 3).unwrap())
+        );
+        // beq x1, x2, +16 at pc 0x100
+        assert_eq!(
+            decode_instruction(0x100, 0x0020_8863),
+            Ok(Instruction::beq(1, 2, 0x110).unwrap())
+        );
+    }
+
+    #[test]
+    fn rejects_out_of_range_immediates() {
+        assert_eq!(
+            Instruction::jalr(1, 2, 2048),
+            Err(InstructionError::ImmediateOutOfRange {
+                op: Op::JALR,
+                imm: 2048
+            })
+        );
+        assert!(Instruction::jalr(1, 2, 0xffff_f800).is_ok());
+        assert!(Instruction::srai(1, 2, 32).is_err());
+        assert!(Instruction::lui(1, 0x1001).is_err());
+        assert_eq!(
+            Instruction::bne(1, 2, 0x102),
+            Err(InstructionError::MisalignedTarget {
+                op: Op::BNE,
+                target: 0x102
+            })
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "register x32 does not exist")]
+    fn rejects_registers() { let _ = Instruction::add(1, 2, 32); }
+}
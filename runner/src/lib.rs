@@ -10,14 +10,27 @@ use mimalloc::MiMalloc;
 static GLOBAL: MiMalloc = MiMalloc;
 
 pub mod code;
+pub mod debugger;
 pub mod decode;
 pub mod ecall;
 pub mod elf;
+#[cfg(feature = "goldilocks")]
+pub mod goldilocks;
 pub mod instruction;
+#[cfg(feature = "memcopy")]
+pub mod memcopy;
+pub mod memory_stats;
+#[cfg(feature = "poseidon2")]
 pub mod poseidon2;
+#[cfg(feature = "secp256k1")]
+pub mod secp256k1;
+pub mod snapshot;
+pub mod spike;
 pub mod state;
 #[cfg(any(feature = "test", test))]
 pub mod test_utils;
+#[cfg(feature = "u256")]
+pub mod u256;
 pub mod vm;
 
 extern crate alloc;
@@ -0,0 +1,145 @@
+//! The memcopy and memset ecalls, which move whole ranges of bytes in a single
+//! instruction instead of a loop of loads and stores.
+
+use itertools::chain;
+use mozak_sdk::core::reg_abi::{REG_A1, REG_A2, REG_A3};
+use plonky2::hash::hash_types::RichField;
+
+use crate::state::{Aux, State};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    /// Copies bytes from `src` to `dst`.
+    Copy,
+    /// Sets bytes at `dst` to a single value.
+    Set,
+}
+
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub operation: Operation,
+    pub dst: u32,
+    /// The source address for [`Operation::Copy`], and the byte to set for
+    /// [`Operation::Set`].
+    pub src: u32,
+    /// The bytes written to `dst`, which for [`Operation::Copy`] are the ones
+    /// read from `src` before any write.
+    pub bytes: Vec<u8>,
+}
+
+/// The `len` addresses from `start`.
+///
+/// # Panics
+///
+/// Panics if the range wraps around the end of the address space.
+fn addresses(start: u32, len: u32) -> impl Iterator<Item = u32> {
+    assert!(
+        u64::from(start) + u64::from(len) <= 1 << 32,
+        "memcopy range {start:#x} of {len} bytes wraps around"
+    );
+    (0..len).map(move |i| start + i)
+}
+
+impl<F: RichField> State<F> {
+    /// Copies or sets `a3` bytes at `a1`, from the bytes at `a2` or to the
+    /// byte in `a2`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a range wraps around the end of the address space, if the
+    /// byte to set does not fit into a byte, or if `a1` points to read-only
+    /// memory.
+    #[must_use]
+    pub fn ecall_memcopy(self, operation: Operation) -> (Aux<F>, Self) {
+        let dst = self.get_register_value(REG_A1);
+        let src = self.get_register_value(REG_A2);
+        let len = self.get_register_value(REG_A3);
+        let writes: Vec<u32> = addresses(dst, len).collect();
+        let (reads, bytes): (Vec<u32>, Vec<u8>) = match operation {
+            Operation::Copy => addresses(src, len)
+                .map(|addr| (addr, self.load_u8(addr)))
+                .unzip(),
+            Operation::Set => {
+                let value = u8::try_from(src).expect("memset value does not fit into a byte");
+                (vec![], vec![value; writes.len()])
+            }
+        };
+        (
+            Aux {
+                mem_addresses_used: chain!(reads, writes.iter().copied()).collect(),
+                memcopy: Some(Entry {
+                    operation,
+                    dst,
+                    src,
+                    bytes: bytes.clone(),
+                }),
+                ..Default::default()
+            },
+            writes
+                .into_iter()
+                .zip(bytes)
+                .fold(self, |updated_self, (addr, byte)| {
+                    updated_self.store_u8(addr, byte).unwrap()
+                })
+                .bump_pc(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mozak_sdk::core::ecall;
+    use mozak_sdk::core::reg_abi::{REG_A0, REG_A1, REG_A2, REG_A3};
+
+    use crate::code;
+    use crate::decode::ECALL;
+
+    fn run(op: u32, dst: u32, src: u32, len: u32) -> Vec<u8> {
+        let memory: Vec<(u32, u8)> = (0x100..).zip(1..=16).collect();
+        let (_program, record) = code::execute([ECALL], &memory, &[
+            (REG_A0, op),
+            (REG_A1, dst),
+            (REG_A2, src),
+            (REG_A3, len),
+        ]);
+        (0x100..0x110)
+            .map(|addr| record.last_state.load_u8(addr))
+            .collect()
+    }
+
+    #[test]
+    fn copy() {
+        assert_eq!(run(ecall::MEMCOPY, 0x108, 0x100, 4), [
+            1, 2, 3, 4, 5, 6, 7, 8, 1, 2, 3, 4, 13, 14, 15, 16
+        ]);
+    }
+
+    #[test]
+    fn copy_overlapping() {
+        assert_eq!(run(ecall::MEMCOPY, 0x102, 0x100, 8), [
+            1, 2, 1, 2, 3, 4, 5, 6, 7, 8, 11, 12, 13, 14, 15, 16
+        ]);
+        assert_eq!(run(ecall::MEMCOPY, 0x100, 0x102, 8), [
+            3, 4, 5, 6, 7, 8, 9, 10, 9, 10, 11, 12, 13, 14, 15, 16
+        ]);
+    }
+
+    #[test]
+    fn set() {
+        assert_eq!(run(ecall::MEMSET, 0x104, 0xAB, 3), [
+            1, 2, 3, 4, 0xAB, 0xAB, 0xAB, 8, 9, 10, 11, 12, 13, 14, 15, 16
+        ]);
+    }
+
+    #[test]
+    fn empty() {
+        assert_eq!(
+            run(ecall::MEMCOPY, 0x100, 0x108, 0),
+            (1..=16).collect::<Vec<u8>>()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "wraps around")]
+    fn copy_wrapping() { let _ = run(ecall::MEMCOPY, u32::MAX, 0x100, 2); }
+}
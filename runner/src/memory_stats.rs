@@ -0,0 +1,200 @@
+//! Per-address memory access statistics of an execution.
+//!
+//! Every accessed byte ends up in the memory table, so the number of accesses
+//! and of distinct addresses estimate its height. The hottest addresses show
+//! where restructuring the guest's data layout pays off most.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use itertools::Itertools;
+#[cfg(feature = "poseidon2")]
+use mozak_sdk::core::constants::DIGEST_BYTES;
+#[cfg(feature = "goldilocks")]
+use mozak_sdk::core::constants::GOLDILOCKS_BYTES;
+#[cfg(feature = "secp256k1")]
+use mozak_sdk::core::constants::SECP256K1_POINT_BYTES;
+#[cfg(feature = "u256")]
+use mozak_sdk::core::constants::U256_BYTES;
+use plonky2::hash::hash_types::RichField;
+
+use crate::instruction::Op;
+#[cfg(feature = "memcopy")]
+use crate::memcopy::Operation;
+use crate::state::StorageDeviceOpcode;
+use crate::vm::{ExecutionRecord, Row};
+
+/// How often a single byte address was read and written.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct AccessCount {
+    pub reads: u64,
+    pub writes: u64,
+}
+
+impl AccessCount {
+    #[must_use]
+    pub fn total(&self) -> u64 { self.reads + self.writes }
+}
+
+/// Read and write counts of every byte address an execution touched.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryAccessStats {
+    pub accesses: HashMap<u32, AccessCount>,
+}
+
+/// Splits the addresses a row accessed into the ones it read and the ones it
+/// wrote.
+pub(crate) fn reads_and_writes<F: RichField>(row: &Row<F>) -> (&[u32], &[u32]) {
+    let used = row.aux.mem_addresses_used.as_slice();
+    match row.instruction.op {
+        Op::LB | Op::LH | Op::LW | Op::LBU | Op::LHU => (used, &[]),
+        Op::ECALL if row.aux.event.is_some() => (used, &[]),
+        // Writing the output tape reads the bytes from memory.
+        Op::ECALL
+            if row
+                .aux
+                .storage_device_entry
+                .as_ref()
+                .is_some_and(|entry| entry.op == StorageDeviceOpcode::LoadOutputTape) =>
+            (used, &[]),
+        // Poseidon2 reads its input, and writes the digest after it.
+        #[cfg(feature = "poseidon2")]
+        Op::ECALL if row.aux.poseidon2.is_some() =>
+            used.split_at(used.len().saturating_sub(DIGEST_BYTES)),
+        // The secp256k1 ecalls read their operands, and write the result last.
+        #[cfg(feature = "secp256k1")]
+        Op::ECALL if row.aux.secp256k1.is_some() =>
+            used.split_at(used.len().saturating_sub(SECP256K1_POINT_BYTES)),
+        // So do the u256 ecalls.
+        #[cfg(feature = "u256")]
+        Op::ECALL if row.aux.u256.is_some() => used.split_at(used.len().saturating_sub(U256_BYTES)),
+        // And the Goldilocks ecalls.
+        #[cfg(feature = "goldilocks")]
+        Op::ECALL if row.aux.goldilocks.is_some() =>
+            used.split_at(used.len().saturating_sub(GOLDILOCKS_BYTES)),
+        // A memcopy reads as many bytes as it writes. A memset only writes.
+        #[cfg(feature = "memcopy")]
+        Op::ECALL
+            if row
+                .aux
+                .memcopy
+                .as_ref()
+                .is_some_and(|entry| entry.operation == Operation::Copy) =>
+            used.split_at(used.len() / 2),
+        // Stores, and ecalls that copy a tape into memory.
+        _ => (&[], used),
+    }
+}
+
+impl MemoryAccessStats {
+    #[must_use]
+    pub fn from_record<F: RichField>(record: &ExecutionRecord<F>) -> Self {
+        let mut accesses: HashMap<u32, AccessCount> = HashMap::new();
+        for row in &record.executed {
+            let (reads, writes) = reads_and_writes(row);
+            for &addr in reads {
+                accesses.entry(addr).or_default().reads += 1;
+            }
+            for &addr in writes {
+                accesses.entry(addr).or_default().writes += 1;
+            }
+        }
+        Self { accesses }
+    }
+
+    #[must_use]
+    pub fn distinct_addresses(&self) -> usize { self.accesses.len() }
+
+    #[must_use]
+    pub fn total(&self) -> AccessCount {
+        self.accesses
+            .values()
+            .fold(AccessCount::default(), |acc, count| AccessCount {
+                reads: acc.reads + count.reads,
+                writes: acc.writes + count.writes,
+            })
+    }
+
+    /// The `n` most accessed addresses, most accessed first. Ties are broken
+    /// by address.
+    #[must_use]
+    pub fn hottest(&self, n: usize) -> Vec<(u32, AccessCount)> {
+        self.accesses
+            .iter()
+            .map(|(&addr, &count)| (addr, count))
+            .sorted_by_key(|&(addr, count)| (std::cmp::Reverse(count.total()), addr))
+            .take(n)
+            .collect()
+    }
+
+    /// Report of the totals and the `n` hottest addresses, for printing.
+    #[must_use]
+    pub fn report(&self, n: usize) -> HotAddressReport<'_> { HotAddressReport { stats: self, n } }
+}
+
+/// Displays [`MemoryAccessStats`] as a table of the hottest addresses.
+pub struct HotAddressReport<'a> {
+    stats: &'a MemoryAccessStats,
+    n: usize,
+}
+
+impl fmt::Display for HotAddressReport<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let total = self.stats.total();
+        writeln!(
+            f,
+            "{} distinct addresses, {} reads, {} writes",
+            self.stats.distinct_addresses(),
+            total.reads,
+            total.writes
+        )?;
+        writeln!(
+            f,
+            "{:>10} {:>10} {:>10} {:>10}",
+            "address", "reads", "writes", "total"
+        )?;
+        for (addr, count) in self.stats.hottest(self.n) {
+            writeln!(
+                f,
+                "{addr:#010x} {:>10} {:>10} {:>10}",
+                count.reads,
+                count.writes,
+                count.total()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mozak_sdk::core::reg_abi::REG_A1;
+
+    use super::{AccessCount, MemoryAccessStats};
+    use crate::code;
+    use crate::instruction::Instruction;
+
+    #[test]
+    fn counts_loads_and_stores() {
+        let store = Instruction::sw(REG_A1, 0, 0x100);
+        let load = Instruction::lbu(REG_A1, 0, 0x101);
+        let (_program, record) = code::execute([store, load, load], &[], &[(REG_A1, 7)]);
+        let stats = MemoryAccessStats::from_record(&record);
+
+        assert_eq!(stats.distinct_addresses(), 4);
+        assert_eq!(stats.total(), AccessCount {
+            reads: 2,
+            writes: 4
+        });
+        assert_eq!(stats.hottest(2), vec![
+            (0x101, AccessCount {
+                reads: 2,
+                writes: 1
+            }),
+            (0x100, AccessCount {
+                reads: 0,
+                writes: 1
+            }),
+        ]);
+    }
+}
@@ -2,7 +2,7 @@ use std::iter::repeat;
 
 use itertools::{chain, izip};
 use mozak_sdk::core::constants::DIGEST_BYTES;
-use mozak_sdk::core::reg_abi::{REG_A1, REG_A2, REG_A3};
+use mozak_sdk::core::reg_abi::{REG_A1, REG_A2, REG_A3, REG_A4};
 use plonky2::hash::hash_types::{HashOut, RichField, NUM_HASH_OUT_ELTS};
 use plonky2::hash::hashing::PlonkyPermutation;
 use plonky2::hash::poseidon2::{Poseidon2Permutation, WIDTH};
@@ -22,6 +22,8 @@ pub struct Entry<F: RichField> {
     pub addr: u32,
     pub output_addr: u32,
     pub len: u32,
+    /// Number of output bytes, a multiple of [`DIGEST_BYTES`].
+    pub output_len: u32,
     pub sponge_data: Vec<SpongeData<F>>,
 }
 
@@ -37,6 +39,24 @@ pub struct Entry<F: RichField> {
 pub fn hash_n_to_m_no_pad<F: RichField, P: PlonkyPermutation<F>>(
     inputs: &[F],
 ) -> (HashOut<F>, Vec<SpongeData<F>>) {
+    let (outputs, sponge_data) = hash_n_to_digests_no_pad::<F, P>(inputs, 1);
+    (outputs[0], sponge_data)
+}
+
+/// Like [`hash_n_to_m_no_pad`], but squeezes `num_digests` digests, XOF style.
+///
+/// The first digest is the plain hash of `inputs`. Each further digest is
+/// the first `NUM_HASH_OUT_ELTS` elements of the state after one more
+/// permutation, so every digest comes from a row with `gen_output` set.
+///
+///  # Panics
+///
+/// Panics if `PlonkyPermutation` is implemented on `STATE_SIZE` different than
+/// 12, or `num_digests` is zero.
+pub fn hash_n_to_digests_no_pad<F: RichField, P: PlonkyPermutation<F>>(
+    inputs: &[F],
+    num_digests: usize,
+) -> (Vec<HashOut<F>>, Vec<SpongeData<F>>) {
     let permute_and_record_data = |perm: &mut P, sponge_data: &mut Vec<SpongeData<F>>| {
         const STATE_SIZE: usize = 12;
         assert_eq!(STATE_SIZE, P::WIDTH);
@@ -55,7 +75,18 @@ pub fn hash_n_to_m_no_pad<F: RichField, P: PlonkyPermutation<F>>(
             gen_output: F::from_bool(false),
         });
     };
+    let squeeze = |perm: &P, sponge_data: &mut Vec<SpongeData<F>>| {
+        sponge_data
+            .last_mut()
+            .expect("Can't fail at least one elem must be there")
+            .gen_output = F::from_bool(true);
+        let outputs: [F; NUM_HASH_OUT_ELTS] = perm.squeeze()[..NUM_HASH_OUT_ELTS]
+            .try_into()
+            .expect("squeeze must have minimum NUM_HASH_OUT_ELTS length");
+        HashOut::from(outputs)
+    };
 
+    assert!(num_digests > 0, "must squeeze at least one digest");
     let mut perm = P::new(repeat(F::ZERO));
     // input length is expected to be multiple of P::RATE
     assert_eq!(inputs.len() % P::RATE, 0);
@@ -67,34 +98,39 @@ pub fn hash_n_to_m_no_pad<F: RichField, P: PlonkyPermutation<F>>(
         permute_and_record_data(&mut perm, &mut sponge_data);
     }
 
-    let outputs: [F; NUM_HASH_OUT_ELTS] = perm.squeeze()[..NUM_HASH_OUT_ELTS]
-        .try_into()
-        .expect("squeeze must have minimum NUM_HASH_OUT_ELTS length");
-    sponge_data
-        .last_mut()
-        .expect("Can't fail at least one elem must be there")
-        .gen_output = F::from_bool(true);
-    (HashOut::from(outputs), sponge_data)
+    let mut outputs = vec![squeeze(&perm, &mut sponge_data)];
+    for _ in 1..num_digests {
+        permute_and_record_data(&mut perm, &mut sponge_data);
+        outputs.push(squeeze(&perm, &mut sponge_data));
+    }
+    (outputs, sponge_data)
 }
 
 impl<F: RichField> State<F> {
     #[must_use]
     /// # Panics
     ///
-    /// Panics if hash output of `hash_n_to_m_no_pad` has length different
-    /// then expected value.
+    /// Panics if the requested output length is not a positive multiple of
+    /// `DIGEST_BYTES`.
     pub fn ecall_poseidon2(self) -> (Aux<F>, Self) {
         let input_ptr = self.get_register_value(REG_A1);
         // lengths are in bytes
         let input_len = self.get_register_value(REG_A2);
         let output_ptr = self.get_register_value(REG_A3);
+        let output_len = self.get_register_value(REG_A4);
+        let digest_bytes = u32::try_from(DIGEST_BYTES).unwrap();
+        assert!(
+            output_len > 0 && output_len % digest_bytes == 0,
+            "poseidon2 output length {output_len} is not a positive multiple of {DIGEST_BYTES}"
+        );
         let input: Vec<F> = (0..input_len)
             .map(|i| F::from_canonical_u8(self.load_u8(input_ptr + i)))
             .collect();
-        let (hash, sponge_data) =
-            hash_n_to_m_no_pad::<F, Poseidon2Permutation<F>>(input.as_slice());
-        let hash = hash.to_bytes();
-        assert_eq!(DIGEST_BYTES, hash.len());
+        let (digests, sponge_data) = hash_n_to_digests_no_pad::<F, Poseidon2Permutation<F>>(
+            input.as_slice(),
+            (output_len / digest_bytes) as usize,
+        );
+        let hash: Vec<u8> = digests.iter().flat_map(GenericHashOut::to_bytes).collect();
 
         let mem_addresses_used: Vec<u32> = chain!(
             (0..input_len).map(|i| input_ptr.wrapping_add(i)),
@@ -107,6 +143,7 @@ impl<F: RichField> State<F> {
                 poseidon2: Some(Entry {
                     addr: input_ptr,
                     output_addr: output_ptr,
+                    output_len,
                     len: input_len.next_multiple_of(
                         u32::try_from(Poseidon2Permutation::<F>::RATE).expect("RATE > 2^32"),
                     ),
@@ -158,4 +195,26 @@ mod tests {
             Poseidon2Hash::hash_no_pad(&data_fields).to_bytes()
         );
     }
+
+    #[test]
+    fn test_hash_n_to_digests_no_pad() {
+        let data_fields: Vec<GoldilocksField> =
+            (0..16).map(GoldilocksField::from_canonical_u8).collect();
+        let (digests, sponge_data) = super::hash_n_to_digests_no_pad::<
+            GoldilocksField,
+            Poseidon2Permutation<GoldilocksField>,
+        >(&data_fields, 3);
+        assert_eq!(digests.len(), 3);
+        assert_eq!(digests[0], Poseidon2Hash::hash_no_pad(&data_fields));
+        // Two permutations absorb the input, and two more squeeze.
+        assert_eq!(sponge_data.len(), 4);
+        assert_eq!(
+            sponge_data
+                .iter()
+                .filter(|datum| datum.gen_output.is_one())
+                .count(),
+            3
+        );
+        assert_eq!(sponge_data[2].preimage, sponge_data[1].output);
+    }
 }
@@ -0,0 +1,296 @@
+//! The secp256k1 point addition and doubling ecalls.
+//!
+//! Points are passed by pointers to their affine coordinates, laid out as
+//! [`SECP256K1_POINT_BYTES`] bytes: `x` and then `y`, each 32 bytes
+//! little-endian. Coordinates do not have to be reduced, but the result that
+//! overwrites the first operand always is.
+//!
+//! Neither operand nor result can be the point at infinity, so the guest has
+//! to handle the cases where the slope is undefined itself. The same goes for
+//! scalar multiplication, which guests build out of these ecalls.
+
+use anyhow::{ensure, Result};
+use itertools::{chain, izip};
+use mozak_sdk::core::constants::SECP256K1_POINT_BYTES;
+use mozak_sdk::core::reg_abi::{REG_A1, REG_A2};
+use num::bigint::BigUint;
+use num::{One, Zero};
+use plonky2::hash::hash_types::RichField;
+
+use crate::state::{Aux, State};
+
+pub const COORDINATE_BYTES: usize = SECP256K1_POINT_BYTES / 2;
+
+/// The prime `2^256 - 2^32 - 977` of the secp256k1 base field.
+#[must_use]
+pub fn modulus() -> BigUint { (BigUint::one() << 256) - (BigUint::one() << 32) - 977_u32 }
+
+/// Inverse of a non-zero `value` modulo [`modulus`].
+#[must_use]
+pub fn inverse(value: &BigUint) -> BigUint {
+    let modulus = modulus();
+    value.modpow(&(&modulus - 2_u32), &modulus)
+}
+
+/// The reduced `x` and `y` coordinates of `point`.
+#[must_use]
+pub fn coordinates(point: &[u8; SECP256K1_POINT_BYTES]) -> (BigUint, BigUint) {
+    let (x, y) = point.split_at(COORDINATE_BYTES);
+    let modulus = modulus();
+    (
+        BigUint::from_bytes_le(x) % &modulus,
+        BigUint::from_bytes_le(y) % &modulus,
+    )
+}
+
+/// The point with reduced coordinates `x` and `y`.
+#[must_use]
+pub fn to_point(x: &BigUint, y: &BigUint) -> [u8; SECP256K1_POINT_BYTES] {
+    let mut point = [0; SECP256K1_POINT_BYTES];
+    for (coordinate, bytes) in izip!([x, y], point.chunks_mut(COORDINATE_BYTES)) {
+        let le = coordinate.to_bytes_le();
+        bytes[..le.len()].copy_from_slice(&le);
+    }
+    point
+}
+
+/// Numerator and denominator of the slope of the line through `p` and `q`,
+/// or of the tangent at `p` when doubling.
+#[must_use]
+pub fn slope_fraction(
+    is_double: bool,
+    p: &[u8; SECP256K1_POINT_BYTES],
+    q: &[u8; SECP256K1_POINT_BYTES],
+) -> (BigUint, BigUint) {
+    let modulus = modulus();
+    let (x1, y1) = coordinates(p);
+    if is_double {
+        (3_u32 * &x1 * &x1 % &modulus, 2_u32 * y1 % &modulus)
+    } else {
+        let (x2, y2) = coordinates(q);
+        (
+            (&modulus + y2 - y1) % &modulus,
+            (&modulus + x2 - x1) % &modulus,
+        )
+    }
+}
+
+/// Adds `q` to `p`, or doubles `p`.
+///
+/// # Errors
+///
+/// Errors if the slope is undefined, ie when adding points with the same `x`
+/// coordinate, or doubling a point with a zero `y` coordinate.
+pub fn add_or_double(
+    is_double: bool,
+    p: &[u8; SECP256K1_POINT_BYTES],
+    q: &[u8; SECP256K1_POINT_BYTES],
+) -> Result<[u8; SECP256K1_POINT_BYTES]> {
+    let modulus = modulus();
+    let (numerator, denominator) = slope_fraction(is_double, p, q);
+    ensure!(
+        !denominator.is_zero(),
+        "secp256k1 {} has an undefined slope",
+        if is_double { "doubling" } else { "addition" }
+    );
+    let slope = numerator * inverse(&denominator) % &modulus;
+    let (x1, y1) = coordinates(p);
+    let (x2, _) = coordinates(q);
+    let x3 = (&slope * &slope + 2_u32 * &modulus - &x1 - x2) % &modulus;
+    let y3 = (slope * (&modulus + x1 - &x3) + &modulus - y1) % &modulus;
+    Ok(to_point(&x3, &y3))
+}
+
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub is_double: bool,
+    pub p_addr: u32,
+    /// Same as `p_addr` when doubling.
+    pub q_addr: u32,
+    pub p: [u8; SECP256K1_POINT_BYTES],
+    /// Same as `p` when doubling.
+    pub q: [u8; SECP256K1_POINT_BYTES],
+    pub result: [u8; SECP256K1_POINT_BYTES],
+}
+
+impl<F: RichField> State<F> {
+    fn load_point(&self, addr: u32) -> [u8; SECP256K1_POINT_BYTES] {
+        let mut point = [0; SECP256K1_POINT_BYTES];
+        for (i, byte) in izip!(0_u32.., &mut point) {
+            *byte = self.load_u8(addr.wrapping_add(i));
+        }
+        point
+    }
+
+    /// Adds the point at `a2` to the one at `a1`, or doubles the point at
+    /// `a1`, and writes the result over the point at `a1`.
+    ///
+    /// # Errors
+    ///
+    /// Errors if the slope is undefined, see [`add_or_double`].
+    pub fn ecall_secp256k1(self, is_double: bool) -> Result<(Aux<F>, Self)> {
+        let p_addr = self.get_register_value(REG_A1);
+        let q_addr = if is_double {
+            p_addr
+        } else {
+            self.get_register_value(REG_A2)
+        };
+        let p = self.load_point(p_addr);
+        let q = self.load_point(q_addr);
+        let result = add_or_double(is_double, &p, &q)?;
+
+        let point_addresses = |addr: u32| {
+            (0..)
+                .take(SECP256K1_POINT_BYTES)
+                .map(move |i| addr.wrapping_add(i))
+        };
+        let mem_addresses_used: Vec<u32> = chain!(
+            point_addresses(p_addr),
+            (!is_double)
+                .then(|| point_addresses(q_addr))
+                .into_iter()
+                .flatten(),
+            point_addresses(p_addr),
+        )
+        .collect();
+        let aux = Aux {
+            mem_addresses_used,
+            secp256k1: Some(Entry {
+                is_double,
+                p_addr,
+                q_addr,
+                p,
+                q,
+                result,
+            }),
+            ..Default::default()
+        };
+        let updated_self = izip!(0.., result).try_fold(self, |updated_self, (i, byte)| {
+            updated_self.store_u8(p_addr.wrapping_add(i), byte)
+        })?;
+        Ok((aux, updated_self.bump_pc()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mozak_sdk::core::constants::SECP256K1_POINT_BYTES;
+    use mozak_sdk::core::ecall;
+    use mozak_sdk::core::reg_abi::{REG_A0, REG_A1, REG_A2};
+    use num::bigint::BigUint;
+    use num::Zero;
+    use plonky2::field::goldilocks_field::GoldilocksField;
+
+    use super::{coordinates, modulus, to_point};
+    use crate::code::{self, Code};
+    use crate::decode::ECALL;
+    use crate::elf::Program;
+    use crate::state::{RawTapes, State};
+    use crate::vm::step;
+
+    fn point(x: &str, y: &str) -> [u8; SECP256K1_POINT_BYTES] {
+        let parse = |hex: &str| BigUint::parse_bytes(hex.as_bytes(), 16).unwrap();
+        to_point(&parse(x), &parse(y))
+    }
+
+    fn generator() -> [u8; SECP256K1_POINT_BYTES] {
+        point(
+            "79BE667EF9DCBBAC55A06295CE870B07029BFCDB2DCE28D959F2815B16F81798",
+            "483ADA7726A3C4655DA4FBFC0E1108A8FD17B448A68554199C47D08FFB10D4B8",
+        )
+    }
+
+    fn generator_times_2() -> [u8; SECP256K1_POINT_BYTES] {
+        point(
+            "C6047F9441ED7D6D3045406E95C07CD85C778E4B8CEF3CA7ABAC09B95C709EE5",
+            "1AE168FEA63DC339A3C58419466CEAEEF7F632653266D0E1236431A950CFE52A",
+        )
+    }
+
+    fn generator_times_3() -> [u8; SECP256K1_POINT_BYTES] {
+        point(
+            "F9308A019258C31049344F85F89D5229B531C845836F99B08601F113BCE036F9",
+            "388F7B0F632DE8140FE337E62A37F3566500A99934C2231B6CB9FD7584B8E672",
+        )
+    }
+
+    fn memory(addr: u32, point: &[u8; SECP256K1_POINT_BYTES]) -> Vec<(u32, u8)> {
+        (addr..).zip(point.iter().copied()).collect()
+    }
+
+    #[test]
+    fn double_generator() {
+        let (_program, record) = code::execute([ECALL], &memory(0x100, &generator()), &[
+            (REG_A0, ecall::SECP256K1_DOUBLE),
+            (REG_A1, 0x100),
+        ]);
+        assert_eq!(record.last_state.load_point(0x100), generator_times_2());
+    }
+
+    #[test]
+    fn add_generator() {
+        let (_program, record) = code::execute(
+            [ECALL],
+            &[
+                memory(0x100, &generator_times_2()),
+                memory(0x200, &generator()),
+            ]
+            .concat(),
+            &[
+                (REG_A0, ecall::SECP256K1_ADD),
+                (REG_A1, 0x100),
+                (REG_A2, 0x200),
+            ],
+        );
+        assert_eq!(record.last_state.load_point(0x100), generator_times_3());
+        assert_eq!(record.last_state.load_point(0x200), generator());
+    }
+
+    /// Runs the secp256k1 ecall in `regs` on `memory`, which has to fail.
+    fn undefined_slope(memory: &[(u32, u8)], regs: &[(u8, u32)]) -> String {
+        let program = Program::create(&[], memory, Code([(0, Ok(ECALL))].into_iter().collect()));
+        let state = regs.iter().fold(
+            State::<GoldilocksField>::new(program.clone(), RawTapes::default()),
+            |state, &(reg, value)| state.set_register_value(reg, value),
+        );
+        step(&program, state).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn add_same_point() {
+        let err = undefined_slope(
+            &[memory(0x100, &generator()), memory(0x200, &generator())].concat(),
+            &[
+                (REG_A0, ecall::SECP256K1_ADD),
+                (REG_A1, 0x100),
+                (REG_A2, 0x200),
+            ],
+        );
+        assert_eq!(err, "secp256k1 addition has an undefined slope");
+    }
+
+    #[test]
+    fn add_negation() {
+        let (x, y) = coordinates(&generator());
+        let negation = to_point(&x, &(modulus() - y));
+        let err = undefined_slope(
+            &[memory(0x100, &generator()), memory(0x200, &negation)].concat(),
+            &[
+                (REG_A0, ecall::SECP256K1_ADD),
+                (REG_A1, 0x100),
+                (REG_A2, 0x200),
+            ],
+        );
+        assert_eq!(err, "secp256k1 addition has an undefined slope");
+    }
+
+    #[test]
+    fn double_zero_y() {
+        let (x, _) = coordinates(&generator());
+        let err = undefined_slope(&memory(0x100, &to_point(&x, &BigUint::zero())), &[
+            (REG_A0, ecall::SECP256K1_DOUBLE),
+            (REG_A1, 0x100),
+        ]);
+        assert_eq!(err, "secp256k1 doubling has an undefined slope");
+    }
+}
@@ -0,0 +1,224 @@
+//! Snapshots of the registers, memory, pc and clock of a [`State`].
+//!
+//! A [`StateSnapshot`] is self-contained and canonical: pages that read as
+//! zero and hold no read-only byte are left out, and the rest are sorted, so
+//! equal states have equal snapshots and equal [`StateSnapshot::digest`]s.
+//! That makes digests fit for chaining segments of an execution, and for
+//! comparing executions in differential tests.
+//!
+//! Snapshots leave out the tapes and the program break. [`State::restore`]
+//! keeps those of the state it restores into.
+
+use std::sync::Arc;
+
+use anyhow::{anyhow, ensure, Result};
+use itertools::{chain, Itertools};
+use plonky2::hash::hash_types::{HashOut, RichField};
+use plonky2::hash::hashing::hash_n_to_hash_no_pad;
+use plonky2::hash::poseidon2::Poseidon2Permutation;
+
+use crate::state::{Page, PageMask, State, StateMemory, PAGE_SIZE};
+
+/// A page of memory in a [`StateSnapshot`].
+#[derive(Clone, Debug, PartialEq, Eq, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
+pub struct SnapshotPage {
+    /// Page number, ie the address of its first byte divided by
+    /// [`PAGE_SIZE`].
+    pub index: u32,
+    /// All [`PAGE_SIZE`] bytes of the page.
+    pub bytes: Vec<u8>,
+    /// One bit per byte, set for read-only bytes. Empty if none is.
+    pub read_only: Vec<u64>,
+}
+
+#[derive(
+    Clone, Debug, Default, PartialEq, Eq, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize,
+)]
+pub struct StateSnapshot {
+    pub clk: u64,
+    pub pc: u32,
+    pub halted: bool,
+    pub registers: [u32; 32],
+    /// Pages with a non-zero or read-only byte, by increasing index.
+    pub pages: Vec<SnapshotPage>,
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn u64_words(value: u64) -> [u32; 2] { [value as u32, (value >> 32) as u32] }
+
+/// Little-endian 32-bit words of `bytes`, the last one padded with zeros.
+fn words(bytes: &[u8]) -> impl Iterator<Item = u32> + '_ {
+    bytes.chunks(4).map(|chunk| {
+        let mut word = [0; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        u32::from_le_bytes(word)
+    })
+}
+
+fn len_word(len: usize) -> u32 { u32::try_from(len).expect("snapshot lengths fit into u32") }
+
+impl StateSnapshot {
+    /// Poseidon2 hash of the snapshot.
+    ///
+    /// The hash is over 32-bit words, one per field element: the clock as two
+    /// words, the pc, whether the state halted, the registers, and then for
+    /// each page its index, the lengths of its bytes and of its read-only
+    /// mask, and both of them packed little-endian.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a page is longer than `u32::MAX`.
+    #[must_use]
+    pub fn digest<F: RichField>(&self) -> HashOut<F> {
+        let elements = chain!(
+            u64_words(self.clk),
+            [self.pc, u32::from(self.halted)],
+            self.registers,
+            self.pages.iter().flat_map(|page| {
+                chain!(
+                    [
+                        page.index,
+                        len_word(page.bytes.len()),
+                        len_word(page.read_only.len())
+                    ],
+                    words(&page.bytes),
+                    page.read_only.iter().flat_map(|&mask| u64_words(mask)),
+                )
+            }),
+        )
+        .map(F::from_canonical_u32)
+        .collect_vec();
+        hash_n_to_hash_no_pad::<F, Poseidon2Permutation<F>>(&elements)
+    }
+}
+
+impl<F: RichField> State<F> {
+    /// Snapshots the registers, memory, pc and clock.
+    #[must_use]
+    pub fn snapshot(&self) -> StateSnapshot {
+        let StateMemory { pages, read_only } = &self.memory;
+        let pages = chain!(pages.keys(), read_only.keys())
+            .copied()
+            .sorted()
+            .dedup()
+            .filter_map(|index| {
+                let bytes = pages
+                    .get(&index)
+                    .map_or_else(|| vec![0; PAGE_SIZE], |page| page.to_vec());
+                let read_only = read_only
+                    .get(&index)
+                    .filter(|mask| mask.iter().any(|&bits| bits != 0))
+                    .map_or_else(Vec::new, |mask| mask.to_vec());
+                (bytes.iter().any(|&byte| byte != 0) || !read_only.is_empty()).then_some(
+                    SnapshotPage {
+                        index,
+                        bytes,
+                        read_only,
+                    },
+                )
+            })
+            .collect();
+        StateSnapshot {
+            clk: self.clk,
+            pc: self.pc,
+            halted: self.halted,
+            registers: self.registers,
+            pages,
+        }
+    }
+
+    /// Replaces the registers, memory, pc and clock with the ones of
+    /// `snapshot`, and keeps the tapes.
+    ///
+    /// # Errors
+    /// Errors if the zero register is not zero, or a page or read-only mask
+    /// has the wrong length.
+    pub fn restore(mut self, snapshot: &StateSnapshot) -> Result<Self> {
+        ensure!(snapshot.registers[0] == 0, "register zero is not zero");
+        let mut memory = StateMemory::default();
+        for page in &snapshot.pages {
+            let bytes: Page = page.bytes.as_slice().try_into().map_err(|_| {
+                anyhow!(
+                    "page {:#x} has {} bytes instead of {PAGE_SIZE}",
+                    page.index,
+                    page.bytes.len()
+                )
+            })?;
+            memory.pages.insert(page.index, Arc::new(bytes));
+            if !page.read_only.is_empty() {
+                let mask: PageMask = page.read_only.as_slice().try_into().map_err(|_| {
+                    anyhow!("page {:#x} has a malformed read-only mask", page.index)
+                })?;
+                memory.read_only.insert(page.index, Arc::new(mask));
+            }
+        }
+        self.clk = snapshot.clk;
+        self.pc = snapshot.pc;
+        self.halted = snapshot.halted;
+        self.registers = snapshot.registers;
+        self.memory = memory;
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use plonky2::field::goldilocks_field::GoldilocksField;
+    use rkyv::rancor::Panic;
+
+    use super::StateSnapshot;
+    use crate::code;
+    use crate::instruction::Instruction;
+    use crate::state::State;
+    use crate::vm::step;
+
+    type F = GoldilocksField;
+
+    #[test]
+    fn snapshot_and_restore() {
+        let store = Instruction::sw(5, 0, 0x1000);
+        let add = Instruction::addi(5, 5, 1);
+        let (program, record) =
+            code::execute([store, add, store, add], &[(0x2000, 0)], &[(5, 0xdead)]);
+
+        let middle = &record.executed[2].state;
+        let snapshot = middle.snapshot();
+        // The untouched page at 0x2000 reads as zero, so it is left out.
+        assert_eq!(
+            snapshot
+                .pages
+                .iter()
+                .map(|page| page.index)
+                .collect::<Vec<_>>(),
+            vec![0x1]
+        );
+
+        let bytes = rkyv::to_bytes::<_, 256, Panic>(&snapshot).unwrap();
+        let read: StateSnapshot = rkyv::from_bytes::<StateSnapshot, Panic>(&bytes).unwrap();
+        assert_eq!(read, snapshot);
+        assert_eq!(read.digest::<F>(), snapshot.digest::<F>());
+
+        let restored = State::<F>::default().restore(&read).unwrap();
+        assert_eq!(restored.snapshot(), snapshot);
+        let resumed = step(&program, restored).unwrap();
+        assert_eq!(
+            resumed.last_state.snapshot().digest::<F>(),
+            record.last_state.snapshot().digest::<F>()
+        );
+        assert_ne!(
+            snapshot.digest::<F>(),
+            record.last_state.snapshot().digest::<F>()
+        );
+    }
+
+    #[test]
+    fn restore_rejects_malformed_pages() {
+        let mut snapshot = State::<F>::default().snapshot();
+        snapshot.pages.push(super::SnapshotPage {
+            index: 1,
+            bytes: vec![1; 7],
+            read_only: vec![],
+        });
+        assert!(State::<F>::default().restore(&snapshot).is_err());
+    }
+}
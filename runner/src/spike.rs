@@ -0,0 +1,183 @@
+//! Export of an [`ExecutionRecord`] in the log format of Spike
+//! (`riscv-isa-sim`), so that the trace diffing tools of the RISC-V ecosystem
+//! can compare our runner with Spike.
+//!
+//! Each executed instruction becomes a [`Commit`], which prints like the
+//! output of `spike -l --log-commits` for a 32-bit core in machine mode: a
+//! line with the pc, the raw instruction and its disassembly, and a line with
+//! the register and memory writes it commits.
+//!
+//! The disassembly is the one of [`disassemble`], which shows the canonical
+//! instruction of what we decoded, and may name a different mnemonic than
+//! Spike for the same word. Programs that do not carry their code in memory,
+//! like hand-assembled test programs, log their raw instructions as zero.
+
+use std::fmt::{self, Display, Formatter};
+
+use itertools::{chain, izip};
+use plonky2::hash::hash_types::RichField;
+
+use crate::elf::Program;
+use crate::instruction::Op;
+use crate::memory_stats::reads_and_writes;
+use crate::state::State;
+use crate::vm::disasm::{disassemble, instruction_word};
+use crate::vm::{ExecutionRecord, Row};
+
+/// A write to memory, of the `bytes` lowest bytes of `value`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MemoryWrite {
+    pub addr: u32,
+    pub value: u32,
+    pub bytes: u32,
+}
+
+/// An executed instruction and its effects, in the terms of Spike.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Commit {
+    pub pc: u32,
+    /// The raw instruction, or zero if the program does not carry its code
+    /// in memory.
+    pub word: u32,
+    pub disassembly: String,
+    /// Registers written, except the zero register, with their new values.
+    pub register_writes: Vec<(u8, u32)>,
+    /// Address of a load.
+    pub load: Option<u32>,
+    pub memory_writes: Vec<MemoryWrite>,
+}
+
+impl Display for Commit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let Self { pc, word, .. } = self;
+        writeln!(
+            f,
+            "core   0: 0x{pc:08x} (0x{word:08x}) {}",
+            self.disassembly
+        )?;
+        write!(f, "core   0: 3 0x{pc:08x} (0x{word:08x})")?;
+        for (reg, value) in &self.register_writes {
+            write!(f, " x{reg:<2} 0x{value:08x}")?;
+        }
+        if let Some(addr) = self.load {
+            write!(f, " mem 0x{addr:08x}")?;
+        }
+        for MemoryWrite { addr, value, bytes } in &self.memory_writes {
+            let width = 2 * *bytes as usize;
+            write!(f, " mem 0x{addr:08x} 0x{value:0width$x}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether `op` writes its destination register.
+fn writes_rd(op: Op) -> bool {
+    !matches!(
+        op,
+        Op::SB
+            | Op::SH
+            | Op::SW
+            | Op::BEQ
+            | Op::BNE
+            | Op::BLT
+            | Op::BGE
+            | Op::BLTU
+            | Op::BGEU
+            | Op::ECALL
+    )
+}
+
+impl Commit {
+    /// The commit of `row`, which leaves the VM in state `next`.
+    #[must_use]
+    pub fn new<F: RichField>(program: &Program, row: &Row<F>, next: &State<F>) -> Self {
+        let pc = row.state.get_pc();
+        let word = instruction_word(program, pc).unwrap_or(0);
+        let op = row.instruction.op;
+        let rd = row.instruction.args.rd;
+        // Ecalls may write any register, so compare all of them.
+        let register_writes = if op == Op::ECALL {
+            izip!(0.., row.state.registers, next.registers)
+                .filter(|&(reg, before, after)| reg != 0 && before != after)
+                .map(|(reg, _, after)| (reg, after))
+                .collect()
+        } else if writes_rd(op) && rd != 0 {
+            vec![(rd, next.get_register_value(rd))]
+        } else {
+            vec![]
+        };
+        let (reads, writes) = reads_and_writes(row);
+        let is_load = matches!(op, Op::LB | Op::LH | Op::LW | Op::LBU | Op::LHU);
+        let memory_writes = match (op, row.aux.mem) {
+            (Op::SB | Op::SH | Op::SW, Some(mem)) => vec![MemoryWrite {
+                addr: mem.addr,
+                value: mem.raw_value,
+                bytes: u32::try_from(writes.len()).expect("stores write at most 4 bytes"),
+            }],
+            // Ecalls write byte by byte.
+            _ => writes
+                .iter()
+                .map(|&addr| MemoryWrite {
+                    addr,
+                    value: u32::from(next.load_u8(addr)),
+                    bytes: 1,
+                })
+                .collect(),
+        };
+        Self {
+            pc,
+            word,
+            disassembly: disassemble(pc, &row.instruction),
+            register_writes,
+            load: reads.first().copied().filter(|_| is_load),
+            memory_writes,
+        }
+    }
+}
+
+/// The commits of all instructions of `record`, in order.
+#[must_use]
+pub fn commits<F: RichField>(program: &Program, record: &ExecutionRecord<F>) -> Vec<Commit> {
+    let nexts = chain!(record.executed.iter().skip(1).map(|row| &row.state), [
+        &record.last_state
+    ]);
+    record
+        .executed
+        .iter()
+        .zip(nexts)
+        .map(|(row, next)| Commit::new(program, row, next))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::commits;
+    use crate::code;
+    use crate::instruction::Instruction;
+
+    #[test]
+    fn log_lines() {
+        let code = [
+            Instruction::li(5, 0x100),
+            Instruction::sb(5, 5, 2),
+            Instruction::lbu(6, 5, 2),
+            Instruction::beq(5, 6, 0).unwrap(),
+        ];
+        let (program, record) = code::execute(code, &[(0x102, 0)], &[]);
+        let log = commits(&program, &record)
+            .iter()
+            .take(4)
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(log, [
+            "core   0: 0x00000000 (0x00000000) li      t0, 256\n\
+             core   0: 3 0x00000000 (0x00000000) x5  0x00000100",
+            "core   0: 0x00000004 (0x00000000) sb      t0, 2(t0)\n\
+             core   0: 3 0x00000004 (0x00000000) mem 0x00000102 0x00",
+            "core   0: 0x00000008 (0x00000000) lbu     t1, 2(t0)\n\
+             core   0: 3 0x00000008 (0x00000000) x6  0x00000000 mem 0x00000102",
+            "core   0: 0x0000000c (0x00000000) beq     t0, t1, pc - 12\n\
+             core   0: 3 0x0000000c (0x00000000)",
+        ]);
+    }
+}
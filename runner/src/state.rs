@@ -1,19 +1,33 @@
+use std::collections::BTreeMap;
 use std::iter::once;
 use std::marker::PhantomData;
-use std::rc::Rc;
+use std::num::NonZeroU64;
+use std::ops::Range;
+use std::sync::Arc;
 
 use anyhow::{anyhow, Result};
 use im::hashmap::HashMap;
-use im::HashSet;
-use log::trace;
-use mozak_sdk::core::constants::DIGEST_BYTES;
+use im::vector::Vector;
+use mozak_sdk::common::types::ProgramIdentifier;
+use mozak_sdk::core::constants::{DIGEST_BYTES, EVENT_BYTES};
+use mozak_sdk::core::trap::TrapReport;
 use plonky2::hash::hash_types::RichField;
 use serde::{Deserialize, Serialize};
+use tracing::trace;
 
 use crate::code::Code;
 use crate::elf::{Data, Program};
+#[cfg(feature = "goldilocks")]
+use crate::goldilocks;
 use crate::instruction::{Args, DecodingError, Instruction};
+#[cfg(feature = "memcopy")]
+use crate::memcopy;
+#[cfg(feature = "poseidon2")]
 use crate::poseidon2;
+#[cfg(feature = "secp256k1")]
+use crate::secp256k1;
+#[cfg(feature = "u256")]
+use crate::u256;
 
 #[derive(Debug, Clone)]
 pub struct CommitmentTape(pub [u8; DIGEST_BYTES]);
@@ -28,7 +42,7 @@ pub fn read_bytes(buf: &[u8], index: &mut usize, num_bytes: usize) -> Vec<u8> {
     let remaining_len = buf.len() - *index;
     let limit = num_bytes.min(remaining_len);
     let read = buf[*index..(*index + limit)].to_vec();
-    log::trace!(
+    tracing::trace!(
         "read: 0x{:0x}, {:?}, data.len: {:?}, data: {:?}",
         index,
         remaining_len,
@@ -75,14 +89,48 @@ pub struct State<F: RichField> {
     pub events_commitment_tape: CommitmentTape,
     pub cast_list_commitment_tape: CommitmentTape,
     pub self_prog_id_tape: [u8; DIGEST_BYTES],
+    /// Bytes the guest wrote with the `IO_WRITE` ecall, in order.
+    pub output_tape: Vector<u8>,
+    /// Untrusted advice for the guest, read with the `HINT_READ` ecall.
+    pub hint_tape: HintTape,
+    /// Set when the guest halted via a failed assertion, see
+    /// [`mozak_sdk::core::trap`].
+    pub trap: Option<TrapReport>,
+    /// Most instructions the guest may execute, see [`State::with_max_cycles`].
+    pub max_cycles: Option<NonZeroU64>,
+    /// Set when the guest was halted for exhausting `max_cycles`, rather than
+    /// via the `HALT` ecall.
+    pub out_of_gas: bool,
+    /// The part of the program's heap above the current break, see
+    /// [`Program::heap`]. Accessing it traps, and the `SBRK` ecall moves its
+    /// start up.
+    pub unmapped_heap: Option<Range<u32>>,
     _phantom: PhantomData<F>,
 }
 
+/// log2 of [`PAGE_SIZE`].
+pub const PAGE_BITS: u32 = 12;
+/// Size in bytes of the pages backing [`StateMemory`].
+pub const PAGE_SIZE: usize = 1 << PAGE_BITS;
+
+pub(crate) type Page = [u8; PAGE_SIZE];
+
+/// Marks which bytes of a page are read-only, one bit per byte.
+pub(crate) type PageMask = [u64; PAGE_SIZE / 64];
+
+const fn page_of(addr: u32) -> (u32, usize) { (addr >> PAGE_BITS, (addr as usize) % PAGE_SIZE) }
+
+/// Byte addressable memory of the VM, stored in 4 KiB pages.
+///
+/// Pages are shared between clones of the memory, and only copied when one
+/// of the clones writes to them. Snapshotting memory at every step thus costs
+/// one page copy per written page, rather than a map entry per byte.
+/// Unmapped addresses read as zero.
 #[allow(clippy::module_name_repetitions)]
 #[derive(Debug, Clone, Default)]
 pub struct StateMemory {
-    pub data: HashMap<u32, u8>,
-    pub is_read_only: HashSet<u32>,
+    pub(crate) pages: HashMap<u32, Arc<Page>>,
+    pub(crate) read_only: HashMap<u32, Arc<PageMask>>,
 }
 
 impl StateMemory {
@@ -90,21 +138,67 @@ impl StateMemory {
     where
         I: Iterator<Item = HashMap<u32, u8>>,
         J: Iterator<Item = HashMap<u32, u8>>, {
-        let ro: HashMap<u32, u8> = ro.flat_map(HashMap::into_iter).collect();
-        let mut rw: HashMap<u32, u8> = rw.flat_map(HashMap::into_iter).collect();
-        StateMemory {
-            is_read_only: ro.keys().copied().collect(),
-            data: {
-                rw.extend(ro);
-                rw
-            },
+        let mut memory = StateMemory::default();
+        for (addr, value) in rw.flat_map(HashMap::into_iter) {
+            memory.write(addr, value);
         }
+        for (addr, value) in ro.flat_map(HashMap::into_iter) {
+            memory.write(addr, value);
+            let (page, offset) = page_of(addr);
+            let mask = memory
+                .read_only
+                .entry(page)
+                .or_insert_with(|| Arc::new([0; PAGE_SIZE / 64]));
+            Arc::make_mut(mask)[offset / 64] |= 1 << (offset % 64);
+        }
+        memory
+    }
+
+    /// Load a byte from memory
+    #[must_use]
+    pub fn load_u8(&self, addr: u32) -> u8 {
+        let (page, offset) = page_of(addr);
+        self.pages.get(&page).map_or(0, |page| page[offset])
+    }
+
+    #[must_use]
+    pub fn is_read_only(&self, addr: u32) -> bool {
+        let (page, offset) = page_of(addr);
+        self.read_only
+            .get(&page)
+            .is_some_and(|mask| mask[offset / 64] & (1 << (offset % 64)) != 0)
+    }
+
+    /// Store a byte to memory
+    ///
+    /// # Errors
+    /// This function returns an error, if you try to store to a read-only
+    /// address.
+    pub fn store_u8(&mut self, addr: u32, value: u8) -> Result<()> {
+        if self.is_read_only(addr) {
+            return Err(anyhow!(
+                "cannot write to ro_memory: address - {:#0x}, value - {:#0x}",
+                addr,
+                value,
+            ));
+        }
+        self.write(addr, value);
+        Ok(())
+    }
+
+    fn write(&mut self, addr: u32, value: u8) {
+        let (page, offset) = page_of(addr);
+        let page = self
+            .pages
+            .entry(page)
+            .or_insert_with(|| Arc::new([0; PAGE_SIZE]));
+        Arc::make_mut(page)[offset] = value;
     }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct StorageDeviceTape {
-    pub data: Rc<[u8]>,
+    pub data: Arc<[u8]>,
     pub read_index: usize,
 }
 
@@ -117,12 +211,61 @@ impl Default for StorageDeviceTape {
     }
 }
 
+/// Answers the `HINT_READ` ecalls of a guest that run past the hints queued
+/// up front, see [`State::with_hint_provider`].
+///
+/// Closures taking a [`HintRequest`] implement this trait.
+pub trait HintProvider: Send + Sync {
+    /// Returns the bytes to append to the hint queue. Returning fewer than
+    /// `request.len` bytes makes the guest read a short hint.
+    fn provide(&self, request: &HintRequest) -> Vec<u8>;
+}
+
+impl<T: Fn(&HintRequest) -> Vec<u8> + Send + Sync> HintProvider for T {
+    fn provide(&self, request: &HintRequest) -> Vec<u8> { self(request) }
+}
+
+/// What a guest asks its [`HintProvider`] for.
+#[derive(Debug)]
+pub struct HintRequest<'a> {
+    pub clk: u64,
+    /// Number of bytes the guest reads, beyond those already queued.
+    pub len: usize,
+    /// Whatever the guest passed in `a3`, typically the address of the
+    /// question in `memory`.
+    pub query: u32,
+    pub memory: &'a StateMemory,
+}
+
+/// Hints are bytes that the host hands to the guest outside of any tape the
+/// verifier sees: neither the hints nor a digest of them are public.
+///
+/// A proof thus holds for whatever hints the prover chose, and guests have to
+/// check each hint before relying on it, see `mozak_sdk::core::hint`.
+#[derive(Clone, Default)]
+pub struct HintTape {
+    /// Every hint so far, queued up front or provided on demand.
+    pub data: Vector<u8>,
+    pub read_index: usize,
+    pub provider: Option<Arc<dyn HintProvider>>,
+}
+
+impl std::fmt::Debug for HintTape {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HintTape")
+            .field("data", &self.data)
+            .field("read_index", &self.read_index)
+            .field("provider", &self.provider.is_some())
+            .finish()
+    }
+}
+
 /// Converts raw bytes in [`Data`] to an [`StorageDeviceTape`] for consumption
 /// via ecalls.
 impl From<Data> for StorageDeviceTape {
     fn from(data: Data) -> Self {
         Self {
-            data: data.0.values().copied().collect::<Rc<[u8]>>(),
+            data: data.0.values().copied().collect::<Arc<[u8]>>(),
             read_index: 0,
         }
     }
@@ -147,6 +290,12 @@ impl<F: RichField> Default for State<F> {
             events_commitment_tape: CommitmentTape([0; DIGEST_BYTES]),
             cast_list_commitment_tape: CommitmentTape([0; DIGEST_BYTES]),
             self_prog_id_tape: [0; 32],
+            output_tape: Vector::new(),
+            hint_tape: HintTape::default(),
+            trap: None,
+            max_cycles: None,
+            out_of_gas: false,
+            unmapped_heap: None,
             _phantom: PhantomData,
         }
     }
@@ -160,6 +309,8 @@ impl<F: RichField> From<Program> for State<F> {
             rw_memory: Data(rw_memory),
             ro_memory: Data(ro_memory),
             entry_point: pc,
+            heap,
+            ..
         }: Program,
     ) -> Self {
         let state: State<F> = State::default();
@@ -167,6 +318,7 @@ impl<F: RichField> From<Program> for State<F> {
         Self {
             pc,
             memory: StateMemory::new(once(ro_memory), once(rw_memory)),
+            unmapped_heap: heap,
             ..state
         }
     }
@@ -190,6 +342,10 @@ pub enum StorageDeviceOpcode {
     StoreEventsCommitmentTape,
     StoreCastListCommitmentTape,
     StoreSelfProgIdTape,
+    /// Stores untrusted hints from the host, see [`HintTape`].
+    StoreHint,
+    /// Loads bytes from memory and appends them to the output tape.
+    LoadOutputTape,
 }
 
 #[derive(Debug, Default, Clone)]
@@ -197,6 +353,18 @@ pub struct StorageDeviceEntry {
     pub addr: u32,
     pub op: StorageDeviceOpcode,
     pub data: Vec<u8>,
+    /// Position on the tape of the first byte read, or written.
+    pub offset: u32,
+    /// Whether the read started at an explicit offset, instead of at the
+    /// current read position.
+    pub is_read_at: bool,
+}
+
+/// An event emitted with the `EMIT_EVENT` ecall.
+#[derive(Debug, Clone)]
+pub struct EventEntry {
+    pub addr: u32,
+    pub event: [u8; EVENT_BYTES],
 }
 
 /// Auxiliary information about the instruction execution
@@ -212,11 +380,21 @@ pub struct Aux<F: RichField> {
     pub op1: u32,
     pub op2: u32,
     pub op2_raw: u32,
+    #[cfg(feature = "poseidon2")]
     pub poseidon2: Option<poseidon2::Entry<F>>,
+    #[cfg(feature = "secp256k1")]
+    pub secp256k1: Option<secp256k1::Entry>,
+    #[cfg(feature = "u256")]
+    pub u256: Option<u256::Entry>,
+    #[cfg(feature = "memcopy")]
+    pub memcopy: Option<memcopy::Entry>,
+    #[cfg(feature = "goldilocks")]
+    pub goldilocks: Option<goldilocks::Entry>,
     pub storage_device_entry: Option<StorageDeviceEntry>,
+    pub event: Option<EventEntry>,
 }
 
-#[derive(Default, Clone)]
+#[derive(Debug, Default, Clone)]
 pub struct RawTapes {
     pub private_tape: Vec<u8>,
     pub public_tape: Vec<u8>,
@@ -227,6 +405,32 @@ pub struct RawTapes {
     pub self_prog_id_tape: [u8; 32],
 }
 
+impl RawTapes {
+    /// Takes the private tape of the program in `self_prog_id_tape` from
+    /// `tapes`. The private tapes of the other programs stay out of the
+    /// execution, so the guest cannot read them.
+    #[must_use]
+    pub fn with_private_tape_from(self, tapes: &PrivateTapes) -> Self {
+        Self {
+            private_tape: tapes.of(&ProgramIdentifier::from(self.self_prog_id_tape)),
+            ..self
+        }
+    }
+}
+
+/// The private tapes of the programs of a multi-program execution, keyed by
+/// the program that may read each.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PrivateTapes(pub BTreeMap<ProgramIdentifier, Vec<u8>>);
+
+impl PrivateTapes {
+    /// The private tape of `program`, empty if it has none.
+    #[must_use]
+    pub fn of(&self, program: &ProgramIdentifier) -> Vec<u8> {
+        self.0.get(program).cloned().unwrap_or_default()
+    }
+}
+
 impl<F: RichField> State<F> {
     #[must_use]
     #[allow(clippy::similar_names)]
@@ -238,6 +442,7 @@ impl<F: RichField> State<F> {
             rw_memory: Data(rw_memory),
             ro_memory: Data(ro_memory),
             entry_point: pc,
+            heap,
             ..
         }: Program,
         raw_tapes: RawTapes,
@@ -245,6 +450,7 @@ impl<F: RichField> State<F> {
         Self {
             pc,
             memory: StateMemory::new(once(ro_memory), once(rw_memory)),
+            unmapped_heap: heap,
             private_tape: StorageDeviceTape {
                 data: raw_tapes.private_tape.into(),
                 read_index: 0,
@@ -346,6 +552,50 @@ impl<F: RichField> State<F> {
     #[must_use]
     pub fn has_halted(&self) -> bool { self.halted }
 
+    /// Bounds the execution to `max_cycles` instructions.
+    ///
+    /// Cycles are counted by the clock, which is 2 for the first instruction.
+    /// A guest that is still running once the clock reaches `max_cycles + 2`
+    /// is halted out of gas, see [`State::out_of_gas`].
+    #[must_use]
+    pub fn with_max_cycles(self, max_cycles: NonZeroU64) -> Self {
+        Self {
+            max_cycles: Some(max_cycles),
+            ..self
+        }
+    }
+
+    /// Queues `hints` for the guest to read with the `HINT_READ` ecall, ahead
+    /// of any from the [`HintProvider`].
+    #[must_use]
+    pub fn with_hints(mut self, hints: impl IntoIterator<Item = u8>) -> Self {
+        self.hint_tape.data.extend(hints);
+        self
+    }
+
+    /// Asks `provider` for more hints whenever the guest reads past the
+    /// queued ones.
+    #[must_use]
+    pub fn with_hint_provider(mut self, provider: impl HintProvider + 'static) -> Self {
+        self.hint_tape.provider = Some(Arc::new(provider));
+        self
+    }
+
+    /// Halts out of gas, if the guest is still running but has used up its
+    /// cycle budget.
+    #[must_use]
+    pub fn check_cycle_budget(mut self) -> Self {
+        if !self.halted
+            && self
+                .max_cycles
+                .is_some_and(|max_cycles| self.clk >= max_cycles.get().saturating_add(2))
+        {
+            self.halted = true;
+            self.out_of_gas = true;
+        }
+        self
+    }
+
     /// Load a byte from memory
     ///
     /// # Panics
@@ -407,9 +657,7 @@ impl<F: RichField> State<F> {
     /// address space you can get with 32 bits.
     /// So no u32 address is out of bounds.
     #[must_use]
-    pub fn load_u8(&self, addr: u32) -> u8 {
-        self.memory.data.get(&addr).copied().unwrap_or_default()
-    }
+    pub fn load_u8(&self, addr: u32) -> u8 { self.memory.load_u8(addr) }
 
     /// Store a byte to memory
     ///
@@ -417,16 +665,8 @@ impl<F: RichField> State<F> {
     /// This function returns an error, if you try to store to an invalid
     /// address.
     pub fn store_u8(mut self, addr: u32, value: u8) -> Result<Self> {
-        if self.memory.is_read_only.contains(&addr) {
-            Err(anyhow!(
-                "cannot write to ro_memory: address - {:#0x}, value - {:#0x}",
-                addr,
-                value,
-            ))
-        } else {
-            self.memory.data.insert(addr, value);
-            Ok(self)
-        }
+        self.memory.store_u8(addr, value)?;
+        Ok(self)
     }
 
     #[must_use]
@@ -441,3 +681,80 @@ impl<F: RichField> State<F> {
         inst
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use im::hashmap::HashMap;
+    use mozak_sdk::common::types::ProgramIdentifier;
+
+    use super::{PrivateTapes, RawTapes, StateMemory, PAGE_SIZE};
+
+    #[test]
+    fn clones_copy_pages_on_write() {
+        let mut memory = StateMemory::default();
+        memory.store_u8(0x1000, 1).unwrap();
+        let snapshot = memory.clone();
+        memory.store_u8(0x1000, 2).unwrap();
+        memory.store_u8(0x1001, 3).unwrap();
+
+        assert_eq!(snapshot.load_u8(0x1000), 1);
+        assert_eq!(snapshot.load_u8(0x1001), 0);
+        assert_eq!(memory.load_u8(0x1000), 2);
+        assert_eq!(memory.load_u8(0x1001), 3);
+    }
+
+    #[test]
+    fn bytes_across_page_boundaries() {
+        let mut memory = StateMemory::default();
+        let last = u32::try_from(PAGE_SIZE).unwrap() - 1;
+        memory.store_u8(last, 0xAB).unwrap();
+        memory.store_u8(last + 1, 0xCD).unwrap();
+        memory.store_u8(u32::MAX, 0xEF).unwrap();
+
+        assert_eq!(memory.load_u8(last - 1), 0);
+        assert_eq!(memory.load_u8(last), 0xAB);
+        assert_eq!(memory.load_u8(last + 1), 0xCD);
+        assert_eq!(memory.load_u8(u32::MAX), 0xEF);
+        assert_eq!(memory.load_u8(0), 0);
+    }
+
+    #[test]
+    fn read_only_bytes() {
+        let ro: HashMap<u32, u8> = [(0x2000, 7)].into_iter().collect();
+        let rw: HashMap<u32, u8> = [(0x2000, 1), (0x2001, 2)].into_iter().collect();
+        let mut memory = StateMemory::new([ro].into_iter(), [rw].into_iter());
+
+        assert_eq!(memory.load_u8(0x2000), 7);
+        assert!(memory.is_read_only(0x2000));
+        assert!(!memory.is_read_only(0x2001));
+        assert!(memory.store_u8(0x2000, 9).is_err());
+        memory.store_u8(0x2001, 9).unwrap();
+        assert_eq!(memory.load_u8(0x2000), 7);
+        assert_eq!(memory.load_u8(0x2001), 9);
+    }
+
+    #[test]
+    fn only_the_private_tape_of_the_program_itself() {
+        let (alice, bob) = ([1; 32], [2; 32]);
+        let tapes = PrivateTapes(
+            [
+                (ProgramIdentifier::from(alice), vec![1, 2, 3]),
+                (ProgramIdentifier::from(bob), vec![4, 5]),
+            ]
+            .into_iter()
+            .collect(),
+        );
+        let tapes_of = |self_prog_id_tape| {
+            RawTapes {
+                self_prog_id_tape,
+                ..RawTapes::default()
+            }
+            .with_private_tape_from(&tapes)
+            .private_tape
+        };
+
+        assert_eq!(tapes_of(alice), vec![1, 2, 3]);
+        assert_eq!(tapes_of(bob), vec![4, 5]);
+        assert_eq!(tapes_of([3; 32]), Vec::<u8>::new());
+    }
+}
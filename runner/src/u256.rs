@@ -0,0 +1,225 @@
+//! The u256 addition, multiplication and modular multiplication ecalls.
+//!
+//! Operands are passed by pointers to [`U256_BYTES`] little-endian bytes.
+//! The result overwrites the first operand.
+
+use itertools::{chain, izip};
+use mozak_sdk::core::constants::U256_BYTES;
+use mozak_sdk::core::reg_abi::{REG_A1, REG_A2, REG_A3};
+use num::bigint::BigUint;
+use num::{One, Zero};
+use plonky2::hash::hash_types::RichField;
+
+use crate::state::{Aux, State};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    /// `a + b` modulo `2^256`.
+    Add,
+    /// `a * b` modulo `2^256`.
+    Mul,
+    /// `a * b` modulo `m`.
+    MulMod,
+}
+
+/// The number with little-endian `bytes`.
+#[must_use]
+pub fn to_biguint(bytes: &[u8; U256_BYTES]) -> BigUint { BigUint::from_bytes_le(bytes) }
+
+/// Little-endian bytes of a `value` below `2^256`.
+#[must_use]
+pub fn to_bytes(value: &BigUint) -> [u8; U256_BYTES] {
+    let mut bytes = [0; U256_BYTES];
+    let le = value.to_bytes_le();
+    bytes[..le.len()].copy_from_slice(&le);
+    bytes
+}
+
+/// The modulus of `operation`, which is `2^256` unless it is
+/// [`Operation::MulMod`].
+#[must_use]
+pub fn modulus(operation: Operation, m: &[u8; U256_BYTES]) -> BigUint {
+    match operation {
+        Operation::Add | Operation::Mul => BigUint::one() << 256,
+        Operation::MulMod => to_biguint(m),
+    }
+}
+
+/// The sum or product of `a` and `b`, before reduction.
+#[must_use]
+pub fn unreduced(operation: Operation, a: &[u8; U256_BYTES], b: &[u8; U256_BYTES]) -> BigUint {
+    match operation {
+        Operation::Add => to_biguint(a) + to_biguint(b),
+        Operation::Mul | Operation::MulMod => to_biguint(a) * to_biguint(b),
+    }
+}
+
+/// Applies `operation` to `a` and `b`, with modulus `m` for
+/// [`Operation::MulMod`].
+///
+/// # Panics
+///
+/// Panics if the modulus is zero.
+#[must_use]
+pub fn apply(
+    operation: Operation,
+    a: &[u8; U256_BYTES],
+    b: &[u8; U256_BYTES],
+    m: &[u8; U256_BYTES],
+) -> [u8; U256_BYTES] {
+    let modulus = modulus(operation, m);
+    assert!(!modulus.is_zero(), "u256 mulmod by a zero modulus");
+    to_bytes(&(unreduced(operation, a, b) % modulus))
+}
+
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub operation: Operation,
+    pub a_addr: u32,
+    pub b_addr: u32,
+    /// Zero unless the operation is [`Operation::MulMod`].
+    pub m_addr: u32,
+    pub a: [u8; U256_BYTES],
+    pub b: [u8; U256_BYTES],
+    /// Zero unless the operation is [`Operation::MulMod`].
+    pub m: [u8; U256_BYTES],
+    pub result: [u8; U256_BYTES],
+}
+
+impl<F: RichField> State<F> {
+    fn load_u256(&self, addr: u32) -> [u8; U256_BYTES] {
+        let mut value = [0; U256_BYTES];
+        for (i, byte) in izip!(0_u32.., &mut value) {
+            *byte = self.load_u8(addr.wrapping_add(i));
+        }
+        value
+    }
+
+    /// Applies `operation` to the integers at `a1` and `a2`, modulo the one at
+    /// `a3` for [`Operation::MulMod`], and writes the result over the one at
+    /// `a1`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the modulus is zero.
+    #[must_use]
+    pub fn ecall_u256(self, operation: Operation) -> (Aux<F>, Self) {
+        let is_mulmod = operation == Operation::MulMod;
+        let a_addr = self.get_register_value(REG_A1);
+        let b_addr = self.get_register_value(REG_A2);
+        let m_addr = if is_mulmod {
+            self.get_register_value(REG_A3)
+        } else {
+            0
+        };
+        let a = self.load_u256(a_addr);
+        let b = self.load_u256(b_addr);
+        let m = if is_mulmod {
+            self.load_u256(m_addr)
+        } else {
+            [0; U256_BYTES]
+        };
+        let result = apply(operation, &a, &b, &m);
+
+        let addresses = |addr: u32| (0..).take(U256_BYTES).map(move |i| addr.wrapping_add(i));
+        let mem_addresses_used: Vec<u32> = chain!(
+            addresses(a_addr),
+            addresses(b_addr),
+            is_mulmod.then(|| addresses(m_addr)).into_iter().flatten(),
+            addresses(a_addr),
+        )
+        .collect();
+        (
+            Aux {
+                mem_addresses_used,
+                u256: Some(Entry {
+                    operation,
+                    a_addr,
+                    b_addr,
+                    m_addr,
+                    a,
+                    b,
+                    m,
+                    result,
+                }),
+                ..Default::default()
+            },
+            izip!(0.., result)
+                .fold(self, |updated_self, (i, byte)| {
+                    updated_self.store_u8(a_addr.wrapping_add(i), byte).unwrap()
+                })
+                .bump_pc(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mozak_sdk::core::constants::U256_BYTES;
+    use mozak_sdk::core::ecall;
+    use mozak_sdk::core::reg_abi::{REG_A0, REG_A1, REG_A2, REG_A3};
+    use num::bigint::BigUint;
+
+    use super::{to_biguint, to_bytes};
+    use crate::code;
+    use crate::decode::ECALL;
+
+    fn parse(hex: &str) -> [u8; U256_BYTES] {
+        to_bytes(&BigUint::parse_bytes(hex.as_bytes(), 16).unwrap())
+    }
+
+    fn memory(addr: u32, value: &[u8; U256_BYTES]) -> Vec<(u32, u8)> {
+        (addr..).zip(value.iter().copied()).collect()
+    }
+
+    fn run(op: u32, a: &str, b: &str, m: &str) -> BigUint {
+        let (_program, record) = code::execute(
+            [ECALL],
+            &[
+                memory(0x100, &parse(a)),
+                memory(0x200, &parse(b)),
+                memory(0x300, &parse(m)),
+            ]
+            .concat(),
+            &[
+                (REG_A0, op),
+                (REG_A1, 0x100),
+                (REG_A2, 0x200),
+                (REG_A3, 0x300),
+            ],
+        );
+        assert_eq!(record.last_state.load_u256(0x200), parse(b));
+        to_biguint(&record.last_state.load_u256(0x100))
+    }
+
+    const MAX: &str = "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF";
+
+    #[test]
+    fn add_wraps() {
+        assert_eq!(run(ecall::U256_ADD, MAX, "2", "0"), BigUint::from(1_u32));
+    }
+
+    #[test]
+    fn mul_wraps() {
+        assert_eq!(run(ecall::U256_MUL, MAX, MAX, "0"), BigUint::from(1_u32));
+    }
+
+    #[test]
+    fn mulmod() {
+        // `2^256 - 1` is `-1` modulo `2^128`, so it squares to one.
+        assert_eq!(
+            run(
+                ecall::U256_MULMOD,
+                MAX,
+                MAX,
+                "100000000000000000000000000000000"
+            ),
+            BigUint::from(1_u32)
+        );
+        assert_eq!(run(ecall::U256_MULMOD, "6", "7", "5"), BigUint::from(2_u32));
+    }
+
+    #[test]
+    #[should_panic(expected = "u256 mulmod by a zero modulus")]
+    fn mulmod_by_zero() { let _ = run(ecall::U256_MULMOD, "6", "7", "0"); }
+}
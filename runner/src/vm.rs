@@ -1,6 +1,9 @@
+pub mod disasm;
+
 use anyhow::{anyhow, Result};
 use itertools::Itertools;
 use plonky2::hash::hash_types::RichField;
+use tracing::instrument;
 
 use crate::elf::Program;
 use crate::instruction::{Args, Instruction, Op};
@@ -107,6 +110,38 @@ impl<F: RichField> State<F> {
         )
     }
 
+    /// Reads the cycle counter, ie the clock of the current instruction.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the clock does not fit into a `u32`.
+    #[must_use]
+    pub fn rdcycle(self, inst: &Args) -> (Aux<F>, Self) {
+        let dst_val = u32::try_from(self.clk).expect("cycle counter does not fit into u32");
+        (
+            Aux {
+                dst_val,
+                ..Default::default()
+            },
+            self.set_register_value(inst.rd, dst_val).bump_pc(),
+        )
+    }
+
+    #[must_use]
+    pub fn madd(self, inst: &Args) -> (Aux<F>, Self) {
+        let dst_val = self
+            .get_register_value(inst.rs1)
+            .wrapping_mul(self.get_register_value(inst.rs2))
+            .wrapping_add(self.get_register_value(inst.rd));
+        (
+            Aux {
+                dst_val,
+                ..Default::default()
+            },
+            self.set_register_value(inst.rd, dst_val).bump_pc(),
+        )
+    }
+
     #[must_use]
     /// # Panics
     ///
@@ -139,7 +174,7 @@ impl<F: RichField> State<F> {
     /// # Errors
     ///
     /// Errors if the program contains an instruction with an unsupported
-    /// opcode.
+    /// opcode, or the guest trapped without a well-formed trap report.
     pub fn execute_instruction(self, program: &Program) -> Result<(Aux<F>, Instruction, Self)> {
         let inst = self
             .current_instruction(program)
@@ -194,7 +229,7 @@ impl<F: RichField> State<F> {
             Op::LHU => self.memory_load(&inst.args, 2, lhu),
             Op::LW => self.memory_load(&inst.args, 4, lw),
 
-            Op::ECALL => self.ecall(),
+            Op::ECALL => self.ecall()?,
             Op::JALR => self.jalr(&inst.args),
             // branches
             Op::BEQ => self.branch_op(&inst.args, |a, b| a == b),
@@ -215,6 +250,14 @@ impl<F: RichField> State<F> {
             Op::DIVU => rop!(divu),
             Op::REM => rop!(rem),
             Op::REMU => rop!(remu),
+            Op::RDCYCLE => self.rdcycle(&inst.args),
+            Op::MADD => self.madd(&inst.args),
+            Op::ANDN => rop!(|a, b| a & !b),
+            Op::ORN => rop!(|a, b| a | !b),
+            Op::XNOR => rop!(|a, b| !(a ^ b)),
+            Op::CLZ => rop!(|a, _| a.leading_zeros()),
+            Op::CTZ => rop!(|a, _| a.trailing_zeros()),
+            Op::CPOP => rop!(|a, _| a.count_ones()),
         };
         Ok((
             Aux {
@@ -251,6 +294,15 @@ impl<F: RichField> Row<F> {
 }
 
 /// Unconstrained Trace produced by running the code
+///
+/// The record is kept in memory in full. Rows are cheap, because each
+/// [`State`] shares its memory pages with its neighbours and only holds
+/// copies of the pages written in that step, so the record stays small
+/// next to the traces generated from it. The prover needs every table's
+/// trace at the same time anyway, for the cross table lookups and the
+/// batched FRI opening. A disk-backed record would therefore not bound the
+/// prover's working set; splitting long executions into shorter segments
+/// would.
 #[derive(Debug, Default)]
 pub struct ExecutionRecord<F: RichField> {
     /// Each row holds the state of the vm and auxiliary
@@ -266,11 +318,68 @@ impl<F: RichField> ExecutionRecord<F> {
     pub fn state_before_final(&self) -> &State<F> { &self.executed[self.executed.len() - 2].state }
 }
 
+/// Executes the instruction that `state` points to, and returns the executed
+/// [`Row`] together with the next state.
+///
+/// # Errors
+/// Same as [`step`], for this one instruction.
+pub fn step_once<F: RichField>(program: &Program, state: State<F>) -> Result<(Row<F>, State<F>)> {
+    let (aux, instruction, new_state) = state.clone().execute_instruction(program)?;
+    let new_state = new_state.check_cycle_budget();
+    if let Some((addr, region)) = aux
+        .mem_addresses_used
+        .iter()
+        .find_map(|&addr| Some((addr, program.guard_region_of(addr)?)))
+    {
+        return Err(anyhow!(
+            "Access to guard region {:#x}..{:#x} at address {addr:#x} from pc {:#x}, \
+             likely a stack overflow",
+            region.start,
+            region.end,
+            state.get_pc()
+        ));
+    }
+    if let Some(&addr) = aux
+        .mem_addresses_used
+        .iter()
+        .find(|&&addr| !program.is_mapped(addr))
+    {
+        return Err(anyhow!(
+            "Access to unmapped memory at address {addr:#x} from pc {:#x}",
+            state.get_pc()
+        ));
+    }
+    if let Some((addr, unmapped)) = state.unmapped_heap.as_ref().and_then(|unmapped| {
+        aux.mem_addresses_used
+            .iter()
+            .find(|addr| unmapped.contains(addr))
+            .map(|&addr| (addr, unmapped))
+    }) {
+        return Err(anyhow!(
+            "Access to the heap at address {addr:#x} from pc {:#x}, above the break at {:#x}",
+            state.get_pc(),
+            unmapped.start
+        ));
+    }
+    tracing::trace!("clk: {:?}, {:?}", new_state.clk, instruction);
+    Ok((
+        Row {
+            state,
+            instruction,
+            aux,
+        },
+        new_state,
+    ))
+}
+
 /// Execute a program
 ///
 /// # Errors
 /// This function returns an error, if an instruction could not be loaded
-/// or executed.
+/// or executed, or if it accessed one of the program's guard regions, e.g.
+/// because the stack overflowed, memory outside of the program's layout, or
+/// the heap above the program break. Also errors if the guest trapped without
+/// a well-formed trap report.
 ///
 /// # Panics
 /// Panics in debug mode, when executing more steps than specified in
@@ -279,19 +388,15 @@ impl<F: RichField> ExecutionRecord<F> {
 /// This is a temporary measure to catch problems with accidental infinite
 /// loops. (Matthias had some trouble debugging a problem with jumps
 /// earlier.)
+#[instrument(name = "execute", skip_all)]
 pub fn step<F: RichField>(
     program: &Program,
     mut last_state: State<F>,
 ) -> Result<ExecutionRecord<F>> {
     let mut executed = vec![];
     while !last_state.has_halted() {
-        let (aux, instruction, new_state) = last_state.clone().execute_instruction(program)?;
-        executed.push(Row {
-            state: last_state,
-            instruction,
-            aux,
-        });
-        log::trace!("clk: {:?}, {:?}", new_state.clk, instruction);
+        let (row, new_state) = step_once(program, last_state)?;
+        executed.push(row);
         last_state = new_state;
 
         if cfg!(debug_assertions) {
@@ -330,14 +435,20 @@ pub fn step<F: RichField>(
 #[allow(clippy::cast_sign_loss)]
 #[allow(clippy::cast_possible_wrap)]
 mod tests {
+    use std::num::NonZeroU64;
+
     use im::HashMap;
+    use mozak_sdk::core::ecall;
+    use mozak_sdk::core::reg_abi::{REG_A0, REG_A1, REG_A2, REG_A3, REG_SP};
+    use mozak_sdk::core::trap::{self, TrapReport};
     use plonky2::field::goldilocks_field::GoldilocksField;
     use proptest::prelude::ProptestConfig;
     use proptest::{prop_assume, proptest};
 
     use super::*;
-    use crate::code;
+    use crate::code::{self, Code};
     use crate::decode::ECALL;
+    use crate::state::{HintRequest, RawTapes, StorageDeviceOpcode};
     use crate::test_utils::{i16_extra, i32_extra, i8_extra, reg, u16_extra, u32_extra, u8_extra};
 
     fn simple_test_code(
@@ -389,15 +500,7 @@ mod tests {
             prop_assume!(rs1 != rs2);
             let sum = rs1_value.wrapping_add(rs2_value);
             let e = simple_test_code(
-                [Instruction::new(
-                    Op::ADD,
-                    Args {
-                        rd,
-                        rs1,
-                        rs2,
-                        ..Args::default()
-                    }
-                )],
+                [Instruction::add(rd, rs1, rs2)],
                 &[],
                 &[(rs1, rs1_value), (rs2, rs2_value)]
             );
@@ -407,15 +510,7 @@ mod tests {
         #[test]
         fn addi_proptest(rd in reg(), rs1 in reg(), rs1_value in u32_extra(), imm in u32_extra()) {
             let e = simple_test_code(
-                [Instruction::new(
-                    Op::ADD,
-                    Args {
-                        rd,
-                        rs1,
-                        imm,
-                        ..Args::default()
-                    }
-                )],
+                [Instruction::addi(rd, rs1, imm)],
                 &[],
                 &[(rs1, rs1_value)]
             );
@@ -426,15 +521,7 @@ mod tests {
         fn sll_proptest(rd in reg(), rs1 in reg(), rs2 in reg(), rs1_value in u32_extra(), rs2_value in u32_extra()) {
             prop_assume!(rs1 != rs2);
             let e = simple_test_code(
-                [Instruction::new(
-                    Op::SLL,
-                    Args {
-                        rd,
-                        rs1,
-                        rs2,
-                        ..Args::default()
-                    }
-                )],
+                [Instruction::sll(rd, rs1, rs2)],
                 &[],
                 &[(rs1, rs1_value), (rs2, rs2_value)]
             );
@@ -448,14 +535,7 @@ mod tests {
         fn and_proptest(rd in reg(), rs1 in reg(), rs2 in reg(), rs1_value in u32_extra(), rs2_value in u32_extra()) {
             prop_assume!(rs1 != rs2);
             let e = simple_test_code(
-                [Instruction::new(
-                    Op::AND,
-                    Args { rd,
-                    rs1,
-                    rs2,
-                        ..Args::default()
-                        }
-                )],
+                [Instruction::and(rd, rs1, rs2)],
                 &[],
                 &[(rs1, rs1_value), (rs2, rs2_value)]
             );
@@ -468,14 +548,7 @@ mod tests {
         #[test]
         fn andi_proptest(rd in reg(), rs1 in reg(), rs1_value in u32_extra(), imm in u32_extra()) {
             let e = simple_test_code(
-                [Instruction::new(
-                    Op::AND,
-                    Args { rd,
-                    rs1,
-                    imm,
-                        ..Args::default()
-                        }
-                )],
+                [Instruction::andi(rd, rs1, imm)],
                 &[],
                 &[(rs1, rs1_value)]
             );
@@ -491,14 +564,7 @@ mod tests {
         fn srl_proptest(rd in reg(), rs1 in reg(), rs2 in reg(), rs1_value in u32_extra(), rs2_value in u32_extra()) {
             prop_assume!(rs1 != rs2);
             let e = simple_test_code(
-                [Instruction::new(
-                    Op::SRL,
-                    Args { rd,
-                    rs1,
-                    rs2,
-                        ..Args::default()
-                        }
-                )],
+                [Instruction::srl(rd, rs1, rs2)],
                 &[],
                 &[(rs1, rs1_value), (rs2, rs2_value)]
             );
@@ -518,14 +584,7 @@ mod tests {
         fn or_proptest(rd in reg(), rs1 in reg(), rs2 in reg(), rs1_value in u32_extra(), rs2_value in u32_extra()) {
             prop_assume!(rs1 != rs2);
             let e = simple_test_code(
-                [Instruction::new(
-                    Op::OR,
-                    Args { rd,
-                    rs1,
-                    rs2,
-                        ..Args::default()
-                        }
-                )],
+                [Instruction::or(rd, rs1, rs2)],
                 &[],
                 &[(rs1, rs1_value), (rs2, rs2_value)]
             );
@@ -538,14 +597,7 @@ mod tests {
         #[test]
         fn ori_proptest(rd in reg(), rs1 in reg(), rs1_value in u32_extra(), imm in u32_extra()) {
             let e = simple_test_code(
-                [Instruction::new(
-                    Op::OR,
-                    Args { rd,
-                    rs1,
-                    imm,
-                        ..Args::default()
-                        }
-                )],
+                [Instruction::ori(rd, rs1, imm)],
                 &[],
                 &[(rs1, rs1_value)]
             );
@@ -560,14 +612,7 @@ mod tests {
         fn xor_proptest(rd in reg(), rs1 in reg(), rs2 in reg(), rs1_value in u32_extra(), rs2_value in u32_extra()) {
             prop_assume!(rs1 != rs2);
             let e = simple_test_code(
-                [Instruction::new(
-                    Op::XOR,
-                    Args { rd,
-                    rs1,
-                    rs2,
-                        ..Args::default()
-                        }
-                )],
+                [Instruction::xor(rd, rs1, rs2)],
                 &[],
                 &[(rs1, rs1_value), (rs2, rs2_value)]
             );
@@ -580,14 +625,7 @@ mod tests {
         #[test]
         fn xori_proptest(rd in reg(), rs1 in reg(), rs1_value in u32_extra(), imm in u32_extra()) {
             let e = simple_test_code(
-                [Instruction::new(
-                    Op::XOR,
-                    Args { rd,
-                    rs1,
-                    imm ,
-                        ..Args::default()
-                        }
-                )],
+                [Instruction::xori(rd, rs1, imm)],
                 &[],
                 &[(rs1, rs1_value)]
             );
@@ -602,14 +640,7 @@ mod tests {
         fn sra_proptest(rd in reg(), rs1 in reg(), rs2 in reg(), rs1_value in u32_extra(), rs2_value in u32_extra()) {
             prop_assume!(rs1 != rs2);
             let e = simple_test_code(
-                [Instruction::new(
-                    Op::SRA,
-                    Args { rd,
-                    rs1,
-                    rs2,
-                        ..Args::default()
-                        }
-                )],
+                [Instruction::sra(rd, rs1, rs2)],
                 &[],
                 &[(rs1, rs1_value), (rs2, rs2_value)]
             );
@@ -644,14 +675,7 @@ mod tests {
         fn slt_proptest(rd in reg(), rs1 in reg(), rs2 in reg(), rs1_value in u32_extra(), rs2_value in u32_extra()) {
             prop_assume!(rs1 != rs2);
             let e = simple_test_code(
-                [Instruction::new(
-                    Op::SLT,
-                    Args { rd,
-                    rs1,
-                    rs2,
-                        ..Args::default()
-                        }
-                )],
+                [Instruction::slt(rd, rs1, rs2)],
                 &[],
                 &[(rs1, rs1_value), (rs2, rs2_value)]
             );
@@ -667,14 +691,7 @@ mod tests {
         fn sltu_proptest(rd in reg(), rs1 in reg(), rs2 in reg(), rs1_value in u32_extra(), rs2_value in u32_extra()) {
             prop_assume!(rs1 != rs2);
             let e = simple_test_code(
-                [Instruction::new(
-                    Op::SLTU,
-                    Args { rd,
-                    rs1,
-                    rs2,
-                    ..Args::default()
-                }
-                )],
+                [Instruction::sltu(rd, rs1, rs2)],
                 &[],
                 &[(rs1, rs1_value), (rs2, rs2_value)]
             );
@@ -687,14 +704,7 @@ mod tests {
         #[test]
         fn slti_proptest(rd in reg(), rs1 in reg(), rs1_value in u32_extra(), imm in u32_extra()) {
             let e = simple_test_code(
-                [Instruction::new(
-                    Op::SLT,
-                    Args { rd,
-                    rs1,
-                    imm,
-                    ..Args::default()
-                }
-                )],
+                [Instruction::slti(rd, rs1, imm)],
                 &[],
                 &[(rs1, rs1_value)]
             );
@@ -704,14 +714,7 @@ mod tests {
         #[test]
         fn sltiu_proptest(rd in reg(), rs1 in reg(), rs1_value in u32_extra(), imm in u32_extra()) {
             let e = simple_test_code(
-                [Instruction::new(
-                    Op::SLTU,
-                    Args { rd,
-                    rs1,
-                    imm,
-                    ..Args::default()
-                }
-                )],
+                [Instruction::sltiu(rd, rs1, imm)],
                 &[],
                 &[(rs1, rs1_value)]
             );
@@ -729,15 +732,7 @@ mod tests {
             let address = rs2_value.wrapping_add(offset);
 
             let e = simple_test_code(
-                [Instruction::new(
-                    Op::LB,
-                    Args { rd,
-                    rs2,
-                    imm: offset,
-                    ..Args::default()
-                }
-
-                )],
+                [Instruction::lb(rd, rs2, offset)],
                 &[(address, memory_value as u8)],
                 &[(rs2, rs2_value)]
             );
@@ -751,15 +746,7 @@ mod tests {
             let address = rs2_value.wrapping_add(offset);
 
             let e = simple_test_code(
-                [Instruction::new(
-                    Op::LBU,
-                    Args { rd,
-                    rs2,
-                    imm: offset,
-                    ..Args::default()
-                }
-
-                )],
+                [Instruction::lbu(rd, rs2, offset)],
                 &[(address, memory_value)],
                 &[(rs2, rs2_value)]
             );
@@ -772,15 +759,7 @@ mod tests {
             let [mem0, mem1] = memory_value.to_le_bytes();
 
             let e = simple_test_code(
-                [Instruction::new(
-                    Op::LH,
-                    Args { rd,
-                    rs2,
-                    imm: offset,
-                    ..Args::default()
-                }
-
-                )],
+                [Instruction::lh(rd, rs2, offset)],
                 &[(address, mem0), (address.wrapping_add(1), mem1)],
                 &[(rs2, rs2_value)]
             );
@@ -793,15 +772,7 @@ mod tests {
             let [mem0, mem1] = memory_value.to_le_bytes();
 
             let e = simple_test_code(
-                [Instruction::new(
-                    Op::LHU,
-                    Args { rd,
-                    rs2,
-                    imm: offset,
-                    ..Args::default()
-                }
-
-                )],
+                [Instruction::lhu(rd, rs2, offset)],
                 &[(address, mem0), (address.wrapping_add(1), mem1)],
                 &[(rs2, rs2_value)]
             );
@@ -815,14 +786,7 @@ mod tests {
             let [mem0, mem1, mem2, mem3] = memory_value.to_le_bytes();
 
             let e = simple_test_code(
-                [Instruction::new(
-                    Op::LW,
-                    Args { rd,
-                    rs2,
-                    imm: offset,
-                    ..Args::default()
-                }
-                )],
+                [Instruction::lw(rd, rs2, offset)],
                 &[(address, mem0), (address.wrapping_add(1), mem1), (address.wrapping_add(2), mem2), (address.wrapping_add(3), mem3)],
                 &[(rs2, rs2_value)]
             );
@@ -834,14 +798,7 @@ mod tests {
             prop_assume!(rs1 != rs2);
             let address = rs2_val.wrapping_add(offset);
             let e = simple_test_code(
-                [Instruction::new(
-                    Op::SB,
-                    Args {rs1,
-                    rs2,
-                    imm: offset,
-                    ..Args::default()
-                }
-                )],
+                [Instruction::sb(rs1, rs2, offset)],
                 &[(address, 0x0)],
                 &[(rs1, rs1_val), (rs2, rs2_val)]
             );
@@ -854,16 +811,7 @@ mod tests {
             prop_assume!(rs1 != rs2);
             let address = rs2_val.wrapping_add(offset);
             let e = simple_test_code(
-                [Instruction::new(
-                    Op::SH,
-                    Args {
-                    rs1,
-                    rs2,
-                    imm: offset,
-                    ..Args::default()
-                }
-
-                )],
+                [Instruction::sh(rs1, rs2, offset)],
                 &[(address, 0x0)],
                 &[(rs1, rs1_val), (rs2, rs2_val)]
             );
@@ -885,15 +833,7 @@ mod tests {
             prop_assume!(rs1 != rs2);
             let address = rs2_val.wrapping_add(offset);
             let e = simple_test_code(
-                [Instruction::new(
-                    Op::SW,
-                Args {
-                    rs1,
-                    rs2,
-                    imm: offset,
-                    ..Args::default()
-                }
-                )],
+                [Instruction::sw(rs1, rs2, offset)],
                 &[(address, 0x0)],
                 &[(rs1, rs1_val), (rs2, rs2_val)]
             );
@@ -916,14 +856,7 @@ mod tests {
             prop_assume!(rs1 != rs2);
             let prod = rs1_value.wrapping_mul(rs2_value);
             let e = simple_test_code(
-                [Instruction::new(
-                    Op::MUL,
-                    Args { rd,
-                    rs1,
-                    rs2,
-                    ..Args::default()
-                }
-                )],
+                [Instruction::mul(rd, rs1, rs2)],
                 &[],
                 &[(rs1, rs1_value), (rs2, rs2_value)]
             );
@@ -942,14 +875,7 @@ mod tests {
             prop_assume!(rs1 != rs2);
             let prod: i64 = i64::from(rs1_value) * i64::from(rs2_value);
             let e = simple_test_code(
-                [Instruction::new(
-                    Op::MULH,
-                    Args { rd,
-                    rs1,
-                    rs2,
-                    ..Args::default()
-                }
-                )],
+                [Instruction::mulh(rd, rs1, rs2)],
                 &[],
                 &[(rs1, rs1_value as u32), (rs2, rs2_value as u32)]
             );
@@ -962,14 +888,7 @@ mod tests {
             prop_assume!(rs1 != rs2);
             let prod: u64 = u64::from(rs1_value) * u64::from(rs2_value);
             let e = simple_test_code(
-                [Instruction::new(
-                    Op::MULHU,
-                    Args { rd,
-                    rs1,
-                    rs2,
-                    ..Args::default()
-                }
-                )],
+                [Instruction::mulhu(rd, rs1, rs2)],
                 &[],
                 &[(rs1, rs1_value), (rs2, rs2_value)]
             );
@@ -982,14 +901,7 @@ mod tests {
             prop_assume!(rs1 != rs2);
             let prod: i64 = i64::from(rs1_value) * i64::from(rs2_value);
             let e = simple_test_code(
-                [Instruction::new(
-                    Op::MULHSU,
-                    Args { rd,
-                    rs1,
-                    rs2,
-                    ..Args::default()
-                }
-                )],
+                [Instruction::mulhsu(rd, rs1, rs2)],
                 &[],
                 &[(rs1, rs1_value as u32), (rs2, rs2_value)]
             );
@@ -1002,14 +914,7 @@ mod tests {
             prop_assume!(rs1 != rs2);
             prop_assume!(rs2_value != 0);
             let e = simple_test_code(
-                [Instruction::new(
-                    Op::DIV,
-                    Args { rd,
-                    rs1,
-                    rs2,
-                    ..Args::default()
-                }
-                )],
+                [Instruction::div(rd, rs1, rs2)],
                 &[],
                 &[(rs1, rs1_value as u32), (rs2, rs2_value as u32)]
             );
@@ -1021,14 +926,7 @@ mod tests {
             prop_assume!(rs1 != rs2);
             prop_assume!(rs2_value != 0);
             let e = simple_test_code(
-                [Instruction::new(
-                    Op::DIVU,
-                    Args { rd,
-                    rs1,
-                    rs2,
-                    ..Args::default()
-                }
-                )],
+                [Instruction::divu(rd, rs1, rs2)],
                 &[],
                 &[(rs1, rs1_value), (rs2, rs2_value)]
             );
@@ -1048,14 +946,7 @@ mod tests {
             prop_assume!(rs1_value != i32::MIN && rs2_value != -1);
             let rem = rs1_value % rs2_value;
             let e = simple_test_code(
-                [Instruction::new(
-                    Op::REM,
-                    Args { rd,
-                    rs1,
-                    rs2,
-                    ..Args::default()
-                }
-                )],
+                [Instruction::rem(rd, rs1, rs2)],
                 &[],
                 &[(rs1, rs1_value as u32), (rs2, rs2_value as u32)]
             );
@@ -1068,14 +959,7 @@ mod tests {
             prop_assume!(rs2_value != 0);
             let rem = rs1_value % rs2_value;
             let e = simple_test_code(
-                [Instruction::new(
-                    Op::REMU,
-                    Args { rd,
-                    rs1,
-                    rs2,
-                    ..Args::default()
-                }
-                )],
+                [Instruction::remu(rd, rs1, rs2)],
                 &[],
                 &[(rs1, rs1_value), (rs2, rs2_value)]
             );
@@ -1097,23 +981,8 @@ mod tests {
                         imm: 8,  // branch target
                 }
                     ),
-                    Instruction::new(
-                        Op::SUB,
-                        Args { rd: rs1,
-                        rs1,
-                        rs2,
-
-                    ..Args::default()
-                }
-                    ),
-                    Instruction::new(
-                        Op::ADD,
-                        Args { rd,
-                        rs1,
-                        rs2,
-                        ..Args::default()
-                    }
-                    ),
+                    Instruction::sub(rs1, rs1, rs2),
+                    Instruction::add(rd, rs1, rs2),
                 ],
                 &[],
                 &[(rs1, rs1_value), (rs2, rs2_value)]
@@ -1136,22 +1005,8 @@ mod tests {
                         imm: 8,  // branch target
                     }
                     ),
-                    Instruction::new(
-                        Op::SUB,
-                        Args { rd: rs1,
-                        rs1,
-                        rs2,
-                        ..Args::default()
-                    }
-                    ),
-                    Instruction::new(
-                        Op::ADD,
-                        Args { rd,
-                        rs1,
-                        rs2,
-                        ..Args::default()
-                    }
-                    ),
+                    Instruction::sub(rs1, rs1, rs2),
+                    Instruction::add(rd, rs1, rs2),
                 ],
                 &[],
                 &[(rs1, rs1_value), (rs2, rs2_value)]
@@ -1176,22 +1031,8 @@ mod tests {
                         imm: 8,  // branch target
                     }
                     ),
-                    Instruction::new(
-                        Op::SUB,
-                        Args {rd: rs1,
-                        rs1,
-                        rs2,
-                        ..Args::default()
-                    }
-                    ),
-                    Instruction::new(
-                        Op::ADD,
-                        Args { rd,
-                        rs1,
-                        rs2,
-                        ..Args::default()
-                    }
-                    ),
+                    Instruction::sub(rs1, rs1, rs2),
+                    Instruction::add(rd, rs1, rs2),
                 ],
                 &[],
                 &[(rs1, rs1_value as u32), (rs2, rs2_value as u32)]
@@ -1216,22 +1057,8 @@ mod tests {
                         imm: 8,  // branch target
                     }
                     ),
-                    Instruction::new(
-                        Op::SUB,
-                        Args { rd: rs1,
-                        rs1,
-                        rs2,
-                        ..Args::default()
-                        }
-                    ),
-                    Instruction::new(
-                        Op::ADD,
-                        Args { rd,
-                        rs1,
-                        rs2,
-                        ..Args::default()
-                        }
-                    ),
+                    Instruction::sub(rs1, rs1, rs2),
+                    Instruction::add(rd, rs1, rs2),
                 ],
                 &[],
                 &[(rs1, rs1_value), (rs2, rs2_value)]
@@ -1256,22 +1083,8 @@ mod tests {
                         imm: 8,  // branch target
                         }
                     ),
-                    Instruction::new(
-                        Op::SUB,
-                        Args { rd: rs1,
-                        rs1,
-                        rs2,
-                        ..Args::default()
-                        }
-                    ),
-                    Instruction::new(
-                        Op::ADD,
-                        Args { rd,
-                        rs1,
-                        rs2,
-                        ..Args::default()
-                        }
-                    ),
+                    Instruction::sub(rs1, rs1, rs2),
+                    Instruction::add(rd, rs1, rs2),
                 ],
                 &[],
                 &[(rs1, rs1_value as u32), (rs2, rs2_value as u32)]
@@ -1306,15 +1119,7 @@ mod tests {
                         imm: 0,  // branch target
                         }
                     ),
-                    Instruction::new(
-                        Op::ADD,
-                        Args {
-                        rd,
-                        rs1,
-                        rs2,
-                        ..Args::default()
-                        }
-                    ),
+                    Instruction::add(rd, rs1, rs2),
                 ],
                 &[],
                 &[(rs1, rs1_value), (rs2, rs2_value)]
@@ -1336,13 +1141,7 @@ mod tests {
                     );
             let e = simple_test_code(
                 [
-                    Instruction::new(
-                        Op::JALR,
-                        Args {
-                            imm: imm_value_fixed,
-                            ..Args::default()
-                        }
-                    ),
+                    Instruction::jal(0, imm_value_fixed).unwrap(),
                     inst,
                     inst,
                     inst,
@@ -1391,6 +1190,275 @@ mod tests {
     #[test]
     fn ecall() { let _ = simple_test_code([ECALL], &[], &[]); }
 
+    #[test]
+    fn ecall_trap() {
+        let report = TrapReport {
+            code: trap::ASSERT_EQ,
+            file_hash: trap::file_hash("src/main.rs"),
+            line: 42,
+            message: "expected: 1, actual: 2".to_string(),
+        };
+        let report_bytes = report.to_bytes();
+        let report_addr = 0x100;
+        let mem = (report_addr..)
+            .zip(report_bytes.iter().copied())
+            .collect_vec();
+        let e = simple_test_code([ECALL], &mem, &[
+            (REG_A0, ecall::TRAP),
+            (REG_A1, report.code),
+            (REG_A2, report_addr),
+            (REG_A3, u32::try_from(report_bytes.len()).unwrap()),
+        ]);
+        assert!(e.last_state.has_halted());
+        assert_eq!(e.last_state.trap, Some(report));
+    }
+
+    #[test]
+    fn ecall_trap_with_malformed_report() {
+        let code = [ECALL];
+        let program = Program::create(&[], &[], Code((0..).step_by(4).zip(code.map(Ok)).collect()));
+        // An empty report lacks the file hash and line.
+        let state = State::<GoldilocksField>::new(program.clone(), RawTapes::default())
+            .set_register_value(REG_A0, ecall::TRAP)
+            .set_register_value(REG_A1, trap::ASSERT_EQ);
+        let err = step(&program, state).unwrap_err();
+        assert!(err.to_string().contains("malformed"), "{err}");
+    }
+
+    #[test]
+    fn ecall_public_tape_read_at() {
+        let public_tape: Vec<u8> = (0..16).collect();
+        let (_, e) = code::execute_code_with_ro_memory(
+            [
+                ECALL,
+                // Continue with a plain read from where `read_at` stopped.
+                Instruction::li(REG_A0, ecall::PUBLIC_TAPE),
+                Instruction::li(REG_A1, 0x200),
+                Instruction::li(REG_A2, 2),
+                ECALL,
+            ],
+            &[],
+            &(0x100..0x103)
+                .chain(0x200..0x202)
+                .map(|addr| (addr, 0))
+                .collect_vec(),
+            &[
+                (REG_A0, ecall::PUBLIC_TAPE_READ_AT),
+                (REG_A1, 0x100),
+                (REG_A2, 3),
+                (REG_A3, 5),
+            ],
+            RawTapes {
+                public_tape,
+                ..RawTapes::default()
+            },
+        );
+        let state = &e.last_state;
+        let read = |addr: u32, len: u32| (addr..addr + len).map(|a| state.load_u8(a)).collect_vec();
+        assert_eq!(read(0x100, 3), [5, 6, 7]);
+        assert_eq!(read(0x200, 2), [8, 9]);
+        let entries = e
+            .executed
+            .iter()
+            .filter_map(|row| row.aux.storage_device_entry.as_ref())
+            .map(|entry| (entry.offset, entry.is_read_at))
+            .collect_vec();
+        assert_eq!(entries, [(5, true), (8, false)]);
+    }
+
+    #[test]
+    fn ecall_io_write_appends_to_the_output_tape() {
+        let (_, e) = code::execute(
+            [
+                ECALL,
+                Instruction::li(REG_A0, ecall::IO_WRITE),
+                Instruction::li(REG_A1, 0x200),
+                Instruction::li(REG_A2, 2),
+                ECALL,
+            ],
+            &[(0x100, 1), (0x101, 2), (0x102, 3), (0x200, 4), (0x201, 5)],
+            &[(REG_A0, ecall::IO_WRITE), (REG_A1, 0x100), (REG_A2, 3)],
+        );
+        assert_eq!(e.last_state.output_tape.iter().copied().collect_vec(), [
+            1, 2, 3, 4, 5
+        ]);
+        let entries = e
+            .executed
+            .iter()
+            .filter_map(|row| row.aux.storage_device_entry.as_ref())
+            .map(|entry| (entry.offset, entry.data.clone()))
+            .collect_vec();
+        assert_eq!(entries, [(0, vec![1, 2, 3]), (3, vec![4, 5])]);
+    }
+
+    #[test]
+    fn ecall_hint_read_asks_the_provider_past_the_queue() {
+        let code = [
+            Instruction::li(REG_A0, ecall::HINT_READ),
+            Instruction::li(REG_A1, 0x100),
+            Instruction::li(REG_A2, 2),
+            ECALL,
+            Instruction::li(REG_A1, 0x200),
+            Instruction::li(REG_A2, 3),
+            Instruction::li(REG_A3, 42),
+            ECALL,
+            Instruction::li(REG_A0, ecall::HALT),
+            ECALL,
+        ];
+        let ro_code = Code((0..).step_by(4).zip(code.map(Ok)).collect());
+        let rw_mem = (0x100..0x103).chain(0x200..0x203).map(|addr| (addr, 0));
+        let program = Program::create(&[], &rw_mem.collect_vec(), ro_code);
+        let state = State::new(program.clone(), RawTapes::default())
+            .with_hints([7, 8, 9])
+            .with_hint_provider(|request: &HintRequest| {
+                assert_eq!(request.len, 2);
+                vec![u8::try_from(request.query).unwrap(), 1]
+            });
+        let e = step(&program, state).unwrap();
+        let state = &e.last_state;
+        let read = |addr: u32, len: u32| (addr..addr + len).map(|a| state.load_u8(a)).collect_vec();
+        assert_eq!(read(0x100, 2), [7, 8]);
+        assert_eq!(read(0x200, 3), [9, 42, 1]);
+        let entries = e
+            .executed
+            .iter()
+            .filter_map(|row| row.aux.storage_device_entry.as_ref())
+            .map(|entry| (entry.op, entry.offset))
+            .collect_vec();
+        assert_eq!(entries, [
+            (StorageDeviceOpcode::StoreHint, 0),
+            (StorageDeviceOpcode::StoreHint, 2)
+        ]);
+    }
+
+    /// Pushes a word, grows the stack by `frame` bytes and pushes another
+    /// one, with a guard page right below a one page stack.
+    fn push_with_guard_page(frame: u32) -> Result<ExecutionRecord<GoldilocksField>> {
+        const STACK_TOP: u32 = 0x2_0000;
+        let push = Instruction::sw(5, REG_SP, 0_u32.wrapping_sub(4));
+        let code = [
+            push,
+            Instruction::addi(REG_SP, REG_SP, 0_u32.wrapping_sub(frame)),
+            push,
+            Instruction::li(REG_A0, ecall::HALT),
+            ECALL,
+        ];
+        let ro_code = Code((0..).step_by(4).zip(code.map(Ok)).collect());
+        let program = Program::create(&[], &[], ro_code)
+            .with_guard_region(STACK_TOP - 0x2000..STACK_TOP - 0x1000);
+        let state = State::new(program.clone(), RawTapes::default())
+            .set_register_value(REG_SP, STACK_TOP)
+            .set_register_value(5, 0xDEAD_BEEF);
+        step(&program, state)
+    }
+
+    #[test]
+    fn stack_within_bounds() {
+        let e = push_with_guard_page(0x800).unwrap();
+        assert_eq!(e.last_state.load_u32(0x1_F7FC), 0xDEAD_BEEF);
+    }
+
+    #[test]
+    fn stack_overflow_hits_guard_page() {
+        let err = push_with_guard_page(0x1000).unwrap_err();
+        assert!(err.to_string().contains("guard region"), "{err}");
+    }
+
+    /// Moves the break to `new_break` and stores a word right below
+    /// `addr`, with a heap from `0x1_0000` to `0x2_0000`.
+    fn store_on_heap(new_break: u32, addr: u32) -> Result<ExecutionRecord<GoldilocksField>> {
+        let code = [
+            Instruction::li(REG_A0, ecall::SBRK),
+            Instruction::li(REG_A1, new_break),
+            ECALL,
+            Instruction::li(5, addr - 4),
+            Instruction::sw(6, 5, 0),
+            Instruction::li(REG_A0, ecall::HALT),
+            ECALL,
+        ];
+        let ro_code = Code((0..).step_by(4).zip(code.map(Ok)).collect());
+        let program = Program::create(&[], &[], ro_code).with_heap(0x1_0000..0x2_0000);
+        let state =
+            State::new(program.clone(), RawTapes::default()).set_register_value(6, 0xDEAD_BEEF);
+        step(&program, state)
+    }
+
+    #[test]
+    fn heap_below_break() {
+        let e = store_on_heap(0x1_1000, 0x1_1000).unwrap();
+        assert_eq!(e.last_state.load_u32(0x1_0FFC), 0xDEAD_BEEF);
+        assert_eq!(e.last_state.unmapped_heap, Some(0x1_1000..0x2_0000));
+    }
+
+    #[test]
+    fn heap_above_break_traps() {
+        let err = store_on_heap(0x1_1000, 0x1_1004).unwrap_err();
+        assert!(err.to_string().contains("above the break"), "{err}");
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot move the break")]
+    fn break_past_heap_end() { store_on_heap(0x2_1000, 0x1_1000).unwrap(); }
+
+    /// Stores a word at `addr`, with a stack from `0x1_0000` to `0x2_0000`
+    /// and a data word at `0x100`.
+    fn store_with_stack(addr: u32) -> Result<ExecutionRecord<GoldilocksField>> {
+        let code = [
+            Instruction::li(5, addr),
+            Instruction::sw(6, 5, 0),
+            Instruction::li(REG_A0, ecall::HALT),
+            ECALL,
+        ];
+        let ro_code = Code((0..).step_by(4).zip(code.map(Ok)).collect());
+        let program = Program::create(
+            &[],
+            &[(0x100, 0), (0x101, 0), (0x102, 0), (0x103, 0)],
+            ro_code,
+        )
+        .with_stack(0x1_0000..0x2_0000);
+        let state =
+            State::new(program.clone(), RawTapes::default()).set_register_value(6, 0xDEAD_BEEF);
+        step(&program, state)
+    }
+
+    #[test]
+    fn stores_to_the_stack_and_data() {
+        let e = store_with_stack(0x1_FFFC).unwrap();
+        assert_eq!(e.last_state.load_u32(0x1_FFFC), 0xDEAD_BEEF);
+        let e = store_with_stack(0x100).unwrap();
+        assert_eq!(e.last_state.load_u32(0x100), 0xDEAD_BEEF);
+    }
+
+    #[test]
+    fn store_outside_of_the_layout_traps() {
+        let err = store_with_stack(0x2_0000).unwrap_err();
+        assert!(err.to_string().contains("unmapped memory"), "{err}");
+    }
+
+    /// Counts up `t0` in an endless loop, with a budget of five instructions.
+    fn count_out_of_gas() -> ExecutionRecord<GoldilocksField> {
+        let code = [
+            Instruction::addi(5, 5, 1),
+            Instruction::new(Op::JALR, Args::default()),
+        ];
+        let ro_code = Code((0..).step_by(4).zip(code.map(Ok)).collect());
+        let program = Program::create(&[], &[], ro_code);
+        let state = State::new(program.clone(), RawTapes::default())
+            .with_max_cycles(NonZeroU64::new(5).unwrap());
+        step(&program, state).unwrap()
+    }
+
+    #[test]
+    fn halts_out_of_gas() {
+        let e = count_out_of_gas();
+        assert_eq!(e.executed.len(), 5);
+        let state = &e.last_state;
+        assert!(state.has_halted() && state.out_of_gas);
+        assert_eq!(state.clk, 7);
+        assert_eq!(state.get_pc(), 4);
+        assert_eq!(state.get_register_value(5), 3);
+    }
+
     #[test]
     fn lui() {
         // at 0 address instruction lui
@@ -1417,6 +1485,70 @@ mod tests {
         assert_eq!(last_state.get_register_value(1) as i32, -2_147_483_644);
     }
 
+    #[test]
+    fn rdcycle() {
+        let rdcycle = Instruction::rdcycle;
+        let e = simple_test_code([rdcycle(5), Instruction::li(7, 1), rdcycle(6)], &[], &[]);
+        let first = e.executed[0].state.clk;
+        assert_eq!(u64::from(e.last_state.get_register_value(5)), first);
+        assert_eq!(u64::from(e.last_state.get_register_value(6)), first + 2);
+    }
+
+    #[test]
+    fn madd() {
+        let e = simple_test_code([Instruction::madd(5, 6, 7)], &[], &[
+            (5, 3),
+            (6, 0x8000_0001),
+            (7, 4),
+        ]);
+        assert_eq!(e.last_state.get_register_value(5), 7);
+    }
+
+    #[test]
+    fn zbb() {
+        let rop = |op, rd| {
+            Instruction::new(op, Args {
+                rd,
+                rs1: 1,
+                rs2: 2,
+                ..Args::default()
+            })
+        };
+        let e = simple_test_code(
+            [
+                rop(Op::ANDN, 3),
+                rop(Op::ORN, 4),
+                rop(Op::XNOR, 5),
+                Instruction::new(Op::CLZ, Args {
+                    rd: 6,
+                    rs1: 1,
+                    ..Args::default()
+                }),
+                Instruction::new(Op::CTZ, Args {
+                    rd: 7,
+                    rs1: 1,
+                    imm: 1,
+                    ..Args::default()
+                }),
+                Instruction::new(Op::CPOP, Args {
+                    rd: 8,
+                    rs1: 1,
+                    imm: 2,
+                    ..Args::default()
+                }),
+            ],
+            &[],
+            &[(1, 0x00F0_0F00), (2, 0x0FF0_0000)],
+        );
+        let state = e.last_state;
+        assert_eq!(state.get_register_value(3), 0x0000_0F00);
+        assert_eq!(state.get_register_value(4), 0xF0FF_FFFF);
+        assert_eq!(state.get_register_value(5), 0xF0FF_F0FF);
+        assert_eq!(state.get_register_value(6), 8);
+        assert_eq!(state.get_register_value(7), 8);
+        assert_eq!(state.get_register_value(8), 8);
+    }
+
     #[test]
     fn system_opcode_instructions() {
         let _ = simple_test(
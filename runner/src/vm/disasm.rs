@@ -0,0 +1,209 @@
+//! Disassembly of decoded instructions and of the code of whole
+//! [`Program`]s into RV32IM assembly.
+//!
+//! Our instructions are decoded ahead of time, and some of them, like `lui`
+//! and `addi`, decode to the same [`Op`]. The disassembly shows the canonical
+//! instruction of what we decoded, which may differ from the mnemonic the
+//! toolchain used for the same word. Branch and jump targets are shown
+//! relative to the pc, like Spike does.
+
+use std::fmt::Write;
+use std::ops::Range;
+
+use itertools::Itertools;
+
+use crate::debugger::{Symbols, REGISTER_NAMES};
+use crate::elf::Program;
+use crate::instruction::{Instruction, Op};
+
+fn reg(reg: u8) -> &'static str { REGISTER_NAMES[usize::from(reg)] }
+
+#[allow(clippy::cast_possible_wrap)]
+fn signed(imm: u32) -> i32 { imm as i32 }
+
+/// A branch or jump target relative to `pc`.
+fn relative(pc: u32, target: u32) -> String {
+    let offset = signed(target.wrapping_sub(pc));
+    if offset < 0 {
+        format!("pc - {}", offset.unsigned_abs())
+    } else {
+        format!("pc + {offset}")
+    }
+}
+
+/// The absolute target of a branch or of a `jal`.
+fn target(instruction: &Instruction) -> Option<u32> {
+    match instruction.op {
+        Op::BEQ | Op::BNE | Op::BLT | Op::BGE | Op::BLTU | Op::BGEU => Some(instruction.args.imm),
+        Op::JALR if instruction.args.rs1 == 0 => Some(instruction.args.imm),
+        _ => None,
+    }
+}
+
+/// Disassembles `instruction` at `pc`.
+#[must_use]
+pub fn disassemble(pc: u32, instruction: &Instruction) -> String {
+    let args = instruction.args;
+    let (rd, rs1, rs2, imm) = (reg(args.rd), reg(args.rs1), reg(args.rs2), args.imm);
+    let mnemonic = format!("{:?}", instruction.op).to_lowercase();
+    let with_imm = args.rs2 == 0 && imm != 0;
+    let (name, operands) = match instruction.op {
+        Op::ADD if with_imm && args.rs1 == 0 =>
+            ("li".to_string(), format!("{rd}, {}", signed(imm))),
+        Op::MUL if with_imm && imm.is_power_of_two() => (
+            "slli".to_string(),
+            format!("{rd}, {rs1}, {}", imm.trailing_zeros()),
+        ),
+        Op::DIVU if with_imm && imm.is_power_of_two() => (
+            "srli".to_string(),
+            format!("{rd}, {rs1}, {}", imm.trailing_zeros()),
+        ),
+        Op::SLL | Op::SRL | Op::SRA if with_imm =>
+            (format!("{mnemonic}i"), format!("{rd}, {rs1}, {imm}")),
+        Op::ADD | Op::XOR | Op::OR | Op::AND | Op::SLT if with_imm => (
+            format!("{mnemonic}i"),
+            format!("{rd}, {rs1}, {}", signed(imm)),
+        ),
+        Op::SLTU if with_imm => ("sltiu".to_string(), format!("{rd}, {rs1}, {}", signed(imm))),
+        Op::LB | Op::LH | Op::LW | Op::LBU | Op::LHU =>
+            (mnemonic, format!("{rd}, {}({rs2})", signed(imm))),
+        Op::SB | Op::SH | Op::SW => (mnemonic, format!("{rs1}, {}({rs2})", signed(imm))),
+        Op::BEQ | Op::BNE | Op::BLT | Op::BGE | Op::BLTU | Op::BGEU =>
+            (mnemonic, format!("{rs1}, {rs2}, {}", relative(pc, imm))),
+        Op::JALR if args.rs1 == 0 => ("jal".to_string(), format!("{rd}, {}", relative(pc, imm))),
+        Op::JALR => (mnemonic, format!("{rd}, {}({rs1})", signed(imm))),
+        Op::ECALL => (mnemonic, String::new()),
+        Op::RDCYCLE => (mnemonic, rd.to_string()),
+        Op::CLZ | Op::CTZ | Op::CPOP => (mnemonic, format!("{rd}, {rs1}")),
+        _ => (mnemonic, format!("{rd}, {rs1}, {rs2}")),
+    };
+    format!("{name:<8}{operands}").trim_end().to_string()
+}
+
+/// `pc` as `<symbol>` or `<symbol+offset>`, if a symbol is at or below it.
+fn symbolize(symbols: &Symbols, pc: u32) -> Option<String> {
+    symbols.locate(pc).map(|(name, offset)| match offset {
+        0 => format!("<{name}>"),
+        offset => format!("<{name}+{offset:#x}>"),
+    })
+}
+
+/// The raw instruction at `pc`, if the program carries its code in memory.
+#[must_use]
+pub fn instruction_word(program: &Program, pc: u32) -> Option<u32> {
+    let bytes = [0, 1, 2, 3].map(|i| {
+        let addr = pc.wrapping_add(i);
+        // Code of writable segments is in the read-write memory.
+        program
+            .ro_memory
+            .get(&addr)
+            .or_else(|| program.rw_memory.get(&addr))
+            .copied()
+    });
+    Some(u32::from_le_bytes([
+        bytes[0]?, bytes[1]?, bytes[2]?, bytes[3]?,
+    ]))
+}
+
+/// Disassembles the code of `program` in `range`, one instruction per line
+/// by increasing address, in the layout of `objdump -d`.
+///
+/// A line with the name of a symbol precedes the first instruction of each
+/// function, and the targets of branches and jumps are symbolized. Words
+/// that do not decode show as `.word`.
+#[must_use]
+pub fn disassemble_program(program: &Program, symbols: &Symbols, range: Range<u32>) -> String {
+    let mut out = String::new();
+    for (&pc, instruction) in program
+        .ro_code
+        .iter()
+        .filter(|(pc, _)| range.contains(pc))
+        .sorted_by_key(|(&pc, _)| pc)
+    {
+        if let Some((name, 0)) = symbols.locate(pc) {
+            if !out.is_empty() {
+                out.push('\n');
+            }
+            writeln!(out, "{pc:08x} <{name}>:").unwrap();
+        }
+        let word = match instruction {
+            Ok(_) => instruction_word(program, pc),
+            Err(error) => Some(error.instruction),
+        };
+        let word = word.map_or_else(|| " ".repeat(8), |word| format!("{word:08x}"));
+        let text = match instruction {
+            Ok(instruction) => {
+                let text = disassemble(pc, instruction);
+                match target(instruction).and_then(|target| symbolize(symbols, target)) {
+                    Some(label) => format!("{text} {label}"),
+                    None => text,
+                }
+            }
+            Err(error) => format!(".word   {:#010x}", error.instruction),
+        };
+        writeln!(out, "{pc:8x}:\t{word}\t{text}").unwrap();
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{disassemble, disassemble_program};
+    use crate::debugger::Symbols;
+    use crate::decode::ECALL;
+    use crate::elf::Program;
+    use crate::instruction::Instruction;
+
+    #[test]
+    fn instructions() {
+        assert_eq!(
+            disassemble(0, &Instruction::addi(10, 2, u32::MAX)),
+            "addi    a0, sp, -1"
+        );
+        assert_eq!(
+            disassemble(0, &Instruction::slli(5, 6, 3).unwrap()),
+            "slli    t0, t1, 3"
+        );
+        assert_eq!(
+            disassemble(0, &Instruction::sw(5, 2, 8)),
+            "sw      t0, 8(sp)"
+        );
+        assert_eq!(
+            disassemble(0x10, &Instruction::jal(1, 0x20).unwrap()),
+            "jal     ra, pc + 16"
+        );
+        assert_eq!(
+            disassemble(0x10, &Instruction::bne(5, 0, 0x8).unwrap()),
+            "bne     t0, zero, pc - 8"
+        );
+        assert_eq!(disassemble(0, &ECALL), "ecall");
+    }
+
+    #[test]
+    fn program() {
+        // addi a0, zero, 1; jal ra, 0x0; and a word that does not decode.
+        let program = Program::from(
+            [
+                (0x100, 0x0010_0513),
+                (0x104, 0xefdf_f0ef),
+                (0x108, 0xffff_ffff),
+            ]
+            .into_iter()
+            .collect::<std::collections::HashMap<u32, u32>>(),
+        );
+        let mut symbols = Symbols::default();
+        symbols.insert("_start", 0x0);
+        symbols.insert("main", 0x100);
+        assert_eq!(
+            disassemble_program(&program, &symbols, 0x100..0x10c),
+            "00000100 <main>:\n\
+             \u{20}    100:\t00100513\tli      a0, 1\n\
+             \u{20}    104:\tefdff0ef\tjal     ra, pc - 260 <_start>\n\
+             \u{20}    108:\tffffffff\t.word   0xffffffff\n"
+        );
+        assert_eq!(
+            disassemble_program(&program, &symbols, 0x104..0x108),
+            "     104:\tefdff0ef\tjal     ra, pc - 260 <_start>\n"
+        );
+    }
+}
@@ -0,0 +1,205 @@
+//! Procedural macros of the mozak SDK, re-exported by `mozak_sdk`.
+#![deny(clippy::pedantic)]
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{
+    parse_macro_input, parse_quote, Error, FnArg, Ident, ImplItem, ItemImpl, Pat, ReturnType, Type,
+    Visibility,
+};
+
+/// A public method of a contract, ie a variant of its `MethodArgs`.
+struct Method {
+    name: Ident,
+    variant: Ident,
+    args: Vec<Ident>,
+    tys: Vec<Type>,
+    output: Type,
+}
+
+/// `increase_counter` becomes `IncreaseCounter`.
+fn camel_case(name: &str) -> String {
+    name.split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect())
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+fn methods(item: &ItemImpl) -> syn::Result<Vec<Method>> {
+    item.items
+        .iter()
+        .filter_map(|item| match item {
+            ImplItem::Fn(method) if matches!(method.vis, Visibility::Public(_)) => Some(method),
+            _ => None,
+        })
+        .map(|method| {
+            let sig = &method.sig;
+            if !sig.generics.params.is_empty() {
+                return Err(Error::new_spanned(
+                    &sig.generics,
+                    "contract methods can not be generic",
+                ));
+            }
+            let (mut args, mut tys) = (Vec::new(), Vec::new());
+            for input in &sig.inputs {
+                let FnArg::Typed(arg) = input else {
+                    return Err(Error::new_spanned(input, "contract methods take no `self`"));
+                };
+                let Pat::Ident(pat) = &*arg.pat else {
+                    return Err(Error::new_spanned(
+                        &arg.pat,
+                        "arguments of contract methods must be plain identifiers",
+                    ));
+                };
+                args.push(pat.ident.clone());
+                tys.push((*arg.ty).clone());
+            }
+            let variant = format_ident!("{}", camel_case(&sig.ident.to_string()));
+            if variant == "Default" {
+                return Err(Error::new_spanned(
+                    &sig.ident,
+                    "`MethodReturns::Default` is taken by the empty return",
+                ));
+            }
+            Ok(Method {
+                name: sig.ident.clone(),
+                variant,
+                args,
+                tys,
+                output: match &sig.output {
+                    ReturnType::Default => parse_quote!(()),
+                    ReturnType::Type(_, ty) => (**ty).clone(),
+                },
+            })
+        })
+        .collect()
+}
+
+fn expand(item: &ItemImpl) -> syn::Result<TokenStream2> {
+    let methods = methods(item)?;
+    let self_ty = &item.self_ty;
+    let names: Vec<_> = methods.iter().map(|method| &method.name).collect();
+    let variants: Vec<_> = methods.iter().map(|method| &method.variant).collect();
+    let args: Vec<_> = methods.iter().map(|method| &method.args).collect();
+    let tys: Vec<_> = methods.iter().map(|method| &method.tys).collect();
+    let outputs: Vec<_> = methods.iter().map(|method| &method.output).collect();
+    let contract = quote!(#self_ty).to_string();
+    let args_doc = format!("The arguments of a call of each method of [`{contract}`].");
+    let returns_doc = format!("What a call of each method of [`{contract}`] returns.");
+    let calls_doc = format!(
+        "Calls of the methods of [`{contract}`] from other programs, which go on the call \
+         tape. Each takes the program that runs the contract first."
+    );
+    Ok(quote! {
+        #item
+
+        #[doc = #args_doc]
+        #[derive(::rkyv::Archive, ::rkyv::Deserialize, ::rkyv::Serialize, PartialEq, Clone)]
+        #[cfg_attr(not(target_os = "mozakvm"), derive(Debug))]
+        pub enum MethodArgs {
+            #(#variants(#(#tys),*),)*
+        }
+
+        #[doc = #returns_doc]
+        #[derive(
+            ::rkyv::Archive, ::rkyv::Deserialize, ::rkyv::Serialize, PartialEq, Clone, Default,
+        )]
+        #[cfg_attr(not(target_os = "mozakvm"), derive(Debug))]
+        pub enum MethodReturns {
+            #[default]
+            Default,
+            #(#variants(#outputs),)*
+        }
+
+        /// Runs the method that `args` calls.
+        #[must_use]
+        pub fn dispatch(args: MethodArgs) -> MethodReturns {
+            match args {
+                #(MethodArgs::#variants(#(#args),*) =>
+                    MethodReturns::#variants(<#self_ty>::#names(#(#args),*)),)*
+            }
+        }
+
+        /// Checks every call of this program on the call tape, by running the
+        /// method it calls and comparing what it returns. This is all the
+        /// `main` of the program has to do in mozakvm.
+        pub fn serve_calls() {
+            while let Some((_caller, args, returns)) =
+                ::mozak_sdk::call_receive::<MethodArgs, MethodReturns>()
+            {
+                assert!(dispatch(args) == returns);
+            }
+        }
+
+        #[doc = #calls_doc]
+        pub mod calls {
+            #[allow(clippy::wildcard_imports)]
+            use super::*;
+
+            #(
+                pub fn #names(
+                    program_id: ::mozak_sdk::common::types::ProgramIdentifier,
+                    #(#args: #tys),*
+                ) -> #outputs {
+                    match ::mozak_sdk::call_send(
+                        program_id,
+                        MethodArgs::#variants(#(#args),*),
+                        dispatch,
+                    ) {
+                        MethodReturns::#variants(returns) => returns,
+                        _ => unreachable!("the call returned for another method"),
+                    }
+                }
+            )*
+        }
+    })
+}
+
+/// Turns the public associated functions of an `impl` block into the methods
+/// of a contract, which other programs call via the call tape.
+///
+/// Next to the `impl` block, this generates
+/// - `MethodArgs`, with a variant per method that holds its arguments, and
+///   `MethodReturns`, with a variant per method that holds what it returns,
+/// - `dispatch`, which runs the method that a `MethodArgs` calls,
+/// - `serve_calls`, the `main` of the contract in mozakvm, which checks every
+///   call on the call tape against `dispatch`,
+/// - and a module `calls` with a function per method, which calls it on a given
+///   program via [`call_send`](../mozak_sdk/fn.call_send.html).
+///
+/// Variants are named after their methods in camel case, so
+/// `increase_counter` becomes `MethodArgs::IncreaseCounter`. As the names are
+/// fixed, a module can hold only one contract.
+///
+/// ```ignore
+/// pub struct Counter;
+///
+/// #[mozak_sdk::contract]
+/// impl Counter {
+///     pub fn increase_counter(object: StateObject) -> StateObject { .. }
+/// }
+///
+/// // Natively, in another program:
+/// let object = calls::increase_counter(counter_program, object);
+/// ```
+#[proc_macro_attribute]
+pub fn contract(attr: TokenStream, item: TokenStream) -> TokenStream {
+    if !attr.is_empty() {
+        return Error::new(
+            proc_macro2::Span::call_site(),
+            "`contract` takes no arguments",
+        )
+        .to_compile_error()
+        .into();
+    }
+    let item = parse_macro_input!(item as ItemImpl);
+    expand(&item)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
@@ -1,4 +1,6 @@
 pub mod merkle;
+pub mod prandom;
+pub mod sparse_merkle;
 pub mod system;
 pub(crate) mod traits;
 pub mod types;
@@ -0,0 +1,76 @@
+//! Deterministic randomness for guests.
+//!
+//! [`Prandom`] is a PRF over a key and a counter: the `n`th draw is the
+//! Poseidon2 hash of the key followed by `n`. Inside the VM the hash goes
+//! through the `POSEIDON2` ecall, which constrains its output to the sponge
+//! table, so a verifier can trust that every draw is exactly the PRF of the
+//! key.
+//!
+//! Key the generator with committed inputs, eg bytes read from the public
+//! tape, which the proof binds to the public tape digest. The draws are then
+//! fixed by the inputs, and no prover can pick them.
+
+use crate::common::types::Poseidon2Hash;
+use crate::core::constants::DIGEST_BYTES;
+#[cfg(target_os = "mozakvm")]
+use crate::mozakvm::poseidon::{poseidon2_hash_no_pad, poseidon2_hash_with_pad};
+#[cfg(not(target_os = "mozakvm"))]
+use crate::native::poseidon::{poseidon2_hash_no_pad, poseidon2_hash_with_pad};
+
+/// A PRF keyed by a seed, see the module docs.
+#[derive(Clone, Debug)]
+pub struct Prandom {
+    key: Poseidon2Hash,
+    counter: u64,
+}
+
+impl Prandom {
+    /// Keys the generator with the hash of `seed`.
+    #[must_use]
+    pub fn new(seed: &[u8]) -> Self {
+        Self {
+            key: poseidon2_hash_with_pad(seed),
+            counter: 0,
+        }
+    }
+
+    /// The next draw, ie the hash of the key followed by the little-endian
+    /// counter.
+    pub fn next_hash(&mut self) -> Poseidon2Hash {
+        let input: [u8; DIGEST_BYTES + 8] =
+            array_concat::concat_arrays!(self.key.inner(), self.counter.to_le_bytes());
+        self.counter += 1;
+        poseidon2_hash_no_pad(&input)
+    }
+
+    /// A random `u64`, from the first bytes of the next draw.
+    pub fn rand(&mut self) -> u64 { self.next_hash().to_u64s()[0] }
+
+    /// Fills `buf` with random bytes, taking a draw per `DIGEST_BYTES`.
+    pub fn fill_bytes(&mut self, buf: &mut [u8]) {
+        for chunk in buf.chunks_mut(DIGEST_BYTES) {
+            chunk.copy_from_slice(&self.next_hash().inner()[..chunk.len()]);
+        }
+    }
+}
+
+#[cfg(all(test, not(target_os = "mozakvm")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn draws_are_deterministic_and_distinct() {
+        let mut prandom = Prandom::new(b"public input");
+        let draws = [prandom.next_hash(), prandom.next_hash()];
+        assert_ne!(draws[0], draws[1]);
+
+        let mut again = Prandom::new(b"public input");
+        assert_eq!([again.next_hash(), again.next_hash()], draws);
+        assert_ne!(Prandom::new(b"other input").next_hash(), draws[0]);
+
+        let mut bytes = [0; DIGEST_BYTES + 3];
+        Prandom::new(b"public input").fill_bytes(&mut bytes);
+        assert_eq!(bytes[..DIGEST_BYTES], draws[0].inner());
+        assert_eq!(bytes[DIGEST_BYTES..], draws[1].inner()[..3]);
+    }
+}
@@ -0,0 +1,260 @@
+//! A sparse Merkle tree over `u64` addresses, for keeping a state root across
+//! calls.
+//!
+//! The tree has a fixed depth, and hashes every node, even the ones of empty
+//! subtrees: leaves are [`Poseidon2Hash`]es, absent leaves are zero, and each
+//! branch is the [`Poseidon2Hash::two_to_one`] of its children. The bits of
+//! an address pick the path from the root down, most significant bit first.
+//!
+//! Branches are hashed byte-wise, as that is all that guests can hash. This
+//! matches the trees that the recursive circuits of `mozak_recproofs` build
+//! with `vm_hashing`, but not their state tree, which hashes the field
+//! elements of its nodes directly.
+//!
+//! Only nodes that differ from the ones of an empty tree are stored.
+
+use std::collections::BTreeMap;
+
+use super::types::Poseidon2Hash;
+
+/// The most address bits a tree can have.
+pub const MAX_DEPTH: u32 = u64::BITS;
+
+#[derive(Clone, PartialEq, Eq)]
+#[cfg_attr(not(target_os = "mozakvm"), derive(Debug))]
+pub struct SparseMerkleTree {
+    /// Number of address bits, ie the height of the root.
+    depth: u32,
+    /// `empty[h]` is the root of an empty subtree of height `h`.
+    empty: Vec<Poseidon2Hash>,
+    /// Non-empty nodes, by height and then by index at that height.
+    nodes: BTreeMap<(u32, u64), Poseidon2Hash>,
+}
+
+/// The siblings of the nodes on the path from a leaf to the root, which
+/// proves the leaf against a root.
+#[derive(Clone, PartialEq, Eq, rkyv::Archive, rkyv::Serialize, rkyv::Deserialize)]
+#[cfg_attr(
+    not(target_os = "mozakvm"),
+    derive(Debug, serde::Serialize, serde::Deserialize)
+)]
+pub struct MerkleProof {
+    pub addr: u64,
+    /// Siblings from the leaf up.
+    pub siblings: Vec<Poseidon2Hash>,
+}
+
+impl MerkleProof {
+    /// The root of the tree with `leaf` at `self.addr`.
+    #[must_use]
+    pub fn root(&self, leaf: Poseidon2Hash) -> Poseidon2Hash {
+        self.siblings
+            .iter()
+            .zip(0..)
+            .fold(leaf, |node, (&sibling, height)| {
+                if self.addr.checked_shr(height).unwrap_or(0) & 1 == 0 {
+                    Poseidon2Hash::two_to_one(node, sibling)
+                } else {
+                    Poseidon2Hash::two_to_one(sibling, node)
+                }
+            })
+    }
+
+    /// Whether the tree with `root` has `leaf` at `self.addr`.
+    #[must_use]
+    pub fn verify(&self, root: Poseidon2Hash, leaf: Poseidon2Hash) -> bool {
+        self.root(leaf) == root
+    }
+
+    /// Replaces `old` by `new` at `self.addr` in the tree with `root`, and
+    /// returns the new root. Returns `None` if the tree does not have `old`
+    /// there.
+    ///
+    /// This lets a guest that only keeps the root update it, with the proof
+    /// from whoever keeps the tree.
+    #[must_use]
+    pub fn update(
+        &self,
+        root: Poseidon2Hash,
+        old: Poseidon2Hash,
+        new: Poseidon2Hash,
+    ) -> Option<Poseidon2Hash> {
+        self.verify(root, old).then(|| self.root(new))
+    }
+}
+
+impl SparseMerkleTree {
+    /// An empty tree with `depth` address bits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `depth` exceeds [`MAX_DEPTH`].
+    #[must_use]
+    pub fn new(depth: u32) -> Self {
+        assert!(
+            depth <= MAX_DEPTH,
+            "trees have at most {MAX_DEPTH} address bits"
+        );
+        let empty = std::iter::successors(Some(Poseidon2Hash::default()), |&node| {
+            Some(Poseidon2Hash::two_to_one(node, node))
+        })
+        .take(depth as usize + 1)
+        .collect();
+        Self {
+            depth,
+            empty,
+            nodes: BTreeMap::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn depth(&self) -> u32 { self.depth }
+
+    #[must_use]
+    pub fn root(&self) -> Poseidon2Hash { self.node(self.depth, 0) }
+
+    /// The leaf at `addr`, zero if absent.
+    #[must_use]
+    pub fn get(&self, addr: u64) -> Poseidon2Hash {
+        self.check_addr(addr);
+        self.node(0, addr)
+    }
+
+    /// Proves the leaf at `addr`.
+    #[must_use]
+    pub fn prove(&self, addr: u64) -> MerkleProof {
+        self.check_addr(addr);
+        MerkleProof {
+            addr,
+            siblings: (0..self.depth)
+                .map(|height| self.node(height, (addr >> height) ^ 1))
+                .collect(),
+        }
+    }
+
+    /// Sets the leaf at `addr`, and returns the previous one.
+    pub fn update(&mut self, addr: u64, leaf: Poseidon2Hash) -> Poseidon2Hash {
+        let old = self.get(addr);
+        self.update_batch([(addr, leaf)]);
+        old
+    }
+
+    /// Sets several leaves at once, hashing each branch above them only once.
+    /// Later updates of the same address win.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an address does not fit into the depth of the tree.
+    pub fn update_batch(&mut self, updates: impl IntoIterator<Item = (u64, Poseidon2Hash)>) {
+        let mut touched: Vec<u64> = updates
+            .into_iter()
+            .map(|(addr, leaf)| {
+                self.check_addr(addr);
+                self.set_node(0, addr, leaf);
+                addr
+            })
+            .collect();
+        for height in 1..=self.depth {
+            touched = touched.into_iter().map(|index| index >> 1).collect();
+            touched.sort_unstable();
+            touched.dedup();
+            for &index in &touched {
+                let node = Poseidon2Hash::two_to_one(
+                    self.node(height - 1, index << 1),
+                    self.node(height - 1, (index << 1) | 1),
+                );
+                self.set_node(height, index, node);
+            }
+        }
+    }
+
+    fn node(&self, height: u32, index: u64) -> Poseidon2Hash {
+        self.nodes
+            .get(&(height, index))
+            .copied()
+            .unwrap_or(self.empty[height as usize])
+    }
+
+    fn set_node(&mut self, height: u32, index: u64, node: Poseidon2Hash) {
+        if node == self.empty[height as usize] {
+            self.nodes.remove(&(height, index));
+        } else {
+            self.nodes.insert((height, index), node);
+        }
+    }
+
+    fn check_addr(&self, addr: u64) {
+        assert!(
+            self.depth == MAX_DEPTH || addr >> self.depth == 0,
+            "address {addr:#x} does not fit into {} bits",
+            self.depth
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SparseMerkleTree;
+    use crate::common::types::Poseidon2Hash;
+
+    fn leaf(byte: u8) -> Poseidon2Hash { Poseidon2Hash([byte; 32]) }
+
+    #[test]
+    fn matches_a_dense_tree() {
+        let mut tree = SparseMerkleTree::new(2);
+        tree.update(1, leaf(1));
+        tree.update(2, leaf(2));
+        let zero = Poseidon2Hash::default();
+        let root = Poseidon2Hash::two_to_one(
+            Poseidon2Hash::two_to_one(zero, leaf(1)),
+            Poseidon2Hash::two_to_one(leaf(2), zero),
+        );
+        assert_eq!(tree.root(), root);
+        assert_eq!(tree.get(2), leaf(2));
+        assert_eq!(tree.get(3), zero);
+    }
+
+    #[test]
+    fn clearing_leaves_restores_the_empty_root() {
+        let mut tree = SparseMerkleTree::new(16);
+        let empty_root = tree.root();
+        assert_eq!(tree.update(0x1234, leaf(7)), Poseidon2Hash::default());
+        assert_ne!(tree.root(), empty_root);
+        assert_eq!(tree.update(0x1234, Poseidon2Hash::default()), leaf(7));
+        assert_eq!(tree.root(), empty_root);
+        assert_eq!(tree, SparseMerkleTree::new(16));
+    }
+
+    #[test]
+    fn proofs_update_roots() {
+        let mut tree = SparseMerkleTree::new(64);
+        tree.update(u64::MAX, leaf(1));
+        tree.update(42, leaf(2));
+        let root = tree.root();
+        let proof = tree.prove(42);
+        assert!(proof.verify(root, leaf(2)));
+        assert!(!proof.verify(root, leaf(3)));
+        assert_eq!(proof.update(root, leaf(3), leaf(4)), None);
+
+        let new_root = proof.update(root, leaf(2), leaf(4)).unwrap();
+        tree.update(42, leaf(4));
+        assert_eq!(tree.root(), new_root);
+    }
+
+    #[test]
+    fn batches_match_single_updates() {
+        let updates = [(3, leaf(1)), (900, leaf(2)), (3, leaf(3)), (901, leaf(4))];
+        let mut batched = SparseMerkleTree::new(10);
+        batched.update_batch(updates);
+        let mut single = SparseMerkleTree::new(10);
+        for (addr, leaf) in updates {
+            single.update(addr, leaf);
+        }
+        assert_eq!(batched, single);
+        assert_eq!(batched.get(3), leaf(3));
+    }
+
+    #[test]
+    #[should_panic(expected = "does not fit")]
+    fn addresses_fit_the_depth() { let _ = SparseMerkleTree::new(8).get(256); }
+}
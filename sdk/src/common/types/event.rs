@@ -1,5 +1,5 @@
 use crate::common::types::state_address::STATE_TREE_DEPTH;
-use crate::core::constants::DIGEST_BYTES;
+use crate::core::constants::{DIGEST_BYTES, EVENT_BYTES};
 #[cfg(target_os = "mozakvm")]
 use crate::mozakvm::poseidon::poseidon2_hash_no_pad;
 #[cfg(not(target_os = "mozakvm"))]
@@ -86,6 +86,13 @@ impl CanonicalEvent {
         }
     }
 
+    /// The event as emitted with the `EMIT_EVENT` ecall: the address, the
+    /// event type, and the hash of the object's data.
+    #[must_use]
+    pub fn to_bytes(&self) -> [u8; EVENT_BYTES] {
+        array_concat::concat_arrays!(self.address.inner(), [self.type_ as u8], self.value.inner())
+    }
+
     #[must_use]
     pub fn canonical_hash(&self) -> super::poseidon2hash::Poseidon2Hash {
         const U64_LEN: usize = 0u64.to_be_bytes().len();
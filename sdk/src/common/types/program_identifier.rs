@@ -19,6 +19,12 @@ use crate::core::constants::DIGEST_BYTES;
     derive(serde::Serialize, serde::Deserialize)
 )]
 #[archive_attr(derive(Debug))]
+/// The Poseidon2 hash that identifies a program.
+///
+/// Its canonical encoding is the [`DIGEST_BYTES`] bytes of the hash, which are
+/// the four elements of the hash as little-endian `u64`s. Natively computed
+/// and in-circuit program ids must both go through [`Self::from_u64s`] and
+/// [`Self::inner`], so that they agree.
 pub struct ProgramIdentifier(pub super::Poseidon2Hash);
 
 impl ProgramIdentifier {
@@ -46,6 +52,16 @@ impl ProgramIdentifier {
         });
     }
 
+    /// The program id with the elements of its hash in canonical form.
+    #[must_use]
+    pub const fn from_u64s(elements: [u64; 4]) -> Self {
+        Self(super::Poseidon2Hash::from_u64s(elements))
+    }
+
+    /// The elements of the hash of the program id.
+    #[must_use]
+    pub const fn to_u64s(&self) -> [u64; 4] { self.0.to_u64s() }
+
     #[must_use]
     pub fn inner(&self) -> [u8; DIGEST_BYTES] {
         let mut le_bytes_array: [u8; DIGEST_BYTES] = [0; DIGEST_BYTES];
@@ -75,6 +91,10 @@ impl std::fmt::Debug for ProgramIdentifier {
     }
 }
 
+impl From<[u8; DIGEST_BYTES]> for ProgramIdentifier {
+    fn from(value: [u8; DIGEST_BYTES]) -> Self { Self(value.into()) }
+}
+
 #[cfg(not(target_os = "mozakvm"))]
 impl From<String> for ProgramIdentifier {
     fn from(value: String) -> ProgramIdentifier {
@@ -87,3 +107,19 @@ impl From<String> for ProgramIdentifier {
         ))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::ProgramIdentifier;
+
+    #[test]
+    fn canonical_encoding_is_little_endian_u64s() {
+        let elements = [1, 2 << 8, u64::MAX, 0x0123_4567_89ab_cdef];
+        let id = ProgramIdentifier::from_u64s(elements);
+        assert_eq!(id.to_u64s(), elements);
+
+        let bytes: Vec<u8> = elements.into_iter().flat_map(u64::to_le_bytes).collect();
+        assert_eq!(id.inner().as_slice(), bytes.as_slice());
+        assert_eq!(ProgramIdentifier::from(id.inner()), id);
+    }
+}
@@ -1,3 +1,7 @@
+/// Granularity in which the heap grows, see
+/// [`SBRK`](crate::core::ecall::SBRK).
+const PAGE_SIZE: usize = 4096;
+
 #[no_mangle]
 #[allow(clippy::module_name_repetitions)]
 #[allow(clippy::borrow_as_ptr)]
@@ -15,6 +19,8 @@ pub extern "C" fn alloc_aligned(bytes: usize, align: usize) -> *mut u8 {
     // Pointer to next heap address to use, or 0 if the heap has not been
     // initialized.
     static mut HEAP_POS: usize = 0;
+    // The program break, below which the heap is mapped.
+    static mut HEAP_BREAK: usize = 0;
 
     // SAFETY: Single threaded, so nothing else can touch this while we're working.
     let mut heap_pos = unsafe { HEAP_POS };
@@ -28,10 +34,30 @@ pub extern "C" fn alloc_aligned(bytes: usize, align: usize) -> *mut u8 {
         heap_pos += align - offset;
     }
 
+    // The heap grows upwards towards the stack, which grows downwards. Running
+    // into the stack is out of memory, which makes the caller panic.
+    let stack_pointer: usize;
+    unsafe { core::arch::asm!("mv {0}, sp", out(reg) stack_pointer) };
     let ptr = heap_pos as *mut u8;
-    heap_pos += bytes;
+    match heap_pos.checked_add(bytes) {
+        Some(end) if end <= stack_pointer => heap_pos = end,
+        _ => return core::ptr::null_mut(),
+    }
 
-    unsafe { HEAP_POS = heap_pos };
+    // Map the heap a page at a time, so that most allocations do not need to
+    // move the break.
+    let mut heap_break = unsafe { HEAP_BREAK };
+    if heap_pos > heap_break {
+        heap_break = heap_pos
+            .checked_next_multiple_of(PAGE_SIZE)
+            .map_or(heap_pos, |page_end| page_end.min(stack_pointer));
+        crate::core::ecall::sbrk(heap_break);
+    }
+
+    unsafe {
+        HEAP_POS = heap_pos;
+        HEAP_BREAK = heap_break;
+    }
     ptr
 }
 
@@ -2,7 +2,9 @@
 use core::arch::asm;
 
 #[cfg(target_os = "mozakvm")]
-use crate::core::constants::DIGEST_BYTES;
+use crate::core::constants::{
+    DIGEST_BYTES, EVENT_BYTES, GOLDILOCKS_BYTES, SECP256K1_POINT_BYTES, U256_BYTES,
+};
 
 pub const HALT: u32 = 0;
 pub const PANIC: u32 = 1;
@@ -16,6 +18,66 @@ pub const CAST_LIST_COMMITMENT_TAPE: u32 = 8;
 pub const SELF_PROG_ID_TAPE: u32 = 9;
 /// Syscall to output the VM trace log at `clk`. Useful for debugging.
 pub const VM_TRACE_LOG: u32 = 10;
+/// Syscall to halt with a trap code and a structured
+/// [`TrapReport`](crate::core::trap::TrapReport), raised by failing guest
+/// assertions.
+pub const TRAP: u32 = 11;
+/// Syscall to read from the public tape starting at a given offset, rather
+/// than at the current read position. Subsequent `PUBLIC_TAPE` reads continue
+/// from where this one stopped, so a zero-length read acts as a seek.
+pub const PUBLIC_TAPE_READ_AT: u32 = 12;
+/// Syscall asserting that `a1` is one. Unlike [`TRAP`], a failure does not
+/// stop the VM: the CPU table constrains the condition, so the execution
+/// simply cannot be proven.
+pub const PROVE_ASSERT: u32 = 13;
+/// Syscall adding the secp256k1 point at `a2` to the one at `a1`, in place.
+/// The points must have different `x` coordinates.
+pub const SECP256K1_ADD: u32 = 14;
+/// Syscall doubling the secp256k1 point at `a1`, in place. The point must
+/// have a non-zero `y` coordinate.
+pub const SECP256K1_DOUBLE: u32 = 15;
+/// Syscall emitting the event of `a2` bytes at `a1`. Events are emitted in
+/// canonical order, by the address of their object and then by event type, so
+/// that the event emission table can check that order.
+pub const EMIT_EVENT: u32 = 16;
+/// Syscall adding the 256-bit integer at `a2` to the one at `a1`, in place,
+/// modulo `2^256`.
+pub const U256_ADD: u32 = 17;
+/// Syscall multiplying the 256-bit integer at `a1` by the one at `a2`, in
+/// place, modulo `2^256`.
+pub const U256_MUL: u32 = 18;
+/// Syscall multiplying the 256-bit integer at `a1` by the one at `a2`, in
+/// place, modulo the one at `a3`. The modulus must not be zero.
+pub const U256_MULMOD: u32 = 19;
+/// Syscall copying `a3` bytes from `a2` to `a1`. All bytes are read before any
+/// is written, so overlapping ranges are copied like `memmove` does. Neither
+/// range may wrap around the end of the address space.
+pub const MEMCOPY: u32 = 20;
+/// Syscall setting `a3` bytes at `a1` to the byte in `a2`. The range may not
+/// wrap around the end of the address space.
+pub const MEMSET: u32 = 21;
+/// Syscall moving the program break, ie the end of the guest heap, up to the
+/// address in `a1`. Memory between the old and the new break reads as zero.
+/// The break never moves down, and not past the end of the heap region of the
+/// program.
+pub const SBRK: u32 = 22;
+/// Syscall adding the Goldilocks field element at `a2` to the one at `a1`, in
+/// place. Both must be canonical, ie below the field order.
+pub const GOLDILOCKS_ADD: u32 = 23;
+/// Syscall multiplying the Goldilocks field element at `a1` by the one at
+/// `a2`, in place. Both must be canonical.
+pub const GOLDILOCKS_MUL: u32 = 24;
+/// Syscall inverting the Goldilocks field element at `a1`, in place. It must be
+/// canonical and not zero.
+pub const GOLDILOCKS_INVERSE: u32 = 25;
+/// Syscall appending the `a2` bytes at `a1` to the output tape. The prover
+/// makes the Poseidon2 digest of the whole output tape public.
+pub const IO_WRITE: u32 = 26;
+/// Syscall reading `a2` bytes of hints from the host into `a1`, with `a3`
+/// passed on to the host as a query. Hints are neither public nor committed to
+/// by the proof, so they are only as good as the checks the guest does on
+/// them, see [`hint`](crate::core::hint).
+pub const HINT_READ: u32 = 27;
 
 #[must_use]
 pub fn log<'a>(raw_id: u32) -> &'a str {
@@ -31,12 +93,44 @@ pub fn log<'a>(raw_id: u32) -> &'a str {
         CAST_LIST_COMMITMENT_TAPE => "ioread cast list commitment tape",
         SELF_PROG_ID_TAPE => "self prog id tape",
         VM_TRACE_LOG => "vm trace log",
+        TRAP => "trap",
+        PUBLIC_TAPE_READ_AT => "ioread public tape at",
+        PROVE_ASSERT => "prove assert",
+        SECP256K1_ADD => "secp256k1 add",
+        SECP256K1_DOUBLE => "secp256k1 double",
+        EMIT_EVENT => "emit event",
+        U256_ADD => "u256 add",
+        U256_MUL => "u256 mul",
+        U256_MULMOD => "u256 mulmod",
+        MEMCOPY => "memcopy",
+        MEMSET => "memset",
+        SBRK => "sbrk",
+        GOLDILOCKS_ADD => "goldilocks add",
+        GOLDILOCKS_MUL => "goldilocks mul",
+        GOLDILOCKS_INVERSE => "goldilocks inverse",
+        IO_WRITE => "iowrite output tape",
+        HINT_READ => "hint read",
         _ => "",
     }
 }
 
 #[cfg(target_os = "mozakvm")]
 pub fn poseidon2(input_ptr: *const u8, input_len: usize, output_ptr: *mut u8) {
+    poseidon2_xof(input_ptr, input_len, output_ptr, DIGEST_BYTES);
+}
+
+/// Hashes `input_len` bytes at `input_ptr`, and writes `output_len` bytes of
+/// output to `output_ptr`. The first [`DIGEST_BYTES`] are the plain hash, and
+/// each further [`DIGEST_BYTES`] take one more permutation of the sponge.
+///
+/// `output_len` must be a positive multiple of [`DIGEST_BYTES`].
+#[cfg(target_os = "mozakvm")]
+pub fn poseidon2_xof(
+    input_ptr: *const u8,
+    input_len: usize,
+    output_ptr: *mut u8,
+    output_len: usize,
+) {
     unsafe {
         core::arch::asm!(
             "ecall",
@@ -44,6 +138,7 @@ pub fn poseidon2(input_ptr: *const u8, input_len: usize, output_ptr: *mut u8) {
             in ("a1") input_ptr,
             in ("a2") input_len,
             in ("a3") output_ptr,
+            in ("a4") output_len,
         );
     }
 }
@@ -72,6 +167,52 @@ pub fn ioread_public(buf: &mut [u8]) {
     }
 }
 
+/// Reads `buf.len()` bytes of the public tape, starting at `offset`.
+#[cfg(target_os = "mozakvm")]
+pub fn ioread_public_at(offset: usize, buf: &mut [u8]) {
+    unsafe {
+        core::arch::asm!(
+        "ecall",
+        in ("a0") PUBLIC_TAPE_READ_AT,
+        in ("a1") buf.as_mut_ptr(),
+        in ("a2") buf.len(),
+        in ("a3") offset,
+        );
+    }
+}
+
+/// Moves the read position of the public tape to `offset`.
+#[cfg(target_os = "mozakvm")]
+pub fn ioseek_public(offset: usize) { ioread_public_at(offset, &mut []); }
+
+/// Appends `buf` to the output tape.
+#[cfg(target_os = "mozakvm")]
+pub fn io_write(buf: &[u8]) {
+    unsafe {
+        core::arch::asm!(
+        "ecall",
+        in ("a0") IO_WRITE,
+        in ("a1") buf.as_ptr(),
+        in ("a2") buf.len(),
+        );
+    }
+}
+
+/// Fills `buf` with hints from the host, which gets `query` to tell what the
+/// guest asks for.
+#[cfg(target_os = "mozakvm")]
+pub fn hint_read(query: u32, buf: &mut [u8]) {
+    unsafe {
+        core::arch::asm!(
+        "ecall",
+        in ("a0") HINT_READ,
+        in ("a1") buf.as_mut_ptr(),
+        in ("a2") buf.len(),
+        in ("a3") query,
+        );
+    }
+}
+
 #[cfg(target_os = "mozakvm")]
 pub fn call_tape_read(buf: &mut [u8]) {
     unsafe {
@@ -147,6 +288,175 @@ pub fn panic(msg: &str) {
     }
 }
 
+#[cfg(target_os = "mozakvm")]
+pub fn trap(code: u32, report: &[u8]) {
+    unsafe {
+        core::arch::asm!(
+            "ecall",
+            in ("a0") TRAP,
+            in ("a1") code,
+            in ("a2") report.as_ptr(),
+            in ("a3") report.len(),
+        );
+    }
+}
+
+#[cfg(target_os = "mozakvm")]
+pub fn prove_assert(cond: bool) {
+    unsafe {
+        core::arch::asm!(
+            "ecall",
+            in ("a0") PROVE_ASSERT,
+            in ("a1") u32::from(cond),
+        );
+    }
+}
+
+#[cfg(target_os = "mozakvm")]
+pub fn secp256k1_add(p: &mut [u8; SECP256K1_POINT_BYTES], q: &[u8; SECP256K1_POINT_BYTES]) {
+    unsafe {
+        core::arch::asm!(
+            "ecall",
+            in ("a0") SECP256K1_ADD,
+            in ("a1") p.as_mut_ptr(),
+            in ("a2") q.as_ptr(),
+        );
+    }
+}
+
+#[cfg(target_os = "mozakvm")]
+pub fn secp256k1_double(p: &mut [u8; SECP256K1_POINT_BYTES]) {
+    unsafe {
+        core::arch::asm!(
+            "ecall",
+            in ("a0") SECP256K1_DOUBLE,
+            in ("a1") p.as_mut_ptr(),
+        );
+    }
+}
+
+/// Emits a canonical event, as laid out by `CanonicalEvent::to_bytes`.
+#[cfg(target_os = "mozakvm")]
+pub fn emit_event(event: &[u8; EVENT_BYTES]) {
+    unsafe {
+        core::arch::asm!(
+            "ecall",
+            in ("a0") EMIT_EVENT,
+            in ("a1") event.as_ptr(),
+            in ("a2") event.len(),
+        );
+    }
+}
+
+#[cfg(target_os = "mozakvm")]
+pub fn u256_add(a: &mut [u8; U256_BYTES], b: &[u8; U256_BYTES]) {
+    unsafe {
+        core::arch::asm!(
+            "ecall",
+            in ("a0") U256_ADD,
+            in ("a1") a.as_mut_ptr(),
+            in ("a2") b.as_ptr(),
+        );
+    }
+}
+
+#[cfg(target_os = "mozakvm")]
+pub fn u256_mul(a: &mut [u8; U256_BYTES], b: &[u8; U256_BYTES]) {
+    unsafe {
+        core::arch::asm!(
+            "ecall",
+            in ("a0") U256_MUL,
+            in ("a1") a.as_mut_ptr(),
+            in ("a2") b.as_ptr(),
+        );
+    }
+}
+
+#[cfg(target_os = "mozakvm")]
+pub fn u256_mulmod(a: &mut [u8; U256_BYTES], b: &[u8; U256_BYTES], m: &[u8; U256_BYTES]) {
+    unsafe {
+        core::arch::asm!(
+            "ecall",
+            in ("a0") U256_MULMOD,
+            in ("a1") a.as_mut_ptr(),
+            in ("a2") b.as_ptr(),
+            in ("a3") m.as_ptr(),
+        );
+    }
+}
+
+#[cfg(target_os = "mozakvm")]
+pub fn goldilocks_add(a: &mut [u8; GOLDILOCKS_BYTES], b: &[u8; GOLDILOCKS_BYTES]) {
+    unsafe {
+        core::arch::asm!(
+            "ecall",
+            in ("a0") GOLDILOCKS_ADD,
+            in ("a1") a.as_mut_ptr(),
+            in ("a2") b.as_ptr(),
+        );
+    }
+}
+
+#[cfg(target_os = "mozakvm")]
+pub fn goldilocks_mul(a: &mut [u8; GOLDILOCKS_BYTES], b: &[u8; GOLDILOCKS_BYTES]) {
+    unsafe {
+        core::arch::asm!(
+            "ecall",
+            in ("a0") GOLDILOCKS_MUL,
+            in ("a1") a.as_mut_ptr(),
+            in ("a2") b.as_ptr(),
+        );
+    }
+}
+
+#[cfg(target_os = "mozakvm")]
+pub fn goldilocks_inverse(a: &mut [u8; GOLDILOCKS_BYTES]) {
+    unsafe {
+        core::arch::asm!(
+            "ecall",
+            in ("a0") GOLDILOCKS_INVERSE,
+            in ("a1") a.as_mut_ptr(),
+        );
+    }
+}
+
+#[cfg(target_os = "mozakvm")]
+pub fn memcopy(dst: *mut u8, src: *const u8, len: usize) {
+    unsafe {
+        core::arch::asm!(
+            "ecall",
+            in ("a0") MEMCOPY,
+            in ("a1") dst,
+            in ("a2") src,
+            in ("a3") len,
+        );
+    }
+}
+
+#[cfg(target_os = "mozakvm")]
+pub fn memset(dst: *mut u8, value: u8, len: usize) {
+    unsafe {
+        core::arch::asm!(
+            "ecall",
+            in ("a0") MEMSET,
+            in ("a1") dst,
+            in ("a2") u32::from(value),
+            in ("a3") len,
+        );
+    }
+}
+
+#[cfg(target_os = "mozakvm")]
+pub fn sbrk(new_break: usize) {
+    unsafe {
+        core::arch::asm!(
+            "ecall",
+            in ("a0") SBRK,
+            in ("a1") new_break,
+        );
+    }
+}
+
 #[cfg(all(target_os = "mozakvm", feature = "trace"))]
 pub fn trace(msg: &str) {
     unsafe {
@@ -159,6 +469,8 @@ pub fn trace(msg: &str) {
     }
 }
 
+/// Halts the VM with `output` as its exit code, passed in `a1`. Proofs make
+/// the exit code public.
 #[cfg(target_os = "mozakvm")]
 pub fn halt(output: u8) {
     unsafe {
@@ -17,13 +17,41 @@ pub fn finalize() {
     }
 }
 
+/// Appends `output_data` to the output tape, see
+/// [`IO_WRITE`](super::ecall::IO_WRITE). The first byte ever written is also
+/// the exit code of the guest.
 #[allow(dead_code)]
 pub fn write(output_data: &[u8]) {
     #[cfg(target_os = "mozakvm")]
     unsafe {
         OUTPUT_BYTES.extend_from_slice(output_data);
+        super::ecall::io_write(output_data);
     }
 
     #[cfg(not(target_os = "mozakvm"))]
     core::hint::black_box(output_data);
 }
+
+/// Reads the cycle counter of the VM, ie the clock of the current
+/// instruction, as `rdcycle` would.
+///
+/// Useful for poor-man's benchmarks of guest code. Natively, there is no
+/// cycle counter, so this always returns 0.
+#[must_use]
+pub fn cycle_count() -> u32 {
+    #[cfg(target_os = "mozakvm")]
+    {
+        let cycles: u32;
+        // This is `rdcycle`, ie `csrrs rd, cycle, x0`, spelled out, because our
+        // target does not enable the `Zicsr` extension.
+        unsafe {
+            core::arch::asm!(
+                ".insn i 0x73, 2, {0}, x0, -1024",
+                out(reg) cycles,
+            );
+        }
+        cycles
+    }
+    #[cfg(not(target_os = "mozakvm"))]
+    0
+}
@@ -0,0 +1,87 @@
+//! Untrusted advice from the host.
+//!
+//! Hints let the host do the expensive part of a computation, like a
+//! division, a square root or a search, and hand the guest just the answer.
+//! Neither the hints nor a digest of them make it into the public inputs, so a
+//! proof holds for whatever hints the prover picked. Each hint thus comes
+//! wrapped in a [`Hint`], which only gives up its value once the guest checked
+//! it:
+//!
+//! ```ignore
+//! let root = mozak_sdk::core::hint::read_array::<4>()
+//!     .map(u32::from_le_bytes)
+//!     .verify(|&root| root.checked_mul(root) == Some(square));
+//! ```
+
+use rust_alloc::vec;
+use rust_alloc::vec::Vec;
+
+/// A value from the host that the guest has not checked yet.
+#[must_use = "hints are untrusted until verified"]
+pub struct Hint<T>(T);
+
+impl<T> Hint<T> {
+    /// Returns the hint, provided `is_valid` accepts it.
+    ///
+    /// Inside the VM, the check is a provable assertion: a rejected hint does
+    /// not stop the guest, but leaves no valid proof of its execution.
+    /// Natively, a rejected hint panics.
+    pub fn verify(self, is_valid: impl FnOnce(&T) -> bool) -> T {
+        let valid = is_valid(&self.0);
+
+        #[cfg(target_os = "mozakvm")]
+        crate::core::ecall::prove_assert(valid);
+
+        #[cfg(not(target_os = "mozakvm"))]
+        assert!(valid, "the guest rejected a hint");
+
+        self.0
+    }
+
+    /// Decodes the hint, which stays unchecked.
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Hint<U> { Hint(f(self.0)) }
+}
+
+/// Fills `buf` with hints. Hints the host does not have read as zero.
+///
+/// Natively, there is no host, so every hint reads as zero.
+fn fill(query: u32, buf: &mut [u8]) {
+    #[cfg(target_os = "mozakvm")]
+    crate::core::ecall::hint_read(query, buf);
+
+    #[cfg(not(target_os = "mozakvm"))]
+    core::hint::black_box((query, buf));
+}
+
+/// Reads `len` bytes of hints.
+pub fn read(len: usize) -> Hint<Vec<u8>> { read_for(0, len) }
+
+/// Reads `len` bytes of hints, passing `query` on to the `HintProvider` of
+/// the host, eg the address of the question in guest memory.
+pub fn read_for(query: u32, len: usize) -> Hint<Vec<u8>> {
+    let mut buf = vec![0; len];
+    fill(query, &mut buf);
+    Hint(buf)
+}
+
+/// Reads `N` bytes of hints.
+pub fn read_array<const N: usize>() -> Hint<[u8; N]> {
+    let mut buf = [0; N];
+    fill(0, &mut buf);
+    Hint(buf)
+}
+
+#[cfg(all(test, not(target_os = "mozakvm")))]
+mod tests {
+    use super::read_array;
+
+    #[test]
+    fn verified_hints_pass_through() {
+        let hint = read_array::<4>().map(u32::from_le_bytes);
+        assert_eq!(hint.verify(|&n| n == 0), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "the guest rejected a hint")]
+    fn rejected_hints_panic() { let _ = super::read(3).verify(|bytes| bytes.len() == 4); }
+}
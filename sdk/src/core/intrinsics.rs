@@ -0,0 +1,43 @@
+//! Wrappers for Mozak's custom instructions.
+//!
+//! The instructions live in the custom-0 opcode space, which standard RISC-V
+//! cores do not implement. They are only emitted with the `madd` feature, so
+//! that guests built without it stay plain RV32IM.
+
+/// Computes `a * b + c`, keeping the lower 32 bits, like
+/// `a.wrapping_mul(b).wrapping_add(c)`.
+///
+/// With the `madd` feature, this is a single MADD instruction on the Mozak
+/// VM, which costs one CPU row instead of two.
+#[must_use]
+#[inline]
+pub fn madd(a: u32, b: u32, c: u32) -> u32 {
+    #[cfg(all(feature = "madd", target_os = "mozakvm"))]
+    {
+        let mut acc = c;
+        // `rd = rs1 * rs2 + rd`, encoded as an R-type instruction in custom-0.
+        unsafe {
+            core::arch::asm!(
+                ".insn r 0x0b, 0, 0, {0}, {1}, {2}",
+                inout(reg) acc,
+                in(reg) a,
+                in(reg) b,
+                options(pure, nomem, nostack),
+            );
+        }
+        acc
+    }
+    #[cfg(not(all(feature = "madd", target_os = "mozakvm")))]
+    a.wrapping_mul(b).wrapping_add(c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::madd;
+
+    #[test]
+    fn madd_wraps() {
+        assert_eq!(madd(0x8000_0001, 4, 3), 7);
+        assert_eq!(madd(u32::MAX, u32::MAX, u32::MAX), 0);
+    }
+}
@@ -0,0 +1,84 @@
+//! Byte-level input and output for guests, via [`io_read!`](crate::io_read)
+//! and [`io_write!`](crate::io_write).
+//!
+//! A complete guest then reads like
+//!
+//! ```ignore
+//! #![cfg_attr(target_os = "mozakvm", no_main)]
+//! #![cfg_attr(not(feature = "std"), no_std)]
+//! #![cfg_attr(feature = "std", feature(restricted_std))]
+//!
+//! fn main() {
+//!     let n = mozak_sdk::io_read!(public: u32);
+//!     mozak_sdk::io_write!((n * 2).to_le_bytes());
+//! }
+//!
+//! mozak_sdk::entry!(main);
+//! ```
+//!
+//! The tapes are read sequentially, so mixing these macros with the buffered
+//! `mozak_sdk::read` makes each of them see only part of a tape.
+
+/// Fills `buf` from the private tape.
+///
+/// Natively, there is no tape, so this leaves `buf` untouched.
+pub fn read_private(buf: &mut [u8]) {
+    #[cfg(target_os = "mozakvm")]
+    crate::core::ecall::ioread_private(buf);
+
+    #[cfg(not(target_os = "mozakvm"))]
+    core::hint::black_box(buf);
+}
+
+/// Fills `buf` from the public tape.
+///
+/// Natively, there is no tape, so this leaves `buf` untouched.
+pub fn read_public(buf: &mut [u8]) {
+    #[cfg(target_os = "mozakvm")]
+    crate::core::ecall::ioread_public(buf);
+
+    #[cfg(not(target_os = "mozakvm"))]
+    core::hint::black_box(buf);
+}
+
+/// Reads from the `private` or `public` tape.
+///
+/// - `io_read!(private, buf)` fills the byte slice `buf`.
+/// - `io_read!(private: u32)` reads a little-endian value of any type with a
+///   `from_le_bytes`.
+#[macro_export]
+macro_rules! io_read {
+    (private, $buf:expr) => {
+        $crate::core::io::read_private($buf)
+    };
+    (public, $buf:expr) => {
+        $crate::core::io::read_public($buf)
+    };
+    ($tape:ident : $ty:ty) => {{
+        let mut bytes = [0_u8; ::core::mem::size_of::<$ty>()];
+        $crate::io_read!($tape, &mut bytes);
+        <$ty>::from_le_bytes(bytes)
+    }};
+}
+
+/// Appends bytes to the output tape of the guest, see
+/// [`env::write`](crate::core::env::write).
+#[macro_export]
+macro_rules! io_write {
+    ($bytes:expr) => {
+        $crate::core::env::write(::core::convert::AsRef::<[u8]>::as_ref(&$bytes))
+    };
+}
+
+#[cfg(all(test, not(target_os = "mozakvm")))]
+mod tests {
+    #[test]
+    fn io_macros_natively() {
+        let mut buf = [7_u8; 4];
+        crate::io_read!(private, &mut buf);
+        assert_eq!(buf, [7; 4]);
+        assert_eq!(crate::io_read!(public: u32), 0);
+        crate::io_write!(buf);
+        crate::io_write!(vec![1, 2, 3]);
+    }
+}
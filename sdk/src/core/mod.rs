@@ -4,7 +4,11 @@ mod alloc;
 pub mod debug_macros;
 pub mod ecall;
 pub mod env;
+pub mod hint;
+pub mod intrinsics;
+pub mod io;
 pub mod reg_abi;
+pub mod trap;
 
 pub mod constants {
     /// The size of a `Poseidon2Hash` digest in bytes.
@@ -13,6 +17,29 @@ pub mod constants {
     /// `RATE` of `Poseidon2Permutation` we use
     #[allow(dead_code)]
     pub const RATE: usize = 8;
+
+    /// The size of an affine secp256k1 point in bytes: the `x` and then the
+    /// `y` coordinate, each 32 bytes little-endian.
+    pub const SECP256K1_POINT_BYTES: usize = 64;
+
+    /// The size of an operand of the u256 ecalls in bytes, little-endian.
+    pub const U256_BYTES: usize = 32;
+
+    /// The size of an operand of the Goldilocks ecalls in bytes: a canonical
+    /// field element as a little-endian `u64`.
+    pub const GOLDILOCKS_BYTES: usize = 8;
+
+    /// Offset of the event type in an emitted event, which starts with the
+    /// address of the object.
+    pub const EVENT_TYPE_OFFSET: usize = 8;
+
+    /// The size of an event emitted with the `EMIT_EVENT` ecall in bytes: the
+    /// address of the object, the event type as one byte, and the hash of the
+    /// object's data.
+    pub const EVENT_BYTES: usize = EVENT_TYPE_OFFSET + 1 + DIGEST_BYTES;
+
+    /// The number of event types. Emitted events must have a type below this.
+    pub const EVENT_TYPES: usize = 5;
 }
 
 /// Wrapper around `std::panic::always_abort`
@@ -23,6 +50,16 @@ pub mod constants {
 #[cfg(feature = "std")]
 pub fn always_abort() { std::panic::always_abort(); }
 
+/// Makes panics raise [`trap::PANIC`], so that a panicking guest halts with a
+/// report, like a failed [`guest_assert!`](crate::guest_assert).
+///
+/// [`entry!`](crate::entry) installs this; without `std`, the panic handler
+/// of the sdk does the same.
+#[cfg(all(feature = "std", target_os = "mozakvm"))]
+pub fn set_panic_hook() {
+    std::panic::set_hook(std::boxed::Box::new(|info| trap::raise_panic(info)));
+}
+
 #[cfg(feature = "std")]
 #[macro_export]
 macro_rules! entry {
@@ -35,7 +72,7 @@ macro_rules! entry {
         mod mozak_generated_main {
             #[no_mangle]
             fn bespoke_entrypoint() {
-                $crate::core::always_abort();
+                $crate::core::set_panic_hook();
                 super::MOZAK_ENTRY();
                 {
                     mozak_sdk::common::system::ensure_clean_shutdown();
@@ -104,12 +141,8 @@ _start:
 mod handlers {
     use core::panic::PanicInfo;
 
-    use crate::core::ecall;
+    use crate::core::trap;
 
     #[panic_handler]
-    fn panic_fault(panic_info: &PanicInfo) -> ! {
-        let msg = rust_alloc::format!("{panic_info}");
-        ecall::panic(msg.as_str());
-        unreachable!();
-    }
+    fn panic_fault(panic_info: &PanicInfo) -> ! { trap::raise_panic(panic_info) }
 }
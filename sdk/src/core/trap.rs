@@ -0,0 +1,233 @@
+//! Structured guest assertion failures.
+//!
+//! The `guest_assert*` macros behave like their `core` counterparts, but on
+//! failure inside the VM they do not go through the panic machinery. Instead,
+//! they hand a [`TrapReport`] to the runner via the `TRAP` ecall, together
+//! with one of the trap codes below. The runner halts, records the report,
+//! and the CLI surfaces it, so failing guest tests can be diagnosed without a
+//! debugger.
+//!
+//! Natively, a failing assertion panics with the same rendered report.
+//!
+//! [`prove_assert!`](crate::prove_assert) is different: it does not stop the
+//! VM, but constrains its condition in the proof.
+
+use rust_alloc::string::String;
+use rust_alloc::vec::Vec;
+
+/// Trap code raised by [`guest_assert!`](crate::guest_assert).
+pub const ASSERT: u32 = 1;
+/// Trap code raised by [`guest_assert_eq!`](crate::guest_assert_eq).
+pub const ASSERT_EQ: u32 = 2;
+/// Trap code raised by [`guest_assert_ne!`](crate::guest_assert_ne).
+pub const ASSERT_NE: u32 = 3;
+/// Trap code raised by panics inside the VM, see
+/// [`set_panic_hook`](crate::core::set_panic_hook).
+pub const PANIC: u32 = 4;
+
+#[must_use]
+pub fn describe<'a>(code: u32) -> &'a str {
+    match code {
+        ASSERT => "assertion failed",
+        ASSERT_EQ => "assertion `left == right` failed",
+        ASSERT_NE => "assertion `left != right` failed",
+        PANIC => "panicked",
+        _ => "unknown trap",
+    }
+}
+
+/// 32-bit FNV-1a hash of a source file path.
+///
+/// Guests only send this hash instead of the full path to keep the report
+/// small. Tooling can map it back by hashing candidate paths.
+#[must_use]
+#[allow(clippy::cast_lossless)]
+pub const fn file_hash(path: &str) -> u32 {
+    const FNV_OFFSET_BASIS: u32 = 0x811c_9dc5;
+    const FNV_PRIME: u32 = 0x0100_0193;
+
+    let bytes = path.as_bytes();
+    let mut hash = FNV_OFFSET_BASIS;
+    let mut i = 0;
+    while i < bytes.len() {
+        hash ^= bytes[i] as u32;
+        hash = hash.wrapping_mul(FNV_PRIME);
+        i += 1;
+    }
+    hash
+}
+
+/// Failure information written by the guest for the runner.
+///
+/// Encoded as `file_hash` and `line` (both little-endian `u32`), followed by
+/// the UTF-8 `message`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrapReport {
+    pub code: u32,
+    pub file_hash: u32,
+    pub line: u32,
+    pub message: String,
+}
+
+impl TrapReport {
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(8 + self.message.len());
+        bytes.extend_from_slice(&self.file_hash.to_le_bytes());
+        bytes.extend_from_slice(&self.line.to_le_bytes());
+        bytes.extend_from_slice(self.message.as_bytes());
+        bytes
+    }
+
+    /// Decodes a report sent with trap code `code`.
+    ///
+    /// Returns `None` if `bytes` is too short or the message is not valid
+    /// UTF-8.
+    #[must_use]
+    pub fn from_bytes(code: u32, bytes: &[u8]) -> Option<Self> {
+        let (file_hash, rest) = bytes.split_first_chunk::<4>()?;
+        let (line, message) = rest.split_first_chunk::<4>()?;
+        Some(Self {
+            code,
+            file_hash: u32::from_le_bytes(*file_hash),
+            line: u32::from_le_bytes(*line),
+            message: String::from(core::str::from_utf8(message).ok()?),
+        })
+    }
+}
+
+impl core::fmt::Display for TrapReport {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "{} (trap code {}) at file hash {:#010x}, line {}: {}",
+            describe(self.code),
+            self.code,
+            self.file_hash,
+            self.line,
+            self.message
+        )
+    }
+}
+
+/// Reports a failed guest assertion and stops execution.
+///
+/// Not meant to be called directly, use the `guest_assert*` macros instead.
+#[doc(hidden)]
+pub fn raise(code: u32, file_hash: u32, line: u32, message: String) -> ! {
+    let report = TrapReport {
+        code,
+        file_hash,
+        line,
+        message,
+    };
+
+    #[cfg(target_os = "mozakvm")]
+    {
+        crate::core::ecall::trap(code, &report.to_bytes());
+        unreachable!();
+    }
+
+    #[cfg(not(target_os = "mozakvm"))]
+    panic!("{report}");
+}
+
+/// Reports a panic with [`PANIC`] and stops execution.
+///
+/// Not meant to be called directly, the sdk installs this as panic handler.
+#[doc(hidden)]
+pub fn raise_panic(info: &core::panic::PanicInfo<'_>) -> ! {
+    let (file_hash, line) = info.location().map_or((0, 0), |location| {
+        (file_hash(location.file()), location.line())
+    });
+    raise(PANIC, file_hash, line, rust_alloc::format!("{info}"))
+}
+
+/// Binds `cond` into the proof, see [`prove_assert!`](crate::prove_assert).
+///
+/// Not meant to be called directly, use the macro instead.
+#[doc(hidden)]
+pub fn prove_assert(cond: bool) {
+    #[cfg(target_os = "mozakvm")]
+    crate::core::ecall::prove_assert(cond);
+
+    #[cfg(not(target_os = "mozakvm"))]
+    assert!(cond, "provable assertion failed");
+}
+
+/// Like `assert!`, but inside the VM a failure makes the execution
+/// unprovable instead of trapping: the CPU constrains the condition of every
+/// [`PROVE_ASSERT`](crate::core::ecall::PROVE_ASSERT) ecall to be true.
+///
+/// Use this for invariants that a verifier should be able to rely on. Natively
+/// it behaves like `assert!`.
+#[macro_export]
+macro_rules! prove_assert {
+    ($cond:expr $(,)?) => {
+        $crate::core::trap::prove_assert($cond)
+    };
+}
+
+/// Like `assert!`, but raises [`ASSERT`] on failure inside the VM.
+#[macro_export]
+macro_rules! guest_assert {
+    ($cond:expr $(,)?) => {
+        $crate::guest_assert!($cond, "{}", stringify!($cond))
+    };
+    ($cond:expr, $($arg:tt)+) => {
+        if !$cond {
+            $crate::core::trap::raise(
+                $crate::core::trap::ASSERT,
+                $crate::core::trap::file_hash(file!()),
+                line!(),
+                $crate::core::trap::format_message(format_args!($($arg)+)),
+            );
+        }
+    };
+}
+
+/// Like `assert_eq!`, but raises [`ASSERT_EQ`] on failure inside the VM.
+///
+/// The report message carries both the actual (left) and expected (right)
+/// values.
+#[macro_export]
+macro_rules! guest_assert_eq {
+    ($actual:expr, $expected:expr $(,)?) => {
+        match (&$actual, &$expected) {
+            (actual, expected) =>
+                if !(*actual == *expected) {
+                    $crate::core::trap::raise(
+                        $crate::core::trap::ASSERT_EQ,
+                        $crate::core::trap::file_hash(file!()),
+                        line!(),
+                        $crate::core::trap::format_message(format_args!(
+                            "expected: {:?}, actual: {:?}",
+                            expected, actual
+                        )),
+                    );
+                },
+        }
+    };
+}
+
+/// Like `assert_ne!`, but raises [`ASSERT_NE`] on failure inside the VM.
+#[macro_export]
+macro_rules! guest_assert_ne {
+    ($left:expr, $right:expr $(,)?) => {
+        match (&$left, &$right) {
+            (left, right) =>
+                if *left == *right {
+                    $crate::core::trap::raise(
+                        $crate::core::trap::ASSERT_NE,
+                        $crate::core::trap::file_hash(file!()),
+                        line!(),
+                        $crate::core::trap::format_message(format_args!("both sides: {:?}", left)),
+                    );
+                },
+        }
+    };
+}
+
+#[doc(hidden)]
+#[must_use]
+pub fn format_message(args: core::fmt::Arguments<'_>) -> String { rust_alloc::fmt::format(args) }
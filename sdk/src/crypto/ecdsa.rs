@@ -0,0 +1,145 @@
+//! ECDSA over secp256k1, with Poseidon2 message digests.
+//!
+//! Apart from the digest, this is textbook ECDSA: a signature `(r, s)` on a
+//! message with digest `z` is valid for the public key `Q` when `r` is the `x`
+//! coordinate, modulo the group order, of `(z / s) G + (r / s) Q`. Scalars
+//! are 32 bytes little-endian, like the coordinates of a [`Point`].
+
+use super::secp256k1::{linear_combination, Point, GENERATOR, SCALARS};
+use super::uint::U256;
+#[cfg(target_os = "mozakvm")]
+use crate::mozakvm::poseidon::poseidon2_hash_with_pad;
+#[cfg(not(target_os = "mozakvm"))]
+use crate::native::poseidon::poseidon2_hash_with_pad;
+
+/// A public key, which is a point on the curve.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PublicKey(pub Point);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Signature {
+    pub r: [u8; 32],
+    pub s: [u8; 32],
+}
+
+/// The Poseidon2 digest of `msg`, as a scalar.
+fn digest(msg: &[u8]) -> U256 {
+    SCALARS.reduce(U256::from_le_bytes(&poseidon2_hash_with_pad(msg).0))
+}
+
+/// Verifies that `sig` signs `msg` under `pubkey`.
+///
+/// Inside mozakvm, the curve arithmetic runs on the secp256k1 ecalls and the
+/// digest on the Poseidon2 ecall, so all of it is proven. Only the scalar
+/// arithmetic runs as ordinary instructions.
+#[must_use]
+pub fn verify_signature(pubkey: &PublicKey, msg: &[u8], sig: &Signature) -> bool {
+    let (r, s) = (U256::from_le_bytes(&sig.r), U256::from_le_bytes(&sig.s));
+    let order = SCALARS.modulus();
+    if r.is_zero() || s.is_zero() || r >= order || s >= order || !pubkey.0.is_valid() {
+        return false;
+    }
+    let w = SCALARS.inverse(s);
+    let u1 = SCALARS.mul(digest(msg), w);
+    let u2 = SCALARS.mul(r, w);
+    linear_combination(u1, &GENERATOR, u2, &pubkey.0)
+        .is_some_and(|point| SCALARS.reduce(point.x()) == r)
+}
+
+#[cfg(not(target_os = "mozakvm"))]
+fn secret_scalar(secret_key: &[u8; 32]) -> U256 {
+    let d = U256::from_le_bytes(secret_key);
+    assert!(
+        !d.is_zero() && d < SCALARS.modulus(),
+        "invalid secp256k1 secret key"
+    );
+    d
+}
+
+/// The public key of `secret_key`.
+///
+/// # Panics
+///
+/// Panics if `secret_key` is zero or not below the group order.
+#[cfg(not(target_os = "mozakvm"))]
+#[must_use]
+pub fn public_key(secret_key: &[u8; 32]) -> PublicKey {
+    let d = secret_scalar(secret_key);
+    PublicKey(linear_combination(d, &GENERATOR, U256::ZERO, &GENERATOR).unwrap())
+}
+
+/// Signs `msg` with `secret_key`, for use in native code and tests.
+///
+/// The nonce is derived from the secret key and the digest, so signing is
+/// deterministic.
+///
+/// # Panics
+///
+/// Panics if `secret_key` is zero or not below the group order.
+#[cfg(not(target_os = "mozakvm"))]
+#[must_use]
+pub fn sign(secret_key: &[u8; 32], msg: &[u8]) -> Signature {
+    let d = secret_scalar(secret_key);
+    let z = digest(msg);
+    let mut seed = [*secret_key, z.to_le_bytes()].concat();
+    loop {
+        seed = poseidon2_hash_with_pad(&seed).0.to_vec();
+        let k = SCALARS.reduce(U256::from_le_bytes(seed[..].try_into().unwrap()));
+        if k.is_zero() {
+            continue;
+        }
+        let point = linear_combination(k, &GENERATOR, U256::ZERO, &GENERATOR).unwrap();
+        let r = SCALARS.reduce(point.x());
+        let s = SCALARS.mul(SCALARS.inverse(k), SCALARS.add(z, SCALARS.mul(r, d)));
+        if !r.is_zero() && !s.is_zero() {
+            return Signature {
+                r: r.to_le_bytes(),
+                s: s.to_le_bytes(),
+            };
+        }
+    }
+}
+
+#[cfg(all(test, not(target_os = "mozakvm")))]
+mod tests {
+    use super::{public_key, sign, verify_signature, Signature};
+
+    const SECRET_KEY: [u8; 32] = [7; 32];
+
+    #[test]
+    fn sign_and_verify() {
+        let pubkey = public_key(&SECRET_KEY);
+        let sig = sign(&SECRET_KEY, b"hello mozak");
+        assert!(verify_signature(&pubkey, b"hello mozak", &sig));
+    }
+
+    #[test]
+    fn wrong_message_fails() {
+        let pubkey = public_key(&SECRET_KEY);
+        let sig = sign(&SECRET_KEY, b"hello mozak");
+        assert!(!verify_signature(&pubkey, b"hello world", &sig));
+    }
+
+    #[test]
+    fn wrong_key_fails() {
+        let pubkey = public_key(&[8; 32]);
+        let sig = sign(&SECRET_KEY, b"hello mozak");
+        assert!(!verify_signature(&pubkey, b"hello mozak", &sig));
+    }
+
+    #[test]
+    fn malformed_signature_fails() {
+        let pubkey = public_key(&SECRET_KEY);
+        let sig = sign(&SECRET_KEY, b"hello mozak");
+        for bad in [
+            Signature { r: [0; 32], ..sig },
+            Signature { s: [0; 32], ..sig },
+            Signature {
+                s: [u8::MAX; 32],
+                ..sig
+            },
+        ] {
+            assert!(!verify_signature(&pubkey, b"hello mozak", &bad));
+        }
+    }
+}
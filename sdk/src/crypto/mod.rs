@@ -0,0 +1,11 @@
+//! Signature verification, backed by the secp256k1 ecalls inside mozakvm and
+//! by a software implementation natively, so that the same program code runs
+//! in both.
+
+pub mod ecdsa;
+pub mod secp256k1;
+pub mod uint;
+
+#[cfg(not(target_os = "mozakvm"))]
+pub use ecdsa::{public_key, sign};
+pub use ecdsa::{verify_signature, PublicKey, Signature};
@@ -0,0 +1,224 @@
+//! Affine points on secp256k1, `y^2 = x^3 + 7` over the prime field of
+//! [`FIELD`].
+//!
+//! Inside mozakvm, [`Point::add_assign`] and [`Point::double_assign`] are the
+//! `SECP256K1_ADD` and `SECP256K1_DOUBLE` ecalls, so that the circuits prove
+//! them. Natively, they compute the same results in software.
+
+use super::uint::{PrimeModulus, U256};
+use crate::core::constants::SECP256K1_POINT_BYTES;
+
+/// The base field, modulo `2^256 - 2^32 - 977`.
+pub const FIELD: PrimeModulus = PrimeModulus::new(U256([
+    0xFFFF_FC2F,
+    0xFFFF_FFFE,
+    u32::MAX,
+    u32::MAX,
+    u32::MAX,
+    u32::MAX,
+    u32::MAX,
+    u32::MAX,
+]));
+
+/// Scalars, modulo the prime order of the group.
+pub const SCALARS: PrimeModulus = PrimeModulus::new(U256([
+    0xD036_4141,
+    0xBFD2_5E8C,
+    0xAF48_A03B,
+    0xBAAE_DCE6,
+    0xFFFF_FFFE,
+    u32::MAX,
+    u32::MAX,
+    u32::MAX,
+]));
+
+/// The generator of the group.
+pub const GENERATOR: Point = Point::from_coordinates(
+    U256([
+        0x16F8_1798,
+        0x59F2_815B,
+        0x2DCE_28D9,
+        0x029B_FCDB,
+        0xCE87_0B07,
+        0x55A0_6295,
+        0xF9DC_BBAC,
+        0x79BE_667E,
+    ]),
+    U256([
+        0xFB10_D4B8,
+        0x9C47_D08F,
+        0xA685_5419,
+        0xFD17_B448,
+        0x0E11_08A8,
+        0x5DA4_FBFC,
+        0x26A3_C465,
+        0x483A_DA77,
+    ]),
+);
+
+/// A point other than the point at infinity, in the layout of the ecalls: `x`
+/// and then `y`, each 32 bytes little-endian.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Point(pub [u8; SECP256K1_POINT_BYTES]);
+
+impl Point {
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)]
+    pub const fn from_coordinates(x: U256, y: U256) -> Self {
+        let mut bytes = [0; SECP256K1_POINT_BYTES];
+        let mut i = 0;
+        while i < 32 {
+            bytes[i] = (x.0[i / 4] >> (8 * (i % 4))) as u8;
+            bytes[32 + i] = (y.0[i / 4] >> (8 * (i % 4))) as u8;
+            i += 1;
+        }
+        Self(bytes)
+    }
+
+    #[must_use]
+    pub fn x(&self) -> U256 { U256::from_le_bytes(self.0[..32].try_into().unwrap()) }
+
+    #[must_use]
+    pub fn y(&self) -> U256 { U256::from_le_bytes(self.0[32..].try_into().unwrap()) }
+
+    /// Whether both coordinates are reduced, and satisfy the curve equation.
+    #[must_use]
+    pub fn is_valid(&self) -> bool {
+        let (x, y) = (self.x(), self.y());
+        x < FIELD.modulus()
+            && y < FIELD.modulus()
+            && FIELD.square(y)
+                == FIELD.add(
+                    FIELD.mul(FIELD.square(x), x),
+                    U256([7, 0, 0, 0, 0, 0, 0, 0]),
+                )
+    }
+
+    /// Adds `other` to `self`. The points must have different `x`
+    /// coordinates.
+    pub fn add_assign(&mut self, other: &Self) {
+        #[cfg(target_os = "mozakvm")]
+        crate::core::ecall::secp256k1_add(&mut self.0, &other.0);
+        #[cfg(not(target_os = "mozakvm"))]
+        {
+            let numerator = FIELD.sub(other.y(), self.y());
+            let denominator = FIELD.sub(other.x(), self.x());
+            *self = self.through(other, numerator, denominator);
+        }
+    }
+
+    /// Doubles `self`. The point must have a non-zero `y` coordinate, which
+    /// every point on secp256k1 does.
+    pub fn double_assign(&mut self) {
+        #[cfg(target_os = "mozakvm")]
+        crate::core::ecall::secp256k1_double(&mut self.0);
+        #[cfg(not(target_os = "mozakvm"))]
+        {
+            let x = self.x();
+            let numerator = FIELD.mul(U256([3, 0, 0, 0, 0, 0, 0, 0]), FIELD.square(x));
+            let denominator = FIELD.add(self.y(), self.y());
+            *self = self.through(self, numerator, denominator);
+        }
+    }
+
+    /// The third intersection of the line through `self` and `other` with
+    /// the curve, reflected about the `x` axis.
+    #[cfg(not(target_os = "mozakvm"))]
+    fn through(&self, other: &Self, numerator: U256, denominator: U256) -> Self {
+        assert!(
+            !denominator.is_zero(),
+            "secp256k1 point operation has an undefined slope"
+        );
+        let slope = FIELD.mul(numerator, FIELD.inverse(denominator));
+        let (x1, y1) = (self.x(), self.y());
+        let x3 = FIELD.sub(FIELD.sub(FIELD.square(slope), x1), other.x());
+        let y3 = FIELD.sub(FIELD.mul(slope, FIELD.sub(x1, x3)), y1);
+        Self::from_coordinates(x3, y3)
+    }
+}
+
+/// `acc + point`, where `None` is the point at infinity.
+///
+/// The ecalls only cover distinct `x` coordinates, so equal and opposite
+/// points are handled here.
+fn add(acc: Option<Point>, point: &Point) -> Option<Point> {
+    match acc {
+        None => Some(*point),
+        Some(mut acc) if acc.x() == point.x() => (acc.y() == point.y()).then(|| {
+            acc.double_assign();
+            acc
+        }),
+        Some(mut acc) => {
+            acc.add_assign(point);
+            Some(acc)
+        }
+    }
+}
+
+fn double(acc: Option<Point>) -> Option<Point> {
+    acc.map(|mut acc| {
+        acc.double_assign();
+        acc
+    })
+}
+
+/// `a * p + b * q`, with a single chain of doublings for both products.
+#[must_use]
+pub fn linear_combination(a: U256, p: &Point, b: U256, q: &Point) -> Option<Point> {
+    let sum = add(Some(*p), q);
+    (0..256).rev().fold(None, |acc, i| {
+        let acc = double(acc);
+        match (a.bit(i), b.bit(i)) {
+            (true, true) => match &sum {
+                Some(sum) => add(acc, sum),
+                None => acc,
+            },
+            (true, false) => add(acc, p),
+            (false, true) => add(acc, q),
+            (false, false) => acc,
+        }
+    })
+}
+
+#[cfg(all(test, not(target_os = "mozakvm")))]
+mod tests {
+    use super::{linear_combination, Point, GENERATOR, SCALARS};
+    use crate::crypto::uint::U256;
+
+    fn scalar(value: u32) -> U256 { U256([value, 0, 0, 0, 0, 0, 0, 0]) }
+
+    #[test]
+    fn generator_is_valid() {
+        assert!(GENERATOR.is_valid());
+    }
+
+    #[test]
+    fn doubling_agrees_with_addition() {
+        let mut twice = GENERATOR;
+        twice.double_assign();
+        let mut thrice = twice;
+        thrice.add_assign(&GENERATOR);
+        assert!(thrice.is_valid());
+        assert_eq!(
+            linear_combination(scalar(2), &GENERATOR, scalar(1), &GENERATOR),
+            Some(thrice)
+        );
+    }
+
+    #[test]
+    fn order_times_generator_is_infinity() {
+        let order = SCALARS.modulus();
+        let (order_minus_one, _) = order.overflowing_sub(U256::ONE);
+        assert_eq!(
+            linear_combination(order_minus_one, &GENERATOR, U256::ONE, &GENERATOR),
+            None
+        );
+        assert_eq!(
+            linear_combination(order_minus_one, &GENERATOR, U256::ZERO, &GENERATOR),
+            Some(Point::from_coordinates(
+                GENERATOR.x(),
+                super::FIELD.sub(U256::ZERO, GENERATOR.y())
+            ))
+        );
+    }
+}
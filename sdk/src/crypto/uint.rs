@@ -0,0 +1,331 @@
+//! 256-bit integers, and arithmetic modulo a fixed prime.
+//!
+//! Limbs are 32 bits wide, so that every limb product is a single `mul` and
+//! `mulhu` pair in mozakvm, see [`crate::math`]. The wrapping and modular
+//! operations on [`U256`] go through the u256 ecalls in mozakvm instead.
+#![allow(clippy::cast_possible_truncation)]
+
+use core::cmp::Ordering;
+
+use crate::math::mul_wide_u32;
+
+const LIMBS: usize = 8;
+
+/// An unsigned 256-bit integer, in little-endian 32-bit limbs.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct U256(pub [u32; LIMBS]);
+
+impl U256 {
+    pub const ONE: Self = Self([1, 0, 0, 0, 0, 0, 0, 0]);
+    pub const ZERO: Self = Self([0; LIMBS]);
+
+    #[must_use]
+    pub fn from_le_bytes(bytes: &[u8; 32]) -> Self {
+        let mut limbs = [0; LIMBS];
+        for (limb, chunk) in limbs.iter_mut().zip(bytes.chunks_exact(4)) {
+            *limb = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        }
+        Self(limbs)
+    }
+
+    #[must_use]
+    pub fn to_le_bytes(self) -> [u8; 32] {
+        let mut bytes = [0; 32];
+        for (chunk, limb) in bytes.chunks_exact_mut(4).zip(self.0) {
+            chunk.copy_from_slice(&limb.to_le_bytes());
+        }
+        bytes
+    }
+
+    #[must_use]
+    pub fn is_zero(&self) -> bool { *self == Self::ZERO }
+
+    #[must_use]
+    pub fn bit(&self, i: usize) -> bool { (self.0[i / 32] >> (i % 32)) & 1 == 1 }
+
+    #[must_use]
+    pub const fn overflowing_add(self, rhs: Self) -> (Self, bool) {
+        let mut sum = [0; LIMBS];
+        let mut carry = 0_u64;
+        let mut i = 0;
+        while i < LIMBS {
+            let limb = self.0[i] as u64 + rhs.0[i] as u64 + carry;
+            sum[i] = limb as u32;
+            carry = limb >> 32;
+            i += 1;
+        }
+        (Self(sum), carry != 0)
+    }
+
+    #[must_use]
+    pub const fn overflowing_sub(self, rhs: Self) -> (Self, bool) {
+        let mut difference = [0; LIMBS];
+        let mut borrow = false;
+        let mut i = 0;
+        while i < LIMBS {
+            let (limb, borrow_a) = self.0[i].overflowing_sub(rhs.0[i]);
+            let (limb, borrow_b) = limb.overflowing_sub(borrow as u32);
+            difference[i] = limb;
+            borrow = borrow_a || borrow_b;
+            i += 1;
+        }
+        (Self(difference), borrow)
+    }
+
+    /// The full 512-bit product, in little-endian limbs. Zero limbs of `self`
+    /// are skipped, so put the operand with more of them first.
+    #[must_use]
+    fn widening_mul(self, rhs: Self) -> [u32; 2 * LIMBS] {
+        let mut product = [0; 2 * LIMBS];
+        for (i, &a) in self.0.iter().enumerate() {
+            if a == 0 {
+                continue;
+            }
+            let mut carry = 0_u64;
+            for (j, &b) in rhs.0.iter().enumerate() {
+                let (lo, hi) = mul_wide_u32(a, b);
+                let limb = u64::from(product[i + j]) + u64::from(lo) + carry;
+                product[i + j] = limb as u32;
+                carry = (limb >> 32) + u64::from(hi);
+            }
+            product[i + LIMBS] = carry as u32;
+        }
+        product
+    }
+
+    /// `self + rhs` modulo `2^256`.
+    #[must_use]
+    pub fn wrapping_add(self, rhs: Self) -> Self {
+        #[cfg(target_os = "mozakvm")]
+        {
+            let mut sum = self.to_le_bytes();
+            crate::core::ecall::u256_add(&mut sum, &rhs.to_le_bytes());
+            Self::from_le_bytes(&sum)
+        }
+        #[cfg(not(target_os = "mozakvm"))]
+        {
+            self.overflowing_add(rhs).0
+        }
+    }
+
+    /// `self * rhs` modulo `2^256`.
+    #[must_use]
+    pub fn wrapping_mul(self, rhs: Self) -> Self {
+        #[cfg(target_os = "mozakvm")]
+        {
+            let mut product = self.to_le_bytes();
+            crate::core::ecall::u256_mul(&mut product, &rhs.to_le_bytes());
+            Self::from_le_bytes(&product)
+        }
+        #[cfg(not(target_os = "mozakvm"))]
+        {
+            Self(self.widening_mul(rhs)[..LIMBS].try_into().unwrap())
+        }
+    }
+
+    /// `self * rhs` modulo any non-zero `modulus`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `modulus` is zero.
+    #[must_use]
+    pub fn mul_mod(self, rhs: Self, modulus: Self) -> Self {
+        assert!(!modulus.is_zero(), "u256 mulmod by a zero modulus");
+        #[cfg(target_os = "mozakvm")]
+        {
+            let mut product = self.to_le_bytes();
+            crate::core::ecall::u256_mulmod(
+                &mut product,
+                &rhs.to_le_bytes(),
+                &modulus.to_le_bytes(),
+            );
+            Self::from_le_bytes(&product)
+        }
+        // Shift the product in from the top, one bit at a time. The remainder
+        // stays below `modulus`, so doubling it overflows by at most one bit,
+        // and one subtraction reduces it again.
+        #[cfg(not(target_os = "mozakvm"))]
+        {
+            self.widening_mul(rhs)
+                .iter()
+                .rev()
+                .flat_map(|&limb| (0..32).rev().map(move |i| (limb >> i) & 1))
+                .fold(Self::ZERO, |remainder, bit| {
+                    let (doubled, overflow) = remainder.overflowing_add(remainder);
+                    let (doubled, _) = doubled.overflowing_add(Self([bit, 0, 0, 0, 0, 0, 0, 0]));
+                    if overflow || doubled >= modulus {
+                        doubled.overflowing_sub(modulus).0
+                    } else {
+                        doubled
+                    }
+                })
+        }
+    }
+}
+
+impl PartialOrd for U256 {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> { Some(self.cmp(other)) }
+}
+
+impl Ord for U256 {
+    fn cmp(&self, other: &Self) -> Ordering { self.0.iter().rev().cmp(other.0.iter().rev()) }
+}
+
+/// Arithmetic modulo a prime just below `2^256`.
+///
+/// All operands have to be reduced already, and so are all results.
+#[derive(Clone, Copy, Debug)]
+pub struct PrimeModulus {
+    modulus: U256,
+    /// `2^256 - modulus`, which is what `2^256` reduces to.
+    complement: U256,
+}
+
+impl PrimeModulus {
+    /// # Panics
+    ///
+    /// Panics if `modulus` is not above `2^255`, which reduction relies on.
+    #[must_use]
+    pub const fn new(modulus: U256) -> Self {
+        assert!(modulus.0[LIMBS - 1] >> 31 == 1);
+        Self {
+            modulus,
+            complement: U256::ZERO.overflowing_sub(modulus).0,
+        }
+    }
+
+    #[must_use]
+    pub const fn modulus(&self) -> U256 { self.modulus }
+
+    /// Reduces any 256-bit `value`, with at most one subtraction.
+    #[must_use]
+    pub fn reduce(&self, value: U256) -> U256 {
+        if value < self.modulus {
+            value
+        } else {
+            value.overflowing_sub(self.modulus).0
+        }
+    }
+
+    #[must_use]
+    pub fn add(&self, a: U256, b: U256) -> U256 {
+        let (sum, carry) = a.overflowing_add(b);
+        if carry || sum >= self.modulus {
+            sum.overflowing_sub(self.modulus).0
+        } else {
+            sum
+        }
+    }
+
+    #[must_use]
+    pub fn sub(&self, a: U256, b: U256) -> U256 {
+        let (difference, borrow) = a.overflowing_sub(b);
+        if borrow {
+            difference.overflowing_add(self.modulus).0
+        } else {
+            difference
+        }
+    }
+
+    #[must_use]
+    pub fn mul(&self, a: U256, b: U256) -> U256 {
+        let mut wide = a.widening_mul(b);
+        // Fold the high half back in as `hi * 2^256 = hi * complement`, until
+        // nothing is left above `2^256`. Each round shrinks the high half by
+        // the bits the complement is short of `2^256`.
+        loop {
+            let (lo, hi) = wide.split_at(LIMBS);
+            let hi = U256(hi.try_into().unwrap());
+            if hi.is_zero() {
+                return self.reduce(U256(lo.try_into().unwrap()));
+            }
+            let mut folded = self.complement.widening_mul(hi);
+            let mut carry = 0_u64;
+            for (i, limb) in folded.iter_mut().enumerate() {
+                let sum = u64::from(*limb) + u64::from(lo.get(i).copied().unwrap_or(0)) + carry;
+                *limb = sum as u32;
+                carry = sum >> 32;
+            }
+            wide = folded;
+        }
+    }
+
+    #[must_use]
+    pub fn square(&self, a: U256) -> U256 { self.mul(a, a) }
+
+    #[must_use]
+    pub fn pow(&self, base: U256, exponent: U256) -> U256 {
+        (0..256).rev().fold(U256::ONE, |acc, i| {
+            let acc = self.square(acc);
+            if exponent.bit(i) {
+                self.mul(acc, base)
+            } else {
+                acc
+            }
+        })
+    }
+
+    /// The inverse of a non-zero `a`, by Fermat's little theorem.
+    #[must_use]
+    pub fn inverse(&self, a: U256) -> U256 {
+        self.pow(
+            a,
+            self.modulus
+                .overflowing_sub(U256([2, 0, 0, 0, 0, 0, 0, 0]))
+                .0,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PrimeModulus, U256};
+
+    /// `2^256 - 189`, the largest prime below `2^256`.
+    const MODULUS: PrimeModulus = PrimeModulus::new(U256([
+        0xFFFF_FF43,
+        u32::MAX,
+        u32::MAX,
+        u32::MAX,
+        u32::MAX,
+        u32::MAX,
+        u32::MAX,
+        u32::MAX,
+    ]));
+
+    #[test]
+    fn minus_one_squared_is_one() {
+        let minus_one = MODULUS.sub(U256::ZERO, U256::ONE);
+        assert_eq!(MODULUS.square(minus_one), U256::ONE);
+    }
+
+    #[test]
+    fn inverse() {
+        for a in [
+            U256::ONE,
+            U256([2, 0, 0, 0, 0, 0, 0, 0]),
+            U256([0xDEAD_BEEF, 0, 0xFEED_CAFE, 0, 1, 2, 3, 0x8000_0000]),
+        ] {
+            assert_eq!(MODULUS.mul(a, MODULUS.inverse(a)), U256::ONE);
+        }
+    }
+
+    #[test]
+    fn wrapping_and_modular_arithmetic() {
+        let max = U256([u32::MAX; 8]);
+        assert_eq!(max.wrapping_add(U256::ONE), U256::ZERO);
+        assert_eq!(max.wrapping_mul(max), U256::ONE);
+        let a = U256([0xDEAD_BEEF, 0, 0xFEED_CAFE, 0, 1, 2, 3, 0x8000_0000]);
+        let minus_one = MODULUS.sub(U256::ZERO, U256::ONE);
+        assert_eq!(a.mul_mod(U256::ONE, MODULUS.modulus()), MODULUS.reduce(a));
+        assert_eq!(minus_one.mul_mod(minus_one, MODULUS.modulus()), U256::ONE);
+        assert_eq!(a.mul_mod(max, max), U256::ZERO);
+        assert_eq!(a.mul_mod(a, U256::ONE), U256::ZERO);
+    }
+
+    #[test]
+    fn byte_round_trip() {
+        let bytes = core::array::from_fn(|i| i as u8);
+        assert_eq!(U256::from_le_bytes(&bytes).to_le_bytes(), bytes);
+    }
+}
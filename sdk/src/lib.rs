@@ -8,12 +8,26 @@
 #![cfg_attr(feature = "std", feature(restricted_std))]
 
 extern crate alloc as rust_alloc;
+// So that code generated by `mozak_sdk_macros` resolves within the SDK, too.
+#[cfg(test)]
+extern crate self as mozak_sdk;
 
 pub mod core;
+pub mod math;
 
 #[cfg(feature = "std")]
 pub mod common;
 
+#[cfg(feature = "std")]
+pub mod crypto;
+
+/// Generates the cross-program call plumbing of a contract from an `impl`
+/// block of its methods.
+#[cfg(feature = "std")]
+pub use mozak_sdk_macros::contract;
+
+#[cfg(feature = "std")]
+pub use crate::common::prandom::Prandom;
 #[cfg(feature = "std")]
 pub use crate::common::system::{call_receive, call_send, event_emit};
 
@@ -33,6 +47,8 @@ pub use crate::mozakvm::inputtape::read;
 pub use crate::mozakvm::poseidon::poseidon2_hash_no_pad;
 #[cfg(all(feature = "std", target_os = "mozakvm"))]
 pub use crate::mozakvm::poseidon::poseidon2_hash_with_pad;
+#[cfg(all(feature = "std", target_os = "mozakvm"))]
+pub use crate::mozakvm::poseidon::poseidon2_xof_no_pad;
 /// Manually add a `ProgramIdentifier` onto `IdentityStack`. Useful
 /// when one want to escape automatic management of `IdentityStack`
 /// via cross-program-calls sends (ideally temporarily).
@@ -0,0 +1,160 @@
+//! Division of `u64` values by a divisor fixed ahead of time.
+//!
+//! `u64` division compiles to `__udivdi3` on RV32, a shift-and-subtract loop
+//! of up to 64 iterations. When the divisor is known up front, the quotient is
+//! instead a high multiplication by a precomputed reciprocal followed by a
+//! shift (Granlund and Montgomery, "Division by invariant integers using
+//! multiplication").
+
+use super::wide::mulhi_u64;
+
+/// Divides `u64` values by a fixed divisor via a precomputed reciprocal.
+///
+/// Construct it once, ideally in a `const`, and reuse it:
+///
+/// ```
+/// use mozak_sdk::math::DivU64;
+///
+/// const BY_TEN: DivU64 = DivU64::new(10);
+/// assert_eq!(BY_TEN.div(1234), 123);
+/// assert_eq!(BY_TEN.rem(1234), 4);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DivU64 {
+    divisor: u64,
+    /// Reciprocal of `divisor`, or zero if `divisor` is a power of two.
+    magic: u64,
+    shift: u32,
+    /// Whether the reciprocal needs a 65th bit, added back in `div`.
+    add: bool,
+}
+
+impl DivU64 {
+    /// Precomputes the reciprocal of `divisor`.
+    ///
+    /// This performs one `u128` division, so it should run at compile time
+    /// or at least outside of hot loops.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `divisor` is zero.
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)]
+    pub const fn new(divisor: u64) -> Self {
+        assert!(divisor != 0, "division by zero");
+        let shift = divisor.ilog2();
+        if divisor.is_power_of_two() {
+            return Self {
+                divisor,
+                magic: 0,
+                shift,
+                add: false,
+            };
+        }
+
+        let numerator = 1_u128 << (64 + shift);
+        let magic = (numerator / divisor as u128) as u64;
+        let rem = (numerator % divisor as u128) as u64;
+        let error = divisor - rem;
+        if error < 1 << shift {
+            // `magic + 1` is a precise enough reciprocal with 64 bits.
+            Self {
+                divisor,
+                magic: magic + 1,
+                shift,
+                add: false,
+            }
+        } else {
+            // Otherwise use the 65 bit reciprocal `2^64 + magic`.
+            let twice_rem = rem.wrapping_add(rem);
+            let carry = if twice_rem >= divisor || twice_rem < rem {
+                1
+            } else {
+                0
+            };
+            Self {
+                divisor,
+                magic: magic.wrapping_add(magic).wrapping_add(carry + 1),
+                shift,
+                add: true,
+            }
+        }
+    }
+
+    #[must_use]
+    pub const fn divisor(&self) -> u64 { self.divisor }
+
+    /// Returns `n / self.divisor()`.
+    #[must_use]
+    #[inline]
+    pub const fn div(&self, n: u64) -> u64 {
+        if self.magic == 0 {
+            return n >> self.shift;
+        }
+        let q = mulhi_u64(self.magic, n);
+        if self.add {
+            // `(n + q) >> 1` without overflowing.
+            (((n - q) >> 1) + q) >> self.shift
+        } else {
+            q >> self.shift
+        }
+    }
+
+    /// Returns `n % self.divisor()`.
+    #[must_use]
+    #[inline]
+    pub const fn rem(&self, n: u64) -> u64 { n - self.div(n) * self.divisor }
+
+    /// Returns `(n / self.divisor(), n % self.divisor())`.
+    #[must_use]
+    #[inline]
+    pub const fn div_rem(&self, n: u64) -> (u64, u64) {
+        let q = self.div(n);
+        (q, n - q * self.divisor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DivU64;
+
+    #[test]
+    fn matches_native_division() {
+        let divisors = [
+            1,
+            2,
+            3,
+            5,
+            7,
+            10,
+            641,
+            1 << 32,
+            1_000_000_007,
+            (1 << 63) + 1,
+            u64::MAX - 1,
+            u64::MAX,
+        ];
+        let dividends = [
+            0,
+            1,
+            9,
+            10,
+            11,
+            0xFFFF_FFFF,
+            0xDEAD_BEEF_FEED_CAFE,
+            1 << 63,
+            u64::MAX - 1,
+            u64::MAX,
+        ];
+        for d in divisors {
+            let by_d = DivU64::new(d);
+            for n in dividends.into_iter().chain([d - 1, d, d.wrapping_add(1)]) {
+                assert_eq!(by_d.div_rem(n), (n / d, n % d), "{n} / {d}");
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "division by zero")]
+    fn zero_divisor() { let _ = DivU64::new(0); }
+}
@@ -0,0 +1,135 @@
+//! Arithmetic in the Goldilocks field, the field of the proofs.
+//!
+//! Simulated with 32-bit instructions, a field multiplication takes dozens of
+//! rows, and an inversion thousands. Inside mozakvm, [`Goldilocks`] goes
+//! through the Goldilocks ecalls instead, which prove each operation in a
+//! single row of a table that computes in the field natively. Natively, it
+//! falls back to a software implementation, so that the same program code
+//! runs in both.
+#![allow(clippy::cast_possible_truncation)]
+
+use core::ops::{Add, Mul};
+
+#[cfg(target_os = "mozakvm")]
+use crate::core::ecall;
+
+/// The order of the Goldilocks field, `2^64 - 2^32 + 1`.
+pub const ORDER: u64 = 0xFFFF_FFFF_0000_0001;
+
+/// An element of the Goldilocks field, always in canonical form.
+///
+/// ```
+/// use mozak_sdk::math::Goldilocks;
+///
+/// let x = Goldilocks::new(3);
+/// assert_eq!(x * x.inverse().unwrap(), Goldilocks::ONE);
+/// assert_eq!(Goldilocks::new(u64::MAX), Goldilocks::new(0xFFFF_FFFE));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Goldilocks(u64);
+
+impl Goldilocks {
+    pub const ONE: Self = Self(1);
+    pub const ZERO: Self = Self(0);
+
+    /// The element `value` modulo [`ORDER`].
+    #[must_use]
+    pub const fn new(value: u64) -> Self {
+        Self(if value >= ORDER { value - ORDER } else { value })
+    }
+
+    /// The canonical representative, below [`ORDER`].
+    #[must_use]
+    pub const fn to_canonical_u64(self) -> u64 { self.0 }
+
+    #[must_use]
+    pub fn is_zero(self) -> bool { self == Self::ZERO }
+
+    /// The multiplicative inverse, or `None` for zero.
+    #[must_use]
+    pub fn inverse(self) -> Option<Self> {
+        if self.is_zero() {
+            return None;
+        }
+        #[cfg(target_os = "mozakvm")]
+        {
+            let mut a = self.0.to_le_bytes();
+            ecall::goldilocks_inverse(&mut a);
+            Some(Self(u64::from_le_bytes(a)))
+        }
+        #[cfg(not(target_os = "mozakvm"))]
+        {
+            Some(self.pow(ORDER - 2))
+        }
+    }
+
+    /// `self` to the power of `exponent`, by square and multiply.
+    #[must_use]
+    pub fn pow(self, mut exponent: u64) -> Self {
+        let (mut base, mut power) = (self, Self::ONE);
+        while exponent != 0 {
+            if exponent & 1 == 1 {
+                power = power * base;
+            }
+            base = base * base;
+            exponent >>= 1;
+        }
+        power
+    }
+}
+
+impl Add for Goldilocks {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        #[cfg(target_os = "mozakvm")]
+        {
+            let mut a = self.0.to_le_bytes();
+            ecall::goldilocks_add(&mut a, &rhs.0.to_le_bytes());
+            Self(u64::from_le_bytes(a))
+        }
+        #[cfg(not(target_os = "mozakvm"))]
+        {
+            Self(((u128::from(self.0) + u128::from(rhs.0)) % u128::from(ORDER)) as u64)
+        }
+    }
+}
+
+impl Mul for Goldilocks {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        #[cfg(target_os = "mozakvm")]
+        {
+            let mut a = self.0.to_le_bytes();
+            ecall::goldilocks_mul(&mut a, &rhs.0.to_le_bytes());
+            Self(u64::from_le_bytes(a))
+        }
+        #[cfg(not(target_os = "mozakvm"))]
+        {
+            Self(((u128::from(self.0) * u128::from(rhs.0)) % u128::from(ORDER)) as u64)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Goldilocks, ORDER};
+
+    #[test]
+    fn wraps_around_the_order() {
+        let minus_one = Goldilocks::new(ORDER - 1);
+        assert_eq!(minus_one + Goldilocks::new(2), Goldilocks::ONE);
+        assert_eq!(minus_one * minus_one, Goldilocks::ONE);
+        assert_eq!(Goldilocks::new(ORDER), Goldilocks::ZERO);
+    }
+
+    #[test]
+    fn inverse() {
+        for value in [1, 2, 0xDEAD_BEEF, ORDER - 1] {
+            let x = Goldilocks::new(value);
+            assert_eq!(x * x.inverse().unwrap(), Goldilocks::ONE);
+        }
+        assert_eq!(Goldilocks::ZERO.inverse(), None);
+    }
+}
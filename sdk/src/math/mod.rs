@@ -0,0 +1,23 @@
+//! Integer routines tuned for the cost of proving, rather than CPU time.
+//!
+//! Every instruction the VM executes costs one row in the CPU table, no matter
+//! how much work it does, so the cost of a routine is its dynamic instruction
+//! count. On RV32IM, `u64` division, and `u128` multiplication and division,
+//! lower to bit-serial loops in `compiler_builtins` that run for hundreds of
+//! instructions. Native `u32` `mul`, `mulhu` and `divu` are single rows.
+//!
+//! The routines here replace those loops with a handful of 32-bit
+//! multiplications, and avoid data dependent branches where a mask does the
+//! same job, so their cost is fixed and small.
+//!
+//! Everything is `no_std` and allocation free.
+
+pub mod divconst;
+pub mod goldilocks;
+pub mod select;
+pub mod wide;
+
+pub use divconst::DivU64;
+pub use goldilocks::Goldilocks;
+pub use select::{lower_bound, max_u32, min_u32, select_u32};
+pub use wide::{mul_wide_u32, mul_wide_u64, mulhi_u64};
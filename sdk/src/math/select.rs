@@ -0,0 +1,75 @@
+//! Branch-free selection and search.
+//!
+//! A taken branch is as cheap as any other instruction, but data dependent
+//! control flow makes the cost of a routine depend on its inputs. These
+//! routines compute with masks instead, so they run the same instructions
+//! for every input.
+
+/// Returns `a` if `condition` holds, and `b` otherwise.
+#[must_use]
+#[inline]
+pub fn select_u32(condition: bool, a: u32, b: u32) -> u32 {
+    let mask = u32::from(condition).wrapping_neg();
+    b ^ ((a ^ b) & mask)
+}
+
+#[must_use]
+#[inline]
+pub fn min_u32(a: u32, b: u32) -> u32 { select_u32(a < b, a, b) }
+
+#[must_use]
+#[inline]
+pub fn max_u32(a: u32, b: u32) -> u32 { select_u32(a < b, b, a) }
+
+/// Returns the index of the first element of the sorted `slice` that is not
+/// less than `needle`, or `slice.len()` if there is none.
+///
+/// Unlike [`slice::partition_point`], the number of iterations only depends on
+/// the length of `slice`, and each iteration narrows the range with a
+/// [`select_u32`] rather than a branch.
+#[must_use]
+#[allow(clippy::cast_possible_truncation)]
+pub fn lower_bound(slice: &[u32], needle: u32) -> usize {
+    if slice.is_empty() {
+        return 0;
+    }
+    let mut base = 0_u32;
+    let mut len = slice.len() as u32;
+    while len > 1 {
+        let half = len / 2;
+        let mid = base + half;
+        base = select_u32(slice[mid as usize - 1] < needle, mid, base);
+        len -= half;
+    }
+    base as usize + usize::from(slice[base as usize] < needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{lower_bound, max_u32, min_u32, select_u32};
+
+    #[test]
+    fn select_min_max() {
+        assert_eq!(select_u32(true, 3, 7), 3);
+        assert_eq!(select_u32(false, 3, 7), 7);
+        for (a, b) in [(0, u32::MAX), (5, 5), (9, 2)] {
+            assert_eq!(min_u32(a, b), a.min(b));
+            assert_eq!(max_u32(a, b), a.max(b));
+        }
+    }
+
+    #[test]
+    fn lower_bound_matches_partition_point() {
+        let slice = [1, 3, 3, 3, 8, 13, 21, 21, 40];
+        for len in 0..=slice.len() {
+            let slice = &slice[..len];
+            for needle in 0..=41 {
+                assert_eq!(
+                    lower_bound(slice, needle),
+                    slice.partition_point(|&x| x < needle),
+                    "{needle} in {slice:?}"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,77 @@
+//! Widening multiplication built from 32-bit limbs.
+//!
+//! `u64 * u64 -> u128` compiles to a call to `__multi3` on RV32, which
+//! multiplies full 128-bit operands. Splitting into 32-bit limbs needs four
+//! `mul`/`mulhu` pairs and no loop.
+#![allow(clippy::cast_possible_truncation)]
+
+/// Multiplies two `u32`s into their full 64-bit product, as `(lo, hi)`.
+///
+/// This lowers to exactly one `mul` and one `mulhu`.
+#[must_use]
+#[inline]
+pub const fn mul_wide_u32(a: u32, b: u32) -> (u32, u32) {
+    let product = a as u64 * b as u64;
+    (product as u32, (product >> 32) as u32)
+}
+
+/// Multiplies two `u64`s into their full 128-bit product, as `(lo, hi)`.
+#[must_use]
+#[inline]
+pub const fn mul_wide_u64(a: u64, b: u64) -> (u64, u64) {
+    let (a0, a1) = (a as u32, (a >> 32) as u32);
+    let (b0, b1) = (b as u32, (b >> 32) as u32);
+
+    let (p00_lo, p00_hi) = mul_wide_u32(a0, b0);
+    let (p01_lo, p01_hi) = mul_wide_u32(a0, b1);
+    let (p10_lo, p10_hi) = mul_wide_u32(a1, b0);
+    let (p11_lo, p11_hi) = mul_wide_u32(a1, b1);
+
+    // Middle column: at most three 32-bit values, so it fits in a `u64`.
+    let mid = p00_hi as u64 + p01_lo as u64 + p10_lo as u64;
+    let lo = (p00_lo as u64) | (mid << 32);
+    let hi = p11_lo as u64 + ((p11_hi as u64) << 32) + p01_hi as u64 + p10_hi as u64 + (mid >> 32);
+    (lo, hi)
+}
+
+/// Returns the high 64 bits of the 128-bit product of `a` and `b`.
+#[must_use]
+#[inline]
+pub const fn mulhi_u64(a: u64, b: u64) -> u64 { mul_wide_u64(a, b).1 }
+
+#[cfg(test)]
+mod tests {
+    use super::{mul_wide_u32, mul_wide_u64};
+
+    #[test]
+    fn matches_native_widening_multiplication() {
+        let samples = [
+            0,
+            1,
+            2,
+            0xFFFF_FFFF,
+            0x1_0000_0000,
+            0xDEAD_BEEF_FEED_CAFE,
+            u64::MAX - 1,
+            u64::MAX,
+        ];
+        for a in samples {
+            for b in samples {
+                let (lo, hi) = mul_wide_u64(a, b);
+                let product = u128::from(a) * u128::from(b);
+                assert_eq!(
+                    u128::from(lo) | (u128::from(hi) << 64),
+                    product,
+                    "{a} * {b}"
+                );
+
+                let (a, b) = (a as u32, b as u32);
+                let (lo, hi) = mul_wide_u32(a, b);
+                assert_eq!(
+                    u64::from(lo) | (u64::from(hi) << 32),
+                    u64::from(a) * u64::from(b)
+                );
+            }
+        }
+    }
+}
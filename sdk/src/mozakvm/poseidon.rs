@@ -37,3 +37,21 @@ pub fn poseidon2_hash_no_pad(input: &[u8]) -> Poseidon2Hash {
     crate::core::ecall::poseidon2(input.as_ptr(), input.len(), output.as_mut_ptr());
     Poseidon2Hash(output)
 }
+
+/// Hashes the input slice, assuming its length to be a multiple of `RATE`,
+/// and fills `output` XOF style: the first `DIGEST_BYTES` are
+/// [`poseidon2_hash_no_pad`] of the input, and each further `DIGEST_BYTES`
+/// take one more permutation of the sponge.
+/// # Panics
+/// If the input length is not a multiple of `RATE`, or the output length is
+/// not a positive multiple of `DIGEST_BYTES`.
+pub fn poseidon2_xof_no_pad(input: &[u8], output: &mut [u8]) {
+    assert!(input.len() % RATE == 0);
+    assert!(!output.is_empty() && output.len() % DIGEST_BYTES == 0);
+    crate::core::ecall::poseidon2_xof(
+        input.as_ptr(),
+        input.len(),
+        output.as_mut_ptr(),
+        output.len(),
+    );
+}
@@ -15,6 +15,16 @@ pub struct RawTape {
     pub writer: HashMap<ProgramIdentifier, RawMessage>,
 }
 
+impl RawTape {
+    /// The bytes written to this tape for `program`. Each program of a
+    /// multi-program execution only reads back its own tape when it runs in
+    /// the VM.
+    #[must_use]
+    pub fn tape_of(&self, program: &ProgramIdentifier) -> &[u8] {
+        self.writer.get(program).map_or(&[], |message| &message.0)
+    }
+}
+
 impl std::fmt::Debug for RawTape {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result { self.writer.fmt(f) }
 }
@@ -65,3 +75,31 @@ pub fn write(kind: &crate::InputTapeType, buf: &[u8]) -> std::io::Result<usize>
         },
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::RawTape;
+    use crate::common::traits::SelfIdentify;
+    use crate::common::types::ProgramIdentifier;
+
+    #[test]
+    fn writes_go_to_the_tape_of_the_current_program() {
+        let (alice, bob) = (
+            ProgramIdentifier::from([1; 32]),
+            ProgramIdentifier::from([2; 32]),
+        );
+        let mut tape = RawTape::default();
+        tape.set_self_identity(alice);
+        tape.write_all(&[1, 2]).unwrap();
+        tape.set_self_identity(bob);
+        tape.write_all(&[3]).unwrap();
+        tape.identity_stack.borrow_mut().rm_identity();
+        tape.write_all(&[4]).unwrap();
+
+        assert_eq!(tape.tape_of(&alice), &[1, 2, 4]);
+        assert_eq!(tape.tape_of(&bob), &[3]);
+        assert!(tape.tape_of(&ProgramIdentifier::from([3; 32])).is_empty());
+    }
+}
@@ -1,13 +1,17 @@
 //! This file contains code snippets used in native execution
 #![allow(clippy::module_name_repetitions)]
 
+use std::iter::repeat;
+
 use plonky2::field::goldilocks_field::GoldilocksField;
 use plonky2::field::types::Field;
-use plonky2::hash::poseidon2::Poseidon2Hash as Plonky2Poseidon2Hash;
+use plonky2::hash::hash_types::{HashOut, NUM_HASH_OUT_ELTS};
+use plonky2::hash::hashing::PlonkyPermutation;
+use plonky2::hash::poseidon2::{Poseidon2Hash as Plonky2Poseidon2Hash, Poseidon2Permutation};
 use plonky2::plonk::config::{GenericHashOut, Hasher};
 
 use crate::common::types::Poseidon2Hash;
-use crate::core::constants::RATE;
+use crate::core::constants::{DIGEST_BYTES, RATE};
 
 /// Hashes the input slice to `Poseidon2Hash` after padding.
 /// We use the well known "Bit padding scheme".
@@ -52,3 +56,45 @@ pub fn poseidon2_hash_no_pad(input: &[u8]) -> Poseidon2Hash {
             .expect("Output length does not match to DIGEST_BYTES"),
     )
 }
+
+/// Hashes the input slice, assuming its length to be a multiple of `RATE`,
+/// and fills `output` XOF style: the first `DIGEST_BYTES` are
+/// [`poseidon2_hash_no_pad`] of the input, and each further `DIGEST_BYTES`
+/// take one more permutation of the sponge.
+/// # Panics
+/// If the input length is not a multiple of `RATE`, or the output length is
+/// not a positive multiple of `DIGEST_BYTES`.
+pub fn poseidon2_xof_no_pad(input: &[u8], output: &mut [u8]) {
+    assert!(input.len() % RATE == 0);
+    assert!(!output.is_empty() && output.len() % DIGEST_BYTES == 0);
+    let mut perm = Poseidon2Permutation::new(repeat(GoldilocksField::ZERO));
+    for chunk in input.chunks(RATE) {
+        perm.set_from_iter(
+            chunk.iter().map(|x| GoldilocksField::from_canonical_u8(*x)),
+            0,
+        );
+        perm.permute();
+    }
+    for (i, digest) in output.chunks_mut(DIGEST_BYTES).enumerate() {
+        if i > 0 {
+            perm.permute();
+        }
+        digest.copy_from_slice(
+            &HashOut::from_partial(&perm.squeeze()[..NUM_HASH_OUT_ELTS]).to_bytes(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{poseidon2_hash_no_pad, poseidon2_xof_no_pad};
+
+    #[test]
+    fn xof_starts_with_hash() {
+        let input = [7_u8; 16];
+        let mut output = [0_u8; 96];
+        poseidon2_xof_no_pad(&input, &mut output);
+        assert_eq!(output[..32], poseidon2_hash_no_pad(&input).0);
+        assert_ne!(output[32..64], output[64..]);
+    }
+}
@@ -0,0 +1,111 @@
+//! A standalone verifier of the proofs written by `mozak-cli prove`.
+//!
+//! Run it like
+//! `mozak-verifier proof.mzk --program-hash 0x.. --public-inputs inputs.json`.
+//! It exits with `0` if the proof verifies, and is a proof of the given
+//! program with the given public inputs, and with `1` otherwise.
+#![deny(clippy::pedantic)]
+
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process;
+
+use anyhow::{ensure, Context, Result};
+use clap::Parser;
+use mozak_circuits::cpu::columns::ExitCode;
+use mozak_circuits::stark::mozak_stark::{MozakStark, PublicInputs};
+use mozak_circuits::stark::proof::AllProof;
+use mozak_circuits::stark::proof_envelope::read_versioned;
+use mozak_circuits::stark::security::SecurityPolicy;
+use mozak_circuits::stark::verifier::verify_proof;
+use plonky2::plonk::config::{GenericConfig, Poseidon2GoldilocksConfig};
+use serde::de::DeserializeOwned;
+use starky::config::StarkConfig;
+
+const D: usize = 2;
+type C = Poseidon2GoldilocksConfig;
+type F = <C as GenericConfig<D>>::F;
+
+/// Magic number at the start of every zstd frame.
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Cli {
+    /// Proof written by `mozak-cli prove`, possibly compressed.
+    proof: PathBuf,
+    /// Expected program hash, as the hex encoding of its bytes.
+    #[arg(long)]
+    program_hash: String,
+    /// JSON file with the expected public inputs.
+    #[arg(long)]
+    public_inputs: PathBuf,
+    /// Expected id of the library that the program links, as the hex encoding
+    /// of its bytes. The program hash already fixes the library, this check
+    /// only tells a wrong library apart from a wrong program.
+    #[arg(long)]
+    library_hash: Option<String>,
+}
+
+/// Decodes `hash`, with or without a `0x` prefix.
+fn decode_hash(hash: &str, what: &str) -> Result<Vec<u8>> {
+    hex::decode(hash.trim_start_matches("0x")).with_context(|| format!("the {what} is not hex"))
+}
+
+/// Deserializes JSON from the file at `path`, which may be compressed with
+/// zstd, like the proofs written by `mozak-cli prove --compress`.
+fn read_json<T: DeserializeOwned>(path: &Path) -> Result<T> {
+    let file = File::open(path).with_context(|| format!("cannot open {}", path.display()))?;
+    let mut reader = BufReader::new(file);
+    if reader.fill_buf()?.starts_with(&ZSTD_MAGIC) {
+        let decoder = zstd::Decoder::with_buffer(reader)?;
+        Ok(serde_json::from_reader(BufReader::new(decoder))?)
+    } else {
+        Ok(serde_json::from_reader(reader)?)
+    }
+}
+
+/// Verifies the proof described by `cli`, and returns the exit code it claims.
+fn verify(cli: &Cli) -> Result<ExitCode> {
+    let stark = MozakStark::<F, D>::default();
+    let config = StarkConfig::standard_fast_config();
+    let (_version, all_proof): (_, AllProof<F, C, D>) = read_versioned(read_json(&cli.proof)?)?;
+
+    let program_hash = decode_hash(&cli.program_hash, "program hash")?;
+    ensure!(
+        all_proof.program_id.inner().as_slice() == program_hash.as_slice(),
+        "the proof is of program 0x{}",
+        hex::encode(all_proof.program_id.inner())
+    );
+    if let Some(library_hash) = &cli.library_hash {
+        let library_id = all_proof.library_id();
+        ensure!(
+            library_id.inner().as_slice() == decode_hash(library_hash, "library hash")?.as_slice(),
+            "the proof links library 0x{}",
+            hex::encode(library_id.inner())
+        );
+    }
+    let public_inputs: PublicInputs<F> = read_json(&cli.public_inputs)?;
+    ensure!(
+        all_proof.public_inputs == public_inputs,
+        "the proof has public inputs {:?}",
+        all_proof.public_inputs
+    );
+
+    SecurityPolicy::default().check_proof(&config, &all_proof)?;
+    let exit_code = all_proof.public_value(&stark)?;
+    verify_proof(&stark, all_proof, &config)?;
+    Ok(exit_code)
+}
+
+fn main() {
+    let cli = Cli::parse();
+    match verify(&cli) {
+        Ok(ExitCode(exit_code)) => println!("verified, exit code: {exit_code}"),
+        Err(err) => {
+            eprintln!("not verified: {err:#}");
+            process::exit(1);
+        }
+    }
+}